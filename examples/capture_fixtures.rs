@@ -0,0 +1,50 @@
+//! Captures recent transactions of a program as JSON test fixtures.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --example capture_fixtures -- --program <PROGRAM_ID> [--count 50] [--out fixtures]
+//! ```
+//!
+//! The RPC endpoint is read from `RPC_URL` (default: mainnet-beta). Load the
+//! result in tests with `solana_indexer_sdk::testing::fixtures::load_fixtures`.
+
+use solana_indexer_sdk::testing::fixtures::capture_fixtures;
+use solana_indexer_sdk::Fetcher;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+
+    let mut program = None;
+    let mut count = 50;
+    let mut out = "fixtures".to_string();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}"))?;
+        match arg.as_str() {
+            "--program" => program = Some(Pubkey::from_str(&value)?),
+            "--count" => count = value.parse()?,
+            "--out" => out = value,
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    let program = program.ok_or("--program is required")?;
+
+    let rpc_url = std::env::var("RPC_URL")
+        .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+    let fetcher = Fetcher::new(rpc_url, CommitmentConfig::confirmed());
+
+    let written = capture_fixtures(&fetcher, &program, count, &out).await?;
+    println!(
+        "Captured {} fixtures for {program} into {out}",
+        written.len()
+    );
+    Ok(())
+}
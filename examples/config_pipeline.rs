@@ -0,0 +1,210 @@
+//! Config-Driven Sink Pipeline Example
+//!
+//! This example runs a reference pipeline described entirely by a TOML file:
+//! the indexer settings come from a configuration profile, the built-in
+//! decoders to register are listed by name, and every `[[sinks]]` entry
+//! forwards the chosen event types to a message bus. New pipelines are new
+//! files in `pipelines/`, not new code.
+//!
+//! Supported sinks: `redis` (Redis Streams). Supported decoders: `system`.
+//!
+//! ```bash
+//! cargo run --example config_pipeline -- --pipeline pipelines/system_to_redis.toml --profile mainnet
+//! ```
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use solana_indexer_sdk::config::profiles::profile_from_args;
+use solana_indexer_sdk::decoders::system::{CreateAccountEvent, SystemTransferEvent};
+use solana_indexer_sdk::sinks::{redis::RedisStreamSink, EventSink, SinkRecord};
+use solana_indexer_sdk::{
+    ConfigProfiles, EventHandler, SolanaIndexer, SolanaIndexerError, TxMetadata,
+};
+use sqlx::PgPool;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const DEFAULT_PIPELINE: &str = "pipelines/system_to_redis.toml";
+
+// ================================================================================================
+// Pipeline File
+// ================================================================================================
+
+/// A pipeline file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pipeline {
+    /// Profile file, relative to the pipeline file
+    profiles: PathBuf,
+    /// Built-in decoders to register
+    decoders: Vec<String>,
+    /// Where events are published
+    #[serde(default)]
+    sinks: Vec<SinkSpec>,
+}
+
+/// One `[[sinks]]` entry.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+enum SinkSpec {
+    Redis {
+        url: String,
+        stream: String,
+        maxlen: Option<usize>,
+        events: Vec<String>,
+    },
+}
+
+impl SinkSpec {
+    async fn connect(&self) -> Result<Arc<dyn EventSink>, SolanaIndexerError> {
+        match self {
+            Self::Redis {
+                url,
+                stream,
+                maxlen,
+                ..
+            } => {
+                let mut sink = RedisStreamSink::new(url, stream.as_str()).await?;
+                if let Some(maxlen) = maxlen {
+                    sink = sink.with_maxlen(*maxlen);
+                }
+                Ok(Arc::new(sink))
+            }
+        }
+    }
+
+    fn events(&self) -> &[String] {
+        match self {
+            Self::Redis { events, .. } => events,
+        }
+    }
+}
+
+// ================================================================================================
+// Forwarding Handler
+// ================================================================================================
+
+/// Publishes an event to every sink subscribed to it.
+struct Forward<E> {
+    sinks: Vec<Arc<dyn EventSink>>,
+    payload: fn(&E) -> serde_json::Value,
+}
+
+#[async_trait]
+impl<E: Send + Sync + 'static> EventHandler<E> for Forward<E> {
+    async fn handle(
+        &self,
+        event: E,
+        context: &TxMetadata,
+        _db: &PgPool,
+    ) -> Result<(), SolanaIndexerError> {
+        let record = SinkRecord {
+            event: std::any::type_name::<E>()
+                .rsplit("::")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            signature: context.signature.clone(),
+            slot: context.slot,
+            block_time: context.block_time,
+            payload: (self.payload)(&event),
+            dedup_key: None,
+        };
+        for sink in &self.sinks {
+            sink.publish(&record).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Connects the sinks subscribed to `event`.
+async fn sinks_for(
+    pipeline: &Pipeline,
+    event: &str,
+) -> Result<Vec<Arc<dyn EventSink>>, SolanaIndexerError> {
+    let mut sinks = Vec::new();
+    for spec in &pipeline.sinks {
+        if spec.events().iter().any(|name| name == event) {
+            sinks.push(spec.connect().await?);
+        }
+    }
+    Ok(sinks)
+}
+
+// ================================================================================================
+// Main
+// ================================================================================================
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    let args: Vec<String> = std::env::args().collect();
+    let pipeline_path = args
+        .iter()
+        .position(|arg| arg == "--pipeline")
+        .and_then(|i| args.get(i + 1))
+        .map_or_else(|| PathBuf::from(DEFAULT_PIPELINE), PathBuf::from);
+    let profile = profile_from_args(args).unwrap_or_else(|| "base".to_string());
+
+    let pipeline: Pipeline = toml::from_str(&std::fs::read_to_string(&pipeline_path)?)?;
+    let profiles_path = pipeline_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join(&pipeline.profiles);
+    let config = ConfigProfiles::load(profiles_path)?
+        .builder(&profile)?
+        .build()?;
+
+    println!(
+        "🚀 Pipeline {} (profile {profile}) starting...",
+        pipeline_path.display()
+    );
+    let mut indexer = SolanaIndexer::new(config).await?;
+
+    for decoder in &pipeline.decoders {
+        match decoder.as_str() {
+            "system" => indexer.register_system_decoder()?,
+            other => return Err(format!("Unknown decoder: {other}").into()),
+        }
+    }
+
+    let transfers = sinks_for(&pipeline, "SystemTransferEvent").await?;
+    if !transfers.is_empty() {
+        indexer.register_handler(Forward::<SystemTransferEvent> {
+            sinks: transfers,
+            payload: |event| {
+                json!({
+                    "from": event.from.to_string(),
+                    "to": event.to.to_string(),
+                    "lamports": event.lamports,
+                })
+            },
+        })?;
+    }
+    let accounts = sinks_for(&pipeline, "CreateAccountEvent").await?;
+    if !accounts.is_empty() {
+        indexer.register_handler(Forward::<CreateAccountEvent> {
+            sinks: accounts,
+            payload: |event| {
+                json!({
+                    "funder": event.funder.to_string(),
+                    "account": event.account.to_string(),
+                    "lamports": event.lamports,
+                    "space": event.space,
+                    "owner": event.owner.to_string(),
+                })
+            },
+        })?;
+    }
+
+    let token = indexer.cancellation_token();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c().await.ok();
+        println!("\n🛑 Shutting down...");
+        token.cancel();
+    });
+
+    indexer.start().await?;
+    Ok(())
+}
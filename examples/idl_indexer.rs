@@ -148,8 +148,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut indexer = SolanaIndexer::new(config).await?;
 
     // Register decoders for types generated from IDL
-    indexer.register_decoder("my_program", IdlInstructionDecoder)?;
-    indexer.register_log_decoder("my_program", IdlLogDecoder)?;
+    indexer.register_decoder(program_id.as_str(), IdlInstructionDecoder)?;
+    indexer.register_log_decoder(program_id.as_str(), IdlLogDecoder)?;
 
     // Register handlers for both events and instructions
     indexer.register_handler(IdlEventHandler)?;
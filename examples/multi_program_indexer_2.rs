@@ -2,8 +2,9 @@ use async_trait::async_trait;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_indexer_sdk::config::BackfillConfig;
 use solana_indexer_sdk::{
-    calculate_discriminator, EventDiscriminator, EventHandler, InstructionDecoder, SolanaIndexer,
-    SolanaIndexerConfigBuilder, SolanaIndexerError, Storage, TxMetadata,
+    calculate_discriminator, BandwidthSchedule, EventDiscriminator, EventHandler,
+    InstructionDecoder, SolanaIndexer, SolanaIndexerConfigBuilder, SolanaIndexerError, Storage,
+    TxMetadata,
 };
 use solana_sdk::pubkey::Pubkey;
 use solana_transaction_status::{UiInstruction, UiParsedInstruction};
@@ -360,14 +361,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let jupiter_backfill_config = BackfillConfig {
         enabled: true,
         start_slot: None, // Let the trigger decide
-        end_slot: None,   // Let the trigger decide
+        start_time: None,
+        end_slot: None, // Let the trigger decide
         batch_size: 100,
         concurrency: 10,
         enable_reorg_handling: true,
         finalization_check_interval: 100,
-        poll_interval_secs: 10,        // Check for backfill every 10s
-        max_depth: None,               // No limit on how far back to go
+        poll_interval_secs: 10,              // Check for backfill every 10s
+        max_depth: None,                     // No limit on how far back to go
         desired_lag_slots: Some(5000), // Start backfilling if we are more than 5000 slots behind
+        bandwidth: BandwidthSchedule::new(), // No time-of-day rate limits
     };
 
     let mut system_backfill_config = jupiter_backfill_config.clone();
@@ -1,13 +1,15 @@
 //! Raydium AMM v4 Swap Indexer Example
 //!
-//! This example demonstrates how to decode partially decoded instructions to
-//! index Raydium swaps.
+//! This example demonstrates how to decode partially decoded and compiled
+//! instructions to index Raydium swaps, resolving their accounts through the
+//! instruction context.
 
 use async_trait::async_trait;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_indexer_sdk::{
     calculate_discriminator, config::BackfillConfig, EventDiscriminator, EventHandler,
-    InstructionDecoder, SolanaIndexer, SolanaIndexerConfigBuilder, SolanaIndexerError,
+    InstructionContext, InstructionDecoder, SolanaIndexer, SolanaIndexerConfigBuilder,
+    SolanaIndexerError,
 };
 use solana_transaction_status::{UiInstruction, UiParsedInstruction};
 use sqlx::PgPool;
@@ -30,32 +32,48 @@ impl EventDiscriminator for RaydiumSwapEvent {
 pub struct RaydiumSwapDecoder;
 impl InstructionDecoder<RaydiumSwapEvent> for RaydiumSwapDecoder {
     fn decode(&self, instruction: &UiInstruction) -> Option<RaydiumSwapEvent> {
-        if let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) = instruction {
-            if decoded.program_id != RAYDIUM_V4_PROGRAM_ID {
-                return None;
-            }
-
-            let data_bytes = solana_sdk::bs58::decode(&decoded.data).into_vec().ok()?;
-
-            // Raydium SwapBaseIn Instruction is index 9 (formerly 3 in older versions)
-            if data_bytes.is_empty() || data_bytes[0] != 9 {
-                return None;
-            }
-            if data_bytes.len() < 17 {
-                return None;
-            }
-
-            let amount_in = u64::from_le_bytes(data_bytes[1..9].try_into().ok()?);
-            let min_amount_out = u64::from_le_bytes(data_bytes[9..17].try_into().ok()?);
-            let user = decoded.accounts.first()?.clone();
-
-            return Some(RaydiumSwapEvent {
-                amount_in,
-                min_amount_out,
-                user,
-            });
+        self.decode_in_context(instruction, &InstructionContext::default())
+    }
+
+    fn decode_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<RaydiumSwapEvent> {
+        let data = match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => &decoded.data,
+            UiInstruction::Compiled(compiled) => &compiled.data,
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return None,
+        };
+        if context.program_id_of(instruction)?.to_string() != RAYDIUM_V4_PROGRAM_ID {
+            return None;
         }
-        None
+
+        let data_bytes = solana_sdk::bs58::decode(data).into_vec().ok()?;
+
+        // Raydium SwapBaseIn Instruction is index 9 (formerly 3 in older versions)
+        if data_bytes.is_empty() || data_bytes[0] != 9 {
+            return None;
+        }
+        if data_bytes.len() < 17 {
+            return None;
+        }
+
+        let amount_in = u64::from_le_bytes(data_bytes[1..9].try_into().ok()?);
+        let min_amount_out = u64::from_le_bytes(data_bytes[9..17].try_into().ok()?);
+        // The swapping wallet is the instruction's only signer
+        let user = context
+            .instruction_accounts(instruction)?
+            .into_iter()
+            .find(|account| account.signer)?
+            .pubkey
+            .to_string();
+
+        Some(RaydiumSwapEvent {
+            amount_in,
+            min_amount_out,
+            user,
+        })
     }
 }
 
@@ -0,0 +1,137 @@
+//! Replays already-indexed transactions through newly registered decoders.
+//!
+//! Usage:
+//!
+//! ```text
+//! cargo run --example replay_archive -- --from <SLOT> --to <SLOT>
+//! ```
+//!
+//! Transactions archived by an indexer running with
+//! `with_transaction_archive(true)` are read from the database; any others
+//! are fetched from `RPC_URL`. The database is read from `DATABASE_URL`.
+//! Register the decoders and handlers to fill in before calling `replay`,
+//! as a live indexer would.
+
+use async_trait::async_trait;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_indexer_sdk::{
+    calculate_discriminator, EventDiscriminator, EventHandler, InstructionDecoder, SolanaIndexer,
+    SolanaIndexerConfigBuilder, SolanaIndexerError, TxMetadata,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct SystemTransferEvent {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
+
+impl EventDiscriminator for SystemTransferEvent {
+    fn discriminator() -> [u8; 8] {
+        calculate_discriminator("SystemTransferEvent")
+    }
+}
+
+pub struct SystemTransferDecoder;
+
+impl InstructionDecoder<SystemTransferEvent> for SystemTransferDecoder {
+    fn decode(&self, instruction: &UiInstruction) -> Option<SystemTransferEvent> {
+        if let UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) = instruction {
+            if parsed.program == "system" && parsed.parsed.get("type")?.as_str()? == "transfer" {
+                let info = parsed.parsed.get("info")?.as_object()?;
+                return Some(SystemTransferEvent {
+                    from: info.get("source")?.as_str()?.parse().ok()?,
+                    to: info.get("destination")?.as_str()?.parse().ok()?,
+                    amount: info.get("lamports")?.as_u64()?,
+                });
+            }
+        }
+        None
+    }
+}
+
+pub struct SystemTransferHandler;
+
+#[async_trait]
+impl EventHandler<SystemTransferEvent> for SystemTransferHandler {
+    async fn initialize_schema(&self, db: &PgPool) -> Result<(), SolanaIndexerError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS replayed_transfers (
+                signature TEXT PRIMARY KEY,
+                from_wallet TEXT NOT NULL,
+                to_wallet TEXT NOT NULL,
+                amount_lamports BIGINT NOT NULL
+            )",
+        )
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    async fn handle(
+        &self,
+        event: SystemTransferEvent,
+        context: &TxMetadata,
+        db: &PgPool,
+    ) -> Result<(), SolanaIndexerError> {
+        sqlx::query(
+            "INSERT INTO replayed_transfers (signature, from_wallet, to_wallet, amount_lamports)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (signature) DO NOTHING",
+        )
+        .bind(&context.signature)
+        .bind(event.from.to_string())
+        .bind(event.to.to_string())
+        .bind(event.amount as i64)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+
+    let mut from = None;
+    let mut to = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value for {arg}"))?;
+        match arg.as_str() {
+            "--from" => from = Some(value.parse::<u64>()?),
+            "--to" => to = Some(value.parse::<u64>()?),
+            other => return Err(format!("Unknown argument: {other}").into()),
+        }
+    }
+    let from = from.ok_or("--from is required")?;
+    let to = to.unwrap_or(u64::MAX);
+
+    let rpc_url = std::env::var("RPC_URL")
+        .unwrap_or_else(|_| "https://api.mainnet-beta.solana.com".to_string());
+    let config = SolanaIndexerConfigBuilder::new()
+        .with_rpc(rpc_url)
+        .with_database(std::env::var("DATABASE_URL")?)
+        .program_id("11111111111111111111111111111111")
+        .with_transaction_archive(true)
+        .build()?;
+
+    let mut indexer = SolanaIndexer::new(config).await?;
+    let pool = PgPool::connect(&std::env::var("DATABASE_URL")?).await?;
+    SystemTransferHandler.initialize_schema(&pool).await?;
+    indexer.register_decoder("system", SystemTransferDecoder)?;
+    indexer.register_handler(SystemTransferHandler)?;
+
+    let summary = indexer.replay(from..=to).await?;
+    println!(
+        "Replayed {} transactions ({} archived, {} fetched, {} failed)",
+        summary.replayed, summary.archived, summary.fetched, summary.failed
+    );
+    Ok(())
+}
@@ -2,6 +2,9 @@
 //!
 //! This example provides a complete, runnable indexer for tracking
 //! System Program transfers, demonstrating the core features of the SDK.
+//!
+//! Without `DATABASE_URL`, a throwaway local `PostgreSQL` server is started
+//! from the local installation for the run (`local-postgres` feature).
 
 use async_trait::async_trait;
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -88,25 +91,26 @@ async fn main() -> Result<(), SolanaIndexerError> {
 
     // 2. Configure the indexer.
     let rpc_url = "https://api.devnet.solana.com".to_string();
-    let database_url = std::env::var("DATABASE_URL")?;
     let program_id = "11111111111111111111111111111111";
 
-    let config = SolanaIndexerConfigBuilder::new()
+    let mut builder = SolanaIndexerConfigBuilder::new()
         .with_rpc(rpc_url)
-        .with_database(database_url.clone())
         .program_id(program_id)
         .with_backfill(BackfillConfig {
             enabled: true,
             ..Default::default()
-        })
-        .build()?;
+        });
+    if let Ok(database_url) = std::env::var("DATABASE_URL") {
+        builder = builder.with_database(database_url);
+    }
+    let config = builder.build()?;
 
     let mut indexer = SolanaIndexer::new(config).await?;
 
     // 3. Register components.
     let handler = SystemTransferHandler;
     handler
-        .initialize_schema(&sqlx::PgPool::connect(&database_url).await?)
+        .initialize_schema(indexer.storage().require_pool()?)
         .await?;
     indexer.register_decoder("system", SystemTransferDecoder)?;
     indexer.register_handler(handler)?;
@@ -0,0 +1,274 @@
+//! Derive macros for `solana-indexer-sdk`.
+//!
+//! `#[derive(IndexerEvent)]` implements `EventDiscriminator` and
+//! `IndexerEvent` for an event type, replacing the hand-written
+//! discriminator, decoding and table description of each event. Enable it
+//! through the SDK's `derive` feature rather than depending on this crate
+//! directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use sha2::{Digest, Sha256};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, Fields, GenericArgument, Lit, LitStr,
+    PathArguments, Type,
+};
+
+/// Implements `EventDiscriminator` and `IndexerEvent`.
+///
+/// The type must also derive `BorshDeserialize`. For structs with named
+/// fields, `EventDiscriminator::borsh_layout` lists every field and its
+/// type, for checks against an IDL. Container attributes, all optional:
+///
+/// - `#[indexer(name = "...")]`: name the discriminator is computed from
+///   (default: the type name).
+/// - `#[indexer(discriminator = "anchor" | "name")]`: `anchor` (default) is
+///   Anchor's event discriminator, the first 8 bytes of
+///   `sha256("event:<name>")`; `name` hashes the bare name.
+/// - `#[indexer(table = "...")]`: describes a table with one column per
+///   field, returned by `IndexerEvent::table_schema`.
+///
+/// Field attributes, used with `table`:
+///
+/// - `#[indexer(sql_type = "...")]`: overrides the column type inferred from
+///   the field type.
+/// - `#[indexer(skip)]`: leaves the field out of the table.
+#[proc_macro_derive(IndexerEvent, attributes(indexer))]
+pub fn derive_indexer_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How the discriminator is derived from the event name.
+enum Discriminator {
+    Anchor,
+    Name,
+}
+
+struct ContainerOptions {
+    name: String,
+    discriminator: Discriminator,
+    table: Option<String>,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let options = container_options(input)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let preimage = match options.discriminator {
+        Discriminator::Anchor => format!("event:{}", options.name),
+        Discriminator::Name => options.name.clone(),
+    };
+    let hash = Sha256::digest(preimage.as_bytes());
+    let bytes = &hash[..8];
+    let name = &options.name;
+
+    let borsh_layout = borsh_fields(input);
+
+    let table_schema = match &options.table {
+        Some(table) => {
+            let columns = table_columns(input)?;
+            quote! {
+                ::core::option::Option::Some(
+                    ::solana_indexer_sdk::TableSchema::new(#table)
+                        #(#columns)*
+                )
+            }
+        }
+        None => quote!(::core::option::Option::None),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::solana_indexer_sdk::EventDiscriminator for #ident #ty_generics #where_clause {
+            fn discriminator() -> [u8; 8] {
+                [#(#bytes),*]
+            }
+
+            fn borsh_layout() -> ::std::vec::Vec<::solana_indexer_sdk::FieldLayout> {
+                ::std::vec![#(#borsh_layout),*]
+            }
+        }
+
+        impl #impl_generics ::solana_indexer_sdk::IndexerEvent for #ident #ty_generics #where_clause {
+            fn event_name() -> &'static str {
+                #name
+            }
+
+            fn table_schema() -> ::core::option::Option<::solana_indexer_sdk::TableSchema> {
+                #table_schema
+            }
+        }
+    })
+}
+
+fn container_options(input: &DeriveInput) -> syn::Result<ContainerOptions> {
+    let mut options = ContainerOptions {
+        name: input.ident.to_string(),
+        discriminator: Discriminator::Anchor,
+        table: None,
+    };
+    for attr in indexer_attrs(&input.attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                options.name = meta.value()?.parse::<LitStr>()?.value();
+            } else if meta.path.is_ident("discriminator") {
+                let value: LitStr = meta.value()?.parse()?;
+                options.discriminator = match value.value().as_str() {
+                    "anchor" => Discriminator::Anchor,
+                    "name" => Discriminator::Name,
+                    _ => {
+                        return Err(syn::Error::new(
+                            value.span(),
+                            "expected \"anchor\" or \"name\"",
+                        ))
+                    }
+                };
+            } else if meta.path.is_ident("table") {
+                options.table = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else {
+                return Err(meta.error("unknown indexer attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(options)
+}
+
+/// Returns a `.with_column(..)` call for each field that is not skipped.
+fn table_columns(input: &DeriveInput) -> syn::Result<Vec<TokenStream2>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`table` is only supported on structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`table` needs a struct with named fields",
+        ));
+    };
+
+    let mut columns = Vec::new();
+    for field in &fields.named {
+        let mut skip = false;
+        let mut sql_type = None;
+        for attr in indexer_attrs(&field.attrs) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("sql_type") {
+                    sql_type = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else {
+                    return Err(meta.error("unknown indexer field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        if skip {
+            continue;
+        }
+
+        let name = field
+            .ident
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+        let sql_type = sql_type.unwrap_or_else(|| sql_type_of(&field.ty).to_string());
+        let description = doc_comment(&field.attrs);
+        columns.push(quote!(.with_column(#name, #sql_type, #description)));
+    }
+    Ok(columns)
+}
+
+/// Returns a `FieldLayout` for each field of a struct with named fields, in
+/// encoding order, and none for other types.
+fn borsh_fields(input: &DeriveInput) -> Vec<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Vec::new();
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Vec::new();
+    };
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let name = field
+                .ident
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            let name = name.strip_prefix("r#").unwrap_or(&name).to_string();
+            let ty = field.ty.to_token_stream().to_string();
+            quote!(::solana_indexer_sdk::FieldLayout::new(#name, #ty))
+        })
+        .collect()
+}
+
+fn indexer_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("indexer"))
+}
+
+/// Joins the `///` lines of a field into one description.
+fn doc_comment(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(doc) => Some(doc.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Picks the column type for a field type; anything unrecognized is stored
+/// as `JSONB`.
+fn sql_type_of(ty: &Type) -> &'static str {
+    match ty {
+        Type::Array(array) if is_u8(&array.elem) => "BYTEA",
+        Type::Path(path) => {
+            let Some(segment) = path.path.segments.last() else {
+                return "JSONB";
+            };
+            match segment.ident.to_string().as_str() {
+                "bool" => "BOOLEAN",
+                "u8" | "i8" | "i16" => "SMALLINT",
+                "u16" | "i32" => "INTEGER",
+                "u32" | "i64" | "u64" => "BIGINT",
+                "u128" | "i128" => "NUMERIC(39, 0)",
+                "f32" => "REAL",
+                "f64" => "DOUBLE PRECISION",
+                "String" | "Pubkey" => "TEXT",
+                "Option" => generic_argument(&segment.arguments).map_or("JSONB", sql_type_of),
+                "Vec" if generic_argument(&segment.arguments).is_some_and(is_u8) => "BYTEA",
+                _ => "JSONB",
+            }
+        }
+        _ => "JSONB",
+    }
+}
+
+fn generic_argument(arguments: &PathArguments) -> Option<&Type> {
+    let PathArguments::AngleBracketed(arguments) = arguments else {
+        return None;
+    };
+    arguments.args.iter().find_map(|argument| match argument {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
+
+fn is_u8(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("u8"))
+}
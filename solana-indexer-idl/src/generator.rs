@@ -114,7 +114,7 @@ fn generate_sdk_types(idl: &Idl) -> Result<String> {
         #![allow(unused_imports)]
         use borsh::{BorshDeserialize, BorshSerialize};
         use solana_sdk::pubkey::Pubkey;
-        use solana_indexer_sdk::EventDiscriminator;
+        use solana_indexer_sdk::{field_path, AccountFields, EventDiscriminator};
     };
 
     // Generate types
@@ -131,11 +131,32 @@ fn generate_sdk_types(idl: &Idl) -> Result<String> {
             }
         });
 
+        // Nested defined types list their own fields; the rest are compared
+        // by their `Debug` output
+        let field_entries = ty.ty.fields.iter().map(|field| {
+            let field_name = &field.name;
+            let field_name_ident = Ident::new(field_name, Span::call_site());
+            match &field.ty {
+                IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Defined(_)) => quote! {
+                    self.#field_name_ident.fields(&field_path(prefix, #field_name), out);
+                },
+                _ => quote! {
+                    out.push((field_path(prefix, #field_name), format!("{:?}", self.#field_name_ident)));
+                },
+            }
+        });
+
         let type_struct = quote! {
             #[derive(BorshSerialize, BorshDeserialize, Clone, Debug)]
             pub struct #type_name_ident {
                 #(#fields)*
             }
+
+            impl AccountFields for #type_name_ident {
+                fn fields(&self, prefix: &str, out: &mut Vec<(String, String)>) {
+                    #(#field_entries)*
+                }
+            }
         };
 
         code.extend(type_struct);
@@ -155,6 +176,14 @@ fn generate_sdk_types(idl: &Idl) -> Result<String> {
             }
         });
 
+        let layout = event.fields.iter().map(|field| {
+            let field_name = &field.name;
+            let field_ty = idl_type_to_rust_type(&field.ty, GenerationMode::Sdk).to_string();
+            quote! {
+                solana_indexer_sdk::FieldLayout::new(#field_name, #field_ty)
+            }
+        });
+
         // Calculate discriminator for the event
         let discriminator_preimage = format!("event:{}", event_name);
         let discriminator_bytes = calculate_discriminator_bytes(&discriminator_preimage);
@@ -178,6 +207,10 @@ fn generate_sdk_types(idl: &Idl) -> Result<String> {
                 fn discriminator() -> [u8; 8] {
                     Self::discriminator()
                 }
+
+                fn borsh_layout() -> Vec<solana_indexer_sdk::FieldLayout> {
+                    vec![#(#layout),*]
+                }
             }
         };
 
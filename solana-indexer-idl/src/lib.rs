@@ -1,5 +1,6 @@
 pub mod generator;
 pub mod model;
+pub mod schema;
 
 // Re-export for convenience
 pub use generator::{generate_types_with_mode, GenerationMode};
@@ -75,3 +76,96 @@ pub fn generate_sdk_types(idl_path: &Path, output_path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Export Avro and protobuf schemas for the events in an IDL file.
+///
+/// Writes one `<Event>.avsc` file per event and a single `<program>.proto`
+/// file into `output_dir`, which must already exist. The IDL program name is
+/// used as the Avro namespace and protobuf package.
+///
+/// # Arguments
+///
+/// * `idl_path` - Path to the IDL JSON file
+/// * `output_dir` - Directory where the schema files will be written
+///
+/// # Errors
+///
+/// Returns an error if the IDL file cannot be read or parsed, if a type cannot
+/// be represented in the target format, or if a schema file cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use solana_indexer_idl::export_event_schemas;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// export_event_schemas(&PathBuf::from("idl/my_program.json"), &PathBuf::from("schemas"))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_event_schemas(idl_path: &Path, output_dir: &Path) -> Result<()> {
+    let idl_content = std::fs::read_to_string(idl_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read IDL file at {:?}: {}", idl_path, e))?;
+    let idl: model::Idl = serde_json::from_str(&idl_content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse IDL JSON: {}", e))?;
+
+    for schema in schema::avro_schemas(&idl, &idl.name)? {
+        let name = schema["name"].as_str().unwrap_or("event");
+        let path = output_dir.join(format!("{name}.avsc"));
+        std::fs::write(&path, serde_json::to_string_pretty(&schema)?)
+            .map_err(|e| anyhow::anyhow!("Failed to write schema to {:?}: {}", path, e))?;
+    }
+
+    let path = output_dir.join(format!("{}.proto", idl.name));
+    std::fs::write(&path, schema::proto_schema(&idl, &idl.name)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write schema to {:?}: {}", path, e))?;
+
+    Ok(())
+}
+
+/// Register the schemas of the events in an IDL file with a
+/// Confluent-compatible schema registry.
+///
+/// Each event is registered under the subject `<program>.<Event>`, with the
+/// IDL program name as namespace (see `schema::SchemaRegistry::register_events`).
+///
+/// # Arguments
+///
+/// * `idl_path` - Path to the IDL JSON file
+/// * `registry_url` - Base URL of the schema registry
+/// * `schema_type` - Whether Avro or protobuf schemas are registered
+///
+/// # Errors
+///
+/// Returns an error if the IDL file cannot be read or parsed, or if a schema
+/// cannot be generated or is rejected by the registry.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use solana_indexer_idl::register_event_schemas;
+/// use solana_indexer_idl::schema::SchemaType;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// register_event_schemas(
+///     &PathBuf::from("idl/my_program.json"),
+///     "http://localhost:8081",
+///     SchemaType::Avro,
+/// )?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn register_event_schemas(
+    idl_path: &Path,
+    registry_url: &str,
+    schema_type: schema::SchemaType,
+) -> Result<Vec<(String, u32)>> {
+    let idl_content = std::fs::read_to_string(idl_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read IDL file at {:?}: {}", idl_path, e))?;
+    let idl: model::Idl = serde_json::from_str(&idl_content)
+        .map_err(|e| anyhow::anyhow!("Failed to parse IDL JSON: {}", e))?;
+
+    schema::SchemaRegistry::new(registry_url).register_events(&idl, &idl.name, schema_type)
+}
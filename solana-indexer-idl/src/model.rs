@@ -109,7 +109,7 @@ pub struct IdlSeedArg {
     pub path: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdlField {
     pub name: String,
@@ -164,6 +164,42 @@ pub struct IdlTypeDefinitionInner {
     pub kind: String,
     #[serde(default)]
     pub fields: Vec<IdlField>,
+    #[serde(default)]
+    pub variants: Vec<IdlEnumVariant>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdlEnumVariant {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Option<IdlEnumFields>,
+}
+
+impl IdlEnumVariant {
+    /// Returns the variant's fields, naming tuple fields `field_0`, `field_1`, ...
+    pub fn named_fields(&self) -> Vec<IdlField> {
+        match &self.fields {
+            None => Vec::new(),
+            Some(IdlEnumFields::Named(fields)) => fields.clone(),
+            Some(IdlEnumFields::Tuple(types)) => types
+                .iter()
+                .enumerate()
+                .map(|(index, ty)| IdlField {
+                    name: format!("field_{index}"),
+                    ty: ty.clone(),
+                    index: false,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum IdlEnumFields {
+    Named(Vec<IdlField>),
+    Tuple(Vec<IdlTypeDefinition>),
 }
 
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,452 @@
+//! Avro and protobuf schema export for IDL events.
+//!
+//! Downstream consumers of indexed events (e.g. message-queue sinks) need a
+//! wire schema to get schema evolution guarantees. This module derives one
+//! from the event definitions in an IDL:
+//!
+//! - `avro_schemas` returns one Avro record schema per event, with custom
+//!   types from the IDL inlined as named records.
+//! - `proto_schema` returns a proto3 file with one message per event and
+//!   custom type.
+//! - `SchemaRegistry` registers either with a Confluent-compatible schema
+//!   registry, one subject per event.
+//!
+//! Enums whose variants carry no data map to Avro and protobuf enums. Enums
+//! with data map to a union of one record per variant in Avro, and to a
+//! message holding a `oneof` of one nested message per variant in protobuf.
+//!
+//! Public keys are encoded as base58 strings. Integers wider than the target
+//! format supports (`u64`/`u128`/`i128` in Avro, `u128`/`i128` in protobuf)
+//! are encoded as decimal strings so no value is truncated.
+
+use crate::model::*;
+use anyhow::{anyhow, Context as _, Result};
+use heck::{ToShoutySnakeCase, ToSnakeCase};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+/// Generates one Avro record schema per event in the IDL.
+///
+/// # Arguments
+///
+/// * `idl` - The parsed IDL
+/// * `namespace` - Avro namespace for the generated records (e.g. `com.example.my_program`)
+///
+/// # Errors
+///
+/// Returns an error if an event references a type that is not defined in the IDL.
+pub fn avro_schemas(idl: &Idl, namespace: &str) -> Result<Vec<Value>> {
+    idl.events
+        .iter()
+        .map(|event| {
+            // Named types may only be defined once per schema document.
+            let mut defined = HashSet::new();
+            defined.insert(event.name.clone());
+            let fields = avro_fields(idl, &event.fields, &mut defined)?;
+            Ok(json!({
+                "type": "record",
+                "name": event.name,
+                "namespace": namespace,
+                "fields": fields,
+            }))
+        })
+        .collect()
+}
+
+fn avro_fields(
+    idl: &Idl,
+    fields: &[IdlField],
+    defined: &mut HashSet<String>,
+) -> Result<Vec<Value>> {
+    fields
+        .iter()
+        .map(|field| {
+            let ty = avro_type(idl, &field.ty, defined)?;
+            let mut value = json!({ "name": field.name, "type": ty });
+            if matches!(
+                field.ty,
+                IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Option(_))
+            ) {
+                value["default"] = Value::Null;
+            }
+            Ok(value)
+        })
+        .collect()
+}
+
+fn avro_type(idl: &Idl, ty: &IdlTypeDefinition, defined: &mut HashSet<String>) -> Result<Value> {
+    Ok(match ty {
+        IdlTypeDefinition::Simple(s) => match s.as_str() {
+            "bool" => json!("boolean"),
+            "u8" | "i8" | "u16" | "i16" | "i32" => json!("int"),
+            "u32" | "i64" => json!("long"),
+            "u64" | "u128" | "i128" | "string" | "publicKey" | "pubkey" => json!("string"),
+            "f32" => json!("float"),
+            "f64" => json!("double"),
+            "bytes" => json!("bytes"),
+            other => avro_defined(idl, other, defined)?,
+        },
+        IdlTypeDefinition::Complex(c) => match c {
+            IdlTypeDefinitionComplex::Defined(name) => avro_defined(idl, name, defined)?,
+            IdlTypeDefinitionComplex::Option(inner) => {
+                // Unions cannot nest, so an optional enum joins its union
+                match avro_type(idl, inner, defined)? {
+                    Value::Array(mut variants) => {
+                        variants.insert(0, json!("null"));
+                        Value::Array(variants)
+                    }
+                    inner => json!(["null", inner]),
+                }
+            }
+            IdlTypeDefinitionComplex::Vec(inner) => {
+                if is_u8(inner) {
+                    json!("bytes")
+                } else {
+                    json!({ "type": "array", "items": avro_type(idl, inner, defined)? })
+                }
+            }
+            IdlTypeDefinitionComplex::Array(parts) => {
+                let inner = array_element(parts)?;
+                if is_u8(&inner) {
+                    json!("bytes")
+                } else {
+                    json!({ "type": "array", "items": avro_type(idl, &inner, defined)? })
+                }
+            }
+        },
+    })
+}
+
+fn avro_defined(idl: &Idl, name: &str, defined: &mut HashSet<String>) -> Result<Value> {
+    let ty = find_type(idl, name)?;
+    if is_enum(ty) {
+        return avro_enum(idl, ty, defined);
+    }
+    if !defined.insert(name.to_string()) {
+        return Ok(json!(name));
+    }
+    let fields = avro_fields(idl, &ty.ty.fields, defined)?;
+    Ok(json!({ "type": "record", "name": name, "fields": fields }))
+}
+
+/// Maps an enum to an Avro enum, or to a union of one record per variant,
+/// named `<Enum><Variant>`, if any variant carries data.
+fn avro_enum(idl: &Idl, ty: &IdlType, defined: &mut HashSet<String>) -> Result<Value> {
+    let name = &ty.name;
+    if is_unit_enum(ty) {
+        if !defined.insert(name.clone()) {
+            return Ok(json!(name));
+        }
+        let symbols: Vec<&str> = ty.ty.variants.iter().map(|v| v.name.as_str()).collect();
+        return Ok(json!({ "type": "enum", "name": name, "symbols": symbols }));
+    }
+
+    ty.ty
+        .variants
+        .iter()
+        .map(|variant| {
+            let record = format!("{name}{}", variant.name);
+            if !defined.insert(record.clone()) {
+                return Ok(json!(record));
+            }
+            let fields = avro_fields(idl, &variant.named_fields(), defined)?;
+            Ok(json!({ "type": "record", "name": record, "fields": fields }))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
+/// Generates a proto3 schema with one message per event and custom type.
+///
+/// # Arguments
+///
+/// * `idl` - The parsed IDL
+/// * `package` - Protobuf package name (e.g. `my_program.events`)
+///
+/// # Errors
+///
+/// Returns an error if a field type cannot be represented in protobuf
+/// (nested vectors or optional vectors) or references an undefined type.
+pub fn proto_schema(idl: &Idl, package: &str) -> Result<String> {
+    let mut out = format!("syntax = \"proto3\";\n\npackage {package};\n");
+
+    for ty in &idl.types {
+        if is_enum(ty) {
+            out.push_str(&proto_enum(idl, ty)?);
+        } else {
+            out.push_str(&proto_message(idl, &ty.name, &ty.ty.fields, "")?);
+        }
+    }
+    for event in &idl.events {
+        out.push_str(&proto_message(idl, &event.name, &event.fields, "")?);
+    }
+
+    Ok(out)
+}
+
+fn proto_message(idl: &Idl, name: &str, fields: &[IdlField], indent: &str) -> Result<String> {
+    let mut out = format!("\n{indent}message {name} {{\n");
+    for (index, field) in fields.iter().enumerate() {
+        let ty = check_defined(idl, &field.ty)
+            .and_then(|()| proto_field_type(&field.ty))
+            .map_err(|e| anyhow!("{name}.{}: {e}", field.name))?;
+        let _ = writeln!(out, "{indent}  {ty} {} = {};", field.name, index + 1);
+    }
+    let _ = writeln!(out, "{indent}}}");
+    Ok(out)
+}
+
+/// Maps an enum to a proto enum, or to a message holding a `oneof` of one
+/// nested message per variant if any variant carries data.
+///
+/// Enum values are prefixed with the enum name, as proto3 scopes them to
+/// the package rather than the enum.
+fn proto_enum(idl: &Idl, ty: &IdlType) -> Result<String> {
+    let name = &ty.name;
+    if is_unit_enum(ty) {
+        let prefix = name.to_shouty_snake_case();
+        let mut out = format!("\nenum {name} {{\n");
+        for (index, variant) in ty.ty.variants.iter().enumerate() {
+            let value = variant.name.to_shouty_snake_case();
+            let _ = writeln!(out, "  {prefix}_{value} = {index};");
+        }
+        out.push_str("}\n");
+        return Ok(out);
+    }
+
+    let mut out = format!("\nmessage {name} {{");
+    for variant in &ty.ty.variants {
+        out.push_str(&proto_message(
+            idl,
+            &variant.name,
+            &variant.named_fields(),
+            "  ",
+        )?);
+    }
+    out.push_str("\n  oneof value {\n");
+    for (index, variant) in ty.ty.variants.iter().enumerate() {
+        let field = variant.name.to_snake_case();
+        let _ = writeln!(out, "    {} {field} = {};", variant.name, index + 1);
+    }
+    out.push_str("  }\n}\n");
+    Ok(out)
+}
+
+fn proto_field_type(ty: &IdlTypeDefinition) -> Result<String> {
+    match ty {
+        IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Option(inner)) => {
+            if repeated(inner)? {
+                return Err(anyhow!("optional repeated fields are not supported"));
+            }
+            Ok(format!("optional {}", proto_scalar(inner)?))
+        }
+        _ if repeated(ty)? => Ok(format!("repeated {}", proto_scalar(&element(ty)?)?)),
+        _ => proto_scalar(ty),
+    }
+}
+
+/// Returns `true` for vectors and arrays that map to a `repeated` field.
+fn repeated(ty: &IdlTypeDefinition) -> Result<bool> {
+    match ty {
+        IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Vec(inner)) => Ok(!is_u8(inner)),
+        IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Array(parts)) => {
+            Ok(!is_u8(&array_element(parts)?))
+        }
+        _ => Ok(false),
+    }
+}
+
+fn element(ty: &IdlTypeDefinition) -> Result<IdlTypeDefinition> {
+    let inner = match ty {
+        IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Vec(inner)) => (**inner).clone(),
+        IdlTypeDefinition::Complex(IdlTypeDefinitionComplex::Array(parts)) => array_element(parts)?,
+        _ => ty.clone(),
+    };
+    if repeated(&inner)? {
+        return Err(anyhow!("nested repeated fields are not supported"));
+    }
+    Ok(inner)
+}
+
+fn proto_scalar(ty: &IdlTypeDefinition) -> Result<String> {
+    Ok(match ty {
+        IdlTypeDefinition::Simple(s) => match s.as_str() {
+            "bool" => "bool".to_string(),
+            "u8" | "u16" | "u32" => "uint32".to_string(),
+            "i8" | "i16" | "i32" => "int32".to_string(),
+            "u64" => "uint64".to_string(),
+            "i64" => "int64".to_string(),
+            "f32" => "float".to_string(),
+            "f64" => "double".to_string(),
+            "u128" | "i128" | "string" | "publicKey" | "pubkey" => "string".to_string(),
+            "bytes" => "bytes".to_string(),
+            other => other.to_string(),
+        },
+        IdlTypeDefinition::Complex(c) => match c {
+            IdlTypeDefinitionComplex::Defined(name) => name.clone(),
+            IdlTypeDefinitionComplex::Vec(_) | IdlTypeDefinitionComplex::Array(_) => {
+                "bytes".to_string()
+            }
+            IdlTypeDefinitionComplex::Option(_) => {
+                return Err(anyhow!("nested optional fields are not supported"))
+            }
+        },
+    })
+}
+
+fn check_defined(idl: &Idl, ty: &IdlTypeDefinition) -> Result<()> {
+    match ty {
+        IdlTypeDefinition::Simple(s) => match s.as_str() {
+            "bool" | "u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "u64" | "i64" | "u128"
+            | "i128" | "f32" | "f64" | "bytes" | "string" | "publicKey" | "pubkey" => Ok(()),
+            other => find_type(idl, other).map(|_| ()),
+        },
+        IdlTypeDefinition::Complex(c) => match c {
+            IdlTypeDefinitionComplex::Defined(name) => find_type(idl, name).map(|_| ()),
+            IdlTypeDefinitionComplex::Option(inner) | IdlTypeDefinitionComplex::Vec(inner) => {
+                check_defined(idl, inner)
+            }
+            IdlTypeDefinitionComplex::Array(parts) => check_defined(idl, &array_element(parts)?),
+        },
+    }
+}
+
+/// Format of a schema sent to a schema registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    /// One Avro record schema per event (see `avro_schemas`)
+    Avro,
+    /// The proto3 file of all events (see `proto_schema`)
+    Protobuf,
+}
+
+/// Blocking client for a Confluent-compatible schema registry.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_idl::schema::{SchemaRegistry, SchemaType};
+///
+/// # fn example(idl: &solana_indexer_idl::model::Idl) -> anyhow::Result<()> {
+/// let registry = SchemaRegistry::new("http://localhost:8081");
+/// for (subject, id) in registry.register_events(idl, "my_program", SchemaType::Avro)? {
+///     println!("{subject}: schema {id}");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaRegistry {
+    url: String,
+    http: reqwest::blocking::Client,
+}
+
+impl SchemaRegistry {
+    /// Creates a client for the registry at `url` (e.g. `http://localhost:8081`).
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            http: reqwest::blocking::Client::new(),
+        }
+    }
+
+    /// Registers `schema` under `subject` and returns its id. Registering
+    /// a schema the subject already has returns the existing id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails, or the registry rejects the
+    /// schema (e.g. as incompatible with the subject's earlier versions).
+    pub fn register(&self, subject: &str, schema_type: SchemaType, schema: &str) -> Result<u32> {
+        let mut body = json!({ "schema": schema });
+        if schema_type == SchemaType::Protobuf {
+            body["schemaType"] = json!("PROTOBUF");
+        }
+        let response = self
+            .http
+            .post(format!("{}/subjects/{subject}/versions", self.url))
+            .header("Content-Type", "application/vnd.schemaregistry.v1+json")
+            .json(&body)
+            .send()
+            .with_context(|| format!("Failed to reach the schema registry for `{subject}`"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let reason = response.text().unwrap_or_default();
+            return Err(anyhow!(
+                "Schema registry rejected `{subject}` ({status}): {reason}"
+            ));
+        }
+        let registered: Value = response
+            .json()
+            .with_context(|| format!("Invalid schema registry response for `{subject}`"))?;
+        registered["id"]
+            .as_u64()
+            .and_then(|id| u32::try_from(id).ok())
+            .ok_or_else(|| anyhow!("Schema registry response for `{subject}` has no id"))
+    }
+
+    /// Registers the schema of every event in `idl`, under the subject
+    /// `<namespace>.<Event>` (the registry's record name strategy), and
+    /// returns each subject with its schema id.
+    ///
+    /// Avro subjects get the event's record schema; protobuf subjects all
+    /// get the proto file, which holds every event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a schema cannot be generated or registered.
+    pub fn register_events(
+        &self,
+        idl: &Idl,
+        namespace: &str,
+        schema_type: SchemaType,
+    ) -> Result<Vec<(String, u32)>> {
+        let schemas: Vec<String> = match schema_type {
+            SchemaType::Avro => avro_schemas(idl, namespace)?
+                .iter()
+                .map(Value::to_string)
+                .collect(),
+            SchemaType::Protobuf => vec![proto_schema(idl, namespace)?; idl.events.len()],
+        };
+        idl.events
+            .iter()
+            .zip(schemas)
+            .map(|(event, schema)| {
+                let subject = format!("{namespace}.{}", event.name);
+                let id = self.register(&subject, schema_type, &schema)?;
+                Ok((subject, id))
+            })
+            .collect()
+    }
+}
+
+fn is_enum(ty: &IdlType) -> bool {
+    ty.ty.kind == "enum"
+}
+
+fn is_unit_enum(ty: &IdlType) -> bool {
+    ty.ty
+        .variants
+        .iter()
+        .all(|variant| variant.fields.is_none())
+}
+
+fn find_type<'a>(idl: &'a Idl, name: &str) -> Result<&'a IdlType> {
+    idl.types
+        .iter()
+        .find(|ty| ty.name == name)
+        .ok_or_else(|| anyhow!("Type `{name}` is not defined in the IDL"))
+}
+
+fn array_element(parts: &[Value]) -> Result<IdlTypeDefinition> {
+    let element = parts
+        .first()
+        .ok_or_else(|| anyhow!("Array type is missing its element type"))?;
+    serde_json::from_value(element.clone())
+        .map_err(|e| anyhow!("Invalid array element type {element}: {e}"))
+}
+
+fn is_u8(ty: &IdlTypeDefinition) -> bool {
+    matches!(ty, IdlTypeDefinition::Simple(s) if s == "u8")
+}
@@ -24,10 +24,14 @@ fn test_sdk_generation_with_idl2() {
     assert!(normalized_code.contains("pubname:String"));
     assert!(normalized_code.contains("pubage:u8"));
     assert!(normalized_code.contains("pubcountry:Option<String>"));
+    assert!(normalized_code.contains("implAccountFieldsforUserProfile"));
+    assert!(normalized_code
+        .contains("out.push((field_path(prefix,\"age\"),format!(\"{:?}\",self.age)))"));
 
     // Test events
     assert!(normalized_code.contains("pubstructUserInitialized"));
     assert!(normalized_code.contains("implEventDiscriminatorforUserInitialized"));
+    assert!(normalized_code.contains("solana_indexer_sdk::FieldLayout::new(\"user\",\"Pubkey\")"));
 
     // Test instruction args
     assert!(normalized_code.contains("pubstructInitializeArgs"));
@@ -0,0 +1,194 @@
+use solana_indexer_idl::{
+    model::Idl,
+    schema::{avro_schemas, proto_schema, SchemaRegistry, SchemaType},
+};
+use std::fs;
+use std::path::PathBuf;
+
+fn load_idl(file_name: &str) -> Idl {
+    let idl_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(file_name);
+    let idl_content = fs::read_to_string(idl_path).unwrap();
+    serde_json::from_str(&idl_content).unwrap()
+}
+
+#[test]
+fn test_avro_schemas_from_events() {
+    let idl = load_idl("idl2.json");
+    let schemas = avro_schemas(&idl, "comprehensive_test_program").unwrap();
+
+    assert_eq!(schemas.len(), 1);
+    let schema = &schemas[0];
+    assert_eq!(schema["type"], "record");
+    assert_eq!(schema["name"], "UserInitialized");
+    assert_eq!(schema["namespace"], "comprehensive_test_program");
+    assert_eq!(schema["fields"][0]["name"], "user");
+    assert_eq!(schema["fields"][0]["type"], "string");
+    assert_eq!(schema["fields"][1]["name"], "name");
+}
+
+#[test]
+fn test_avro_defined_and_optional_types() {
+    let idl: Idl = serde_json::from_str(
+        r#"{
+            "name": "p",
+            "instructions": [],
+            "types": [{ "name": "Inner", "type": { "kind": "struct", "fields": [
+                { "name": "amount", "type": "u64" }
+            ] } }],
+            "events": [{ "name": "Ev", "fields": [
+                { "name": "first", "type": { "defined": "Inner" } },
+                { "name": "second", "type": { "option": { "defined": "Inner" } } },
+                { "name": "raw", "type": { "array": ["u8", 32] } }
+            ] }]
+        }"#,
+    )
+    .unwrap();
+
+    let schema = &avro_schemas(&idl, "ns").unwrap()[0];
+    assert_eq!(schema["fields"][0]["type"]["type"], "record");
+    assert_eq!(schema["fields"][0]["type"]["fields"][0]["type"], "string");
+    // The second use refers to the record by name.
+    assert_eq!(schema["fields"][1]["type"][1], "Inner");
+    assert!(schema["fields"][1]["default"].is_null());
+    assert_eq!(schema["fields"][2]["type"], "bytes");
+}
+
+#[test]
+fn test_proto_schema() {
+    let idl = load_idl("idl2.json");
+    let proto = proto_schema(&idl, "comprehensive_test_program").unwrap();
+
+    assert!(proto.starts_with("syntax = \"proto3\";"));
+    assert!(proto.contains("package comprehensive_test_program;"));
+    assert!(proto.contains("message UserProfile {"));
+    assert!(proto.contains("  optional string country = 3;"));
+    assert!(proto.contains("message UserInitialized {"));
+    assert!(proto.contains("  string user = 1;"));
+}
+
+#[test]
+fn test_undefined_type_is_rejected() {
+    let idl: Idl = serde_json::from_str(
+        r#"{
+            "name": "p",
+            "instructions": [],
+            "events": [{ "name": "Ev", "fields": [
+                { "name": "missing", "type": { "defined": "Nope" } }
+            ] }]
+        }"#,
+    )
+    .unwrap();
+
+    assert!(avro_schemas(&idl, "ns").is_err());
+    assert!(proto_schema(&idl, "ns").is_err());
+}
+
+const ENUM_IDL: &str = r#"{
+    "name": "p",
+    "instructions": [],
+    "types": [
+        { "name": "Side", "type": { "kind": "enum", "variants": [
+            { "name": "Bid" }, { "name": "Ask" }
+        ] } },
+        { "name": "Fill", "type": { "kind": "enum", "variants": [
+            { "name": "Partial", "fields": [{ "name": "remaining", "type": "u64" }] },
+            { "name": "Full" },
+            { "name": "Split", "fields": ["u32", "u32"] }
+        ] } }
+    ],
+    "events": [{ "name": "OrderFilled", "fields": [
+        { "name": "side", "type": { "defined": "Side" } },
+        { "name": "fill", "type": { "defined": "Fill" } },
+        { "name": "previous", "type": { "option": { "defined": "Fill" } } }
+    ] }]
+}"#;
+
+#[test]
+fn test_avro_enum_fields() {
+    let idl: Idl = serde_json::from_str(ENUM_IDL).unwrap();
+    let schema = &avro_schemas(&idl, "ns").unwrap()[0];
+
+    let side = &schema["fields"][0]["type"];
+    assert_eq!(side["type"], "enum");
+    assert_eq!(side["symbols"], serde_json::json!(["Bid", "Ask"]));
+
+    let fill = &schema["fields"][1]["type"];
+    assert_eq!(fill[0]["name"], "FillPartial");
+    assert_eq!(fill[0]["fields"][0]["name"], "remaining");
+    assert_eq!(fill[1]["name"], "FillFull");
+    assert_eq!(fill[2]["fields"][1]["name"], "field_1");
+    // An optional enum extends the union rather than nesting it.
+    assert_eq!(
+        schema["fields"][2]["type"],
+        serde_json::json!(["null", "FillPartial", "FillFull", "FillSplit"])
+    );
+}
+
+#[test]
+fn test_proto_enum_fields() {
+    let idl: Idl = serde_json::from_str(ENUM_IDL).unwrap();
+    let proto = proto_schema(&idl, "ns").unwrap();
+
+    assert!(proto.contains("enum Side {\n  SIDE_BID = 0;\n  SIDE_ASK = 1;\n}"));
+    assert!(proto.contains("  message Partial {\n    uint64 remaining = 1;\n  }"));
+    assert!(
+        proto.contains("  message Split {\n    uint32 field_0 = 1;\n    uint32 field_1 = 2;\n  }")
+    );
+    assert!(proto.contains(
+        "  oneof value {\n    Partial partial = 1;\n    Full full = 2;\n    Split split = 3;\n  }"
+    ));
+    assert!(proto.contains("  Side side = 1;"));
+    assert!(proto.contains("  optional Fill previous = 3;"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_registry_registers_event_schemas() {
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/subjects/ns.OrderFilled/versions"))
+        .and(body_partial_json(
+            serde_json::json!({ "schemaType": "PROTOBUF" }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 7 })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let url = server.uri();
+    let registered = tokio::task::spawn_blocking(move || {
+        let idl: Idl = serde_json::from_str(ENUM_IDL).unwrap();
+        SchemaRegistry::new(url).register_events(&idl, "ns", SchemaType::Protobuf)
+    })
+    .await
+    .unwrap()
+    .unwrap();
+
+    assert_eq!(registered, vec![("ns.OrderFilled".to_string(), 7)]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_registry_rejection_is_an_error() {
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(409).set_body_string("incompatible"))
+        .mount(&server)
+        .await;
+
+    let url = server.uri();
+    let error = tokio::task::spawn_blocking(move || {
+        SchemaRegistry::new(url).register("ns.Ev", SchemaType::Avro, "{}")
+    })
+    .await
+    .unwrap()
+    .unwrap_err();
+
+    assert!(error.to_string().contains("incompatible"));
+}
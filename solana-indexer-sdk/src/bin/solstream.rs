@@ -0,0 +1,234 @@
+//! `solstream` command-line tool.
+//!
+//! ```text
+//! solstream tail [--addr HOST:PORT] [--event NAME]... [--json]
+//! solstream docs --input DICTIONARY.json [--out PATH]
+//! solstream fixtures capture --program ADDRESS [--count N] [--rpc URL] [--out DIR]
+//! ```
+//!
+//! `tail` connects to the `TailSink` of a running indexer and prints its
+//! decoded events as they arrive, all of them or only those named with
+//! `--event`. With `--json` each event is printed as one JSON line, ready
+//! for `jq`.
+//!
+//! `docs` renders a data dictionary for downstream analysts. Handlers live in
+//! the application, so the application dumps `SolanaIndexer::data_dictionary`
+//! as JSON and `docs` turns it into Markdown, or HTML if `--out` ends in
+//! `.html` (default: `DATA_DICTIONARY.md`).
+//!
+//! `fixtures capture` pulls the `--count` (default: 50) most recent
+//! transactions of a program from `--rpc` (default: `$RPC_URL`, then mainnet)
+//! and writes them as sanitized JSON fixtures into `--out` (default:
+//! `tests/fixtures/<program>`), ready for `testing::fixtures::load_fixtures`.
+
+use solana_indexer_sdk::sinks::tail::{follow, DEFAULT_TAIL_ADDR};
+use solana_indexer_sdk::sinks::SinkRecord;
+use solana_indexer_sdk::testing::fixtures::capture_fixtures;
+use solana_indexer_sdk::{DataDictionary, Fetcher};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+  solstream tail [--addr HOST:PORT] [--event NAME]... [--json]
+  solstream docs --input DICTIONARY.json [--out PATH]
+  solstream fixtures capture --program ADDRESS [--count N] [--rpc URL] [--out DIR]";
+
+const DEFAULT_RPC_URL: &str = "https://api.mainnet-beta.solana.com";
+
+/// A parsed subcommand.
+enum Command {
+    Tail(TailOptions),
+    Docs(DocsOptions),
+    Capture(CaptureOptions),
+}
+
+/// Options of `solstream tail`.
+struct TailOptions {
+    addr: String,
+    events: Vec<String>,
+    json: bool,
+}
+
+fn parse_tail(mut args: impl Iterator<Item = String>) -> Result<TailOptions, String> {
+    let mut options = TailOptions {
+        addr: DEFAULT_TAIL_ADDR.to_string(),
+        events: Vec::new(),
+        json: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--addr" => options.addr = args.next().ok_or("--addr needs a value")?,
+            "--event" => options
+                .events
+                .push(args.next().ok_or("--event needs a value")?),
+            "--json" => options.json = true,
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    Ok(options)
+}
+
+/// Options of `solstream docs`.
+struct DocsOptions {
+    input: String,
+    out: String,
+}
+
+fn parse_docs(mut args: impl Iterator<Item = String>) -> Result<DocsOptions, String> {
+    let mut input = None;
+    let mut out = "DATA_DICTIONARY.md".to_string();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.next().ok_or("--input needs a value")?),
+            "--out" => out = args.next().ok_or("--out needs a value")?,
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    Ok(DocsOptions {
+        input: input.ok_or("--input is required")?,
+        out,
+    })
+}
+
+/// Options of `solstream fixtures capture`.
+struct CaptureOptions {
+    program: Pubkey,
+    count: usize,
+    rpc: String,
+    out: String,
+}
+
+fn parse_capture(mut args: impl Iterator<Item = String>) -> Result<CaptureOptions, String> {
+    let mut program = None;
+    let mut count = 50;
+    let mut rpc = std::env::var("RPC_URL").unwrap_or_else(|_| DEFAULT_RPC_URL.to_string());
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--program" => {
+                let value = args.next().ok_or("--program needs a value")?;
+                program = Some(
+                    value
+                        .parse::<Pubkey>()
+                        .map_err(|e| format!("Invalid program {value}: {e}"))?,
+                );
+            }
+            "--count" => {
+                let value = args.next().ok_or("--count needs a value")?;
+                count = value
+                    .parse()
+                    .map_err(|_| format!("Invalid count: {value}"))?;
+            }
+            "--rpc" => rpc = args.next().ok_or("--rpc needs a value")?,
+            "--out" => out = Some(args.next().ok_or("--out needs a value")?),
+            other => return Err(format!("Unknown argument: {other}")),
+        }
+    }
+    let program = program.ok_or("--program is required")?;
+    Ok(CaptureOptions {
+        program,
+        count,
+        rpc,
+        out: out.unwrap_or_else(|| format!("tests/fixtures/{program}")),
+    })
+}
+
+/// Renders a record for a terminal.
+fn pretty(record: &SinkRecord) -> String {
+    let payload = serde_json::to_string_pretty(&record.payload)
+        .unwrap_or_else(|_| record.payload.to_string());
+    format!(
+        "[slot {}] {} {}\n{}",
+        record.slot, record.event, record.signature, payload
+    )
+}
+
+async fn tail(options: TailOptions) -> ExitCode {
+    let result = follow(options.addr.as_str(), &options.events, |record| {
+        if options.json {
+            if let Ok(line) = serde_json::to_string(&record) {
+                println!("{line}");
+            }
+        } else {
+            println!("{}", pretty(&record));
+        }
+    })
+    .await;
+    match result {
+        Ok(()) => {
+            eprintln!("Indexer closed the feed");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("solstream tail: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn capture(options: &CaptureOptions) -> ExitCode {
+    let fetcher = Fetcher::new(options.rpc.as_str(), CommitmentConfig::confirmed());
+    match capture_fixtures(&fetcher, &options.program, options.count, &options.out).await {
+        Ok(written) => {
+            eprintln!("Captured {} fixture(s) into {}", written.len(), options.out);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("solstream fixtures capture: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn docs(options: &DocsOptions) -> ExitCode {
+    let dictionary = std::fs::read_to_string(&options.input)
+        .map_err(|e| format!("Failed to read {}: {e}", options.input))
+        .and_then(|json| {
+            serde_json::from_str::<DataDictionary>(&json)
+                .map_err(|e| format!("Invalid data dictionary {}: {e}", options.input))
+        })
+        .and_then(|dictionary| {
+            dictionary
+                .write_to(&options.out)
+                .map_err(|e| e.to_string())?;
+            Ok(dictionary)
+        });
+    match dictionary {
+        Ok(dictionary) => {
+            eprintln!(
+                "Wrote the data dictionary of {} event(s) to {}",
+                dictionary.events.len(),
+                options.out
+            );
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("solstream docs: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = match args.next().as_deref() {
+        Some("tail") => parse_tail(args).map(Command::Tail),
+        Some("docs") => parse_docs(args).map(Command::Docs),
+        Some("fixtures") => match args.next().as_deref() {
+            Some("capture") => parse_capture(args).map(Command::Capture),
+            _ => Err(USAGE.to_string()),
+        },
+        _ => Err(USAGE.to_string()),
+    };
+    match command {
+        Ok(Command::Tail(options)) => tail(options).await,
+        Ok(Command::Docs(options)) => docs(&options),
+        Ok(Command::Capture(options)) => capture(&options).await,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
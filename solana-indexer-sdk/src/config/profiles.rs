@@ -0,0 +1,498 @@
+//! Named configuration profiles in a single TOML file.
+//!
+//! Each top-level table is a profile. A profile inherits every setting of
+//! its parent and overrides only the keys it sets. The parent is named with
+//! `inherits`, and defaults to `base` when the file has a `base` profile:
+//!
+//! ```toml
+//! [base]
+//! database_url = "postgresql://localhost/indexer"
+//! program_ids = ["TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"]
+//! batch_size = 100
+//!
+//! [devnet]
+//! rpc_url = "https://api.devnet.solana.com"
+//!
+//! [prod]
+//! rpc_url = "https://api.mainnet-beta.solana.com"
+//! commitment = "finalized"
+//!
+//! [prod-backfill]
+//! inherits = "prod"
+//! batch_size = 1000
+//! ```
+//!
+//! Nested tables are merged key by key; arrays and values are replaced
+//! whole. The profile to run is usually picked on the command line with
+//! `--profile <name>`, read by `profile_from_args`.
+
+use super::{CommitmentLevel, SolanaIndexerConfigBuilder};
+use crate::utils::error::{Result, SolanaIndexerError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use toml::value::Table;
+use toml::Value;
+
+/// Profile the others inherit from when they do not name a parent.
+pub const BASE_PROFILE: &str = "base";
+
+/// Environment variable read by `profile_from_args` when no `--profile`
+/// argument is given.
+pub const PROFILE_ENV: &str = "SOLANA_INDEXER_PROFILE";
+
+/// Key naming the parent of a profile.
+const INHERITS_KEY: &str = "inherits";
+
+/// Settings a profile can set; every one is optional and left at the
+/// builder's default when no profile in the chain sets it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSettings {
+    /// RPC endpoint
+    pub rpc_url: Option<String>,
+    /// WebSocket endpoint; needs `rpc_url` and the `websockets` feature
+    pub ws_url: Option<String>,
+    /// Postgres connection URL
+    pub database_url: Option<String>,
+    /// Programs to index
+    pub program_ids: Option<Vec<String>>,
+    /// Addresses to track
+    pub tracked_addresses: Option<Vec<String>>,
+    /// Polling interval in seconds
+    pub poll_interval_secs: Option<u64>,
+    /// Random delay added to each poll, in milliseconds
+    pub poll_jitter_ms: Option<u64>,
+    /// Signatures fetched per poll
+    pub batch_size: Option<usize>,
+    /// Worker threads processing transactions
+    pub worker_threads: Option<usize>,
+    /// Commitment level: `processed`, `confirmed` or `finalized`
+    pub commitment: Option<CommitmentLevel>,
+    /// Signature to start indexing from
+    pub start_signature: Option<String>,
+    /// Skip failed transactions
+    pub skip_failed_transactions: Option<bool>,
+    /// Skip vote transactions
+    pub skip_vote_transactions: Option<bool>,
+    /// Postgres schema for the indexer's tables
+    pub db_schema: Option<String>,
+    /// Proxy for RPC requests
+    pub proxy: Option<String>,
+    /// Headers of RPC requests, by name
+    pub rpc_headers: Option<BTreeMap<String, String>>,
+    /// User agent of RPC requests
+    pub user_agent: Option<String>,
+    /// Capacity of the in-memory processed-signature cache
+    pub processed_cache: Option<usize>,
+    /// Decode inner instructions
+    pub inner_instructions: Option<bool>,
+    /// Archive raw transactions
+    pub transaction_archive: Option<bool>,
+    /// Store events without a handler
+    pub unhandled_event_storage: Option<bool>,
+    /// Keep the block index
+    pub block_index: Option<bool>,
+    /// Track slots over WebSocket; needs the `websockets` feature
+    pub slot_tracking: Option<bool>,
+    /// Track transaction confirmation
+    pub confirmation_tracking: Option<bool>,
+    /// Track RPC usage
+    pub usage_tracking: Option<bool>,
+}
+
+impl ProfileSettings {
+    /// Applies the settings that are set onto `builder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `ws_url` is set without
+    /// `rpc_url`, or if `ws_url` or `slot_tracking` is set without the
+    /// `websockets` feature.
+    pub fn apply(self, builder: SolanaIndexerConfigBuilder) -> Result<SolanaIndexerConfigBuilder> {
+        let mut builder = builder;
+        // Set before the source, which captures them
+        if let Some(secs) = self.poll_interval_secs {
+            builder = builder.with_poll_interval(secs);
+        }
+        if let Some(size) = self.batch_size {
+            builder = builder.with_batch_size(size);
+        }
+
+        builder = match (self.ws_url, self.rpc_url) {
+            (Some(ws_url), Some(rpc_url)) => with_ws(builder, ws_url, rpc_url)?,
+            (Some(_), None) => {
+                return Err(SolanaIndexerError::Config(
+                    "Profile sets ws_url without rpc_url".to_string(),
+                ))
+            }
+            (None, Some(rpc_url)) => builder.with_rpc(rpc_url),
+            (None, None) => builder,
+        };
+
+        if let Some(url) = self.database_url {
+            builder = builder.with_database(url);
+        }
+        if let Some(ids) = self.program_ids {
+            builder = builder.program_ids(ids);
+        }
+        if let Some(addresses) = self.tracked_addresses {
+            builder = builder.track_addresses(addresses);
+        }
+        if let Some(ms) = self.poll_jitter_ms {
+            builder = builder.with_poll_jitter(ms);
+        }
+        if let Some(threads) = self.worker_threads {
+            builder = builder.with_worker_threads(threads);
+        }
+        if let Some(level) = self.commitment {
+            builder = builder.with_commitment(level);
+        }
+        if let Some(signature) = self.start_signature {
+            builder = builder.with_start_signature(signature);
+        }
+        if let Some(skip) = self.skip_failed_transactions {
+            builder = builder.with_skip_failed_transactions(skip);
+        }
+        if let Some(skip) = self.skip_vote_transactions {
+            builder = builder.with_skip_vote_transactions(skip);
+        }
+        if let Some(schema) = self.db_schema {
+            builder = builder.with_db_schema(schema);
+        }
+        if let Some(url) = self.proxy {
+            builder = builder.with_proxy(url);
+        }
+        for (name, value) in self.rpc_headers.unwrap_or_default() {
+            builder = builder.with_rpc_header(name, value);
+        }
+        if let Some(user_agent) = self.user_agent {
+            builder = builder.with_user_agent(user_agent);
+        }
+        if let Some(capacity) = self.processed_cache {
+            builder = builder.with_processed_cache(capacity);
+        }
+        if let Some(enabled) = self.inner_instructions {
+            builder = builder.with_inner_instructions(enabled);
+        }
+        if let Some(enabled) = self.transaction_archive {
+            builder = builder.with_transaction_archive(enabled);
+        }
+        if let Some(enabled) = self.unhandled_event_storage {
+            builder = builder.with_unhandled_event_storage(enabled);
+        }
+        if let Some(enabled) = self.block_index {
+            builder = builder.with_block_index(enabled);
+        }
+        if let Some(enabled) = self.slot_tracking {
+            builder = with_slot_tracking(builder, enabled)?;
+        }
+        if let Some(enabled) = self.confirmation_tracking {
+            builder = builder.with_confirmation_tracking(enabled);
+        }
+        if let Some(enabled) = self.usage_tracking {
+            builder = builder.with_usage_tracking(enabled);
+        }
+        Ok(builder)
+    }
+}
+
+#[cfg(feature = "websockets")]
+#[allow(clippy::unnecessary_wraps)]
+fn with_ws(
+    builder: SolanaIndexerConfigBuilder,
+    ws_url: String,
+    rpc_url: String,
+) -> Result<SolanaIndexerConfigBuilder> {
+    Ok(builder.with_ws(ws_url, rpc_url))
+}
+
+#[cfg(not(feature = "websockets"))]
+fn with_ws(
+    _builder: SolanaIndexerConfigBuilder,
+    _ws_url: String,
+    _rpc_url: String,
+) -> Result<SolanaIndexerConfigBuilder> {
+    Err(SolanaIndexerError::Config(
+        "Profile sets ws_url but the websockets feature is disabled".to_string(),
+    ))
+}
+
+#[cfg(feature = "websockets")]
+#[allow(clippy::unnecessary_wraps)]
+fn with_slot_tracking(
+    builder: SolanaIndexerConfigBuilder,
+    enabled: bool,
+) -> Result<SolanaIndexerConfigBuilder> {
+    Ok(builder.with_slot_tracking(enabled))
+}
+
+#[cfg(not(feature = "websockets"))]
+fn with_slot_tracking(
+    builder: SolanaIndexerConfigBuilder,
+    enabled: bool,
+) -> Result<SolanaIndexerConfigBuilder> {
+    if enabled {
+        return Err(SolanaIndexerError::Config(
+            "Profile enables slot_tracking but the websockets feature is disabled".to_string(),
+        ));
+    }
+    Ok(builder)
+}
+
+/// The profiles of one configuration file.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::config::profiles::{profile_from_args, ConfigProfiles, BASE_PROFILE};
+/// # fn example() -> solana_indexer_sdk::utils::error::Result<()> {
+/// let profile = profile_from_args(std::env::args()).unwrap_or_else(|| BASE_PROFILE.to_string());
+/// let config = ConfigProfiles::load("indexer.toml")?.builder(&profile)?.build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProfiles {
+    profiles: Table,
+}
+
+impl ConfigProfiles {
+    /// Reads the profiles in the TOML file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the file cannot be read
+    /// or is not a table of profiles.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            SolanaIndexerError::Config(format!(
+                "Failed to read config file {}: {e}",
+                path.display()
+            ))
+        })?;
+        contents.parse()
+    }
+
+    /// Returns the profile names, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Resolves `profile` and its parents into one set of settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if a profile in the chain
+    /// does not exist, the chain loops, or a setting is unknown or has the
+    /// wrong type.
+    pub fn settings(&self, profile: &str) -> Result<ProfileSettings> {
+        let merged = self.resolve(profile, &mut Vec::new())?;
+        Value::Table(merged).try_into().map_err(|e| {
+            SolanaIndexerError::Config(format!("Invalid settings in profile '{profile}': {e}"))
+        })
+    }
+
+    /// Returns a builder with the settings of `profile` applied. Anything
+    /// the profiles leave out, such as handlers' registry settings, can
+    /// still be set on the builder before `build`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the profile cannot be
+    /// resolved or applied.
+    pub fn builder(&self, profile: &str) -> Result<SolanaIndexerConfigBuilder> {
+        self.settings(profile)?
+            .apply(SolanaIndexerConfigBuilder::new())
+    }
+
+    /// Merges `profile` over its parent chain; `chain` holds the profiles
+    /// being resolved, to detect loops.
+    fn resolve(&self, profile: &str, chain: &mut Vec<String>) -> Result<Table> {
+        if chain.iter().any(|name| name == profile) {
+            chain.push(profile.to_string());
+            return Err(SolanaIndexerError::Config(format!(
+                "Profile inheritance loops: {}",
+                chain.join(" -> ")
+            )));
+        }
+        let table = match self.profiles.get(profile) {
+            Some(Value::Table(table)) => table,
+            Some(_) => {
+                return Err(SolanaIndexerError::Config(format!(
+                    "Profile '{profile}' is not a table"
+                )))
+            }
+            None => {
+                return Err(SolanaIndexerError::Config(format!(
+                    "Unknown profile '{profile}'"
+                )))
+            }
+        };
+        chain.push(profile.to_string());
+
+        let parent = match table.get(INHERITS_KEY) {
+            Some(Value::String(parent)) => Some(parent.as_str()),
+            Some(_) => {
+                return Err(SolanaIndexerError::Config(format!(
+                    "'{INHERITS_KEY}' of profile '{profile}' must be a profile name"
+                )))
+            }
+            None if profile != BASE_PROFILE && self.profiles.contains_key(BASE_PROFILE) => {
+                Some(BASE_PROFILE)
+            }
+            None => None,
+        };
+
+        let mut merged = match parent {
+            Some(parent) => self.resolve(parent, chain)?,
+            None => Table::new(),
+        };
+        merge(&mut merged, table);
+        merged.remove(INHERITS_KEY);
+        Ok(merged)
+    }
+}
+
+impl FromStr for ConfigProfiles {
+    type Err = SolanaIndexerError;
+
+    fn from_str(contents: &str) -> Result<Self> {
+        let profiles = toml::from_str(contents)
+            .map_err(|e| SolanaIndexerError::Config(format!("Invalid config file: {e}")))?;
+        Ok(Self { profiles })
+    }
+}
+
+/// Overlays `overrides` onto `base`, merging nested tables.
+fn merge(base: &mut Table, overrides: &Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Returns the profile named by `--profile <name>` or `--profile=<name>` in
+/// `args`, falling back to the `SOLANA_INDEXER_PROFILE` environment
+/// variable.
+pub fn profile_from_args(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(profile) = arg.strip_prefix("--profile=") {
+            return Some(profile.to_string());
+        }
+    }
+    std::env::var(PROFILE_ENV).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [base]
+        rpc_url = "http://localhost:8899"
+        database_url = "postgresql://localhost/indexer"
+        program_ids = ["11111111111111111111111111111111"]
+        batch_size = 100
+
+        [devnet]
+        rpc_url = "https://api.devnet.solana.com"
+
+        [prod]
+        rpc_url = "https://api.mainnet-beta.solana.com"
+        commitment = "finalized"
+
+        [prod-backfill]
+        inherits = "prod"
+        batch_size = 1000
+    "#;
+
+    #[test]
+    fn test_profiles_inherit_and_override() {
+        let profiles: ConfigProfiles = CONFIG.parse().unwrap();
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["base", "devnet", "prod", "prod-backfill"]
+        );
+
+        let devnet = profiles.settings("devnet").unwrap();
+        assert_eq!(
+            devnet.rpc_url.as_deref(),
+            Some("https://api.devnet.solana.com")
+        );
+        assert_eq!(
+            devnet.database_url.as_deref(),
+            Some("postgresql://localhost/indexer")
+        );
+        assert_eq!(devnet.batch_size, Some(100));
+        assert_eq!(devnet.commitment, None);
+
+        let backfill = profiles.settings("prod-backfill").unwrap();
+        assert_eq!(
+            backfill.rpc_url.as_deref(),
+            Some("https://api.mainnet-beta.solana.com")
+        );
+        assert_eq!(backfill.commitment, Some(CommitmentLevel::Finalized));
+        assert_eq!(backfill.batch_size, Some(1000));
+        assert_eq!(
+            backfill.program_ids,
+            Some(vec!["11111111111111111111111111111111".to_string()])
+        );
+
+        let config = profiles.builder("prod").unwrap().build().unwrap();
+        assert_eq!(config.commitment_level, CommitmentLevel::Finalized);
+    }
+
+    #[test]
+    fn test_rejects_invalid_profiles() {
+        let profiles: ConfigProfiles = r#"
+            [a]
+            inherits = "b"
+            [b]
+            inherits = "a"
+            [typo]
+            batch_sise = 10
+        "#
+        .parse()
+        .unwrap();
+
+        assert!(profiles.settings("a").is_err());
+        assert!(profiles.settings("typo").is_err());
+        assert!(profiles.settings("missing").is_err());
+        assert!("not toml [".parse::<ConfigProfiles>().is_err());
+    }
+
+    #[test]
+    fn test_merges_nested_tables() {
+        let mut base: Table = toml::from_str("[a]\nx = 1\ny = 2\nlist = [1, 2]").unwrap();
+        let overrides: Table = toml::from_str("[a]\ny = 3\nlist = [3]").unwrap();
+        merge(&mut base, &overrides);
+        assert_eq!(
+            Value::Table(base),
+            toml::from_str::<Value>("[a]\nx = 1\ny = 3\nlist = [3]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_profile_from_args() {
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            profile_from_args(args(&["indexer", "--profile", "prod"])),
+            Some("prod".to_string())
+        );
+        assert_eq!(
+            profile_from_args(args(&["indexer", "--profile=devnet"])),
+            Some("devnet".to_string())
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Time-of-day rate limits for backfill.
+//!
+//! Historical catch-up competes with the live indexer for RPC capacity. A
+//! `BandwidthSchedule` caps how many slots per second backfill fetches,
+//! depending on the time of day in UTC, so it can run at full speed
+//! overnight and trickle during business hours:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::config::BackfillConfig;
+//! # use solana_indexer_sdk::BandwidthSchedule;
+//! # fn example() -> solana_indexer_sdk::Result<()> {
+//! let backfill = BackfillConfig {
+//!     enabled: true,
+//!     bandwidth: BandwidthSchedule::new()
+//!         .with_default_rate(Some(0.5))
+//!         .with_window("00:00", "06:00", None)?,
+//!     ..Default::default()
+//! };
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use chrono::{Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// How long a paused backfill waits before checking the schedule again.
+const PAUSE_RECHECK: Duration = Duration::from_secs(30);
+
+/// A daily time window with its own backfill rate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthWindow {
+    /// Start of the window, in minutes after midnight UTC
+    pub start_minute: u32,
+    /// End of the window (exclusive), in minutes after midnight UTC; a
+    /// window ending before it starts runs past midnight, and one ending
+    /// where it starts covers the whole day
+    pub end_minute: u32,
+    /// Slots per second backfilled in the window; `None` is unlimited
+    pub max_slots_per_sec: Option<f64>,
+}
+
+impl BandwidthWindow {
+    /// Returns whether `minute` (after midnight UTC) falls in the window.
+    #[must_use]
+    pub fn contains(&self, minute: u32) -> bool {
+        match self.start_minute.cmp(&self.end_minute) {
+            std::cmp::Ordering::Less => (self.start_minute..self.end_minute).contains(&minute),
+            std::cmp::Ordering::Greater => minute >= self.start_minute || minute < self.end_minute,
+            std::cmp::Ordering::Equal => true,
+        }
+    }
+}
+
+/// Backfill rate limits by time of day.
+///
+/// The first window containing the current time sets the rate; outside
+/// every window the default rate applies. A rate of `0` pauses backfill.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthSchedule {
+    windows: Vec<BandwidthWindow>,
+    default_rate: Option<f64>,
+}
+
+impl BandwidthSchedule {
+    /// Creates a schedule that never limits backfill.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the rate outside every window, in slots per second (default:
+    /// unlimited).
+    #[must_use]
+    pub fn with_default_rate(mut self, max_slots_per_sec: Option<f64>) -> Self {
+        self.default_rate = max_slots_per_sec;
+        self
+    }
+
+    /// Adds a window from `start` to `end`, both `HH:MM` in UTC.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if a time is malformed or
+    /// the rate is negative.
+    pub fn with_window(
+        mut self,
+        start: &str,
+        end: &str,
+        max_slots_per_sec: Option<f64>,
+    ) -> Result<Self> {
+        if max_slots_per_sec.is_some_and(|rate| rate.is_nan() || rate < 0.0) {
+            return Err(SolanaIndexerError::Config(format!(
+                "Invalid backfill rate for window {start}-{end}"
+            )));
+        }
+        self.windows.push(BandwidthWindow {
+            start_minute: parse_time(start)?,
+            end_minute: parse_time(end)?,
+            max_slots_per_sec,
+        });
+        Ok(self)
+    }
+
+    /// Returns the windows, in the order they are matched.
+    #[must_use]
+    pub fn windows(&self) -> &[BandwidthWindow] {
+        &self.windows
+    }
+
+    /// Returns the rate at `minute` after midnight UTC; `None` is
+    /// unlimited.
+    #[must_use]
+    pub fn rate_at(&self, minute: u32) -> Option<f64> {
+        self.windows
+            .iter()
+            .find(|window| window.contains(minute))
+            .map_or(self.default_rate, |window| window.max_slots_per_sec)
+    }
+}
+
+/// Spaces out backfilled slots to the rate of a `BandwidthSchedule`.
+#[derive(Debug, Default)]
+pub(crate) struct Throttle {
+    next: Option<Instant>,
+}
+
+impl Throttle {
+    /// Waits until the next slot may be fetched. Returns early if `cancel`
+    /// fires.
+    pub(crate) async fn wait(&mut self, schedule: &BandwidthSchedule, cancel: &CancellationToken) {
+        loop {
+            let rate = schedule.rate_at(current_minute());
+            let delay = match rate {
+                None => {
+                    self.next = None;
+                    return;
+                }
+                Some(rate) if rate > 0.0 => Duration::from_secs_f64(1.0 / rate),
+                Some(_) => {
+                    // Paused until the schedule allows backfill again
+                    self.next = None;
+                    tokio::select! {
+                        () = tokio::time::sleep(PAUSE_RECHECK) => continue,
+                        () = cancel.cancelled() => return,
+                    }
+                }
+            };
+
+            if let Some(next) = self.next {
+                tokio::select! {
+                    () = tokio::time::sleep_until(next) => {}
+                    () = cancel.cancelled() => return,
+                }
+            }
+            self.next = Some(Instant::now() + delay);
+            return;
+        }
+    }
+}
+
+fn current_minute() -> u32 {
+    let now = Utc::now();
+    now.hour() * 60 + now.minute()
+}
+
+/// Parses `HH:MM` into minutes after midnight.
+fn parse_time(time: &str) -> Result<u32> {
+    let invalid = || SolanaIndexerError::Config(format!("Invalid time '{time}', expected HH:MM"));
+    let (hours, minutes) = time.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    // "24:00" ends a window at midnight
+    let minute = hours * 60 + minutes;
+    if minutes >= 60 || minute > MINUTES_PER_DAY {
+        return Err(invalid());
+    }
+    Ok(minute % MINUTES_PER_DAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_by_time_of_day() -> Result<()> {
+        let schedule = BandwidthSchedule::new()
+            .with_default_rate(Some(2.0))
+            .with_window("00:00", "06:00", None)?
+            .with_window("22:30", "01:00", Some(10.0))?;
+
+        assert_eq!(schedule.rate_at(0), None);
+        assert_eq!(schedule.rate_at(5 * 60 + 59), None);
+        assert_eq!(schedule.rate_at(6 * 60), Some(2.0));
+        assert_eq!(schedule.rate_at(23 * 60), Some(10.0));
+        assert_eq!(schedule.rate_at(22 * 60), Some(2.0));
+        assert_eq!(BandwidthSchedule::new().rate_at(12 * 60), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rejects_invalid_windows() {
+        assert!(BandwidthSchedule::new()
+            .with_window("6:00", "24:00", None)
+            .is_ok());
+        assert!(BandwidthSchedule::new()
+            .with_window("06:60", "07:00", None)
+            .is_err());
+        assert!(BandwidthSchedule::new()
+            .with_window("0600", "07:00", None)
+            .is_err());
+        assert!(BandwidthSchedule::new()
+            .with_window("24:01", "07:00", None)
+            .is_err());
+        assert!(BandwidthSchedule::new()
+            .with_window("06:00", "07:00", Some(-1.0))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_throttle_spaces_slots() -> Result<()> {
+        let schedule = BandwidthSchedule::new().with_window("00:00", "00:00", Some(50.0))?;
+        let cancel = CancellationToken::new();
+        let mut throttle = Throttle::default();
+
+        let started = Instant::now();
+        for _ in 0..5 {
+            throttle.wait(&schedule, &cancel).await;
+        }
+        assert!(started.elapsed() >= Duration::from_millis(80));
+
+        cancel.cancel();
+        let cancelled = Instant::now();
+        throttle.wait(&schedule, &cancel).await;
+        assert!(cancelled.elapsed() < Duration::from_millis(20));
+        Ok(())
+    }
+}
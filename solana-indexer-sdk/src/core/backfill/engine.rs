@@ -1,15 +1,22 @@
 use crate::config::SolanaIndexerConfig;
+use crate::core::backfill::bandwidth::Throttle;
+use crate::core::backfill::time;
 use crate::core::decoding::Decoder;
-use crate::core::execution::fetcher::Fetcher;
-use crate::core::execution::indexer::SolanaIndexer;
+use crate::core::execution::fetcher::{
+    account_keys, inner_instructions, instructions, is_vote_transaction, transaction_error,
+    transaction_index, Fetcher,
+};
+use crate::core::execution::indexer::{reject_oversized, resolve_missing_meta, SolanaIndexer};
 use crate::core::registry::account::AccountDecoderRegistry;
 use crate::core::registry::logs::LogDecoderRegistry;
-use crate::core::registry::DecoderRegistry;
+use crate::core::registry::{DecoderRegistry, ProgramScope};
+use crate::storage::holders::{self, TokenHolders};
 use crate::storage::StorageBackend;
 use crate::types::backfill_traits::{
     BackfillHandlerRegistry, BackfillProgress, BackfillRange, BackfillStrategy,
     FinalizedBlockTracker, ReorgHandler,
 };
+use crate::types::metadata::{TransactionAccount, TxMetadata};
 use crate::types::traits::HandlerRegistry;
 use crate::utils::error::{Result, SolanaIndexerError};
 use crate::utils::logging::{log, log_error, LogLevel};
@@ -84,12 +91,25 @@ impl BackfillEngine {
             ),
         );
 
-        let mut current_slot = range.start_slot;
+        let (resume_slot, mut resume_after) = self.storage.begin_backfill_range(range).await?;
+        if resume_slot > range.start_slot || resume_after.is_some() {
+            log(
+                LogLevel::Info,
+                &format!("BackfillEngine: Resuming range from slot {resume_slot}"),
+            );
+        }
+
+        let mut current_slot = resume_slot;
         let end_slot = range.end_slot;
+        let batch_size = self.strategy.batch_size().max(1);
         let concurrency = self.strategy.concurrency();
         let semaphore = Arc::new(Semaphore::new(concurrency));
+        let mut throttle = Throttle::default();
 
         while current_slot <= end_slot {
+            throttle
+                .wait(&self.config.backfill.bandwidth, &self.cancellation_token)
+                .await;
             if self.cancellation_token.is_cancelled() {
                 log(LogLevel::Warning, "Backfill cancelled by user.");
                 break;
@@ -106,9 +126,11 @@ impl BackfillEngine {
                     .await?;
             }
 
+            let mut transactions = 0;
             match self.fetcher.fetch_block(current_slot).await {
                 Ok(block) => {
                     let block_hash = block.blockhash;
+                    let watched = self.config.watched_addresses();
                     let mut relevant_signatures = Vec::new();
 
                     if let Some(transactions) = block.transactions {
@@ -124,7 +146,7 @@ impl BackfillEngine {
                                                         &acc.pubkey,
                                                     )
                                                 {
-                                                    self.config.program_ids.contains(&acc_pubkey)
+                                                    watched.contains(&acc_pubkey)
                                                 } else {
                                                     false
                                                 }
@@ -138,10 +160,7 @@ impl BackfillEngine {
                                         }
                                         UiMessage::Raw(msg) => {
                                             let is_relevant = msg.account_keys.iter().any(|key| {
-                                                self.config
-                                                    .program_ids
-                                                    .iter()
-                                                    .any(|p| p.to_string() == *key)
+                                                watched.iter().any(|p| p.to_string() == *key)
                                             });
                                             if is_relevant {
                                                 relevant_signatures.push((
@@ -156,59 +175,14 @@ impl BackfillEngine {
                         }
                     }
 
-                    if !relevant_signatures.is_empty() {
-                        let mut tasks = Vec::new();
-                        for (sig_str, blk_hash) in relevant_signatures.into_iter() {
-                            if let Ok(sig) = Signature::from_str(&sig_str) {
-                                let permit =
-                                    semaphore.clone().acquire_owned().await.map_err(|e| {
-                                        SolanaIndexerError::InternalError(e.to_string())
-                                    })?;
-
-                                let fetcher = self.fetcher.clone();
-                                let decoder = self.decoder.clone();
-                                let decoder_registry = self.decoder_registry.clone();
-                                let log_decoder_registry = self.log_decoder_registry.clone();
-                                let account_decoder_registry =
-                                    self.account_decoder_registry.clone();
-                                let backfill_handlers = self.backfill_handlers.clone();
-                                let storage = self.storage.clone();
-                                let config = self.config.clone();
-
-                                tasks.push(tokio::spawn(async move {
-                                    let res = Self::process_backfill_transaction_core(
-                                        sig,
-                                        fetcher,
-                                        decoder,
-                                        decoder_registry,
-                                        log_decoder_registry,
-                                        account_decoder_registry,
-                                        backfill_handlers,
-                                        storage,
-                                        config,
-                                        Some(blk_hash),
-                                    )
-                                    .await;
-                                    drop(permit);
-                                    res
-                                }));
-                            }
-                        }
-
-                        for task in tasks {
-                            match task.await {
-                                Ok(res) => {
-                                    if let Err(e) = res {
-                                        log_error(
-                                            "Error processing backfill transaction",
-                                            &e.to_string(),
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    log_error("Task join error", &e.to_string());
-                                }
-                            }
+                    skip_handled(&mut relevant_signatures, resume_after.take());
+                    let batches: Vec<_> = relevant_signatures.chunks(batch_size).collect();
+                    for (index, batch) in batches.iter().enumerate() {
+                        let handled = self.backfill_batch(batch, &semaphore).await?;
+                        if index + 1 == batches.len() {
+                            transactions = handled;
+                        } else if self.checkpoint(range, current_slot, batch, handled).await? {
+                            return Ok(());
                         }
                     }
 
@@ -227,6 +201,9 @@ impl BackfillEngine {
                 }
             }
 
+            self.storage
+                .advance_backfill_range(range, current_slot, None, transactions)
+                .await?;
             current_slot += 1;
         }
 
@@ -240,10 +217,160 @@ impl BackfillEngine {
         Ok(())
     }
 
+    /// Runs the backfill handlers over `batch`, as `(signature, block hash)`
+    /// pairs, and marks the handled signatures processed with one COPY.
+    /// Returns how many were handled.
+    async fn backfill_batch(
+        &self,
+        batch: &[(String, String)],
+        semaphore: &Arc<Semaphore>,
+    ) -> Result<u64> {
+        let mut tasks = Vec::new();
+        for (sig_str, blk_hash) in batch {
+            if let Ok(sig) = Signature::from_str(sig_str) {
+                let permit = semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+
+                let fetcher = self.fetcher.clone();
+                let decoder = self.decoder.clone();
+                let decoder_registry = self.decoder_registry.clone();
+                let log_decoder_registry = self.log_decoder_registry.clone();
+                let account_decoder_registry = self.account_decoder_registry.clone();
+                let backfill_handlers = self.backfill_handlers.clone();
+                let storage = self.storage.clone();
+                let config = self.config.clone();
+                let sig_str = sig_str.clone();
+                let blk_hash = blk_hash.clone();
+
+                tasks.push(tokio::spawn(async move {
+                    let res = Self::process_backfill_transaction_core(
+                        sig,
+                        fetcher,
+                        decoder,
+                        decoder_registry,
+                        log_decoder_registry,
+                        account_decoder_registry,
+                        backfill_handlers,
+                        storage,
+                        config,
+                        Some(blk_hash),
+                    )
+                    .await;
+                    drop(permit);
+                    (sig_str, res)
+                }));
+            }
+        }
+
+        let mut processed = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            match task.await {
+                Ok((sig_str, Ok(slot))) => processed.push((sig_str, slot)),
+                Ok((_, Err(e))) => {
+                    log_error("Error processing backfill transaction", &e.to_string());
+                }
+                Err(e) => {
+                    log_error("Task join error", &e.to_string());
+                }
+            }
+        }
+        self.storage.mark_processed_batch(&processed).await?;
+        Ok(processed.len() as u64)
+    }
+
+    /// Runs the live handlers over `batch`, as `(signature, block hash)`
+    /// pairs. Returns how many transactions were handled.
+    async fn index_batch(
+        &self,
+        batch: &[(String, String)],
+        semaphore: &Arc<Semaphore>,
+    ) -> Result<u64> {
+        let mut tasks = Vec::new();
+        for (sig_str, blk_hash) in batch {
+            if let Ok(sig) = Signature::from_str(sig_str) {
+                let permit = semaphore
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+
+                let fetcher = self.fetcher.clone();
+                let decoder = self.decoder.clone();
+                let decoder_registry = self.decoder_registry.clone();
+                let log_decoder_registry = self.log_decoder_registry.clone();
+                let account_decoder_registry = self.account_decoder_registry.clone();
+                let handler_registry = self.handler_registry.clone();
+                let storage = self.storage.clone();
+                let config = self.config.clone();
+                let blk_hash = blk_hash.clone();
+
+                tasks.push(tokio::spawn(async move {
+                    let res = SolanaIndexer::process_transaction_core(
+                        sig,
+                        fetcher,
+                        decoder,
+                        decoder_registry,
+                        log_decoder_registry,
+                        account_decoder_registry,
+                        handler_registry,
+                        storage,
+                        config,
+                        true, // is_finalized
+                        Some(blk_hash),
+                        None, // preloaded_transaction
+                    )
+                    .await;
+                    drop(permit);
+                    res
+                }));
+            }
+        }
+
+        let mut handled = 0;
+        for task in tasks {
+            match task.await {
+                Ok(Ok(_)) => handled += 1,
+                Ok(Err(e)) => {
+                    log_error("Error processing backfill transaction", &e.to_string());
+                }
+                Err(e) => {
+                    log_error("Task join error", &e.to_string());
+                }
+            }
+        }
+        Ok(handled)
+    }
+
+    /// Records that `slot` of `range` is handled through the last signature
+    /// of `batch`, so a restart skips the batches handled so far. Returns
+    /// whether backfill was cancelled, in which case the caller stops here.
+    async fn checkpoint(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        batch: &[(String, String)],
+        handled: u64,
+    ) -> Result<bool> {
+        let cursor = batch.last().map(|(signature, _)| signature.as_str());
+        self.storage
+            .advance_backfill_range(range, slot, cursor, handled)
+            .await?;
+        let cancelled = self.cancellation_token.is_cancelled();
+        if cancelled {
+            log(LogLevel::Warning, "Backfill cancelled by user.");
+        }
+        Ok(cancelled)
+    }
+
     /// Processes a backfill transaction using BackfillHandlerRegistry.
     ///
     /// Similar to `SolanaIndexer::process_transaction_core` but dispatches
-    /// to BackfillHandler<T> instead of EventHandler<T>.
+    /// to BackfillHandler<T> instead of EventHandler<T>. Returns the slot of
+    /// the transaction; the caller marks it processed, so a whole block can
+    /// be marked at once with `StorageBackend::mark_processed_batch`.
     #[allow(clippy::too_many_arguments)]
     pub(crate) async fn process_backfill_transaction_core(
         signature: Signature,
@@ -256,173 +383,175 @@ impl BackfillEngine {
         storage: Arc<dyn StorageBackend>,
         config: SolanaIndexerConfig,
         known_block_hash: Option<String>,
-    ) -> Result<()> {
+    ) -> Result<u64> {
         let sig_str = signature.to_string();
 
         // Fetch transaction
-        let transaction = fetcher.fetch_transaction(&signature).await?;
+        let transaction = Arc::new(fetcher.fetch_transaction(&signature).await?);
+        let fetched_slot = transaction.slot;
+        let Some(transaction) = resolve_missing_meta(
+            &fetcher,
+            &config,
+            backfill_handlers.metrics(),
+            &signature,
+            transaction,
+        )
+        .await?
+        else {
+            return Ok(fetched_slot);
+        };
+
+        // Apply source-level filters before spending any work on decoding
+        let tx_error = transaction_error(&transaction);
+        if (config.skip_failed_transactions && tx_error.is_some())
+            || (config.skip_vote_transactions && is_vote_transaction(&transaction))
+        {
+            return Ok(transaction.slot);
+        }
 
         // Decode transaction metadata
         let decoded_meta = decoder.decode_transaction(&transaction)?;
         let slot = decoded_meta.slot;
 
-        // Extract native metadata
-        let meta = transaction.transaction.meta.as_ref().ok_or_else(|| {
-            SolanaIndexerError::DecodingError("Missing transaction metadata".into())
-        })?;
-
-        let pre_token_balances_opt: Option<
-            Vec<solana_transaction_status::UiTransactionTokenBalance>,
-        > = meta.pre_token_balances.clone().into();
-        let pre_token_balances = pre_token_balances_opt.unwrap_or_default();
-        let post_token_balances_opt: Option<
-            Vec<solana_transaction_status::UiTransactionTokenBalance>,
-        > = meta.post_token_balances.clone().into();
-        let post_token_balances = post_token_balances_opt.unwrap_or_default();
-
         // Construct context
-        let context = crate::types::metadata::TxMetadata {
-            slot,
-            block_time: transaction.block_time,
-            fee: meta.fee,
-            pre_balances: meta.pre_balances.clone(),
-            post_balances: meta.post_balances.clone(),
-            pre_token_balances: pre_token_balances
-                .into_iter()
-                .map(|b| crate::types::metadata::TokenBalanceInfo {
-                    account_index: b.account_index,
-                    mint: b.mint,
-                    owner: Into::<Option<String>>::into(b.owner).unwrap_or_default(),
-                    amount: b.ui_token_amount.amount,
-                    decimals: b.ui_token_amount.decimals,
-                    program_id: b.program_id.into(),
-                })
-                .collect(),
-            post_token_balances: post_token_balances
-                .into_iter()
-                .map(|b| crate::types::metadata::TokenBalanceInfo {
-                    account_index: b.account_index,
-                    mint: b.mint,
-                    owner: Into::<Option<String>>::into(b.owner).unwrap_or_default(),
-                    amount: b.ui_token_amount.amount,
-                    decimals: b.ui_token_amount.decimals,
-                    program_id: b.program_id.into(),
-                })
-                .collect(),
-            signature: sig_str.clone(),
-        };
+        let mut context = TxMetadata::from_transaction(&transaction, sig_str.clone(), tx_error);
+        context.flags = backfill_handlers.feature_flags().clone();
+        context.jobs = backfill_handlers.deferred_jobs().clone();
+        if !config.token_holder_mints.is_empty() {
+            let holdings = holders::holdings(
+                &context,
+                &account_keys(&transaction),
+                &config.token_holder_mints,
+            );
+            TokenHolders::upsert(storage.require_pool()?, &holdings).await?;
+        }
 
         let block_hash = if let Some(h) = known_block_hash {
             h
         } else {
             match fetcher.fetch_block(slot).await {
-                Ok(block) => block.blockhash,
+                Ok(block) => {
+                    context.index_in_block = transaction_index(&block, &sig_str);
+                    block.blockhash
+                }
                 Err(_) => "UNKNOWN".to_string(),
             }
         };
+        if context.index_in_block.is_none() && config.block_index {
+            context.index_in_block = fetcher.transaction_index(slot, &sig_str).await?;
+        }
 
         // Extract UI instructions from the transaction
-        let instructions: &[solana_transaction_status::UiInstruction] = match &transaction
-            .transaction
-            .transaction
-        {
-            solana_transaction_status::EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
-                solana_transaction_status::UiMessage::Parsed(msg) => &msg.instructions,
-                _ => &[],
-            },
-            _ => &[],
-        };
-
-        let mut events_processed = 0;
+        let instructions = instructions(&transaction);
 
-        // Process based on indexing mode - dispatch to BackfillHandler
+        // Decode instruction and log events up front, so oversized
+        // transactions are caught before any handler runs
+        let scope = ProgramScope::for_transaction(&config, &transaction, &instructions);
+        let mut events = Vec::new();
         if config.indexing_mode.inputs {
-            let events = decoder_registry.decode_transaction(instructions);
+            let inner = if config.inner_instructions {
+                inner_instructions(&transaction)
+            } else {
+                &[]
+            };
+            events.extend(decoder_registry.decode_with_accounts(
+                &instructions,
+                inner,
+                TransactionAccount::from_transaction(&transaction),
+                &scope,
+            ));
+        }
+        if config.indexing_mode.logs {
+            events.extend(log_decoder_registry.decode_events(&decoded_meta.events, &scope));
+        }
 
-            for (discriminator, event_data) in events {
-                match backfill_handlers
-                    .handle_backfill(&discriminator, &event_data, &context, storage.pool())
-                    .await
-                {
-                    Ok(()) => events_processed += 1,
-                    Err(e) => {
-                        log_error("Backfill handler error", &format!("{sig_str}: {e}"));
-                        return Err(e);
-                    }
-                }
-            }
+        if reject_oversized(
+            storage.as_ref(),
+            &config,
+            backfill_handlers.metrics(),
+            &sig_str,
+            slot,
+            events.len(),
+        )
+        .await?
+        {
+            return Ok(slot);
         }
 
-        if config.indexing_mode.logs {
-            let events = log_decoder_registry.decode_logs(&decoded_meta.events);
+        let mut events_processed = 0;
 
-            for (discriminator, event_data) in events {
-                match backfill_handlers
-                    .handle_backfill(&discriminator, &event_data, &context, storage.pool())
-                    .await
-                {
-                    Ok(()) => events_processed += 1,
-                    Err(e) => {
-                        log_error("Backfill handler error (logs)", &format!("{sig_str}: {e}"));
-                        return Err(e);
-                    }
+        // Dispatch to BackfillHandler
+        for (discriminator, event_data) in events {
+            match backfill_handlers
+                .handle_backfill(
+                    &discriminator,
+                    &event_data,
+                    &context,
+                    storage.require_pool()?,
+                )
+                .await
+            {
+                Ok(()) => events_processed += 1,
+                Err(e) => {
+                    log_error("Backfill handler error", &format!("{sig_str}: {e}"));
+                    return Err(e);
                 }
             }
+        }
 
-            if config.indexing_mode.accounts {
-                let mut writable_accounts = std::collections::HashSet::new();
-
-                if let solana_transaction_status::EncodedTransaction::Json(ui_tx) =
-                    &transaction.transaction.transaction
-                {
-                    match &ui_tx.message {
-                        solana_transaction_status::UiMessage::Parsed(msg) => {
-                            for account in &msg.account_keys {
-                                if account.writable {
-                                    if let Ok(pubkey) =
-                                        solana_sdk::pubkey::Pubkey::from_str(&account.pubkey)
-                                    {
-                                        writable_accounts.insert(pubkey);
-                                    }
+        if config.indexing_mode.logs && config.indexing_mode.accounts {
+            let mut writable_accounts = std::collections::HashSet::new();
+
+            if let solana_transaction_status::EncodedTransaction::Json(ui_tx) =
+                &transaction.transaction.transaction
+            {
+                match &ui_tx.message {
+                    solana_transaction_status::UiMessage::Parsed(msg) => {
+                        for account in &msg.account_keys {
+                            if account.writable {
+                                if let Ok(pubkey) =
+                                    solana_sdk::pubkey::Pubkey::from_str(&account.pubkey)
+                                {
+                                    writable_accounts.insert(pubkey);
                                 }
                             }
                         }
-                        solana_transaction_status::UiMessage::Raw(msg) => {
-                            for key_str in &msg.account_keys {
-                                if let Ok(pubkey) = solana_sdk::pubkey::Pubkey::from_str(key_str) {
-                                    writable_accounts.insert(pubkey);
-                                }
+                    }
+                    solana_transaction_status::UiMessage::Raw(msg) => {
+                        for key_str in &msg.account_keys {
+                            if let Ok(pubkey) = solana_sdk::pubkey::Pubkey::from_str(key_str) {
+                                writable_accounts.insert(pubkey);
                             }
                         }
                     }
-                };
-
-                if !writable_accounts.is_empty() {
-                    let keys: Vec<_> = writable_accounts.into_iter().collect();
-                    if let Ok(accounts) = fetcher.fetch_multiple_accounts(&keys).await {
-                        for (index, account_option) in accounts.iter().enumerate() {
-                            if let Some(account) = account_option {
-                                let pubkey = &keys[index];
-                                let decoded_list =
-                                    account_decoder_registry.decode_account(pubkey, account);
-                                for (discriminator, event_data) in decoded_list {
-                                    match backfill_handlers
-                                        .handle_backfill(
-                                            &discriminator,
-                                            &event_data,
-                                            &context,
-                                            storage.pool(),
-                                        )
-                                        .await
-                                    {
-                                        Ok(()) => events_processed += 1,
-                                        Err(e) => {
-                                            log_error(
-                                                "Backfill handler error (account)",
-                                                &format!("{sig_str}: {e}"),
-                                            );
-                                            return Err(e);
-                                        }
+                }
+            };
+
+            if !writable_accounts.is_empty() {
+                let keys: Vec<_> = writable_accounts.into_iter().collect();
+                if let Ok(accounts) = fetcher.fetch_multiple_accounts(&keys).await {
+                    for (index, account_option) in accounts.iter().enumerate() {
+                        if let Some(account) = account_option {
+                            let pubkey = &keys[index];
+                            let decoded_list =
+                                account_decoder_registry.decode_account(pubkey, account);
+                            for (discriminator, event_data) in decoded_list {
+                                match backfill_handlers
+                                    .handle_backfill(
+                                        &discriminator,
+                                        &event_data,
+                                        &context,
+                                        storage.require_pool()?,
+                                    )
+                                    .await
+                                {
+                                    Ok(()) => events_processed += 1,
+                                    Err(e) => {
+                                        log_error(
+                                            "Backfill handler error (account)",
+                                            &format!("{sig_str}: {e}"),
+                                        );
+                                        return Err(e);
                                     }
                                 }
                             }
@@ -432,9 +561,8 @@ impl BackfillEngine {
             }
         }
 
-        // Mark as processed (backfill transactions are always finalized)
+        // Backfill transactions are always finalized
         storage.mark_finalized(slot, &block_hash).await?;
-        storage.mark_processed(&sig_str, slot).await?;
 
         if events_processed > 0 {
             log(
@@ -443,7 +571,7 @@ impl BackfillEngine {
             );
         }
 
-        Ok(())
+        Ok(slot)
     }
 
     /// Starts the backfill process.
@@ -462,6 +590,13 @@ impl BackfillEngine {
 
         let mut current_slot = if let Some(s) = start_slot_opt {
             s
+        } else if let Some(start_time) = self.config.backfill.start_time {
+            let slot = time::slot_at_time(&self.fetcher, start_time).await?;
+            log(
+                LogLevel::Info,
+                &format!("Backfill start time {start_time} resolved to slot {slot}"),
+            );
+            slot
         } else if let Some(saved) = self
             .progress_tracker
             .load_progress(self.storage.as_ref())
@@ -488,11 +623,43 @@ impl BackfillEngine {
             LogLevel::Info,
             &format!("Backfill range: {} to {}", current_slot, end_slot),
         );
+        // Pick up an earlier run over the same range where it stopped
+        let range = self
+            .storage
+            .load_backfill_ranges()
+            .await?
+            .into_iter()
+            .find(|progress| {
+                !progress.is_complete()
+                    && progress.range.start_slot == current_slot
+                    && end_slot_opt.map_or(true, |end| end == progress.range.end_slot)
+            })
+            .map_or(BackfillRange::new(current_slot, end_slot), |progress| {
+                progress.range
+            });
+        let end_slot = range.end_slot;
+        let mut resume_after = None;
+        if !range.is_empty() {
+            let (resume_slot, cursor) = self.storage.begin_backfill_range(range).await?;
+            if resume_slot > current_slot || cursor.is_some() {
+                log(
+                    LogLevel::Info,
+                    &format!("Resuming backfill range from slot {resume_slot}"),
+                );
+            }
+            current_slot = resume_slot;
+            resume_after = cursor;
+        }
+        let batch_size = self.strategy.batch_size().max(1);
 
         let concurrency = self.strategy.concurrency();
         let semaphore = Arc::new(Semaphore::new(concurrency));
+        let mut throttle = Throttle::default();
 
         while current_slot <= end_slot {
+            throttle
+                .wait(&self.config.backfill.bandwidth, &self.cancellation_token)
+                .await;
             if self.cancellation_token.is_cancelled() {
                 log(LogLevel::Warning, "Backfill cancelled by user.");
                 break;
@@ -509,10 +676,12 @@ impl BackfillEngine {
                     .await?;
             }
 
+            let mut transactions = 0;
             match self.fetcher.fetch_block(current_slot).await {
                 Ok(block) => {
                     let block_hash = block.blockhash;
-                    // Filter transactions for our program
+                    // Filter transactions for our programs and tracked addresses
+                    let watched = self.config.watched_addresses();
                     let mut relevant_signatures = Vec::new();
 
                     if let Some(transactions) = block.transactions {
@@ -537,9 +706,7 @@ impl BackfillEngine {
                                                                 &acc.pubkey,
                                                             )
                                                         {
-                                                            self.config
-                                                                .program_ids
-                                                                .contains(&acc_pubkey)
+                                                            watched.contains(&acc_pubkey)
                                                         } else {
                                                             false
                                                         }
@@ -554,8 +721,7 @@ impl BackfillEngine {
                                             UiMessage::Raw(msg) => {
                                                 let is_relevant =
                                                     msg.account_keys.iter().any(|key| {
-                                                        self.config
-                                                            .program_ids
+                                                        watched
                                                             .iter()
                                                             .any(|p| p.to_string() == *key)
                                                     });
@@ -587,61 +753,16 @@ impl BackfillEngine {
                                 relevant_signatures.len()
                             ),
                         );
+                    }
 
-                        let mut tasks = Vec::new();
-                        for (sig_str, blk_hash) in relevant_signatures.into_iter() {
-                            if let Ok(sig) = Signature::from_str(&sig_str) {
-                                let permit =
-                                    semaphore.clone().acquire_owned().await.map_err(|e| {
-                                        SolanaIndexerError::InternalError(e.to_string())
-                                    })?;
-
-                                let fetcher = self.fetcher.clone();
-                                let decoder = self.decoder.clone();
-                                let decoder_registry = self.decoder_registry.clone();
-                                let log_decoder_registry = self.log_decoder_registry.clone();
-                                let account_decoder_registry =
-                                    self.account_decoder_registry.clone();
-                                let handler_registry = self.handler_registry.clone();
-                                let storage = self.storage.clone();
-                                let config = self.config.clone();
-
-                                tasks.push(tokio::spawn(async move {
-                                    let res = SolanaIndexer::process_transaction_core(
-                                        sig,
-                                        fetcher,
-                                        decoder,
-                                        decoder_registry,
-                                        log_decoder_registry,
-                                        account_decoder_registry,
-                                        handler_registry,
-                                        storage,
-                                        config,
-                                        true, // is_finalized
-                                        Some(blk_hash),
-                                        None, // preloaded_transaction
-                                    )
-                                    .await;
-                                    drop(permit);
-                                    res
-                                }));
-                            }
-                        }
-
-                        for task in tasks {
-                            match task.await {
-                                Ok(res) => {
-                                    if let Err(e) = res {
-                                        log_error(
-                                            "Error processing backfill transaction",
-                                            &e.to_string(),
-                                        );
-                                    }
-                                }
-                                Err(e) => {
-                                    log_error("Task join error", &e.to_string());
-                                }
-                            }
+                    skip_handled(&mut relevant_signatures, resume_after.take());
+                    let batches: Vec<_> = relevant_signatures.chunks(batch_size).collect();
+                    for (index, batch) in batches.iter().enumerate() {
+                        let handled = self.index_batch(batch, &semaphore).await?;
+                        if index + 1 == batches.len() {
+                            transactions = handled;
+                        } else if self.checkpoint(range, current_slot, batch, handled).await? {
+                            return Ok(());
                         }
                     }
 
@@ -661,6 +782,9 @@ impl BackfillEngine {
                 }
             }
 
+            self.storage
+                .advance_backfill_range(range, current_slot, None, transactions)
+                .await?;
             current_slot += 1;
         }
 
@@ -671,3 +795,42 @@ impl BackfillEngine {
         Ok(())
     }
 }
+
+/// Drops the signatures of a partly backfilled slot up to and including
+/// `cursor`, the last one a previous run handled. Nothing is dropped if
+/// `cursor` is not among them.
+fn skip_handled(signatures: &mut Vec<(String, String)>, cursor: Option<String>) {
+    let Some(cursor) = cursor else {
+        return;
+    };
+    if let Some(position) = signatures
+        .iter()
+        .position(|(signature, _)| *signature == cursor)
+    {
+        signatures.drain(..=position);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_handled_resumes_after_cursor() {
+        let block = |names: &[&str]| -> Vec<(String, String)> {
+            names
+                .iter()
+                .map(|name| (name.to_string(), "hash".to_string()))
+                .collect()
+        };
+        let mut signatures = block(&["a", "b", "c", "d"]);
+        skip_handled(&mut signatures, Some("b".to_string()));
+        assert_eq!(signatures, block(&["c", "d"]));
+
+        // A cursor from another slot skips nothing
+        skip_handled(&mut signatures, Some("z".to_string()));
+        assert_eq!(signatures, block(&["c", "d"]));
+        skip_handled(&mut signatures, None);
+        assert_eq!(signatures, block(&["c", "d"]));
+    }
+}
@@ -0,0 +1,217 @@
+//! Signature gap detection for RPC polling.
+//!
+//! `getSignaturesForAddress` is queried with `until` set to the last processed
+//! signature (the overlap anchor). When the response fills the whole page, the
+//! RPC node stopped at the limit before reaching the anchor, either because more
+//! transactions landed than fit in a page or because the anchor was pruned.
+//! Everything between the anchor and the oldest returned signature would then be
+//! skipped silently. `SignatureGapDetector` spots these pages and returns the
+//! slot range that needs repairing. After a restart it is seeded with the slot
+//! of each stored cursor, so the first poll, which covers the downtime, is
+//! checked as well.
+//!
+//! Push-based live sources have no page to inspect: a dropped WebSocket
+//! message just never arrives. `SlotGapAlarm` instead watches the slots of
+//! consecutive live transactions and reports a jump wider than expected.
+
+use crate::streams::TransactionEvent;
+use crate::types::backfill_traits::BackfillRange;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Tracks the newest slot seen per program and detects gaps between pages.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::core::backfill::gaps::SignatureGapDetector;
+/// use solana_indexer_sdk::streams::TransactionEvent;
+/// use solana_sdk::pubkey::Pubkey;
+/// use solana_sdk::signature::Signature;
+///
+/// let program = Pubkey::new_unique();
+/// let mut detector = SignatureGapDetector::new(2);
+/// let page = |slots: &[u64]| -> Vec<TransactionEvent> {
+///     slots
+///         .iter()
+///         .map(|&slot| TransactionEvent::Signature { signature: Signature::new_unique(), slot })
+///         .collect()
+/// };
+///
+/// assert_eq!(detector.observe(&program, &page(&[10])), None);
+/// // A full page means the anchor at slot 10 was never reached.
+/// let gap = detector.observe(&program, &page(&[30, 25])).unwrap();
+/// assert_eq!((gap.start_slot, gap.end_slot), (10, 25));
+/// ```
+#[derive(Debug)]
+pub struct SignatureGapDetector {
+    /// Page size passed to `getSignaturesForAddress`.
+    page_limit: usize,
+    /// Newest slot seen per program (the anchor for the next page).
+    anchors: HashMap<Pubkey, u64>,
+}
+
+impl SignatureGapDetector {
+    /// Creates a detector for pages of at most `page_limit` signatures.
+    #[must_use]
+    pub fn new(page_limit: usize) -> Self {
+        Self {
+            page_limit,
+            anchors: HashMap::new(),
+        }
+    }
+
+    /// Anchors `program_id` at `slot`, the slot of the cursor its next page
+    /// is fetched `until`.
+    pub fn seed(&mut self, program_id: Pubkey, slot: u64) {
+        self.anchors.insert(program_id, slot);
+    }
+
+    /// Inspects a page of signatures (newest first) fetched for `program_id`.
+    ///
+    /// Returns the slot range between the previous anchor and the oldest
+    /// signature in the page if the page may not reach back to the anchor.
+    /// The first page seen for a program that was not seeded only
+    /// establishes the anchor.
+    pub fn observe(
+        &mut self,
+        program_id: &Pubkey,
+        page: &[TransactionEvent],
+    ) -> Option<BackfillRange> {
+        let newest = page.iter().map(TransactionEvent::slot).max()?;
+        let previous = self.anchors.insert(*program_id, newest);
+
+        let anchor_slot = previous?;
+        if self.page_limit == 0 || page.len() < self.page_limit {
+            return None;
+        }
+
+        let oldest = page.iter().map(TransactionEvent::slot).min()?;
+        if oldest < anchor_slot {
+            return None;
+        }
+        Some(BackfillRange::new(anchor_slot, oldest))
+    }
+}
+
+/// Detects jumps between the slots of consecutive live transactions.
+///
+/// Quiet programs legitimately go many slots without a transaction, so
+/// `max_gap` should sit above the longest idle stretch expected.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::core::backfill::gaps::SlotGapAlarm;
+///
+/// let alarm = SlotGapAlarm::new(100);
+/// assert_eq!(alarm.observe(1_000), None);
+/// assert_eq!(alarm.observe(1_050), None);
+/// let gap = alarm.observe(1_500).unwrap();
+/// assert_eq!((gap.start_slot, gap.end_slot), (1_050, 1_500));
+/// ```
+#[derive(Debug)]
+pub struct SlotGapAlarm {
+    /// Largest tolerated jump, in slots.
+    max_gap: u64,
+    /// Highest slot seen so far (0 before the first transaction).
+    last_slot: AtomicU64,
+}
+
+impl SlotGapAlarm {
+    /// Creates an alarm for jumps of more than `max_gap` slots.
+    #[must_use]
+    pub fn new(max_gap: u64) -> Self {
+        Self {
+            max_gap,
+            last_slot: AtomicU64::new(0),
+        }
+    }
+
+    /// Records the slot of a live transaction.
+    ///
+    /// Returns the slot range since the previous highest slot if the jump
+    /// exceeds `max_gap`. Older slots, such as gap fills, never alarm. The
+    /// first slot seen only establishes the baseline.
+    pub fn observe(&self, slot: u64) -> Option<BackfillRange> {
+        let previous = self.last_slot.fetch_max(slot, Ordering::Relaxed);
+        if previous == 0 || slot <= previous.saturating_add(self.max_gap) {
+            return None;
+        }
+        Some(BackfillRange::new(previous, slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Signature;
+
+    fn page(slots: &[u64]) -> Vec<TransactionEvent> {
+        slots
+            .iter()
+            .map(|&slot| TransactionEvent::Signature {
+                signature: Signature::new_unique(),
+                slot,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_partial_page_has_no_gap() {
+        let program = Pubkey::new_unique();
+        let mut detector = SignatureGapDetector::new(3);
+
+        assert_eq!(detector.observe(&program, &page(&[5, 4, 3])), None);
+        assert_eq!(detector.observe(&program, &page(&[8, 6])), None);
+        assert_eq!(detector.observe(&program, &[]), None);
+    }
+
+    #[test]
+    fn test_full_page_reports_gap_per_program() {
+        let program = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut detector = SignatureGapDetector::new(2);
+
+        assert_eq!(detector.observe(&program, &page(&[10])), None);
+        assert_eq!(detector.observe(&other, &page(&[50])), None);
+
+        let gap = detector
+            .observe(&program, &page(&[40, 20]))
+            .expect("full page should report a gap");
+        assert_eq!((gap.start_slot, gap.end_slot), (10, 20));
+
+        // The anchor moves to the newest slot of the last page.
+        assert_eq!(detector.observe(&program, &page(&[41])), None);
+        let gap = detector
+            .observe(&other, &page(&[60, 55]))
+            .expect("full page should report a gap");
+        assert_eq!((gap.start_slot, gap.end_slot), (50, 55));
+    }
+
+    #[test]
+    fn test_seeded_anchor_checks_first_page() {
+        let program = Pubkey::new_unique();
+        let mut detector = SignatureGapDetector::new(2);
+
+        // Restarted with a cursor at slot 100
+        detector.seed(program, 100);
+        assert_eq!(
+            detector.observe(&program, &page(&[300, 250])),
+            Some(BackfillRange::new(100, 250))
+        );
+    }
+
+    #[test]
+    fn test_slot_gap_alarm_ignores_older_slots() {
+        let alarm = SlotGapAlarm::new(10);
+
+        assert_eq!(alarm.observe(500), None);
+        assert_eq!(alarm.observe(510), None);
+        // Out-of-order and repaired slots stay below the high mark
+        assert_eq!(alarm.observe(200), None);
+        assert_eq!(alarm.observe(521), Some(BackfillRange::new(510, 521)));
+        assert_eq!(alarm.observe(525), None);
+    }
+}
@@ -9,10 +9,12 @@ use crate::core::backfill::engine::BackfillEngine;
 use crate::core::decoding::Decoder;
 use crate::core::execution::fetcher::Fetcher;
 use crate::storage::StorageBackend;
+use crate::streams::slots::SlotTracker;
 use crate::types::backfill_traits::{
     BackfillContext, BackfillHandlerRegistry, BackfillProgress, BackfillRange, BackfillStrategy,
     BackfillTrigger, FinalizedBlockTracker, ReorgHandler,
 };
+use crate::utils::backoff::poll_jitter;
 use crate::utils::error::Result;
 use crate::utils::logging::{log, log_error, LogLevel};
 use std::sync::Arc;
@@ -39,6 +41,7 @@ pub struct BackfillManager {
     decoder_registry: Arc<crate::core::registry::DecoderRegistry>,
     log_decoder_registry: Arc<crate::core::registry::logs::LogDecoderRegistry>,
     account_decoder_registry: Arc<crate::core::registry::account::AccountDecoderRegistry>,
+    slot_tracker: Option<SlotTracker>,
 }
 
 impl BackfillManager {
@@ -75,9 +78,17 @@ impl BackfillManager {
             decoder_registry,
             log_decoder_registry,
             account_decoder_registry,
+            slot_tracker: None,
         }
     }
 
+    /// Exposes the confirmed and processed slots of `tracker` to the trigger.
+    #[must_use]
+    pub fn with_slot_tracker(mut self, tracker: SlotTracker) -> Self {
+        self.slot_tracker = Some(tracker);
+        self
+    }
+
     /// Runs the backfill manager loop.
     ///
     /// This method runs indefinitely, periodically checking for backfill ranges
@@ -87,7 +98,7 @@ impl BackfillManager {
 
         // Initialize schemas for all backfill handlers
         self.backfill_handlers
-            .initialize_schemas(self.storage.pool())
+            .initialize_schemas(self.storage.require_pool()?)
             .await?;
 
         let poll_interval = Duration::from_secs(self.config.backfill.poll_interval_secs);
@@ -103,6 +114,11 @@ impl BackfillManager {
                     break;
                 }
                 _ = interval_timer.tick() => {
+                    // Stagger wave starts across instances sharing a provider
+                    tokio::select! {
+                        _ = self.cancellation_token.cancelled() => continue,
+                        _ = tokio::time::sleep(poll_jitter(self.config.poll_jitter_ms)) => {}
+                    }
                     match self.check_and_process_range().await {
                         Ok(processed) => {
                             if processed {
@@ -124,6 +140,25 @@ impl BackfillManager {
 
     /// Checks for a backfill range and processes it if available.
     async fn check_and_process_range(&self) -> Result<bool> {
+        // Finish a range an earlier run stopped partway through first
+        let pending = self
+            .storage
+            .load_backfill_ranges()
+            .await?
+            .into_iter()
+            .find(|progress| !progress.is_complete());
+        if let Some(pending) = pending {
+            log(
+                LogLevel::Info,
+                &format!(
+                    "BackfillManager: Resuming range [{}, {}] from slot {}",
+                    pending.range.start_slot, pending.range.end_slot, pending.next_slot
+                ),
+            );
+            self.process_range(pending.range).await?;
+            return Ok(true);
+        }
+
         // Build context for trigger decision
         let latest_finalized = self
             .finalized_tracker
@@ -137,6 +172,8 @@ impl BackfillManager {
 
         let ctx = BackfillContext {
             latest_finalized_slot: latest_finalized,
+            latest_confirmed_slot: self.slot_tracker.as_ref().and_then(SlotTracker::confirmed),
+            latest_processed_slot: self.slot_tracker.as_ref().and_then(SlotTracker::processed),
             last_backfilled_slot: last_backfilled,
             max_depth: self.config.backfill.max_depth,
             desired_lag_slots: self.config.backfill.desired_lag_slots,
@@ -196,10 +233,14 @@ impl BackfillManager {
 
         // Run the engine for this range
         engine.start_range(range).await?;
+        if self.cancellation_token.is_cancelled() {
+            // Stopped partway; the range resumes on the next run
+            return Ok(());
+        }
 
         // Notify handlers that range is complete
         self.backfill_handlers
-            .notify_range_complete(&range, self.storage.pool())
+            .notify_range_complete(&range, self.storage.require_pool()?)
             .await?;
 
         Ok(())
@@ -1,3 +1,7 @@
+pub mod bandwidth;
 pub mod defaults;
 pub mod engine;
+pub mod gaps;
 pub mod manager;
+pub mod status;
+pub mod time;
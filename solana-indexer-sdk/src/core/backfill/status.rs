@@ -0,0 +1,184 @@
+//! Progress reporting for long backfills.
+//!
+//! The backfill engine records every range it works on in
+//! `_solana_indexer_sdk_backfill_ranges`: the next slot to backfill, the
+//! transactions processed so far, and when the current run over the range
+//! started. `SolanaIndexer::backfill_status` reads the table back as a
+//! `BackfillStatus`, with throughput and an ETA, so operators can follow a
+//! multi-day backfill without reading logs:
+//!
+//! ```no_run
+//! # async fn example(indexer: &solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! let status = indexer.backfill_status().await?;
+//! println!(
+//!     "{} ranges done, {} slots left, ETA {:?}",
+//!     status.completed.len(),
+//!     status.remaining_slots,
+//!     status.eta
+//! );
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Within a slot the engine checkpoints after every batch of
+//! `BackfillStrategy::batch_size` transactions, recording the last signature
+//! handled, so a restarted indexer resumes a range mid-slot instead of
+//! running its handlers over the same transactions again.
+//!
+//! Throughput is measured over the latest run of each range only, so time
+//! the indexer spent stopped does not count against it.
+
+use crate::types::backfill_traits::BackfillRange;
+use std::time::Duration;
+
+/// Recorded progress of one backfill range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeProgress {
+    /// Slots of the range
+    pub range: BackfillRange,
+    /// First slot not backfilled yet; past `range.end_slot` once complete
+    pub next_slot: u64,
+    /// Last signature handled in `next_slot`, if a run stopped partway
+    /// through it
+    pub cursor: Option<String>,
+    /// Transactions processed in the range
+    pub transactions: u64,
+    /// `next_slot` when the latest run over the range started
+    pub run_start_slot: u64,
+    /// `transactions` when the latest run over the range started
+    pub run_start_transactions: u64,
+    /// When the latest run started, in Unix milliseconds
+    pub run_started_at: i64,
+    /// When the range last advanced, in Unix milliseconds
+    pub updated_at: i64,
+}
+
+impl RangeProgress {
+    /// Whether every slot of the range was backfilled.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.next_slot > self.range.end_slot
+    }
+
+    /// Slots of the range not backfilled yet.
+    #[must_use]
+    pub fn remaining_slots(&self) -> u64 {
+        BackfillRange::new(
+            self.next_slot.max(self.range.start_slot),
+            self.range.end_slot,
+        )
+        .len()
+    }
+}
+
+/// Snapshot of backfill progress, returned by `SolanaIndexer::backfill_status`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackfillStatus {
+    /// Ranges fully backfilled, lowest first
+    pub completed: Vec<RangeProgress>,
+    /// Ranges started but not finished, lowest first
+    pub pending: Vec<RangeProgress>,
+    /// Transactions processed across every range
+    pub transactions: u64,
+    /// Slots of pending ranges not backfilled yet
+    pub remaining_slots: u64,
+    /// Slots backfilled per second over the latest runs
+    pub slots_per_second: Option<f64>,
+    /// Transactions processed per second over the latest runs
+    pub transactions_per_second: Option<f64>,
+    /// Time left for the pending ranges at the current throughput
+    pub eta: Option<Duration>,
+}
+
+impl BackfillStatus {
+    /// Summarizes recorded range progress.
+    #[must_use]
+    pub fn from_ranges(mut ranges: Vec<RangeProgress>) -> Self {
+        ranges
+            .sort_unstable_by_key(|progress| (progress.range.start_slot, progress.range.end_slot));
+
+        let mut slots = 0;
+        let mut transactions = 0;
+        let mut millis = 0;
+        for progress in &ranges {
+            slots += progress.next_slot.saturating_sub(progress.run_start_slot);
+            transactions += progress
+                .transactions
+                .saturating_sub(progress.run_start_transactions);
+            millis += progress
+                .updated_at
+                .saturating_sub(progress.run_started_at)
+                .max(0);
+        }
+        let seconds = millis as f64 / 1000.0;
+        let rate = |count: u64| (seconds > 0.0).then(|| count as f64 / seconds);
+        let slots_per_second = rate(slots);
+
+        let (completed, pending): (Vec<_>, Vec<_>) =
+            ranges.into_iter().partition(RangeProgress::is_complete);
+        let remaining_slots = pending.iter().map(RangeProgress::remaining_slots).sum();
+        let eta = if remaining_slots == 0 {
+            Some(Duration::ZERO)
+        } else {
+            slots_per_second
+                .filter(|rate| *rate > 0.0)
+                .map(|rate| Duration::from_secs_f64(remaining_slots as f64 / rate))
+        };
+
+        Self {
+            transactions: completed
+                .iter()
+                .chain(&pending)
+                .map(|progress| progress.transactions)
+                .sum(),
+            completed,
+            pending,
+            remaining_slots,
+            slots_per_second,
+            transactions_per_second: rate(transactions),
+            eta,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(range: (u64, u64), next_slot: u64, run: (u64, i64, i64)) -> RangeProgress {
+        RangeProgress {
+            range: BackfillRange::new(range.0, range.1),
+            next_slot,
+            cursor: None,
+            transactions: (next_slot - range.0) * 2,
+            run_start_slot: run.0,
+            run_start_transactions: (run.0 - range.0) * 2,
+            run_started_at: run.1,
+            updated_at: run.2,
+        }
+    }
+
+    #[test]
+    fn test_summarizes_latest_runs() {
+        let status = BackfillStatus::from_ranges(vec![
+            // Resumed at slot 150 and ran 100 slots in 10 seconds
+            progress((100, 399), 250, (150, 50_000, 60_000)),
+            // Done in one run of 100 slots in 10 seconds
+            progress((0, 99), 100, (0, 0, 10_000)),
+        ]);
+
+        assert_eq!(status.completed.len(), 1);
+        assert_eq!(status.completed[0].range, BackfillRange::new(0, 99));
+        assert_eq!(status.pending.len(), 1);
+        assert_eq!(status.pending[0].remaining_slots(), 150);
+        assert_eq!(status.transactions, 500);
+        assert_eq!(status.remaining_slots, 150);
+        assert_eq!(status.slots_per_second, Some(10.0));
+        assert_eq!(status.transactions_per_second, Some(20.0));
+        assert_eq!(status.eta, Some(Duration::from_secs(15)));
+
+        let idle = BackfillStatus::from_ranges(Vec::new());
+        assert_eq!(idle.slots_per_second, None);
+        assert_eq!(idle.eta, Some(Duration::ZERO));
+    }
+}
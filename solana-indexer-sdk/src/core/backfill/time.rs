@@ -0,0 +1,93 @@
+//! Resolution of a backfill start time to a slot.
+//!
+//! `BackfillConfig::start_time` lets a backfill start from a date instead of
+//! a slot number. The engine turns it into the first block produced at or
+//! after that time by binary search between the node's first available
+//! block and the latest finalized slot, asking `getBlockTime` for the first
+//! block at or after each probed slot, so skipped slots do not stop the
+//! search. Block times only estimate when a block was produced, so the
+//! resolved slot can be a few slots off the exact instant.
+
+use crate::core::execution::fetcher::Fetcher;
+use crate::utils::error::Result;
+use chrono::{DateTime, Utc};
+use std::future::Future;
+
+/// Returns the first finalized block produced at or after `time`, or the
+/// slot after the latest finalized one if no block has been produced since.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Rpc` if a slot or block time lookup fails.
+pub async fn slot_at_time(fetcher: &Fetcher, time: DateTime<Utc>) -> Result<u64> {
+    let first = fetcher.get_first_available_block().await?;
+    let last = fetcher.get_latest_finalized_slot().await?;
+    let found = search(time.timestamp(), first, last, |slot| async move {
+        match fetcher.first_block_from(slot).await? {
+            Some(block) => Ok(Some((block, fetcher.get_block_time(block).await?))),
+            None => Ok(None),
+        }
+    })
+    .await?;
+    Ok(found.unwrap_or(last + 1))
+}
+
+/// Returns the lowest block in `[low, high]` whose time is at least
+/// `timestamp`.
+///
+/// `block_from(slot)` returns the first block at or after `slot` and its
+/// time. Block times never decrease with the slot, so neither does its
+/// result, which makes it searchable.
+async fn search<F, Fut>(
+    timestamp: i64,
+    mut low: u64,
+    mut high: u64,
+    block_from: F,
+) -> Result<Option<u64>>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Result<Option<(u64, i64)>>>,
+{
+    let mut found = None;
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        match block_from(mid).await? {
+            Some((block, time)) if block <= high && time < timestamp => low = block + 1,
+            found_block => {
+                // No block in [mid, high] is early enough to rule it out
+                if let Some((block, _)) = found_block.filter(|(block, _)| *block <= high) {
+                    found = Some(block);
+                }
+                match mid.checked_sub(1) {
+                    Some(below) => high = below,
+                    None => break,
+                }
+            }
+        }
+    }
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_search_skips_empty_slots() -> Result<()> {
+        // Blocks every third slot, ten seconds apart, from slot 0
+        let chain: Vec<(u64, i64)> = (0..100).map(|n| (n * 3, 1_000 + n as i64 * 10)).collect();
+        let block_from = |slot: u64| {
+            let block = chain.iter().copied().find(|(block, _)| *block >= slot);
+            async move { Ok(block) }
+        };
+
+        // Exact block time, between two blocks, before the first and after the last
+        assert_eq!(search(1_500, 0, 299, block_from).await?, Some(150));
+        assert_eq!(search(1_505, 0, 299, block_from).await?, Some(153));
+        assert_eq!(search(0, 0, 299, block_from).await?, Some(0));
+        assert_eq!(search(5_000, 0, 299, block_from).await?, None);
+        // Blocks after the searched range do not count
+        assert_eq!(search(1_500, 0, 120, block_from).await?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! Built-in decoder for Anchor events.
+//!
+//! Anchor programs emit events two ways, both carrying the event's 8-byte
+//! discriminator followed by its Borsh-serialized fields:
+//!
+//! - `emit!` writes a `Program data: <base64>` log line.
+//! - `emit_cpi!` invokes the program itself, with instruction data prefixed
+//!   by Anchor's event instruction tag, so the event survives log
+//!   truncation.
+//!
+//! `AnchorEventDecoder` reads both. Register it with
+//! `SolanaIndexer::register_anchor_events` and add a handler per event type;
+//! the handlers receive the same data whichever way the event was emitted.
+
+use crate::types::events::{EventDiscriminator, EventType, ParsedEvent};
+use crate::types::metadata::InstructionContext;
+use crate::types::traits::{DynamicInstructionDecoder, DynamicLogDecoder};
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use std::collections::HashSet;
+
+/// Decodes the Anchor events of one program from its logs and self-CPIs.
+///
+/// Only events whose discriminator was added with `with_event` are
+/// decoded, so events without a handler do not fail their transaction.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::{AnchorEventDecoder, SolanaIndexer, TransferEvent};
+/// # fn example(indexer: &mut SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+/// let decoder = AnchorEventDecoder::new().with_event::<TransferEvent>();
+/// indexer.register_anchor_events("MyProgram111111111111111111111111111111111", decoder)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnchorEventDecoder {
+    discriminators: HashSet<[u8; 8]>,
+}
+
+impl AnchorEventDecoder {
+    /// Creates a decoder accepting no events yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes `E` events.
+    #[must_use]
+    pub fn with_event<E: EventDiscriminator>(self) -> Self {
+        self.with_discriminator(E::discriminator())
+    }
+
+    /// Decodes events with `discriminator`.
+    #[must_use]
+    pub fn with_discriminator(mut self, discriminator: [u8; 8]) -> Self {
+        self.discriminators.insert(discriminator);
+        self
+    }
+
+    /// Returns the discriminators of the events decoded.
+    pub fn discriminators(&self) -> impl Iterator<Item = &[u8; 8]> {
+        self.discriminators.iter()
+    }
+
+    /// Splits event bytes into a known discriminator and the event data.
+    fn split(&self, bytes: &[u8]) -> Option<([u8; 8], Vec<u8>)> {
+        let discriminator: [u8; 8] = bytes.get(..8)?.try_into().ok()?;
+        self.discriminators
+            .contains(&discriminator)
+            .then(|| (discriminator, bytes[8..].to_vec()))
+    }
+}
+
+impl DynamicLogDecoder for AnchorEventDecoder {
+    fn decode_log_dynamic(&self, event: &ParsedEvent) -> Option<([u8; 8], Vec<u8>)> {
+        if event.event_type != EventType::ProgramData {
+            return None;
+        }
+        let bytes = BASE64.decode(event.data.as_deref()?).ok()?;
+        self.split(&bytes)
+    }
+}
+
+impl DynamicInstructionDecoder for AnchorEventDecoder {
+    fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) = instruction
+        else {
+            return None;
+        };
+        let bytes = bs58::decode(&partial.data).into_vec().ok()?;
+        self.split(bytes.strip_prefix(&EVENT_IX_TAG_LE)?)
+    }
+
+    fn decode_dynamic_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        // `emit_cpi!` events are always inner instructions
+        if !context.is_inner() {
+            return None;
+        }
+        self.decode_dynamic(instruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_transaction_status::UiPartiallyDecodedInstruction;
+
+    fn event_bytes() -> Vec<u8> {
+        let event = TransferEvent {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 7,
+        };
+        let mut bytes = TransferEvent::discriminator().to_vec();
+        bytes.extend(borsh::to_vec(&event).unwrap());
+        bytes
+    }
+
+    fn self_cpi(data: &[u8]) -> UiInstruction {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: Pubkey::new_unique().to_string(),
+                accounts: Vec::new(),
+                data: bs58::encode(data).into_string(),
+                stack_height: Some(2),
+            },
+        ))
+    }
+
+    #[test]
+    fn test_decodes_program_data_logs() {
+        let decoder = AnchorEventDecoder::new().with_event::<TransferEvent>();
+        let log = |data: &[u8]| ParsedEvent {
+            event_type: EventType::ProgramData,
+            program_id: None,
+            data: Some(BASE64.encode(data)),
+        };
+
+        let bytes = event_bytes();
+        let (discriminator, data) = decoder.decode_log_dynamic(&log(&bytes)).unwrap();
+        assert_eq!(discriminator, TransferEvent::discriminator());
+        assert_eq!(data, bytes[8..]);
+
+        assert!(AnchorEventDecoder::new()
+            .decode_log_dynamic(&log(&bytes))
+            .is_none());
+        assert!(decoder.decode_log_dynamic(&log(&bytes[..4])).is_none());
+        let mut not_data = log(&bytes);
+        not_data.event_type = EventType::ProgramLog;
+        assert!(decoder.decode_log_dynamic(&not_data).is_none());
+    }
+
+    #[test]
+    fn test_decodes_self_cpi_events() {
+        let decoder = AnchorEventDecoder::new().with_event::<TransferEvent>();
+        let bytes = event_bytes();
+        let mut data = EVENT_IX_TAG_LE.to_vec();
+        data.extend(&bytes);
+        let inner = InstructionContext {
+            index: 0,
+            parent_index: Some(0),
+            stack_height: Some(2),
+            ..Default::default()
+        };
+
+        let (discriminator, decoded) = decoder
+            .decode_dynamic_in_context(&self_cpi(&data), &inner)
+            .unwrap();
+        assert_eq!(discriminator, TransferEvent::discriminator());
+        assert_eq!(decoded, bytes[8..]);
+
+        // Untagged data and top-level calls are ordinary instructions
+        assert!(decoder
+            .decode_dynamic_in_context(&self_cpi(&bytes), &inner)
+            .is_none());
+        assert!(decoder
+            .decode_dynamic_in_context(&self_cpi(&data), &InstructionContext::default())
+            .is_none());
+    }
+}
@@ -0,0 +1,299 @@
+//! Field-level change events for decoded accounts.
+//!
+//! `SolanaIndexer::register_account_diffs` decodes accounts with a regular
+//! account decoder and compares each decoded state with the previous one
+//! seen for the same account. Every field whose value changed is delivered
+//! as an `AccountFieldChanged` event, so a rule such as "notify when a
+//! pool's fee changes" is a handler that checks `field`:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{AccountFieldChanged, EventHandler, Result, TxMetadata};
+//! # use async_trait::async_trait;
+//! # use sqlx::PgPool;
+//! struct FeeAlert;
+//!
+//! #[async_trait]
+//! impl EventHandler<AccountFieldChanged> for FeeAlert {
+//!     async fn handle(&self, change: AccountFieldChanged, _context: &TxMetadata, _db: &PgPool) -> Result<()> {
+//!         if change.field == "fees.trade_fee_bps" {
+//!             println!("{}: fee {} -> {}", change.account, change.old, change.new);
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! Decoded types list their fields through `AccountFields`. Types generated
+//! from an IDL with `solana_indexer_idl::generate_sdk_types` implement it,
+//! nested IDL types included, with each value rendered by its `Debug`
+//! output (public keys in base58).
+//!
+//! The first state seen of an account is only remembered; changes are
+//! reported from the second one on. Previous states are kept in memory, so
+//! a restarted indexer starts over from the next state it sees.
+
+use crate::types::events::{calculate_discriminator, EventDiscriminator, IndexerEvent};
+use crate::types::traits::{AccountDecoder, DynamicAccountDecoder};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+use std::sync::Mutex;
+
+/// A decoded type whose fields can be compared one by one.
+pub trait AccountFields {
+    /// Pushes the path and rendered value of every field, descending into
+    /// nested structures. Paths are joined with `.` and start with `prefix`.
+    fn fields(&self, prefix: &str, out: &mut Vec<(String, String)>);
+}
+
+/// Returns the path of field `name` under `prefix`.
+#[must_use]
+pub fn field_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// A field of an account that changed between two consecutive states.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AccountFieldChanged {
+    /// The account, in base58
+    pub account: String,
+    /// Path of the field, e.g. `fees.trade_fee_bps`
+    pub field: String,
+    /// Value in the previous state
+    pub old: String,
+    /// Value in the new state
+    pub new: String,
+}
+
+impl EventDiscriminator for AccountFieldChanged {
+    fn discriminator() -> [u8; 8] {
+        calculate_discriminator("AccountFieldChanged")
+    }
+}
+
+impl IndexerEvent for AccountFieldChanged {
+    fn event_name() -> &'static str {
+        "AccountFieldChanged"
+    }
+}
+
+/// Returns the fields whose value differs between `old` and `new`, as
+/// `(path, old, new)`, in the order of `new`. Fields present on one side
+/// only are reported with an empty value on the other.
+#[must_use]
+pub fn diff_fields(
+    old: &[(String, String)],
+    new: &[(String, String)],
+) -> Vec<(String, String, String)> {
+    let before: HashMap<&str, &str> = old.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+    let after: HashSet<&str> = new.iter().map(|(k, _)| k.as_str()).collect();
+    let mut changes: Vec<_> = new
+        .iter()
+        .filter_map(|(path, value)| {
+            let previous = before.get(path.as_str()).copied().unwrap_or_default();
+            (previous != value).then(|| (path.clone(), previous.to_string(), value.clone()))
+        })
+        .collect();
+    changes.extend(
+        old.iter()
+            .filter(|(path, _)| !after.contains(path.as_str()))
+            .map(|(path, value)| (path.clone(), value.clone(), String::new())),
+    );
+    changes
+}
+
+/// Decodes accounts with `D` and emits an `AccountFieldChanged` per field
+/// that changed since the account's previous state.
+pub(crate) struct AccountDiffDecoder<D, T> {
+    decoder: D,
+    watched: Option<HashSet<String>>,
+    previous: Mutex<HashMap<Pubkey, Vec<(String, String)>>>,
+    _state: PhantomData<fn() -> T>,
+}
+
+impl<D, T> AccountDiffDecoder<D, T> {
+    /// Reports changes of the `watched` fields only, or of every field.
+    pub(crate) fn new(decoder: D, watched: Option<HashSet<String>>) -> Self {
+        Self {
+            decoder,
+            watched,
+            previous: Mutex::new(HashMap::new()),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<D, T> AccountDiffDecoder<D, T>
+where
+    D: AccountDecoder<T>,
+    T: AccountFields,
+{
+    /// Decodes `account` and returns its changed fields.
+    fn changes(&self, pubkey: &Pubkey, account: &Account) -> Vec<AccountFieldChanged> {
+        let Some(state) = self.decoder.decode(pubkey, account) else {
+            return Vec::new();
+        };
+        let mut fields = Vec::new();
+        state.fields("", &mut fields);
+        let Ok(mut previous) = self.previous.lock() else {
+            return Vec::new();
+        };
+        let Some(old) = previous.insert(*pubkey, fields.clone()) else {
+            return Vec::new();
+        };
+        diff_fields(&old, &fields)
+            .into_iter()
+            .filter(|(path, _, _)| self.watched.as_ref().map_or(true, |w| w.contains(path)))
+            .map(|(field, old, new)| AccountFieldChanged {
+                account: pubkey.to_string(),
+                field,
+                old,
+                new,
+            })
+            .collect()
+    }
+}
+
+impl<D, T> DynamicAccountDecoder for AccountDiffDecoder<D, T>
+where
+    D: AccountDecoder<T>,
+    T: AccountFields,
+{
+    fn decode_account_dynamic(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        self.decode_account_dynamic_many(pubkey, account)
+            .into_iter()
+            .next()
+    }
+
+    fn decode_account_dynamic_many(
+        &self,
+        pubkey: &Pubkey,
+        account: &Account,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.changes(pubkey, account)
+            .iter()
+            .filter_map(|change| {
+                Some((
+                    AccountFieldChanged::discriminator(),
+                    borsh::to_vec(change).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        Some(AccountFieldChanged::discriminator())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fees {
+        trade_fee_bps: u16,
+    }
+
+    struct Pool {
+        authority: Pubkey,
+        fees: Fees,
+    }
+
+    impl AccountFields for Fees {
+        fn fields(&self, prefix: &str, out: &mut Vec<(String, String)>) {
+            out.push((
+                field_path(prefix, "trade_fee_bps"),
+                format!("{:?}", self.trade_fee_bps),
+            ));
+        }
+    }
+
+    impl AccountFields for Pool {
+        fn fields(&self, prefix: &str, out: &mut Vec<(String, String)>) {
+            out.push((
+                field_path(prefix, "authority"),
+                format!("{:?}", self.authority),
+            ));
+            self.fees.fields(&field_path(prefix, "fees"), out);
+        }
+    }
+
+    /// Reads the fee from the account's lamports.
+    struct PoolDecoder(Pubkey);
+
+    impl AccountDecoder<Pool> for PoolDecoder {
+        fn decode(&self, _: &Pubkey, account: &Account) -> Option<Pool> {
+            Some(Pool {
+                authority: self.0,
+                fees: Fees {
+                    trade_fee_bps: u16::try_from(account.lamports).ok()?,
+                },
+            })
+        }
+    }
+
+    fn pool(lamports: u64) -> Account {
+        Account {
+            lamports,
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn test_changes_are_reported_from_the_second_state() {
+        let decoder = AccountDiffDecoder::new(PoolDecoder(Pubkey::new_unique()), None);
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert!(decoder
+            .decode_account_dynamic_many(&a, &pool(25))
+            .is_empty());
+        assert!(decoder.changes(&a, &pool(25)).is_empty());
+        assert!(decoder.changes(&b, &pool(30)).is_empty());
+
+        let changes = decoder.changes(&a, &pool(30));
+        assert_eq!(
+            changes,
+            [AccountFieldChanged {
+                account: a.to_string(),
+                field: "fees.trade_fee_bps".to_string(),
+                old: "25".to_string(),
+                new: "30".to_string(),
+            }]
+        );
+        let (discriminator, data) = decoder.decode_account_dynamic(&a, &pool(5)).unwrap();
+        assert_eq!(discriminator, AccountFieldChanged::discriminator());
+        assert_eq!(
+            AccountFieldChanged::try_from_slice(&data).unwrap().old,
+            "30"
+        );
+    }
+
+    #[test]
+    fn test_watched_fields_and_removed_fields() {
+        let watched = HashSet::from(["authority".to_string()]);
+        let decoder = AccountDiffDecoder::new(PoolDecoder(Pubkey::new_unique()), Some(watched));
+        let account = Pubkey::new_unique();
+        decoder.changes(&account, &pool(25));
+        assert!(decoder.changes(&account, &pool(30)).is_empty());
+
+        let old = vec![
+            ("a".to_string(), "1".to_string()),
+            ("b".to_string(), "2".to_string()),
+        ];
+        let new = vec![("a".to_string(), "1".to_string())];
+        assert_eq!(
+            diff_fields(&old, &new),
+            [("b".to_string(), "2".to_string(), String::new())]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Discriminator schemes and routing of instructions by discriminator.
+//!
+//! Programs tag their instruction data in different ways. Anchor prefixes
+//! instruction arguments with the first 8 bytes of `sha256("global:<name>")`
+//! and events with those of `sha256("event:<Name>")`; native programs often
+//! use a single leading tag byte. A `DiscriminatorStrategy` describes one
+//! such scheme, and an `InstructionRouter` uses it to turn the instructions
+//! of a program into typed events without a hand-written decoder:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{
+//! #     DiscriminatorStrategy, EventDiscriminator, InstructionRouter, SolanaIndexer,
+//! # };
+//! # #[derive(borsh::BorshSerialize, borsh::BorshDeserialize)]
+//! # struct SwapArgs { amount_in: u64, minimum_out: u64 }
+//! # impl EventDiscriminator for SwapArgs {
+//! #     fn discriminator() -> [u8; 8] { *b"swapargs" }
+//! # }
+//! # fn example(indexer: &mut SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! // The arguments of every `swap` instruction reach the `SwapArgs` handlers
+//! let router = InstructionRouter::new(DiscriminatorStrategy::AnchorInstruction)
+//!     .with_instruction::<SwapArgs>("swap")?;
+//! indexer.register_instruction_router("MyAmm11111111111111111111111111111111111111", router)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::events::EventDiscriminator;
+use crate::types::traits::DynamicInstructionDecoder;
+use crate::utils::error::{Result, SolanaIndexerError};
+use borsh::BorshDeserialize;
+use sha2::{Digest, Sha256};
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Splits data into a discriminator and the offset of the payload after it.
+pub type DiscriminatorFn = dyn Fn(&[u8]) -> Option<([u8; 8], usize)> + Send + Sync;
+
+/// How a program tags its instruction data.
+#[derive(Clone)]
+pub enum DiscriminatorStrategy {
+    /// Anchor events: the first 8 bytes of `sha256("event:<Name>")`
+    AnchorEvent,
+    /// Anchor instructions: the first 8 bytes of `sha256("global:<name>")`
+    AnchorInstruction,
+    /// The first `n` bytes of the data (at most 8), zero-padded to 8
+    Prefix(usize),
+    /// A closure returning the discriminator of some data and the offset
+    /// its payload starts at, or `None` if the data carries none
+    Custom(Arc<DiscriminatorFn>),
+}
+
+impl fmt::Debug for DiscriminatorStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AnchorEvent => f.write_str("AnchorEvent"),
+            Self::AnchorInstruction => f.write_str("AnchorInstruction"),
+            Self::Prefix(n) => f.debug_tuple("Prefix").field(n).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl DiscriminatorStrategy {
+    /// Uses `split` to find the discriminator and payload of data.
+    #[must_use]
+    pub fn custom(
+        split: impl Fn(&[u8]) -> Option<([u8; 8], usize)> + Send + Sync + 'static,
+    ) -> Self {
+        Self::Custom(Arc::new(split))
+    }
+
+    /// Returns the discriminator of the event or instruction called `name`,
+    /// or `None` if the strategy does not derive discriminators from names.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_indexer_sdk::{calculate_discriminator, DiscriminatorStrategy};
+    ///
+    /// assert_eq!(
+    ///     DiscriminatorStrategy::AnchorEvent.for_name("TransferEvent"),
+    ///     Some(calculate_discriminator("TransferEvent"))
+    /// );
+    /// assert_eq!(DiscriminatorStrategy::Prefix(1).for_name("transfer"), None);
+    /// ```
+    #[must_use]
+    pub fn for_name(&self, name: &str) -> Option<[u8; 8]> {
+        let namespace = match self {
+            Self::AnchorEvent => "event",
+            Self::AnchorInstruction => "global",
+            Self::Prefix(_) | Self::Custom(_) => return None,
+        };
+        let hash = Sha256::digest(format!("{namespace}:{name}").as_bytes());
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash[..8]);
+        Some(discriminator)
+    }
+
+    /// Splits `data` into its discriminator and payload.
+    #[must_use]
+    pub fn split<'a>(&self, data: &'a [u8]) -> Option<([u8; 8], &'a [u8])> {
+        let length = match self {
+            Self::AnchorEvent | Self::AnchorInstruction => 8,
+            Self::Prefix(n) => (*n).min(8),
+            Self::Custom(split) => {
+                let (discriminator, offset) = split(data)?;
+                return Some((discriminator, data.get(offset..)?));
+            }
+        };
+        let prefix = data.get(..length)?;
+        Some((pad(prefix), &data[length..]))
+    }
+}
+
+/// Zero-pads up to 8 bytes into a discriminator.
+fn pad(bytes: &[u8]) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    let length = bytes.len().min(8);
+    discriminator[..length].copy_from_slice(&bytes[..length]);
+    discriminator
+}
+
+/// Checks that a payload decodes as the routed type.
+type PayloadCheck = fn(&[u8]) -> bool;
+
+/// Decodes the instructions of one program into typed events, picked by
+/// their discriminator under a `DiscriminatorStrategy`.
+///
+/// The payload after the discriminator must be the Borsh encoding of the
+/// routed type, as Anchor instruction arguments are. It is handed to the
+/// handlers of that type unchanged. Instructions with an unknown
+/// discriminator, or a payload that does not decode, are not matched.
+#[derive(Debug, Clone)]
+pub struct InstructionRouter {
+    strategy: DiscriminatorStrategy,
+    routes: HashMap<[u8; 8], ([u8; 8], PayloadCheck)>,
+}
+
+impl InstructionRouter {
+    /// Creates a router with no routes yet.
+    #[must_use]
+    pub fn new(strategy: DiscriminatorStrategy) -> Self {
+        Self {
+            strategy,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Routes the instruction called `name` to `E` events.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the strategy does not
+    /// derive discriminators from names; use `with_discriminator` instead.
+    pub fn with_instruction<E>(self, name: &str) -> Result<Self>
+    where
+        E: EventDiscriminator + BorshDeserialize,
+    {
+        let discriminator = self.strategy.for_name(name).ok_or_else(|| {
+            SolanaIndexerError::Config(format!(
+                "{:?} discriminators cannot be derived from the name {name:?}",
+                self.strategy
+            ))
+        })?;
+        Ok(self.with_discriminator::<E>(&discriminator))
+    }
+
+    /// Routes instructions whose discriminator is `discriminator` to `E`
+    /// events. Discriminators shorter than 8 bytes, such as a one-byte tag
+    /// under `Prefix(1)`, are zero-padded.
+    #[must_use]
+    pub fn with_discriminator<E>(mut self, discriminator: &[u8]) -> Self
+    where
+        E: EventDiscriminator + BorshDeserialize,
+    {
+        let check: PayloadCheck = |payload| E::try_from_slice(payload).is_ok();
+        self.routes
+            .insert(pad(discriminator), (E::discriminator(), check));
+        self
+    }
+
+    /// Returns the discriminators of the events produced.
+    pub fn event_discriminators(&self) -> impl Iterator<Item = &[u8; 8]> {
+        self.routes.values().map(|(discriminator, _)| discriminator)
+    }
+
+    /// Routes raw instruction data.
+    fn route(&self, data: &[u8]) -> Option<([u8; 8], Vec<u8>)> {
+        let (discriminator, payload) = self.strategy.split(data)?;
+        let (event, check) = self.routes.get(&discriminator)?;
+        check(payload).then(|| (*event, payload.to_vec()))
+    }
+}
+
+impl DynamicInstructionDecoder for InstructionRouter {
+    fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        let data = match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => &partial.data,
+            UiInstruction::Compiled(compiled) => &compiled.data,
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return None,
+        };
+        self.route(&bs58::decode(data).into_vec().ok()?)
+    }
+
+    fn instruction_prefixes(&self) -> Vec<Vec<u8>> {
+        // Routes are keyed by zero-padded discriminators
+        let length = match self.strategy {
+            DiscriminatorStrategy::AnchorEvent | DiscriminatorStrategy::AnchorInstruction => 8,
+            DiscriminatorStrategy::Prefix(n) => n.min(8),
+            DiscriminatorStrategy::Custom(_) => return Vec::new(),
+        };
+        self.routes
+            .keys()
+            .map(|discriminator| discriminator[..length].to_vec())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::calculate_discriminator;
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize, BorshDeserialize)]
+    struct Swap {
+        amount: u64,
+    }
+
+    impl EventDiscriminator for Swap {
+        fn discriminator() -> [u8; 8] {
+            *b"swap_evt"
+        }
+    }
+
+    fn call(tag: &[u8], amount: u64) -> Vec<u8> {
+        [tag, &amount.to_le_bytes()].concat()
+    }
+
+    #[test]
+    fn test_names_and_splits() {
+        let event = DiscriminatorStrategy::AnchorEvent;
+        assert_eq!(
+            event.for_name("TransferEvent"),
+            Some(calculate_discriminator("TransferEvent"))
+        );
+        // Anchor's sighash of `initialize`
+        assert_eq!(
+            DiscriminatorStrategy::AnchorInstruction.for_name("initialize"),
+            Some([175, 175, 109, 31, 13, 152, 155, 237])
+        );
+
+        let data = [9, 1, 2, 3];
+        assert_eq!(
+            DiscriminatorStrategy::Prefix(1).split(&data),
+            Some(([9, 0, 0, 0, 0, 0, 0, 0], &data[1..]))
+        );
+        assert_eq!(event.split(&data), None);
+
+        let custom = DiscriminatorStrategy::custom(|data| Some((pad(data.get(1..3)?), 3)));
+        assert_eq!(
+            custom.split(&data),
+            Some(([1, 2, 0, 0, 0, 0, 0, 0], &data[3..]))
+        );
+        assert!(custom.for_name("swap").is_none());
+    }
+
+    #[test]
+    fn test_router_routes_by_discriminator() {
+        let router = InstructionRouter::new(DiscriminatorStrategy::AnchorInstruction)
+            .with_instruction::<Swap>("swap")
+            .unwrap();
+        let sighash = DiscriminatorStrategy::AnchorInstruction
+            .for_name("swap")
+            .unwrap();
+
+        assert_eq!(
+            router.route(&call(&sighash, 7)),
+            Some((Swap::discriminator(), 7u64.to_le_bytes().to_vec()))
+        );
+        // Unknown instruction, and a payload that is not a `Swap`
+        assert_eq!(router.route(&call(&[0; 8], 7)), None);
+        assert_eq!(router.route(&sighash), None);
+
+        let tagged = InstructionRouter::new(DiscriminatorStrategy::Prefix(1))
+            .with_discriminator::<Swap>(&[3]);
+        assert!(tagged.route(&call(&[3], 7)).is_some());
+        assert!(InstructionRouter::new(DiscriminatorStrategy::Prefix(1))
+            .with_instruction::<Swap>("swap")
+            .is_err());
+    }
+}
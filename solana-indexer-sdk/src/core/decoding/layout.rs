@@ -0,0 +1,238 @@
+//! Account decoding for non-Borsh layouts.
+//!
+//! Programs outside Anchor often store accounts as bincode or as raw
+//! `#[repr(C)]` structs read in place (zero-copy), typically behind a
+//! header such as Serum's `serum` padding or an 8-byte discriminator.
+//! Account data comes as a `Vec<u8>` with no alignment guarantee, so
+//! casting it to a struct directly is undefined behavior for most layouts.
+//!
+//! The helpers here read such data safely:
+//!
+//! - `pod_at` copies a `bytemuck::Pod` struct out of any offset.
+//! - `pod_slice` views a run of `Pod` values, borrowing when the bytes are
+//!   aligned and copying them when not.
+//! - `bincode_at` deserializes a bincode value from an offset.
+//!
+//! `PodAccountDecoder` and `BincodeAccountDecoder` wrap them as
+//! `AccountDecoder`s: they read the layout after a header and map it to the
+//! event delivered to handlers.
+//!
+//! ```
+//! use bytemuck::{Pod, Zeroable};
+//! use solana_indexer_sdk::{AccountDecoder, PodAccountDecoder};
+//! # use solana_sdk::{account::Account, pubkey::Pubkey};
+//!
+//! #[derive(Clone, Copy, Pod, Zeroable)]
+//! #[repr(C)]
+//! struct MarketState {
+//!     base_lots: u64,
+//!     quote_lots: u64,
+//! }
+//!
+//! // Skips Serum's 5-byte `serum` padding
+//! let decoder = PodAccountDecoder::new(5, |_pubkey, market: &MarketState| {
+//!     Some(market.base_lots + market.quote_lots)
+//! });
+//! # let data = [&b"serum"[..], &7u64.to_le_bytes(), &3u64.to_le_bytes()].concat();
+//! # let account = Account { data, ..Account::default() };
+//! # assert_eq!(decoder.decode(&Pubkey::new_unique(), &account), Some(10));
+//! ```
+
+use crate::types::traits::AccountDecoder;
+use bytemuck::Pod;
+use serde::de::DeserializeOwned;
+use solana_sdk::account::Account;
+use solana_sdk::pubkey::Pubkey;
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+/// Copies the `T` stored at `offset` of `data`, whatever its alignment.
+/// Returns `None` if `data` is too short.
+#[must_use]
+pub fn pod_at<T: Pod>(data: &[u8], offset: usize) -> Option<T> {
+    let bytes = data.get(offset..offset.checked_add(std::mem::size_of::<T>())?)?;
+    bytemuck::try_pod_read_unaligned(bytes).ok()
+}
+
+/// Returns the `len` values of `T` stored from `offset` of `data`. The
+/// values are borrowed when the bytes are aligned for `T` and copied
+/// otherwise. Returns `None` if `data` is too short.
+#[must_use]
+pub fn pod_slice<T: Pod>(data: &[u8], offset: usize, len: usize) -> Option<Cow<'_, [T]>> {
+    let size = std::mem::size_of::<T>().checked_mul(len)?;
+    let bytes = data.get(offset..offset.checked_add(size)?)?;
+    if let Ok(values) = bytemuck::try_cast_slice(bytes) {
+        return Some(Cow::Borrowed(values));
+    }
+    bytes
+        .chunks_exact(std::mem::size_of::<T>().max(1))
+        .take(len)
+        .map(|chunk| bytemuck::try_pod_read_unaligned(chunk).ok())
+        .collect::<Option<Vec<T>>>()
+        .map(Cow::Owned)
+}
+
+/// Deserializes the bincode value stored from `offset` of `data`. Trailing
+/// bytes, such as the unused space of a fixed-size account, are ignored.
+#[must_use]
+pub fn bincode_at<T: DeserializeOwned>(data: &[u8], offset: usize) -> Option<T> {
+    bincode::deserialize(data.get(offset..)?).ok()
+}
+
+/// Decodes accounts laid out as a `bytemuck::Pod` struct `L` after a
+/// header of `offset` bytes, and maps it to the event `T`.
+pub struct PodAccountDecoder<L, T, F> {
+    offset: usize,
+    owner: Option<Pubkey>,
+    map: F,
+    _layout: PhantomData<fn(&L) -> T>,
+}
+
+impl<L, T, F> PodAccountDecoder<L, T, F>
+where
+    L: Pod,
+    F: Fn(&Pubkey, &L) -> Option<T> + Send + Sync,
+{
+    /// Reads `L` after `offset` bytes of header and maps it with `map`.
+    pub fn new(offset: usize, map: F) -> Self {
+        Self {
+            offset,
+            owner: None,
+            map,
+            _layout: PhantomData,
+        }
+    }
+
+    /// Only decodes accounts owned by `program`.
+    #[must_use]
+    pub fn with_owner(mut self, program: Pubkey) -> Self {
+        self.owner = Some(program);
+        self
+    }
+}
+
+impl<L, T, F> AccountDecoder<T> for PodAccountDecoder<L, T, F>
+where
+    L: Pod,
+    F: Fn(&Pubkey, &L) -> Option<T> + Send + Sync,
+{
+    fn decode(&self, pubkey: &Pubkey, account: &Account) -> Option<T> {
+        if self.owner.is_some_and(|owner| owner != account.owner) {
+            return None;
+        }
+        let layout = pod_at::<L>(&account.data, self.offset)?;
+        (self.map)(pubkey, &layout)
+    }
+}
+
+/// Decodes bincode accounts of type `L` after a header of `offset` bytes,
+/// and maps them to the event `T`.
+pub struct BincodeAccountDecoder<L, T, F> {
+    offset: usize,
+    owner: Option<Pubkey>,
+    map: F,
+    _layout: PhantomData<fn(L) -> T>,
+}
+
+impl<L, T, F> BincodeAccountDecoder<L, T, F>
+where
+    L: DeserializeOwned,
+    F: Fn(&Pubkey, L) -> Option<T> + Send + Sync,
+{
+    /// Reads `L` after `offset` bytes of header and maps it with `map`.
+    pub fn new(offset: usize, map: F) -> Self {
+        Self {
+            offset,
+            owner: None,
+            map,
+            _layout: PhantomData,
+        }
+    }
+
+    /// Only decodes accounts owned by `program`.
+    #[must_use]
+    pub fn with_owner(mut self, program: Pubkey) -> Self {
+        self.owner = Some(program);
+        self
+    }
+}
+
+impl<L, T, F> AccountDecoder<T> for BincodeAccountDecoder<L, T, F>
+where
+    L: DeserializeOwned,
+    F: Fn(&Pubkey, L) -> Option<T> + Send + Sync,
+{
+    fn decode(&self, pubkey: &Pubkey, account: &Account) -> Option<T> {
+        if self.owner.is_some_and(|owner| owner != account.owner) {
+            return None;
+        }
+        let layout = bincode_at::<L>(&account.data, self.offset)?;
+        (self.map)(pubkey, layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    #[repr(C)]
+    struct Order {
+        price: u64,
+        size: u64,
+    }
+
+    #[test]
+    fn test_pod_reads_are_alignment_safe() {
+        let orders = [Order { price: 10, size: 2 }, Order { price: 11, size: 5 }];
+        // One header byte leaves the orders unaligned
+        let data = [&[0xff][..], bytemuck::cast_slice(&orders)].concat();
+
+        assert_eq!(pod_at::<Order>(&data, 17), Some(orders[1]));
+        assert_eq!(pod_at::<Order>(&data, 18), None);
+        let unaligned = pod_slice::<Order>(&data, 1, 2).unwrap();
+        assert!(matches!(unaligned, Cow::Owned(_)));
+        assert_eq!(&*unaligned, &orders);
+        assert!(pod_slice::<Order>(&data, 1, 3).is_none());
+
+        let aligned: Vec<u64> = vec![1, 2, 3];
+        let bytes: &[u8] = bytemuck::cast_slice(&aligned);
+        assert!(matches!(
+            pod_slice::<u64>(bytes, 8, 2),
+            Some(Cow::Borrowed([2, 3]))
+        ));
+    }
+
+    #[test]
+    fn test_bincode_decoder_checks_owner() {
+        #[derive(Deserialize)]
+        struct Config {
+            authority: Pubkey,
+            fee_bps: u16,
+        }
+
+        let (program, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![1, 0, 0, 0];
+        data.extend(bincode::serialize(&(authority, 30u16)).unwrap());
+        data.extend([0; 16]);
+        let account = Account {
+            owner: program,
+            data,
+            ..Account::default()
+        };
+
+        let decoder = BincodeAccountDecoder::new(4, |_, config: Config| {
+            Some((config.authority, config.fee_bps))
+        })
+        .with_owner(program);
+        assert_eq!(
+            decoder.decode(&Pubkey::new_unique(), &account),
+            Some((authority, 30))
+        );
+
+        let other = decoder.with_owner(Pubkey::new_unique());
+        assert_eq!(other.decode(&Pubkey::new_unique(), &account), None);
+    }
+}
@@ -13,6 +13,11 @@ use solana_transaction_status::{
 };
 use std::collections::HashMap;
 
+pub mod anchor;
+pub mod diff;
+pub mod discriminator;
+pub mod layout;
+
 // pub mod registry; // Removed, now a sibling in core
 // pub use registry::DecoderRegistry; // Removed, exported from core/mod.rs
 
@@ -97,7 +102,7 @@ impl Decoder {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DecodingError` if log parsing fails.
+    /// Returns `SolanaIndexerError::Decode` if log parsing fails.
     ///
     /// # Returns
     ///
@@ -132,7 +137,7 @@ impl Decoder {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DecodingError` if log parsing fails.
+    /// Returns `SolanaIndexerError::Decode` if log parsing fails.
     ///
     /// # Returns
     ///
@@ -264,7 +269,7 @@ impl Decoder {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DecodingError` if:
+    /// Returns `SolanaIndexerError::Decode` if:
     /// - The transaction format is invalid
     /// - Required fields are missing
     /// - Instruction data cannot be parsed
@@ -381,7 +386,7 @@ impl Decoder {
                             .account_keys
                             .get(program_id_index)
                             .ok_or_else(|| {
-                                SolanaIndexerError::DecodingError(format!(
+                                SolanaIndexerError::Decode(format!(
                                     "Invalid program_id_index: {program_id_index}"
                                 ))
                             })?
@@ -0,0 +1,376 @@
+//! Windowed event-rate monitoring for anomaly detection.
+//!
+//! `RateMonitor` wraps an event handler and counts the events it receives
+//! per key, such as a wallet or a mint, over fixed windows of event time.
+//! When a key's count in the current window passes an absolute limit, or
+//! spikes above a multiple of its usual rate, an alert hook is invoked once
+//! for that window. Exploit drains and wash trading show up this way as
+//! bursts from one wallet or on one mint.
+//!
+//! The usual rate of a key is an exponential moving average of its counts
+//! in previous windows, idle windows included. Rates are kept in memory,
+//! so a restarted indexer learns them again. Windows follow the block time
+//! of each transaction, falling back to the clock when it is unknown, so a
+//! backfill is measured at the pace the events happened on chain.
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{EventHandler, RateAlert, RateMonitor, Result, SolanaIndexer, TransferEvent, TxMetadata};
+//! # use std::time::Duration;
+//! # fn example(indexer: &mut SolanaIndexer, handler: impl EventHandler<TransferEvent> + 'static) -> Result<()> {
+//! let monitor = RateMonitor::new(handler, |event: &TransferEvent| vec![event.from.clone()])
+//!     .with_window(Duration::from_secs(60))
+//!     .with_spike_factor(5.0, 20)
+//!     .on_alert(|alert: &RateAlert| {
+//!         eprintln!("{} sent {} transfers in a minute", alert.key, alert.events);
+//!     });
+//! indexer.register_handler(monitor)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::EventHandler;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Weight of the latest window in a key's usual rate.
+const BASELINE_WEIGHT: f64 = 0.2;
+
+/// Why a key was reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateAlertKind {
+    /// The window's count passed the absolute limit.
+    Limit,
+    /// The window's count passed the spike factor times the usual rate.
+    Spike,
+}
+
+/// A key whose event rate crossed a threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateAlert {
+    /// The key, e.g. a wallet address
+    pub key: String,
+    /// Which threshold was crossed
+    pub kind: RateAlertKind,
+    /// Start of the window, as a Unix timestamp
+    pub window_start: i64,
+    /// Events counted in the window so far
+    pub events: u64,
+    /// Usual events per window, `None` before the key's first full window
+    pub baseline: Option<f64>,
+    /// Signature of the transaction that crossed the threshold
+    pub signature: String,
+    /// Slot of that transaction
+    pub slot: u64,
+}
+
+/// Receives the alerts of a `RateMonitor`. Closures taking a `&RateAlert`
+/// implement it.
+#[async_trait]
+pub trait RateAlertHook: Send + Sync + 'static {
+    /// Handles one alert. An error is logged and does not fail the event.
+    async fn alert(&self, alert: &RateAlert) -> Result<()>;
+}
+
+#[async_trait]
+impl<F> RateAlertHook for F
+where
+    F: Fn(&RateAlert) + Send + Sync + 'static,
+{
+    async fn alert(&self, alert: &RateAlert) -> Result<()> {
+        self(alert);
+        Ok(())
+    }
+}
+
+/// Returns the keys an event is counted under.
+type KeyFn<E> = Box<dyn Fn(&E) -> Vec<String> + Send + Sync>;
+
+/// Rate of one key.
+#[derive(Debug, Clone, Default)]
+struct KeyRate {
+    window: i64,
+    events: u64,
+    baseline: Option<f64>,
+    alerted: bool,
+}
+
+impl KeyRate {
+    /// Moves to `window`, folding the windows left behind into the usual
+    /// rate.
+    fn advance(&mut self, window: i64) {
+        if window <= self.window {
+            return;
+        }
+        let idle = (window - self.window - 1).min(64);
+        let mut baseline = self.baseline.map_or(self.events as f64, |baseline| {
+            baseline + BASELINE_WEIGHT * (self.events as f64 - baseline)
+        });
+        for _ in 0..idle {
+            baseline *= 1.0 - BASELINE_WEIGHT;
+        }
+        *self = Self {
+            window,
+            events: 0,
+            baseline: Some(baseline),
+            alerted: false,
+        };
+    }
+}
+
+/// Event handler wrapper reporting keys whose event rate spikes.
+///
+/// Events are always passed on to the wrapped handler. Without a limit or
+/// spike factor, rates are tracked but nothing is reported.
+pub struct RateMonitor<H, E> {
+    handler: H,
+    keys: KeyFn<E>,
+    window: Duration,
+    max_events: Option<u64>,
+    spike: Option<(f64, u64)>,
+    max_keys: usize,
+    hook: Option<Arc<dyn RateAlertHook>>,
+    rates: Mutex<HashMap<String, KeyRate>>,
+}
+
+impl<H, E> RateMonitor<H, E> {
+    /// Wraps `handler`, counting each event under the keys `keys` returns.
+    pub fn new(handler: H, keys: impl Fn(&E) -> Vec<String> + Send + Sync + 'static) -> Self {
+        Self {
+            handler,
+            keys: Box::new(keys),
+            window: Duration::from_secs(60),
+            max_events: None,
+            spike: None,
+            max_keys: 100_000,
+            hook: None,
+            rates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the length of a window (default: 60 seconds).
+    #[must_use]
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Reports a key once it has more than `max_events` events in a window.
+    #[must_use]
+    pub fn with_max_events(mut self, max_events: u64) -> Self {
+        self.max_events = Some(max_events);
+        self
+    }
+
+    /// Reports a key once its count in a window is more than `factor` times
+    /// its usual rate, and at least `min_events`, which keeps quiet keys
+    /// from alerting on a handful of events.
+    #[must_use]
+    pub fn with_spike_factor(mut self, factor: f64, min_events: u64) -> Self {
+        self.spike = Some((factor, min_events));
+        self
+    }
+
+    /// Sets how many keys are tracked before keys idle since the previous
+    /// window are dropped (default: 100,000).
+    #[must_use]
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys;
+        self
+    }
+
+    /// Sets the hook alerts are delivered to.
+    #[must_use]
+    pub fn on_alert(mut self, hook: impl RateAlertHook) -> Self {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Returns the events counted for `key` in its current window.
+    pub fn events(&self, key: &str) -> u64 {
+        self.rates
+            .lock()
+            .ok()
+            .and_then(|rates| rates.get(key).map(|rate| rate.events))
+            .unwrap_or_default()
+    }
+
+    /// Counts one event at `time` for every key, returning the alerts due.
+    fn record(&self, keys: Vec<String>, time: i64, context: &TxMetadata) -> Vec<RateAlert> {
+        let window_secs = i64::try_from(self.window.as_secs().max(1)).unwrap_or(i64::MAX);
+        let window = time.div_euclid(window_secs);
+        let Ok(mut rates) = self.rates.lock() else {
+            return Vec::new();
+        };
+        if rates.len() >= self.max_keys {
+            rates.retain(|_, rate| rate.window >= window - 1);
+        }
+
+        let mut alerts = Vec::new();
+        for key in keys {
+            let rate = rates.entry(key.clone()).or_insert_with(|| KeyRate {
+                window,
+                ..KeyRate::default()
+            });
+            rate.advance(window);
+            rate.events += 1;
+            if rate.alerted {
+                continue;
+            }
+            let events = rate.events;
+            let kind = if self.max_events.is_some_and(|max| events > max) {
+                RateAlertKind::Limit
+            } else if self.spike.is_some_and(|(factor, min_events)| {
+                events >= min_events
+                    && rate
+                        .baseline
+                        .is_some_and(|baseline| events as f64 > factor * baseline.max(1.0))
+            }) {
+                RateAlertKind::Spike
+            } else {
+                continue;
+            };
+            rate.alerted = true;
+            alerts.push(RateAlert {
+                key,
+                kind,
+                window_start: rate.window * window_secs,
+                events,
+                baseline: rate.baseline,
+                signature: context.signature.clone(),
+                slot: context.slot,
+            });
+        }
+        alerts
+    }
+}
+
+#[async_trait]
+impl<H, E> EventHandler<E> for RateMonitor<H, E>
+where
+    H: EventHandler<E>,
+    E: Send + Sync + 'static,
+{
+    async fn handle(&self, event: E, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        let time = context.block_time.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| {
+                    i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX)
+                })
+        });
+        let alerts = self.record((self.keys)(&event), time, context);
+        if let Some(hook) = &self.hook {
+            for alert in &alerts {
+                if let Err(e) = hook.alert(alert).await {
+                    logging::log_error("Rate alert hook failed", &e.to_string());
+                }
+            }
+        }
+        self.handler.handle(event, context, db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+
+    struct Ignore;
+
+    #[async_trait]
+    impl EventHandler<TransferEvent> for Ignore {
+        async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn context(block_time: i64) -> TxMetadata {
+        TxMetadata {
+            slot: 1,
+            block_time: Some(block_time),
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        }
+    }
+
+    fn monitor() -> (
+        RateMonitor<Ignore, TransferEvent>,
+        Arc<Mutex<Vec<RateAlert>>>,
+    ) {
+        let alerts = Arc::new(Mutex::new(Vec::new()));
+        let sent = Arc::clone(&alerts);
+        let monitor = RateMonitor::new(Ignore, |event: &TransferEvent| vec![event.from.clone()])
+            .with_window(Duration::from_secs(10))
+            .on_alert(move |alert: &RateAlert| sent.lock().unwrap().push(alert.clone()));
+        (monitor, alerts)
+    }
+
+    async fn send(monitor: &RateMonitor<Ignore, TransferEvent>, from: &str, time: i64, count: u32) {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        for _ in 0..count {
+            let event = TransferEvent {
+                from: from.to_string(),
+                to: "pool".to_string(),
+                amount: 1,
+            };
+            monitor.handle(event, &context(time), &pool).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limit_alerts_once_per_window() {
+        let (monitor, alerts) = monitor();
+        let monitor = monitor.with_max_events(3);
+
+        send(&monitor, "alice", 100, 6).await;
+        send(&monitor, "bob", 100, 2).await;
+        send(&monitor, "alice", 110, 4).await;
+
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].key, "alice");
+        assert_eq!(alerts[0].kind, RateAlertKind::Limit);
+        assert_eq!((alerts[0].window_start, alerts[0].events), (100, 4));
+        assert_eq!(alerts[1].window_start, 110);
+        assert_eq!(monitor.events("alice"), 4);
+    }
+
+    #[tokio::test]
+    async fn test_spikes_are_relative_to_the_usual_rate() {
+        let (monitor, alerts) = monitor();
+        let monitor = monitor.with_spike_factor(3.0, 5);
+
+        for window in 0..5 {
+            send(&monitor, "alice", window * 10, 2).await;
+        }
+        send(&monitor, "alice", 50, 6).await;
+        assert!(alerts.lock().unwrap().is_empty());
+
+        send(&monitor, "alice", 50, 1).await;
+        let alerts = alerts.lock().unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, RateAlertKind::Spike);
+        assert_eq!(alerts[0].events, 7);
+        assert_eq!(alerts[0].baseline, Some(2.0));
+    }
+}
@@ -0,0 +1,152 @@
+//! Signature confirmation tracking.
+//!
+//! With `confirmation_tracking` enabled, every transaction the indexer
+//! handles is recorded at the commitment it was fetched with. A background
+//! task then walks the signatures that are not finalized yet, asks the node
+//! for their status with `getSignatureStatuses`, and records each level as
+//! it is reached, so storage ends up with a processed → confirmed →
+//! finalized timeline per signature.
+//!
+//! The walk pages through pending signatures in slot order and wraps around,
+//! so signatures the node has forgotten (dropped forks) cannot starve newer
+//! ones.
+
+use crate::config::CommitmentLevel;
+use crate::core::execution::fetcher::Fetcher;
+use crate::storage::StorageBackend;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Largest number of signatures `getSignatureStatuses` accepts.
+const MAX_STATUSES_PER_REQUEST: usize = 256;
+
+/// Pause between status sweeps.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Advances the recorded commitment of pending signatures.
+pub struct ConfirmationTracker {
+    fetcher: Arc<Fetcher>,
+    storage: Arc<dyn StorageBackend>,
+    /// Last slot and signature checked, where the next page starts
+    cursor: Option<(u64, String)>,
+}
+
+impl ConfirmationTracker {
+    /// Creates a tracker that reads statuses through `fetcher` and records
+    /// them in `storage`.
+    #[must_use]
+    pub fn new(fetcher: Arc<Fetcher>, storage: Arc<dyn StorageBackend>) -> Self {
+        Self {
+            fetcher,
+            storage,
+            cursor: None,
+        }
+    }
+
+    /// Runs sweeps until `cancellation_token` fires.
+    pub fn spawn(mut self, cancellation_token: CancellationToken) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+            loop {
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = self.sweep().await {
+                            logging::log_error("Confirmation tracking error", &e.to_string());
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Checks one page of pending signatures and records the levels they
+    /// reached. Returns the number of signatures whose status was found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if storage or the status lookup fails.
+    pub async fn sweep(&mut self) -> Result<usize> {
+        let after = self
+            .cursor
+            .as_ref()
+            .map(|(slot, signature)| (*slot, signature.as_str()));
+        let page = self
+            .storage
+            .load_unfinalized_signatures(after, MAX_STATUSES_PER_REQUEST)
+            .await?;
+        // A short page means the end was reached; start over next time
+        self.cursor = if page.len() < MAX_STATUSES_PER_REQUEST {
+            None
+        } else {
+            page.last().cloned()
+        };
+
+        let signatures: Vec<Signature> = page
+            .iter()
+            .filter_map(|(_, signature)| Signature::from_str(signature).ok())
+            .collect();
+        if signatures.is_empty() {
+            return Ok(0);
+        }
+
+        let statuses = self.fetcher.get_signature_statuses(&signatures).await?;
+        let mut found = 0;
+        for (signature, status) in signatures.iter().zip(statuses) {
+            let Some(status) = status else { continue };
+            self.storage
+                .record_commitment(&signature.to_string(), status.slot, level_of(&status))
+                .await?;
+            found += 1;
+        }
+        Ok(found)
+    }
+}
+
+/// Maps an RPC status to the commitment level it represents.
+fn level_of(status: &TransactionStatus) -> CommitmentLevel {
+    match status.confirmation_status() {
+        TransactionConfirmationStatus::Processed => CommitmentLevel::Processed,
+        TransactionConfirmationStatus::Confirmed => CommitmentLevel::Confirmed,
+        TransactionConfirmationStatus::Finalized => CommitmentLevel::Finalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_of_status() {
+        let status = |confirmations, confirmation_status| TransactionStatus {
+            slot: 1,
+            confirmations,
+            status: Ok(()),
+            err: None,
+            confirmation_status,
+        };
+
+        assert_eq!(
+            level_of(&status(
+                Some(0),
+                Some(TransactionConfirmationStatus::Processed)
+            )),
+            CommitmentLevel::Processed
+        );
+        assert_eq!(
+            level_of(&status(
+                Some(3),
+                Some(TransactionConfirmationStatus::Confirmed)
+            )),
+            CommitmentLevel::Confirmed
+        );
+        // Rooted transactions report no confirmations
+        assert_eq!(level_of(&status(None, None)), CommitmentLevel::Finalized);
+    }
+}
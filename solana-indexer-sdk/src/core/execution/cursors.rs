@@ -0,0 +1,178 @@
+//! Per-address polling cursors.
+//!
+//! When an indexer tracks many programs and addresses, each one needs its own
+//! `until` anchor for `getSignaturesForAddress`: a signature from one address's
+//! history means nothing to another. `CursorSchedule` keeps one cursor per
+//! address and hands out a bounded slice of addresses on every poll in
+//! round-robin order, so hundreds of addresses can share one indexer without
+//! every tick issuing hundreds of RPC calls.
+//!
+//! A cursor only moves past signatures that were processed: `settled_cursor`
+//! holds it below the oldest failed signature of a page, so the next poll
+//! fetches that signature again instead of leaving its slot half-processed.
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{HashMap, HashSet};
+
+/// Round-robin schedule of tracked addresses and their last-seen signatures.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::core::execution::cursors::CursorSchedule;
+/// use solana_sdk::pubkey::Pubkey;
+/// use solana_sdk::signature::Signature;
+///
+/// let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+/// let mut schedule = CursorSchedule::new(vec![a, b, c], 2);
+///
+/// let sig = Signature::new_unique();
+/// schedule.set_cursor(a, sig);
+///
+/// assert_eq!(schedule.next_batch(), vec![(a, Some(sig)), (b, None)]);
+/// assert_eq!(schedule.next_batch(), vec![(c, None), (a, Some(sig))]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CursorSchedule {
+    /// Addresses in scheduling order.
+    addresses: Vec<Pubkey>,
+    /// Newest signature seen per address.
+    cursors: HashMap<Pubkey, Signature>,
+    /// Maximum number of addresses polled per tick (0 = all).
+    per_poll: usize,
+    /// Index of the next address to poll.
+    next: usize,
+}
+
+impl CursorSchedule {
+    /// Creates a schedule over `addresses`, polling at most `per_poll` of them
+    /// per tick (0 polls every address on every tick).
+    #[must_use]
+    pub fn new(addresses: Vec<Pubkey>, per_poll: usize) -> Self {
+        Self {
+            addresses,
+            cursors: HashMap::new(),
+            per_poll,
+            next: 0,
+        }
+    }
+
+    /// Returns the scheduled addresses.
+    #[must_use]
+    pub fn addresses(&self) -> &[Pubkey] {
+        &self.addresses
+    }
+
+    /// Returns the cursor for `address`, if one is known.
+    #[must_use]
+    pub fn cursor(&self, address: &Pubkey) -> Option<Signature> {
+        self.cursors.get(address).copied()
+    }
+
+    /// Records `signature` as the newest signature seen for `address`.
+    pub fn set_cursor(&mut self, address: Pubkey, signature: Signature) {
+        self.cursors.insert(address, signature);
+    }
+
+    /// Returns the next addresses to poll together with their cursors and
+    /// advances the round-robin position.
+    pub fn next_batch(&mut self) -> Vec<(Pubkey, Option<Signature>)> {
+        let len = self.addresses.len();
+        if len == 0 {
+            return Vec::new();
+        }
+
+        let count = if self.per_poll == 0 {
+            len
+        } else {
+            self.per_poll.min(len)
+        };
+        let batch = (0..count)
+            .map(|offset| {
+                let address = self.addresses[(self.next + offset) % len];
+                (address, self.cursor(&address))
+            })
+            .collect();
+        self.next = (self.next + count) % len;
+        batch
+    }
+}
+
+/// Returns the cursor an address can move to once its polled `page` of
+/// `(signature, slot)` pairs, newest first, has been processed.
+///
+/// That is the newest signature of the page, unless some of `failed` are in
+/// it: then the signature just below the oldest failure, or `None` (keep the
+/// current cursor) if the oldest signature of the page failed.
+#[must_use]
+pub fn settled_cursor(
+    page: &[(Signature, u64)],
+    failed: &HashSet<Signature>,
+) -> Option<(Signature, u64)> {
+    match page
+        .iter()
+        .rposition(|(signature, _)| failed.contains(signature))
+    {
+        Some(oldest_failure) => page.get(oldest_failure + 1).copied(),
+        None => page.first().copied(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_all_when_unbounded() {
+        let addresses = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut schedule = CursorSchedule::new(addresses.clone(), 0);
+
+        for _ in 0..3 {
+            let batch: Vec<Pubkey> = schedule.next_batch().into_iter().map(|(a, _)| a).collect();
+            assert_eq!(batch, addresses);
+        }
+    }
+
+    #[test]
+    fn test_round_robin_covers_every_address() {
+        let addresses: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let mut schedule = CursorSchedule::new(addresses.clone(), 2);
+
+        let polled: Vec<Pubkey> = (0..5)
+            .flat_map(|_| schedule.next_batch())
+            .map(|(address, _)| address)
+            .collect();
+
+        // Five ticks of two addresses visit each of the five addresses twice.
+        for address in &addresses {
+            assert_eq!(polled.iter().filter(|a| *a == address).count(), 2);
+        }
+        assert!(CursorSchedule::new(vec![], 2).next_batch().is_empty());
+    }
+
+    #[test]
+    fn test_cursors_are_independent() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut schedule = CursorSchedule::new(vec![a, b], 0);
+        let sig = Signature::new_unique();
+
+        schedule.set_cursor(b, sig);
+
+        assert_eq!(schedule.cursor(&a), None);
+        assert_eq!(schedule.next_batch(), vec![(a, None), (b, Some(sig))]);
+    }
+
+    #[test]
+    fn test_cursor_holds_below_failures() {
+        let page: Vec<(Signature, u64)> =
+            (0..4).map(|n| (Signature::new_unique(), 40 - n)).collect();
+        let failed = |indices: &[usize]| indices.iter().map(|&i| page[i].0).collect();
+
+        assert_eq!(settled_cursor(&page, &failed(&[])), Some(page[0]));
+        // Retry from the oldest failure, even with newer ones processed
+        assert_eq!(settled_cursor(&page, &failed(&[0, 2])), Some(page[3]));
+        assert_eq!(settled_cursor(&page, &failed(&[3])), None);
+        assert_eq!(settled_cursor(&[], &failed(&[])), None);
+    }
+}
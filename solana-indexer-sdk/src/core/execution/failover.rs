@@ -0,0 +1,246 @@
+//! Reconnect budget and failover escalation.
+//!
+//! Live sources count consecutive failures. Once `failure_budget` failures
+//! have piled up without a success in between, the indexer escalates one
+//! rung up the recovery ladder and starts counting again:
+//!
+//! 1. Switch to the next RPC endpoint in `rpc_endpoints`.
+//! 2. Fall back to a simpler source type: WebSocket sources drop to RPC
+//!    polling against the current endpoint.
+//! 3. Alert through the registered `ErrorObserver`, once per exhausted
+//!    budget, while the indexer keeps retrying.
+//!
+//! Every escalation is also reported to the observer, so operators can see
+//! the indexer move down the ladder before the final alert.
+
+use crate::types::backfill_traits::BackfillRange;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Failover settings for live sources.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    /// Consecutive source failures tolerated before escalating
+    /// (default: 0, which disables failover)
+    pub failure_budget: u32,
+
+    /// Additional RPC endpoints to switch to, in order, after the configured
+    /// one has exhausted its budget
+    pub rpc_endpoints: Vec<String>,
+}
+
+/// A step up the recovery ladder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Escalation {
+    /// Requests now go to another RPC endpoint
+    SwitchEndpoint {
+        /// The endpoint being abandoned
+        from: String,
+        /// The endpoint taking over
+        to: String,
+    },
+    /// The live source was replaced by a simpler source type
+    FallbackSource {
+        /// The source being abandoned, e.g. `websocket`
+        from: &'static str,
+        /// The source taking over, e.g. `rpc`
+        to: &'static str,
+    },
+    /// Every endpoint and fallback has been tried and the source is still
+    /// failing
+    Alert {
+        /// Failures since the last success
+        consecutive_failures: u32,
+        /// The most recent error
+        last_error: String,
+    },
+}
+
+impl fmt::Display for Escalation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SwitchEndpoint { from, to } => {
+                write!(f, "switching RPC endpoint from {from} to {to}")
+            }
+            Self::FallbackSource { from, to } => {
+                write!(f, "falling back from the {from} source to {to}")
+            }
+            Self::Alert {
+                consecutive_failures,
+                last_error,
+            } => write!(
+                f,
+                "source still failing after {consecutive_failures} consecutive failures: {last_error}"
+            ),
+        }
+    }
+}
+
+/// Receives escalations from the failover ladder.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::{ErrorObserver, Escalation, SolanaIndexer};
+/// # use std::sync::Arc;
+/// struct PagerObserver;
+///
+/// impl ErrorObserver for PagerObserver {
+///     fn on_escalation(&self, escalation: &Escalation) {
+///         if let Escalation::Alert { .. } = escalation {
+///             eprintln!("PAGE: {escalation}");
+///         }
+///     }
+/// }
+///
+/// # fn example(indexer: &mut SolanaIndexer) {
+/// indexer.set_error_observer(Arc::new(PagerObserver));
+/// # }
+/// ```
+pub trait ErrorObserver: Send + Sync {
+    /// Called for every escalation, including the final alert.
+    fn on_escalation(&self, escalation: &Escalation);
+
+    /// Called when consecutive live transactions jump more slots than
+    /// `with_slot_gap_alarm` allows. `range` runs from the last slot seen to
+    /// the new one.
+    fn on_slot_gap(&self, range: &BackfillRange) {
+        let _ = range;
+    }
+}
+
+/// Counts consecutive failures and decides when and how to escalate.
+#[derive(Debug, Clone)]
+pub struct FailoverLadder {
+    budget: u32,
+    endpoints: Vec<String>,
+    endpoint: usize,
+    fallback: Option<(&'static str, &'static str)>,
+    since_escalation: u32,
+    consecutive: u32,
+}
+
+impl FailoverLadder {
+    /// Creates a ladder starting at `primary`, followed by the configured
+    /// endpoints. `fallback` names the source switch available once the
+    /// endpoints are exhausted, as `(from, to)`.
+    #[must_use]
+    pub fn new(
+        config: &FailoverConfig,
+        primary: impl Into<String>,
+        fallback: Option<(&'static str, &'static str)>,
+    ) -> Self {
+        let mut endpoints = vec![primary.into()];
+        endpoints.extend(config.rpc_endpoints.iter().cloned());
+        Self {
+            budget: config.failure_budget,
+            endpoints,
+            endpoint: 0,
+            fallback,
+            since_escalation: 0,
+            consecutive: 0,
+        }
+    }
+
+    /// Returns `true` if a failure budget is configured.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.budget > 0
+    }
+
+    /// Returns the endpoint currently in use.
+    #[must_use]
+    pub fn current_endpoint(&self) -> &str {
+        &self.endpoints[self.endpoint]
+    }
+
+    /// Resets the failure count after a successful poll or batch.
+    pub fn record_success(&mut self) {
+        self.since_escalation = 0;
+        self.consecutive = 0;
+    }
+
+    /// Counts a failure and returns the escalation to perform, if the
+    /// budget is now exhausted.
+    pub fn record_failure(&mut self, error: &str) -> Option<Escalation> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.consecutive = self.consecutive.saturating_add(1);
+        self.since_escalation += 1;
+        if self.since_escalation < self.budget {
+            return None;
+        }
+        self.since_escalation = 0;
+
+        if self.endpoint + 1 < self.endpoints.len() {
+            let from = self.endpoints[self.endpoint].clone();
+            self.endpoint += 1;
+            return Some(Escalation::SwitchEndpoint {
+                from,
+                to: self.endpoints[self.endpoint].clone(),
+            });
+        }
+        if let Some((from, to)) = self.fallback.take() {
+            return Some(Escalation::FallbackSource { from, to });
+        }
+        Some(Escalation::Alert {
+            consecutive_failures: self.consecutive,
+            last_error: error.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ladder_escalates_in_order() {
+        let config = FailoverConfig {
+            failure_budget: 2,
+            rpc_endpoints: vec!["http://b".to_string()],
+        };
+        let mut ladder = FailoverLadder::new(&config, "http://a", Some(("websocket", "rpc")));
+
+        assert_eq!(ladder.record_failure("down"), None);
+        // A success in between resets the budget
+        ladder.record_success();
+        assert_eq!(ladder.record_failure("down"), None);
+        assert_eq!(
+            ladder.record_failure("down"),
+            Some(Escalation::SwitchEndpoint {
+                from: "http://a".to_string(),
+                to: "http://b".to_string(),
+            })
+        );
+        assert_eq!(ladder.current_endpoint(), "http://b");
+
+        assert_eq!(ladder.record_failure("down"), None);
+        assert_eq!(
+            ladder.record_failure("down"),
+            Some(Escalation::FallbackSource {
+                from: "websocket",
+                to: "rpc",
+            })
+        );
+
+        assert_eq!(ladder.record_failure("down"), None);
+        assert_eq!(
+            ladder.record_failure("still down"),
+            Some(Escalation::Alert {
+                consecutive_failures: 6,
+                last_error: "still down".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_ladder_disabled_without_budget() {
+        let mut ladder = FailoverLadder::new(&FailoverConfig::default(), "http://a", None);
+        assert!(!ladder.is_enabled());
+        for _ in 0..10 {
+            assert_eq!(ladder.record_failure("down"), None);
+        }
+    }
+}
@@ -4,14 +4,147 @@
 //! Solana RPC endpoints. It takes transaction signatures and fetches the
 //! complete transaction data including instruction details, logs, and metadata.
 
+use crate::core::execution::usage;
+use crate::utils::backoff::{retry_with_backoff, BackoffConfig};
 use crate::utils::error::{Result, SolanaIndexerError};
-use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcTransactionConfig;
+use crate::utils::network::{HttpTransport, RpcTimeouts, RPC_TIMEOUT};
+use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::{
+    RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig,
+};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
 use solana_transaction_status::{
-    EncodedConfirmedTransactionWithStatusMeta, UiConfirmedBlock, UiTransactionEncoding,
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiConfirmedBlock,
+    UiInnerInstructions, UiInstruction, UiMessage, UiTransactionEncoding,
 };
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+
+/// Offset of the owner in an SPL token account; the amount follows it.
+const TOKEN_ACCOUNT_OWNER_OFFSET: usize = 32;
+
+/// Returns the on-chain error of a fetched transaction, if it failed.
+///
+/// The error is rendered with its `Debug` representation (e.g.
+/// `InstructionError(0, Custom(6001))`) so it can be stored or logged as-is.
+#[must_use]
+pub fn transaction_error(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Option<String> {
+    tx.transaction
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.err.as_ref())
+        .map(|err| format!("{err:?}"))
+}
+
+/// Returns the inner instructions (CPIs) recorded in a fetched transaction's
+/// metadata, grouped by the top-level instruction that invoked them.
+#[must_use]
+pub fn inner_instructions(
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> &[UiInnerInstructions] {
+    match tx
+        .transaction
+        .meta
+        .as_ref()
+        .map(|meta| &meta.inner_instructions)
+    {
+        Some(OptionSerializer::Some(inner)) => inner,
+        _ => &[],
+    }
+}
+
+/// Returns the top-level instructions of a fetched transaction's message.
+///
+/// Instructions of a raw (`json`-encoded) message come back as
+/// `UiInstruction::Compiled`.
+#[must_use]
+pub fn instructions(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Cow<'_, [UiInstruction]> {
+    match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
+            UiMessage::Parsed(msg) => Cow::Borrowed(&msg.instructions),
+            UiMessage::Raw(msg) => Cow::Owned(
+                msg.instructions
+                    .iter()
+                    .cloned()
+                    .map(UiInstruction::Compiled)
+                    .collect(),
+            ),
+        },
+        _ => Cow::Borrowed(&[]),
+    }
+}
+
+/// Returns the account keys of a fetched transaction's message, which
+/// include every program it invokes at any depth.
+#[must_use]
+pub fn account_keys(tx: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<&str> {
+    match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
+            UiMessage::Parsed(msg) => msg.account_keys.iter().map(|k| k.pubkey.as_str()).collect(),
+            UiMessage::Raw(msg) => msg.account_keys.iter().map(String::as_str).collect(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Returns `true` if the transaction invokes the native Vote program.
+///
+/// Vote transactions make up the bulk of Solana traffic and are rarely
+/// interesting to application indexers.
+#[must_use]
+pub fn is_vote_transaction(tx: &EncodedConfirmedTransactionWithStatusMeta) -> bool {
+    let vote_program = solana_sdk::vote::program::id().to_string();
+    match &tx.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
+            UiMessage::Parsed(msg) => msg.account_keys.iter().any(|k| k.pubkey == vote_program),
+            UiMessage::Raw(msg) => msg.account_keys.contains(&vote_program),
+        },
+        _ => false,
+    }
+}
+
+/// Returns the position of `signature` among the transactions of `block`.
+///
+/// Uses the block's signature list when it was fetched with
+/// `TransactionDetails::Signatures`, and its JSON-encoded transactions
+/// otherwise.
+#[must_use]
+pub fn transaction_index(block: &UiConfirmedBlock, signature: &str) -> Option<u32> {
+    let position = match (&block.signatures, &block.transactions) {
+        (Some(signatures), _) => signatures.iter().position(|s| s == signature),
+        (None, Some(transactions)) => transactions.iter().position(|tx| match &tx.transaction {
+            EncodedTransaction::Json(ui_tx) => {
+                ui_tx.signatures.first().is_some_and(|s| s == signature)
+            }
+            _ => false,
+        }),
+        (None, None) => None,
+    };
+    position.and_then(|i| u32::try_from(i).ok())
+}
+
+/// Number of blocks whose signature order `Fetcher::transaction_index`
+/// keeps around.
+const BLOCK_ORDER_SLOTS: usize = 64;
+
+/// Signature lists of recently looked-up blocks.
+///
+/// Transactions of one slot are processed concurrently; each slot holds a
+/// cell they all wait on, so the block is fetched only once.
+type BlockOrders = std::sync::Mutex<VecDeque<(u64, Arc<OnceCell<Vec<String>>>)>>;
+
+/// Helius-parsed events of a poll's signatures, fetched ahead of the
+/// transactions and taken by each as it is processed.
+#[cfg(feature = "helius")]
+type PrefetchedEvents =
+    std::sync::Mutex<std::collections::HashMap<Signature, Vec<crate::types::events::ParsedEvent>>>;
 
 /// Transaction fetcher for retrieving full transaction details.
 ///
@@ -37,6 +170,18 @@ pub struct Fetcher {
     rpc_url: String,
     /// Commitment configuration for fetching
     commitment: CommitmentConfig,
+    /// Retry policy applied to RPC failures
+    backoff: BackoffConfig,
+    /// Proxy and TLS settings used to build RPC clients
+    transport: HttpTransport,
+    /// Helius Enhanced Transactions API client, if enabled
+    #[cfg(feature = "helius")]
+    enhanced: Option<crate::streams::helius_enhanced::HeliusEnhancedClient>,
+    /// Helius-parsed events fetched by `prefetch_enhanced_events`
+    #[cfg(feature = "helius")]
+    prefetched_events: PrefetchedEvents,
+    /// Signature order of recently looked-up blocks
+    block_orders: BlockOrders,
 }
 
 impl Fetcher {
@@ -57,9 +202,80 @@ impl Fetcher {
         Self {
             rpc_url: rpc_url.into(),
             commitment,
+            backoff: BackoffConfig::default(),
+            transport: HttpTransport::default(),
+            #[cfg(feature = "helius")]
+            enhanced: None,
+            #[cfg(feature = "helius")]
+            prefetched_events: PrefetchedEvents::default(),
+            block_orders: BlockOrders::default(),
         }
     }
 
+    /// Sets the retry policy used for RPC failures.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use solana_indexer_sdk::Fetcher;
+    /// # use solana_indexer_sdk::utils::backoff::BackoffConfig;
+    /// let fetcher = Fetcher::new("http://127.0.0.1:8899", solana_sdk::commitment_config::CommitmentConfig::confirmed())
+    ///     .with_backoff(BackoffConfig {
+    ///         max_attempts: 8,
+    ///         ..BackoffConfig::default()
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn with_backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Routes RPC requests through `transport` (proxy, TLS roots, connection
+    /// reuse and DNS cache).
+    ///
+    /// Set this before `with_helius_enhanced` so the Helius client uses it too.
+    #[must_use]
+    pub fn with_transport(mut self, transport: HttpTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Returns the transport used to build RPC clients.
+    #[must_use]
+    pub fn transport(&self) -> &HttpTransport {
+        &self.transport
+    }
+
+    /// Opens a connection to the RPC node ahead of the first real request and
+    /// pre-resolves `ws_url`, if given (see `HttpTransport::warm_up`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the node cannot be reached.
+    pub async fn warm_up(&self, ws_url: Option<String>) -> Result<()> {
+        let rpc_url = self.rpc_url.clone();
+        let transport = self.transport.clone();
+        let commitment = self.commitment;
+        tokio::task::spawn_blocking(move || {
+            transport.warm_up(rpc_url, commitment, ws_url.as_deref())
+        })
+        .await
+        .map_err(|e| SolanaIndexerError::Internal(format!("Task join error: {e}")))?
+    }
+
+    /// Enables fetching Helius-parsed events through the Enhanced
+    /// Transactions API at `url` (see `fetch_enhanced_events`).
+    #[must_use]
+    #[cfg(feature = "helius")]
+    pub fn with_helius_enhanced(mut self, url: impl Into<String>) -> Self {
+        self.enhanced = Some(
+            crate::streams::helius_enhanced::HeliusEnhancedClient::new(url)
+                .with_transport(self.transport.clone()),
+        );
+        self
+    }
+
     /// Fetches a single transaction by its signature.
     ///
     /// This method retrieves the full transaction details including:
@@ -75,7 +291,7 @@ impl Fetcher {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::RpcError` if:
+    /// Returns `SolanaIndexerError::Rpc` if:
     /// - The RPC request fails
     /// - The transaction is not found
     /// - The network is unreachable
@@ -105,55 +321,28 @@ impl Fetcher {
         &self,
         signature: &Signature,
     ) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
-        let rpc_url = self.rpc_url.clone();
         let sig = *signature;
+        let commitment = self.commitment;
 
-        let max_retries = 5;
-        let mut attempt = 0;
-
-        loop {
-            attempt += 1;
-
-            // We use a new client per attempt or reuse one?
-            // In spawned blocking task we can't easily reuse across awaits unless we move it in/out.
-            // Spawning a new task for each attempt is simpler for error handling but maybe slightly more overhead.
-            // Let's keep the spawn_blocking wrapping the RPC call.
-
-            let rpc_url_clone = rpc_url.clone();
-            let default_commitment = self.commitment;
-            let result = tokio::task::spawn_blocking(move || {
-                let rpc_client = RpcClient::new_with_commitment(rpc_url_clone, default_commitment);
-
+        self.blocking_rpc_with_timeout(
+            &format!("Fetch of {sig}"),
+            commitment,
+            self.timeouts().transaction(),
+            move |rpc_client| {
                 let config = RpcTransactionConfig {
                     encoding: Some(UiTransactionEncoding::JsonParsed),
-                    commitment: Some(default_commitment),
+                    commitment: Some(commitment),
                     max_supported_transaction_version: Some(0),
                 };
 
-                rpc_client.get_transaction_with_config(&sig, config)
-            })
-            .await
-            .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?;
-
-            match result {
-                Ok(tx) => return Ok(tx),
-                Err(e) => {
-                    if attempt >= max_retries {
-                        return Err(SolanaIndexerError::RpcError(format!(
-                            "Failed to fetch transaction {sig} after {max_retries} attempts: {e}"
-                        )));
-                    }
-
-                    // Simple backoff: 100ms * 2^attempt
-                    let backoff = std::time::Duration::from_millis(100 * (1 << attempt));
-                    tracing::warn!(
-                        "⚠️ Fetch failed for {sig} (Attempt {attempt}/{max_retries}): {e}. Retrying in {:?}...",
-                        backoff
-                    );
-                    tokio::time::sleep(backoff).await;
-                }
-            }
-        }
+                rpc_client
+                    .get_transaction_with_config(&sig, config)
+                    .map_err(|e| {
+                        SolanaIndexerError::rpc(format!("Failed to fetch transaction {sig}: {e}"))
+                    })
+            },
+        )
+        .await
     }
 
     /// Fetches multiple transactions in batch.
@@ -167,7 +356,7 @@ impl Fetcher {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::RpcError` if the RPC client cannot be created.
+    /// Returns `SolanaIndexerError::Rpc` if the RPC client cannot be created.
     /// Individual transaction fetch failures are returned in the result vector.
     ///
     /// # Returns
@@ -205,10 +394,14 @@ impl Fetcher {
 
         let rpc_url = self.rpc_url.clone();
         let sigs = signatures.to_vec();
+        let transport = self.transport.clone();
+        let timeout = self.timeouts().transaction();
 
         let default_commitment = self.commitment;
+        usage::record_rpc_calls(sigs.len() as u64);
         tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, default_commitment);
+            let rpc_client =
+                transport.rpc_client_with_timeout(rpc_url, default_commitment, timeout)?;
 
             // Use rayon for parallel fetching
             let results: Vec<Result<EncodedConfirmedTransactionWithStatusMeta>> = sigs
@@ -222,7 +415,7 @@ impl Fetcher {
                     rpc_client
                         .get_transaction_with_config(sig, config)
                         .map_err(|e| {
-                            SolanaIndexerError::RpcError(format!(
+                            SolanaIndexerError::rpc(format!(
                                 "Failed to fetch transaction {sig}: {e}"
                             ))
                         })
@@ -232,7 +425,7 @@ impl Fetcher {
             Ok(results)
         })
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
+        .map_err(|e| SolanaIndexerError::Internal(format!("Task join error: {e}")))?
     }
 
     /// Fetches a single account by its public key.
@@ -248,18 +441,18 @@ impl Fetcher {
         &self,
         pubkey: &solana_sdk::pubkey::Pubkey,
     ) -> Result<solana_sdk::account::Account> {
-        let rpc_url = self.rpc_url.clone();
         let key = *pubkey;
 
-        let default_commitment = self.commitment;
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, default_commitment);
-            rpc_client.get_account(&key).map_err(|e| {
-                SolanaIndexerError::RpcError(format!("Failed to fetch account {key}: {e}"))
-            })
-        })
+        self.blocking_rpc(
+            &format!("Fetch of account {key}"),
+            self.commitment,
+            move |rpc_client| {
+                rpc_client.get_account(&key).map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Failed to fetch account {key}: {e}"))
+                })
+            },
+        )
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
     }
 
     /// Fetches multiple accounts by their public keys.
@@ -275,18 +468,18 @@ impl Fetcher {
         &self,
         pubkeys: &[solana_sdk::pubkey::Pubkey],
     ) -> Result<Vec<Option<solana_sdk::account::Account>>> {
-        let rpc_url = self.rpc_url.clone();
         let keys = pubkeys.to_vec();
 
-        let default_commitment = self.commitment;
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, default_commitment);
-            rpc_client.get_multiple_accounts(&keys).map_err(|e| {
-                SolanaIndexerError::RpcError(format!("Failed to fetch multiple accounts: {e}"))
-            })
-        })
+        self.blocking_rpc(
+            "Fetch of multiple accounts",
+            self.commitment,
+            move |rpc_client| {
+                rpc_client.get_multiple_accounts(&keys).map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Failed to fetch multiple accounts: {e}"))
+                })
+            },
+        )
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
     }
 
     /// Fetches all accounts owned by a program.
@@ -302,18 +495,139 @@ impl Fetcher {
         &self,
         program_id: &solana_sdk::pubkey::Pubkey,
     ) -> Result<Vec<(solana_sdk::pubkey::Pubkey, solana_sdk::account::Account)>> {
-        let rpc_url = self.rpc_url.clone();
         let pid = *program_id;
-        let default_commitment = self.commitment;
 
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, default_commitment);
-            rpc_client.get_program_accounts(&pid).map_err(|e| {
-                SolanaIndexerError::RpcError(format!("Failed to fetch program accounts: {e}"))
-            })
+        self.blocking_rpc(
+            "Fetch of program accounts",
+            self.commitment,
+            move |rpc_client| {
+                rpc_client.get_program_accounts(&pid).map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Failed to fetch program accounts: {e}"))
+                })
+            },
+        )
+        .await
+    }
+
+    /// Lists the accounts owned by a program without downloading their data.
+    ///
+    /// Uses a zero-length `dataSlice`, so even programs with millions of
+    /// accounts return only keys; fetch the data in pages with
+    /// `fetch_multiple_accounts`. Also returns the slot observed just before
+    /// the scan, which every listed account is at least as new as.
+    ///
+    /// # Arguments
+    ///
+    /// * `program_id` - The program ID to list accounts for
+    /// * `filters` - `getProgramAccounts` filters (data size, memcmp)
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the RPC request fails.
+    pub async fn get_program_account_keys(
+        &self,
+        program_id: &solana_sdk::pubkey::Pubkey,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<(u64, Vec<solana_sdk::pubkey::Pubkey>)> {
+        let pid = *program_id;
+        let commitment = self.commitment;
+
+        self.blocking_rpc(
+            "Listing of program accounts",
+            commitment,
+            move |rpc_client| {
+                let slot = rpc_client
+                    .get_slot_with_commitment(commitment)
+                    .map_err(|e| SolanaIndexerError::rpc(format!("Failed to get slot: {e}")))?;
+                let config = RpcProgramAccountsConfig {
+                    filters: (!filters.is_empty()).then(|| filters.clone()),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        data_slice: Some(UiDataSliceConfig {
+                            offset: 0,
+                            length: 0,
+                        }),
+                        commitment: Some(commitment),
+                        min_context_slot: Some(slot),
+                    },
+                    with_context: None,
+                };
+                let accounts = rpc_client
+                    .get_program_accounts_with_config(&pid, config)
+                    .map_err(|e| {
+                        SolanaIndexerError::rpc(format!("Failed to list program accounts: {e}"))
+                    })?;
+                Ok((slot, accounts.into_iter().map(|(key, _)| key).collect()))
+            },
+        )
+        .await
+    }
+
+    /// Lists the token accounts of `mint` under `token_program`, returning
+    /// each account's owner and raw amount.
+    ///
+    /// Matches the mint at the start of the account data with a `memcmp`
+    /// filter and downloads only the owner and amount (a 40-byte
+    /// `dataSlice`), so mints with many holders stay cheap to list. Also
+    /// returns the slot observed just before the scan.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the RPC request fails.
+    pub async fn get_token_accounts_by_mint(
+        &self,
+        token_program: &solana_sdk::pubkey::Pubkey,
+        mint: &solana_sdk::pubkey::Pubkey,
+    ) -> Result<(
+        u64,
+        Vec<(solana_sdk::pubkey::Pubkey, solana_sdk::pubkey::Pubkey, u64)>,
+    )> {
+        let pid = *token_program;
+        let mint = *mint;
+        let commitment = self.commitment;
+
+        self.blocking_rpc("Listing of token accounts", commitment, move |rpc_client| {
+            let slot = rpc_client
+                .get_slot_with_commitment(commitment)
+                .map_err(|e| SolanaIndexerError::rpc(format!("Failed to get slot: {e}")))?;
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    mint.as_ref(),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: Some(UiDataSliceConfig {
+                        offset: TOKEN_ACCOUNT_OWNER_OFFSET,
+                        length: 40,
+                    }),
+                    commitment: Some(commitment),
+                    min_context_slot: Some(slot),
+                },
+                with_context: None,
+            };
+            let accounts = rpc_client
+                .get_program_accounts_with_config(&pid, config)
+                .map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Failed to list token accounts: {e}"))
+                })?;
+            Ok((
+                slot,
+                accounts
+                    .into_iter()
+                    .filter_map(|(key, account)| {
+                        let owner = account.data.get(..32)?.try_into().ok()?;
+                        let amount = account.data.get(32..40)?.try_into().ok()?;
+                        Some((
+                            key,
+                            solana_sdk::pubkey::Pubkey::new_from_array(owner),
+                            u64::from_le_bytes(amount),
+                        ))
+                    })
+                    .collect(),
+            ))
         })
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
     }
 
     /// Fetches a block with a specific commitment level.
@@ -322,42 +636,38 @@ impl Fetcher {
         slot: u64,
         commitment: CommitmentConfig,
     ) -> Result<UiConfirmedBlock> {
-        let rpc_url = self.rpc_url.clone();
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, commitment);
-            rpc_client
-                .get_block_with_config(
-                    slot,
-                    solana_client::rpc_config::RpcBlockConfig {
-                        encoding: Some(UiTransactionEncoding::Base64),
-                        transaction_details: Some(
-                            solana_transaction_status::TransactionDetails::Full,
-                        ),
-                        rewards: Some(false),
-                        commitment: Some(commitment),
-                        max_supported_transaction_version: Some(0),
-                    },
-                )
-                .map_err(|e| SolanaIndexerError::RpcError(e.to_string()))
-        })
+        self.blocking_rpc_with_timeout(
+            &format!("Fetch of block {slot}"),
+            commitment,
+            self.timeouts().block(),
+            move |rpc_client| {
+                rpc_client
+                    .get_block_with_config(
+                        slot,
+                        solana_client::rpc_config::RpcBlockConfig {
+                            encoding: Some(UiTransactionEncoding::Base64),
+                            transaction_details: Some(
+                                solana_transaction_status::TransactionDetails::Full,
+                            ),
+                            rewards: Some(false),
+                            commitment: Some(commitment),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                    .map_err(|e| SolanaIndexerError::rpc(e.to_string()))
+            },
+        )
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
     }
 
     /// Fetches a block by slot.
     pub async fn fetch_block(&self, slot: u64) -> Result<UiConfirmedBlock> {
-        let rpc_url = self.rpc_url.clone();
-
-        let max_retries = 5;
-        let mut attempt = 0;
-
-        loop {
-            attempt += 1;
-            let rpc_url_clone = rpc_url.clone();
-
-            let result = tokio::task::spawn_blocking(move || {
-                let rpc_client =
-                    RpcClient::new_with_commitment(rpc_url_clone, CommitmentConfig::confirmed());
+        let commitment = CommitmentConfig::confirmed();
+        self.blocking_rpc_with_timeout(
+            &format!("Fetch of block {slot}"),
+            commitment,
+            self.timeouts().block(),
+            move |rpc_client| {
                 // Using get_block_with_encoding
                 let config = solana_client::rpc_config::RpcBlockConfig {
                     encoding: Some(UiTransactionEncoding::JsonParsed),
@@ -367,47 +677,405 @@ impl Fetcher {
                     max_supported_transaction_version: Some(0),
                 };
                 rpc_client.get_block_with_config(slot, config).map_err(|e| {
-                    SolanaIndexerError::RpcError(format!("Failed to fetch block {slot}: {e}"))
+                    SolanaIndexerError::rpc(format!("Failed to fetch block {slot}: {e}"))
                 })
-            })
-            .await
-            .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?;
-
-            match result {
-                Ok(block) => return Ok(block),
-                Err(e) => {
-                    // Check if oversight/skip (optional handling)
-                    // But for general errors:
-                    if attempt >= max_retries {
-                        return Err(e);
-                    }
+            },
+        )
+        .await
+    }
 
-                    let backoff = std::time::Duration::from_millis(100 * (1 << attempt));
-                    tracing::warn!(
-                        "⚠️ Fetch block failed for {slot} (Attempt {attempt}/{max_retries}): {e}. Retrying...",
-                    );
-                    tokio::time::sleep(backoff).await;
+    /// Fetches the events Helius parsed from the transactions of
+    /// `signatures`, batched as far as the API allows, for
+    /// `fetch_enhanced_events` to hand out.
+    ///
+    /// Replaces the events prefetched before. A failed request is logged and
+    /// leaves the signatures without events, so their transactions are
+    /// processed from the raw transaction alone.
+    #[cfg(feature = "helius")]
+    pub async fn prefetch_enhanced_events(&self, signatures: &[Signature]) {
+        let Some(client) = &self.enhanced else {
+            return;
+        };
+        let mut events: std::collections::HashMap<_, _> = signatures
+            .iter()
+            .map(|signature| (*signature, Vec::new()))
+            .collect();
+        match client.fetch_transactions(signatures).await {
+            Ok(transactions) => {
+                for tx in &transactions {
+                    let prefetched = tx
+                        .signature
+                        .parse::<Signature>()
+                        .ok()
+                        .and_then(|signature| events.get_mut(&signature));
+                    if let Some(prefetched) = prefetched {
+                        prefetched.extend(tx.parsed_events());
+                    }
                 }
             }
+            Err(e) => crate::utils::logging::log(
+                crate::utils::logging::LogLevel::Warning,
+                &format!(
+                    "Helius enhanced transactions unavailable, processing {} transactions \
+                     without them: {e}",
+                    signatures.len()
+                ),
+            ),
         }
+        if let Ok(mut prefetched) = self.prefetched_events.lock() {
+            *prefetched = events;
+        }
+    }
+
+    /// Fetches the events Helius parsed from a transaction, taking them
+    /// from `prefetch_enhanced_events` when they were fetched ahead.
+    ///
+    /// Returns no events unless the fetcher was configured with
+    /// `with_helius_enhanced`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the Helius request fails.
+    #[cfg(feature = "helius")]
+    pub async fn fetch_enhanced_events(
+        &self,
+        signature: &Signature,
+    ) -> Result<Vec<crate::types::events::ParsedEvent>> {
+        let Some(client) = &self.enhanced else {
+            return Ok(Vec::new());
+        };
+        let prefetched = self
+            .prefetched_events
+            .lock()
+            .ok()
+            .and_then(|mut prefetched| prefetched.remove(signature));
+        if let Some(events) = prefetched {
+            return Ok(events);
+        }
+        let transactions = client.fetch_transactions(&[*signature]).await?;
+        Ok(transactions
+            .iter()
+            .flat_map(|tx| tx.parsed_events())
+            .collect())
+    }
+
+    /// Fetches up to `limit` signatures involving `address`, newest first.
+    ///
+    /// Pages through `getSignaturesForAddress` (at most 1000 per request)
+    /// until `limit` signatures are collected or the history is exhausted.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The program or account address to query
+    /// * `before` - Only return signatures older than this one
+    /// * `limit` - Maximum number of signatures to return
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if a request fails.
+    pub async fn fetch_signatures(
+        &self,
+        address: &solana_sdk::pubkey::Pubkey,
+        before: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<Signature>> {
+        const MAX_PAGE: usize = 1000;
+
+        let mut signatures = Vec::new();
+        let mut before = before;
+        while signatures.len() < limit {
+            let address = *address;
+            let page_limit = (limit - signatures.len()).min(MAX_PAGE);
+            let commitment = self.commitment;
+            let page = self
+                .blocking_rpc_with_timeout(
+                    &format!("Signature lookup for {address}"),
+                    commitment,
+                    self.timeouts().signatures(),
+                    move |rpc_client| {
+                        rpc_client
+                            .get_signatures_for_address_with_config(
+                                &address,
+                                GetConfirmedSignaturesForAddress2Config {
+                                    before,
+                                    until: None,
+                                    limit: Some(page_limit),
+                                    commitment: Some(commitment),
+                                },
+                            )
+                            .map_err(|e| {
+                                SolanaIndexerError::rpc(format!(
+                                    "Failed to fetch signatures for {address}: {e}"
+                                ))
+                            })
+                    },
+                )
+                .await?;
+
+            let fetched = page.len();
+            signatures.extend(
+                page.iter()
+                    .filter_map(|status| status.signature.parse::<Signature>().ok()),
+            );
+            before = signatures.last().copied();
+            if fetched < page_limit || before.is_none() {
+                break;
+            }
+        }
+
+        Ok(signatures)
+    }
+
+    /// Fetches one page of confirmed signatures for `address`, newest first,
+    /// stopping at `until`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the request still fails after
+    /// the backoff policy's retries.
+    pub async fn fetch_signature_page(
+        &self,
+        address: &solana_sdk::pubkey::Pubkey,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>> {
+        let address = *address;
+        let commitment = CommitmentConfig::confirmed();
+        self.blocking_rpc_with_timeout(
+            &format!("Signature poll for {address}"),
+            commitment,
+            self.timeouts().signatures(),
+            move |rpc_client| {
+                rpc_client
+                    .get_signatures_for_address_with_config(
+                        &address,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before: None,
+                            until,
+                            limit: Some(limit),
+                            commitment: Some(commitment),
+                        },
+                    )
+                    .map_err(|e| {
+                        SolanaIndexerError::rpc(format!(
+                            "Failed to poll signatures for {address}: {e}"
+                        ))
+                    })
+            },
+        )
+        .await
+    }
+
+    /// Looks up the status of up to 256 signatures with
+    /// `getSignatureStatuses`.
+    ///
+    /// Only the node's recent status cache is searched, so signatures that
+    /// are unknown or too old come back as `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the request fails.
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<Option<solana_transaction_status::TransactionStatus>>> {
+        let signatures = signatures.to_vec();
+        self.blocking_rpc(
+            "Signature status lookup",
+            self.commitment,
+            move |rpc_client| {
+                rpc_client
+                    .get_signature_statuses(&signatures)
+                    .map(|response| response.value)
+                    .map_err(|e| {
+                        SolanaIndexerError::rpc(format!("Failed to fetch signature statuses: {e}"))
+                    })
+            },
+        )
+        .await
+    }
+
+    /// Returns the index of `signature` within the block at `slot`.
+    ///
+    /// The block's signature list is fetched once per slot and kept for
+    /// the most recent slots, so looking up every transaction of a block
+    /// costs a single `getBlock`. Returns `None` if the signature is not in
+    /// the block.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the block cannot be fetched.
+    pub async fn transaction_index(&self, slot: u64, signature: &str) -> Result<Option<u32>> {
+        let cell = {
+            let mut orders = self
+                .block_orders
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if let Some((_, cell)) = orders.iter().find(|(s, _)| *s == slot) {
+                cell.clone()
+            } else {
+                let cell = Arc::new(OnceCell::new());
+                orders.push_back((slot, cell.clone()));
+                if orders.len() > BLOCK_ORDER_SLOTS {
+                    orders.pop_front();
+                }
+                cell
+            }
+        };
+        let signatures = cell
+            .get_or_try_init(|| self.fetch_block_signatures(slot))
+            .await?;
+        Ok(signatures
+            .iter()
+            .position(|s| s == signature)
+            .and_then(|i| u32::try_from(i).ok()))
+    }
+
+    /// Fetches the signatures of a block, in block order.
+    async fn fetch_block_signatures(&self, slot: u64) -> Result<Vec<String>> {
+        let commitment = self.commitment;
+        let block = self
+            .blocking_rpc_with_timeout(
+                &format!("Fetch of block {slot} signatures"),
+                commitment,
+                self.timeouts().block(),
+                move |rpc_client| {
+                    rpc_client
+                        .get_block_with_config(
+                            slot,
+                            solana_client::rpc_config::RpcBlockConfig {
+                                encoding: None,
+                                transaction_details: Some(
+                                    solana_transaction_status::TransactionDetails::Signatures,
+                                ),
+                                rewards: Some(false),
+                                commitment: Some(commitment),
+                                max_supported_transaction_version: Some(0),
+                            },
+                        )
+                        .map_err(|e| {
+                            SolanaIndexerError::rpc(format!("Failed to fetch block {slot}: {e}"))
+                        })
+                },
+            )
+            .await?;
+        Ok(block.signatures.unwrap_or_default())
+    }
+
+    /// Returns the first finalized slot at or after `slot` that produced a
+    /// block, or `None` if there is none yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if `getBlocksWithLimit` fails.
+    pub async fn first_block_from(&self, slot: u64) -> Result<Option<u64>> {
+        let commitment = CommitmentConfig::finalized();
+        self.blocking_rpc("Block lookup", commitment, move |rpc_client| {
+            rpc_client
+                .get_blocks_with_limit_and_commitment(slot, 1, commitment)
+                .map(|blocks| blocks.first().copied())
+                .map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Failed to list blocks from {slot}: {e}"))
+                })
+        })
+        .await
+    }
+
+    /// Returns the estimated production time of the block at `slot`, as a
+    /// Unix timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if `getBlockTime` fails, e.g. for a
+    /// skipped slot.
+    pub async fn get_block_time(&self, slot: u64) -> Result<i64> {
+        let commitment = CommitmentConfig::finalized();
+        self.blocking_rpc("Block time lookup", commitment, move |rpc_client| {
+            rpc_client.get_block_time(slot).map_err(|e| {
+                SolanaIndexerError::rpc(format!("Failed to get block time of {slot}: {e}"))
+            })
+        })
+        .await
+    }
+
+    /// Returns the lowest slot the node still has a block for.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if `getFirstAvailableBlock` fails.
+    pub async fn get_first_available_block(&self) -> Result<u64> {
+        let commitment = CommitmentConfig::finalized();
+        self.blocking_rpc("First available block lookup", commitment, |rpc_client| {
+            rpc_client.get_first_available_block().map_err(|e| {
+                SolanaIndexerError::rpc(format!("Failed to get first available block: {e}"))
+            })
+        })
+        .await
     }
 
     /// Gets the latest finalized slot.
     pub async fn get_latest_finalized_slot(&self) -> Result<u64> {
-        let rpc_url = self.rpc_url.clone();
-
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+        let commitment = CommitmentConfig::confirmed();
+        self.blocking_rpc("Latest finalized slot lookup", commitment, |rpc_client| {
             rpc_client
                 .get_slot_with_commitment(CommitmentConfig::finalized())
                 .map_err(|e| {
-                    SolanaIndexerError::RpcError(format!(
-                        "Failed to get latest finalized slot: {e}"
-                    ))
+                    SolanaIndexerError::rpc(format!("Failed to get latest finalized slot: {e}"))
+                })
+        })
+        .await
+    }
+
+    /// Returns the per-call timeouts of the transport.
+    fn timeouts(&self) -> RpcTimeouts {
+        self.transport.network().timeouts
+    }
+
+    /// Runs a blocking RPC call on the blocking thread pool, retrying failures
+    /// according to the configured backoff policy.
+    async fn blocking_rpc<T, F>(
+        &self,
+        context: &str,
+        commitment: CommitmentConfig,
+        call: F,
+    ) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&RpcClient) -> Result<T> + Send + Sync + 'static,
+    {
+        self.blocking_rpc_with_timeout(context, commitment, RPC_TIMEOUT, call)
+            .await
+    }
+
+    /// Like `blocking_rpc`, with each attempt timing out after `timeout`.
+    async fn blocking_rpc_with_timeout<T, F>(
+        &self,
+        context: &str,
+        commitment: CommitmentConfig,
+        timeout: std::time::Duration,
+        call: F,
+    ) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&RpcClient) -> Result<T> + Send + Sync + 'static,
+    {
+        let call = Arc::new(call);
+        retry_with_backoff(&self.backoff, context, || {
+            let rpc_url = self.rpc_url.clone();
+            let transport = self.transport.clone();
+            let call = call.clone();
+            usage::record_rpc_calls(1);
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    let rpc_client =
+                        transport.rpc_client_with_timeout(rpc_url, commitment, timeout)?;
+                    call(&rpc_client)
                 })
+                .await
+                .map_err(|e| SolanaIndexerError::Internal(format!("Task join error: {e}")))?
+            }
         })
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))?
     }
 }
 
@@ -433,4 +1101,153 @@ mod tests {
         );
         assert_eq!(fetcher.rpc_url, "http://localhost:8899");
     }
+
+    fn raw_tx(
+        account_keys: &[&str],
+        err: Option<solana_sdk::transaction::TransactionError>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        use solana_sdk::message::MessageHeader;
+        use solana_transaction_status::{
+            EncodedTransactionWithStatusMeta, UiRawMessage, UiTransaction, UiTransactionStatusMeta,
+        };
+
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: 1,
+            block_time: None,
+            transaction: EncodedTransactionWithStatusMeta {
+                version: None,
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: vec![Signature::default().to_string()],
+                    message: UiMessage::Raw(UiRawMessage {
+                        header: MessageHeader::default(),
+                        account_keys: account_keys.iter().map(ToString::to_string).collect(),
+                        recent_blockhash: String::new(),
+                        instructions: vec![],
+                        address_table_lookups: None,
+                    }),
+                }),
+                meta: Some(UiTransactionStatusMeta {
+                    err: err.clone(),
+                    status: err.map_or(Ok(()), Err),
+                    fee: 5000,
+                    pre_balances: vec![],
+                    post_balances: vec![],
+                    inner_instructions: OptionSerializer::None,
+                    log_messages: OptionSerializer::None,
+                    pre_token_balances: OptionSerializer::None,
+                    post_token_balances: OptionSerializer::None,
+                    rewards: OptionSerializer::None,
+                    loaded_addresses: OptionSerializer::Skip,
+                    return_data: OptionSerializer::Skip,
+                    compute_units_consumed: OptionSerializer::Skip,
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_transaction_error() {
+        let ok = raw_tx(&["11111111111111111111111111111111"], None);
+        assert_eq!(transaction_error(&ok), None);
+
+        let failed = raw_tx(
+            &["11111111111111111111111111111111"],
+            Some(solana_sdk::transaction::TransactionError::InstructionError(
+                0,
+                solana_sdk::instruction::InstructionError::Custom(1),
+            )),
+        );
+        let err = transaction_error(&failed).expect("error should be present");
+        assert!(err.contains("Custom(1)"));
+    }
+
+    #[test]
+    fn test_transaction_index() {
+        let block = |signatures: Option<Vec<String>>, transactions| UiConfirmedBlock {
+            previous_blockhash: String::new(),
+            blockhash: String::new(),
+            parent_slot: 0,
+            transactions,
+            signatures,
+            rewards: None,
+            block_time: None,
+            block_height: None,
+        };
+        let sig = Signature::default().to_string();
+
+        let by_signature = block(Some(vec!["other".to_string(), sig.clone()]), None);
+        assert_eq!(transaction_index(&by_signature, &sig), Some(1));
+        assert_eq!(transaction_index(&by_signature, "missing"), None);
+
+        let tx = raw_tx(&["11111111111111111111111111111111"], None).transaction;
+        let by_transaction = block(None, Some(vec![tx]));
+        assert_eq!(transaction_index(&by_transaction, &sig), Some(0));
+    }
+
+    #[test]
+    fn test_is_vote_transaction() {
+        let vote = raw_tx(
+            &[
+                "11111111111111111111111111111111",
+                "Vote111111111111111111111111111111111111111",
+            ],
+            None,
+        );
+        assert!(is_vote_transaction(&vote));
+
+        let other = raw_tx(&["11111111111111111111111111111111"], None);
+        assert!(!is_vote_transaction(&other));
+    }
+
+    #[cfg(feature = "helius")]
+    #[tokio::test]
+    async fn test_enhanced_events_are_prefetched_per_poll() -> Result<()> {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let first = Signature::new_unique();
+        let second = Signature::new_unique();
+        let body = serde_json::json!([{
+            "signature": first.to_string(),
+            "slot": 1,
+            "nativeTransfers": [{"fromUserAccount": "a", "toUserAccount": "b", "amount": 5}]
+        }]);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(body))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let fetcher = Fetcher::new(server.uri(), CommitmentConfig::confirmed())
+            .with_helius_enhanced(server.uri());
+
+        // One request covers the poll; a signature Helius omits has no events
+        fetcher.prefetch_enhanced_events(&[first, second]).await;
+        assert_eq!(fetcher.fetch_enhanced_events(&first).await?.len(), 1);
+        assert!(fetcher.fetch_enhanced_events(&second).await?.is_empty());
+        Ok(())
+    }
+
+    #[cfg(feature = "helius")]
+    #[tokio::test]
+    async fn test_failed_enhanced_prefetch_falls_back_to_raw() -> Result<()> {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let fetcher = Fetcher::new(server.uri(), CommitmentConfig::confirmed())
+            .with_helius_enhanced(server.uri());
+
+        let signatures = [Signature::new_unique(), Signature::new_unique()];
+        fetcher.prefetch_enhanced_events(&signatures).await;
+        for signature in &signatures {
+            assert!(fetcher.fetch_enhanced_events(signature).await?.is_empty());
+        }
+        Ok(())
+    }
 }
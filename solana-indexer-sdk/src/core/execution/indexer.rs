@@ -3,41 +3,110 @@
 //! This module provides the `SolanaIndexer` struct that orchestrates the complete
 //! indexing pipeline: polling, fetching, decoding, deduplication, and event handling.
 
-use crate::config::SourceConfig;
+use crate::config::{CommitmentLevel, MissingMetaPolicy, OversizedAction, SourceConfig};
+use crate::core::execution::fetcher::{
+    account_keys, inner_instructions, instructions, is_vote_transaction, transaction_error,
+    transaction_index,
+};
+use crate::core::pipeline::Pipeline;
+use crate::sinks::EventSink;
 use crate::{
     config::{SolanaIndexerConfig, StartStrategy},
     core::{
-        backfill::defaults::*, backfill::engine::BackfillEngine,
-        backfill::manager::BackfillManager, decoding::Decoder, execution::fetcher::Fetcher,
-        registry::account::AccountDecoderRegistry, registry::logs::LogDecoderRegistry,
-        registry::DecoderRegistry,
+        backfill::defaults::*,
+        backfill::engine::BackfillEngine,
+        backfill::gaps::{SignatureGapDetector, SlotGapAlarm},
+        backfill::manager::BackfillManager,
+        backfill::status::BackfillStatus,
+        backfill::time::slot_at_time,
+        decoding::{
+            anchor::AnchorEventDecoder, diff::AccountDiffDecoder, discriminator::InstructionRouter,
+            Decoder,
+        },
+        execution::confirmations::ConfirmationTracker,
+        execution::cursors::{settled_cursor, CursorSchedule},
+        execution::failover::{ErrorObserver, Escalation, FailoverLadder},
+        execution::fetcher::Fetcher,
+        execution::observer::{self, PipelineObserver},
+        execution::ordering::{KeyedExecutor, OrderingKey},
+        execution::prefilter::{self, Prefilter, TransactionPreview},
+        execution::schedule::{PeriodicTask, ScheduledTask},
+        execution::snapshot::{SnapshotBootstrapper, SnapshotSummary},
+        execution::usage::{self, UsageTracker},
+        execution::watermark::WatermarkNotifier,
+        registry::account::AccountDecoderRegistry,
+        registry::filter::FilteredHandler,
+        registry::handle::RegistryHandle,
+        registry::limit::{RateLimit, RateLimitedHandler},
+        registry::logs::LogDecoderRegistry,
+        registry::metrics::RegistryMetrics,
+        registry::retry::RetryPolicy,
+        registry::toggles::HandlerToggles,
+        registry::{chain::ChainControl, DecoderRegistry, ProgramScope},
     },
-    storage::{Storage, StorageBackend},
-    streams::TransactionSource,
+    idl::{registry::IdlRegistry, validate},
+    storage::{
+        archive::{ReplaySummary, TransactionArchive},
+        atomic::TxWrites,
+        batch_watermark,
+        buffer::WriteBuffer,
+        cache::ProcessedCache,
+        cold::{self, ColdArchive, ColdArchiveConfig, SegmentStore},
+        cursor::{CursorStorage, CursorStore},
+        dlq::DeadLetterQueue,
+        flags::FeatureFlags,
+        holders::{self, TokenHolders, TOKEN_PROGRAM_IDS},
+        jobs::{DeferredJob, DeferredJobs, DynamicJobHandler, JobHandler, TypedJobHandler},
+        outbox::OutboxRelay,
+        partitions,
+        replica::ReplicatedStorage,
+        retention::{self, RetentionMetrics},
+        spill::{self, SpillQueue, SpilledTransaction},
+        state::{AccountStateTable, StateDecoder, StateHandler},
+        stats::{self, TableStats},
+        unhandled::UnhandledEvents,
+        vacuum::{self, VacuumMetrics},
+        Storage, StorageBackend,
+    },
+    streams::{slots::SlotTracker, TransactionSource},
+    testing::replay,
     types::{
         backfill_traits::{
             BackfillHandlerRegistry, BackfillRange, BackfillTrigger, FinalizedBlockTracker,
         },
-        metadata::{TokenBalanceInfo, TxMetadata},
+        events::calculate_discriminator,
+        metadata::{TransactionAccount, TxMetadata},
+        schema::DataDictionary,
         traits::{HandlerRegistry, SchemaInitializer},
     },
     utils::{
+        backoff::{poll_jitter, ErrorClass},
         error::{Result, SolanaIndexerError},
         logging,
+        macros::Idl,
+        network::HttpTransport,
     },
 };
 
+#[cfg(feature = "local-postgres")]
+use crate::storage::local_postgres::{LocalPostgres, LOCAL_DATABASE_URL};
 #[cfg(feature = "helius")]
 use crate::streams::helius::HeliusSource;
 
 #[cfg(feature = "websockets")]
 use crate::streams::websocket::WebSocketSource;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tokio::time::{interval, Duration};
 
+/// Number of processed signatures `replay` reads per query.
+const REPLAY_PAGE_SIZE: usize = 500;
+
 /// Main indexer that orchestrates the complete pipeline.
 ///
 /// The `SolanaIndexer` integrates all components to provide a complete,
@@ -79,6 +148,32 @@ pub struct SolanaIndexer {
     backfill_handler_registry: Arc<BackfillHandlerRegistry>,
     backfill_trigger: Option<Arc<dyn BackfillTrigger>>,
     schema_initializers: Vec<Box<dyn SchemaInitializer>>,
+    prefilters: Vec<Arc<dyn Prefilter>>,
+    ordering: Option<OrderingKey>,
+    slot_tracker: SlotTracker,
+    write_buffer: Option<Arc<WriteBuffer>>,
+    usage: Option<Arc<UsageTracker>>,
+    retention_metrics: Arc<RetentionMetrics>,
+    vacuum_metrics: Arc<VacuumMetrics>,
+    table_stats: Arc<TableStats>,
+    replica: Option<Arc<ReplicatedStorage>>,
+    watermark: Arc<WatermarkNotifier>,
+    processed_cache: Option<Arc<ProcessedCache>>,
+    spill_queue: Option<Arc<SpillQueue>>,
+    unhandled_events: Option<Arc<UnhandledEvents>>,
+    dead_letter_queue: Option<Arc<DeadLetterQueue>>,
+    cold_archive: Option<Arc<ColdArchive>>,
+    scheduled_tasks: Vec<ScheduledTask>,
+    deferred_jobs: Option<DeferredJobs>,
+    job_handlers: HashMap<String, Arc<dyn DynamicJobHandler>>,
+    outbox: Option<OutboxRelay>,
+    idl_registry: IdlRegistry,
+    expected_idls: Vec<Idl>,
+    error_observer: Option<Arc<dyn ErrorObserver>>,
+    pipeline_observer: Option<Arc<dyn PipelineObserver>>,
+    slot_gap_alarm: Option<SlotGapAlarm>,
+    #[cfg(feature = "local-postgres")]
+    local_postgres: Option<Arc<LocalPostgres>>,
     cancellation_token: tokio_util::sync::CancellationToken,
 }
 
@@ -109,54 +204,210 @@ impl SolanaIndexer {
     /// # }
     /// ```
     pub async fn new(config: SolanaIndexerConfig) -> Result<Self> {
-        let storage = Arc::new(Storage::new(&config.database_url).await?);
+        #[cfg(feature = "local-postgres")]
+        let mut config = config;
+        #[cfg(feature = "local-postgres")]
+        let local_postgres = Self::start_local_postgres(&mut config).await?;
+        let (storage, write_buffer) = Self::open_storage(&config).await?;
+        let (storage, replica) = Self::replicate(&config, storage).await?;
         storage.initialize().await?;
 
-        let fetcher = Arc::new(Fetcher::new(
-            config.rpc_url(),
-            config.commitment_level.into(),
-        ));
-        let decoder = Arc::new(Decoder::new());
-        let decoder_registry = Arc::new(DecoderRegistry::new_bounded(&config.registry));
-        let log_decoder_registry = Arc::new(LogDecoderRegistry::new_bounded(&config.registry));
-        let account_decoder_registry =
-            Arc::new(AccountDecoderRegistry::new_bounded(&config.registry));
-        let handler_registry = Arc::new(HandlerRegistry::new_bounded(&config.registry));
-        let backfill_handler_registry =
-            Arc::new(BackfillHandlerRegistry::new_bounded(&config.registry));
+        let mut indexer = Self::wire_optional_components(config, storage);
+        indexer.write_buffer = write_buffer;
+        indexer.replica = replica;
+        #[cfg(feature = "local-postgres")]
+        {
+            indexer.local_postgres = local_postgres;
+        }
+        Ok(indexer)
+    }
 
-        Ok(Self {
-            config,
-            storage,
-            fetcher,
-            decoder,
-            decoder_registry,
-            log_decoder_registry,
-            account_decoder_registry,
-            handler_registry,
-            backfill_handler_registry,
-            backfill_trigger: None,
-            schema_initializers: Vec::new(),
-            cancellation_token: tokio_util::sync::CancellationToken::new(),
+    /// Starts a throwaway server when `config` asks for one with
+    /// `LOCAL_DATABASE_URL`, pointing `config` at it.
+    #[cfg(feature = "local-postgres")]
+    async fn start_local_postgres(
+        config: &mut SolanaIndexerConfig,
+    ) -> Result<Option<Arc<LocalPostgres>>> {
+        if config.database_url != LOCAL_DATABASE_URL {
+            return Ok(None);
+        }
+        let server = LocalPostgres::start().await?;
+        config.database_url = server.url();
+        Ok(Some(Arc::new(server)))
+    }
+
+    /// Opens the storage backend selected by the scheme of the database URL,
+    /// behind a write buffer when one is configured for `PostgreSQL`.
+    async fn open_storage(
+        config: &SolanaIndexerConfig,
+    ) -> Result<(Arc<dyn StorageBackend>, Option<Arc<WriteBuffer>>)> {
+        let database_url = config.database_url.as_str();
+        #[cfg(feature = "sqlite")]
+        if database_url.starts_with("sqlite:") {
+            let mut storage = crate::storage::sqlite::SqliteStorage::new(database_url).await?;
+            if let Some(url) = &config.handler_database_url {
+                let pool = sqlx::postgres::PgPoolOptions::new()
+                    .max_connections(5)
+                    .acquire_timeout(Duration::from_secs(3))
+                    .connect_lazy(url)?;
+                storage = storage.with_handler_pool(pool);
+            }
+            return Ok((Arc::new(storage), None));
+        }
+
+        let mut storage = match &config.db_schema {
+            Some(schema) => Storage::new_in_schema(database_url, schema).await?,
+            None => Storage::new(database_url).await?,
+        };
+        if let Some(partitioning) = config.partitioning {
+            storage = storage.with_partitioning(partitioning);
+        }
+        Ok(match config.write_buffer {
+            Some(policy) => {
+                let buffer = Arc::new(WriteBuffer::new(storage, policy));
+                (buffer.clone(), Some(buffer))
+            }
+            None => (Arc::new(storage), None),
         })
     }
 
+    /// Mirrors the writes made to `storage` to the replica database, when
+    /// one is configured.
+    async fn replicate(
+        config: &SolanaIndexerConfig,
+        storage: Arc<dyn StorageBackend>,
+    ) -> Result<(Arc<dyn StorageBackend>, Option<Arc<ReplicatedStorage>>)> {
+        let Some(url) = &config.replica_database_url else {
+            return Ok((storage, None));
+        };
+        let mut standby = match &config.db_schema {
+            Some(schema) => Storage::new_in_schema(url, schema).await?,
+            None => Storage::new(url).await?,
+        };
+        if let Some(partitioning) = config.partitioning {
+            standby = standby.with_partitioning(partitioning);
+        }
+        let replica = Arc::new(ReplicatedStorage::new(storage, Arc::new(standby)));
+        Ok((replica.clone(), Some(replica)))
+    }
+
+    /// Puts the processed-signature cache in front of `storage`, unless it
+    /// is disabled.
+    fn cache_processed(
+        config: &SolanaIndexerConfig,
+        storage: Arc<dyn StorageBackend>,
+    ) -> (Arc<dyn StorageBackend>, Option<Arc<ProcessedCache>>) {
+        if config.processed_cache == 0 {
+            return (storage, None);
+        }
+        let cache = Arc::new(ProcessedCache::new(storage, config.processed_cache));
+        (cache.clone(), Some(cache))
+    }
+
+    /// Creates the fetcher described by `config`.
+    fn build_fetcher(config: &SolanaIndexerConfig) -> Fetcher {
+        let fetcher = Fetcher::new(config.rpc_url(), config.commitment_level.into())
+            .with_backoff(config.backoff)
+            .with_transport(HttpTransport::new(config.network.clone()));
+        #[cfg(feature = "helius")]
+        if let Some(url) = config.helius_enhanced_url() {
+            return fetcher.with_helius_enhanced(url);
+        }
+        fetcher
+    }
+
     /// Creates a new indexer instance with a custom storage backend.
     ///
     /// This is useful for testing with mock storage.
     pub fn new_with_storage(config: SolanaIndexerConfig, storage: Arc<dyn StorageBackend>) -> Self {
-        let fetcher = Arc::new(Fetcher::new(
-            config.rpc_url(),
-            config.commitment_level.into(),
-        ));
+        Self::wire_optional_components(config, storage)
+    }
+
+    /// Builds the indexer around `storage`, wiring in the optional
+    /// components `config` turns on. The write buffer, replica and local
+    /// server are opened by `new` and left unset here.
+    fn wire_optional_components(
+        config: SolanaIndexerConfig,
+        storage: Arc<dyn StorageBackend>,
+    ) -> Self {
+        let (storage, processed_cache) = Self::cache_processed(&config, storage);
+        let fetcher = Arc::new(Self::build_fetcher(&config));
         let decoder = Arc::new(Decoder::new());
         let decoder_registry = Arc::new(DecoderRegistry::new_bounded(&config.registry));
         let log_decoder_registry = Arc::new(LogDecoderRegistry::new_bounded(&config.registry));
         let account_decoder_registry =
             Arc::new(AccountDecoderRegistry::new_bounded(&config.registry));
-        let handler_registry = Arc::new(HandlerRegistry::new_bounded(&config.registry));
-        let backfill_handler_registry =
-            Arc::new(BackfillHandlerRegistry::new_bounded(&config.registry));
+        let usage = config.usage_tracking.then(|| Arc::new(UsageTracker::new()));
+        let slot_gap_alarm = config.slot_gap_alarm.map(SlotGapAlarm::new);
+        let mut handler_registry = HandlerRegistry::new_bounded(&config.registry);
+        if let Some(tracker) = &usage {
+            handler_registry = handler_registry.with_usage_tracker(tracker.clone());
+        }
+        let spill_queue = config
+            .spill_queue
+            .clone()
+            .map(|path| Arc::new(SpillQueue::new(path)));
+        if let Some(queue) = &spill_queue {
+            handler_registry = handler_registry.with_spill_queue(queue.clone());
+        }
+        let unhandled_events = config
+            .unhandled_events
+            .then(|| Arc::new(UnhandledEvents::new()));
+        if let Some(unhandled) = &unhandled_events {
+            handler_registry = handler_registry.with_unhandled_events(unhandled.clone());
+        }
+        let dead_letter_queue = config
+            .dead_letter_queue
+            .then(|| Arc::new(DeadLetterQueue::new()));
+        if let Some(queue) = &dead_letter_queue {
+            handler_registry = handler_registry.with_dead_letter_queue(queue.clone());
+        }
+        let feature_flags = config
+            .feature_flags_refresh_ms
+            .zip(storage.pool())
+            .map(|(ms, pool)| FeatureFlags::new(pool.clone(), Duration::from_millis(ms)));
+        let mut backfill_handler_registry = BackfillHandlerRegistry::new_bounded(&config.registry);
+        if let Some(flags) = &feature_flags {
+            handler_registry = handler_registry.with_feature_flags(flags.clone());
+            backfill_handler_registry = backfill_handler_registry.with_feature_flags(flags.clone());
+        }
+        let idl_registry = IdlRegistry::new(fetcher.clone());
+        let deferred_jobs = config
+            .deferred_jobs_poll_ms
+            .and(storage.pool())
+            .map(|pool| DeferredJobs::new(pool.clone()));
+        if let Some(jobs) = &deferred_jobs {
+            handler_registry = handler_registry.with_deferred_jobs(jobs.clone());
+            backfill_handler_registry = backfill_handler_registry.with_deferred_jobs(jobs.clone());
+        }
+        let handler_registry = Arc::new(handler_registry);
+        let backfill_handler_registry = Arc::new(backfill_handler_registry);
+        let mut schema_initializers: Vec<Box<dyn SchemaInitializer>> = Vec::new();
+        if let Some(flags) = feature_flags {
+            schema_initializers.push(Box::new(flags));
+        }
+        if let Some(jobs) = &deferred_jobs {
+            schema_initializers.push(Box::new(jobs.clone()));
+        }
+        let outbox = config
+            .outbox_poll_ms
+            .and(storage.pool())
+            .map(|pool| OutboxRelay::new(pool.clone()));
+        if let Some(relay) = &outbox {
+            schema_initializers.push(Box::new(relay.clone()));
+        }
+        if let Some(unhandled) = &unhandled_events {
+            schema_initializers.push(Box::new(unhandled.clone()));
+        }
+        if let Some(queue) = &dead_letter_queue {
+            schema_initializers.push(Box::new(queue.clone()));
+        }
+        if config.archive_transactions {
+            schema_initializers.push(Box::new(TransactionArchive));
+        }
+        if !config.token_holder_mints.is_empty() {
+            schema_initializers.push(Box::new(TokenHolders));
+        }
 
         Self {
             config,
@@ -169,7 +420,33 @@ impl SolanaIndexer {
             handler_registry,
             backfill_handler_registry,
             backfill_trigger: None,
-            schema_initializers: Vec::new(),
+            schema_initializers,
+            prefilters: Vec::new(),
+            ordering: None,
+            slot_tracker: SlotTracker::new(),
+            write_buffer: None,
+            usage,
+            retention_metrics: Arc::new(RetentionMetrics::new()),
+            vacuum_metrics: Arc::new(VacuumMetrics::new()),
+            table_stats: Arc::new(TableStats::new()),
+            replica: None,
+            watermark: Arc::new(WatermarkNotifier::new()),
+            processed_cache,
+            spill_queue,
+            unhandled_events,
+            dead_letter_queue,
+            cold_archive: None,
+            scheduled_tasks: Vec::new(),
+            deferred_jobs,
+            job_handlers: HashMap::new(),
+            outbox,
+            idl_registry,
+            expected_idls: Vec::new(),
+            error_observer: None,
+            pipeline_observer: None,
+            slot_gap_alarm,
+            #[cfg(feature = "local-postgres")]
+            local_postgres: None,
             cancellation_token: tokio_util::sync::CancellationToken::new(),
         }
     }
@@ -180,20 +457,238 @@ impl SolanaIndexer {
         &self.config
     }
 
+    /// Returns the write buffer configured with `with_write_buffer`, if any.
+    ///
+    /// Handlers can hold on to it and `queue` their inserts, which then
+    /// commit in the same transaction as the signatures they belong to.
+    #[must_use]
+    pub fn write_buffer(&self) -> Option<Arc<WriteBuffer>> {
+        self.write_buffer.clone()
+    }
+
+    /// Returns the totals of rows and bytes pruned under the retention
+    /// policy set with `with_retention`.
+    #[must_use]
+    pub fn retention_metrics(&self) -> Arc<RetentionMetrics> {
+        self.retention_metrics.clone()
+    }
+
+    /// Returns the runs and table bloat of the vacuum schedule set with
+    /// `with_vacuum`.
+    #[must_use]
+    pub fn vacuum_metrics(&self) -> Arc<VacuumMetrics> {
+        self.vacuum_metrics.clone()
+    }
+
+    /// Returns the latest row counts of the handler tables, refreshed as set
+    /// with `with_table_stats`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # fn example(indexer: &SolanaIndexer) {
+    /// let stats = indexer.table_stats();
+    /// if let Some(transfers) = stats.table("transfers") {
+    ///     println!("{} rows up to slot {:?}", transfers.rows, transfers.max_slot);
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn table_stats(&self) -> Arc<TableStats> {
+        self.table_stats.clone()
+    }
+
+    /// Returns the replication to the database set with
+    /// `with_replica_database`, if any.
+    #[must_use]
+    pub fn replica(&self) -> Option<Arc<ReplicatedStorage>> {
+        self.replica.clone()
+    }
+
+    /// Returns the notifier that publishes each advance of the
+    /// processed-slot watermark.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # fn example(indexer: &SolanaIndexer) {
+    /// let mut watermark = indexer.watermark().subscribe();
+    /// tokio::spawn(async move {
+    ///     while watermark.changed().await.is_ok() {
+    ///         let slot = *watermark.borrow_and_update();
+    ///         println!("rows up to slot {slot:?} are complete");
+    ///     }
+    /// });
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn watermark(&self) -> Arc<WatermarkNotifier> {
+        self.watermark.clone()
+    }
+
+    /// Returns the fallback for events without a handler, enabled with
+    /// `with_unhandled_event_storage`, if any.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{SolanaIndexer, TransferEvent};
+    /// # fn example(indexer: &SolanaIndexer) {
+    /// if let Some(unhandled) = indexer.unhandled_events() {
+    ///     unhandled.describe::<TransferEvent>();
+    /// }
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn unhandled_events(&self) -> Option<Arc<UnhandledEvents>> {
+        self.unhandled_events.clone()
+    }
+
+    /// Returns the dead letter queue enabled with `with_dead_letter_queue`,
+    /// if any.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # async fn example(indexer: &SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+    /// if let Some(dlq) = indexer.dead_letter_queue() {
+    ///     let db = indexer.storage().require_pool()?;
+    ///     for entry in dlq.list(db, 10).await? {
+    ///         dlq.reprocess(indexer.handler_registry(), db, entry.id).await?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn dead_letter_queue(&self) -> Option<Arc<DeadLetterQueue>> {
+        self.dead_letter_queue.clone()
+    }
+
+    /// Returns the local server started because no database URL was
+    /// configured, if any.
+    #[cfg(feature = "local-postgres")]
+    #[must_use]
+    pub fn local_postgres(&self) -> Option<Arc<LocalPostgres>> {
+        self.local_postgres.clone()
+    }
+
+    /// Returns the spill queue set up with `with_spill_queue`, if any.
+    #[must_use]
+    pub fn spill_queue(&self) -> Option<Arc<SpillQueue>> {
+        self.spill_queue.clone()
+    }
+
+    /// Returns the per-program usage tracker enabled with
+    /// `with_usage_tracking`, if any.
+    #[must_use]
+    pub fn usage_tracker(&self) -> Option<Arc<UsageTracker>> {
+        self.usage.clone()
+    }
+
     /// Returns a reference to the handler registry for registering handlers.
     #[must_use]
     pub fn handler_registry(&self) -> &HandlerRegistry {
         &self.handler_registry
     }
 
+    /// Returns a handle for enabling or disabling event handlers at runtime.
+    ///
+    /// The handle stays valid after `start()` consumes the indexer, so it can be
+    /// wired into an admin endpoint or a config watcher to switch off a
+    /// misbehaving handler without stopping the pipeline.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{calculate_discriminator, SolanaIndexer};
+    /// # async fn example(indexer: SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// let toggles = indexer.handler_toggles();
+    /// tokio::spawn(indexer.start());
+    ///
+    /// // Later, e.g. from an admin API:
+    /// toggles.disable(calculate_discriminator("TransferEvent"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn handler_toggles(&self) -> HandlerToggles {
+        self.handler_registry.toggles()
+    }
+
+    /// Returns a handle for registering and removing instruction decoders
+    /// and event handlers while the indexer runs.
+    ///
+    /// Like `handler_toggles`, the handle stays valid after `start()`
+    /// consumes the indexer. See the `registry::handle` module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{SolanaIndexer, TransferEvent, TxMetadata};
+    /// # struct TransferDecoder;
+    /// # impl solana_indexer_sdk::InstructionDecoder<TransferEvent> for TransferDecoder { fn decode(&self, _: &solana_transaction_status::UiInstruction) -> Option<TransferEvent> { None } }
+    /// # struct TransferHandler;
+    /// # #[async_trait::async_trait]
+    /// # impl solana_indexer_sdk::EventHandler<TransferEvent> for TransferHandler { async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> { Ok(()) } }
+    /// # async fn example(indexer: SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// let registry = indexer.registry_handle();
+    /// tokio::spawn(indexer.start());
+    ///
+    /// // Later, once the new event type is supported:
+    /// registry.register_handler(TransferHandler)?;
+    /// registry.register_decoder("MyProgram111111111111111111111111111111111", TransferDecoder)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn registry_handle(&self) -> RegistryHandle {
+        RegistryHandle::new(&self.decoder_registry, &self.handler_registry)
+    }
+
+    /// Returns the chain-tip tracker shared with the backfill manager.
+    ///
+    /// It is only fed by a subscription when slot tracking is enabled in the
+    /// configuration; otherwise it holds the finalized slots fetched by backfill.
+    #[must_use]
+    pub fn slot_tracker(&self) -> SlotTracker {
+        self.slot_tracker.clone()
+    }
+
+    /// Builds a data dictionary of every registered event and the tables its
+    /// handler writes.
+    ///
+    /// Applications typically expose this behind a `docs` subcommand to
+    /// publish the dictionary for downstream analysts, or dump it as JSON
+    /// for `solstream docs` to render.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # fn example(indexer: &SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// if std::env::args().nth(1).as_deref() == Some("docs") {
+    ///     indexer.data_dictionary().write_to("DATA_DICTIONARY.md")?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn data_dictionary(&self) -> DataDictionary {
+        self.handler_registry.data_dictionary()
+    }
+
     /// Returns a mutable reference to the handler registry.
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the registry has multiple references.
+    /// Returns `SolanaIndexerError::Internal` if the registry has multiple references.
     pub fn handler_registry_mut(&mut self) -> Result<&mut HandlerRegistry> {
         Arc::get_mut(&mut self.handler_registry).ok_or_else(|| {
-            SolanaIndexerError::InternalError("HandlerRegistry has multiple references".to_string())
+            SolanaIndexerError::Internal("HandlerRegistry has multiple references".to_string())
         })
     }
 
@@ -207,10 +702,10 @@ impl SolanaIndexer {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the registry has multiple references.
+    /// Returns `SolanaIndexerError::Internal` if the registry has multiple references.
     pub fn decoder_registry_mut(&mut self) -> Result<&mut DecoderRegistry> {
         Arc::get_mut(&mut self.decoder_registry).ok_or_else(|| {
-            SolanaIndexerError::InternalError("DecoderRegistry has multiple references".to_string())
+            SolanaIndexerError::Internal("DecoderRegistry has multiple references".to_string())
         })
     }
 
@@ -224,12 +719,10 @@ impl SolanaIndexer {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the registry has multiple references.
+    /// Returns `SolanaIndexerError::Internal` if the registry has multiple references.
     pub fn log_decoder_registry_mut(&mut self) -> Result<&mut LogDecoderRegistry> {
         Arc::get_mut(&mut self.log_decoder_registry).ok_or_else(|| {
-            SolanaIndexerError::InternalError(
-                "LogDecoderRegistry has multiple references".to_string(),
-            )
+            SolanaIndexerError::Internal("LogDecoderRegistry has multiple references".to_string())
         })
     }
 
@@ -243,10 +736,10 @@ impl SolanaIndexer {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the registry has multiple references.
+    /// Returns `SolanaIndexerError::Internal` if the registry has multiple references.
     pub fn account_decoder_registry_mut(&mut self) -> Result<&mut AccountDecoderRegistry> {
         Arc::get_mut(&mut self.account_decoder_registry).ok_or_else(|| {
-            SolanaIndexerError::InternalError(
+            SolanaIndexerError::Internal(
                 "AccountDecoderRegistry has multiple references".to_string(),
             )
         })
@@ -264,12 +757,16 @@ impl SolanaIndexer {
     ///
     /// # Arguments
     ///
-    /// * `program_id` - The program ID associated with this decoder
+    /// * `program_id` - The program ID associated with this decoder, or the
+    ///   name the RPC node gives its parsed instructions, such as
+    ///   `spl-token` (see `registry::programs`)
     /// * `decoder` - The typed decoder instance
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
     ///
     /// # Example
     ///
@@ -290,6 +787,52 @@ impl SolanaIndexer {
         program_id: impl Into<String>,
         decoder: D,
     ) -> Result<()>
+    where
+        D: crate::types::traits::InstructionDecoder<E> + 'static,
+        E: crate::types::events::EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
+    {
+        self.register_decoder_with_priority(program_id, decoder, 0, ChainControl::Stop)
+    }
+
+    /// Registers a typed instruction decoder at a position in the program's
+    /// decoder chain.
+    ///
+    /// Decoders of a program run from the highest `priority` to the lowest,
+    /// in registration order among equal priorities; `register_decoder` uses
+    /// priority 0. The first decoder to accept an instruction ends the chain
+    /// unless `control` is `ChainControl::Continue`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{ChainControl, SolanaIndexer};
+    /// # struct SwapDecoder;
+    /// # struct CatchAllDecoder;
+    /// # struct MyEvent;
+    /// # impl solana_indexer_sdk::InstructionDecoder<MyEvent> for SwapDecoder { fn decode(&self, _: &solana_transaction_status::UiInstruction) -> Option<MyEvent> { None } }
+    /// # impl solana_indexer_sdk::InstructionDecoder<MyEvent> for CatchAllDecoder { fn decode(&self, _: &solana_transaction_status::UiInstruction) -> Option<MyEvent> { None } }
+    /// # impl solana_indexer_sdk::EventDiscriminator for MyEvent { fn discriminator() -> [u8; 8] { [0; 8] } }
+    /// # impl borsh::BorshSerialize for MyEvent { fn serialize<W: std::io::Write>(&self, _: &mut W) -> std::io::Result<()> { Ok(()) } }
+    /// # fn example(indexer: &mut SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// // Swaps are decoded by the specialized decoder; everything else by the catch-all
+    /// indexer.register_decoder_with_priority("program_id", SwapDecoder, 10, ChainControl::Stop)?;
+    /// indexer.register_decoder_with_priority("program_id", CatchAllDecoder, -10, ChainControl::Stop)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_decoder_with_priority<D, E>(
+        &mut self,
+        program_id: impl Into<String>,
+        decoder: D,
+        priority: i32,
+        control: ChainControl,
+    ) -> Result<()>
     where
         D: crate::types::traits::InstructionDecoder<E> + 'static,
         E: crate::types::events::EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
@@ -297,8 +840,16 @@ impl SolanaIndexer {
         use crate::types::traits::DynamicInstructionDecoder;
         let boxed_typed: Box<dyn crate::types::traits::InstructionDecoder<E>> = Box::new(decoder);
         let boxed_dynamic: Box<dyn DynamicInstructionDecoder> = Box::new(boxed_typed);
-        self.decoder_registry_mut()?
-            .register(program_id.into(), boxed_dynamic)?;
+        let program_id = program_id.into();
+        if let Some(usage) = &self.usage {
+            usage.assign(E::discriminator(), program_id.clone());
+        }
+        self.decoder_registry_mut()?.register_with_priority(
+            program_id,
+            boxed_dynamic,
+            priority,
+            control,
+        )?;
         self.config.indexing_mode.inputs = true;
         Ok(())
     }
@@ -313,57 +864,447 @@ impl SolanaIndexer {
         D: crate::types::traits::LogDecoder<E> + 'static,
         E: crate::types::events::EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
     {
-        use crate::types::traits::DynamicLogDecoder;
-        let boxed_typed: Box<dyn crate::types::traits::LogDecoder<E>> = Box::new(decoder);
-        let boxed_dynamic: Box<dyn DynamicLogDecoder> = Box::new(boxed_typed);
-        self.log_decoder_registry_mut()?
-            .register(program_id.into(), boxed_dynamic)?;
-        self.config.indexing_mode.logs = true;
-        Ok(())
+        self.register_log_decoder_with_priority(program_id, decoder, 0, ChainControl::Stop)
     }
 
-    /// Registers a typed account decoder and enables account indexing mode.
-    pub fn register_account_decoder<D, E>(&mut self, decoder: D) -> Result<()>
+    /// Registers a typed log decoder at a position in the program's decoder
+    /// chain, ordered and stopped as with `register_decoder_with_priority`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
+    pub fn register_log_decoder_with_priority<D, E>(
+        &mut self,
+        program_id: impl Into<String>,
+        decoder: D,
+        priority: i32,
+        control: ChainControl,
+    ) -> Result<()>
     where
-        D: crate::types::traits::AccountDecoder<E> + 'static,
+        D: crate::types::traits::LogDecoder<E> + 'static,
         E: crate::types::events::EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
     {
-        use crate::types::traits::DynamicAccountDecoder;
-        let boxed: Box<dyn crate::types::traits::AccountDecoder<E>> = Box::new(decoder);
-        let dynamic_boxed: Box<dyn DynamicAccountDecoder> = Box::new(boxed);
-        self.account_decoder_registry_mut()?
-            .register(dynamic_boxed)?;
-        self.config.indexing_mode.accounts = true;
+        use crate::types::traits::DynamicLogDecoder;
+        let boxed_typed: Box<dyn crate::types::traits::LogDecoder<E>> = Box::new(decoder);
+        let boxed_dynamic: Box<dyn DynamicLogDecoder> = Box::new(boxed_typed);
+        let program_id = program_id.into();
+        if let Some(usage) = &self.usage {
+            usage.assign(E::discriminator(), program_id.clone());
+        }
+        self.log_decoder_registry_mut()?.register_with_priority(
+            program_id,
+            boxed_dynamic,
+            priority,
+            control,
+        )?;
+        self.config.indexing_mode.logs = true;
         Ok(())
     }
 
-    /// Returns a mutable reference to the backfill handler registry.
+    /// Registers the built-in Anchor event decoder for `program_id`, reading
+    /// both `Program data:` logs and `emit_cpi!` self-CPIs.
+    ///
+    /// Enables log and instruction indexing, and inner instruction decoding
+    /// (see `with_inner_instructions`), since `emit_cpi!` events are CPIs.
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the registry has multiple references.
-    pub fn backfill_handler_registry_mut(&mut self) -> Result<&mut BackfillHandlerRegistry> {
-        Arc::get_mut(&mut self.backfill_handler_registry).ok_or_else(|| {
-            SolanaIndexerError::InternalError(
-                "BackfillHandlerRegistry has multiple references".to_string(),
-            )
-        })
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if either
+    /// registry is full.
+    pub fn register_anchor_events(
+        &mut self,
+        program_id: impl Into<String>,
+        decoder: AnchorEventDecoder,
+    ) -> Result<()> {
+        let program_id = program_id.into();
+        if let Some(usage) = &self.usage {
+            for discriminator in decoder.discriminators() {
+                usage.assign(*discriminator, program_id.clone());
+            }
+        }
+        self.log_decoder_registry_mut()?
+            .register(program_id.clone(), Box::new(decoder.clone()))?;
+        self.decoder_registry_mut()?
+            .register(program_id, Box::new(decoder))?;
+        self.config.indexing_mode.logs = true;
+        self.config.indexing_mode.inputs = true;
+        self.config.inner_instructions = true;
+        Ok(())
     }
 
-    /// Registers a typed backfill handler.
+    /// Fetches the Anchor IDL `program_id` stores on-chain and decodes every
+    /// event it declares, like `register_anchor_events` with each event
+    /// added. Register a handler for each event type to process.
     ///
-    /// This generic method automatically handles the boxing and type erasure required by the registry.
+    /// The IDL is fetched again whenever the program is upgraded (see
+    /// `with_idl_refresh_interval`), so events added by an upgrade are
+    /// decoded without a restart.
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `handler` - The typed backfill handler instance
+    /// Returns `SolanaIndexerError::InvalidPublicKey` if `program_id` is not
+    /// a public key, `SolanaIndexerError::Config` if the program has no
+    /// on-chain IDL, and `SolanaIndexerError::Rpc` if it cannot be
+    /// fetched.
+    pub async fn register_idl_program(&mut self, program_id: &str) -> Result<()> {
+        let program = Pubkey::from_str(program_id)?;
+        let idl = self.idl_registry.load(&program).await?;
+        if let Some(usage) = &self.usage {
+            for event in &idl.events {
+                usage.assign(calculate_discriminator(&event.name), program_id.to_string());
+            }
+        }
+        let log_decoder = self.idl_registry.event_decoder(program);
+        let decoder = self.idl_registry.event_decoder(program);
+        self.log_decoder_registry_mut()?
+            .register(program_id.to_string(), Box::new(log_decoder))?;
+        self.decoder_registry_mut()?
+            .register(program_id.to_string(), Box::new(decoder))?;
+        self.config.indexing_mode.logs = true;
+        self.config.indexing_mode.inputs = true;
+        self.config.inner_instructions = true;
+        Ok(())
+    }
+
+    /// Checks the registered event structs against `idl` when the indexer
+    /// starts; see `validate_event_layouts`.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use solana_indexer_sdk::{SolanaIndexer, TxMetadata};
-    /// # use async_trait::async_trait;
-    /// # struct MyBackfillHandler;
+    /// # use solana_indexer_sdk::{Idl, SolanaIndexer};
+    /// # fn example(indexer: &mut SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// let idl = Idl::parse(&std::fs::read_to_string("idl/my_program.json")?)?;
+    /// indexer.validate_against_idl(idl);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_against_idl(&mut self, idl: Idl) {
+        self.expected_idls.push(idl);
+    }
+
+    /// Checks the Borsh layout of every handled event type against the IDLs
+    /// given to `validate_against_idl` and those loaded by
+    /// `register_idl_program`. `start` runs this before processing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` listing, per event, the
+    /// discriminators and fields that differ from the IDL.
+    pub fn validate_event_layouts(&self) -> Result<()> {
+        let layouts = self.handler_registry.event_layouts();
+        let loaded = self
+            .idl_registry
+            .programs()
+            .into_iter()
+            .filter_map(|program| self.idl_registry.get(&program));
+        for idl in &self.expected_idls {
+            validate::validate_event_layouts(idl, &layouts)?;
+        }
+        for idl in loaded {
+            validate::validate_event_layouts(&idl, &layouts)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the cache of the on-chain IDLs loaded by
+    /// `register_idl_program`.
+    #[must_use]
+    pub fn idl_registry(&self) -> &IdlRegistry {
+        &self.idl_registry
+    }
+
+    /// Registers an `InstructionRouter` for `program_id` and enables
+    /// instruction indexing mode.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the
+    /// registry is full.
+    pub fn register_instruction_router(
+        &mut self,
+        program_id: impl Into<String>,
+        router: InstructionRouter,
+    ) -> Result<()> {
+        let program_id = program_id.into();
+        if let Some(usage) = &self.usage {
+            for discriminator in router.event_discriminators() {
+                usage.assign(*discriminator, program_id.clone());
+            }
+        }
+        self.decoder_registry_mut()?
+            .register(program_id, Box::new(router))?;
+        self.config.indexing_mode.inputs = true;
+        Ok(())
+    }
+
+    /// Registers the built-in System Program decoder (see
+    /// `decoders::system`) and enables instruction indexing mode.
+    ///
+    /// Transactions are only fetched for the configured program IDs and
+    /// tracked addresses, so System Program instructions are decoded when
+    /// they appear in those transactions, or in every transaction if the
+    /// System Program is itself a configured program ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the
+    /// registry is full.
+    pub fn register_system_decoder(&mut self) -> Result<()> {
+        use crate::decoders::system::SystemDecoder;
+        if let Some(usage) = &self.usage {
+            for discriminator in SystemDecoder::discriminators() {
+                usage.assign(discriminator, solana_sdk::system_program::id().to_string());
+            }
+        }
+        self.decoder_registry_mut()?.register(
+            solana_sdk::system_program::id().to_string(),
+            Box::new(SystemDecoder),
+        )?;
+        self.config.indexing_mode.inputs = true;
+        Ok(())
+    }
+
+    /// Registers a typed account decoder and enables account indexing mode.
+    pub fn register_account_decoder<D, E>(&mut self, decoder: D) -> Result<()>
+    where
+        D: crate::types::traits::AccountDecoder<E> + 'static,
+        E: crate::types::events::EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
+    {
+        use crate::types::traits::DynamicAccountDecoder;
+        let boxed: Box<dyn crate::types::traits::AccountDecoder<E>> = Box::new(decoder);
+        let dynamic_boxed: Box<dyn DynamicAccountDecoder> = Box::new(boxed);
+        self.account_decoder_registry_mut()?
+            .register(dynamic_boxed)?;
+        self.config.indexing_mode.accounts = true;
+        Ok(())
+    }
+
+    /// Registers an account decoder whose output is kept in an SDK-managed
+    /// latest-state table, and enables account indexing mode.
+    ///
+    /// No handler is needed: the tables are created on startup, and each
+    /// decoded account is upserted into `table` as `JSONB`, keyed by pubkey,
+    /// and appended to its history table when one is kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{AccountDecoder, AccountStateTable, SolanaIndexer};
+    /// # #[derive(serde::Serialize)]
+    /// # struct Pool { liquidity: u64 }
+    /// # fn example(
+    /// #     indexer: &mut SolanaIndexer,
+    /// #     decoder: impl AccountDecoder<Pool> + 'static,
+    /// # ) -> solana_indexer_sdk::Result<()> {
+    /// indexer.register_account_state(decoder, AccountStateTable::new("pools").with_history())?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the table name is not a
+    /// plain identifier or the storage has no `PostgreSQL` database for the
+    /// table (SQLite storage without `with_handler_database`), or
+    /// propagates registry errors.
+    pub fn register_account_state<D, E>(
+        &mut self,
+        decoder: D,
+        table: AccountStateTable,
+    ) -> Result<()>
+    where
+        D: crate::types::traits::AccountDecoder<E> + 'static,
+        E: serde::Serialize + 'static,
+    {
+        table.validate()?;
+        self.storage.require_pool()?;
+        let discriminator = table.discriminator();
+        self.account_decoder_registry_mut()?
+            .register(Box::new(StateDecoder::new(decoder, &table)))?;
+        self.handler_registry_mut()?
+            .register(discriminator, Box::new(StateHandler::new(table.clone())))?;
+        self.register_schema_initializer(Box::new(table));
+        self.config.indexing_mode.accounts = true;
+        Ok(())
+    }
+
+    /// Registers an account decoder whose consecutive states are compared
+    /// field by field, and enables account indexing mode.
+    ///
+    /// Each changed field is dispatched as an `AccountFieldChanged` event
+    /// (see `decoding::diff`) to the handlers registered for it. With
+    /// `fields`, only changes of those field paths are reported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{AccountDecoder, AccountFields, SolanaIndexer};
+    /// # struct Pool;
+    /// # impl AccountFields for Pool { fn fields(&self, _: &str, _: &mut Vec<(String, String)>) {} }
+    /// # fn example(
+    /// #     indexer: &mut SolanaIndexer,
+    /// #     decoder: impl AccountDecoder<Pool> + 'static,
+    /// # ) -> solana_indexer_sdk::Result<()> {
+    /// indexer.register_account_diffs(decoder, Some(&["fees.trade_fee_bps"]))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the
+    /// registry is full.
+    pub fn register_account_diffs<D, E>(
+        &mut self,
+        decoder: D,
+        fields: Option<&[&str]>,
+    ) -> Result<()>
+    where
+        D: crate::types::traits::AccountDecoder<E> + 'static,
+        E: crate::core::decoding::diff::AccountFields + 'static,
+    {
+        let watched = fields.map(|fields| fields.iter().map(ToString::to_string).collect());
+        self.account_decoder_registry_mut()?
+            .register(Box::new(AccountDiffDecoder::new(decoder, watched)))?;
+        self.config.indexing_mode.accounts = true;
+        Ok(())
+    }
+
+    /// Runs `task` on the cron schedule `cron`, in UTC, while the indexer
+    /// runs.
+    ///
+    /// See the `schedule` module for the expression syntax. A failing run is
+    /// logged and does not stop later runs.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{PeriodicTask, SolanaIndexer};
+    /// # fn example(
+    /// #     indexer: &mut SolanaIndexer,
+    /// #     daily_rollup: impl PeriodicTask,
+    /// # ) -> solana_indexer_sdk::Result<()> {
+    /// indexer.schedule("5 0 * * *", daily_rollup)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `cron` is not a valid
+    /// cron expression or the storage has no `PostgreSQL` database for the
+    /// task (SQLite storage without `with_handler_database`).
+    pub fn schedule(&mut self, cron: &str, task: impl PeriodicTask) -> Result<()> {
+        self.storage.require_pool()?;
+        self.scheduled_tasks.push(ScheduledTask {
+            schedule: cron.parse()?,
+            task: Arc::new(task),
+        });
+        Ok(())
+    }
+
+    /// Registers the handler of deferred jobs of type `J`, which handlers
+    /// schedule with `TxMetadata::schedule_in`. See the `jobs` module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{DeferredJob, JobHandler, Result, SolanaIndexer};
+    /// # use async_trait::async_trait;
+    /// # use serde::{Deserialize, Serialize};
+    /// # use sqlx::PgPool;
+    /// # #[derive(Serialize, Deserialize)]
+    /// # struct Rollup { pool: String }
+    /// # impl DeferredJob for Rollup { fn kind() -> &'static str { "rollup" } }
+    /// # struct RollupHandler;
+    /// # #[async_trait]
+    /// # impl JobHandler<Rollup> for RollupHandler {
+    /// #     async fn run(&self, _job: Rollup, _db: &PgPool) -> Result<()> { Ok(()) }
+    /// # }
+    /// # fn example(indexer: &mut SolanaIndexer) -> Result<()> {
+    /// indexer.register_job_handler(RollupHandler)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if deferred jobs are not
+    /// enabled with `with_deferred_jobs`, or a handler is already registered
+    /// for `J`.
+    pub fn register_job_handler<J: DeferredJob>(
+        &mut self,
+        handler: impl JobHandler<J>,
+    ) -> Result<()> {
+        if self.deferred_jobs.is_none() {
+            return Err(SolanaIndexerError::Config(
+                "Deferred jobs are not enabled; see with_deferred_jobs".to_string(),
+            ));
+        }
+        if self.job_handlers.contains_key(J::kind()) {
+            return Err(SolanaIndexerError::Config(format!(
+                "A job handler is already registered for '{}'",
+                J::kind()
+            )));
+        }
+        self.job_handlers.insert(
+            J::kind().to_string(),
+            Arc::new(TypedJobHandler::<J, _>::new(handler)),
+        );
+        Ok(())
+    }
+
+    /// Relays the records handlers write to the transactional outbox to
+    /// `sink` as well. See `storage::outbox`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the outbox is not enabled
+    /// with `with_outbox`.
+    pub fn add_outbox_sink(&mut self, sink: impl EventSink) -> Result<()> {
+        let Some(relay) = &mut self.outbox else {
+            return Err(SolanaIndexerError::Config(
+                "The outbox is not enabled; see with_outbox".to_string(),
+            ));
+        };
+        relay.add_sink(sink);
+        Ok(())
+    }
+
+    /// Returns the outbox relay enabled with `with_outbox`, if any.
+    #[must_use]
+    pub fn outbox(&self) -> Option<&OutboxRelay> {
+        self.outbox.as_ref()
+    }
+
+    /// Returns a mutable reference to the backfill handler registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if the registry has multiple references.
+    pub fn backfill_handler_registry_mut(&mut self) -> Result<&mut BackfillHandlerRegistry> {
+        Arc::get_mut(&mut self.backfill_handler_registry).ok_or_else(|| {
+            SolanaIndexerError::Internal(
+                "BackfillHandlerRegistry has multiple references".to_string(),
+            )
+        })
+    }
+
+    /// Registers a typed backfill handler.
+    ///
+    /// This generic method automatically handles the boxing and type erasure required by the registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `handler` - The typed backfill handler instance
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{SolanaIndexer, TxMetadata};
+    /// # use async_trait::async_trait;
+    /// # struct MyBackfillHandler;
     /// # struct MyEvent;
     /// # #[async_trait]
     /// # impl solana_indexer_sdk::BackfillHandler<MyEvent> for MyBackfillHandler {
@@ -409,10 +1350,105 @@ impl SolanaIndexer {
         Ok(())
     }
 
+    /// Sets the observer that receives failover escalations.
+    ///
+    /// See `SolanaIndexerConfigBuilder::with_failover` for the recovery
+    /// ladder it reports on.
+    pub fn set_error_observer(&mut self, observer: Arc<dyn ErrorObserver>) {
+        self.error_observer = Some(observer);
+    }
+
+    /// Sets the observer notified when a slot or batch has been fully
+    /// processed, with every handler's writes committed.
+    pub fn set_pipeline_observer(&mut self, observer: Arc<dyn PipelineObserver>) {
+        self.pipeline_observer = Some(observer);
+    }
+
+    /// Keeps per-address polling cursors in `store` instead of the database.
+    ///
+    /// Processed signatures and handler rows still go to the configured
+    /// database; only the cursors that `StartStrategy::Resume` starts from
+    /// move, so stateless replicas can share them through a key-value store.
+    pub fn set_cursor_store(&mut self, store: Arc<dyn CursorStore>) {
+        self.storage = Arc::new(CursorStorage::new(self.storage.clone(), store));
+    }
+
+    /// Exports archived transactions older than `config.max_age_days` to
+    /// `store` every hour, and reads them back from there in `replay`.
+    ///
+    /// Only has an effect with `with_transaction_archive`; see
+    /// `storage::cold` for the segment layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `config` is invalid.
+    pub fn set_cold_archive(
+        &mut self,
+        store: Arc<dyn SegmentStore>,
+        config: ColdArchiveConfig,
+    ) -> Result<()> {
+        self.cold_archive = Some(Arc::new(ColdArchive::new(store, config)?));
+        Ok(())
+    }
+
+    /// Returns the cold archive set with `set_cold_archive`, to export or
+    /// inspect segments on demand.
+    #[must_use]
+    pub fn cold_archive(&self) -> Option<Arc<ColdArchive>> {
+        self.cold_archive.clone()
+    }
+
+    /// Registers a prefilter that decides, before `getTransaction`, whether a
+    /// transaction is fetched at all.
+    ///
+    /// A transaction is fetched only if every registered prefilter matches.
+    /// Skipped transactions are not marked as processed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{PrefilterExpr, SolanaIndexer, TransactionPreview};
+    /// # fn example(indexer: &mut SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// indexer.register_prefilter(PrefilterExpr::parse(r#"err == null && memo ~ "order:""#)?);
+    /// indexer.register_prefilter(|tx: &TransactionPreview| tx.slot >= 250_000_000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_prefilter(&mut self, prefilter: impl Prefilter + 'static) {
+        self.prefilters.push(Arc::new(prefilter));
+    }
+
+    /// Serializes the processing of transactions sharing an ordering key.
+    ///
+    /// Transactions with different keys are still processed in parallel; see
+    /// `core::execution::ordering`. Applies to RPC polling, the Helius
+    /// source and `start_with_source`, the sources that process
+    /// transactions concurrently.
+    pub fn set_ordering_key(&mut self, key: OrderingKey) {
+        self.ordering = Some(key);
+    }
+
+    /// Returns the ordering key of `event`, if one is set.
+    fn ordering_key(
+        &self,
+        event: &crate::streams::TransactionEvent,
+        account: Option<Pubkey>,
+    ) -> Option<String> {
+        self.ordering.as_ref().map(|ordering| {
+            ordering.key(
+                &TransactionPreview::from_event(event),
+                account.or_else(|| event.fee_payer()),
+            )
+        })
+    }
+
     /// Registers a typed event handler.
     ///
     /// This generic method automatically handles the boxing and type erasure required by the registry.
     ///
+    /// Handlers registered for the same event type all receive its events,
+    /// in registration order, each with its own retries and dead letters.
+    ///
     /// # Arguments
     ///
     /// * `handler` - The typed handler instance
@@ -453,14 +1489,162 @@ impl SolanaIndexer {
             .register(E::discriminator(), boxed_dynamic)
     }
 
+    /// Registers a handler that is retried as `policy` says, rather than
+    /// with the default three attempts.
+    ///
+    /// # Errors
+    ///
+    /// See `register_handler`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{RetryPolicy, SolanaIndexer, TransferEvent, TxMetadata};
+    /// # use async_trait::async_trait;
+    /// # struct WebhookHandler;
+    /// # #[async_trait]
+    /// # impl solana_indexer_sdk::EventHandler<TransferEvent> for WebhookHandler { async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> { Ok(()) } }
+    /// # fn example(indexer: &mut SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+    /// indexer.register_handler_with_retry(WebhookHandler, RetryPolicy::new(10))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_handler_with_retry<H, E>(
+        &mut self,
+        handler: H,
+        policy: RetryPolicy,
+    ) -> Result<()>
+    where
+        H: crate::types::traits::EventHandler<E> + 'static,
+        E: crate::types::events::EventDiscriminator
+            + borsh::BorshDeserialize
+            + Send
+            + Sync
+            + 'static,
+    {
+        use crate::types::traits::DynamicEventHandler;
+        let boxed_typed: Box<dyn crate::types::traits::EventHandler<E>> = Box::new(handler);
+        let boxed_dynamic: Box<dyn DynamicEventHandler> = Box::new(boxed_typed);
+
+        self.handler_registry_mut()?
+            .register_with_retry(E::discriminator(), boxed_dynamic, policy)
+    }
+
+    /// Registers a handler that only receives the events `filter` returns
+    /// `true` for. The filter runs before the handler is awaited, so
+    /// skipped events cost no database round trip.
+    ///
+    /// # Errors
+    ///
+    /// See `register_handler`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{SolanaIndexer, TransferEvent, TxMetadata};
+    /// # use async_trait::async_trait;
+    /// # struct TransferHandler;
+    /// # #[async_trait]
+    /// # impl solana_indexer_sdk::EventHandler<TransferEvent> for TransferHandler { async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> { Ok(()) } }
+    /// # fn example(indexer: &mut SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+    /// indexer.register_handler_with_filter(TransferHandler, |event: &TransferEvent, _: &TxMetadata| {
+    ///     event.amount >= 1_000
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_handler_with_filter<H, E>(
+        &mut self,
+        handler: H,
+        filter: impl Fn(&E, &TxMetadata) -> bool + Send + Sync + 'static,
+    ) -> Result<()>
+    where
+        H: crate::types::traits::EventHandler<E> + 'static,
+        E: crate::types::events::EventDiscriminator
+            + borsh::BorshDeserialize
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.register_handler(FilteredHandler::new(handler, filter))
+    }
+
+    /// Registers a handler whose calls are held back to stay within
+    /// `limit`, e.g. one calling a rate-limited external API. Calls over the
+    /// limit wait for their turn instead of failing.
+    ///
+    /// # Errors
+    ///
+    /// See `register_handler`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::{RateLimit, SolanaIndexer, TransferEvent, TxMetadata};
+    /// # use async_trait::async_trait;
+    /// # struct PriceHandler;
+    /// # #[async_trait]
+    /// # impl solana_indexer_sdk::EventHandler<TransferEvent> for PriceHandler { async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> { Ok(()) } }
+    /// # fn example(indexer: &mut SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+    /// indexer.register_handler_with_rate_limit(
+    ///     PriceHandler,
+    ///     RateLimit::new().max_in_flight(4).max_per_second(10),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn register_handler_with_rate_limit<H, E>(
+        &mut self,
+        handler: H,
+        limit: RateLimit,
+    ) -> Result<()>
+    where
+        H: crate::types::traits::EventHandler<E> + 'static,
+        E: crate::types::events::EventDiscriminator
+            + borsh::BorshDeserialize
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.register_handler(RateLimitedHandler::new(handler, limit))
+    }
+
+    /// Attaches a pipeline built with `Pipeline::builder`.
+    ///
+    /// Registers the pipeline's decoders for its source and installs the
+    /// pipeline as the handler of its event type.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if a handler or another
+    /// pipeline is already registered for `E`, or propagates decoder
+    /// registration errors.
+    pub fn attach_pipeline<E>(&mut self, pipeline: Pipeline<E>) -> Result<()>
+    where
+        E: crate::types::events::EventDiscriminator
+            + borsh::BorshDeserialize
+            + Send
+            + Sync
+            + 'static,
+    {
+        if self.handler_registry.contains(&E::discriminator()) {
+            return Err(SolanaIndexerError::Config(format!(
+                "Cannot attach pipeline '{}': its event type already has a handler",
+                pipeline.name()
+            )));
+        }
+        let pipeline = pipeline.register_decoders(self)?;
+        self.register_handler(pipeline)
+    }
+
     /// Returns a reference to the decoder for registering event discriminators.
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::InternalError` if the decoder has multiple references.
+    /// Returns `SolanaIndexerError::Internal` if the decoder has multiple references.
     pub fn decoder_mut(&mut self) -> Result<&mut Decoder> {
         Arc::get_mut(&mut self.decoder).ok_or_else(|| {
-            SolanaIndexerError::InternalError("Decoder has multiple references".to_string())
+            SolanaIndexerError::Internal("Decoder has multiple references".to_string())
         })
     }
 
@@ -476,6 +1660,99 @@ impl SolanaIndexer {
         &*self.storage
     }
 
+    /// Snapshots the accounts of `program_id` into the account handlers.
+    ///
+    /// Uses the filters and page size from `SnapshotConfig`, whether or not
+    /// the startup bootstrap is enabled. See `snapshot::SnapshotBootstrapper`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError` if an RPC request, a handler, or the
+    /// snapshot-slot write fails.
+    pub async fn snapshot_program(
+        &self,
+        program_id: &solana_sdk::pubkey::Pubkey,
+    ) -> Result<SnapshotSummary> {
+        SnapshotBootstrapper::new(
+            self.fetcher.clone(),
+            self.account_decoder_registry.clone(),
+            self.handler_registry.clone(),
+            self.storage.clone(),
+        )
+        .with_filters(self.config.snapshot.filters.clone())
+        .with_page_size(self.config.snapshot.page_size)
+        .run(program_id)
+        .await
+    }
+
+    /// Bootstraps every configured program that has no recorded snapshot yet.
+    async fn bootstrap_snapshots(&self) -> Result<()> {
+        if !self.config.snapshot.enabled {
+            return Ok(());
+        }
+        for program_id in &self.config.program_ids {
+            if let Some(slot) = self
+                .storage
+                .load_snapshot_slot(&program_id.to_string())
+                .await?
+            {
+                logging::log(
+                    logging::LogLevel::Info,
+                    &format!("Snapshot of {program_id} already taken at slot {slot}, skipping"),
+                );
+                continue;
+            }
+            self.snapshot_program(program_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Lists the token accounts of every mint set with `with_token_holders`,
+    /// stores their balances and adds them to the tracked addresses.
+    async fn discover_token_holders(&mut self) -> Result<()> {
+        if self.config.token_holder_mints.is_empty() {
+            return Ok(());
+        }
+        TokenHolders
+            .initialize(self.storage.require_pool()?)
+            .await?;
+
+        for mint in self.config.token_holder_mints.clone() {
+            let mut holdings = Vec::new();
+            for token_program in TOKEN_PROGRAM_IDS {
+                let token_program = Pubkey::from_str(token_program)
+                    .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+                let (slot, accounts) = self
+                    .fetcher
+                    .get_token_accounts_by_mint(&token_program, &mint)
+                    .await?;
+                holdings.extend(accounts.into_iter().map(|(account, owner, amount)| {
+                    holders::TokenHolding {
+                        token_account: account.to_string(),
+                        mint: mint.to_string(),
+                        owner: owner.to_string(),
+                        amount,
+                        slot,
+                    }
+                }));
+            }
+            TokenHolders::upsert(self.storage.require_pool()?, &holdings).await?;
+
+            for holding in &holdings {
+                let account = Pubkey::from_str(&holding.token_account)
+                    .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+                if !self.config.tracked_addresses.contains(&account) {
+                    self.config.tracked_addresses.push(account);
+                }
+            }
+            logging::log(
+                logging::LogLevel::Info,
+                &format!("Tracking {} token accounts of {mint}", holdings.len()),
+            );
+        }
+        Ok(())
+    }
+
     /// Starts the backfill process.
     ///
     /// This runs the backfill engine until complete or error.
@@ -509,7 +1786,7 @@ impl SolanaIndexer {
 
         // Setup default handlers
         let reorg_handler = Arc::new(DefaultReorgHandler);
-        let finalized_tracker = Arc::new(DefaultFinalizedBlockTracker);
+        let finalized_tracker = Arc::new(self.slot_tracker.clone());
         let progress_tracker = Arc::new(DefaultBackfillProgress);
 
         let engine = BackfillEngine::new(
@@ -543,7 +1820,7 @@ impl SolanaIndexer {
     #[tracing::instrument(skip(self))]
     pub async fn backfill_slots(&self, from_slot: u64, to_slot: Option<u64>) -> Result<()> {
         // Resolve the target end slot if not provided explicitly.
-        let finalized_tracker = Arc::new(DefaultFinalizedBlockTracker);
+        let finalized_tracker = Arc::new(self.slot_tracker.clone());
         let effective_end_slot = if let Some(slot) = to_slot {
             slot
         } else {
@@ -557,19 +1834,38 @@ impl SolanaIndexer {
             return Ok(());
         }
 
-        // Reuse the same defaults as `start_backfill` for strategy and helpers.
+        let range = BackfillRange::new(from_slot, effective_end_slot);
+        self.range_backfill_engine(range).start_range(range).await
+    }
+
+    /// Returns the progress of every backfill range this indexer's storage
+    /// has recorded, with throughput and an ETA for the pending ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the progress cannot be read.
+    pub async fn backfill_status(&self) -> Result<BackfillStatus> {
+        Ok(BackfillStatus::from_ranges(
+            self.storage.load_backfill_ranges().await?,
+        ))
+    }
+
+    /// Builds a backfill engine for an explicit slot range, using the same
+    /// defaults as `start_backfill` for strategy and helpers.
+    fn range_backfill_engine(&self, range: BackfillRange) -> BackfillEngine {
         let backfill_config = self.config.backfill.clone();
         let strategy = Arc::new(DefaultBackfillStrategy {
-            start_slot: Some(from_slot),
-            end_slot: Some(effective_end_slot),
+            start_slot: Some(range.start_slot),
+            end_slot: Some(range.end_slot),
             batch_size: backfill_config.batch_size,
             concurrency: backfill_config.concurrency,
         });
 
         let reorg_handler = Arc::new(DefaultReorgHandler);
+        let finalized_tracker = Arc::new(self.slot_tracker.clone());
         let progress_tracker = Arc::new(DefaultBackfillProgress);
 
-        let engine = BackfillEngine::new(
+        BackfillEngine::new(
             self.config.clone(),
             self.fetcher.clone(),
             self.decoder.clone(),
@@ -586,11 +1882,7 @@ impl SolanaIndexer {
             progress_tracker,
             self.cancellation_token.clone(),
             self.backfill_handler_registry.clone(),
-        );
-
-        engine
-            .start_range(BackfillRange::new(from_slot, effective_end_slot))
-            .await
+        )
     }
 
     /// Starts the indexer.
@@ -607,7 +1899,10 @@ impl SolanaIndexer {
     /// - RPC/WebSocket connection fails
     /// - Decoding errors occur
     #[tracing::instrument(skip(self))]
-    pub async fn start(self) -> Result<()> {
+    pub async fn start(mut self) -> Result<()> {
+        // Refuse to misdecode events if the program changed under them
+        self.validate_event_layouts()?;
+
         let token = self.cancellation_token.clone();
 
         // Spawn signal handler
@@ -618,8 +1913,48 @@ impl SolanaIndexer {
             }
         });
 
+        // Open the RPC connection before the first batch needs it
+        if let Err(e) = self.fetcher.warm_up(self.config.ws_url()).await {
+            logging::log(
+                logging::LogLevel::Warning,
+                &format!("Connection warm-up failed: {e}"),
+            );
+        }
+
+        self.discover_token_holders().await?;
+
+        // Follow the chain tip so backfill does not poll `getSlot`
+        #[cfg(feature = "websockets")]
+        if self.config.slot_tracking {
+            if let Some(ws_url) = self.config.ws_url() {
+                self.slot_tracker.spawn(
+                    ws_url,
+                    self.config.network.clone(),
+                    5,
+                    self.cancellation_token.clone(),
+                );
+            } else {
+                logging::log(
+                    logging::LogLevel::Warning,
+                    "Slot tracking needs a WebSocket endpoint, falling back to getSlot",
+                );
+            }
+        }
+
         // Start BackfillManager if enabled
         if self.config.backfill.enabled {
+            // The trigger and strategy work in slots
+            if let (None, Some(start_time)) = (
+                self.config.backfill.start_slot,
+                self.config.backfill.start_time,
+            ) {
+                let slot = slot_at_time(&self.fetcher, start_time).await?;
+                logging::log(
+                    logging::LogLevel::Info,
+                    &format!("Backfill start time {start_time} resolved to slot {slot}"),
+                );
+                self.config.backfill.start_slot = Some(slot);
+            }
             let backfill_config = self.config.backfill.clone();
             let backfill_fetcher = self.fetcher.clone();
             let backfill_decoder = self.decoder.clone();
@@ -631,7 +1966,7 @@ impl SolanaIndexer {
                 concurrency: backfill_config.concurrency,
             });
             let backfill_reorg_handler = Arc::new(DefaultReorgHandler);
-            let backfill_finalized_tracker = Arc::new(DefaultFinalizedBlockTracker);
+            let backfill_finalized_tracker = Arc::new(self.slot_tracker.clone());
             let backfill_progress_tracker = Arc::new(DefaultBackfillProgress);
             let backfill_trigger = self.backfill_trigger.clone().unwrap_or_else(|| {
                 Arc::new(DefaultBackfillTrigger::new(backfill_config.clone()))
@@ -643,7 +1978,7 @@ impl SolanaIndexer {
             let backfill_log_decoder_registry = self.log_decoder_registry.clone();
             let backfill_account_decoder_registry = self.account_decoder_registry.clone();
 
-            let manager = BackfillManager::new(
+            let mut manager = BackfillManager::new(
                 self.config.clone(),
                 backfill_fetcher,
                 backfill_decoder,
@@ -659,6 +1994,9 @@ impl SolanaIndexer {
                 backfill_log_decoder_registry,
                 backfill_account_decoder_registry,
             );
+            if self.config.slot_tracking {
+                manager = manager.with_slot_tracker(self.slot_tracker.clone());
+            }
 
             tokio::spawn(async move {
                 if let Err(e) = manager.run().await {
@@ -693,6 +2031,133 @@ impl SolanaIndexer {
             }
         });
 
+        // Commit buffered writes on a timer and once more at shutdown
+        if let Some(buffer) = &self.write_buffer {
+            buffer.clone().spawn(self.cancellation_token.clone());
+        }
+
+        // Add per-program usage to the daily table
+        if let Some(usage) = &self.usage {
+            usage
+                .clone()
+                .spawn(self.storage.clone(), self.cancellation_token.clone());
+        }
+
+        // Keep partitions of the processed table ahead of the chain tip
+        if self.config.partitioning.is_some() {
+            let fetcher = self.fetcher.clone();
+            partitions::spawn_maintenance(
+                self.storage.clone(),
+                move || {
+                    let fetcher = fetcher.clone();
+                    async move { fetcher.get_latest_finalized_slot().await.ok() }
+                },
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Prune rows past the retention age
+        if let Some(config) = &self.config.retention {
+            let fetcher = self.fetcher.clone();
+            retention::spawn_retention(
+                self.storage.clone(),
+                config.clone(),
+                self.retention_metrics.clone(),
+                move || {
+                    let fetcher = fetcher.clone();
+                    async move { fetcher.get_latest_finalized_slot().await.ok() }
+                },
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Vacuum and analyze the SDK tables on schedule
+        if let Some(config) = &self.config.vacuum {
+            vacuum::spawn_vacuum(
+                self.storage.clone(),
+                config.clone(),
+                self.vacuum_metrics.clone(),
+                self.cancellation_token.clone(),
+            )?;
+        }
+
+        // Move aged archived transactions to cold storage
+        if let Some(archive) = &self.cold_archive {
+            cold::spawn_cold_archive(
+                archive.clone(),
+                self.storage.require_pool()?.clone(),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Replay events spilled during database outages, including those
+        // left by an earlier run
+        if let Some(queue) = &self.spill_queue {
+            let pending = queue.recover().await?;
+            if pending > 0 {
+                logging::log(
+                    logging::LogLevel::Info,
+                    &format!("Spill queue holds {pending} transactions from an earlier run"),
+                );
+            }
+            queue.clone().spawn(
+                self.handler_registry.clone(),
+                self.storage.clone(),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Count the rows of the handler tables
+        if let Some(secs) = self.config.table_stats_refresh_secs {
+            stats::spawn_table_stats(
+                self.storage.require_pool()?.clone(),
+                stats::counted_tables(&self.data_dictionary()),
+                Duration::from_secs(secs),
+                self.table_stats.clone(),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Run periodic tasks on their cron schedules
+        for task in self.scheduled_tasks.drain(..) {
+            task.spawn(self.storage.clone(), self.cancellation_token.clone());
+        }
+
+        // Deliver the jobs handlers deferred, including those left by an
+        // earlier run
+        if let (Some(jobs), Some(poll_ms)) =
+            (&self.deferred_jobs, self.config.deferred_jobs_poll_ms)
+        {
+            jobs.clone().spawn(
+                std::mem::take(&mut self.job_handlers),
+                Duration::from_millis(poll_ms),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Publish the records handlers wrote to the outbox, including those
+        // left by an earlier run
+        if let (Some(relay), Some(poll_ms)) = (&self.outbox, self.config.outbox_poll_ms) {
+            relay.clone().spawn(
+                Duration::from_millis(poll_ms),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Reload the IDLs of programs upgraded while running
+        if !self.idl_registry.programs().is_empty() {
+            self.idl_registry.clone().spawn(
+                Duration::from_secs(self.config.idl_refresh_secs),
+                self.cancellation_token.clone(),
+            );
+        }
+
+        // Follow the commitment of processed signatures
+        if self.config.confirmation_tracking {
+            ConfirmationTracker::new(self.fetcher.clone(), self.storage.clone())
+                .spawn(self.cancellation_token.clone());
+        }
+
         match &self.config.source {
             SourceConfig::Rpc { .. } => self.process_rpc_source().await,
             #[cfg(feature = "websockets")]
@@ -712,6 +2177,191 @@ impl SolanaIndexer {
         }
     }
 
+    /// Runs the indexer against a custom `TransactionSource`.
+    ///
+    /// Events are processed concurrently (bounded by `worker_threads`) through
+    /// the same pipeline as the built-in sources. Full transactions are used
+    /// as-is without fetching their block, which makes this suitable for
+    /// driving the indexer from `testing::synthetic::SyntheticSource` in load
+    /// tests. Backfill and the stale-transaction cleanup are not started.
+    ///
+    /// Runs until the cancellation token is cancelled (or Ctrl+C is received).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError` if schema initialization or a storage
+    /// lookup fails.
+    pub async fn start_with_source(self, mut source: impl TransactionSource) -> Result<()> {
+        let token = self.cancellation_token.clone();
+        tokio::spawn(async move {
+            if let Ok(()) = tokio::signal::ctrl_c().await {
+                logging::log(logging::LogLevel::Info, "Received Ctrl+C, shutting down...");
+                token.cancel();
+            }
+        });
+
+        for initializer in &self.schema_initializers {
+            logging::log(logging::LogLevel::Info, "Initializing database schema...");
+            initializer.initialize(self.storage.require_pool()?).await?;
+        }
+        logging::log(logging::LogLevel::Success, "Database schema initialized");
+
+        logging::log(
+            logging::LogLevel::Info,
+            &format!("Starting indexer loop ({})...\n", source.source_name()),
+        );
+
+        let semaphore = Arc::new(Semaphore::new(self.config.worker_threads));
+        let mut executor = KeyedExecutor::new(self.config.worker_threads);
+        // Consecutive `next_batch` failures, driving the retry backoff
+        let mut failures = 0;
+
+        loop {
+            let batch = tokio::select! {
+                _ = self.cancellation_token.cancelled() => {
+                    logging::log(logging::LogLevel::Info, "Graceful shutdown initiated...");
+                    break;
+                }
+                res = source.next_batch() => res,
+            };
+
+            let events = match batch {
+                Ok(events) => {
+                    failures = 0;
+                    events
+                }
+                Err(e) => {
+                    logging::log_error(&format!("{} error", source.source_name()), &e.to_string());
+                    // Back off before asking the source again; once the policy
+                    // gives up, keep retrying at the poll interval.
+                    failures += 1;
+                    let delay = self
+                        .config
+                        .backoff
+                        .delay(failures, ErrorClass::of(&e))
+                        .unwrap_or(Duration::from_secs(self.config.poll_interval_secs));
+                    tokio::select! {
+                        _ = self.cancellation_token.cancelled() => {
+                            logging::log(logging::LogLevel::Info, "Graceful shutdown initiated...");
+                            break;
+                        }
+                        _ = tokio::time::sleep(delay) => continue,
+                    }
+                }
+            };
+            if events.is_empty() {
+                continue;
+            }
+
+            let start_time = std::time::Instant::now();
+            let total = events.len();
+            let mut tasks = Vec::with_capacity(total);
+            // (slot, settled) for every transaction, reported to the pipeline observer
+            let mut outcomes = Vec::with_capacity(total);
+
+            for event in events {
+                let signature = event.signature();
+                let sig_str = signature.to_string();
+                let slot = event.slot();
+
+                // Check if already processed (idempotency)
+                if self.storage.is_processed(&sig_str).await? {
+                    outcomes.push((slot, true));
+                    continue;
+                }
+
+                if self.config.skip_failed_transactions && event.is_failed() {
+                    outcomes.push((slot, true));
+                    continue;
+                }
+
+                if !prefilter::passes(&self.prefilters, &TransactionPreview::from_event(&event)) {
+                    outcomes.push((slot, true));
+                    continue;
+                }
+                self.check_slot_gap(slot);
+                let key = self.ordering_key(&event, None);
+
+                let (known_block_hash, preloaded_transaction) = match event {
+                    crate::streams::TransactionEvent::FullTransaction { tx, .. } => {
+                        (Some("UNKNOWN".to_string()), Some(tx))
+                    }
+                    _ => (None, None),
+                };
+
+                // Ordered transactions take their permit from the executor
+                let permit = match key {
+                    Some(_) => None,
+                    None => Some(semaphore.clone().acquire_owned().await.map_err(|e| {
+                        SolanaIndexerError::Internal(format!("Semaphore error: {e}"))
+                    })?),
+                };
+
+                let fetcher = self.fetcher.clone();
+                let decoder = self.decoder.clone();
+                let decoder_registry = self.decoder_registry.clone();
+                let log_decoder_registry = self.log_decoder_registry.clone();
+                let account_decoder_registry = self.account_decoder_registry.clone();
+                let handler_registry = self.handler_registry.clone();
+                let storage = self.storage.clone();
+                let config = self.config.clone();
+
+                tasks.push(executor.spawn(key, async move {
+                    let res = Self::process_transaction_core(
+                        signature,
+                        fetcher,
+                        decoder,
+                        decoder_registry,
+                        log_decoder_registry,
+                        account_decoder_registry,
+                        handler_registry,
+                        storage,
+                        config,
+                        false, // is_finalized
+                        known_block_hash,
+                        preloaded_transaction,
+                    )
+                    .await;
+                    drop(permit);
+                    (sig_str, slot, res)
+                }));
+            }
+
+            let mut processed_count = 0;
+            let mut panicked = 0;
+            for task in tasks {
+                match task.await {
+                    Ok((_, slot, Ok(()))) => {
+                        processed_count += 1;
+                        outcomes.push((slot, true));
+                    }
+                    Ok((sig_str, slot, Err(e))) => {
+                        logging::log_error("Transaction error", &format!("{sig_str}: {e}"));
+                        outcomes.push((slot, false));
+                    }
+                    Err(e) => {
+                        logging::log_error("Task join error", &e.to_string());
+                        panicked += 1;
+                    }
+                }
+            }
+            if let Some(pipeline_observer) = &self.pipeline_observer {
+                observer::notify(pipeline_observer, &outcomes, panicked, start_time.elapsed())
+                    .await;
+            }
+
+            if processed_count > 0 {
+                let duration_ms =
+                    u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
+                logging::log_batch(processed_count, total, duration_ms);
+                self.report_metrics();
+            }
+        }
+
+        logging::log(logging::LogLevel::Info, "Graceful shutdown complete.");
+        Ok(())
+    }
+
     /// Internal method to run the Laserstream (gRPC) source loop.
     #[cfg(feature = "laserstream")]
     async fn process_laserstream_source(self) -> Result<()> {
@@ -723,8 +2373,9 @@ impl SolanaIndexer {
 
         // Initialize schema
         for initializer in &self.schema_initializers {
-            initializer.initialize(self.storage.pool()).await?;
+            initializer.initialize(self.storage.require_pool()?).await?;
         }
+        self.bootstrap_snapshots().await?;
 
         loop {
             match source.next_batch().await {
@@ -736,6 +2387,7 @@ impl SolanaIndexer {
                         // For now, we reuse process_transaction_core which can handle full transactions.
 
                         for event in events {
+                            self.check_slot_gap(event.slot());
                             // Clone dependencies for each task/call
                             let fetcher = self.fetcher.clone();
                             let decoder = self.decoder.clone();
@@ -746,60 +2398,243 @@ impl SolanaIndexer {
                             let storage = self.storage.clone();
                             let config = self.config.clone();
 
-                            match event {
-                                crate::streams::TransactionEvent::Signature {
-                                    signature,
-                                    slot: _,
-                                } => {
-                                    // Fallback if full tx not available
-                                    let _ = Self::process_transaction_core(
-                                        signature,
-                                        fetcher,
-                                        decoder,
-                                        decoder_registry,
-                                        log_decoder_registry,
-                                        account_decoder_registry,
-                                        handler_registry,
-                                        storage,
-                                        config,
-                                        true, // Assuming confirmed/finalized from stream
-                                        None,
-                                        None,
-                                    )
-                                    .await;
-                                }
-                                crate::streams::TransactionEvent::FullTransaction {
-                                    signature,
-                                    slot: _,
-                                    tx,
-                                } => {
-                                    let _ = Self::process_transaction_core(
-                                        signature,
-                                        fetcher,
-                                        decoder,
-                                        decoder_registry,
-                                        log_decoder_registry,
-                                        account_decoder_registry,
-                                        handler_registry,
-                                        storage,
-                                        config,
-                                        true, // Assuming confirmed/finalized from stream
-                                        None,
-                                        Some(tx),
-                                    )
-                                    .await;
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
+                            match event {
+                                crate::streams::TransactionEvent::Signature {
+                                    signature,
+                                    slot: _,
+                                } => {
+                                    // Fallback if full tx not available
+                                    let _ = Self::process_transaction_core(
+                                        signature,
+                                        fetcher,
+                                        decoder,
+                                        decoder_registry,
+                                        log_decoder_registry,
+                                        account_decoder_registry,
+                                        handler_registry,
+                                        storage,
+                                        config,
+                                        true, // Assuming confirmed/finalized from stream
+                                        None,
+                                        None,
+                                    )
+                                    .await;
+                                }
+                                crate::streams::TransactionEvent::FullTransaction {
+                                    signature,
+                                    slot: _,
+                                    tx,
+                                } => {
+                                    let _ = Self::process_transaction_core(
+                                        signature,
+                                        fetcher,
+                                        decoder,
+                                        decoder_registry,
+                                        log_decoder_registry,
+                                        account_decoder_registry,
+                                        handler_registry,
+                                        storage,
+                                        config,
+                                        true, // Assuming confirmed/finalized from stream
+                                        None,
+                                        Some(tx),
+                                    )
+                                    .await;
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    logging::log_error("Laserstream error", &e.to_string());
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    /// Replays `transactions` twice into a scratch schema and reports whether
+    /// the registered handlers left their tables unchanged on the second pass.
+    ///
+    /// A fresh schema is created in the configured database, the SDK tables
+    /// and schema initializers are set up in it, and every transaction is run
+    /// through the decoders and handlers twice (skipping the processed-check
+    /// a live indexer would apply). The schema is dropped afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the scratch schema cannot be set up, a transaction
+    /// has no signature, or a handler fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # use solana_indexer_sdk::testing::fixtures::load_fixtures;
+    /// # async fn example(indexer: SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+    /// let transactions = load_fixtures("tests/fixtures/my_program")?;
+    /// let report = indexer.verify_idempotence(transactions).await?;
+    /// for diff in &report.diffs {
+    ///     eprintln!("{}: {} rows added, {} removed", diff.table, diff.added.len(), diff.removed.len());
+    /// }
+    /// assert!(report.is_idempotent());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn verify_idempotence(
+        &self,
+        transactions: Vec<solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta>,
+    ) -> Result<replay::ReplayReport> {
+        let transactions: Vec<_> = transactions.into_iter().map(Arc::new).collect();
+        let schema = format!(
+            "solana_indexer_sdk_replay_{}",
+            chrono::Utc::now().timestamp_millis()
+        );
+        let storage = Arc::new(Storage::new_in_schema(&self.config.database_url, &schema).await?);
+
+        let result = self.replay_twice(&storage, &schema, &transactions).await;
+
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS {schema} CASCADE"))
+            .execute(storage.require_pool()?)
+            .await?;
+        result
+    }
+
+    async fn replay_twice(
+        &self,
+        storage: &Arc<Storage>,
+        schema: &str,
+        transactions: &[Arc<
+            solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta,
+        >],
+    ) -> Result<replay::ReplayReport> {
+        storage.initialize().await?;
+        for initializer in &self.schema_initializers {
+            initializer.initialize(storage.require_pool()?).await?;
+        }
+
+        let mut snapshots = Vec::with_capacity(2);
+        for _ in 0..2 {
+            for transaction in transactions {
+                Self::process_transaction_core(
+                    replay::transaction_signature(transaction)?,
+                    self.fetcher.clone(),
+                    self.decoder.clone(),
+                    self.decoder_registry.clone(),
+                    self.log_decoder_registry.clone(),
+                    self.account_decoder_registry.clone(),
+                    self.handler_registry.clone(),
+                    storage.clone(),
+                    self.config.clone(),
+                    true, // is_finalized
+                    Some("UNKNOWN".to_string()),
+                    Some(transaction.clone()),
+                )
+                .await?;
+            }
+            snapshots.push(replay::snapshot(storage.require_pool()?, schema).await?);
+        }
+
+        Ok(replay::ReplayReport {
+            transactions: transactions.len(),
+            tables: snapshots[0].len().max(snapshots[1].len()),
+            diffs: replay::diff(&snapshots[0], &snapshots[1]),
+        })
+    }
+
+    /// Runs the decoders and handlers again over every processed transaction
+    /// whose slot lies within `slots`, oldest first.
+    ///
+    /// Transactions are read from the archive kept with
+    /// `with_transaction_archive` where possible, and fetched from RPC
+    /// otherwise, so a decoder or handler added later can be filled in
+    /// without a full backfill. Schema initializers run first. A transaction
+    /// that fails is logged and counted, and the replay moves on.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::SolanaIndexer;
+    /// # async fn example(indexer: &SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+    /// let summary = indexer.replay(250_000_000..=250_100_000).await?;
+    /// println!("{} replayed, {} from RPC", summary.replayed, summary.fetched);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a schema initializer fails or the processed
+    /// signatures or archive cannot be read.
+    pub async fn replay(&self, slots: RangeInclusive<u64>) -> Result<ReplaySummary> {
+        for initializer in &self.schema_initializers {
+            initializer.initialize(self.storage.require_pool()?).await?;
+        }
+
+        let mut summary = ReplaySummary::default();
+        let mut after: Option<(u64, String)> = None;
+        loop {
+            let page = self
+                .storage
+                .load_processed_signatures(
+                    (*slots.start(), *slots.end()),
+                    after.as_ref().map(|(slot, sig)| (*slot, sig.as_str())),
+                    REPLAY_PAGE_SIZE,
+                )
+                .await?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            after = Some(last.clone());
+
+            for (slot, sig_str) in page {
+                let mut archived = if self.config.archive_transactions {
+                    TransactionArchive::load(self.storage.require_pool()?, &sig_str)
+                        .await?
+                        .map(Arc::new)
+                } else {
+                    None
+                };
+                if let (None, Some(cold)) = (&archived, &self.cold_archive) {
+                    archived = cold.load(slot, &sig_str).await?;
                 }
-                Err(e) => {
-                    logging::log_error("Laserstream error", &e.to_string());
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                if archived.is_some() {
+                    summary.archived += 1;
+                } else {
+                    summary.fetched += 1;
+                }
+
+                let result = match Signature::from_str(&sig_str) {
+                    Ok(signature) => {
+                        Self::process_transaction_core(
+                            signature,
+                            self.fetcher.clone(),
+                            self.decoder.clone(),
+                            self.decoder_registry.clone(),
+                            self.log_decoder_registry.clone(),
+                            self.account_decoder_registry.clone(),
+                            self.handler_registry.clone(),
+                            self.storage.clone(),
+                            self.config.clone(),
+                            true, // is_finalized
+                            archived.is_some().then(|| "UNKNOWN".to_string()),
+                            archived,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(SolanaIndexerError::Data(format!("Invalid signature: {e}"))),
+                };
+                match result {
+                    Ok(()) => summary.replayed += 1,
+                    Err(e) => {
+                        logging::log_error("Replay failed", &format!("{sig_str}: {e}"));
+                        summary.failed += 1;
+                    }
                 }
             }
         }
+        Ok(summary)
     }
 
     /// Triggers a graceful shutdown programmatically.
@@ -818,7 +2653,7 @@ impl SolanaIndexer {
         logging::log_startup(
             &self
                 .config
-                .program_ids
+                .watched_addresses()
                 .iter()
                 .map(|p| p.to_string())
                 .collect::<Vec<String>>()
@@ -830,68 +2665,37 @@ impl SolanaIndexer {
         // Run schema initializers
         for initializer in &self.schema_initializers {
             logging::log(logging::LogLevel::Info, "Initializing database schema...");
-            initializer.initialize(self.storage.pool()).await?;
+            initializer.initialize(self.storage.require_pool()?).await?;
         }
         logging::log(logging::LogLevel::Success, "Database schema initialized");
+        self.bootstrap_snapshots().await?;
 
-        let mut poll_interval = interval(Duration::from_secs(self.config.poll_interval_secs));
-        let mut last_signature: Option<Signature> = match &self.config.start_strategy {
-            StartStrategy::Latest => {
-                logging::log(
-                    logging::LogLevel::Info,
-                    "Strategy: Latest (Fetching checkpoint from RPC)",
-                );
-                self.fetch_signatures(None)
-                    .await?
-                    .first()
-                    .map(|e| e.signature())
-            }
-            StartStrategy::Signature(sig) => {
-                logging::log(
-                    logging::LogLevel::Info,
-                    &format!("Strategy: Signature (Starting from {sig})"),
-                );
-                Some(*sig)
-            }
-            StartStrategy::Resume => {
-                logging::log(
-                    logging::LogLevel::Info,
-                    "Strategy: Resume (Checking database)",
-                );
-                if let Some(sig_str) = self.storage.get_last_processed_signature().await? {
-                    let sig = Signature::from_str(&sig_str).map_err(|e| {
-                        SolanaIndexerError::InternalError(format!("Invalid signature in DB: {e}"))
-                    })?;
-                    logging::log(logging::LogLevel::Success, &format!("Resuming from: {sig}"));
-                    Some(sig)
-                } else {
-                    logging::log(
-                        logging::LogLevel::Warning,
-                        "No previous state found. Defaulting to Latest.",
-                    );
-                    self.fetch_signatures(None)
-                        .await?
-                        .first()
-                        .map(|e| e.signature())
-                }
-            }
-        };
+        let ladder = FailoverLadder::new(&self.config.failover, self.config.rpc_url(), None);
+        self.run_rpc_loop(ladder).await
+    }
 
-        if let Some(sig) = last_signature {
-            logging::log(
-                logging::LogLevel::Info,
-                &format!("Indexer checkpoint: {sig}"),
-            );
-        }
+    /// Polls the watched addresses over RPC until a fatal error, escalating
+    /// through `ladder` while polls keep failing.
+    async fn run_rpc_loop(mut self, mut ladder: FailoverLadder) -> Result<()> {
+        let mut poll_interval = interval(Duration::from_secs(self.config.poll_interval_secs));
+        let mut schedule = self.initial_cursor_schedule().await?;
 
         logging::log(logging::LogLevel::Info, "Starting indexer loop (RPC)...\n");
 
+        let mut gap_detector = SignatureGapDetector::new(self.config.batch_size);
+        self.seed_gap_anchors(&schedule, &mut gap_detector).await;
+
         loop {
             poll_interval.tick().await;
+            tokio::time::sleep(poll_jitter(self.config.poll_jitter_ms)).await;
 
             let start_time = std::time::Instant::now();
-            match self.poll_and_process(&mut last_signature).await {
+            match self
+                .poll_and_process(&mut schedule, &mut gap_detector)
+                .await
+            {
                 Ok(processed) => {
+                    ladder.record_success();
                     if processed > 0 {
                         let duration_ms =
                             u64::try_from(start_time.elapsed().as_millis()).unwrap_or(u64::MAX);
@@ -902,35 +2706,37 @@ impl SolanaIndexer {
                         self.report_metrics();
                     }
                 }
-                Err(e) => match e {
-                    SolanaIndexerError::RpcError(ref msg) => {
-                        logging::log_error("RPC failure (Exiting)", msg);
-                        return Err(e);
+                Err(SolanaIndexerError::Rpc { message, .. }) if ladder.is_enabled() => {
+                    logging::log_error("RPC failure (Retrying next cycle)", &message);
+                    if let Some(escalation) = ladder.record_failure(&message) {
+                        self.escalate(&escalation);
                     }
-                    SolanaIndexerError::DatabaseError(ref err) => {
+                }
+                Err(e) => {
+                    // RPC calls were already retried under the shared backoff
+                    // policy, so the next poll is the next attempt
+                    if e.is_retryable() {
                         logging::log_error(
-                            "Database failure (Retrying next cycle)",
-                            &err.to_string(),
+                            "Indexing failure (Retrying next cycle)",
+                            &e.to_string(),
                         );
-                        tokio::time::sleep(Duration::from_secs(5)).await;
-                    }
-                    _ => {
-                        logging::log_error("Indexing error", &e.to_string());
-                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    } else {
+                        logging::log_error("Indexing failure (Exiting)", &e.to_string());
+                        return Err(e);
                     }
-                },
+                }
             }
         }
     }
 
     /// Internal method to run the WebSocket subscription loop.
     #[cfg(feature = "websockets")]
-    async fn process_websocket_source(self) -> Result<()> {
+    async fn process_websocket_source(mut self) -> Result<()> {
         // Display startup banner
         logging::log_startup(
             &self
                 .config
-                .program_ids
+                .watched_addresses()
                 .iter()
                 .map(|p| p.to_string())
                 .collect::<Vec<String>>()
@@ -942,9 +2748,10 @@ impl SolanaIndexer {
         // Run schema initializers
         for initializer in &self.schema_initializers {
             logging::log(logging::LogLevel::Info, "Initializing database schema...");
-            initializer.initialize(self.storage.pool()).await?;
+            initializer.initialize(self.storage.require_pool()?).await?;
         }
         logging::log(logging::LogLevel::Success, "Database schema initialized");
+        self.bootstrap_snapshots().await?;
 
         // Extract WebSocket config
         let (ws_url, reconnect_delay) = match &self.config.source {
@@ -954,7 +2761,7 @@ impl SolanaIndexer {
                 ..
             } => (ws_url.clone(), *reconnect_delay_secs),
             _ => {
-                return Err(crate::utils::error::SolanaIndexerError::ConfigError(
+                return Err(crate::utils::error::SolanaIndexerError::Config(
                     "Invalid source config".to_string(),
                 ));
             }
@@ -966,7 +2773,13 @@ impl SolanaIndexer {
         );
 
         let mut source =
-            WebSocketSource::new(ws_url, self.config.program_ids.clone(), reconnect_delay);
+            WebSocketSource::new(ws_url, self.config.watched_addresses(), reconnect_delay)
+                .with_network(self.config.network.clone());
+        let mut ladder = FailoverLadder::new(
+            &self.config.failover,
+            self.config.rpc_url(),
+            Some(("websocket", "rpc")),
+        );
 
         loop {
             if self.cancellation_token.is_cancelled() {
@@ -984,6 +2797,7 @@ impl SolanaIndexer {
 
             match batch {
                 Ok(signatures) => {
+                    ladder.record_success();
                     let start_time = std::time::Instant::now();
                     let mut processed_count = 0;
 
@@ -996,6 +2810,18 @@ impl SolanaIndexer {
                             continue;
                         }
 
+                        if self.config.skip_failed_transactions && event.is_failed() {
+                            continue;
+                        }
+
+                        if !prefilter::passes(
+                            &self.prefilters,
+                            &TransactionPreview::from_event(&event),
+                        ) {
+                            continue;
+                        }
+                        self.check_slot_gap(event.slot());
+
                         // Optimization for LogEvents
                         match &event {
                             crate::streams::TransactionEvent::LogEvent {
@@ -1010,37 +2836,21 @@ impl SolanaIndexer {
                                 // Parse logs directly
                                 match self.decoder.parse_event_logs(logs) {
                                     Ok(parsed_events) => {
-                                        let decoded =
-                                            self.log_decoder_registry.decode_logs(&parsed_events);
-
-                                        // Construct partial context for log optimization
-                                        let context = TxMetadata {
-                                            slot: *slot,
-                                            block_time: None, // Not available in log event
-                                            fee: 0,           // Not available
-                                            pre_balances: vec![],
-                                            post_balances: vec![],
-                                            pre_token_balances: vec![],
-                                            post_token_balances: vec![],
-                                            signature: sig_str.clone(),
-                                        };
-
-                                        // Handle decoded events
-                                        for (discriminator, event_data) in decoded {
-                                            self.handler_registry
-                                                .handle(
-                                                    &discriminator,
-                                                    &event_data,
-                                                    &context,
-                                                    self.storage.pool(),
-                                                )
-                                                .await?;
+                                        match self
+                                            .process_log_event(
+                                                &sig_str,
+                                                *slot,
+                                                logs,
+                                                &parsed_events,
+                                            )
+                                            .await
+                                        {
+                                            Ok(()) => processed_count += 1,
+                                            Err(e) => logging::log_error(
+                                                "Transaction error",
+                                                &format!("{sig_str}: {e}"),
+                                            ),
                                         }
-
-                                        // Mark as processed
-                                        self.storage.mark_processed(&sig_str, *slot).await?;
-
-                                        processed_count += 1;
                                         continue;
                                     }
                                     Err(e) => {
@@ -1074,6 +2884,19 @@ impl SolanaIndexer {
                 }
                 Err(e) => {
                     logging::log_error("WebSocket error", &e.to_string());
+                    match ladder.record_failure(&e.to_string()) {
+                        Some(escalation @ Escalation::FallbackSource { .. }) => {
+                            self.escalate(&escalation);
+                            self.config.source = SourceConfig::Rpc {
+                                rpc_url: self.config.rpc_url().to_string(),
+                                poll_interval_secs: self.config.poll_interval_secs,
+                                batch_size: self.config.batch_size,
+                            };
+                            return self.run_rpc_loop(ladder).await;
+                        }
+                        Some(escalation) => self.escalate(&escalation),
+                        None => {}
+                    }
                     tokio::time::sleep(Duration::from_secs(reconnect_delay)).await;
                 }
             }
@@ -1086,7 +2909,7 @@ impl SolanaIndexer {
         logging::log_startup(
             &self
                 .config
-                .program_ids
+                .watched_addresses()
                 .iter()
                 .map(|p| p.to_string())
                 .collect::<Vec<String>>()
@@ -1098,8 +2921,9 @@ impl SolanaIndexer {
         // Run schema initializers
         for initializer in &self.schema_initializers {
             logging::log(logging::LogLevel::Info, "Initializing database schema...");
-            initializer.initialize(self.storage.pool()).await?;
+            initializer.initialize(self.storage.require_pool()?).await?;
         }
+        self.bootstrap_snapshots().await?;
 
         // Extract Hybrid config
         let (ws_url, rpc_url, poll_interval, reconnect_delay, gap_threshold) =
@@ -1118,7 +2942,7 @@ impl SolanaIndexer {
                     *gap_threshold_slots,
                 ),
                 _ => {
-                    return Err(crate::utils::error::SolanaIndexerError::ConfigError(
+                    return Err(crate::utils::error::SolanaIndexerError::Config(
                         "Invalid source config".to_string(),
                     ));
                 }
@@ -1129,13 +2953,14 @@ impl SolanaIndexer {
             &format!("Starting indexer loop (Hybrid: WS={ws_url}, RPC={rpc_url})...\n"),
         );
 
-        let mut source = crate::streams::hybrid::HybridSource::new(
+        let mut source = crate::streams::hybrid::HybridSource::new_with_network(
             ws_url,
             rpc_url,
-            self.config.program_ids.clone(),
+            self.config.watched_addresses(),
             poll_interval,
             reconnect_delay,
             gap_threshold,
+            self.config.network.clone(),
         );
 
         loop {
@@ -1166,6 +2991,18 @@ impl SolanaIndexer {
                             continue;
                         }
 
+                        if self.config.skip_failed_transactions && event.is_failed() {
+                            continue;
+                        }
+
+                        if !prefilter::passes(
+                            &self.prefilters,
+                            &TransactionPreview::from_event(&event),
+                        ) {
+                            continue;
+                        }
+                        self.check_slot_gap(event.slot());
+
                         // Optimization for LogEvents
                         match &event {
                             crate::streams::TransactionEvent::LogEvent {
@@ -1180,37 +3017,21 @@ impl SolanaIndexer {
                                 // Parse logs directly
                                 match self.decoder.parse_event_logs(logs) {
                                     Ok(parsed_events) => {
-                                        let decoded =
-                                            self.log_decoder_registry.decode_logs(&parsed_events);
-
-                                        // Construct partial context for log optimization
-                                        let context = TxMetadata {
-                                            slot: *slot,
-                                            block_time: None, // Not available in log event
-                                            fee: 0,           // Not available
-                                            pre_balances: vec![],
-                                            post_balances: vec![],
-                                            pre_token_balances: vec![],
-                                            post_token_balances: vec![],
-                                            signature: sig_str.clone(),
-                                        };
-
-                                        // Handle decoded events
-                                        for (discriminator, event_data) in decoded {
-                                            self.handler_registry
-                                                .handle(
-                                                    &discriminator,
-                                                    &event_data,
-                                                    &context,
-                                                    self.storage.pool(),
-                                                )
-                                                .await?;
+                                        match self
+                                            .process_log_event(
+                                                &sig_str,
+                                                *slot,
+                                                logs,
+                                                &parsed_events,
+                                            )
+                                            .await
+                                        {
+                                            Ok(()) => processed_count += 1,
+                                            Err(e) => logging::log_error(
+                                                "Transaction error",
+                                                &format!("{sig_str}: {e}"),
+                                            ),
                                         }
-
-                                        // Mark as processed
-                                        self.storage.mark_processed(&sig_str, *slot).await?;
-
-                                        processed_count += 1;
                                         continue;
                                     }
                                     Err(e) => {
@@ -1258,7 +3079,7 @@ impl SolanaIndexer {
         logging::log_startup(
             &self
                 .config
-                .program_ids
+                .watched_addresses()
                 .iter()
                 .map(|p| p.to_string())
                 .collect::<Vec<String>>()
@@ -1270,9 +3091,10 @@ impl SolanaIndexer {
         // Run schema initializers
         for initializer in &self.schema_initializers {
             logging::log(logging::LogLevel::Info, "Initializing database schema...");
-            initializer.initialize(self.storage.pool()).await?;
+            initializer.initialize(self.storage.require_pool()?).await?;
         }
         logging::log(logging::LogLevel::Success, "Database schema initialized");
+        self.bootstrap_snapshots().await?;
 
         // Instantiate HeliusSource on demand from configuration
         let mut source = HeliusSource::new(self.config.clone()).await?;
@@ -1284,6 +3106,7 @@ impl SolanaIndexer {
 
         // Semaphore to limit concurrent transaction processing
         let semaphore = Arc::new(tokio::sync::Semaphore::new(100)); // Limit to 100 concurrent tasks
+        let mut executor = KeyedExecutor::new(100);
 
         loop {
             if self.cancellation_token.is_cancelled() {
@@ -1312,6 +3135,18 @@ impl SolanaIndexer {
                             continue;
                         }
 
+                        if self.config.skip_failed_transactions && event.is_failed() {
+                            continue;
+                        }
+
+                        if !prefilter::passes(
+                            &self.prefilters,
+                            &TransactionPreview::from_event(&event),
+                        ) {
+                            continue;
+                        }
+                        self.check_slot_gap(event.slot());
+
                         // Optimization: If indexing mode is Logs Only, decode logs directly
                         match &event {
                             crate::streams::TransactionEvent::LogEvent {
@@ -1326,37 +3161,21 @@ impl SolanaIndexer {
                                 // Parse logs
                                 match self.decoder.parse_event_logs(logs) {
                                     Ok(parsed_events) => {
-                                        let decoded =
-                                            self.log_decoder_registry.decode_logs(&parsed_events);
-
-                                        // Construct partial context for log optimization
-                                        let context = TxMetadata {
-                                            slot: *slot,
-                                            block_time: None, // Not available in log event
-                                            fee: 0,           // Not available
-                                            pre_balances: vec![],
-                                            post_balances: vec![],
-                                            pre_token_balances: vec![],
-                                            post_token_balances: vec![],
-                                            signature: sig_str.clone(),
-                                        };
-
-                                        // Handle decoded events
-                                        for (discriminator, event_data) in decoded {
-                                            self.handler_registry
-                                                .handle(
-                                                    &discriminator,
-                                                    &event_data,
-                                                    &context,
-                                                    self.storage.pool(),
-                                                )
-                                                .await?;
+                                        match self
+                                            .process_log_event(
+                                                &sig_str,
+                                                *slot,
+                                                logs,
+                                                &parsed_events,
+                                            )
+                                            .await
+                                        {
+                                            Ok(()) => processed_count += 1,
+                                            Err(e) => logging::log_error(
+                                                "Transaction error",
+                                                &format!("{sig_str}: {e}"),
+                                            ),
                                         }
-                                        // Mark as processed
-                                        self.storage.mark_processed(&sig_str, *slot).await?;
-
-                                        // Skip full processing
-                                        processed_count += 1;
                                         continue;
                                     }
                                     Err(e) => {
@@ -1371,10 +3190,14 @@ impl SolanaIndexer {
                             _ => {}
                         }
 
-                        // Acquire permit
-                        let permit = semaphore.clone().acquire_owned().await.map_err(|e| {
-                            SolanaIndexerError::InternalError(format!("Semaphore error: {e}"))
-                        })?;
+                        // Acquire permit, unless the executor orders the task
+                        let key = self.ordering_key(&event, None);
+                        let permit = match key {
+                            Some(_) => None,
+                            None => Some(semaphore.clone().acquire_owned().await.map_err(|e| {
+                                SolanaIndexerError::Internal(format!("Semaphore error: {e}"))
+                            })?),
+                        };
 
                         // Clone Arcs for the task
                         let fetcher = self.fetcher.clone();
@@ -1394,7 +3217,7 @@ impl SolanaIndexer {
                         };
 
                         // Spawn task
-                        tokio::spawn(async move {
+                        executor.spawn(key, async move {
                             match Self::process_transaction_core(
                                 signature,
                                 fetcher,
@@ -1448,37 +3271,212 @@ impl SolanaIndexer {
         Ok(())
     }
 
-    async fn poll_and_process(&self, last_signature: &mut Option<Signature>) -> Result<usize> {
-        // Fetch new signatures
-        let signatures = self.fetch_signatures(last_signature.as_ref()).await?;
+    /// Anchors gap detection at the slot of each address's starting cursor,
+    /// so the first poll, which covers any downtime, is checked for gaps too.
+    async fn seed_gap_anchors(
+        &self,
+        schedule: &CursorSchedule,
+        gap_detector: &mut SignatureGapDetector,
+    ) {
+        let mut slots = HashMap::new();
+        for address in schedule.addresses() {
+            let Some(cursor) = schedule.cursor(address) else {
+                continue;
+            };
+            let slot = match slots.get(&cursor) {
+                Some(slot) => *slot,
+                None => match self.fetcher.fetch_transaction(&cursor).await {
+                    Ok(transaction) => *slots.entry(cursor).or_insert(transaction.slot),
+                    Err(e) => {
+                        logging::log_error(
+                            &format!("Gap detection for {address} starts unanchored"),
+                            &e.to_string(),
+                        );
+                        continue;
+                    }
+                },
+            };
+            gap_detector.seed(*address, slot);
+        }
+    }
+
+    /// Builds the per-address cursor schedule according to the start strategy.
+    async fn initial_cursor_schedule(&self) -> Result<CursorSchedule> {
+        let addresses = self.config.watched_addresses();
+        let mut schedule = CursorSchedule::new(addresses.clone(), self.config.addresses_per_poll);
+        let mut start_at_latest = Vec::new();
+
+        match &self.config.start_strategy {
+            StartStrategy::Latest => {
+                logging::log(
+                    logging::LogLevel::Info,
+                    "Strategy: Latest (Fetching checkpoint from RPC)",
+                );
+                start_at_latest = addresses;
+            }
+            StartStrategy::Signature(sig) => {
+                logging::log(
+                    logging::LogLevel::Info,
+                    &format!("Strategy: Signature (Starting from {sig})"),
+                );
+                for address in &addresses {
+                    schedule.set_cursor(*address, *sig);
+                }
+            }
+            StartStrategy::Resume => {
+                logging::log(
+                    logging::LogLevel::Info,
+                    "Strategy: Resume (Checking database)",
+                );
+                // Databases written before per-address cursors only have the
+                // global last signature; use it for addresses without a cursor.
+                let fallback = match self.storage.get_last_processed_signature().await? {
+                    Some(sig_str) => Some(parse_stored_signature(&sig_str)?),
+                    None => None,
+                };
+
+                for address in &addresses {
+                    let cursor = match self.storage.load_cursor(&address.to_string()).await? {
+                        Some(sig_str) => Some(parse_stored_signature(&sig_str)?),
+                        None => fallback,
+                    };
+                    match cursor {
+                        Some(sig) => schedule.set_cursor(*address, sig),
+                        None => start_at_latest.push(*address),
+                    }
+                }
+
+                if start_at_latest.len() == addresses.len() {
+                    logging::log(
+                        logging::LogLevel::Warning,
+                        "No previous state found. Defaulting to Latest.",
+                    );
+                } else {
+                    logging::log(
+                        logging::LogLevel::Success,
+                        &format!(
+                            "Resuming {} of {} addresses from stored cursors",
+                            addresses.len() - start_at_latest.len(),
+                            addresses.len()
+                        ),
+                    );
+                }
+            }
+        }
+
+        if !start_at_latest.is_empty() {
+            let targets: Vec<_> = start_at_latest
+                .into_iter()
+                .map(|address| (address, None))
+                .collect();
+            let (pages, _) = self.fetch_signature_pages(&targets).await?;
+            for (address, page) in pages {
+                if let Some(newest) = page.first() {
+                    schedule.set_cursor(address, newest.signature());
+                }
+            }
+        }
+
+        for address in schedule.addresses() {
+            if let Some(sig) = schedule.cursor(address) {
+                logging::log(
+                    logging::LogLevel::Info,
+                    &format!("Indexer checkpoint for {address}: {sig}"),
+                );
+            }
+        }
+
+        Ok(schedule)
+    }
+
+    async fn poll_and_process(
+        &self,
+        schedule: &mut CursorSchedule,
+        gap_detector: &mut SignatureGapDetector,
+    ) -> Result<usize> {
+        let started = std::time::Instant::now();
+        // Fetch new signatures for the addresses due this tick
+        let targets = schedule.next_batch();
+        let (pages, filtered) = self.fetch_signature_pages(&targets).await?;
+
+        let mut signatures = Vec::new();
+        let mut polled = Vec::new();
+        for (address, page) in pages {
+            if let Some(range) = gap_detector.observe(&address, &page) {
+                self.schedule_gap_repair(&address, range);
+            }
+            let page_cursors: Vec<(Signature, u64)> = page
+                .iter()
+                .map(|event| (event.signature(), event.slot()))
+                .collect();
+            polled.push((address, page_cursors));
+            signatures.extend(page.into_iter().map(|event| (address, event)));
+        }
 
         if signatures.is_empty() {
             return Ok(0);
         }
 
-        // Update last signature for next poll
-        if let Some(first_event) = signatures.first() {
-            *last_signature = Some(first_event.signature());
+        // Ordered keys need each address's signatures oldest first, while
+        // pages list them newest first
+        if self.ordering.is_some() {
+            signatures.reverse();
+            signatures.sort_by_key(|(_, event)| event.slot());
         }
 
         let concurrency = self.config.worker_threads;
         let semaphore = Arc::new(Semaphore::new(concurrency));
+        let mut executor = KeyedExecutor::new(concurrency);
         let mut tasks = Vec::new();
+        // (slot, succeeded) for every signature in the batch, used to advance
+        // the processed-slot watermark once the batch has settled.
+        let mut outcomes = Vec::with_capacity(signatures.len());
+
+        // A transaction touching several watched addresses appears once per address
+        let mut seen = std::collections::HashSet::new();
+
+        // Fetch the Helius-parsed events of the whole poll in one go, rather
+        // than one request per transaction
+        #[cfg(feature = "helius")]
+        if self.config.indexing_mode.logs {
+            let mut unique = std::collections::HashSet::new();
+            let pending: Vec<Signature> = signatures
+                .iter()
+                .map(|(_, event)| event.signature())
+                .filter(|signature| !filtered.contains(signature) && unique.insert(*signature))
+                .collect();
+            self.fetcher.prefetch_enhanced_events(&pending).await;
+        }
 
-        for event in signatures {
+        for (address, event) in signatures {
             let signature = event.signature();
+            if !seen.insert(signature) {
+                continue;
+            }
+            let slot = event.slot();
             let sig_str = signature.to_string();
 
+            // Rejected by a prefilter: nothing to fetch, so the slot is settled
+            if filtered.contains(&signature) {
+                outcomes.push((slot, true));
+                continue;
+            }
+
             // Check if already processed (idempotency)
             if self.storage.is_processed(&sig_str).await? {
+                outcomes.push((slot, true));
                 continue;
             }
 
-            // Acquire permit
+            // Acquire permit, unless the executor orders the task
+            let key = self.ordering_key(&event, Some(address));
             let permit =
-                semaphore.clone().acquire_owned().await.map_err(|e| {
-                    SolanaIndexerError::InternalError(format!("Semaphore error: {e}"))
-                })?;
+                match key {
+                    Some(_) => None,
+                    None => Some(semaphore.clone().acquire_owned().await.map_err(|e| {
+                        SolanaIndexerError::Internal(format!("Semaphore error: {e}"))
+                    })?),
+                };
 
             let fetcher = self.fetcher.clone();
             let decoder = self.decoder.clone();
@@ -1488,9 +3486,10 @@ impl SolanaIndexer {
             let handler_registry = self.handler_registry.clone();
             let storage = self.storage.clone();
             let config = self.config.clone();
+            let usage = self.usage.clone();
 
-            tasks.push(tokio::spawn(async move {
-                let res = Self::process_transaction_core(
+            tasks.push(executor.spawn(key, async move {
+                let process = Self::process_transaction_core(
                     signature,
                     fetcher,
                     decoder,
@@ -1503,84 +3502,264 @@ impl SolanaIndexer {
                     false, // is_finalized
                     None,
                     None, // preloaded_transaction
-                )
-                .await;
+                );
+                // RPC calls made for this transaction count against the
+                // address it was found under
+                let res = match usage {
+                    Some(tracker) => usage::attribute(tracker, address.to_string(), process).await,
+                    None => process.await,
+                };
                 drop(permit);
-                (sig_str, res)
+                (signature, sig_str, slot, res)
             }));
         }
 
-        let mut processed_count = 0;
-        for task in tasks {
-            match task.await {
-                Ok((sig_str, res)) => match res {
-                    Ok(()) => processed_count += 1,
-                    Err(e) => {
-                        logging::log_error("Transaction error", &format!("{sig_str}: {e}"));
-                        eprintln!("Error processing transaction {sig_str}: {e}");
-                    }
-                },
-                Err(e) => {
-                    logging::log_error("Task join error", &e.to_string());
-                }
+        let mut processed_count = 0;
+        let mut panicked = 0;
+        let mut failed = std::collections::HashSet::new();
+        for task in tasks {
+            match task.await {
+                Ok((signature, sig_str, slot, res)) => match res {
+                    Ok(()) => {
+                        processed_count += 1;
+                        outcomes.push((slot, true));
+                    }
+                    Err(e) => {
+                        logging::log_error("Transaction error", &format!("{sig_str}: {e}"));
+                        eprintln!("Error processing transaction {sig_str}: {e}");
+                        outcomes.push((slot, false));
+                        failed.insert(signature);
+                    }
+                },
+                Err(e) => {
+                    logging::log_error("Task join error", &e.to_string());
+                    // The slot of a panicked task is unknown, so hold the watermark.
+                    panicked += 1;
+                }
+            }
+        }
+
+        // Advance each address's cursor for its next poll, but not past a
+        // failed signature, so the next poll retries it. The signature of a
+        // panicked task is unknown, so then every cursor stays put.
+        if panicked == 0 {
+            for (address, page) in polled {
+                if let Some((signature, slot)) = settled_cursor(&page, &failed) {
+                    schedule.set_cursor(address, signature);
+                    self.storage
+                        .save_cursor(&address.to_string(), &signature.to_string(), slot)
+                        .await?;
+                }
+            }
+        }
+
+        let mut processed_slots: Vec<u64> = outcomes
+            .iter()
+            .filter(|(_, succeeded)| *succeeded)
+            .map(|(slot, _)| *slot)
+            .collect();
+        processed_slots.sort_unstable();
+        for slot in processed_slots {
+            self.check_slot_gap(slot);
+        }
+
+        if panicked == 0 {
+            if let Some(watermark) = batch_watermark(&outcomes) {
+                self.storage.advance_watermark(watermark).await?;
+                self.watermark.publish(watermark).await;
+            }
+        }
+        if let Some(pipeline_observer) = &self.pipeline_observer {
+            observer::notify(pipeline_observer, &outcomes, panicked, started.elapsed()).await;
+        }
+
+        Ok(processed_count)
+    }
+
+    /// Schedules a backfill of `range` after a signature gap was detected.
+    ///
+    /// The gap is always logged; the repair only runs when backfill is enabled.
+    fn schedule_gap_repair(&self, program_id: &solana_sdk::pubkey::Pubkey, range: BackfillRange) {
+        logging::log(
+            logging::LogLevel::Warning,
+            &format!(
+                "Signature gap detected for {program_id}: page did not reach the previous anchor (slots {}-{})",
+                range.start_slot, range.end_slot
+            ),
+        );
+        self.repair_gap(range);
+    }
+
+    /// Records the slot of a live transaction with the slot-gap alarm, and
+    /// alerts and schedules a repair if it jumped too far.
+    fn check_slot_gap(&self, slot: u64) {
+        let Some(range) = self
+            .slot_gap_alarm
+            .as_ref()
+            .and_then(|alarm| alarm.observe(slot))
+        else {
+            return;
+        };
+        logging::log(
+            logging::LogLevel::Warning,
+            &format!(
+                "Slot gap detected on the live source: jumped from slot {} to {}",
+                range.start_slot, range.end_slot
+            ),
+        );
+        if let Some(observer) = &self.error_observer {
+            observer.on_slot_gap(&range);
+        }
+        self.repair_gap(range);
+    }
+
+    /// Backfills `range` in the background if backfill is enabled.
+    fn repair_gap(&self, range: BackfillRange) {
+        if !self.config.backfill.enabled {
+            logging::log(
+                logging::LogLevel::Warning,
+                "Backfill disabled in config, gap will not be repaired.",
+            );
+            return;
+        }
+
+        let engine = self.range_backfill_engine(range);
+        tokio::spawn(async move {
+            if let Err(e) = engine.start_range(range).await {
+                logging::log_error("Gap repair failed", &e.to_string());
+            }
+        });
+    }
+
+    /// Fetches one page of signatures per address, newest first, stopping at
+    /// each address's cursor.
+    ///
+    /// Pages are complete so cursors and gap detection see every signature;
+    /// the signatures rejected by a prefilter are returned separately.
+    async fn fetch_signature_pages(
+        &self,
+        targets: &[(solana_sdk::pubkey::Pubkey, Option<Signature>)],
+    ) -> Result<(
+        Vec<(
+            solana_sdk::pubkey::Pubkey,
+            Vec<crate::streams::TransactionEvent>,
+        )>,
+        std::collections::HashSet<Signature>,
+    )> {
+        let mut all_pages = Vec::new();
+        let mut filtered = std::collections::HashSet::new();
+
+        for (address, cursor) in targets {
+            if let Some(usage) = &self.usage {
+                usage.record_rpc_calls(&address.to_string(), 1);
             }
+            let sigs = self
+                .fetcher
+                .fetch_signature_page(address, *cursor, self.config.batch_size)
+                .await?;
+
+            let events: Vec<crate::streams::TransactionEvent> = sigs
+                .iter()
+                .filter_map(|s| {
+                    Signature::from_str(&s.signature).ok().map(|sig| {
+                        if !prefilter::passes(
+                            &self.prefilters,
+                            &TransactionPreview::from_status(sig, s),
+                        ) {
+                            filtered.insert(sig);
+                        }
+                        crate::streams::TransactionEvent::Signature {
+                            signature: sig,
+                            slot: s.slot,
+                        }
+                    })
+                })
+                .collect();
+            all_pages.push((*address, events));
         }
 
-        Ok(processed_count)
+        Ok((all_pages, filtered))
     }
 
-    async fn fetch_signatures(
+    /// Handles a log notification without fetching its transaction, for
+    /// sources indexing logs only.
+    ///
+    /// Events are decoded within the scope the logs allow, and go through
+    /// the same retries, dead-lettering and spilling as fetched transactions.
+    #[cfg(any(feature = "websockets", feature = "helius"))]
+    async fn process_log_event(
         &self,
-        last_signature: Option<&Signature>,
-    ) -> Result<Vec<crate::streams::TransactionEvent>> {
-        use solana_client::rpc_client::RpcClient;
-        use solana_sdk::commitment_config::CommitmentConfig;
-
-        let rpc_url = self.config.rpc_url().to_string();
-        let program_ids = self.config.program_ids.clone();
-        let batch_size = self.config.batch_size;
-        let last_sig = last_signature.copied();
-
-        tokio::task::spawn_blocking(move || {
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
-            let mut all_events = Vec::new();
-
-            for program_id in program_ids {
-                #[allow(deprecated)]
-                let sigs = rpc_client
-                    .get_signatures_for_address_with_config(
-                        &program_id,
-                        solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config {
-                            before: None,
-                            until: last_sig,
-                            limit: Some(batch_size),
-                            commitment: Some(CommitmentConfig::confirmed()),
-                        },
-                    )
-                    .map_err(|e| {
-                        crate::utils::error::SolanaIndexerError::RpcError(e.to_string())
-                    })?;
-
-                let events: Vec<crate::streams::TransactionEvent> = sigs
-                    .into_iter()
-                    .filter_map(|s| {
-                        Signature::from_str(&s.signature).ok().map(|sig| {
-                            crate::streams::TransactionEvent::Signature {
-                                signature: sig,
-                                slot: s.slot,
-                            }
-                        })
-                    })
-                    .collect();
-                all_events.extend(events);
-            }
+        sig_str: &str,
+        slot: u64,
+        logs: &[String],
+        parsed_events: &[crate::types::events::ParsedEvent],
+    ) -> Result<()> {
+        // Already waiting in the spill queue for the database to come back
+        if self
+            .spill_queue
+            .as_ref()
+            .is_some_and(|queue| queue.contains(sig_str))
+        {
+            return Ok(());
+        }
 
-            Ok(all_events)
-        })
-        .await
-        .map_err(|e| crate::utils::error::SolanaIndexerError::InternalError(e.to_string()))?
+        let decoded = self
+            .log_decoder_registry
+            .decode_events(parsed_events, &ProgramScope::for_logs(&self.config, logs));
+        if reject_oversized(
+            self.storage.as_ref(),
+            &self.config,
+            self.handler_registry.metrics(),
+            sig_str,
+            slot,
+            decoded.len(),
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        // Construct partial context for log optimization
+        let context = TxMetadata {
+            slot,
+            block_time: None, // Not available in log event
+            fee: 0,           // Not available
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: sig_str.to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: self.handler_registry.feature_flags().clone(),
+            jobs: self.handler_registry.deferred_jobs().clone(),
+            writes: TxWrites::new(self.config.atomic_transactions),
+        };
+
+        let dispatch = dispatch_events(
+            &self.handler_registry,
+            self.storage.as_ref(),
+            context,
+            decoded,
+            None,
+            false,
+        )
+        .await?;
+        let Dispatch::Handled { context, .. } = dispatch else {
+            return Ok(());
+        };
+
+        // Mark as processed
+        mark_handled(self.storage.as_ref(), &context, slot, None).await?;
+        track_commitment(self.storage.as_ref(), &self.config, sig_str, slot, false).await
     }
 
+    #[cfg(any(feature = "websockets", feature = "helius"))]
     async fn process_transaction(&self, signature: &Signature) -> Result<()> {
         Self::process_transaction_core(
             *signature,
@@ -1599,6 +3778,23 @@ impl SolanaIndexer {
         .await
     }
 
+    /// Logs an escalation chosen by the failover ladder, switches the RPC
+    /// endpoint if it asks for one, and reports it to the error observer.
+    /// Source fallbacks are carried out by the source loop itself.
+    fn escalate(&mut self, escalation: &Escalation) {
+        logging::log(
+            logging::LogLevel::Warning,
+            &format!("Failover: {escalation}"),
+        );
+        if let Escalation::SwitchEndpoint { to, .. } = escalation {
+            self.config.set_rpc_url(to);
+            self.fetcher = Arc::new(Self::build_fetcher(&self.config));
+        }
+        if let Some(observer) = &self.error_observer {
+            observer.on_escalation(escalation);
+        }
+    }
+
     /// Logs metrics for all registries if metrics are enabled.
     fn report_metrics(&self) {
         if self.config.registry.enable_metrics {
@@ -1606,6 +3802,31 @@ impl SolanaIndexer {
             self.log_decoder_registry.metrics().report();
             self.account_decoder_registry.metrics().report();
             self.handler_registry.metrics().report();
+            if self.config.slot_tracking {
+                self.slot_tracker.report();
+            }
+            if let Some(usage) = &self.usage {
+                usage.report();
+            }
+            if self.config.retention.is_some() {
+                self.retention_metrics.report();
+            }
+            if self.config.vacuum.is_some() {
+                self.vacuum_metrics.report();
+            }
+            self.table_stats.report();
+            if let Some(replica) = &self.replica {
+                replica.report();
+            }
+            if self.watermark.latest().is_some() {
+                self.watermark.report();
+            }
+            if let Some(cache) = &self.processed_cache {
+                cache.report();
+            }
+            if let Some(queue) = &self.spill_queue {
+                queue.report();
+            }
         }
     }
 
@@ -1630,260 +3851,541 @@ impl SolanaIndexer {
     ) -> Result<()> {
         let sig_str = signature.to_string();
 
+        // Already waiting in the spill queue for the database to come back
+        let spill_queue = handler_registry.spill_queue();
+        if spill_queue.is_some_and(|queue| queue.contains(&sig_str)) {
+            return Ok(());
+        }
+
         // Fetch transaction if not preloaded
         let transaction = if let Some(tx) = preloaded_transaction {
             tx
         } else {
             Arc::new(fetcher.fetch_transaction(&signature).await?)
         };
+        let fetched_slot = transaction.slot;
+        let Some(transaction) = resolve_missing_meta(
+            &fetcher,
+            &config,
+            handler_registry.metrics(),
+            &signature,
+            transaction,
+        )
+        .await?
+        else {
+            storage.mark_processed(&sig_str, fetched_slot).await?;
+            return Ok(());
+        };
+
+        // Apply source-level filters before spending any work on decoding
+        let tx_error = transaction_error(&transaction);
+        if (config.skip_failed_transactions && tx_error.is_some())
+            || (config.skip_vote_transactions && is_vote_transaction(&transaction))
+        {
+            storage.mark_processed(&sig_str, transaction.slot).await?;
+            return Ok(());
+        }
+
+        if config.archive_transactions {
+            TransactionArchive::store(storage.require_pool()?, &sig_str, &transaction).await?;
+        }
 
         // Decode transaction metadata
         let decoded_meta = decoder.decode_transaction(&transaction)?;
         let slot = decoded_meta.slot;
 
-        // Extract native metadata
-        let meta = transaction.transaction.meta.as_ref().ok_or_else(|| {
-            SolanaIndexerError::DecodingError("Missing transaction metadata".into())
-        })?;
-
-        let pre_token_balances_opt: Option<
-            Vec<solana_transaction_status::UiTransactionTokenBalance>,
-        > = meta.pre_token_balances.clone().into();
-        let pre_token_balances = pre_token_balances_opt.unwrap_or_default();
-        let post_token_balances_opt: Option<
-            Vec<solana_transaction_status::UiTransactionTokenBalance>,
-        > = meta.post_token_balances.clone().into();
-        let post_token_balances = post_token_balances_opt.unwrap_or_default();
-
         // Construct context
-        let context = TxMetadata {
-            slot,
-            block_time: transaction.block_time,
-            fee: meta.fee,
-            pre_balances: meta.pre_balances.clone(),
-            post_balances: meta.post_balances.clone(),
-            pre_token_balances: pre_token_balances
-                .into_iter()
-                .map(|b| TokenBalanceInfo {
-                    account_index: b.account_index,
-                    mint: b.mint,
-                    owner: Into::<Option<String>>::into(b.owner).unwrap_or_default(),
-                    amount: b.ui_token_amount.amount,
-                    decimals: b.ui_token_amount.decimals,
-                    program_id: b.program_id.into(),
-                })
-                .collect(),
-            post_token_balances: post_token_balances
-                .into_iter()
-                .map(|b| TokenBalanceInfo {
-                    account_index: b.account_index,
-                    mint: b.mint,
-                    owner: Into::<Option<String>>::into(b.owner).unwrap_or_default(),
-                    amount: b.ui_token_amount.amount,
-                    decimals: b.ui_token_amount.decimals,
-                    program_id: b.program_id.into(),
-                })
-                .collect(),
-            signature: sig_str.clone(),
-        };
+        let mut context = TxMetadata::from_transaction(&transaction, sig_str.clone(), tx_error);
+        context.flags = handler_registry.feature_flags().clone();
+        context.jobs = handler_registry.deferred_jobs().clone();
+        context.writes = TxWrites::new(config.atomic_transactions);
+        if !config.token_holder_mints.is_empty() {
+            let holdings = holders::holdings(
+                &context,
+                &account_keys(&transaction),
+                &config.token_holder_mints,
+            );
+            TokenHolders::upsert(storage.require_pool()?, &holdings).await?;
+        }
 
         let block_hash = if let Some(h) = known_block_hash {
             h
         } else {
             match fetcher.fetch_block(slot).await {
-                Ok(block) => block.blockhash,
+                Ok(block) => {
+                    context.index_in_block = transaction_index(&block, &sig_str);
+                    block.blockhash
+                }
                 Err(_) => "UNKNOWN".to_string(),
             }
         };
+        if context.index_in_block.is_none() && config.block_index {
+            context.index_in_block = fetcher.transaction_index(slot, &sig_str).await?;
+        }
 
         // Extract UI instructions from the transaction
-        let instructions: &[solana_transaction_status::UiInstruction] = match &transaction
-            .transaction
-            .transaction
+        let instructions = instructions(&transaction);
+
+        // Decode instruction and log events up front, so oversized
+        // transactions are caught before any handler runs
+        let scope = ProgramScope::for_transaction(&config, &transaction, &instructions);
+        let mut events = Vec::new();
+        if config.indexing_mode.inputs {
+            let inner = if config.inner_instructions {
+                inner_instructions(&transaction)
+            } else {
+                &[]
+            };
+            events.extend(decoder_registry.decode_with_accounts(
+                &instructions,
+                inner,
+                TransactionAccount::from_transaction(&transaction),
+                &scope,
+            ));
+        }
+        if config.indexing_mode.logs {
+            events.extend(log_decoder_registry.decode_events(&decoded_meta.events, &scope));
+
+            // Helius-parsed events already carry their discriminator. They
+            // are a bonus: without them the raw transaction is still processed
+            #[cfg(feature = "helius")]
+            match fetcher.fetch_enhanced_events(&signature).await {
+                Ok(enhanced) => events.extend(
+                    enhanced
+                        .iter()
+                        .filter_map(crate::streams::helius_enhanced::decode_event),
+                ),
+                Err(e) => logging::log(
+                    logging::LogLevel::Warning,
+                    &format!("Helius enhanced transaction unavailable for {sig_str}: {e}"),
+                ),
+            }
+        }
+
+        if reject_oversized(
+            storage.as_ref(),
+            &config,
+            handler_registry.metrics(),
+            &sig_str,
+            slot,
+            events.len(),
+        )
+        .await?
+        {
+            return Ok(());
+        }
+
+        // Account states of the writable accounts are dispatched after the
+        // instruction and log events, under the same retry, dead-letter and
+        // spill handling
+        if config.indexing_mode.logs && config.indexing_mode.accounts {
+            events.extend(
+                decode_writable_accounts(&fetcher, &account_decoder_registry, &transaction).await,
+            );
+        }
+
+        let (context, events_processed) = match dispatch_events(
+            &handler_registry,
+            storage.as_ref(),
+            context,
+            events,
+            Some(&block_hash),
+            is_finalized,
+        )
+        .await?
         {
-            solana_transaction_status::EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
-                solana_transaction_status::UiMessage::Parsed(msg) => &msg.instructions,
-                _ => &[],
-            },
-            _ => &[],
+            Dispatch::Handled { context, events } => (*context, events),
+            Dispatch::Spilled => return Ok(()),
         };
 
-        let mut events_processed = 0;
+        // Mark as processed or tentative, together with the held writes
+        if is_finalized {
+            storage.mark_finalized(slot, &block_hash).await?;
+        }
+        let tentative = (!is_finalized).then_some(block_hash.as_str());
+        mark_handled(storage.as_ref(), &context, slot, tentative).await?;
+        track_commitment(storage.as_ref(), &config, &sig_str, slot, is_finalized).await?;
 
-        // Process based on indexing mode
-        if config.indexing_mode.inputs {
-            let events = decoder_registry.decode_transaction(instructions);
-
-            for (discriminator, event_data) in events {
-                // Retry handler 3 times
-                let mut attempts = 0;
-                let max_attempts = 3;
-                loop {
-                    attempts += 1;
-                    match handler_registry
-                        .handle(&discriminator, &event_data, &context, storage.pool())
-                        .await
-                    {
-                        Ok(()) => break,
-                        Err(e) if attempts < max_attempts => {
-                            logging::log_error(
-                                "Handler error",
-                                &format!("Attempt {attempts}/{max_attempts} for {sig_str}: {e}"),
-                            );
-                            tokio::time::sleep(Duration::from_millis(100 * attempts)).await;
-                        }
-                        Err(e) => {
-                            logging::log_error(
-                                "Handler failed after retries",
-                                &format!("{sig_str}: {e}"),
-                            );
-                            return Err(e);
+        if events_processed > 0 {
+            logging::log(
+                logging::LogLevel::Success,
+                &format!("Processed transaction: {sig_str} ({events_processed} events)"),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Marks the signature of `context` processed, or tentative in
+/// `block_hash`, committing the writes its handlers held in atomic mode in
+/// the same transaction.
+async fn mark_handled(
+    storage: &dyn StorageBackend,
+    context: &TxMetadata,
+    slot: u64,
+    block_hash: Option<&str>,
+) -> Result<()> {
+    if context.writes.is_atomic() {
+        return storage
+            .commit_transaction(context.writes.take(), &context.signature, slot, block_hash)
+            .await;
+    }
+    match block_hash {
+        Some(block_hash) => {
+            storage
+                .mark_tentative(&context.signature, slot, block_hash)
+                .await
+        }
+        None => storage.mark_processed(&context.signature, slot).await,
+    }
+}
+
+/// Outcome of `dispatch_events`.
+enum Dispatch {
+    /// Every event went through its handlers, with failures dead-lettered.
+    Handled {
+        context: Box<TxMetadata>,
+        /// Number of events dispatched
+        events: usize,
+    },
+    /// The database went down and the rest of the transaction was spilled.
+    Spilled,
+}
+
+/// Runs the handlers of one transaction's decoded `events`.
+///
+/// Each handler is retried under its own policy and dead-lettered once it
+/// gives up. If it gave up because the database is unreachable and a spill
+/// queue is set up, the remaining events are spilled instead, to be marked
+/// tentative in `block_hash` (or processed, if `None` or `is_finalized`)
+/// when they are replayed.
+///
+/// # Errors
+///
+/// Returns the handler's error if it cannot be dead-lettered,
+/// `SolanaIndexerError::Internal` if spilling fails, or
+/// `SolanaIndexerError::Config` if there are events but `storage` has no
+/// database for handlers.
+async fn dispatch_events(
+    handler_registry: &HandlerRegistry,
+    storage: &dyn StorageBackend,
+    context: TxMetadata,
+    events: Vec<([u8; 8], Vec<u8>)>,
+    block_hash: Option<&str>,
+    is_finalized: bool,
+) -> Result<Dispatch> {
+    // Transactions without events never need the handler database
+    if events.is_empty() {
+        return Ok(Dispatch::Handled {
+            context: Box::new(context),
+            events: 0,
+        });
+    }
+    let db = storage.require_pool()?;
+    let sig_str = context.signature.clone();
+    let spill_queue = handler_registry.spill_queue();
+    let dead_letters = handler_registry.dead_letter_queue();
+    let mut events_processed = 0;
+    // Events whose writes are held for the atomic commit
+    let mut handled = Vec::new();
+
+    let mut events = events.into_iter();
+    while let Some((discriminator, event_data)) = events.next() {
+        // Each handler of the event is retried and dead-lettered on its own
+        let handlers = handler_registry.handler_count(&discriminator).max(1);
+        let mut succeeded = false;
+        for index in 0..handlers {
+            let retry = handler_registry.handler_retry_policy(&discriminator, index);
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let held = context.writes.len();
+                let result = handler_registry
+                    .handle_at(&discriminator, index, &event_data, &context, db)
+                    .await;
+                if result.is_err() {
+                    context.writes.truncate(held);
+                }
+                let delay = result.as_ref().err().and_then(|e| retry.delay(attempts, e));
+                match (result, delay) {
+                    (Ok(()), _) => {
+                        succeeded = true;
+                        break;
+                    }
+                    (Err(e), Some(delay)) => {
+                        logging::log_error(
+                            "Handler error",
+                            &format!(
+                                "Attempt {attempts}/{} for {sig_str}: {e}",
+                                retry.max_attempts()
+                            ),
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    (Err(e), None) if spill_queue.is_some() && spill::is_outage(&e) => {
+                        // Set the rest of the transaction aside until the
+                        // database is back, along with the events whose
+                        // held writes are now dropped
+                        let remaining = handled
+                            .into_iter()
+                            .chain(std::iter::once((discriminator, event_data)))
+                            .chain(events)
+                            .collect::<Vec<_>>();
+                        if let Some(queue) = spill_queue {
+                            queue
+                                .push(&SpilledTransaction::new(
+                                    context,
+                                    block_hash.map(ToString::to_string),
+                                    is_finalized,
+                                    remaining,
+                                ))
+                                .await?;
                         }
+                        logging::log(
+                            logging::LogLevel::Warning,
+                            &format!("Database unavailable, spilled {sig_str}: {e}"),
+                        );
+                        return Ok(Dispatch::Spilled);
+                    }
+                    (Err(e), None) => {
+                        logging::log_error(
+                            "Handler failed after retries",
+                            &format!("{sig_str}: {e}"),
+                        );
+                        dead_letter(
+                            dead_letters,
+                            &discriminator,
+                            index,
+                            &event_data,
+                            &context,
+                            e,
+                            attempts,
+                            db,
+                        )
+                        .await?;
+                        break;
                     }
                 }
-                events_processed += 1;
             }
         }
+        if succeeded && context.writes.is_atomic() {
+            handled.push((discriminator, event_data));
+        }
+        events_processed += 1;
+    }
 
-        if config.indexing_mode.logs {
-            let events = log_decoder_registry.decode_logs(&decoded_meta.events);
-
-            for (discriminator, event_data) in events {
-                // Retry handler 3 times
-                let mut attempts = 0;
-                let max_attempts = 3;
-                loop {
-                    attempts += 1;
-                    match handler_registry
-                        .handle(&discriminator, &event_data, &context, storage.pool())
-                        .await
-                    {
-                        Ok(()) => break,
-                        Err(e) if attempts < max_attempts => {
-                            logging::log_error(
-                                "Handler error",
-                                &format!("Attempt {attempts}/{max_attempts} for {sig_str}: {e}"),
-                            );
-                            tokio::time::sleep(Duration::from_millis(100 * attempts)).await;
-                        }
-                        Err(e) => {
-                            logging::log_error(
-                                "Handler failed after retries",
-                                &format!("{sig_str}: {e}"),
-                            );
-                            return Err(e);
+    Ok(Dispatch::Handled {
+        context: Box::new(context),
+        events: events_processed,
+    })
+}
+
+/// Fetches the writable accounts of `transaction` and decodes their state
+/// with the registered account decoders.
+///
+/// Accounts that cannot be fetched are skipped; the transaction's other
+/// events are still handled.
+async fn decode_writable_accounts(
+    fetcher: &Fetcher,
+    account_decoder_registry: &AccountDecoderRegistry,
+    transaction: &solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta,
+) -> Vec<([u8; 8], Vec<u8>)> {
+    let mut writable_accounts = std::collections::HashSet::new();
+
+    if let solana_transaction_status::EncodedTransaction::Json(ui_tx) =
+        &transaction.transaction.transaction
+    {
+        match &ui_tx.message {
+            solana_transaction_status::UiMessage::Parsed(msg) => {
+                for account in &msg.account_keys {
+                    #[allow(clippy::collapsible_if)]
+                    if account.writable {
+                        if let Ok(pubkey) = solana_sdk::pubkey::Pubkey::from_str(&account.pubkey) {
+                            writable_accounts.insert(pubkey);
                         }
                     }
                 }
-                events_processed += 1;
-            }
-
-            if config.indexing_mode.accounts {
-                // Extract unique writable accounts from the transaction
-                // We focus on writable accounts as their state might have changed
-                let mut writable_accounts = std::collections::HashSet::new();
-
-                if let solana_transaction_status::EncodedTransaction::Json(ui_tx) =
-                    &transaction.transaction.transaction
-                {
-                    match &ui_tx.message {
-                        solana_transaction_status::UiMessage::Parsed(msg) => {
-                            for account in &msg.account_keys {
-                                #[allow(clippy::collapsible_if)]
-                                if account.writable {
-                                    if let Ok(pubkey) =
-                                        solana_sdk::pubkey::Pubkey::from_str(&account.pubkey)
-                                    {
-                                        writable_accounts.insert(pubkey);
-                                    }
-                                }
-                            }
-                        }
-                        solana_transaction_status::UiMessage::Raw(msg) => {
-                            for key_str in &msg.account_keys {
-                                if let Ok(pubkey) = solana_sdk::pubkey::Pubkey::from_str(key_str) {
-                                    writable_accounts.insert(pubkey);
-                                }
-                            }
-                        }
-                    }
-                };
-
-                if !writable_accounts.is_empty() {
-                    let keys: Vec<_> = writable_accounts.into_iter().collect();
-                    // Batch fetch
-                    if let Ok(accounts) = fetcher.fetch_multiple_accounts(&keys).await {
-                        for (index, account_option) in accounts.iter().enumerate() {
-                            if let Some(account) = account_option {
-                                let pubkey = &keys[index];
-                                let decoded_list =
-                                    account_decoder_registry.decode_account(pubkey, account);
-                                for (discriminator, event_data) in decoded_list {
-                                    // Dispatch to handler
-                                    // Retry logic similar to above
-                                    let mut attempts = 0;
-                                    let max_attempts = 3;
-                                    loop {
-                                        attempts += 1;
-                                        match handler_registry
-                                            .handle(
-                                                &discriminator,
-                                                &event_data,
-                                                &context,
-                                                storage.pool(),
-                                            )
-                                            .await
-                                        {
-                                            Ok(()) => break,
-                                            Err(e) if attempts < max_attempts => {
-                                                logging::log_error(
-                                                    "Handler error (Account)",
-                                                    &format!(
-                                                    "Attempt {attempts}/{max_attempts} for {sig_str}: {e}"
-                                                ),
-                                                );
-                                                tokio::time::sleep(Duration::from_millis(
-                                                    100 * attempts,
-                                                ))
-                                                .await;
-                                            }
-                                            Err(e) => {
-                                                logging::log_error(
-                                                    "Handler failed after retries (Account)",
-                                                    &format!("{sig_str}: {e}"),
-                                                );
-                                                // We log error but maybe don't fail the whole tx for one account?
-                                                // Return error to be safe
-                                                return Err(e);
-                                            }
-                                        }
-                                    }
-                                    events_processed += 1;
-                                }
-                            }
-                        }
+            }
+            solana_transaction_status::UiMessage::Raw(msg) => {
+                for key_str in &msg.account_keys {
+                    if let Ok(pubkey) = solana_sdk::pubkey::Pubkey::from_str(key_str) {
+                        writable_accounts.insert(pubkey);
                     }
                 }
             }
         }
+    };
 
-        // Mark as processed or tentative
-        if is_finalized {
-            storage.mark_finalized(slot, &block_hash).await?;
-            storage.mark_processed(&sig_str, slot).await?;
-        } else {
-            storage.mark_tentative(&sig_str, slot, &block_hash).await?;
-        }
+    if writable_accounts.is_empty() {
+        return Vec::new();
+    }
+    let keys: Vec<_> = writable_accounts.into_iter().collect();
+    let Ok(accounts) = fetcher.fetch_multiple_accounts(&keys).await else {
+        return Vec::new();
+    };
+    keys.iter()
+        .zip(&accounts)
+        .filter_map(|(pubkey, account)| account.as_ref().map(|account| (pubkey, account)))
+        .flat_map(|(pubkey, account)| account_decoder_registry.decode_account(pubkey, account))
+        .collect()
+}
 
-        if events_processed > 0 {
+/// Parses a signature read back from storage.
+fn parse_stored_signature(sig_str: &str) -> Result<Signature> {
+    Signature::from_str(sig_str)
+        .map_err(|e| SolanaIndexerError::Internal(format!("Invalid signature in DB: {e}")))
+}
+
+/// Sets aside a transaction whose `event_count` decoded events exceed
+/// `max_events_per_transaction`.
+///
+/// The transaction is counted as rejected in `metrics`, dead-lettered when
+/// configured to, and marked processed without being handled. Returns
+/// `false` (and does nothing) if the transaction is within the limit.
+pub(crate) async fn reject_oversized(
+    storage: &dyn StorageBackend,
+    config: &SolanaIndexerConfig,
+    metrics: &RegistryMetrics,
+    signature: &str,
+    slot: u64,
+    event_count: usize,
+) -> Result<bool> {
+    let limit = config.registry.max_events_per_transaction;
+    if limit == 0 || event_count <= limit {
+        return Ok(false);
+    }
+
+    metrics.inc_rejected();
+    let reason = format!("{event_count} events exceed the limit of {limit} per transaction");
+    logging::log(
+        logging::LogLevel::Warning,
+        &format!("Skipping transaction {signature}: {reason}"),
+    );
+    if config.registry.oversized_action == OversizedAction::DeadLetter {
+        storage.record_dead_letter(signature, slot, &reason).await?;
+    }
+    storage.mark_processed(signature, slot).await?;
+    Ok(true)
+}
+
+/// Applies `config.missing_meta` to a fetched transaction.
+///
+/// Transactions with metadata pass through untouched. Otherwise the
+/// occurrence is counted in `metrics` and, depending on the policy, the
+/// transaction is refetched until its metadata shows up, passed on with
+/// empty metadata, or skipped (`None`), in which case the caller marks it
+/// processed.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Decode` if the metadata is still
+/// missing once the `Retry` policy runs out of attempts, or the refetch
+/// error itself.
+pub(crate) async fn resolve_missing_meta(
+    fetcher: &Fetcher,
+    config: &SolanaIndexerConfig,
+    metrics: &RegistryMetrics,
+    signature: &Signature,
+    transaction: Arc<solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta>,
+) -> Result<Option<Arc<solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta>>> {
+    if transaction.transaction.meta.is_some() {
+        return Ok(Some(transaction));
+    }
+
+    metrics.inc_missing_meta();
+    match config.missing_meta {
+        MissingMetaPolicy::DispatchEmpty => Ok(Some(transaction)),
+        MissingMetaPolicy::Skip => {
             logging::log(
-                logging::LogLevel::Success,
-                &format!("Processed transaction: {sig_str} ({events_processed} events)"),
+                logging::LogLevel::Warning,
+                &format!("Skipping transaction {signature}: RPC response has no metadata"),
             );
+            Ok(None)
+        }
+        MissingMetaPolicy::Retry => {
+            let mut attempt = 1;
+            while let Some(delay) = config.backoff.delay(attempt, ErrorClass::Transient) {
+                tokio::time::sleep(delay).await;
+                let refetched = fetcher.fetch_transaction(signature).await?;
+                if refetched.transaction.meta.is_some() {
+                    return Ok(Some(Arc::new(refetched)));
+                }
+                attempt += 1;
+            }
+            Err(SolanaIndexerError::Decode(format!(
+                "Missing transaction metadata for {signature} after {attempt} attempts"
+            )))
         }
+    }
+}
 
-        Ok(())
+/// Stores an event whose handler failed after `attempts` calls in the dead
+/// letter queue, so its transaction can go on.
+///
+/// # Errors
+///
+/// Returns `error`, wrapped in `SolanaIndexerError::Handler`, if there is no
+/// queue or the database could not be reached, and the queue's error if the
+/// event cannot be stored.
+#[allow(clippy::too_many_arguments)]
+async fn dead_letter(
+    queue: Option<&Arc<DeadLetterQueue>>,
+    discriminator: &[u8; 8],
+    handler: usize,
+    data: &[u8],
+    context: &TxMetadata,
+    error: SolanaIndexerError,
+    attempts: u32,
+    db: &sqlx::PgPool,
+) -> Result<()> {
+    let error = SolanaIndexerError::Handler {
+        discriminator: *discriminator,
+        index: handler,
+        source: Box::new(error),
+    };
+    let Some(queue) = queue else {
+        return Err(error);
+    };
+    if spill::is_outage(&error) {
+        return Err(error);
+    }
+    queue
+        .push(
+            discriminator,
+            Some(handler),
+            data,
+            context,
+            &error,
+            attempts,
+            db,
+        )
+        .await?;
+    logging::log(
+        logging::LogLevel::Warning,
+        &format!("Dead-lettered an event of {}: {error}", context.signature),
+    );
+    Ok(())
+}
+
+/// Records the commitment a handled transaction was fetched at, when
+/// confirmation tracking is enabled.
+async fn track_commitment(
+    storage: &dyn StorageBackend,
+    config: &SolanaIndexerConfig,
+    signature: &str,
+    slot: u64,
+    is_finalized: bool,
+) -> Result<()> {
+    if !config.confirmation_tracking {
+        return Ok(());
     }
+    let level = if is_finalized {
+        CommitmentLevel::Finalized
+    } else {
+        config.commitment_level
+    };
+    storage.record_commitment(signature, slot, level).await
 }
 
 #[cfg(test)]
@@ -1923,4 +4425,151 @@ mod tests {
         }
         Ok(())
     }
+
+    #[cfg(all(feature = "sqlite", feature = "websockets"))]
+    #[tokio::test]
+    async fn test_log_events_follow_duplicate_policy() -> Result<()> {
+        use crate::config::DuplicatePolicy;
+        use crate::types::events::{EventType, ParsedEvent, TransferEvent};
+        use crate::types::traits::{EventHandler, LogDecoder};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        struct DataDecoder;
+
+        impl LogDecoder<TransferEvent> for DataDecoder {
+            fn decode(&self, event: &ParsedEvent) -> Option<TransferEvent> {
+                (event.event_type == EventType::ProgramData).then(|| TransferEvent {
+                    from: String::new(),
+                    to: String::new(),
+                    amount: 1,
+                })
+            }
+        }
+
+        struct Counter(Arc<AtomicU32>);
+
+        #[async_trait::async_trait]
+        impl EventHandler<TransferEvent> for Counter {
+            async fn handle(
+                &self,
+                _: TransferEvent,
+                _: &TxMetadata,
+                _: &sqlx::PgPool,
+            ) -> Result<()> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        // The second program is called first, but the first is configured first
+        let logs = [second, first]
+            .iter()
+            .flat_map(|program| {
+                [
+                    format!("Program {program} invoke [1]"),
+                    "Program data: AQIDBA==".to_string(),
+                    format!("Program {program} success"),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        for (policy, expected) in [
+            (DuplicatePolicy::AllPrograms, 2),
+            (DuplicatePolicy::FirstRegistration, 1),
+        ] {
+            let config = SolanaIndexerConfigBuilder::new()
+                .with_ws("ws://127.0.0.1:8900", "http://127.0.0.1:8899")
+                .with_database("postgresql://localhost/db")
+                .program_ids(vec![first.to_string(), second.to_string()])
+                .with_duplicate_policy(policy)
+                .build()?;
+            // Handlers never touch the pool, so it is never connected
+            let handler_pool = sqlx::PgPool::connect_lazy("postgresql://localhost/db")?;
+            let storage = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:")
+                .await?
+                .with_handler_pool(handler_pool);
+            storage.initialize().await?;
+            let mut indexer = SolanaIndexer::new_with_storage(config, Arc::new(storage));
+            indexer.register_log_decoder(first.to_string(), DataDecoder)?;
+            indexer.register_log_decoder(second.to_string(), DataDecoder)?;
+            let calls = Arc::new(AtomicU32::new(0));
+            indexer.register_handler(Counter(calls.clone()))?;
+
+            let parsed = indexer.decoder.parse_event_logs(&logs)?;
+            indexer
+                .process_log_event("sig_duplicate", 7, &logs, &parsed)
+                .await?;
+            assert_eq!(calls.load(Ordering::SeqCst), expected, "{policy:?}");
+            assert!(indexer.storage.is_processed("sig_duplicate").await?);
+        }
+        Ok(())
+    }
+
+    #[cfg(all(feature = "sqlite", feature = "websockets"))]
+    #[tokio::test]
+    async fn test_sqlite_without_handler_database_marks_eventless_logs() -> Result<()> {
+        let program = Pubkey::new_unique();
+        let logs = [
+            format!("Program {program} invoke [1]"),
+            "Program data: AQIDBA==".to_string(),
+            format!("Program {program} success"),
+        ];
+        let config = SolanaIndexerConfigBuilder::new()
+            .with_ws("ws://127.0.0.1:8900", "http://127.0.0.1:8899")
+            .with_database("sqlite::memory:")
+            .program_id(program.to_string())
+            .build()?;
+        let storage = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?;
+        storage.initialize().await?;
+        let indexer = SolanaIndexer::new_with_storage(config, Arc::new(storage));
+
+        // No decoder is registered, so nothing needs the handler database
+        let parsed = indexer.decoder.parse_event_logs(&logs)?;
+        indexer
+            .process_log_event("sig_no_events", 7, &logs, &parsed)
+            .await?;
+        assert!(indexer.storage.is_processed("sig_no_events").await?);
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_source_errors_back_off() -> Result<()> {
+        /// Source whose every batch fails, counting how often it is asked
+        struct FailingSource(Arc<std::sync::atomic::AtomicU32>);
+
+        #[async_trait::async_trait]
+        impl TransactionSource for FailingSource {
+            async fn next_batch(&mut self) -> Result<Vec<crate::streams::TransactionEvent>> {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                Err(SolanaIndexerError::rpc("connection reset"))
+            }
+
+            fn source_name(&self) -> &str {
+                "failing"
+            }
+        }
+
+        let config = SolanaIndexerConfigBuilder::new()
+            .with_rpc("http://127.0.0.1:8899")
+            .with_database("postgresql://localhost/db")
+            .program_id("11111111111111111111111111111111")
+            .build()?;
+        let storage = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?;
+        let indexer = SolanaIndexer::new_with_storage(config, Arc::new(storage));
+        let token = indexer.cancellation_token();
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let run = tokio::spawn(indexer.start_with_source(FailingSource(calls.clone())));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        token.cancel();
+        run.await.expect("indexer task panicked")?;
+
+        // Default backoff waits 50-100ms, then 100-200ms, and so on: a handful
+        // of calls rather than a spin
+        let calls = calls.load(std::sync::atomic::Ordering::Relaxed);
+        assert!((1..=4).contains(&calls), "next_batch called {calls} times");
+        Ok(())
+    }
 }
@@ -1,2 +1,13 @@
+pub mod anomaly;
+pub mod confirmations;
+pub mod cursors;
+pub mod failover;
 pub mod fetcher;
 pub mod indexer;
+pub mod observer;
+pub mod ordering;
+pub mod prefilter;
+pub mod schedule;
+pub mod snapshot;
+pub mod usage;
+pub mod watermark;
@@ -0,0 +1,168 @@
+//! Lifecycle notifications for slots and batches.
+//!
+//! A `PipelineObserver` hears when the indexer has settled a batch of
+//! transactions and which slots are now consistent: every transaction of a
+//! completed slot, and of every slot below it in the batch, has been
+//! committed by all of its handlers. Downstream consumers can read the
+//! database up to that slot without seeing a partially indexed one.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Summary of one settled batch, passed to `PipelineObserver::on_batch_complete`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Transactions in the batch, including ones skipped as already
+    /// processed or filtered out
+    pub transactions: usize,
+    /// Transactions whose handlers committed
+    pub processed: usize,
+    /// Transactions that failed, or whose task panicked
+    pub failed: usize,
+    /// Lowest slot in the batch
+    pub first_slot: Option<u64>,
+    /// Highest slot in the batch
+    pub last_slot: Option<u64>,
+    /// Highest slot of the batch whose transactions, and those of every
+    /// lower slot in the batch, all settled; `None` if the lowest slot
+    /// failed or a task panicked
+    pub complete_through: Option<u64>,
+    /// Time spent processing the batch
+    pub duration: Duration,
+}
+
+/// Receives slot and batch completions from the indexing loop.
+///
+/// Both methods run on the indexing loop after the batch's writes have
+/// committed, so a slow observer delays the next batch; hand long work off
+/// to a task.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::{BatchSummary, PipelineObserver, SolanaIndexer};
+/// # use std::sync::Arc;
+/// struct ConsistencyMarker;
+///
+/// #[async_trait::async_trait]
+/// impl PipelineObserver for ConsistencyMarker {
+///     async fn on_slot_complete(&self, slot: u64) {
+///         println!("consistent through slot {slot}");
+///     }
+/// }
+///
+/// # fn example(indexer: &mut SolanaIndexer) {
+/// indexer.set_pipeline_observer(Arc::new(ConsistencyMarker));
+/// # }
+/// ```
+#[async_trait]
+pub trait PipelineObserver: Send + Sync {
+    /// Called, in ascending order, for every slot of a batch at or below its
+    /// `complete_through`. A slot whose transactions span several batches
+    /// is reported once per batch.
+    async fn on_slot_complete(&self, slot: u64) {
+        let _ = slot;
+    }
+
+    /// Called once per batch after its slots have been reported.
+    async fn on_batch_complete(&self, batch: &BatchSummary) {
+        let _ = batch;
+    }
+}
+
+/// Reports a settled batch to `observer`.
+///
+/// `outcomes` holds `(slot, settled)` for every transaction of the batch;
+/// `panicked` counts tasks whose slot is unknown, which hold back every slot.
+pub(crate) async fn notify(
+    observer: &Arc<dyn PipelineObserver>,
+    outcomes: &[(u64, bool)],
+    panicked: usize,
+    duration: Duration,
+) {
+    let summary = summarize(outcomes, panicked, duration);
+    for slot in completed_slots(outcomes, summary.complete_through) {
+        observer.on_slot_complete(slot).await;
+    }
+    observer.on_batch_complete(&summary).await;
+}
+
+fn summarize(outcomes: &[(u64, bool)], panicked: usize, duration: Duration) -> BatchSummary {
+    let failed = outcomes.iter().filter(|(_, settled)| !settled).count();
+    let complete_through = if panicked > 0 {
+        None
+    } else {
+        crate::storage::batch_watermark(outcomes)
+    };
+    BatchSummary {
+        transactions: outcomes.len() + panicked,
+        processed: outcomes.len() - failed,
+        failed: failed + panicked,
+        first_slot: outcomes.iter().map(|(slot, _)| *slot).min(),
+        last_slot: outcomes.iter().map(|(slot, _)| *slot).max(),
+        complete_through,
+        duration,
+    }
+}
+
+/// Returns the distinct slots of `outcomes` at or below `complete_through`,
+/// lowest first.
+fn completed_slots(outcomes: &[(u64, bool)], complete_through: Option<u64>) -> Vec<u64> {
+    let Some(through) = complete_through else {
+        return Vec::new();
+    };
+    let mut slots: Vec<u64> = outcomes
+        .iter()
+        .map(|(slot, _)| *slot)
+        .filter(|slot| *slot <= through)
+        .collect();
+    slots.sort_unstable();
+    slots.dedup();
+    slots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorded {
+        slots: Mutex<Vec<u64>>,
+        batches: Mutex<Vec<BatchSummary>>,
+    }
+
+    #[async_trait]
+    impl PipelineObserver for Recorded {
+        async fn on_slot_complete(&self, slot: u64) {
+            self.slots.lock().unwrap().push(slot);
+        }
+
+        async fn on_batch_complete(&self, batch: &BatchSummary) {
+            self.batches.lock().unwrap().push(batch.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reports_slots_below_first_failure() {
+        let recorded = Arc::new(Recorded::default());
+        let observer: Arc<dyn PipelineObserver> = recorded.clone();
+        let outcomes = [(12, true), (10, true), (10, true), (11, true), (13, false)];
+
+        notify(&observer, &outcomes, 0, Duration::ZERO).await;
+        notify(&observer, &[(14, true)], 1, Duration::ZERO).await;
+
+        assert_eq!(*recorded.slots.lock().unwrap(), [10, 11, 12]);
+        let batches = recorded.batches.lock().unwrap();
+        assert_eq!(batches[0].transactions, 5);
+        assert_eq!(batches[0].processed, 4);
+        assert_eq!(batches[0].failed, 1);
+        assert_eq!(batches[0].first_slot, Some(10));
+        assert_eq!(batches[0].last_slot, Some(13));
+        assert_eq!(batches[0].complete_through, Some(12));
+        // A panicked task holds back every slot of its batch
+        assert_eq!(batches[1].complete_through, None);
+        assert_eq!(batches[1].failed, 1);
+    }
+}
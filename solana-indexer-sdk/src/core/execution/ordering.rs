@@ -0,0 +1,182 @@
+//! Per-key ordered parallel execution.
+//!
+//! Transactions are processed concurrently, so two transactions touching the
+//! same account can reach their handlers in either order. Stateful handlers,
+//! such as a balance tracker applying deltas, need them in chain order. With
+//! `SolanaIndexer::set_ordering_key`, every transaction gets a key:
+//! transactions sharing a key are processed one after another, in the order
+//! the source reported them, while transactions with different keys still
+//! run in parallel, up to `worker_threads` at a time.
+//!
+//! - `OrderingKey::Account` orders the transactions of one account: the
+//!   watched address a polled transaction was found under, or the fee payer
+//!   of a full transaction from a streaming source.
+//! - `OrderingKey::Signature` gives every transaction its own key, which
+//!   orders nothing beyond the events of each transaction.
+//! - `OrderingKey::Custom` derives the key from the `TransactionPreview`.
+//!
+//! A transaction an account key cannot be found for is keyed by its
+//! signature. RPC polling processes each batch oldest first while a key is
+//! set, so the order also holds within a batch.
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{OrderingKey, SolanaIndexer, TransactionPreview};
+//! # fn example(indexer: &mut SolanaIndexer) {
+//! indexer.set_ordering_key(OrderingKey::Account);
+//! // Or one key per memo, e.g. per order ID
+//! indexer.set_ordering_key(OrderingKey::custom(|tx: &TransactionPreview| {
+//!     tx.memo.unwrap_or_default().to_string()
+//! }));
+//! # }
+//! ```
+
+use super::prefilter::TransactionPreview;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::oneshot::{self, error::TryRecvError};
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+/// Keys tracked before finished ones are dropped.
+const PRUNE_THRESHOLD: usize = 1024;
+
+/// Derives an ordering key from a transaction preview.
+type KeyFn = Arc<dyn Fn(&TransactionPreview<'_>) -> String + Send + Sync>;
+
+/// What transactions are serialized by.
+#[derive(Clone)]
+pub enum OrderingKey {
+    /// Each transaction on its own
+    Signature,
+    /// The account a transaction was found under, or its fee payer
+    Account,
+    /// A key computed from the transaction preview
+    Custom(KeyFn),
+}
+
+impl std::fmt::Debug for OrderingKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Signature => f.write_str("Signature"),
+            Self::Account => f.write_str("Account"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl OrderingKey {
+    /// Creates a key computed by `key` from the transaction preview.
+    #[must_use]
+    pub fn custom(key: impl Fn(&TransactionPreview<'_>) -> String + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(key))
+    }
+
+    /// Returns the key of a transaction. `account` is the account it was
+    /// found under or paid by, if known.
+    #[must_use]
+    pub fn key(&self, preview: &TransactionPreview<'_>, account: Option<Pubkey>) -> String {
+        match (self, account) {
+            (Self::Account, Some(account)) => account.to_string(),
+            (Self::Signature | Self::Account, _) => preview.signature.to_string(),
+            (Self::Custom(key), _) => key(preview),
+        }
+    }
+}
+
+/// Spawns tasks that wait for the earlier tasks of their key.
+///
+/// Keyed tasks take a worker permit only once their turn comes, so a key
+/// with a backlog does not hold up the others.
+#[derive(Debug)]
+pub struct KeyedExecutor {
+    workers: Arc<Semaphore>,
+    tails: HashMap<String, oneshot::Receiver<()>>,
+    prune_at: usize,
+}
+
+impl KeyedExecutor {
+    /// Creates an executor running at most `workers` keyed tasks at a time.
+    #[must_use]
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            tails: HashMap::new(),
+            prune_at: PRUNE_THRESHOLD,
+        }
+    }
+
+    /// Spawns `task` to run after every earlier task with the same key has
+    /// finished, successfully or not. A task without a key starts right
+    /// away and does not count against the workers.
+    pub fn spawn<F>(&mut self, key: Option<String>, task: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let Some(key) = key else {
+            return tokio::spawn(task);
+        };
+        if self.tails.len() >= self.prune_at {
+            self.prune();
+        }
+
+        // Dropped when the task finishes or panics, releasing the next one
+        let (done, tail) = oneshot::channel::<()>();
+        let previous = self.tails.insert(key, tail);
+        let workers = self.workers.clone();
+        tokio::spawn(async move {
+            if let Some(previous) = previous {
+                let _ = previous.await;
+            }
+            let permit = workers.acquire_owned().await;
+            let output = task.await;
+            drop(permit);
+            drop(done);
+            output
+        })
+    }
+
+    /// Returns the number of keys with a task queued or running.
+    pub fn pending_keys(&mut self) -> usize {
+        self.prune();
+        self.tails.len()
+    }
+
+    /// Forgets keys whose last task has finished.
+    fn prune(&mut self) {
+        self.tails
+            .retain(|_, tail| matches!(tail.try_recv(), Err(TryRecvError::Empty)));
+        self.prune_at = PRUNE_THRESHOLD.max(self.tails.len() * 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_key_runs_in_order() {
+        let mut executor = KeyedExecutor::new(4);
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let mut tasks = Vec::new();
+        // Earlier tasks of a key sleep longer, so only ordering keeps them first
+        for (key, step, sleep_ms) in [("a", 1, 60), ("a", 2, 30), ("b", 1, 10), ("a", 3, 0)] {
+            let log = log.clone();
+            tasks.push(executor.spawn(Some(key.to_string()), async move {
+                tokio::time::sleep(Duration::from_millis(sleep_ms)).await;
+                log.lock().unwrap().push(format!("{key}{step}"));
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(*log.lock().unwrap(), ["b1", "a1", "a2", "a3"]);
+        assert_eq!(executor.pending_keys(), 0);
+    }
+}
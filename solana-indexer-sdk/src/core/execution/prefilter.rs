@@ -0,0 +1,540 @@
+//! Cheap transaction filters evaluated before `getTransaction`.
+//!
+//! Fetching a full transaction is the most expensive step of the pipeline, and
+//! many indexers discard most of what they fetch. A `Prefilter` sees what the
+//! source already reported about a transaction (a `TransactionPreview`) and
+//! decides whether it is worth fetching at all:
+//!
+//! - RPC polling reports the error, memo and block time of every signature.
+//! - WebSocket and Helius sources report the error and log messages.
+//!
+//! Prefilters are either closures or `PrefilterExpr`, a small expression
+//! language for the common cases:
+//!
+//! ```text
+//! err == null && memo ~ "order:"
+//! logs ~ "Instruction: Swap" || slot >= 250000000
+//! !(block_time == null)
+//! ```
+//!
+//! Fields are `err`, `memo`, `logs`, `slot` and `block_time`. Operators are
+//! `==` and `!=` (against `null`, a string or a number), `~` (substring match;
+//! for `logs`, any line), and `<`, `<=`, `>`, `>=` for the numeric fields.
+//! Conditions combine with `&&`, `||`, `!` and parentheses. A field the source
+//! does not report is `null`, so a `memo` condition drops every transaction
+//! from a WebSocket source.
+
+use crate::streams::TransactionEvent;
+use crate::utils::error::{Result, SolanaIndexerError};
+use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+use solana_sdk::signature::Signature;
+use std::sync::Arc;
+
+/// What a source reported about a transaction before it is fetched.
+#[derive(Debug, Clone)]
+pub struct TransactionPreview<'a> {
+    /// Transaction signature
+    pub signature: Signature,
+    /// Slot the transaction landed in
+    pub slot: u64,
+    /// Error of a failed transaction, rendered as text
+    pub err: Option<String>,
+    /// Memo attached to the transaction (RPC polling only)
+    pub memo: Option<&'a str>,
+    /// Log messages (WebSocket and Helius sources only)
+    pub logs: Option<&'a [String]>,
+    /// Block time as a Unix timestamp, if reported
+    pub block_time: Option<i64>,
+}
+
+impl<'a> TransactionPreview<'a> {
+    /// Builds a preview from a source event.
+    #[must_use]
+    pub fn from_event(event: &'a TransactionEvent) -> Self {
+        let mut preview = Self {
+            signature: event.signature(),
+            slot: event.slot(),
+            err: None,
+            memo: None,
+            logs: None,
+            block_time: None,
+        };
+        match event {
+            TransactionEvent::Signature { .. } => {}
+            TransactionEvent::LogEvent { logs, err, .. } => {
+                preview.err = err
+                    .as_ref()
+                    .filter(|err| !err.is_null())
+                    .map(ToString::to_string);
+                preview.logs = Some(logs);
+            }
+            TransactionEvent::FullTransaction { tx, .. } => {
+                preview.block_time = tx.block_time;
+                if let Some(meta) = &tx.transaction.meta {
+                    preview.err = meta.err.as_ref().map(ToString::to_string);
+                    preview.logs = Option::from(meta.log_messages.as_ref()).map(Vec::as_slice);
+                }
+            }
+        }
+        preview
+    }
+
+    /// Builds a preview from a `getSignaturesForAddress` entry.
+    pub(crate) fn from_status(
+        signature: Signature,
+        status: &'a RpcConfirmedTransactionStatusWithSignature,
+    ) -> Self {
+        Self {
+            signature,
+            slot: status.slot,
+            err: status.err.as_ref().map(ToString::to_string),
+            memo: status.memo.as_deref(),
+            logs: None,
+            block_time: status.block_time,
+        }
+    }
+}
+
+/// Decides whether a transaction is fetched and processed.
+///
+/// Implemented for closures, so the simplest prefilter is
+/// `|tx: &TransactionPreview| tx.err.is_none()`.
+pub trait Prefilter: Send + Sync {
+    /// Returns `true` to fetch the transaction, `false` to skip it.
+    fn matches(&self, preview: &TransactionPreview<'_>) -> bool;
+}
+
+impl<F> Prefilter for F
+where
+    F: Fn(&TransactionPreview<'_>) -> bool + Send + Sync,
+{
+    fn matches(&self, preview: &TransactionPreview<'_>) -> bool {
+        self(preview)
+    }
+}
+
+/// Returns `true` if `preview` passes every prefilter.
+pub(crate) fn passes(prefilters: &[Arc<dyn Prefilter>], preview: &TransactionPreview<'_>) -> bool {
+    prefilters
+        .iter()
+        .all(|prefilter| prefilter.matches(preview))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Err,
+    Memo,
+    Logs,
+    Slot,
+    BlockTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Contains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value {
+    Null,
+    Str(String),
+    Int(i64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Compare(Field, Op, Value),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+/// A prefilter parsed from an expression (see the module documentation).
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::core::execution::prefilter::{Prefilter, PrefilterExpr, TransactionPreview};
+/// use solana_sdk::signature::Signature;
+///
+/// let filter = PrefilterExpr::parse(r#"err == null && memo ~ "order:""#)?;
+///
+/// let preview = TransactionPreview {
+///     signature: Signature::default(),
+///     slot: 1,
+///     err: None,
+///     memo: Some("order:42"),
+///     logs: None,
+///     block_time: None,
+/// };
+/// assert!(filter.matches(&preview));
+/// # Ok::<(), solana_indexer_sdk::SolanaIndexerError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefilterExpr {
+    source: String,
+    expr: Expr,
+}
+
+impl PrefilterExpr {
+    /// Parses `source` into a prefilter.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` describing the first syntax
+    /// or type error (e.g. `slot ~ "x"`).
+    pub fn parse(source: &str) -> Result<Self> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(syntax_error(format!("unexpected {token:?}")));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// Returns the expression this prefilter was parsed from.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl Prefilter for PrefilterExpr {
+    fn matches(&self, preview: &TransactionPreview<'_>) -> bool {
+        eval(&self.expr, preview)
+    }
+}
+
+fn eval(expr: &Expr, preview: &TransactionPreview<'_>) -> bool {
+    match expr {
+        Expr::Not(inner) => !eval(inner, preview),
+        Expr::And(left, right) => eval(left, preview) && eval(right, preview),
+        Expr::Or(left, right) => eval(left, preview) || eval(right, preview),
+        Expr::Compare(field, op, value) => match field {
+            Field::Err => compare_text(preview.err.as_deref(), *op, value),
+            Field::Memo => compare_text(preview.memo, *op, value),
+            Field::Logs => match (op, value) {
+                (Op::Contains, Value::Str(needle)) => preview
+                    .logs
+                    .is_some_and(|logs| logs.iter().any(|line| line.contains(needle.as_str()))),
+                (Op::Eq, Value::Null) => preview.logs.is_none(),
+                (Op::Ne, Value::Null) => preview.logs.is_some(),
+                _ => false,
+            },
+            Field::Slot => compare_number(i64::try_from(preview.slot).ok(), *op, value),
+            Field::BlockTime => compare_number(preview.block_time, *op, value),
+        },
+    }
+}
+
+fn compare_text(actual: Option<&str>, op: Op, value: &Value) -> bool {
+    match (op, value) {
+        (Op::Eq, Value::Null) => actual.is_none(),
+        (Op::Ne, Value::Null) => actual.is_some(),
+        (Op::Eq, Value::Str(expected)) => actual == Some(expected.as_str()),
+        (Op::Ne, Value::Str(expected)) => actual != Some(expected.as_str()),
+        (Op::Contains, Value::Str(needle)) => actual.is_some_and(|s| s.contains(needle.as_str())),
+        _ => false,
+    }
+}
+
+fn compare_number(actual: Option<i64>, op: Op, value: &Value) -> bool {
+    match (actual, value) {
+        (actual, Value::Null) => match op {
+            Op::Eq => actual.is_none(),
+            Op::Ne => actual.is_some(),
+            _ => false,
+        },
+        (Some(actual), Value::Int(expected)) => match op {
+            Op::Eq => actual == *expected,
+            Op::Ne => actual != *expected,
+            Op::Lt => actual < *expected,
+            Op::Le => actual <= *expected,
+            Op::Gt => actual > *expected,
+            Op::Ge => actual >= *expected,
+            Op::Contains => false,
+        },
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Int(i64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn syntax_error(message: impl std::fmt::Display) -> SolanaIndexerError {
+    SolanaIndexerError::Config(format!("Invalid prefilter expression: {message}"))
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut next_is = |expected: char| chars.next_if(|&(_, c)| c == expected).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '~' => Token::Op(Op::Contains),
+            '&' if next_is('&') => Token::And,
+            '|' if next_is('|') => Token::Or,
+            '=' if next_is('=') => Token::Op(Op::Eq),
+            '!' if next_is('=') => Token::Op(Op::Ne),
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Op(Op::Le),
+            '<' => Token::Op(Op::Lt),
+            '>' if next_is('=') => Token::Op(Op::Ge),
+            '>' => Token::Op(Op::Gt),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, escaped)) => value.push(escaped),
+                            None => return Err(syntax_error("unterminated string")),
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(syntax_error("unterminated string")),
+                    }
+                }
+                Token::Str(value)
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                let mut end = start + c.len_utf8();
+                while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                    end = i + 1;
+                }
+                let number = &source[start..end];
+                Token::Int(
+                    number
+                        .parse()
+                        .map_err(|_| syntax_error(format!("invalid number '{number}'")))?,
+                )
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = start + 1;
+                while let Some((i, _)) =
+                    chars.next_if(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+                {
+                    end = i + 1;
+                }
+                Token::Ident(source[start..end].to_string())
+            }
+            other => return Err(syntax_error(format!("unexpected character '{other}'"))),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; `||` binds weaker than `&&`, which binds weaker than `!`.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(expected) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::LParen) {
+            let expr = self.or()?;
+            if !self.eat(&Token::RParen) {
+                return Err(syntax_error("missing ')'"));
+            }
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let field = match self.next() {
+            Some(Token::Ident(name)) => match name.as_str() {
+                "err" => Field::Err,
+                "memo" => Field::Memo,
+                "logs" => Field::Logs,
+                "slot" => Field::Slot,
+                "block_time" => Field::BlockTime,
+                _ => return Err(syntax_error(format!("unknown field '{name}'"))),
+            },
+            other => return Err(syntax_error(format!("expected a field, found {other:?}"))),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            other => {
+                return Err(syntax_error(format!(
+                    "expected an operator, found {other:?}"
+                )))
+            }
+        };
+        let value = match self.next() {
+            Some(Token::Ident(name)) if name == "null" => Value::Null,
+            Some(Token::Str(s)) => Value::Str(s),
+            Some(Token::Int(n)) => Value::Int(n),
+            other => return Err(syntax_error(format!("expected a value, found {other:?}"))),
+        };
+
+        let numeric = matches!(field, Field::Slot | Field::BlockTime);
+        let valid = match (&value, op) {
+            (Value::Null, Op::Eq | Op::Ne) => true,
+            (Value::Null, _) => false,
+            (Value::Int(_), Op::Contains) => false,
+            (Value::Int(_), _) => numeric,
+            (Value::Str(_), Op::Eq | Op::Ne) => !numeric && field != Field::Logs,
+            (Value::Str(_), Op::Contains) => !numeric,
+            (Value::Str(_), _) => false,
+        };
+        if !valid {
+            return Err(syntax_error(format!(
+                "{op:?} cannot compare {field:?} with {value:?}"
+            )));
+        }
+        Ok(Expr::Compare(field, op, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preview<'a>(
+        err: Option<&str>,
+        memo: Option<&'a str>,
+        logs: Option<&'a [String]>,
+    ) -> TransactionPreview<'a> {
+        TransactionPreview {
+            signature: Signature::default(),
+            slot: 100,
+            err: err.map(ToString::to_string),
+            memo,
+            logs,
+            block_time: None,
+        }
+    }
+
+    #[test]
+    fn test_expression_evaluation() -> Result<()> {
+        let logs = vec!["Program log: Instruction: Swap".to_string()];
+        let ok_swap = preview(None, Some("order:7"), Some(&logs));
+        let failed = preview(Some("InsufficientFundsForFee"), None, None);
+
+        let cases = [
+            ("err == null", true, false),
+            ("err != null", false, true),
+            (r#"err ~ "InsufficientFunds""#, false, true),
+            (r#"memo ~ "order:" && err == null"#, true, false),
+            (r#"memo == "order:8""#, false, false),
+            (r#"logs ~ "Instruction: Swap""#, true, false),
+            ("!(logs == null) || slot >= 100", true, true),
+            ("slot > 100 || block_time != null", false, false),
+            (
+                r#"err != null || (memo ~ "order" && slot == 100)"#,
+                true,
+                true,
+            ),
+        ];
+        for (source, expect_swap, expect_failed) in cases {
+            let filter = PrefilterExpr::parse(source)?;
+            assert_eq!(filter.matches(&ok_swap), expect_swap, "{source} (swap)");
+            assert_eq!(filter.matches(&failed), expect_failed, "{source} (failed)");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        for source in [
+            "",
+            "err ==",
+            "fee > 5",
+            r#"slot ~ "1""#,
+            r#"logs == "x""#,
+            "memo > 3",
+            "(err == null",
+            r#"memo ~ "unterminated"#,
+            "err == null err == null",
+        ] {
+            assert!(
+                matches!(
+                    PrefilterExpr::parse(source),
+                    Err(SolanaIndexerError::Config(_))
+                ),
+                "{source:?} should not parse"
+            );
+        }
+    }
+
+    #[test]
+    fn test_preview_from_event_and_closure() {
+        let event = TransactionEvent::LogEvent {
+            signature: Signature::default(),
+            logs: vec!["Program log: hello".to_string()],
+            err: Some(serde_json::json!({ "InstructionError": [0, "Custom"] })),
+            slot: 7,
+            program_id: None,
+        };
+        let preview = TransactionPreview::from_event(&event);
+        assert_eq!(preview.slot, 7);
+        assert!(preview.err.is_some());
+        assert_eq!(preview.logs.map(<[String]>::len), Some(1));
+
+        let prefilters: Vec<Arc<dyn Prefilter>> = vec![
+            Arc::new(|tx: &TransactionPreview<'_>| tx.slot > 5),
+            Arc::new(PrefilterExpr::parse("err == null").unwrap()),
+        ];
+        assert!(!passes(&prefilters, &preview));
+        assert!(passes(&prefilters[..1], &preview));
+    }
+}
@@ -0,0 +1,308 @@
+//! Periodic tasks run on cron schedules.
+//!
+//! Rollups such as hourly volume or daily active wallets are derived from
+//! the rows handlers write. Registering them with `SolanaIndexer::schedule`
+//! runs them inside the indexer, against the same database and with the
+//! current processed-slot watermark, instead of from an external cron job:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{PeriodicTask, Result, SolanaIndexer};
+//! # use async_trait::async_trait;
+//! # use sqlx::PgPool;
+//! struct HourlyVolume;
+//!
+//! #[async_trait]
+//! impl PeriodicTask for HourlyVolume {
+//!     async fn run(&self, db: &PgPool, watermark: Option<u64>) -> Result<()> {
+//!         sqlx::query(
+//!             "INSERT INTO hourly_volume (hour, amount)
+//!              SELECT date_trunc('hour', NOW()), COALESCE(SUM(amount), 0)
+//!              FROM transfers WHERE slot <= $1",
+//!         )
+//!         .bind(watermark.map(|slot| slot as i64))
+//!         .execute(db)
+//!         .await?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn example(indexer: &mut SolanaIndexer) -> Result<()> {
+//! indexer.schedule("0 * * * *", HourlyVolume)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Schedules use the five standard cron fields (minute, hour, day of month,
+//! month, day of week) in UTC, each a `*`, a value, a range, a list or a
+//! `/` step, or one of `@hourly`, `@daily`, `@weekly`, `@monthly` and
+//! `@yearly`.
+
+use crate::storage::StorageBackend;
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Work run by the indexer on a cron schedule.
+#[async_trait]
+pub trait PeriodicTask: Send + Sync + 'static {
+    /// Runs the task once.
+    ///
+    /// `watermark` is the slot at or below which every transaction has been
+    /// handled, or `None` if no batch has committed yet.
+    async fn run(&self, db: &PgPool, watermark: Option<u64>) -> Result<()>;
+}
+
+/// A parsed five-field cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether day of month and day of week were both restricted, in which
+    /// case a day matching either runs the task
+    either_day: bool,
+}
+
+impl CronSchedule {
+    /// Returns the expression the schedule was parsed from.
+    #[must_use]
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Returns the first minute strictly after `after` matching the
+    /// schedule, or `None` if none falls within the next five years.
+    #[must_use]
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = time + Duration::days(5 * 366);
+        while time < limit {
+            if !contains(self.months, time.month()) {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(time) {
+                time = time
+                    .date_naive()
+                    .succ_opt()?
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+            } else if !contains(self.hours, time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !contains(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day = contains(self.days, time.day());
+        let weekday = contains(self.weekdays, time.weekday().num_days_from_sunday());
+        if self.either_day {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = SolanaIndexerError;
+
+    fn from_str(expression: &str) -> Result<Self> {
+        let fields = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = fields.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(cron_error(expression, "expected five fields"));
+        };
+
+        // Sunday may be written as 7
+        let mut weekday_mask =
+            parse_field(weekdays, 0, 7).map_err(|e| cron_error(expression, &e))?;
+        if contains(weekday_mask, 7) {
+            weekday_mask = (weekday_mask | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            expression: expression.to_string(),
+            minutes: parse_field(minutes, 0, 59).map_err(|e| cron_error(expression, &e))?,
+            hours: parse_field(hours, 0, 23).map_err(|e| cron_error(expression, &e))?,
+            days: parse_field(days, 1, 31).map_err(|e| cron_error(expression, &e))?,
+            months: parse_field(months, 1, 12).map_err(|e| cron_error(expression, &e))?,
+            weekdays: weekday_mask,
+            either_day: !days.starts_with('*') && !weekdays.starts_with('*'),
+        })
+    }
+}
+
+fn contains(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Parses one cron field into a bit mask of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32) -> std::result::Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step '{step}'"))?;
+                if step == 0 {
+                    return Err("step must be positive".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, min, max)?, parse_value(end, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // `5/15` runs from 5 to the end of the field
+            (value, if part.contains('/') { max } else { value })
+        };
+        if start > end {
+            return Err(format!("empty range '{range}'"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> std::result::Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|value| (min..=max).contains(value))
+        .ok_or_else(|| format!("'{value}' is not between {min} and {max}"))
+}
+
+fn cron_error(expression: &str, reason: &str) -> SolanaIndexerError {
+    SolanaIndexerError::Config(format!("Invalid cron expression '{expression}': {reason}"))
+}
+
+/// A task together with its schedule.
+pub(crate) struct ScheduledTask {
+    pub(crate) schedule: CronSchedule,
+    pub(crate) task: Arc<dyn PeriodicTask>,
+}
+
+impl ScheduledTask {
+    /// Runs the task at every scheduled time until `cancellation_token`
+    /// fires. Failures are logged and the next run goes ahead as usual.
+    pub(crate) fn spawn(
+        self,
+        storage: Arc<dyn StorageBackend>,
+        cancellation_token: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let expression = self.schedule.expression();
+            while let Some(next) = self.schedule.next_after(Utc::now()) {
+                let wait = (next - Utc::now()).to_std().unwrap_or_default();
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    () = tokio::time::sleep(wait) => {}
+                }
+
+                let watermark = match storage.get_watermark().await {
+                    Ok(watermark) => watermark,
+                    Err(e) => {
+                        logging::log_error("Periodic task watermark error", &e.to_string());
+                        None
+                    }
+                };
+                let result = match storage.require_pool() {
+                    Ok(pool) => self.task.run(pool, watermark).await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    logging::log_error("Periodic task failed", &format!("'{expression}': {e}"));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn next(expression: &str, after: DateTime<Utc>) -> DateTime<Utc> {
+        expression
+            .parse::<CronSchedule>()
+            .unwrap()
+            .next_after(after)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_next_after() {
+        let now = at(2024, 3, 15, 10, 30);
+        assert_eq!(next("* * * * *", now), at(2024, 3, 15, 10, 31));
+        assert_eq!(next("@hourly", now), at(2024, 3, 15, 11, 0));
+        assert_eq!(next("*/20 * * * *", now), at(2024, 3, 15, 10, 40));
+        assert_eq!(next("15 2 * * *", now), at(2024, 3, 16, 2, 15));
+        assert_eq!(next("0 0 1 * *", now), at(2024, 4, 1, 0, 0));
+        assert_eq!(next("0 0 29 2 *", now), at(2028, 2, 29, 0, 0));
+        // 2024-03-15 is a Friday
+        assert_eq!(next("0 9 * * 1-5", now), at(2024, 3, 18, 9, 0));
+        assert_eq!(next("0 0 * * 7", now), at(2024, 3, 17, 0, 0));
+        // Day of month or day of week
+        assert_eq!(next("0 0 20 * 6", now), at(2024, 3, 16, 0, 0));
+        assert_eq!(
+            next("0 0 1 1 *", at(2024, 12, 31, 23, 59)),
+            at(2025, 1, 1, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        for expression in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(
+                expression.parse::<CronSchedule>().is_err(),
+                "{expression} should not parse"
+            );
+        }
+        assert!("0 0 31 2 *"
+            .parse::<CronSchedule>()
+            .unwrap()
+            .next_after(Utc::now())
+            .is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Per-program usage accounting.
+//!
+//! With `usage_tracking` enabled, the indexer counts what each indexed
+//! program costs to run: events handled and their encoded size per event
+//! discriminator, and RPC calls spent fetching the program's signatures and
+//! transactions. Counts are added to a daily table every minute, so teams
+//! can attribute infrastructure cost to individual protocols, and are
+//! logged with the registry metrics.
+//!
+//! Events are attributed to the program whose decoder was registered for
+//! their discriminator. RPC calls are attributed while a program's work is
+//! in scope (see [`attribute`]), which the RPC polling loop sets up for
+//! every address it polls; calls made outside any scope are not counted.
+
+use crate::storage::StorageBackend;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Program label for events whose discriminator no decoder was registered for.
+pub const UNATTRIBUTED: &str = "unattributed";
+
+/// Pause between writes to the daily usage table.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+tokio::task_local! {
+    static SCOPE: (Arc<UsageTracker>, Arc<str>);
+}
+
+/// Usage counted for one program, or one event type of a program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageRecord {
+    /// Program address
+    pub program: String,
+    /// Event discriminator, or `None` for the program's RPC calls
+    pub discriminator: Option<[u8; 8]>,
+    /// Events handled
+    pub events: u64,
+    /// Total size of the handled events, Borsh-encoded, in bytes
+    pub bytes: u64,
+    /// RPC calls made
+    pub rpc_calls: u64,
+}
+
+type UsageKey = (String, Option<[u8; 8]>);
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Counters {
+    events: u64,
+    bytes: u64,
+    rpc_calls: u64,
+}
+
+impl Counters {
+    fn add(&mut self, other: Counters) {
+        self.events += other.events;
+        self.bytes += other.bytes;
+        self.rpc_calls += other.rpc_calls;
+    }
+}
+
+/// Counts events, bytes and RPC calls per program.
+#[derive(Debug, Default)]
+pub struct UsageTracker {
+    programs: RwLock<HashMap<[u8; 8], String>>,
+    /// Counts not yet written to storage
+    pending: Mutex<HashMap<UsageKey, Counters>>,
+    /// Counts since startup
+    totals: Mutex<HashMap<UsageKey, Counters>>,
+}
+
+impl UsageTracker {
+    /// Creates an empty tracker.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attributes events with `discriminator` to `program`.
+    pub fn assign(&self, discriminator: [u8; 8], program: impl Into<String>) {
+        if let Ok(mut programs) = self.programs.write() {
+            programs.insert(discriminator, program.into());
+        }
+    }
+
+    /// Counts one handled event of `len` encoded bytes.
+    pub fn record_event(&self, discriminator: [u8; 8], len: usize) {
+        let program = self
+            .programs
+            .read()
+            .ok()
+            .and_then(|programs| programs.get(&discriminator).cloned())
+            .unwrap_or_else(|| UNATTRIBUTED.to_string());
+        self.add(
+            (program, Some(discriminator)),
+            Counters {
+                events: 1,
+                bytes: len as u64,
+                rpc_calls: 0,
+            },
+        );
+    }
+
+    /// Counts `calls` RPC calls made for `program`.
+    pub fn record_rpc_calls(&self, program: &str, calls: u64) {
+        self.add(
+            (program.to_string(), None),
+            Counters {
+                rpc_calls: calls,
+                ..Counters::default()
+            },
+        );
+    }
+
+    /// Returns the usage counted since startup, ordered by program.
+    #[must_use]
+    pub fn totals(&self) -> Vec<UsageRecord> {
+        self.totals
+            .lock()
+            .map(|totals| records(&totals))
+            .unwrap_or_default()
+    }
+
+    /// Adds the usage counted since the last flush to today's row in
+    /// storage (UTC).
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the write fails. The
+    /// counts are kept and written by the next flush.
+    pub async fn flush(&self, storage: &dyn StorageBackend) -> Result<()> {
+        let pending = match self.pending.lock() {
+            Ok(mut pending) => std::mem::take(&mut *pending),
+            Err(_) => return Ok(()),
+        };
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        if let Err(e) = storage.record_usage(&day, &records(&pending)).await {
+            if let Ok(mut current) = self.pending.lock() {
+                for (key, counters) in pending {
+                    current.entry(key).or_default().add(counters);
+                }
+            }
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Flushes to `storage` every minute, and once more when
+    /// `cancellation_token` fires.
+    pub fn spawn(
+        self: Arc<Self>,
+        storage: Arc<dyn StorageBackend>,
+        cancellation_token: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+            interval.tick().await;
+            loop {
+                tokio::select! {
+                    () = cancellation_token.cancelled() => {
+                        if let Err(e) = self.flush(storage.as_ref()).await {
+                            logging::log_error("Usage flush error", &e.to_string());
+                        }
+                        break;
+                    }
+                    _ = interval.tick() => {
+                        if let Err(e) = self.flush(storage.as_ref()).await {
+                            logging::log_error("Usage flush error", &e.to_string());
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Logs the usage counted since startup, one line per program.
+    pub fn report(&self) {
+        let mut per_program: Vec<(String, Counters)> = Vec::new();
+        for record in self.totals() {
+            let counters = Counters {
+                events: record.events,
+                bytes: record.bytes,
+                rpc_calls: record.rpc_calls,
+            };
+            match per_program.last_mut() {
+                Some((program, total)) if *program == record.program => total.add(counters),
+                _ => per_program.push((record.program, counters)),
+            }
+        }
+
+        for (program, total) in per_program {
+            logging::log(
+                logging::LogLevel::Info,
+                &format!(
+                    "Usage [{program}]: Events: {} | Bytes: {} | RPC calls: {}",
+                    total.events, total.bytes, total.rpc_calls
+                ),
+            );
+        }
+    }
+
+    fn add(&self, key: UsageKey, counters: Counters) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.entry(key.clone()).or_default().add(counters);
+        }
+        if let Ok(mut totals) = self.totals.lock() {
+            totals.entry(key).or_default().add(counters);
+        }
+    }
+}
+
+/// Runs `future` with its RPC calls attributed to `program`.
+pub async fn attribute<F: Future>(
+    tracker: Arc<UsageTracker>,
+    program: impl Into<Arc<str>>,
+    future: F,
+) -> F::Output {
+    SCOPE.scope((tracker, program.into()), future).await
+}
+
+/// Counts `calls` RPC calls against the program in scope, if any.
+pub(crate) fn record_rpc_calls(calls: u64) {
+    let _ = SCOPE.try_with(|(tracker, program)| tracker.record_rpc_calls(program, calls));
+}
+
+fn records(counters: &HashMap<UsageKey, Counters>) -> Vec<UsageRecord> {
+    let mut records: Vec<UsageRecord> = counters
+        .iter()
+        .map(|((program, discriminator), counters)| UsageRecord {
+            program: program.clone(),
+            discriminator: *discriminator,
+            events: counters.events,
+            bytes: counters.bytes,
+            rpc_calls: counters.rpc_calls,
+        })
+        .collect();
+    records.sort_by(|a, b| (&a.program, a.discriminator).cmp(&(&b.program, b.discriminator)));
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_usage_attribution() {
+        let tracker = Arc::new(UsageTracker::new());
+        tracker.assign([1; 8], "ProgramA");
+
+        tracker.record_event([1; 8], 40);
+        tracker.record_event([1; 8], 60);
+        tracker.record_event([2; 8], 8);
+        attribute(tracker.clone(), "ProgramA", async {
+            record_rpc_calls(1);
+            record_rpc_calls(1);
+        })
+        .await;
+        // Outside any scope nothing is counted
+        record_rpc_calls(1);
+
+        assert_eq!(
+            tracker.totals(),
+            vec![
+                UsageRecord {
+                    program: "ProgramA".into(),
+                    discriminator: None,
+                    rpc_calls: 2,
+                    ..UsageRecord::default()
+                },
+                UsageRecord {
+                    program: "ProgramA".into(),
+                    discriminator: Some([1; 8]),
+                    events: 2,
+                    bytes: 100,
+                    rpc_calls: 0,
+                },
+                UsageRecord {
+                    program: UNATTRIBUTED.into(),
+                    discriminator: Some([2; 8]),
+                    events: 1,
+                    bytes: 8,
+                    rpc_calls: 0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Downstream notification of processed-slot watermark advances.
+//!
+//! Every row at or below the watermark has been fully handled, so ETL jobs
+//! can pull incrementally: everything above their last-seen watermark, up to
+//! the new one. Each advance is published three ways:
+//!
+//! - **`LISTEN`/`NOTIFY`**: `Storage` notifies `solana_indexer_sdk_watermark`
+//!   with the new slot as payload, in the same statement that raises it.
+//! - **In process**: `WatermarkNotifier::subscribe` returns a watch channel.
+//! - **Event sinks**: sinks added with `WatermarkNotifier::add_sink` receive
+//!   a `Watermark` record.
+//!
+//! ```sql
+//! LISTEN solana_indexer_sdk_watermark;
+//! ```
+
+use crate::sinks::{EventSink, SinkRecord};
+use crate::utils::logging;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+/// `NOTIFY` channel the Postgres storage publishes watermark advances on.
+pub const WATERMARK_CHANNEL: &str = "solana_indexer_sdk_watermark";
+
+/// Publishes watermark advances in process and to event sinks.
+pub struct WatermarkNotifier {
+    sender: watch::Sender<Option<u64>>,
+    sinks: RwLock<Vec<Arc<dyn EventSink>>>,
+    /// Number of times the watermark advanced
+    pub advances: AtomicU64,
+}
+
+impl Default for WatermarkNotifier {
+    fn default() -> Self {
+        Self {
+            sender: watch::channel(None).0,
+            sinks: RwLock::default(),
+            advances: AtomicU64::new(0),
+        }
+    }
+}
+
+impl WatermarkNotifier {
+    /// Creates a notifier that has seen no watermark yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a receiver that observes every advance; it holds `None` until
+    /// the first batch commits.
+    #[must_use]
+    pub fn subscribe(&self) -> watch::Receiver<Option<u64>> {
+        self.sender.subscribe()
+    }
+
+    /// Returns the latest watermark published, if any.
+    #[must_use]
+    pub fn latest(&self) -> Option<u64> {
+        *self.sender.borrow()
+    }
+
+    /// Publishes each advance to `sink` as a `Watermark` record whose slot
+    /// is the new watermark.
+    pub fn add_sink(&self, sink: impl EventSink) {
+        self.sinks
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(Arc::new(sink));
+    }
+
+    /// Publishes `slot` if it is above the latest watermark. Sink failures
+    /// are logged; they never hold up indexing.
+    pub(crate) async fn publish(&self, slot: u64) {
+        let advanced = self.sender.send_if_modified(|latest| {
+            if latest.is_some_and(|latest| latest >= slot) {
+                return false;
+            }
+            *latest = Some(slot);
+            true
+        });
+        if !advanced {
+            return;
+        }
+        self.advances.fetch_add(1, Ordering::Relaxed);
+
+        let sinks = self
+            .sinks
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
+        let record = watermark_record(slot);
+        for sink in sinks {
+            if let Err(e) = sink.publish(&record).await {
+                logging::log_error("Watermark sink error", &e.to_string());
+            }
+        }
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Watermark Stats: Slot: {} | Advances: {}",
+                self.latest()
+                    .map_or_else(|| "none".to_string(), |slot| slot.to_string()),
+                self.advances.load(Ordering::Relaxed)
+            ),
+        );
+    }
+}
+
+fn watermark_record(slot: u64) -> SinkRecord {
+    SinkRecord {
+        event: "Watermark".to_string(),
+        signature: String::new(),
+        slot,
+        block_time: None,
+        payload: serde_json::json!({ "watermark": slot }),
+        dedup_key: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::error::Result;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    #[derive(Default, Clone)]
+    struct Recorded(Arc<Mutex<Vec<u64>>>);
+
+    #[async_trait]
+    impl EventSink for Recorded {
+        async fn publish(&self, record: &SinkRecord) -> Result<()> {
+            self.0.lock().unwrap().push(record.slot);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_publishes_only_advances() {
+        let notifier = WatermarkNotifier::new();
+        let recorded = Recorded::default();
+        notifier.add_sink(recorded.clone());
+        let mut receiver = notifier.subscribe();
+        assert_eq!(notifier.latest(), None);
+
+        notifier.publish(100).await;
+        notifier.publish(90).await;
+        notifier.publish(100).await;
+        notifier.publish(120).await;
+
+        assert!(receiver.has_changed().unwrap());
+        assert_eq!(*receiver.borrow_and_update(), Some(120));
+        assert_eq!(*recorded.0.lock().unwrap(), [100, 120]);
+        assert_eq!(notifier.advances.load(Ordering::Relaxed), 2);
+    }
+}
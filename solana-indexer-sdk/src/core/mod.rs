@@ -1,4 +1,5 @@
 pub mod backfill;
 pub mod decoding;
 pub mod execution;
+pub mod pipeline;
 pub mod registry;
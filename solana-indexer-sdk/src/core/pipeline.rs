@@ -0,0 +1,627 @@
+//! Named pipelines composed with a fluent builder.
+//!
+//! A `Pipeline` describes everything the indexer does with one event type:
+//! the program it comes from, the decoders producing it, the filters and
+//! transforms it passes through, and the sinks and handlers it ends up in.
+//!
+//! ```text
+//! source → filters → decoders → transforms → sinks / handlers
+//! ```
+//!
+//! Stages run in the order they were added, and every sink or handler sees
+//! the event as it is at its position in the chain, so one pipeline can
+//! publish the raw event to a sink and a transformed one to a table. A
+//! pipeline is attached with `SolanaIndexer::attach_pipeline`, which
+//! registers its decoders and installs the pipeline as the handler of its
+//! event type.
+//!
+//! Routes send the events matching a predicate to their own target, which
+//! may write to a database of its own. A failing route is logged and
+//! counted but does not fail the transaction or the other outputs, so one
+//! unavailable target cannot hold up the rest of the pipeline.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{Pipeline, SolanaIndexer, TransferEvent, TxMetadata};
+//! # use solana_indexer_sdk::sinks::EventSink;
+//! # fn example(
+//! #     indexer: &mut SolanaIndexer,
+//! #     decoder: impl solana_indexer_sdk::InstructionDecoder<TransferEvent> + 'static,
+//! #     archive: impl EventSink + Clone,
+//! #     alerts: impl EventSink,
+//! #     whales_pool: sqlx::PgPool,
+//! #     whales_handler: impl solana_indexer_sdk::EventHandler<TransferEvent>,
+//! # ) -> solana_indexer_sdk::Result<()> {
+//! let pipeline = Pipeline::<TransferEvent>::builder("large-transfers")
+//!     .source("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA")
+//!     .filter(|context: &TxMetadata| context.err.is_none())
+//!     .decoder(decoder)
+//!     .sink(archive.clone())
+//!     .retain(|transfer: &TransferEvent, _: &TxMetadata| transfer.amount >= 1_000_000)
+//!     .sink(alerts)
+//!     .build()?;
+//!
+//! let whales = Pipeline::<TransferEvent>::builder("whales")
+//!     .route_sink("archive", |_: &TransferEvent, _: &TxMetadata| true, archive)
+//!     .route_database(
+//!         "whales-db",
+//!         |transfer: &TransferEvent, _: &TxMetadata| transfer.amount >= 1_000_000_000,
+//!         whales_pool,
+//!         whales_handler,
+//!     )
+//!     .build()?;
+//!
+//! indexer.attach_pipeline(pipeline)?;
+//! indexer.attach_pipeline(whales)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::core::execution::indexer::SolanaIndexer;
+use crate::sinks::{EventSink, SinkHandler};
+use crate::types::events::EventDiscriminator;
+use crate::types::metadata::TxMetadata;
+use crate::types::schema::TableSchema;
+use crate::types::traits::{EventHandler, InstructionDecoder, LogDecoder};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// A chain of stages taking the decoded event `E` and yielding the value
+/// that continues down the pipeline, or `None` once it was filtered out.
+type Stage<E, T> = Arc<
+    dyn for<'a> Fn(E, &'a TxMetadata, &'a PgPool) -> BoxFuture<'a, Result<Option<T>>> + Send + Sync,
+>;
+
+/// Registers a decoder with the indexer under the pipeline's source.
+type Registration = Box<dyn FnOnce(&mut SolanaIndexer, String) -> Result<()> + Send + Sync>;
+
+fn stage<E, T, F>(f: F) -> Stage<E, T>
+where
+    F: for<'a> Fn(E, &'a TxMetadata, &'a PgPool) -> BoxFuture<'a, Result<Option<T>>>
+        + Send
+        + Sync
+        + 'static,
+{
+    Arc::new(f)
+}
+
+/// The hooks of a sink or handler that are not tied to its event type.
+#[async_trait]
+trait Output: Send + Sync {
+    async fn on_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()>;
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()>;
+    fn output_schema(&self) -> Vec<TableSchema>;
+}
+
+struct HandlerOutput<T> {
+    handler: Arc<dyn EventHandler<T>>,
+    /// Database the handler writes to instead of the indexer's
+    database: Option<PgPool>,
+    _event: PhantomData<fn(T)>,
+}
+
+#[async_trait]
+impl<T: 'static> Output for HandlerOutput<T> {
+    async fn on_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        let db = self.database.as_ref().unwrap_or(db);
+        self.handler.on_rollback(context, db).await
+    }
+
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
+        let pool = self.database.as_ref().unwrap_or(pool);
+        self.handler.initialize_schema(pool).await
+    }
+
+    fn output_schema(&self) -> Vec<TableSchema> {
+        self.handler.output_schema()
+    }
+}
+
+/// Delivery counts of one route.
+struct RouteState {
+    name: String,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Delivery counts of a route, returned by `Pipeline::routes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteReport {
+    /// Name the route was added with
+    pub name: String,
+    /// Events the route's target accepted
+    pub delivered: u64,
+    /// Events the route's target failed on
+    pub failed: u64,
+}
+
+/// Everything the indexer does with events of type `E`, built with
+/// `Pipeline::builder`.
+pub struct Pipeline<E> {
+    name: String,
+    source: Option<String>,
+    decoders: Vec<Registration>,
+    run: Stage<E, ()>,
+    outputs: Vec<Arc<dyn Output>>,
+    routes: Vec<Arc<RouteState>>,
+}
+
+impl<E: Send + 'static> Pipeline<E> {
+    /// Starts a pipeline named `name`. The name shows up in errors about
+    /// the pipeline.
+    #[must_use]
+    pub fn builder(name: impl Into<String>) -> PipelineBuilder<E> {
+        PipelineBuilder {
+            name: name.into(),
+            source: None,
+            decoders: Vec::new(),
+            stage: stage(|event, _, _| Box::pin(async move { Ok(Some(event)) })),
+            outputs: Vec::new(),
+            routes: Vec::new(),
+        }
+    }
+}
+
+impl<E> Pipeline<E> {
+    /// Returns the name of the pipeline.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns how many events each route delivered and failed on, in the
+    /// order the routes were added.
+    #[must_use]
+    pub fn routes(&self) -> Vec<RouteReport> {
+        self.routes
+            .iter()
+            .map(|route| RouteReport {
+                name: route.name.clone(),
+                delivered: route.delivered.load(Ordering::Relaxed),
+                failed: route.failed.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Registers the pipeline's decoders with `indexer` and returns the
+    /// pipeline, ready to be registered as the handler of `E`.
+    pub(crate) fn register_decoders(mut self, indexer: &mut SolanaIndexer) -> Result<Self> {
+        let source = self.source.clone().unwrap_or_default();
+        for register in std::mem::take(&mut self.decoders) {
+            register(indexer, source.clone())?;
+        }
+        Ok(self)
+    }
+}
+
+#[async_trait]
+impl<E: Send + 'static> EventHandler<E> for Pipeline<E> {
+    async fn handle(&self, event: E, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        (self.run)(event, context, db).await.map(|_| ())
+    }
+
+    async fn on_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        for output in &self.outputs {
+            output.on_rollback(context, db).await?;
+        }
+        Ok(())
+    }
+
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
+        for output in &self.outputs {
+            output.initialize_schema(pool).await?;
+        }
+        Ok(())
+    }
+
+    fn output_schema(&self) -> Vec<TableSchema> {
+        self.outputs
+            .iter()
+            .flat_map(|output| output.output_schema())
+            .collect()
+    }
+}
+
+/// Builder for a `Pipeline` over events of type `E`, currently carrying
+/// values of type `T` (`E` until a transform changes it).
+pub struct PipelineBuilder<E, T = E> {
+    name: String,
+    source: Option<String>,
+    decoders: Vec<Registration>,
+    stage: Stage<E, T>,
+    outputs: Vec<Arc<dyn Output>>,
+    routes: Vec<Arc<RouteState>>,
+}
+
+impl<E, T> PipelineBuilder<E, T>
+where
+    E: Send + 'static,
+    T: Send + 'static,
+{
+    /// Sets the program whose transactions feed the pipeline. Decoders
+    /// added with `decoder` and `log_decoder` are registered for it.
+    #[must_use]
+    pub fn source(mut self, program_id: impl Into<String>) -> Self {
+        self.source = Some(program_id.into());
+        self
+    }
+
+    /// Adds an instruction decoder producing `E`.
+    #[must_use]
+    pub fn decoder<D>(mut self, decoder: D) -> Self
+    where
+        D: InstructionDecoder<E> + 'static,
+        E: EventDiscriminator + borsh::BorshSerialize + Sync,
+    {
+        self.decoders.push(Box::new(move |indexer, program_id| {
+            indexer.register_decoder(program_id, decoder)
+        }));
+        self
+    }
+
+    /// Adds a log decoder producing `E`.
+    #[must_use]
+    pub fn log_decoder<D>(mut self, decoder: D) -> Self
+    where
+        D: LogDecoder<E> + 'static,
+        E: EventDiscriminator + borsh::BorshSerialize + Sync,
+    {
+        self.decoders.push(Box::new(move |indexer, program_id| {
+            indexer.register_log_decoder(program_id, decoder)
+        }));
+        self
+    }
+
+    /// Drops events from transactions for which `predicate` returns `false`.
+    #[must_use]
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&TxMetadata) -> bool + Send + Sync + 'static,
+    {
+        self.retain(move |_: &T, context: &TxMetadata| predicate(context))
+    }
+
+    /// Drops events for which `predicate` returns `false`.
+    #[must_use]
+    pub fn retain<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&T, &TxMetadata) -> bool + Send + Sync + 'static,
+    {
+        let predicate = Arc::new(predicate);
+        self.then(stage(move |value: T, context, _| {
+            let keep = predicate(&value, context);
+            Box::pin(async move { Ok(keep.then_some(value)) })
+        }))
+    }
+
+    /// Replaces each event with the result of `transform`. Later stages
+    /// see the transformed value.
+    #[must_use]
+    pub fn transform<U, F>(self, transform: F) -> PipelineBuilder<E, U>
+    where
+        U: Send + 'static,
+        F: Fn(T, &TxMetadata) -> U + Send + Sync + 'static,
+    {
+        let transform = Arc::new(transform);
+        self.then(stage(move |value: T, context, _| {
+            let value = transform(value, context);
+            Box::pin(async move { Ok(Some(value)) })
+        }))
+    }
+
+    /// Hands a copy of each event to `handler` and passes it on.
+    #[must_use]
+    pub fn handler<H>(mut self, handler: H) -> Self
+    where
+        H: EventHandler<T>,
+        T: Clone + Sync,
+    {
+        let handler: Arc<dyn EventHandler<T>> = Arc::new(handler);
+        self.outputs.push(Arc::new(HandlerOutput {
+            handler: handler.clone(),
+            database: None,
+            _event: PhantomData,
+        }));
+        self.then(stage(move |value: T, context, db| {
+            let handler = handler.clone();
+            Box::pin(async move {
+                handler.handle(value.clone(), context, db).await?;
+                Ok(Some(value))
+            })
+        }))
+    }
+
+    /// Publishes each event to `sink` and passes it on.
+    #[must_use]
+    pub fn sink<S>(self, sink: S) -> Self
+    where
+        S: EventSink,
+        T: Serialize + Clone + Sync,
+    {
+        self.handler(SinkHandler::<T, S>::new(sink))
+    }
+
+    /// Hands a copy of each event matching `predicate` to `handler` and
+    /// passes every event on.
+    ///
+    /// Errors from `handler` are logged and counted in `Pipeline::routes`
+    /// instead of failing the transaction.
+    #[must_use]
+    pub fn route<P, H>(self, name: impl Into<String>, predicate: P, handler: H) -> Self
+    where
+        P: Fn(&T, &TxMetadata) -> bool + Send + Sync + 'static,
+        H: EventHandler<T>,
+        T: Clone + Sync,
+    {
+        self.add_route(name.into(), predicate, Arc::new(handler), None)
+    }
+
+    /// Publishes each event matching `predicate` to `sink` and passes every
+    /// event on. Failures are isolated as with `route`.
+    #[must_use]
+    pub fn route_sink<P, S>(self, name: impl Into<String>, predicate: P, sink: S) -> Self
+    where
+        P: Fn(&T, &TxMetadata) -> bool + Send + Sync + 'static,
+        S: EventSink,
+        T: Serialize + Clone + Sync,
+    {
+        self.route(name, predicate, SinkHandler::<T, S>::new(sink))
+    }
+
+    /// Like `route`, but `handler` is given `database` instead of the
+    /// indexer's pool, including for schema setup and rollbacks.
+    #[must_use]
+    pub fn route_database<P, H>(
+        self,
+        name: impl Into<String>,
+        predicate: P,
+        database: PgPool,
+        handler: H,
+    ) -> Self
+    where
+        P: Fn(&T, &TxMetadata) -> bool + Send + Sync + 'static,
+        H: EventHandler<T>,
+        T: Clone + Sync,
+    {
+        self.add_route(name.into(), predicate, Arc::new(handler), Some(database))
+    }
+
+    /// Finishes the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the pipeline has no sink
+    /// or handler, or has decoders but no source.
+    pub fn build(self) -> Result<Pipeline<E>> {
+        if self.outputs.is_empty() {
+            return Err(SolanaIndexerError::Config(format!(
+                "Pipeline '{}' has no sinks or handlers",
+                self.name
+            )));
+        }
+        if !self.decoders.is_empty() && self.source.is_none() {
+            return Err(SolanaIndexerError::Config(format!(
+                "Pipeline '{}' has decoders but no source",
+                self.name
+            )));
+        }
+        let done = self.then(stage(|_: T, _, _| Box::pin(async { Ok(Some(())) })));
+        Ok(Pipeline {
+            name: done.name,
+            source: done.source,
+            decoders: done.decoders,
+            run: done.stage,
+            outputs: done.outputs,
+            routes: done.routes,
+        })
+    }
+
+    fn add_route<P>(
+        mut self,
+        name: String,
+        predicate: P,
+        handler: Arc<dyn EventHandler<T>>,
+        database: Option<PgPool>,
+    ) -> Self
+    where
+        P: Fn(&T, &TxMetadata) -> bool + Send + Sync + 'static,
+        T: Clone + Sync,
+    {
+        let route = Arc::new(RouteState {
+            name,
+            delivered: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        });
+        self.routes.push(route.clone());
+        self.outputs.push(Arc::new(HandlerOutput {
+            handler: handler.clone(),
+            database: database.clone(),
+            _event: PhantomData,
+        }));
+        let pipeline = self.name.clone();
+        let predicate = Arc::new(predicate);
+        self.then(stage(move |value: T, context, db| {
+            let matched = predicate(&value, context);
+            let handler = handler.clone();
+            let route = route.clone();
+            let pipeline = pipeline.clone();
+            let database = database.clone();
+            Box::pin(async move {
+                if matched {
+                    let db = database.as_ref().unwrap_or(db);
+                    match handler.handle(value.clone(), context, db).await {
+                        Ok(()) => {
+                            route.delivered.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(e) => {
+                            route.failed.fetch_add(1, Ordering::Relaxed);
+                            logging::log_error(
+                                &format!("Route '{}' of pipeline '{pipeline}' failed", route.name),
+                                &e.to_string(),
+                            );
+                        }
+                    }
+                }
+                Ok(Some(value))
+            })
+        }))
+    }
+
+    /// Appends `next` to the chain.
+    fn then<U: Send + 'static>(self, next: Stage<T, U>) -> PipelineBuilder<E, U> {
+        let previous = self.stage;
+        PipelineBuilder {
+            name: self.name,
+            source: self.source,
+            decoders: self.decoders,
+            stage: stage(move |event: E, context, db| {
+                let previous = previous.clone();
+                let next = next.clone();
+                Box::pin(async move {
+                    match previous(event, context, db).await? {
+                        Some(value) => next(value, context, db).await,
+                        None => Ok(None),
+                    }
+                })
+            }),
+            outputs: self.outputs,
+            routes: self.routes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+    use tokio::sync::Mutex;
+
+    #[derive(Clone)]
+    struct Collect<T>(Arc<Mutex<Vec<T>>>);
+
+    impl<T> Collect<T> {
+        fn new() -> Self {
+            Self(Arc::new(Mutex::new(Vec::new())))
+        }
+    }
+
+    #[async_trait]
+    impl<T: Send + Sync + 'static> EventHandler<T> for Collect<T> {
+        async fn handle(&self, event: T, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            self.0.lock().await.push(event);
+            Ok(())
+        }
+    }
+
+    fn context(err: Option<&str>) -> TxMetadata {
+        TxMetadata {
+            slot: 1,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: err.map(ToString::to_string),
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        }
+    }
+
+    fn transfer(amount: u64) -> TransferEvent {
+        TransferEvent {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stages_run_in_order() -> Result<()> {
+        let all = Collect::<TransferEvent>::new();
+        let large = Collect::<u64>::new();
+        let pipeline = Pipeline::<TransferEvent>::builder("transfers")
+            .filter(|context: &TxMetadata| context.err.is_none())
+            .handler(all.clone())
+            .retain(|transfer: &TransferEvent, _: &TxMetadata| transfer.amount >= 100)
+            .transform(|transfer: TransferEvent, _: &TxMetadata| transfer.amount)
+            .handler(large.clone())
+            .build()?;
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+        pipeline.handle(transfer(5), &context(None), &pool).await?;
+        pipeline
+            .handle(transfer(500), &context(None), &pool)
+            .await?;
+        pipeline
+            .handle(transfer(900), &context(Some("failed")), &pool)
+            .await?;
+
+        assert_eq!(all.0.lock().await.len(), 2);
+        assert_eq!(*large.0.lock().await, vec![500]);
+        Ok(())
+    }
+
+    /// Fails every event, like a target that is down.
+    struct Failing;
+
+    #[async_trait]
+    impl EventHandler<TransferEvent> for Failing {
+        async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            Err(SolanaIndexerError::Internal("target down".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_are_isolated() -> Result<()> {
+        let whales = Collect::<TransferEvent>::new();
+        let all = Collect::<TransferEvent>::new();
+        let pipeline = Pipeline::<TransferEvent>::builder("routed")
+            .route("broken", |_: &TransferEvent, _: &TxMetadata| true, Failing)
+            .route(
+                "whales",
+                |transfer: &TransferEvent, _: &TxMetadata| transfer.amount >= 100,
+                whales.clone(),
+            )
+            .handler(all.clone())
+            .build()?;
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+        pipeline.handle(transfer(5), &context(None), &pool).await?;
+        pipeline
+            .handle(transfer(500), &context(None), &pool)
+            .await?;
+
+        assert_eq!(whales.0.lock().await.len(), 1);
+        assert_eq!(all.0.lock().await.len(), 2);
+        let routes = pipeline.routes();
+        assert_eq!((routes[0].delivered, routes[0].failed), (0, 2));
+        assert_eq!(routes[1].name, "whales");
+        assert_eq!((routes[1].delivered, routes[1].failed), (1, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_requires_output() {
+        let result = Pipeline::<TransferEvent>::builder("empty")
+            .retain(|_: &TransferEvent, _: &TxMetadata| true)
+            .build();
+        assert!(matches!(result, Err(SolanaIndexerError::Config(_))));
+    }
+}
@@ -12,6 +12,8 @@ use crate::utils::error::{Result, SolanaIndexerError};
 pub struct AccountDecoderRegistry {
     decoders: Vec<Box<dyn DynamicAccountDecoder>>,
     metrics: RegistryMetrics,
+    /// Largest account data passed to decoders, in bytes (0 = unlimited).
+    max_data_bytes: usize,
 }
 
 impl AccountDecoderRegistry {
@@ -20,6 +22,7 @@ impl AccountDecoderRegistry {
         Self {
             decoders: Vec::new(),
             metrics: RegistryMetrics::new("AccountDecoder", 0),
+            max_data_bytes: 0,
         }
     }
 
@@ -27,7 +30,9 @@ impl AccountDecoderRegistry {
     pub fn new_bounded(config: &RegistryConfig) -> Self {
         Self {
             decoders: Vec::new(),
-            metrics: RegistryMetrics::new("AccountDecoder", config.max_account_decoders),
+            metrics: RegistryMetrics::new("AccountDecoder", config.max_account_decoders)
+                .with_miss_sampling(config.decode_miss_sample_every),
+            max_data_bytes: config.max_account_data_bytes,
         }
     }
 
@@ -50,20 +55,41 @@ impl AccountDecoderRegistry {
     /// Returns a vector of tuples `(discriminator, data)` for all matches if multiple decoders handle it,
     /// or typically just one. For accounts, usually only one decoder matches a given account structure.
     /// However, following the pattern of `DecoderRegistry`, we return a list of matches.
+    ///
+    /// Accounts larger than `RegistryConfig::max_account_data_bytes` are not
+    /// decoded; they are counted as rejected and an empty list is returned.
     pub fn decode_account(
         &self,
         pubkey: &solana_sdk::pubkey::Pubkey,
         account: &solana_sdk::account::Account,
     ) -> Vec<([u8; 8], Vec<u8>)> {
         self.metrics.inc_calls();
+        if self.max_data_bytes > 0 && account.data.len() > self.max_data_bytes {
+            self.metrics.inc_rejected();
+            tracing::warn!(
+                account = %pubkey,
+                len = account.data.len(),
+                limit = self.max_data_bytes,
+                "Skipping account whose data exceeds the decode size limit"
+            );
+            return Vec::new();
+        }
         let results: Vec<_> = self
             .decoders
             .iter()
-            .filter_map(|decoder| decoder.decode_account_dynamic(pubkey, account))
+            .flat_map(|decoder| decoder.decode_account_dynamic_many(pubkey, account))
             .collect();
 
         if !results.is_empty() {
             self.metrics.inc_hits();
+        } else if self.metrics.samples_misses() {
+            self.metrics.record_miss(
+                &account.owner.to_string(),
+                &account.data,
+                self.decoders
+                    .iter()
+                    .filter_map(|decoder| decoder.discriminator()),
+            );
         }
 
         results
@@ -135,11 +161,40 @@ mod tests {
         assert_eq!(*discriminator, TestAccount::discriminator());
 
         let event = TestAccount::try_from_slice(data)
-            .map_err(|e| SolanaIndexerError::DecodingError(e.to_string()))?;
+            .map_err(|e| SolanaIndexerError::Decode(e.to_string()))?;
         assert_eq!(event.value, 10);
         Ok(())
     }
 
+    #[test]
+    fn test_oversized_account_rejected() -> Result<()> {
+        let mut registry = AccountDecoderRegistry::new_bounded(&RegistryConfig {
+            max_account_data_bytes: 8,
+            ..RegistryConfig::default()
+        });
+        registry.register(Box::new(
+            Box::new(TestDecoder) as Box<dyn crate::types::traits::AccountDecoder<TestAccount>>
+        ))?;
+
+        let mut account = Account {
+            data: vec![10, 0, 0, 0, 0, 0, 0, 0],
+            ..Account::default()
+        };
+        let pubkey = Pubkey::new_unique();
+        assert_eq!(registry.decode_account(&pubkey, &account).len(), 1);
+
+        account.data.push(0);
+        assert!(registry.decode_account(&pubkey, &account).is_empty());
+        assert_eq!(
+            registry
+                .metrics()
+                .rejected
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_decode_empty() {
         let registry = AccountDecoderRegistry::new();
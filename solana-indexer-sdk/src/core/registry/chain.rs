@@ -0,0 +1,148 @@
+//! Ordered decoder chains.
+//!
+//! Every program can have several decoders. They run from the highest
+//! priority to the lowest, in registration order among equal priorities.
+//! The first decoder that accepts an input ends the chain, unless it was
+//! registered with `ChainControl::Continue`, in which case the decoders
+//! after it run as well. A specialized decoder registered with a high
+//! priority can thus claim the inputs it knows, and a catch-all registered
+//! with a low priority captures everything else.
+
+use std::sync::Arc;
+
+/// What happens after a decoder in a chain accepts an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChainControl {
+    /// No further decoder runs (default)
+    #[default]
+    Stop,
+    /// The next decoders run too, and may emit their own events
+    Continue,
+}
+
+/// The decoders of one program, highest priority first. A decoder can sit
+/// in the chains of several programs.
+pub(crate) struct DecoderChain<D: ?Sized> {
+    entries: Vec<ChainEntry<D>>,
+}
+
+struct ChainEntry<D: ?Sized> {
+    priority: i32,
+    control: ChainControl,
+    decoder: Arc<D>,
+}
+
+impl<D: ?Sized> Default for DecoderChain<D> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<D: ?Sized> DecoderChain<D> {
+    /// Adds `decoder` after every decoder of the same or higher priority.
+    pub(crate) fn insert(
+        &mut self,
+        decoder: impl Into<Arc<D>>,
+        priority: i32,
+        control: ChainControl,
+    ) {
+        let position = self
+            .entries
+            .partition_point(|entry| entry.priority >= priority);
+        self.entries.insert(
+            position,
+            ChainEntry {
+                priority,
+                control,
+                decoder: decoder.into(),
+            },
+        );
+    }
+
+    /// Runs the chain, pushing what each decoder returns into `out`. A
+    /// decoder accepts an input by returning at least one item. Returns
+    /// `true` if any decoder accepted the input.
+    pub(crate) fn run<T, I>(&self, mut decode: impl FnMut(&D) -> I, out: &mut Vec<T>) -> bool
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut accepted = false;
+        for entry in &self.entries {
+            let before = out.len();
+            out.extend(decode(&entry.decoder));
+            if out.len() > before {
+                accepted = true;
+                if entry.control == ChainControl::Stop {
+                    break;
+                }
+            }
+        }
+        accepted
+    }
+
+    /// Returns the decoders in the order they run.
+    pub(crate) fn decoders(&self) -> impl Iterator<Item = &D> {
+        self.entries.iter().map(|entry| &*entry.decoder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Decoder = dyn Fn(u8) -> Option<&'static str>;
+
+    fn chain(entries: Vec<(i32, ChainControl, Box<Decoder>)>) -> DecoderChain<Decoder> {
+        let mut chain = DecoderChain::default();
+        for (priority, control, decoder) in entries {
+            chain.insert(decoder, priority, control);
+        }
+        chain
+    }
+
+    fn run(chain: &DecoderChain<Decoder>, input: u8) -> Vec<&'static str> {
+        let mut out = Vec::new();
+        chain.run(|decoder| decoder(input), &mut out);
+        out
+    }
+
+    #[test]
+    fn test_priority_then_registration_order() {
+        let chain = chain(vec![
+            (0, ChainControl::Stop, Box::new(|_| Some("catch-all"))),
+            (
+                10,
+                ChainControl::Stop,
+                Box::new(|n| (n == 1).then_some("swap")),
+            ),
+            (
+                0,
+                ChainControl::Stop,
+                Box::new(|_| Some("second catch-all")),
+            ),
+        ]);
+
+        assert_eq!(run(&chain, 1), ["swap"]);
+        assert_eq!(run(&chain, 2), ["catch-all"]);
+    }
+
+    #[test]
+    fn test_continue_runs_the_next_decoders() {
+        let chain = chain(vec![
+            (0, ChainControl::Stop, Box::new(|_| Some("catch-all"))),
+            (
+                5,
+                ChainControl::Continue,
+                Box::new(|n| (n == 1).then_some("audit")),
+            ),
+        ]);
+
+        assert_eq!(run(&chain, 1), ["audit", "catch-all"]);
+        assert_eq!(run(&chain, 2), ["catch-all"]);
+
+        let empty: DecoderChain<Decoder> = DecoderChain::default();
+        assert!(!empty.run(|decoder| decoder(1), &mut Vec::new()));
+    }
+}
@@ -0,0 +1,151 @@
+//! Registration-time event filters.
+//!
+//! Most handlers only care about some of the events of their type: dust
+//! transfers, self-transfers or a program's test accounts are typically
+//! dropped by the first lines of `handle`. `FilteredHandler` moves that check
+//! in front of the handler, and `SolanaIndexer::register_handler_with_filter`
+//! registers a handler behind a predicate in one call. The predicate is a
+//! plain synchronous closure over the decoded event and its transaction, so
+//! a skipped event costs neither the handler's await nor a database round
+//! trip, and never counts against its retries.
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{EventHandler, Result, SolanaIndexer, TransferEvent, TxMetadata};
+//! # fn example(indexer: &mut SolanaIndexer, handler: impl EventHandler<TransferEvent> + 'static) -> Result<()> {
+//! // Skip dust and self-transfers
+//! indexer.register_handler_with_filter(handler, |event: &TransferEvent, _: &TxMetadata| {
+//!     event.amount >= 1_000 && event.from != event.to
+//! })?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The filter belongs to one handler: other handlers of the same event type
+//! still receive the events it skips.
+
+use crate::types::metadata::TxMetadata;
+use crate::types::schema::TableSchema;
+use crate::types::traits::EventHandler;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Decides whether an event is passed on to the handler.
+type FilterFn<E> = Box<dyn Fn(&E, &TxMetadata) -> bool + Send + Sync>;
+
+/// Event handler wrapper that only passes on the events its filter accepts.
+///
+/// Skipped events are acknowledged as handled.
+pub struct FilteredHandler<H, E> {
+    handler: H,
+    filter: FilterFn<E>,
+    skipped: AtomicU64,
+}
+
+impl<H, E> FilteredHandler<H, E> {
+    /// Wraps `handler`, passing on the events `filter` returns `true` for.
+    pub fn new(
+        handler: H,
+        filter: impl Fn(&E, &TxMetadata) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            handler,
+            filter: Box::new(filter),
+            skipped: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of events the filter has skipped.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+#[async_trait]
+impl<H, E> EventHandler<E> for FilteredHandler<H, E>
+where
+    H: EventHandler<E>,
+    E: Send + Sync + 'static,
+{
+    async fn handle(&self, event: E, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        if !(self.filter)(&event, context) {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        self.handler.handle(event, context, db).await
+    }
+
+    async fn on_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        self.handler.on_rollback(context, db).await
+    }
+
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
+        self.handler.initialize_schema(pool).await
+    }
+
+    fn output_schema(&self) -> Vec<TableSchema> {
+        self.handler.output_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+    use std::sync::Arc;
+
+    struct Count(Arc<AtomicU64>);
+
+    #[async_trait]
+    impl EventHandler<TransferEvent> for Count {
+        async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_skips_rejected_events() -> Result<()> {
+        let handled = Arc::new(AtomicU64::new(0));
+        let filtered = FilteredHandler::new(
+            Count(handled.clone()),
+            |event: &TransferEvent, _: &TxMetadata| event.amount >= 1_000 && event.from != event.to,
+        );
+        // Never connects: neither the filter nor the handler touch the pool
+        let pool = PgPool::connect_lazy("postgresql://localhost/unused")?;
+        let context = TxMetadata {
+            slot: 1,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        for (from, to, amount) in [("a", "b", 5), ("a", "a", 5_000), ("a", "b", 5_000)] {
+            let event = TransferEvent {
+                from: from.to_string(),
+                to: to.to_string(),
+                amount,
+            };
+            filtered.handle(event, &context, &pool).await?;
+        }
+
+        assert_eq!(handled.load(Ordering::Relaxed), 1);
+        assert_eq!(filtered.skipped(), 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,294 @@
+//! Runtime registration of decoders and handlers.
+//!
+//! `SolanaIndexer::registry_handle` returns a `RegistryHandle` sharing the
+//! indexer's instruction decoders and event handlers. Decoders and handlers
+//! registered or removed through it take effect for the next transaction,
+//! so a long-running indexer can pick up a new event type without being
+//! restarted and dropping its WebSocket connection:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{Result, SolanaIndexer};
+//! # fn example(indexer: &SolanaIndexer) -> Result<()> {
+//! let registry = indexer.registry_handle();
+//! tokio::spawn(async move {
+//!     // e.g. on an admin request
+//!     registry.unregister_decoders("system")?;
+//!     Ok::<_, solana_indexer_sdk::SolanaIndexerError>(())
+//! });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Transactions are still fetched as configured: a decoder only sees the
+//! instructions of the programs and addresses the indexer already follows.
+
+use crate::core::execution::usage::UsageTracker;
+use crate::core::registry::chain::ChainControl;
+use crate::core::registry::metrics::RegistryMetrics;
+use crate::core::registry::programs::resolve_program;
+use crate::core::registry::{DecoderChains, DecoderRegistry};
+use crate::types::events::EventDiscriminator;
+use crate::types::traits::{
+    DynamicEventHandler, DynamicInstructionDecoder, EventHandler, EventHandlers, HandlerRegistry,
+    InstructionDecoder,
+};
+use crate::utils::error::{Result, SolanaIndexerError};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Thread-safe handle registering and removing decoders and handlers of a
+/// running indexer.
+///
+/// Cloning is cheap and every clone refers to the same registries.
+#[derive(Clone)]
+pub struct RegistryHandle {
+    decoders: Arc<RwLock<DecoderChains>>,
+    decoder_metrics: Arc<RegistryMetrics>,
+    handlers: Arc<RwLock<EventHandlers>>,
+    handler_metrics: Arc<RegistryMetrics>,
+    usage: Option<Arc<UsageTracker>>,
+}
+
+impl RegistryHandle {
+    /// Creates a handle sharing the state of `decoders` and `handlers`.
+    pub(crate) fn new(decoders: &DecoderRegistry, handlers: &HandlerRegistry) -> Self {
+        let (decoders, decoder_metrics) = decoders.shared();
+        let (handlers, handler_metrics, usage) = handlers.shared();
+        Self {
+            decoders,
+            decoder_metrics,
+            handlers,
+            handler_metrics,
+            usage,
+        }
+    }
+
+    /// Registers a typed instruction decoder, like
+    /// `SolanaIndexer::register_decoder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
+    pub fn register_decoder<D, E>(&self, program_id: &str, decoder: D) -> Result<()>
+    where
+        D: InstructionDecoder<E> + 'static,
+        E: EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
+    {
+        self.register_decoder_with_priority(program_id, decoder, 0, ChainControl::Stop)
+    }
+
+    /// Registers a typed instruction decoder at a position in the program's
+    /// decoder chain, like `SolanaIndexer::register_decoder_with_priority`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full.
+    pub fn register_decoder_with_priority<D, E>(
+        &self,
+        program_id: &str,
+        decoder: D,
+        priority: i32,
+        control: ChainControl,
+    ) -> Result<()>
+    where
+        D: InstructionDecoder<E> + 'static,
+        E: EventDiscriminator + borsh::BorshSerialize + Send + Sync + 'static,
+    {
+        let boxed_typed: Box<dyn InstructionDecoder<E>> = Box::new(decoder);
+        let decoder: Arc<dyn DynamicInstructionDecoder> = Arc::new(boxed_typed);
+        if let Some(usage) = &self.usage {
+            usage.assign(E::discriminator(), program_id.to_string());
+        }
+        DecoderRegistry::insert(
+            &self.decoders,
+            &self.decoder_metrics,
+            program_id,
+            decoder,
+            priority,
+            control,
+        )
+    }
+
+    /// Removes every instruction decoder of the programs `program_id` stands
+    /// for, returning how many programs had decoders.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name.
+    pub fn unregister_decoders(&self, program_id: &str) -> Result<usize> {
+        let program_ids = resolve_program(program_id)?;
+        let mut decoders = self.decoders.write().map_err(|_| {
+            SolanaIndexerError::Internal("DecoderRegistry lock poisoned".to_string())
+        })?;
+        let chains: Vec<_> = program_ids
+            .iter()
+            .filter_map(|id| decoders.remove(id))
+            .collect();
+        // Registering counts each decoder once, even when it was added to
+        // the chains of several programs
+        let unregistered: HashSet<*const ()> = chains
+            .iter()
+            .flat_map(|chain| chain.decoders())
+            .map(|decoder| std::ptr::addr_of!(*decoder).cast::<()>())
+            .collect();
+        for _ in 0..unregistered.len() {
+            self.decoder_metrics.dec_registered();
+        }
+        Ok(chains.len())
+    }
+
+    /// Registers a typed event handler, like `SolanaIndexer::register_handler`,
+    /// after any handlers of the same event type.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the registry
+    /// is full.
+    pub fn register_handler<H, E>(&self, handler: H) -> Result<()>
+    where
+        H: EventHandler<E> + 'static,
+        E: EventDiscriminator + borsh::BorshDeserialize + Send + Sync + 'static,
+    {
+        let boxed_typed: Box<dyn EventHandler<E>> = Box::new(handler);
+        let handler: Arc<dyn DynamicEventHandler> = Arc::new(boxed_typed);
+        HandlerRegistry::insert(
+            &self.handlers,
+            &self.handler_metrics,
+            E::discriminator(),
+            handler,
+            None,
+        )
+    }
+
+    /// Removes every handler of events with `discriminator`, returning
+    /// whether any was registered. Such events are then treated like any
+    /// event without a handler.
+    pub fn unregister_handler(&self, discriminator: [u8; 8]) -> bool {
+        let removed = self
+            .handlers
+            .write()
+            .ok()
+            .and_then(|mut handlers| handlers.remove(&discriminator))
+            .map_or(0, |removed| removed.len());
+        for _ in 0..removed {
+            self.handler_metrics.dec_registered();
+        }
+        removed > 0
+    }
+
+    /// Returns whether a handler is registered for `discriminator`.
+    #[must_use]
+    pub fn has_handler(&self, discriminator: &[u8; 8]) -> bool {
+        self.handlers
+            .read()
+            .is_ok_and(|handlers| handlers.contains_key(discriminator))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::{calculate_discriminator, TransferEvent};
+    use crate::types::metadata::TxMetadata;
+    use async_trait::async_trait;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_transaction_status::{UiCompiledInstruction, UiInstruction};
+    use sqlx::PgPool;
+
+    struct AnyTransfer;
+
+    impl InstructionDecoder<TransferEvent> for AnyTransfer {
+        fn decode(&self, _: &UiInstruction) -> Option<TransferEvent> {
+            Some(TransferEvent {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount: 1,
+            })
+        }
+    }
+
+    struct Ignore;
+
+    #[async_trait]
+    impl EventHandler<TransferEvent> for Ignore {
+        async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registrations_reach_the_shared_registries() -> Result<()> {
+        let decoders = DecoderRegistry::new();
+        let handlers = HandlerRegistry::new();
+        let handle = RegistryHandle::new(&decoders, &handlers);
+        let program = Pubkey::new_unique();
+        let transfer = calculate_discriminator("TransferEvent");
+
+        let instruction = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: 0,
+            accounts: vec![],
+            data: String::new(),
+            stack_height: None,
+        });
+        let accounts = vec![crate::types::metadata::TransactionAccount {
+            pubkey: program,
+            signer: false,
+            writable: false,
+        }];
+        let decode = || {
+            decoders.decode_with_accounts(
+                std::slice::from_ref(&instruction),
+                &[],
+                accounts.clone(),
+                &super::super::ProgramScope::all(),
+            )
+        };
+        assert!(decode().is_empty());
+
+        handle.register_decoder(&program.to_string(), AnyTransfer)?;
+        handle.register_handler(Ignore)?;
+        assert_eq!(decode().len(), 1);
+        assert!(handlers.contains(&transfer));
+
+        assert_eq!(handle.unregister_decoders(&program.to_string())?, 1);
+        assert!(handle.unregister_handler(transfer));
+        assert!(!handle.unregister_handler(transfer));
+        assert!(decode().is_empty());
+        assert!(handlers.is_empty());
+        assert_eq!(
+            handlers
+                .metrics()
+                .registered_count
+                .load(std::sync::atomic::Ordering::Relaxed),
+            0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unregister_decoders_counts_every_decoder() -> Result<()> {
+        let decoders = DecoderRegistry::new();
+        let handlers = HandlerRegistry::new();
+        let handle = RegistryHandle::new(&decoders, &handlers);
+        let program = Pubkey::new_unique().to_string();
+        let registered = || {
+            decoders
+                .metrics()
+                .registered_count
+                .load(std::sync::atomic::Ordering::Relaxed)
+        };
+
+        handle.register_decoder(&program, AnyTransfer)?;
+        handle.register_decoder(&program, AnyTransfer)?;
+        assert_eq!(registered(), 2);
+
+        assert_eq!(handle.unregister_decoders(&program)?, 1);
+        assert_eq!(registered(), 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+//! Per-handler concurrency and rate limits.
+//!
+//! Handlers that enrich events through an external API, such as a price
+//! feed or a token metadata service, are called once per event from every
+//! indexer worker at once. `RateLimitedHandler` caps how many calls of one
+//! handler are in flight and how many start per second, so a busy slot
+//! neither gets the handler throttled nor overwhelms the service, and
+//! `SolanaIndexer::register_handler_with_rate_limit` registers a handler
+//! behind such a limit in one call:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{EventHandler, RateLimit, Result, SolanaIndexer, TransferEvent};
+//! # fn example(indexer: &mut SolanaIndexer, price_handler: impl EventHandler<TransferEvent> + 'static) -> Result<()> {
+//! // At most 4 requests in flight and 10 per second
+//! indexer.register_handler_with_rate_limit(
+//!     price_handler,
+//!     RateLimit::new().max_in_flight(4).max_per_second(10),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Calls over the limit wait for their turn rather than failing, and every
+//! retry of a failed call waits again. The limit belongs to one handler:
+//! other handlers of the same event type run unthrottled.
+
+use crate::types::metadata::TxMetadata;
+use crate::types::schema::TableSchema;
+use crate::types::traits::EventHandler;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::Instant;
+
+/// Limits on the calls of one handler.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RateLimit {
+    max_in_flight: Option<usize>,
+    max_per_second: Option<u32>,
+}
+
+impl RateLimit {
+    /// Creates a limit that allows every call.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows at most `max` calls to run at once.
+    #[must_use]
+    pub fn max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max.max(1));
+        self
+    }
+
+    /// Starts at most `max` calls per second, spaced evenly.
+    #[must_use]
+    pub fn max_per_second(mut self, max: u32) -> Self {
+        self.max_per_second = Some(max.max(1));
+        self
+    }
+
+    /// Returns the time between the starts of two calls, if limited.
+    fn interval(self) -> Option<Duration> {
+        self.max_per_second.map(|max| Duration::from_secs(1) / max)
+    }
+}
+
+/// Event handler wrapper that holds calls back to stay within a
+/// `RateLimit`.
+pub struct RateLimitedHandler<H> {
+    handler: H,
+    in_flight: Option<Semaphore>,
+    interval: Option<Duration>,
+    next_start: Mutex<Option<Instant>>,
+    delayed: AtomicU64,
+}
+
+impl<H> RateLimitedHandler<H> {
+    /// Wraps `handler`, calling it within `limit`.
+    pub fn new(handler: H, limit: RateLimit) -> Self {
+        Self {
+            handler,
+            in_flight: limit.max_in_flight.map(Semaphore::new),
+            interval: limit.interval(),
+            next_start: Mutex::new(None),
+            delayed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of calls that had to wait for their turn.
+    pub fn delayed(&self) -> u64 {
+        self.delayed.load(Ordering::Relaxed)
+    }
+
+    /// Waits until the next call may start under the per-second limit, and
+    /// reserves the start after it. Returns whether the call had to wait.
+    async fn pace(&self) -> bool {
+        let Some(interval) = self.interval else {
+            return false;
+        };
+        let start = {
+            let mut next_start = self.next_start.lock().await;
+            let now = Instant::now();
+            let start = next_start.map_or(now, |next| next.max(now));
+            *next_start = Some(start + interval);
+            start
+        };
+        if start <= Instant::now() {
+            return false;
+        }
+        tokio::time::sleep_until(start).await;
+        true
+    }
+}
+
+#[async_trait]
+impl<H, E> EventHandler<E> for RateLimitedHandler<H>
+where
+    H: EventHandler<E>,
+    E: Send + Sync + 'static,
+{
+    async fn handle(&self, event: E, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        let mut waited = false;
+        let _permit = match &self.in_flight {
+            Some(semaphore) => {
+                waited = semaphore.available_permits() == 0;
+                Some(semaphore.acquire().await.map_err(|_| {
+                    SolanaIndexerError::Internal("Rate limit semaphore closed".to_string())
+                })?)
+            }
+            None => None,
+        };
+        if self.pace().await || waited {
+            self.delayed.fetch_add(1, Ordering::Relaxed);
+        }
+        self.handler.handle(event, context, db).await
+    }
+
+    async fn on_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        self.handler.on_rollback(context, db).await
+    }
+
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
+        self.handler.initialize_schema(pool).await
+    }
+
+    fn output_schema(&self) -> Vec<TableSchema> {
+        self.handler.output_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+
+    /// Records the most calls seen running at once.
+    #[derive(Default)]
+    struct Peak {
+        running: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EventHandler<TransferEvent> for Arc<Peak> {
+        async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(25)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_limits_concurrency_and_rate() -> Result<()> {
+        let peak = Arc::new(Peak::default());
+        let limited = Arc::new(RateLimitedHandler::new(
+            peak.clone(),
+            RateLimit::new().max_in_flight(2).max_per_second(100),
+        ));
+        // Never connects: the handler does not touch the pool
+        let pool = PgPool::connect_lazy("postgresql://localhost/unused")?;
+        let context = TxMetadata {
+            slot: 1,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        let started = Instant::now();
+        let calls = (0..5).map(|amount| {
+            let limited = limited.clone();
+            let pool = pool.clone();
+            let context = context.clone();
+            tokio::spawn(async move {
+                let event = TransferEvent {
+                    from: "a".to_string(),
+                    to: "b".to_string(),
+                    amount,
+                };
+                limited.handle(event, &context, &pool).await
+            })
+        });
+        for call in futures_util::future::join_all(calls).await {
+            call.unwrap()?;
+        }
+
+        assert_eq!(peak.peak.load(Ordering::SeqCst), 2);
+        // Five starts at least 10ms apart
+        assert!(started.elapsed() >= Duration::from_millis(40));
+        assert_eq!(limited.delayed(), 4);
+        Ok(())
+    }
+}
@@ -5,10 +5,13 @@
 //! logs and events dynamically.
 
 use crate::config::RegistryConfig;
+use crate::core::registry::chain::{ChainControl, DecoderChain};
 use crate::core::registry::metrics::RegistryMetrics;
-use crate::types::events::ParsedEvent;
+use crate::core::registry::ProgramScope;
+use crate::types::events::{EventType, ParsedEvent};
 use crate::types::traits::DynamicLogDecoder;
 use crate::utils::error::{Result, SolanaIndexerError};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use std::collections::HashMap;
 
 /// Registry for managing log decoders by program ID.
@@ -17,7 +20,7 @@ use std::collections::HashMap;
 /// When processing a transaction, the registry routes log events to the
 /// appropriate decoders based on the program ID that emitted the log.
 pub struct LogDecoderRegistry {
-    decoders: HashMap<String, Vec<Box<dyn DynamicLogDecoder>>>,
+    decoders: HashMap<String, DecoderChain<dyn DynamicLogDecoder>>,
     metrics: RegistryMetrics,
 }
 
@@ -39,14 +42,17 @@ impl LogDecoderRegistry {
     pub fn new_bounded(config: &RegistryConfig) -> Self {
         Self {
             decoders: HashMap::new(),
-            metrics: RegistryMetrics::new("LogDecoder", config.max_log_decoder_programs),
+            metrics: RegistryMetrics::new("LogDecoder", config.max_log_decoder_programs)
+                .with_miss_sampling(config.decode_miss_sample_every),
         }
     }
 
     /// Registers a log decoder for a specific program ID.
     ///
     /// This method associates a decoder with a program ID. Multiple decoders
-    /// can be registered for the same program ID; they will be tried in order.
+    /// can be registered for the same program ID; they will be tried in order,
+    /// after any registered with a higher priority (see
+    /// `register_with_priority`).
     ///
     /// # Arguments
     ///
@@ -56,6 +62,24 @@ impl LogDecoderRegistry {
         &mut self,
         program_id: String,
         decoder: Box<dyn DynamicLogDecoder>,
+    ) -> Result<()> {
+        self.register_with_priority(program_id, decoder, 0, ChainControl::Stop)
+    }
+
+    /// Registers a log decoder in the program's decoder chain (see
+    /// `registry::chain`): it runs before decoders of lower `priority`, and
+    /// `control` decides whether they still run once it accepts an event.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the registry
+    /// is full and a new program ID is being added.
+    pub fn register_with_priority(
+        &mut self,
+        program_id: String,
+        decoder: Box<dyn DynamicLogDecoder>,
+        priority: i32,
+        control: ChainControl,
     ) -> Result<()> {
         if !self.decoders.contains_key(&program_id) && self.metrics.is_full() {
             return Err(SolanaIndexerError::RegistryCapacityExceeded(format!(
@@ -64,7 +88,10 @@ impl LogDecoderRegistry {
             )));
         }
 
-        self.decoders.entry(program_id).or_default().push(decoder);
+        self.decoders
+            .entry(program_id)
+            .or_default()
+            .insert(decoder, priority, control);
         self.metrics.inc_registered();
         Ok(())
     }
@@ -73,30 +100,43 @@ impl LogDecoderRegistry {
     ///
     /// This method iterates through the provided events and attempts to decode
     /// them using the decoders registered for their respective programs.
+    /// Events emitted by programs outside `scope` are skipped.
     ///
     /// # Arguments
     ///
     /// * `events` - A slice of `ParsedEvent`s to decode.
+    /// * `scope` - The programs whose events are decoded.
     ///
     /// # Returns
     ///
     /// A vector of decoded event data tuples: `(discriminator, data)`.
     #[must_use]
-    pub fn decode_logs(&self, events: &[ParsedEvent]) -> Vec<([u8; 8], Vec<u8>)> {
+    pub fn decode_events(
+        &self,
+        events: &[ParsedEvent],
+        scope: &ProgramScope,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
         let mut decoded_events = Vec::new();
 
         for event in events {
             self.metrics.inc_calls();
             if let Some(program_id) = &event.program_id {
                 let program_id_str = program_id.to_string();
+                if !scope.includes(&program_id_str) {
+                    continue;
+                }
 
                 if let Some(decoders) = self.decoders.get(&program_id_str) {
-                    for decoder in decoders {
-                        if let Some(decoded) = decoder.decode_log_dynamic(event) {
-                            decoded_events.push(decoded);
-                            self.metrics.inc_hits();
-                            break;
-                        }
+                    let decoded_any = decoders.run(
+                        |decoder| decoder.decode_log_dynamic(event),
+                        &mut decoded_events,
+                    );
+                    if decoded_any {
+                        self.metrics.inc_hits();
+                    }
+
+                    if !decoded_any && self.metrics.samples_misses() {
+                        self.record_miss(&program_id_str, event, decoders);
                     }
                 }
             }
@@ -105,6 +145,28 @@ impl LogDecoderRegistry {
         decoded_events
     }
 
+    /// Records a decode miss for a `Program data:` event no decoder accepted.
+    fn record_miss(
+        &self,
+        program_id: &str,
+        event: &ParsedEvent,
+        decoders: &DecoderChain<dyn DynamicLogDecoder>,
+    ) {
+        if event.event_type != EventType::ProgramData {
+            return;
+        }
+        let Some(data) = event.data.as_deref().and_then(|d| BASE64.decode(d).ok()) else {
+            return;
+        };
+        self.metrics.record_miss(
+            program_id,
+            &data,
+            decoders
+                .decoders()
+                .filter_map(DynamicLogDecoder::discriminator),
+        );
+    }
+
     /// Returns the metrics for this registry.
     pub fn metrics(&self) -> &RegistryMetrics {
         &self.metrics
@@ -120,7 +182,6 @@ impl Default for LogDecoderRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::events::{EventType, ParsedEvent};
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
 
@@ -136,6 +197,10 @@ mod tests {
                 None
             }
         }
+
+        fn discriminator(&self) -> Option<[u8; 8]> {
+            Some([1; 8])
+        }
     }
 
     #[test]
@@ -172,7 +237,7 @@ mod tests {
         };
 
         // Test successful decoding
-        let results = registry.decode_logs(std::slice::from_ref(&event));
+        let results = registry.decode_events(std::slice::from_ref(&event), &ProgramScope::all());
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].1, vec![1, 2, 3]);
 
@@ -184,7 +249,7 @@ mod tests {
                 should_decode: false,
             }),
         )?;
-        let results = registry.decode_logs(std::slice::from_ref(&event));
+        let results = registry.decode_events(std::slice::from_ref(&event), &ProgramScope::all());
         assert!(results.is_empty());
         Ok(())
     }
@@ -208,8 +273,47 @@ mod tests {
             data: Some("test log".to_string()),
         };
 
-        let results = registry.decode_logs(std::slice::from_ref(&event));
+        let results = registry.decode_events(std::slice::from_ref(&event), &ProgramScope::all());
         assert!(results.is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_decode_miss_sampling() -> Result<()> {
+        let mut registry = LogDecoderRegistry::new_bounded(&RegistryConfig {
+            decode_miss_sample_every: 1,
+            ..RegistryConfig::default()
+        });
+        let program_id_str = "11111111111111111111111111111111";
+        registry.register(
+            program_id_str.to_string(),
+            Box::new(MockLogDecoder {
+                should_decode: false,
+            }),
+        )?;
+
+        let program_id = Pubkey::from_str(program_id_str)?;
+        let event_with_data = |bytes: &[u8]| ParsedEvent {
+            event_type: EventType::ProgramData,
+            program_id: Some(program_id),
+            data: Some(BASE64.encode(bytes)),
+        };
+        let events = [
+            event_with_data(&[1, 1, 1, 1, 1, 1, 1, 1, 42]),
+            event_with_data(&[2; 9]),
+        ];
+
+        assert!(registry
+            .decode_events(&events, &ProgramScope::all())
+            .is_empty());
+        // Only the event carrying the decoder's discriminator counts as a miss.
+        assert_eq!(
+            registry
+                .metrics()
+                .decode_misses
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+        Ok(())
+    }
 }
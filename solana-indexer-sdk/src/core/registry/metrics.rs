@@ -2,10 +2,14 @@
 //!
 //! This module provides the `RegistryMetrics` struct which tracks usage statistics
 //! for registries, including the number of registered items, decode calls, and
-//! cache hits. It also enforces capacity limits.
+//! cache hits. It also enforces capacity limits and emits sampled debug
+//! records for decode misses.
 
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
+/// Number of leading data bytes included in a decode miss record.
+const MISS_PREFIX_LEN: usize = 16;
+
 /// Metrics and capacity tracking for a registry.
 #[doc(hidden)]
 #[derive(Debug)]
@@ -16,6 +20,14 @@ pub struct RegistryMetrics {
     pub decode_calls: AtomicU64,
     /// Total number of successful decodes/hits.
     pub decode_hits: AtomicU64,
+    /// Total number of decode misses (see `record_miss`).
+    pub decode_misses: AtomicU64,
+    /// Total number of items rejected by a size guardrail.
+    pub rejected: AtomicU64,
+    /// Total number of fetched transactions whose `meta` was missing.
+    pub missing_meta: AtomicU64,
+    /// Emit a debug record for one in every N misses (0 = disabled).
+    pub miss_sample_every: u64,
     /// Maximum capacity of the registry (0 = unlimited).
     pub capacity_limit: usize,
     /// Name of the registry for logging.
@@ -34,11 +46,79 @@ impl RegistryMetrics {
             registered_count: AtomicUsize::new(0),
             decode_calls: AtomicU64::new(0),
             decode_hits: AtomicU64::new(0),
+            decode_misses: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            missing_meta: AtomicU64::new(0),
+            miss_sample_every: 0,
             capacity_limit,
             name,
         }
     }
 
+    /// Enables sampled debug records for decode misses.
+    ///
+    /// # Arguments
+    ///
+    /// * `every` - Emit a record for one in every `every` misses (0 disables sampling)
+    #[must_use]
+    pub fn with_miss_sampling(mut self, every: u64) -> Self {
+        self.miss_sample_every = every;
+        self
+    }
+
+    /// Returns `true` if decode misses are being sampled.
+    ///
+    /// Registries check this before extracting raw bytes so that the common
+    /// path does no extra work when sampling is disabled.
+    #[must_use]
+    pub fn samples_misses(&self) -> bool {
+        self.miss_sample_every > 0
+    }
+
+    /// Records a decode miss for `data` addressed to `program_id`.
+    ///
+    /// Only data starting with one of `prefixes`, the discriminators of the
+    /// registered decoders, counts as a miss: that is data a decoder claims
+    /// to handle but returned `None` for.
+    /// Sampled misses are logged at debug level with the program, the leading
+    /// bytes (hex) and the total length.
+    pub fn record_miss(
+        &self,
+        program_id: &str,
+        data: &[u8],
+        prefixes: impl IntoIterator<Item = impl AsRef<[u8]>>,
+    ) {
+        if !self.samples_misses() {
+            return;
+        }
+        let matched = prefixes.into_iter().any(|prefix| {
+            let prefix = prefix.as_ref();
+            !prefix.is_empty() && data.starts_with(prefix)
+        });
+        if !matched {
+            return;
+        }
+
+        let misses = self.decode_misses.fetch_add(1, Ordering::Relaxed);
+        if misses % self.miss_sample_every != 0 {
+            return;
+        }
+
+        let prefix: String = data
+            .iter()
+            .take(MISS_PREFIX_LEN)
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        tracing::debug!(
+            registry = self.name,
+            program_id,
+            prefix = %prefix,
+            len = data.len(),
+            misses = misses + 1,
+            "Decoder returned None for data matching its program and discriminator"
+        );
+    }
+
     /// Checks if the registry is at capacity.
     ///
     /// # Returns
@@ -58,6 +138,15 @@ impl RegistryMetrics {
         self.registered_count.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Decrements the registered count, stopping at zero.
+    pub fn dec_registered(&self) {
+        let _ = self
+            .registered_count
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| {
+                count.checked_sub(1)
+            });
+    }
+
     /// Increments the decode call count.
     pub fn inc_calls(&self) {
         self.decode_calls.fetch_add(1, Ordering::Relaxed);
@@ -68,11 +157,24 @@ impl RegistryMetrics {
         self.decode_hits.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Increments the guardrail rejection count.
+    pub fn inc_rejected(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Increments the missing-metadata count.
+    pub fn inc_missing_meta(&self) {
+        self.missing_meta.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Reports current metrics to logs.
     pub fn report(&self) {
         let count = self.registered_count.load(Ordering::Relaxed);
         let calls = self.decode_calls.load(Ordering::Relaxed);
         let hits = self.decode_hits.load(Ordering::Relaxed);
+        let misses = self.decode_misses.load(Ordering::Relaxed);
+        let rejected = self.rejected.load(Ordering::Relaxed);
+        let missing_meta = self.missing_meta.load(Ordering::Relaxed);
 
         let limit_str = if self.capacity_limit == 0 {
             "unlimited".to_string()
@@ -83,9 +185,39 @@ impl RegistryMetrics {
         crate::utils::logging::log(
             crate::utils::logging::LogLevel::Info,
             &format!(
-                "Registry [{}] Stats: {}/{} items | Calls: {} | Hits: {}",
-                self.name, count, limit_str, calls, hits
+                "Registry [{}] Stats: {}/{} items | Calls: {} | Hits: {} | Misses: {} | Rejected: {} | Missing meta: {}",
+                self.name, count, limit_str, calls, hits, misses, rejected, missing_meta
             ),
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_miss_sampling() {
+        let metrics = RegistryMetrics::new("Test", 0).with_miss_sampling(2);
+        let discriminator = [7; 8];
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&[0, 1, 2]);
+
+        for _ in 0..3 {
+            metrics.record_miss("program", &data, [discriminator]);
+        }
+        // Data without a known discriminator prefix is not a miss.
+        metrics.record_miss("program", &[9; 11], [discriminator]);
+
+        assert_eq!(metrics.decode_misses.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_record_miss_disabled() {
+        let metrics = RegistryMetrics::new("Test", 0);
+        assert!(!metrics.samples_misses());
+
+        metrics.record_miss("program", &[7; 8], [[7; 8]]);
+        assert_eq!(metrics.decode_misses.load(Ordering::Relaxed), 0);
+    }
+}
@@ -1,18 +1,44 @@
 pub mod account;
+pub mod chain;
+pub mod filter;
+pub mod handle;
+pub mod limit;
 pub mod logs;
 pub mod metrics;
+pub mod programs;
+pub mod retry;
+pub mod toggles;
 
-use crate::config::RegistryConfig;
+use crate::config::{DuplicatePolicy, RegistryConfig, SolanaIndexerConfig};
+use crate::core::execution::fetcher::account_keys;
+use crate::core::registry::chain::{ChainControl, DecoderChain};
 use crate::core::registry::metrics::RegistryMetrics;
+use crate::core::registry::programs::resolve_program;
+use crate::types::metadata::{InstructionContext, TransactionAccount};
 use crate::types::traits::DynamicInstructionDecoder;
 use crate::utils::error::{Result, SolanaIndexerError};
-use solana_transaction_status::UiInstruction;
-use std::collections::HashMap;
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, UiInnerInstructions, UiInstruction,
+    UiParsedInstruction,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+/// Decoder chains by program ID.
+pub(crate) type DecoderChains = HashMap<Pubkey, DecoderChain<dyn DynamicInstructionDecoder>>;
 
 /// Registry for managing instruction decoders by program ID.
+///
+/// Decoders are registered under a base58 program ID or an RPC program name
+/// such as `system` (see `registry::programs`), and instructions are routed
+/// by the program ID they call, whether the RPC node parsed them or not.
+///
+/// The chains are shared with the registry's `RegistryHandle`s, through
+/// which decoders are added and removed while the indexer runs.
 pub struct DecoderRegistry {
-    decoders: HashMap<String, Vec<Box<dyn DynamicInstructionDecoder>>>,
-    metrics: RegistryMetrics,
+    decoders: Arc<RwLock<DecoderChains>>,
+    metrics: Arc<RegistryMetrics>,
 }
 
 impl DecoderRegistry {
@@ -20,8 +46,8 @@ impl DecoderRegistry {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            decoders: HashMap::new(),
-            metrics: RegistryMetrics::new("InstructionDecoder", 0),
+            decoders: Arc::default(),
+            metrics: Arc::new(RegistryMetrics::new("InstructionDecoder", 0)),
         }
     }
 
@@ -32,54 +58,166 @@ impl DecoderRegistry {
     /// * `config` - Registry configuration containing limits
     pub fn new_bounded(config: &RegistryConfig) -> Self {
         Self {
-            decoders: HashMap::new(),
-            metrics: RegistryMetrics::new("InstructionDecoder", config.max_decoder_programs),
+            decoders: Arc::default(),
+            metrics: Arc::new(
+                RegistryMetrics::new("InstructionDecoder", config.max_decoder_programs)
+                    .with_miss_sampling(config.decode_miss_sample_every),
+            ),
         }
     }
 
-    /// Registers an instruction decoder for a specific program ID.
+    /// Registers an instruction decoder for a program ID or RPC program
+    /// name, with priority 0 and `ChainControl::Stop`.
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full
     /// and a new program ID is being added.
     pub fn register(
         &mut self,
         program_id: String,
         decoder: Box<dyn DynamicInstructionDecoder>,
     ) -> Result<()> {
+        self.register_with_priority(program_id, decoder, 0, ChainControl::Stop)
+    }
+
+    /// Registers an instruction decoder in the program's decoder chain (see
+    /// `registry::chain`): it runs before decoders of lower `priority`, and `control`
+    /// decides whether they still run once it accepts an instruction.
+    ///
+    /// A program name is registered for every program it stands for; the
+    /// decoder is then shared by their chains.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `program_id` is neither a
+    /// program ID nor a known program name, and
+    /// `SolanaIndexerError::RegistryCapacityExceeded` if the registry is full
+    /// and a new program ID is being added.
+    pub fn register_with_priority(
+        &mut self,
+        program_id: String,
+        decoder: Box<dyn DynamicInstructionDecoder>,
+        priority: i32,
+        control: ChainControl,
+    ) -> Result<()> {
+        Self::insert(
+            &self.decoders,
+            &self.metrics,
+            &program_id,
+            Arc::from(decoder),
+            priority,
+            control,
+        )
+    }
+
+    /// Adds `decoder` to the chains of the programs `program_id` stands for.
+    pub(crate) fn insert(
+        decoders: &RwLock<DecoderChains>,
+        metrics: &RegistryMetrics,
+        program_id: &str,
+        decoder: Arc<dyn DynamicInstructionDecoder>,
+        priority: i32,
+        control: ChainControl,
+    ) -> Result<()> {
+        let program_ids = resolve_program(program_id)?;
+        let mut decoders = decoders.write().map_err(|_| {
+            SolanaIndexerError::Internal("DecoderRegistry lock poisoned".to_string())
+        })?;
         // specific check: if key doesn't exist and we are full, error
-        if !self.decoders.contains_key(&program_id) && self.metrics.is_full() {
+        if program_ids.iter().any(|id| !decoders.contains_key(id)) && metrics.is_full() {
             return Err(SolanaIndexerError::RegistryCapacityExceeded(format!(
                 "InstructionDecoder registry full (limit: {})",
-                self.metrics.capacity_limit
+                metrics.capacity_limit
             )));
         }
 
-        self.decoders.entry(program_id).or_default().push(decoder);
-        self.metrics.inc_registered();
+        for program_id in program_ids {
+            decoders
+                .entry(program_id)
+                .or_default()
+                .insert(decoder.clone(), priority, control);
+        }
+        metrics.inc_registered();
         Ok(())
     }
 
+    /// Returns the chains and metrics shared with `RegistryHandle`s.
+    pub(crate) fn shared(&self) -> (Arc<RwLock<DecoderChains>>, Arc<RegistryMetrics>) {
+        (self.decoders.clone(), self.metrics.clone())
+    }
+
     /// Decodes all instructions in a transaction.
     #[must_use]
     pub fn decode_transaction(&self, instructions: &[UiInstruction]) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_instructions(instructions, &ProgramScope::all())
+    }
+
+    /// Decodes the instructions in a transaction that call a program in
+    /// `scope`.
+    #[must_use]
+    pub fn decode_instructions(
+        &self,
+        instructions: &[UiInstruction],
+        scope: &ProgramScope,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_with_inner(instructions, &[], scope)
+    }
+
+    /// Decodes the instructions in a transaction along with the inner
+    /// instructions (CPIs) recorded in its `meta.inner_instructions`.
+    ///
+    /// Events come out in execution order: each top-level instruction is
+    /// followed by the instructions it invoked. Decoders see where each
+    /// instruction sits through `InstructionContext`.
+    #[must_use]
+    pub fn decode_with_inner(
+        &self,
+        instructions: &[UiInstruction],
+        inner_instructions: &[UiInnerInstructions],
+        scope: &ProgramScope,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_with_accounts(instructions, inner_instructions, Vec::new(), scope)
+    }
+
+    /// Like `decode_with_inner`, with the transaction's accounts, so
+    /// compiled instructions are routed by their resolved program ID and
+    /// decoders can resolve their account indices through
+    /// `InstructionContext`.
+    #[must_use]
+    pub fn decode_with_accounts(
+        &self,
+        instructions: &[UiInstruction],
+        inner_instructions: &[UiInnerInstructions],
+        account_keys: Vec<TransactionAccount>,
+        scope: &ProgramScope,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
         let mut events = Vec::new();
+        // One context for the whole transaction, updated per instruction
+        let mut context = InstructionContext {
+            account_keys,
+            ..InstructionContext::default()
+        };
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            context.index = index;
+            context.parent_index = None;
+            context.stack_height = Some(stack_height(instruction).unwrap_or(1));
+            context.program_id = context.program_id_of(instruction);
+            self.decode_instruction(instruction, &context, scope, &mut events);
 
-        for instruction in instructions {
-            // Count every instruction processed as a "call" opportunity
-            self.metrics.inc_calls();
-
-            #[allow(clippy::collapsible_if)]
-            if let Some(program_id) = Self::extract_program_id(instruction) {
-                if let Some(decoders) = self.decoders.get(&program_id) {
-                    for decoder in decoders {
-                        if let Some(event) = decoder.decode_dynamic(instruction) {
-                            events.push(event);
-                            self.metrics.inc_hits();
-                            break;
-                        }
-                    }
+            for inner in inner_instructions
+                .iter()
+                .filter(|inner| usize::from(inner.index) == index)
+            {
+                for (inner_index, instruction) in inner.instructions.iter().enumerate() {
+                    context.index = inner_index;
+                    context.parent_index = Some(index);
+                    context.stack_height = stack_height(instruction);
+                    context.program_id = context.program_id_of(instruction);
+                    self.decode_instruction(instruction, &context, scope, &mut events);
                 }
             }
         }
@@ -87,20 +225,78 @@ impl DecoderRegistry {
         events
     }
 
-    fn extract_program_id(instruction: &UiInstruction) -> Option<String> {
-        match instruction {
-            UiInstruction::Parsed(parsed) => match parsed {
-                solana_transaction_status::UiParsedInstruction::Parsed(p) => {
-                    Some(p.program.clone())
-                }
-                solana_transaction_status::UiParsedInstruction::PartiallyDecoded(p) => {
-                    Some(p.program_id.clone())
-                }
-            },
-            UiInstruction::Compiled(_) => None,
+    /// Runs the decoder chain of the instruction's program.
+    fn decode_instruction(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+        scope: &ProgramScope,
+        events: &mut Vec<([u8; 8], Vec<u8>)>,
+    ) {
+        // Count every instruction processed as a "call" opportunity
+        self.metrics.inc_calls();
+
+        let Some(program_id) = context.program_id else {
+            return;
+        };
+        // Compiled instructions only name their program by account index
+        let compiled_program_id = match instruction {
+            UiInstruction::Compiled(_) => Some(program_id.to_string()),
+            UiInstruction::Parsed(_) => None,
+        };
+        let called = compiled_program_id
+            .as_deref()
+            .unwrap_or_else(|| instruction_program_id(instruction));
+        if !scope.includes(called) {
+            return;
+        }
+        let Ok(chains) = self.decoders.read() else {
+            return;
+        };
+        let Some(decoders) = chains.get(&program_id) else {
+            return;
+        };
+
+        if decoders.run(
+            |decoder| decoder.decode_dynamic_many(instruction, context),
+            events,
+        ) {
+            self.metrics.inc_hits();
+            return;
+        }
+
+        if self.metrics.samples_misses() {
+            self.record_miss(&program_id.to_string(), instruction, decoders);
         }
     }
 
+    /// Records a decode miss for raw instruction data no decoder accepted,
+    /// if it starts with the instruction prefix of one of `decoders`.
+    ///
+    /// Instructions the RPC node already parsed carry no raw bytes and are skipped.
+    fn record_miss(
+        &self,
+        program_id: &str,
+        instruction: &UiInstruction,
+        decoders: &DecoderChain<dyn DynamicInstructionDecoder>,
+    ) {
+        let data = match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => &partial.data,
+            UiInstruction::Compiled(compiled) => &compiled.data,
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return,
+        };
+        let Ok(data) = bs58::decode(data).into_vec() else {
+            return;
+        };
+        self.metrics.record_miss(
+            program_id,
+            &data,
+            decoders
+                .decoders()
+                .flat_map(DynamicInstructionDecoder::instruction_prefixes),
+        );
+    }
+
     /// Returns the metrics for this registry.
     pub fn metrics(&self) -> &RegistryMetrics {
         &self.metrics
@@ -111,3 +307,298 @@ impl Default for DecoderRegistry {
         Self::new()
     }
 }
+
+/// Returns the program ID an instruction names; compiled instructions only
+/// name it by account index, and give an empty string.
+fn instruction_program_id(instruction: &UiInstruction) -> &str {
+    match instruction {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(p)) => &p.program_id,
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(p)) => &p.program_id,
+        UiInstruction::Compiled(_) => "",
+    }
+}
+
+/// Programs whose instructions and logs a decode pass covers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramScope {
+    only: Option<String>,
+    excluded: HashSet<String>,
+}
+
+impl ProgramScope {
+    /// Covers every program.
+    #[must_use]
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Covers `program_id` alone.
+    #[must_use]
+    pub fn only(program_id: impl Into<String>) -> Self {
+        Self {
+            only: Some(program_id.into()),
+            excluded: HashSet::new(),
+        }
+    }
+
+    /// Leaves `program_id` out.
+    #[must_use]
+    pub fn exclude(mut self, program_id: impl Into<String>) -> Self {
+        self.excluded.insert(program_id.into());
+        self
+    }
+
+    /// Returns `true` if the scope covers `program_id`.
+    #[must_use]
+    pub fn includes(&self, program_id: &str) -> bool {
+        self.only.as_deref().map_or(true, |only| only == program_id)
+            && !self.excluded.contains(program_id)
+    }
+
+    /// Returns the scope of one transaction: the programs left by the
+    /// duplicate policy, minus those whose companion programs the
+    /// transaction does not include.
+    pub(crate) fn for_transaction(
+        config: &SolanaIndexerConfig,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        instructions: &[UiInstruction],
+    ) -> Self {
+        Self::scoped(
+            config,
+            first_called_program(&config.program_ids, instructions),
+            &account_keys(transaction),
+        )
+    }
+
+    /// Returns the scope of a transaction known only by its logs, such as a
+    /// `logsSubscribe` notification. Top-level invocations stand in for its
+    /// instructions, and every invoked program for its account keys.
+    #[cfg(any(feature = "websockets", feature = "helius"))]
+    pub(crate) fn for_logs(config: &SolanaIndexerConfig, logs: &[String]) -> Self {
+        let invoked = invocations(logs).collect::<Vec<_>>();
+        let first_called = config.program_ids.iter().find(|program_id| {
+            let program_id = program_id.to_string();
+            invoked
+                .iter()
+                .any(|&(invoked, depth)| depth == 1 && invoked == program_id)
+        });
+        let keys = invoked
+            .iter()
+            .map(|&(program_id, _)| program_id)
+            .collect::<Vec<_>>();
+        Self::scoped(config, first_called, &keys)
+    }
+
+    /// Applies the duplicate policy to `first_called` and the companion
+    /// programs to `keys`.
+    fn scoped(config: &SolanaIndexerConfig, first_called: Option<&Pubkey>, keys: &[&str]) -> Self {
+        let mut scope = match config.duplicate_policy {
+            DuplicatePolicy::AllPrograms => Self::all(),
+            DuplicatePolicy::FirstRegistration => {
+                first_called.map_or_else(Self::all, |program_id| Self::only(program_id.to_string()))
+            }
+        };
+        for (program_id, companions) in &config.companion_programs {
+            if !companions
+                .iter()
+                .any(|companion| keys.contains(&companion.to_string().as_str()))
+            {
+                scope = scope.exclude(program_id.to_string());
+            }
+        }
+        scope
+    }
+}
+
+/// Returns the program and depth of every `Program <id> invoke [<depth>]` log.
+#[cfg(any(feature = "websockets", feature = "helius"))]
+fn invocations(logs: &[String]) -> impl Iterator<Item = (&str, u32)> {
+    logs.iter().filter_map(|log| {
+        let (program_id, depth) = log.strip_prefix("Program ")?.split_once(" invoke [")?;
+        Some((program_id, depth.strip_suffix(']')?.parse().ok()?))
+    })
+}
+
+/// Returns the invocation depth the RPC node reported for an instruction.
+fn stack_height(instruction: &UiInstruction) -> Option<u32> {
+    match instruction {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(p)) => p.stack_height,
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(p)) => p.stack_height,
+        UiInstruction::Compiled(c) => c.stack_height,
+    }
+}
+
+/// Returns the first of `program_ids` that a top-level instruction calls.
+pub(crate) fn first_called_program<'a>(
+    program_ids: &'a [Pubkey],
+    instructions: &[UiInstruction],
+) -> Option<&'a Pubkey> {
+    program_ids.iter().find(|program_id| {
+        let program_id = program_id.to_string();
+        instructions
+            .iter()
+            .any(|instruction| instruction_program_id(instruction) == program_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::UiPartiallyDecodedInstruction;
+
+    fn call(program_id: &Pubkey) -> UiInstruction {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: program_id.to_string(),
+                accounts: Vec::new(),
+                data: String::new(),
+                stack_height: None,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_first_called_program_follows_configured_order() {
+        let (first, second, unrelated) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let configured = [first, second];
+
+        let instructions = [call(&unrelated), call(&second), call(&first)];
+        assert_eq!(
+            first_called_program(&configured, &instructions),
+            Some(&first)
+        );
+        assert_eq!(
+            first_called_program(&configured, &[call(&second)]),
+            Some(&second)
+        );
+        assert_eq!(first_called_program(&configured, &[call(&unrelated)]), None);
+    }
+
+    fn transaction_with_keys(keys: &[Pubkey]) -> EncodedConfirmedTransactionWithStatusMeta {
+        serde_json::from_value(serde_json::json!({
+            "slot": 1,
+            "transaction": {
+                "signatures": [],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 0
+                    },
+                    "accountKeys": keys.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                    "recentBlockhash": "",
+                    "instructions": []
+                }
+            },
+            "blockTime": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_companion_programs_scope_transactions() -> Result<()> {
+        let (token, protocol, other) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let config = crate::config::SolanaIndexerConfigBuilder::new()
+            .with_rpc("http://127.0.0.1:8899")
+            .with_database("postgresql://localhost/db")
+            .program_id(token.to_string())
+            .with_companion_program(token.to_string(), protocol.to_string())
+            .build()?;
+
+        let inside = transaction_with_keys(&[other, token, protocol]);
+        let scope = ProgramScope::for_transaction(&config, &inside, &[]);
+        assert!(scope.includes(&token.to_string()));
+
+        let outside = transaction_with_keys(&[other, token]);
+        let scope = ProgramScope::for_transaction(&config, &outside, &[]);
+        assert!(!scope.includes(&token.to_string()));
+        assert!(scope.includes(&other.to_string()));
+        Ok(())
+    }
+    #[cfg(any(feature = "websockets", feature = "helius"))]
+    #[test]
+    fn test_companion_programs_scope_logs() -> Result<()> {
+        let (token, protocol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let config = crate::config::SolanaIndexerConfigBuilder::new()
+            .with_rpc("http://127.0.0.1:8899")
+            .with_database("postgresql://localhost/db")
+            .program_id(token.to_string())
+            .with_companion_program(token.to_string(), protocol.to_string())
+            .build()?;
+
+        let inside = [
+            format!("Program {protocol} invoke [1]"),
+            format!("Program {token} invoke [2]"),
+            format!("Program {token} success"),
+            format!("Program {protocol} success"),
+        ];
+        assert!(ProgramScope::for_logs(&config, &inside).includes(&token.to_string()));
+
+        let outside = [
+            format!("Program {token} invoke [1]"),
+            format!("Program {token} success"),
+        ];
+        assert!(!ProgramScope::for_logs(&config, &outside).includes(&token.to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_miss_matches_instruction_sighash() -> Result<()> {
+        use crate::core::decoding::discriminator::{DiscriminatorStrategy, InstructionRouter};
+        use crate::types::events::EventDiscriminator;
+
+        #[derive(borsh::BorshDeserialize)]
+        struct SwapArgs {
+            _amount: u64,
+        }
+        impl EventDiscriminator for SwapArgs {
+            fn discriminator() -> [u8; 8] {
+                *b"swap_evt"
+            }
+        }
+
+        let program = Pubkey::new_unique();
+        let mut registry = DecoderRegistry::new_bounded(&RegistryConfig {
+            decode_miss_sample_every: 1,
+            ..RegistryConfig::default()
+        });
+        let router = InstructionRouter::new(DiscriminatorStrategy::AnchorInstruction)
+            .with_instruction::<SwapArgs>("swap")?;
+        registry.register(program.to_string(), Box::new(router))?;
+
+        let with_data = |data: &[u8]| {
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+                UiPartiallyDecodedInstruction {
+                    program_id: program.to_string(),
+                    accounts: Vec::new(),
+                    data: bs58::encode(data).into_string(),
+                    stack_height: None,
+                },
+            ))
+        };
+        let sighash = DiscriminatorStrategy::AnchorInstruction
+            .for_name("swap")
+            .unwrap_or_default();
+        // A `swap` call with truncated arguments, and another instruction
+        let truncated = with_data(&[&sighash[..], &[1, 2]].concat());
+        let other = with_data(&[&[0; 8][..], &7u64.to_le_bytes()].concat());
+
+        assert!(registry.decode_transaction(&[truncated, other]).is_empty());
+        assert_eq!(
+            registry
+                .metrics()
+                .decode_misses
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! Program IDs behind the names of RPC-parsed instructions.
+//!
+//! With `jsonParsed` encoding, the RPC node parses the instructions of the
+//! programs it knows and labels them with a program name such as `system`
+//! or `spl-token`, next to their program ID. The decoder registry is keyed
+//! by program ID; a decoder registered under one of these names is
+//! registered for every program the name stands for, so it sees both the
+//! parsed instructions and the raw ones of those programs.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use solana_sdk::pubkey::Pubkey;
+
+/// The program names the RPC node gives parsed instructions, and the
+/// programs each stands for.
+pub const PROGRAM_ALIASES: &[(&str, &[&str])] = &[
+    (
+        "address-lookup-table",
+        &["AddressLookupTab1e1111111111111111111111111"],
+    ),
+    (
+        "bpf-loader",
+        &["BPFLoader2111111111111111111111111111111111"],
+    ),
+    (
+        "bpf-upgradeable-loader",
+        &["BPFLoaderUpgradeab1e11111111111111111111111"],
+    ),
+    (
+        "spl-associated-token-account",
+        &["ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"],
+    ),
+    (
+        "spl-memo",
+        &[
+            "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo",
+            "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+        ],
+    ),
+    (
+        "spl-token",
+        &[
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+        ],
+    ),
+    ("stake", &["Stake11111111111111111111111111111111111111"]),
+    ("system", &["11111111111111111111111111111111"]),
+    ("vote", &["Vote111111111111111111111111111111111111111"]),
+];
+
+/// Resolves a program name from `PROGRAM_ALIASES`, or a base58 program ID,
+/// to the program IDs it stands for.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Config` if `program` is neither a
+/// known name nor a valid program ID.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::resolve_program;
+///
+/// let system = resolve_program("system")?;
+/// assert_eq!(system, resolve_program("11111111111111111111111111111111")?);
+/// assert_eq!(resolve_program("spl-token")?.len(), 2);
+/// # Ok::<(), solana_indexer_sdk::SolanaIndexerError>(())
+/// ```
+pub fn resolve_program(program: &str) -> Result<Vec<Pubkey>> {
+    if let Some((_, ids)) = PROGRAM_ALIASES.iter().find(|(name, _)| *name == program) {
+        return ids
+            .iter()
+            .map(|id| {
+                id.parse().map_err(|e| {
+                    SolanaIndexerError::Config(format!("Invalid program ID {id}: {e}"))
+                })
+            })
+            .collect();
+    }
+    program.parse().map(|id| vec![id]).map_err(|_| {
+        SolanaIndexerError::Config(format!(
+            "Unknown program {program:?}: expected a base58 program ID or one of {}",
+            PROGRAM_ALIASES
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{bpf_loader, bpf_loader_upgradeable, stake, system_program, vote};
+
+    #[test]
+    fn test_aliases_name_the_native_programs() {
+        for (name, id) in [
+            ("system", system_program::id()),
+            ("stake", stake::program::id()),
+            ("vote", vote::program::id()),
+            ("bpf-loader", bpf_loader::id()),
+            ("bpf-upgradeable-loader", bpf_loader_upgradeable::id()),
+        ] {
+            assert_eq!(resolve_program(name).unwrap(), [id]);
+        }
+        for (_, ids) in PROGRAM_ALIASES {
+            for id in *ids {
+                assert!(id.parse::<Pubkey>().is_ok(), "{id}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_ids_and_unknown_names() {
+        let program = Pubkey::new_unique();
+        assert_eq!(resolve_program(&program.to_string()).unwrap(), [program]);
+        assert!(resolve_program("raydium").is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Per-handler retry policies.
+//!
+//! A handler that fails is called again before its event is given up on,
+//! dead-lettered or spilled. How often, how long to wait in between and
+//! which errors are worth another attempt differ per handler: a webhook
+//! handler may want many slow attempts, while a handler writing to the
+//! local database wants few quick ones. `RetryPolicy` describes this and is
+//! attached with `SolanaIndexer::register_handler_with_retry`; handlers
+//! registered without one use `RetryPolicy::default`.
+//!
+//! Errors rejected by the policy's classifier are not retried at all, so a
+//! payload that does not decode goes straight to the dead letter queue
+//! instead of failing the same way three times.
+
+use crate::utils::backoff::{BackoffConfig, ErrorClass};
+use crate::utils::error::SolanaIndexerError;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Decides whether a handler error is worth another attempt.
+type Classifier = Arc<dyn Fn(&SolanaIndexerError) -> bool + Send + Sync>;
+
+/// Returns `true` for errors that may go away on retry, and `false` for
+/// errors the same event will fail with again: decoding, configuration and
+/// data errors, and rejected queries. See `SolanaIndexerError::is_retryable`.
+#[must_use]
+pub fn is_transient(error: &SolanaIndexerError) -> bool {
+    error.is_retryable()
+}
+
+/// Attempts, backoff and retryable errors of one handler.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::utils::backoff::BackoffConfig;
+/// use solana_indexer_sdk::{RetryPolicy, SolanaIndexerError};
+///
+/// // Up to 8 attempts, backing off from 500ms to 30s, retrying only
+/// // connection failures
+/// let policy = RetryPolicy::new(8)
+///     .with_backoff(BackoffConfig {
+///         base_delay_ms: 500,
+///         max_delay_ms: 30_000,
+///         ..BackoffConfig::default()
+///     })
+///     .retry_on(|e| matches!(e, SolanaIndexerError::Connection(_)));
+///
+/// let timeout = SolanaIndexerError::Connection("timed out".to_string());
+/// assert!(policy.delay(1, &timeout).is_some());
+/// assert!(policy.delay(8, &timeout).is_none());
+/// assert!(policy
+///     .delay(1, &SolanaIndexerError::Decode("bad data".to_string()))
+///     .is_none());
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: BackoffConfig,
+    retry_on: Classifier,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, 100ms then 200ms apart, retrying transient errors.
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy making up to `max_attempts` calls, including the
+    /// first, doubling the delay from 100ms and retrying `is_transient`
+    /// errors.
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            backoff: BackoffConfig {
+                base_delay_ms: 100,
+                max_delay_ms: 10_000,
+                jitter: 0.0,
+                ..BackoffConfig::default()
+            },
+            retry_on: Arc::new(is_transient),
+        }
+    }
+
+    /// Creates a policy that calls the handler once and never retries.
+    #[must_use]
+    pub fn never() -> Self {
+        Self::new(1)
+    }
+
+    /// Sets the delays between attempts. The attempt count of `backoff` is
+    /// ignored in favor of the policy's.
+    #[must_use]
+    pub fn with_backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Retries only the errors `classifier` returns `true` for.
+    #[must_use]
+    pub fn retry_on(
+        mut self,
+        classifier: impl Fn(&SolanaIndexerError) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.retry_on = Arc::new(classifier);
+        self
+    }
+
+    /// Returns the number of calls made before an event is given up on.
+    #[must_use]
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns how long to wait before calling the handler again after
+    /// attempt `attempt` (1-based) failed with `error`, or `None` if the
+    /// event should be given up on.
+    #[must_use]
+    pub fn delay(&self, attempt: u32, error: &SolanaIndexerError) -> Option<Duration> {
+        if !(self.retry_on)(error) {
+            return None;
+        }
+        // Rate-limited webhooks back off harder
+        let class = match ErrorClass::of(error) {
+            ErrorClass::RateLimited => ErrorClass::RateLimited,
+            _ => ErrorClass::Transient,
+        };
+        let backoff = BackoffConfig {
+            max_attempts: self.max_attempts,
+            ..self.backoff
+        };
+        backoff.delay(attempt, class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = RetryPolicy::default();
+        let outage = SolanaIndexerError::Storage(sqlx::Error::PoolTimedOut);
+        assert_eq!(policy.delay(1, &outage), Some(Duration::from_millis(100)));
+        assert_eq!(policy.delay(2, &outage), Some(Duration::from_millis(200)));
+        assert_eq!(policy.delay(3, &outage), None);
+
+        let permanent = SolanaIndexerError::Decode("truncated".to_string());
+        assert_eq!(policy.delay(1, &permanent), None);
+        assert_eq!(RetryPolicy::never().delay(1, &outage), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! Runtime on/off switches for event handlers.
+//!
+//! This module provides the `HandlerToggles` handle, which lets operators
+//! disable a misbehaving handler by discriminator while the rest of the
+//! pipeline keeps running.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+
+/// Shared set of disabled event discriminators.
+///
+/// Cloning is cheap and every clone refers to the same state, so a handle
+/// taken before `SolanaIndexer::start()` keeps working while the indexer runs.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::{calculate_discriminator, HandlerToggles};
+///
+/// let toggles = HandlerToggles::new();
+/// let discriminator = calculate_discriminator("TransferEvent");
+///
+/// toggles.disable(discriminator);
+/// assert!(!toggles.is_enabled(&discriminator));
+///
+/// toggles.enable(discriminator);
+/// assert!(toggles.is_enabled(&discriminator));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HandlerToggles {
+    disabled: Arc<RwLock<HashSet<[u8; 8]>>>,
+}
+
+impl HandlerToggles {
+    /// Creates a new set of toggles with every handler enabled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Disables the handler registered for `discriminator`.
+    ///
+    /// Events with this discriminator are acknowledged without being handled
+    /// until the handler is re-enabled.
+    pub fn disable(&self, discriminator: [u8; 8]) {
+        if let Ok(mut disabled) = self.disabled.write() {
+            disabled.insert(discriminator);
+        }
+    }
+
+    /// Re-enables the handler registered for `discriminator`.
+    pub fn enable(&self, discriminator: [u8; 8]) {
+        if let Ok(mut disabled) = self.disabled.write() {
+            disabled.remove(&discriminator);
+        }
+    }
+
+    /// Returns `true` unless the handler for `discriminator` has been disabled.
+    #[must_use]
+    pub fn is_enabled(&self, discriminator: &[u8; 8]) -> bool {
+        self.disabled
+            .read()
+            .map(|disabled| !disabled.contains(discriminator))
+            .unwrap_or(true)
+    }
+
+    /// Returns the discriminators that are currently disabled.
+    #[must_use]
+    pub fn disabled(&self) -> Vec<[u8; 8]> {
+        self.disabled
+            .read()
+            .map(|disabled| disabled.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggles_default_enabled() {
+        let toggles = HandlerToggles::new();
+        assert!(toggles.is_enabled(&[1; 8]));
+        assert!(toggles.disabled().is_empty());
+    }
+
+    #[test]
+    fn test_toggles_shared_between_clones() {
+        let toggles = HandlerToggles::new();
+        let handle = toggles.clone();
+
+        handle.disable([1; 8]);
+        assert!(!toggles.is_enabled(&[1; 8]));
+        assert!(toggles.is_enabled(&[2; 8]));
+        assert_eq!(toggles.disabled(), vec![[1; 8]]);
+
+        toggles.enable([1; 8]);
+        assert!(handle.is_enabled(&[1; 8]));
+    }
+}
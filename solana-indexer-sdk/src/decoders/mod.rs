@@ -0,0 +1,6 @@
+//! Built-in decoders for common native programs.
+//!
+//! Each module provides typed events for one program and a decoder that is
+//! registered with a single `SolanaIndexer` call.
+
+pub mod system;
@@ -0,0 +1,430 @@
+//! System Program decoder.
+//!
+//! `SystemDecoder` turns System Program instructions into typed events:
+//!
+//! | Instructions                                   | Event                 |
+//! |------------------------------------------------|-----------------------|
+//! | `transfer`, `transferWithSeed`                 | `SystemTransferEvent` |
+//! | `createAccount`, `createAccountWithSeed`       | `CreateAccountEvent`  |
+//! | `assign`, `assignWithSeed`                     | `AssignEvent`         |
+//! | `allocate`, `allocateWithSeed`                 | `AllocateEvent`       |
+//! | `initializeNonce`, `advanceNonce`, `withdrawFromNonce`, `authorizeNonce`, `upgradeNonce` | `NonceEvent` |
+//!
+//! Register it with `SolanaIndexer::register_system_decoder` and add a
+//! handler for each event type needed; events without a handler are
+//! skipped. Both instructions the RPC node parsed and raw instruction data
+//! are decoded.
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::decoders::system::SystemTransferEvent;
+//! # use solana_indexer_sdk::{EventHandler, SolanaIndexer, SolanaIndexerError, TxMetadata};
+//! # use async_trait::async_trait;
+//! # use sqlx::PgPool;
+//! struct TransferHandler;
+//!
+//! #[async_trait]
+//! impl EventHandler<SystemTransferEvent> for TransferHandler {
+//!     async fn handle(
+//!         &self,
+//!         event: SystemTransferEvent,
+//!         context: &TxMetadata,
+//!         _db: &PgPool,
+//!     ) -> Result<(), SolanaIndexerError> {
+//!         println!("{}: {} -> {} ({} lamports)", context.signature, event.from, event.to, event.lamports);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn example(indexer: &mut SolanaIndexer) -> Result<(), SolanaIndexerError> {
+//! indexer.register_system_decoder()?;
+//! indexer.register_handler(TransferHandler)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::types::events::{calculate_discriminator, EventDiscriminator, IndexerEvent};
+use crate::types::traits::DynamicInstructionDecoder;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde_json::Value;
+use solana_sdk::instruction::CompiledInstruction;
+use solana_sdk::message::AccountKeys;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_program;
+use solana_transaction_status::parse_instruction::ParsedInstructionEnum;
+use solana_transaction_status::parse_system::parse_system;
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+
+/// Program name the RPC node gives System Program instructions it parsed.
+pub const SYSTEM_PROGRAM_NAME: &str = "system";
+
+/// Lamports moved by `transfer` or `transferWithSeed`.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SystemTransferEvent {
+    /// Account debited
+    pub from: Pubkey,
+    /// Account credited
+    pub to: Pubkey,
+    /// Lamports transferred
+    pub lamports: u64,
+}
+
+/// Account created by `createAccount` or `createAccountWithSeed`.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CreateAccountEvent {
+    /// Account funding the new account
+    pub funder: Pubkey,
+    /// Account created
+    pub account: Pubkey,
+    /// Lamports the account was funded with
+    pub lamports: u64,
+    /// Bytes allocated
+    pub space: u64,
+    /// Program assigned as owner
+    pub owner: Pubkey,
+    /// Base and seed the address was derived from, for `createAccountWithSeed`
+    pub seed: Option<(Pubkey, String)>,
+}
+
+/// Account assigned to a program by `assign` or `assignWithSeed`.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AssignEvent {
+    /// Account assigned
+    pub account: Pubkey,
+    /// New owner program
+    pub owner: Pubkey,
+    /// Base and seed the address was derived from, for `assignWithSeed`
+    pub seed: Option<(Pubkey, String)>,
+}
+
+/// Space allocated by `allocate` or `allocateWithSeed`.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct AllocateEvent {
+    /// Account allocated
+    pub account: Pubkey,
+    /// Bytes allocated
+    pub space: u64,
+    /// Base and seed the address was derived from, for `allocateWithSeed`
+    pub seed: Option<(Pubkey, String)>,
+}
+
+/// Nonce account instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum NonceAction {
+    /// `initializeNonce`
+    Initialize,
+    /// `advanceNonce`
+    Advance,
+    /// `withdrawFromNonce`
+    Withdraw,
+    /// `authorizeNonce`
+    Authorize,
+    /// `upgradeNonce`
+    Upgrade,
+}
+
+/// Instruction on a durable nonce account.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct NonceEvent {
+    /// Instruction executed
+    pub action: NonceAction,
+    /// Nonce account
+    pub nonce_account: Pubkey,
+    /// Nonce authority; for `Initialize`, the authority set
+    pub authority: Option<Pubkey>,
+    /// Authority set by `Authorize`
+    pub new_authority: Option<Pubkey>,
+    /// Account credited by `Withdraw`
+    pub destination: Option<Pubkey>,
+    /// Lamports withdrawn by `Withdraw`
+    pub lamports: Option<u64>,
+}
+
+macro_rules! system_event {
+    ($($event:ident),*) => {
+        $(
+            impl EventDiscriminator for $event {
+                fn discriminator() -> [u8; 8] {
+                    calculate_discriminator(stringify!($event))
+                }
+            }
+
+            impl IndexerEvent for $event {
+                fn event_name() -> &'static str {
+                    stringify!($event)
+                }
+            }
+        )*
+    };
+}
+
+system_event!(
+    SystemTransferEvent,
+    CreateAccountEvent,
+    AssignEvent,
+    AllocateEvent,
+    NonceEvent
+);
+
+/// A decoded System Program instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemEvent {
+    /// `transfer` or `transferWithSeed`
+    Transfer(SystemTransferEvent),
+    /// `createAccount` or `createAccountWithSeed`
+    CreateAccount(CreateAccountEvent),
+    /// `assign` or `assignWithSeed`
+    Assign(AssignEvent),
+    /// `allocate` or `allocateWithSeed`
+    Allocate(AllocateEvent),
+    /// A nonce account instruction
+    Nonce(NonceEvent),
+}
+
+impl SystemEvent {
+    /// Returns the event's discriminator and Borsh encoding.
+    fn encode(&self) -> Option<([u8; 8], Vec<u8>)> {
+        match self {
+            Self::Transfer(event) => encode(event),
+            Self::CreateAccount(event) => encode(event),
+            Self::Assign(event) => encode(event),
+            Self::Allocate(event) => encode(event),
+            Self::Nonce(event) => encode(event),
+        }
+    }
+}
+
+fn encode<E: EventDiscriminator + BorshSerialize>(event: &E) -> Option<([u8; 8], Vec<u8>)> {
+    Some((E::discriminator(), borsh::to_vec(event).ok()?))
+}
+
+/// Decodes System Program instructions into `SystemEvent`s.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemDecoder;
+
+impl SystemDecoder {
+    /// Returns the discriminators of every event the decoder produces.
+    #[must_use]
+    pub fn discriminators() -> [[u8; 8]; 5] {
+        [
+            SystemTransferEvent::discriminator(),
+            CreateAccountEvent::discriminator(),
+            AssignEvent::discriminator(),
+            AllocateEvent::discriminator(),
+            NonceEvent::discriminator(),
+        ]
+    }
+
+    /// Decodes `instruction`, if it is a System Program instruction with a
+    /// typed event.
+    #[must_use]
+    pub fn decode_event(&self, instruction: &UiInstruction) -> Option<SystemEvent> {
+        match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed))
+                if parsed.program == SYSTEM_PROGRAM_NAME =>
+            {
+                let instruction_type = parsed.parsed.get("type")?.as_str()?;
+                event_from_info(instruction_type, parsed.parsed.get("info")?)
+            }
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial))
+                if partial.program_id == system_program::id().to_string() =>
+            {
+                let keys = partial
+                    .accounts
+                    .iter()
+                    .map(|key| key.parse().ok())
+                    .collect::<Option<Vec<Pubkey>>>()?;
+                let compiled = CompiledInstruction {
+                    program_id_index: 0,
+                    accounts: (0..keys.len())
+                        .map(|i| u8::try_from(i).ok())
+                        .collect::<Option<_>>()?,
+                    data: bs58::decode(&partial.data).into_vec().ok()?,
+                };
+                let ParsedInstructionEnum {
+                    instruction_type,
+                    info,
+                } = parse_system(&compiled, &AccountKeys::new(&keys, None)).ok()?;
+                event_from_info(&instruction_type, &info)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl DynamicInstructionDecoder for SystemDecoder {
+    fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        self.decode_event(instruction)?.encode()
+    }
+}
+
+/// Builds the event for an instruction in the RPC node's `jsonParsed` form.
+fn event_from_info(instruction_type: &str, info: &Value) -> Option<SystemEvent> {
+    let key = |name: &str| -> Option<Pubkey> { info.get(name)?.as_str()?.parse().ok() };
+    let number = |name: &str| info.get(name).and_then(Value::as_u64);
+    let seed = || -> Option<(Pubkey, String)> {
+        Some((key("base")?, info.get("seed")?.as_str()?.to_string()))
+    };
+    let nonce = |action: NonceAction| -> Option<SystemEvent> {
+        Some(SystemEvent::Nonce(NonceEvent {
+            action,
+            nonce_account: key("nonceAccount")?,
+            authority: key("nonceAuthority"),
+            new_authority: key("newAuthorized"),
+            destination: key("destination"),
+            lamports: number("lamports"),
+        }))
+    };
+
+    match instruction_type {
+        "transfer" | "transferWithSeed" => Some(SystemEvent::Transfer(SystemTransferEvent {
+            from: key("source")?,
+            to: key("destination")?,
+            lamports: number("lamports")?,
+        })),
+        "createAccount" | "createAccountWithSeed" => {
+            Some(SystemEvent::CreateAccount(CreateAccountEvent {
+                funder: key("source")?,
+                account: key("newAccount")?,
+                lamports: number("lamports")?,
+                space: number("space")?,
+                owner: key("owner")?,
+                seed: seed(),
+            }))
+        }
+        "assign" | "assignWithSeed" => Some(SystemEvent::Assign(AssignEvent {
+            account: key("account")?,
+            owner: key("owner")?,
+            seed: seed(),
+        })),
+        "allocate" | "allocateWithSeed" => Some(SystemEvent::Allocate(AllocateEvent {
+            account: key("account")?,
+            space: number("space")?,
+            seed: seed(),
+        })),
+        "initializeNonce" => nonce(NonceAction::Initialize),
+        "advanceNonce" => nonce(NonceAction::Advance),
+        "withdrawFromNonce" => nonce(NonceAction::Withdraw),
+        "authorizeNonce" => nonce(NonceAction::Authorize),
+        "upgradeNonce" => nonce(NonceAction::Upgrade),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::system_instruction;
+    use solana_transaction_status::parse_instruction::ParsedInstruction;
+    use solana_transaction_status::UiPartiallyDecodedInstruction;
+
+    fn parsed(info: Value) -> UiInstruction {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: SYSTEM_PROGRAM_NAME.to_string(),
+            program_id: system_program::id().to_string(),
+            parsed: info,
+            stack_height: None,
+        }))
+    }
+
+    fn raw(instruction: &solana_sdk::instruction::Instruction) -> UiInstruction {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: instruction.program_id.to_string(),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| meta.pubkey.to_string())
+                    .collect(),
+                data: bs58::encode(&instruction.data).into_string(),
+                stack_height: None,
+            },
+        ))
+    }
+
+    #[test]
+    fn test_decodes_parsed_instructions() {
+        let (from, to) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = parsed(json!({
+            "type": "transfer",
+            "info": { "source": from.to_string(), "destination": to.to_string(), "lamports": 5 },
+        }));
+        assert_eq!(
+            SystemDecoder.decode_event(&transfer),
+            Some(SystemEvent::Transfer(SystemTransferEvent {
+                from,
+                to,
+                lamports: 5
+            }))
+        );
+
+        let (discriminator, data) = SystemDecoder.decode_dynamic(&transfer).unwrap();
+        assert_eq!(discriminator, SystemTransferEvent::discriminator());
+        assert_eq!(
+            SystemTransferEvent::try_from_slice(&data).unwrap().lamports,
+            5
+        );
+
+        let unknown = parsed(json!({ "type": "someFutureInstruction", "info": {} }));
+        assert!(SystemDecoder.decode_event(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_decodes_raw_instructions() {
+        let (funder, account, owner) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let create = system_instruction::create_account(&funder, &account, 10, 165, &owner);
+        assert_eq!(
+            SystemDecoder.decode_event(&raw(&create)),
+            Some(SystemEvent::CreateAccount(CreateAccountEvent {
+                funder,
+                account,
+                lamports: 10,
+                space: 165,
+                owner,
+                seed: None,
+            }))
+        );
+
+        let allocate =
+            system_instruction::allocate_with_seed(&account, &funder, "vault", 8, &owner);
+        assert_eq!(
+            SystemDecoder.decode_event(&raw(&allocate)),
+            Some(SystemEvent::Allocate(AllocateEvent {
+                account,
+                space: 8,
+                seed: Some((funder, "vault".to_string())),
+            }))
+        );
+
+        let (nonce_account, authority, destination) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let withdraw =
+            system_instruction::withdraw_nonce_account(&nonce_account, &authority, &destination, 3);
+        assert_eq!(
+            SystemDecoder.decode_event(&raw(&withdraw)),
+            Some(SystemEvent::Nonce(NonceEvent {
+                action: NonceAction::Withdraw,
+                nonce_account,
+                authority: Some(authority),
+                new_authority: None,
+                destination: Some(destination),
+                lamports: Some(3),
+            }))
+        );
+
+        let mut other_program = raw(&create);
+        if let UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) =
+            &mut other_program
+        {
+            partial.program_id = Pubkey::new_unique().to_string();
+        }
+        assert!(SystemDecoder.decode_event(&other_program).is_none());
+    }
+}
@@ -33,3 +33,9 @@
 
 //! Note: The IDL parser functions are available as build-dependencies.
 //! Use `solana_indexer_idl::generate_sdk_types` directly in your `build.rs` script.
+//!
+//! To decode a deployed program from the IDL it stores on-chain instead,
+//! see [`registry`].
+
+pub mod registry;
+pub mod validate;
@@ -0,0 +1,412 @@
+//! On-chain Anchor IDLs.
+//!
+//! `anchor idl init` stores a program's IDL, zlib-compressed, in an account
+//! derived from the program ID. `IdlRegistry` fetches and caches these IDLs
+//! so a program can be indexed from its ID alone:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::SolanaIndexer;
+//! # async fn example(indexer: &mut SolanaIndexer) -> Result<(), Box<dyn std::error::Error>> {
+//! // Decodes every event the program's IDL declares; register a handler per
+//! // event type you want to process
+//! indexer
+//!     .register_idl_program("MyProgram111111111111111111111111111111111")
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The indexer re-checks the programs every `idl_refresh_secs` and reloads
+//! the IDL of any program upgraded since, so events added by the upgrade
+//! are decoded without a restart.
+
+use crate::core::decoding::anchor::AnchorEventDecoder;
+use crate::core::execution::fetcher::Fetcher;
+use crate::types::events::{calculate_discriminator, ParsedEvent};
+use crate::types::metadata::InstructionContext;
+use crate::types::traits::{DynamicInstructionDecoder, DynamicLogDecoder};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use crate::utils::macros::Idl;
+use flate2::read::ZlibDecoder;
+use solana_sdk::account::Account;
+use solana_sdk::bpf_loader_upgradeable::{self, UpgradeableLoaderState};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::UiInstruction;
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Seed of the IDL account, relative to the program's base address.
+const IDL_SEED: &str = "anchor:idl";
+
+/// Bytes before the compressed IDL: account discriminator, authority and
+/// data length.
+const IDL_HEADER_LEN: usize = 8 + 32 + 4;
+
+/// Returns the address of the IDL account of `program_id`.
+///
+/// # Example
+///
+/// ```
+/// # use solana_indexer_sdk::idl::registry::idl_address;
+/// # use solana_sdk::pubkey::Pubkey;
+/// let program_id = Pubkey::new_unique();
+/// assert_eq!(idl_address(&program_id), idl_address(&program_id));
+/// ```
+#[must_use]
+pub fn idl_address(program_id: &Pubkey) -> Pubkey {
+    let (base, _) = Pubkey::find_program_address(&[], program_id);
+    // The seed is far below the maximum length, so this cannot fail
+    Pubkey::create_with_seed(&base, IDL_SEED, program_id).unwrap_or_default()
+}
+
+/// Decodes the data of an IDL account.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Decode` if the data is truncated, not
+/// zlib-compressed, or not an IDL.
+pub fn decode_idl_account(data: &[u8]) -> Result<Idl> {
+    let len_bytes = data
+        .get(IDL_HEADER_LEN - 4..IDL_HEADER_LEN)
+        .ok_or_else(|| SolanaIndexerError::Decode("IDL account too short".to_string()))?;
+    let len = u32::from_le_bytes([len_bytes[0], len_bytes[1], len_bytes[2], len_bytes[3]]);
+    let compressed = data
+        .get(IDL_HEADER_LEN..IDL_HEADER_LEN + len as usize)
+        .ok_or_else(|| SolanaIndexerError::Decode("IDL account truncated".to_string()))?;
+
+    let mut json = String::new();
+    ZlibDecoder::new(compressed)
+        .read_to_string(&mut json)
+        .map_err(|e| SolanaIndexerError::Decode(format!("Invalid IDL data: {e}")))?;
+    Idl::parse(&json).map_err(|e| SolanaIndexerError::Decode(format!("Invalid IDL: {e}")))
+}
+
+/// Returns the address of the program data account of an upgradeable
+/// program.
+fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// Returns the slot an upgradeable program was last deployed at, from its
+/// program data account.
+fn deployed_slot(program_data: Option<&Account>) -> Option<u64> {
+    match bincode::deserialize(&program_data?.data).ok()? {
+        UpgradeableLoaderState::ProgramData { slot, .. } => Some(slot),
+        _ => None,
+    }
+}
+
+/// A cached IDL.
+struct IdlEntry {
+    idl: Arc<Idl>,
+    decoder: AnchorEventDecoder,
+    /// Deployment slot the IDL was loaded for, `None` for programs that
+    /// cannot be upgraded
+    deployed_slot: Option<u64>,
+}
+
+impl IdlEntry {
+    fn new(idl: Idl, deployed_slot: Option<u64>) -> Self {
+        let decoder = idl
+            .events
+            .iter()
+            .fold(AnchorEventDecoder::new(), |decoder, event| {
+                decoder.with_discriminator(calculate_discriminator(&event.name))
+            });
+        Self {
+            idl: Arc::new(idl),
+            decoder,
+            deployed_slot,
+        }
+    }
+}
+
+type IdlEntries = Arc<RwLock<HashMap<Pubkey, IdlEntry>>>;
+
+/// Cache of on-chain Anchor IDLs, keyed by program ID.
+///
+/// Cloning is cheap and every clone shares the cache. The indexer keeps one,
+/// returned by `SolanaIndexer::idl_registry`.
+#[derive(Clone)]
+pub struct IdlRegistry {
+    fetcher: Arc<Fetcher>,
+    entries: IdlEntries,
+}
+
+impl IdlRegistry {
+    /// Creates an empty registry fetching IDLs through `fetcher`.
+    #[must_use]
+    pub fn new(fetcher: Arc<Fetcher>) -> Self {
+        Self {
+            fetcher,
+            entries: Arc::default(),
+        }
+    }
+
+    /// Returns the IDL of `program_id`, fetching it on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the program has no IDL
+    /// account, `SolanaIndexerError::Decode` if the account holds no
+    /// valid IDL, and `SolanaIndexerError::Rpc` if the fetch fails.
+    pub async fn load(&self, program_id: &Pubkey) -> Result<Arc<Idl>> {
+        if let Some(idl) = self.get(program_id) {
+            return Ok(idl);
+        }
+        let entry = self.fetch(program_id).await?;
+        let idl = entry.idl.clone();
+        self.write()?.insert(*program_id, entry);
+        Ok(idl)
+    }
+
+    /// Returns the cached IDL of `program_id`, if loaded.
+    #[must_use]
+    pub fn get(&self, program_id: &Pubkey) -> Option<Arc<Idl>> {
+        let entries = self.entries.read().ok()?;
+        entries.get(program_id).map(|entry| entry.idl.clone())
+    }
+
+    /// Returns the programs whose IDL is loaded.
+    #[must_use]
+    pub fn programs(&self) -> Vec<Pubkey> {
+        self.entries
+            .read()
+            .map(|entries| entries.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Reloads the IDL of every loaded program deployed again since its IDL
+    /// was fetched, returning the programs reloaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if the program data accounts
+    /// cannot be fetched. A program whose new IDL cannot be loaded keeps its
+    /// old one and is retried on the next refresh.
+    pub async fn refresh(&self) -> Result<Vec<Pubkey>> {
+        let programs = self.programs();
+        let addresses: Vec<Pubkey> = programs.iter().map(program_data_address).collect();
+        let accounts = self.fetcher.fetch_multiple_accounts(&addresses).await?;
+
+        let mut reloaded = Vec::new();
+        for (program_id, program_data) in programs.into_iter().zip(accounts) {
+            let slot = deployed_slot(program_data.as_ref());
+            let known = self
+                .entries
+                .read()
+                .ok()
+                .and_then(|entries| entries.get(&program_id).map(|e| e.deployed_slot));
+            if known == Some(slot) {
+                continue;
+            }
+            match self.fetch(&program_id).await {
+                Ok(entry) => {
+                    self.write()?.insert(program_id, entry);
+                    reloaded.push(program_id);
+                }
+                Err(e) => logging::log_error(
+                    &format!("IDL reload of upgraded program {program_id} failed"),
+                    &e.to_string(),
+                ),
+            }
+        }
+        Ok(reloaded)
+    }
+
+    /// Returns a decoder of the events declared by the IDL of `program_id`,
+    /// following reloads of the IDL.
+    ///
+    /// Decodes nothing until the IDL is loaded.
+    #[must_use]
+    pub fn event_decoder(&self, program_id: Pubkey) -> IdlEventDecoder {
+        IdlEventDecoder {
+            program_id,
+            entries: self.entries.clone(),
+        }
+    }
+
+    /// Fetches the IDL and deployment slot of `program_id`.
+    async fn fetch(&self, program_id: &Pubkey) -> Result<IdlEntry> {
+        let address = idl_address(program_id);
+        let program_data = program_data_address(program_id);
+        let mut accounts = self
+            .fetcher
+            .fetch_multiple_accounts(&[address, program_data])
+            .await?
+            .into_iter();
+        let idl_account = accounts.next().flatten().ok_or_else(|| {
+            SolanaIndexerError::Config(format!(
+                "Program {program_id} has no on-chain IDL at {address}"
+            ))
+        })?;
+        let idl = decode_idl_account(&idl_account.data)?;
+        Ok(IdlEntry::new(
+            idl,
+            deployed_slot(accounts.next().flatten().as_ref()),
+        ))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<Pubkey, IdlEntry>>> {
+        self.entries
+            .write()
+            .map_err(|_| SolanaIndexerError::Internal("IdlRegistry lock poisoned".to_string()))
+    }
+
+    /// Spawns the refresh of the loaded IDLs every `interval`.
+    pub(crate) fn spawn(
+        self,
+        interval: Duration,
+        cancellation_token: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    () = tokio::time::sleep(interval) => {}
+                }
+                match self.refresh().await {
+                    Ok(reloaded) => {
+                        for program_id in reloaded {
+                            logging::log(
+                                logging::LogLevel::Info,
+                                &format!("Reloaded the IDL of upgraded program {program_id}"),
+                            );
+                        }
+                    }
+                    Err(e) => logging::log_error("IDL refresh error", &e.to_string()),
+                }
+            }
+        })
+    }
+}
+
+/// Decodes the Anchor events declared by a program's cached IDL, from its
+/// logs and self-CPIs like `AnchorEventDecoder`.
+///
+/// Created by `IdlRegistry::event_decoder`.
+pub struct IdlEventDecoder {
+    program_id: Pubkey,
+    entries: IdlEntries,
+}
+
+impl IdlEventDecoder {
+    fn with_decoder<T>(&self, decode: impl FnOnce(&AnchorEventDecoder) -> T) -> Option<T> {
+        let entries = self.entries.read().ok()?;
+        entries
+            .get(&self.program_id)
+            .map(|entry| decode(&entry.decoder))
+    }
+}
+
+impl DynamicLogDecoder for IdlEventDecoder {
+    fn decode_log_dynamic(&self, event: &ParsedEvent) -> Option<([u8; 8], Vec<u8>)> {
+        self.with_decoder(|decoder| decoder.decode_log_dynamic(event))?
+    }
+}
+
+impl DynamicInstructionDecoder for IdlEventDecoder {
+    fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        self.with_decoder(|decoder| decoder.decode_dynamic(instruction))?
+    }
+
+    fn decode_dynamic_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        self.with_decoder(|decoder| decoder.decode_dynamic_in_context(instruction, context))?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::{EventType, TransferEvent};
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use solana_sdk::commitment_config::CommitmentConfig;
+    use std::io::Write;
+
+    fn idl_account(json: &str) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut data = vec![0u8; 8];
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend((compressed.len() as u32).to_le_bytes());
+        data.extend(compressed);
+        // Accounts are allocated with room for larger IDLs
+        data.extend([0u8; 64]);
+        data
+    }
+
+    #[test]
+    fn test_decodes_idl_accounts() -> Result<()> {
+        // Anchor 0.30+ IDLs keep the name and version under `metadata`
+        let data = idl_account(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": {"name": "my_program", "version": "0.1.0", "spec": "0.1.0"},
+                "instructions": [{
+                    "name": "transfer",
+                    "discriminator": [1, 2, 3, 4, 5, 6, 7, 8],
+                    "accounts": [{"name": "from", "writable": true, "signer": true}],
+                    "args": [{"name": "amount", "type": "u64"}]
+                }],
+                "events": [{"name": "TransferEvent", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}]
+            }"#,
+        );
+        let idl = decode_idl_account(&data)?;
+        assert_eq!(idl.event_names(), vec!["TransferEvent"]);
+        assert!(idl.instructions[0].accounts[0].is_mut);
+
+        assert!(decode_idl_account(&data[..IDL_HEADER_LEN + 4]).is_err());
+        assert!(decode_idl_account(&[0u8; 8]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_decoder_follows_reloads() {
+        let fetcher = Fetcher::new("http://127.0.0.1:8899", CommitmentConfig::confirmed());
+        let registry = IdlRegistry::new(Arc::new(fetcher));
+        let program_id = Pubkey::new_unique();
+        let decoder = registry.event_decoder(program_id);
+
+        let mut bytes = TransferEvent::discriminator().to_vec();
+        bytes.extend([1, 2, 3]);
+        let log = ParsedEvent {
+            event_type: EventType::ProgramData,
+            program_id: Some(program_id),
+            data: Some(BASE64.encode(&bytes)),
+        };
+        assert!(decoder.decode_log_dynamic(&log).is_none());
+
+        let v1 = Idl::parse(r#"{"name": "my_program", "events": []}"#).unwrap();
+        registry
+            .write()
+            .unwrap()
+            .insert(program_id, IdlEntry::new(v1, Some(10)));
+        assert!(decoder.decode_log_dynamic(&log).is_none());
+
+        let v2 = Idl::parse(
+            r#"{"name": "my_program", "events": [{"name": "TransferEvent", "fields": []}]}"#,
+        )
+        .unwrap();
+        registry
+            .write()
+            .unwrap()
+            .insert(program_id, IdlEntry::new(v2, Some(20)));
+        assert_eq!(
+            decoder.decode_log_dynamic(&log),
+            Some((TransferEvent::discriminator(), vec![1, 2, 3]))
+        );
+        assert_eq!(registry.programs(), vec![program_id]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Checks of event structs against a program's IDL.
+//!
+//! Handlers decode events with the Borsh layout of their Rust struct. When a
+//! program upgrade reorders, retypes or adds event fields and the structs
+//! are not regenerated, events keep decoding, into the wrong fields, or stop
+//! decoding altogether. Comparing the structs with the IDL catches this
+//! before any event is processed.
+//!
+//! Layouts come from `EventDiscriminator::borsh_layout`, which
+//! `#[derive(IndexerEvent)]` and IDL-generated types implement. Events
+//! absent from the IDL are not checked, and neither are the fields of types
+//! that do not describe them.
+
+use crate::types::events::calculate_discriminator;
+use crate::types::schema::{EventLayout, FieldLayout};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::macros::{idl_type_to_rust, Idl, IdlField, IdlType};
+
+/// Returns the Rust type an IDL type decodes to, e.g. `Option<Vec<u8>>`.
+///
+/// # Example
+///
+/// ```
+/// # use solana_indexer_sdk::idl::validate::rust_type;
+/// # use solana_indexer_sdk::IdlType;
+/// let ty: IdlType = serde_json::from_str(r#"{"option": {"vec": "publicKey"}}"#).unwrap();
+/// assert_eq!(rust_type(&ty), "Option<Vec<Pubkey>>");
+/// ```
+#[must_use]
+pub fn rust_type(ty: &IdlType) -> String {
+    let inner = |value: &serde_json::Value| {
+        serde_json::from_value::<IdlType>(value.clone())
+            .map_or_else(|_| value.to_string(), |ty| rust_type(&ty))
+    };
+    match ty {
+        IdlType::Simple(name) => idl_type_to_rust(name),
+        IdlType::Complex(map) => {
+            if let Some(ty) = map.get("vec") {
+                format!("Vec<{}>", inner(ty))
+            } else if let Some(ty) = map.get("option") {
+                format!("Option<{}>", inner(ty))
+            } else if let Some(serde_json::Value::Array(array)) = map.get("array") {
+                match array.as_slice() {
+                    [ty, len] => format!("[{}; {len}]", inner(ty)),
+                    _ => format!("{map:?}"),
+                }
+            } else if let Some(defined) = map.get("defined") {
+                // `"defined": "Name"`, or `"defined": {"name": "Name"}` since
+                // Anchor 0.30
+                defined
+                    .as_str()
+                    .or_else(|| defined.get("name")?.as_str())
+                    .map_or_else(|| defined.to_string(), ToString::to_string)
+            } else {
+                format!("{map:?}")
+            }
+        }
+    }
+}
+
+/// Normalizes a Rust type for comparison: whitespace and module paths are
+/// dropped, so `solana_sdk::pubkey::Pubkey` matches `Pubkey`.
+fn normalize_type(ty: &str) -> String {
+    let mut out = String::with_capacity(ty.len());
+    let mut chars = ty.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            while out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                out.pop();
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Normalizes a field name for comparison, since IDLs before Anchor 0.30
+/// use camelCase.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn describe_idl(field: Option<&IdlField>) -> String {
+    field.map_or_else(
+        || "none".to_string(),
+        |field| format!("`{}: {}`", field.name, rust_type(&field.ty)),
+    )
+}
+
+fn describe_struct(field: Option<&FieldLayout>) -> String {
+    field.map_or_else(
+        || "none".to_string(),
+        |field| format!("`{}: {}`", field.name, normalize_type(&field.ty)),
+    )
+}
+
+/// Compares `layouts` with the events of `idl`, returning one line per
+/// difference.
+///
+/// # Example
+///
+/// ```
+/// # use solana_indexer_sdk::idl::validate::layout_diff;
+/// # use solana_indexer_sdk::{calculate_discriminator, EventLayout, FieldLayout, Idl};
+/// let idl = Idl::parse(r#"{"events": [{"name": "TransferEvent", "fields": [
+///     {"name": "amount", "type": "u64"}
+/// ]}]}"#).unwrap();
+/// let layout = EventLayout {
+///     event: "TransferEvent".to_string(),
+///     discriminator: calculate_discriminator("TransferEvent"),
+///     fields: vec![FieldLayout::new("amount", "u32")],
+/// };
+/// assert_eq!(
+///     layout_diff(&idl, &[layout]),
+///     vec!["TransferEvent field 0: IDL `amount: u64`, struct `amount: u32`"]
+/// );
+/// ```
+#[must_use]
+pub fn layout_diff(idl: &Idl, layouts: &[EventLayout]) -> Vec<String> {
+    let mut diff = Vec::new();
+    for layout in layouts {
+        let Some(idl_fields) = idl.event_fields(&layout.event) else {
+            continue;
+        };
+        let expected = calculate_discriminator(&layout.event);
+        if layout.discriminator != expected {
+            diff.push(format!(
+                "{} discriminator: IDL {expected:?}, struct {:?}",
+                layout.event, layout.discriminator
+            ));
+        }
+        if layout.fields.is_empty() {
+            continue;
+        }
+
+        for index in 0..idl_fields.len().max(layout.fields.len()) {
+            let idl_field = idl_fields.get(index);
+            let field = layout.fields.get(index);
+            let matches = match (idl_field, field) {
+                (Some(idl_field), Some(field)) => {
+                    normalize_name(&idl_field.name) == normalize_name(&field.name)
+                        && normalize_type(&rust_type(&idl_field.ty)) == normalize_type(&field.ty)
+                }
+                _ => false,
+            };
+            if !matches {
+                diff.push(format!(
+                    "{} field {index}: IDL {}, struct {}",
+                    layout.event,
+                    describe_idl(idl_field),
+                    describe_struct(field)
+                ));
+            }
+        }
+    }
+    diff
+}
+
+/// Checks `layouts` against the events of `idl`.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Config` listing every difference if any
+/// layout does not match the IDL.
+pub fn validate_event_layouts(idl: &Idl, layouts: &[EventLayout]) -> Result<()> {
+    let diff = layout_diff(idl, layouts);
+    if diff.is_empty() {
+        return Ok(());
+    }
+    let program = if idl.name.is_empty() {
+        "the IDL"
+    } else {
+        &idl.name
+    };
+    Err(SolanaIndexerError::Config(format!(
+        "Event structs do not match {program}:\n  {}",
+        diff.join("\n  ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(event: &str, fields: &[(&str, &str)]) -> EventLayout {
+        EventLayout {
+            event: event.to_string(),
+            discriminator: calculate_discriminator(event),
+            fields: fields
+                .iter()
+                .map(|(name, ty)| FieldLayout::new(*name, *ty))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_layout_diff() {
+        let idl = Idl::parse(
+            r#"{
+                "name": "my_program",
+                "events": [
+                    {"name": "TransferEvent", "fields": [
+                        {"name": "fromAccount", "type": "publicKey"},
+                        {"name": "amount", "type": "u64"},
+                        {"name": "memo", "type": {"option": "string"}}
+                    ]},
+                    {"name": "DepositEvent", "fields": [{"name": "amount", "type": "u64"}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let matching = layout(
+            "TransferEvent",
+            &[
+                ("from_account", "solana_sdk :: pubkey :: Pubkey"),
+                ("amount", "u64"),
+                ("memo", "Option < String >"),
+            ],
+        );
+        // Not in the IDL, or without a described layout
+        let unknown = layout("SwapEvent", &[("amount", "u8")]);
+        let opaque = layout("DepositEvent", &[]);
+        assert!(validate_event_layouts(&idl, &[matching, unknown, opaque]).is_ok());
+
+        let mut stale = layout(
+            "TransferEvent",
+            &[("from_account", "Pubkey"), ("amount", "u32")],
+        );
+        stale.discriminator = [0; 8];
+        let diff = layout_diff(&idl, &[stale]);
+        assert_eq!(diff.len(), 3);
+        assert!(diff[0].starts_with("TransferEvent discriminator"));
+        assert_eq!(
+            diff[1],
+            "TransferEvent field 1: IDL `amount: u64`, struct `amount: u32`"
+        );
+        assert_eq!(
+            diff[2],
+            "TransferEvent field 2: IDL `memo: Option<String>`, struct none"
+        );
+    }
+}
@@ -10,7 +10,64 @@
 //!
 //! - **Multiple Data Sources**: Ingest data via RPC polling, WebSocket subscriptions, Helius Enhanced RPC, or Laserstream (Yellowstone gRPC).
 //! - **Automatic Indexing Modes**: The indexer automatically detects which on-chain data to process (instructions, logs, or account states) based on the decoders you register.
+//! - **Instruction Routing**: `InstructionRouter` decodes instruction arguments straight into typed events by discriminator, under Anchor's `global:` sighashes, leading tag bytes or a custom scheme.
+//! - **Non-Borsh Accounts**: `PodAccountDecoder` and `BincodeAccountDecoder` decode zero-copy `bytemuck` and bincode account layouts, with alignment-safe `pod_at` and `pod_slice` readers.
 //! - **Dynamic Backfill**: Automatically detects when the indexer is behind the chain tip and backfills historical data concurrently with live indexing.
+//! - **Account Snapshots**: Optionally bootstraps account indexers from a `getProgramAccounts` snapshot at startup, so they start from the complete on-chain state.
+//! - **Transaction Prefilters**: Skips transactions before they are fetched, using closures or short expressions over the error, memo, logs and slot a source reports.
+//! - **Ordered Parallelism**: `set_ordering_key` processes transactions with the same key, such as the same account, in order while other keys run in parallel, for stateful handlers like balance trackers.
+//! - **Compute Budget and Return Data**: `TxMetadata` carries the compute unit limit and price set by ComputeBudget instructions, the resulting priority fee, and the data a program returned, so handlers need not reparse the transaction.
+//! - **Chain-Tip Tracking**: Optionally follows processed, confirmed and finalized slots over `slotSubscribe` instead of polling `getSlot`.
+//! - **Confirmation Tracking**: Optionally records when each processed signature became processed, confirmed and finalized, for data-quality reports.
+//! - **Write Buffering**: Optionally batches processed-signature writes and handler inserts into one transaction per flush, removing the per-signature commit bottleneck at high TPS.
+//! - **Time-Based Backfill Start**: `BackfillConfig::start_time` starts a backfill from a date; the engine binary-searches `getBlockTime` for the first block produced at or after it.
+//! - **Backfill Progress**: Every backfilled range is recorded in `_solana_indexer_sdk_backfill_ranges`, and `backfill_status` reports completed and pending ranges, transaction counts, throughput and an ETA.
+//! - **Resumable Backfill**: Backfill checkpoints every batch with the last signature handled, so a crashed or restarted indexer resumes a range exactly where it stopped.
+//! - **Bulk Loading**: Backfills mark processed signatures with one binary `COPY` per block, and `BulkLoader` gives backfill handlers the same path for their own tables.
+//! - **Table Partitioning**: Optionally partitions the processed-signatures table by slot range or month, creating partitions ahead of time and detaching old ones.
+//! - **Retention**: Optionally deletes or archives processed signatures and handler rows older than a number of days or slots, tracking the rows and bytes reclaimed.
+//! - **Scheduled Vacuum**: Optionally runs `VACUUM`/`ANALYZE` and custom maintenance SQL on the SDK tables on a cron schedule, reporting the table bloat found.
+//! - **Table Stats**: Optionally counts the rows and highest slot of the handler tables on an interval, in one consistent snapshot, so dashboards read `SolanaIndexer::table_stats` instead of running `COUNT(*)` against production tables.
+//! - **Standby Replication**: `with_replica_database` mirrors cursors, processed markers and the other SDK writes to a second database in the background, so a standby region can take over indexing.
+//! - **Processed Cache**: Keeps recently processed signatures in memory, so duplicate checks on overlapping pages skip the database.
+//! - **Schema Namespaces**: `with_db_schema` keeps an indexer's tables in a dedicated `PostgreSQL` schema, so several indexers can share one database.
+//! - **Account State Tables**: `register_account_state` keeps the latest decoded state of each account, and optionally its history, in SDK-managed tables without a handler.
+//! - **Account Diffs**: `register_account_diffs` compares consecutive decoded states of each account and delivers an `AccountFieldChanged` event per changed field.
+//! - **Cursor Stores**: `set_cursor_store` keeps per-address polling cursors in a `CursorStore` such as `EtcdCursorStore`, or `RedisCursorStore` with the `redis` feature, while events still go to the database.
+//! - **Unhandled Events**: Optionally stores decoded events that have no handler yet in a generic table, as JSON for described event types, instead of failing their transaction.
+//! - **Fan-out Handlers**: Several handlers can be registered for one event type, e.g. to both store an event and push it to a webhook; each is retried and dead-lettered independently.
+//! - **Handler Filters**: `register_handler_with_filter` puts a synchronous predicate over the event and its transaction in front of a handler, so dust or self-transfers are skipped before any await or database round trip.
+//! - **Handler Rate Limits**: `register_handler_with_rate_limit` caps how many calls of a handler run at once and start per second, so handlers enriching events through price feeds or metadata APIs stay under the service's limits.
+//! - **Retry Policies**: `register_handler_with_retry` attaches a `RetryPolicy` to a handler, setting its attempts, backoff and which errors are retried, so transient database or webhook failures are retried while permanent decode errors go straight to the dead letter queue.
+//! - **Atomic Transactions**: `with_atomic_transactions` commits the writes handlers make through `TxMetadata::execute` in one database transaction with the signature's processed marker, so a crash never leaves partial handler rows behind.
+//! - **Transactional Outbox**: With `with_outbox`, `OutboxHandler` stores events in `_solana_indexer_sdk_outbox` in the same database transaction as the handler rows, and a relay publishes them to the outbox sinks with stable dedup keys, for exactly-once delivery to consumers that drop repeated keys.
+//! - **Dead Letter Queue**: Optionally stores events whose handler still fails after its retries in `_solana_indexer_sdk_dlq`, with their context and error, instead of failing their transaction, and reprocesses them on request through `SolanaIndexer::dead_letter_queue`.
+//! - **Feature Flags**: Optionally exposes boolean flags from `_solana_indexer_sdk_flags` to handlers through `TxMetadata::flags`, cached and refreshed on an interval, so behavior can be toggled without redeploying.
+//! - **Deferred Jobs**: Optionally lets handlers schedule work for later with `TxMetadata::schedule_in`, such as a re-check after finalization, stored in `_solana_indexer_sdk_jobs` and delivered to a `JobHandler` once due.
+//! - **Hot Reload**: `SolanaIndexer::registry_handle` registers and removes instruction decoders and event handlers while the indexer runs, so a new event type can be picked up without a restart.
+//! - **On-Chain IDLs**: `SolanaIndexer::register_idl_program` fetches and caches a program's Anchor IDL from its on-chain IDL account and decodes every event it declares, reloading the IDL when the program is upgraded.
+//! - **IDL Layout Checks**: On start, the Borsh layout of each handled event struct, described by `#[derive(IndexerEvent)]` or IDL-generated types, is compared with the IDLs given to `validate_against_idl` or loaded on-chain, failing with a field-by-field diff rather than silently misdecoding after a program upgrade.
+//! - **Watermark Notifications**: Publishes each advance of the processed-slot watermark through `LISTEN`/`NOTIFY`, a watch channel and event sinks, so downstream jobs can pull only the rows beyond their last-seen watermark.
+//! - **Lifecycle Hooks**: A `PipelineObserver` set with `set_pipeline_observer` hears when each batch settles and which slots are fully processed, with every handler's writes committed.
+//! - **Cache Invalidation**: `InvalidationTrigger` notifies every change to a table over `LISTEN`/`NOTIFY`, and `InvalidationListener` delivers the changed keys to handlers' in-process caches, keeping multi-instance deployments consistent.
+//! - **Periodic Tasks**: Runs `PeriodicTask` implementations, such as hourly rollups, on cron schedules with access to the database and the processed-slot watermark.
+//! - **Transaction Archive**: Optionally keeps every decoded transaction, compressed, in an archive table so new decoders can be run over history without refetching it from RPC. `SolanaIndexer::replay` runs the decoders and handlers again over a slot range of processed transactions.
+//! - **Cold Archive**: `set_cold_archive` moves archived transactions older than a number of days into slot-partitioned, compressed segments in object storage, indexed by a manifest, and replays read them back from there.
+//! - **Spill Queue**: Optionally appends decoded events to a local file while the database is unreachable and replays them once it recovers.
+//! - **SQLite Storage**: With the `sqlite` feature, a `sqlite:` database URL keeps the indexer's bookkeeping in SQLite, so small deployments and tests need no `PostgreSQL` server; handlers write to the database set with `with_handler_database`, which connects on first use.
+//! - **Local PostgreSQL**: With the `local-postgres` feature, a configuration without a database URL starts a throwaway server from the local `PostgreSQL` installation, reachable only over a private Unix socket with a generated password, so examples run without setting up a database.
+//! - **Pipelines**: `Pipeline::builder` composes a source, filters, decoders, transforms and any number of sinks and handlers for one event type, attached to the indexer in one call. Routes send matching events to their own sink or database, isolated from each other's failures.
+//! - **Event Sinks**: Forward decoded events to external systems through the `EventSink` trait; with the `redis` feature, `RedisStreamSink` appends them to a Redis Stream.
+//! - **Webhook Dispatch**: `WebhookHandler` POSTs decoded events as JSON to one or more URLs, HMAC-SHA256 signed, with per-URL retries and a cap on requests in flight.
+//! - **JSON Lines Output**: `JsonlHandler` writes events with their transaction metadata to stdout or a rolling file, one JSON object per line, for dry runs that show what a decoder produces without writing to the database.
+//! - **Live Tail**: `TailSink` streams decoded events over TCP, and `solstream tail --event <Name> --json` prints them as they are decoded, for debugging a running indexer.
+//! - **Webhook Verification**: Checks Helius auth headers or HMAC-SHA256 signatures on webhook deliveries and rejects spoofed or replayed payloads.
+//! - **Guardrails**: Caps the events decoded from one transaction and the account data passed to decoders; oversized transactions are skipped or dead-lettered and counted in the registry metrics.
+//! - **Failover**: Counts consecutive source failures and, past a configurable budget, switches RPC endpoints, falls back from WebSocket to RPC polling, then alerts an `ErrorObserver`.
+//! - **Slot-Gap Alarm**: Optionally alerts the `ErrorObserver` when consecutive live transactions jump more slots than expected, and queues a backfill of the skipped range.
+//! - **Rate Anomalies**: `RateMonitor` wraps a handler, tracks rolling event rates per wallet, mint or other key, and calls an alert hook when a key passes a limit or spikes above its usual rate.
+//! - **Usage Reporting**: Optionally counts events, bytes and RPC calls per program into a daily table, so infrastructure cost can be attributed to individual protocols.
+//! - **Event Derive**: With the `derive` feature, `#[derive(IndexerEvent)]` implements the event discriminator, discriminator-checked Borsh decoding and an optional table description from the struct's fields.
 //! - **Simple Handler Pattern**: Implement the `EventHandler` trait to add your custom business logic (e.g., writing to a database).
 //! - **Concurrent & Performant**: Built on `tokio` to process transactions in parallel, with configurable worker limits.
 //!
@@ -135,25 +192,97 @@
 #![allow(clippy::module_name_repetitions)]
 
 // Public API exports
-pub use config::{SolanaIndexerConfig, SolanaIndexerConfigBuilder};
+pub use config::profiles::{ConfigProfiles, ProfileSettings};
+pub use config::{
+    DuplicatePolicy, MissingMetaPolicy, OversizedAction, RegistryConfig, SolanaIndexerConfig,
+    SolanaIndexerConfigBuilder,
+};
+pub use core::backfill::bandwidth::{BandwidthSchedule, BandwidthWindow};
+pub use core::backfill::status::{BackfillStatus, RangeProgress};
+pub use core::decoding::anchor::AnchorEventDecoder;
+pub use core::decoding::diff::{diff_fields, field_path, AccountFieldChanged, AccountFields};
+pub use core::decoding::discriminator::{
+    DiscriminatorFn, DiscriminatorStrategy, InstructionRouter,
+};
+pub use core::decoding::layout::{
+    bincode_at, pod_at, pod_slice, BincodeAccountDecoder, PodAccountDecoder,
+};
 pub use core::decoding::Decoder;
 pub use core::decoding::{DecodedTransaction, InstructionInfo};
+pub use core::execution::anomaly::{RateAlert, RateAlertHook, RateAlertKind, RateMonitor};
+pub use core::execution::failover::{ErrorObserver, Escalation, FailoverConfig, FailoverLadder};
 pub use core::execution::fetcher::Fetcher;
 pub use core::execution::indexer::SolanaIndexer;
+pub use core::execution::observer::{BatchSummary, PipelineObserver};
+pub use core::execution::ordering::{KeyedExecutor, OrderingKey};
+pub use core::execution::prefilter::{Prefilter, PrefilterExpr, TransactionPreview};
+pub use core::execution::schedule::{CronSchedule, PeriodicTask};
+pub use core::execution::usage::{UsageRecord, UsageTracker};
+pub use core::execution::watermark::{WatermarkNotifier, WATERMARK_CHANNEL};
+pub use core::pipeline::{Pipeline, PipelineBuilder, RouteReport};
 pub use core::registry::account::AccountDecoderRegistry;
+pub use core::registry::chain::ChainControl;
+pub use core::registry::filter::FilteredHandler;
+pub use core::registry::handle::RegistryHandle;
+pub use core::registry::limit::{RateLimit, RateLimitedHandler};
 pub use core::registry::logs::LogDecoderRegistry;
-pub use core::registry::DecoderRegistry;
-pub use storage::{Storage, StorageBackend};
+pub use core::registry::programs::{resolve_program, PROGRAM_ALIASES};
+pub use core::registry::retry::RetryPolicy;
+pub use core::registry::toggles::HandlerToggles;
+pub use core::registry::{DecoderRegistry, ProgramScope};
+pub use idl::registry::{IdlEventDecoder, IdlRegistry};
+pub use idl::validate::{layout_diff, validate_event_layouts};
+pub use sinks::{EventSink, SinkHandler, SinkRecord};
+pub use storage::archive::{ReplaySummary, TransactionArchive};
+pub use storage::atomic::TxWrites;
+pub use storage::buffer::{FlushPolicy, WriteBuffer};
+pub use storage::cache::ProcessedCache;
+pub use storage::cold::{
+    ColdArchive, ColdArchiveConfig, ColdArchiveReport, DirectorySegmentStore, SegmentInfo,
+    SegmentManifest, SegmentStore,
+};
+pub use storage::copy::{BulkLoader, CopyRows, CopyValue};
+pub use storage::cursor::{CursorStorage, CursorStore};
+pub use storage::dlq::{DeadLetterQueue, DlqEntry};
+pub use storage::flags::FeatureFlags;
+pub use storage::holders::{TokenHolders, TokenHolding};
+pub use storage::invalidation::{
+    notify_invalidation, Invalidation, InvalidationListener, InvalidationTrigger,
+};
+pub use storage::jobs::{DeferredJob, DeferredJobs, JobHandler};
+pub use storage::outbox::{OutboxHandler, OutboxRelay};
+pub use storage::partitions::{PartitionConfig, PartitionReport, PartitionScheme};
+pub use storage::replica::{ReplicaMetrics, ReplicatedStorage};
+pub use storage::retention::{
+    PrunedTable, RetentionAction, RetentionAge, RetentionConfig, RetentionMetrics, RetentionReport,
+    RetentionTable,
+};
+pub use storage::spill::SpillQueue;
+pub use storage::state::AccountStateTable;
+pub use storage::stats::{TableCount, TableStats, TableStatsSnapshot};
+pub use storage::unhandled::UnhandledEvents;
+pub use storage::vacuum::{TableBloat, VacuumConfig, VacuumMetrics, VacuumMode, VacuumReport};
+pub use storage::{ConfirmationStatus, ConfirmationSummary, DeadLetter, Storage, StorageBackend};
 pub use streams::poller::Poller;
+pub use streams::slots::SlotTracker;
 pub use types::backfill_traits::{
     BackfillContext, BackfillHandler, BackfillHandlerRegistry, BackfillProgress, BackfillRange,
     BackfillStrategy, BackfillTrigger, FinalizedBlockTracker, ReorgEvent, ReorgHandler,
 };
 pub use types::events::{
-    calculate_discriminator, DepositEvent, EventDiscriminator, EventType, ParsedEvent,
-    TransferEvent, WithdrawEvent,
+    calculate_discriminator, DepositEvent, EventDiscriminator, EventType, IndexerEvent,
+    ParsedEvent, TransferEvent, WithdrawEvent,
+};
+
+/// Derives `EventDiscriminator` and `IndexerEvent` for an event type.
+#[cfg(feature = "derive")]
+pub use solana_indexer_derive::IndexerEvent;
+pub use types::metadata::{
+    InstructionContext, ReturnData, TokenBalanceInfo, TransactionAccount, TxMetadata,
+};
+pub use types::schema::{
+    ColumnSchema, DataDictionary, EventLayout, EventSchema, FieldLayout, TableSchema,
 };
-pub use types::metadata::{TokenBalanceInfo, TxMetadata};
 pub use types::traits::{
     AccountDecoder, DynamicAccountDecoder, DynamicEventHandler, DynamicInstructionDecoder,
     EventHandler, HandlerRegistry, InstructionDecoder, LogDecoder, SchemaInitializer,
@@ -171,14 +300,25 @@ pub use utils::macros::{
 #[cfg(feature = "telemetry")]
 pub use telemetry::{init_telemetry, shutdown_telemetry, TelemetryConfig};
 
+// SQLite storage exports
+#[cfg(feature = "sqlite")]
+pub use storage::sqlite::SqliteStorage;
+
+// Local PostgreSQL exports
+#[cfg(feature = "local-postgres")]
+pub use storage::local_postgres::{LocalPostgres, LOCAL_DATABASE_URL};
+
 // Module declarations
 pub mod config;
 pub mod core;
+pub mod decoders;
 pub mod idl;
+pub mod sinks;
 pub mod storage;
 pub mod streams;
 #[cfg(feature = "telemetry")]
 pub mod telemetry;
+pub mod testing;
 pub mod types;
 pub mod utils;
 
@@ -0,0 +1,352 @@
+//! JSON Lines output for dry runs.
+//!
+//! `JsonlHandler` writes every event it receives as one JSON object per
+//! line: the event type, the event itself and the full `TxMetadata` of its
+//! transaction. It never touches the database, so registering it for each
+//! event type of a decoder shows exactly what the decoder produces before
+//! any handler or schema is written, e.g. piped into `jq`:
+//!
+//! ```no_run
+//! # fn example(indexer: &mut solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! use solana_indexer_sdk::sinks::jsonl::{JsonlHandler, JsonlWriter};
+//! use solana_indexer_sdk::TransferEvent;
+//! use std::sync::Arc;
+//!
+//! let stdout = Arc::new(JsonlWriter::stdout());
+//! indexer.register_handler(JsonlHandler::<TransferEvent>::new(stdout.clone()))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! `JsonlWriter::rolling_file` writes to a file instead, which is moved to
+//! `<path>.1` once it reaches a size limit, shifting older files up to a
+//! configured count. The writer is also an `EventSink`, writing
+//! `SinkRecord`s, so pipelines can route events to it.
+
+use super::{event_name, EventSink, SinkRecord};
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::EventHandler;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// One line written by `JsonlHandler`.
+#[derive(Serialize)]
+struct JsonlLine<'a, T> {
+    event: &'a str,
+    payload: &'a T,
+    metadata: &'a TxMetadata,
+}
+
+/// Where lines go.
+enum Output {
+    Stdout(tokio::io::Stdout),
+    File(RollingFile),
+}
+
+/// A file moved aside once it reaches `max_bytes`.
+struct RollingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: tokio::fs::File,
+    written: u64,
+}
+
+impl RollingFile {
+    /// Moves the current file to `<path>.1`, shifting older ones up and
+    /// dropping the oldest, and starts a new one.
+    async fn roll(&mut self) -> Result<()> {
+        self.file
+            .flush()
+            .await
+            .map_err(|e| io_error("flush", &self.path, &e))?;
+        if self.keep == 0 {
+            tokio::fs::remove_file(&self.path)
+                .await
+                .map_err(|e| io_error("remove", &self.path, &e))?;
+        } else {
+            for index in (1..self.keep).rev() {
+                let from = rolled_path(&self.path, index);
+                if tokio::fs::try_exists(&from).await.unwrap_or(false) {
+                    tokio::fs::rename(&from, rolled_path(&self.path, index + 1))
+                        .await
+                        .map_err(|e| io_error("roll", &from, &e))?;
+                }
+            }
+            tokio::fs::rename(&self.path, rolled_path(&self.path, 1))
+                .await
+                .map_err(|e| io_error("roll", &self.path, &e))?;
+        }
+        self.file = open(&self.path).await?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Writes JSON lines to stdout or a rolling file.
+///
+/// Share one writer between the handlers of several event types through an
+/// `Arc`; lines are never interleaved.
+pub struct JsonlWriter {
+    output: Mutex<Output>,
+    lines: AtomicU64,
+}
+
+impl std::fmt::Debug for JsonlWriter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonlWriter")
+            .field("lines", &self.lines())
+            .finish_non_exhaustive()
+    }
+}
+
+impl JsonlWriter {
+    /// Creates a writer printing to stdout.
+    #[must_use]
+    pub fn stdout() -> Self {
+        Self::with_output(Output::Stdout(tokio::io::stdout()))
+    }
+
+    /// Creates a writer appending to `path`, moved aside once it holds
+    /// `max_bytes`, keeping up to `keep` older files.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if the file cannot be
+    /// opened.
+    pub async fn rolling_file(
+        path: impl Into<PathBuf>,
+        max_bytes: u64,
+        keep: usize,
+    ) -> Result<Self> {
+        let path = path.into();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("create directory of", &path, &e))?;
+        }
+        let file = open(&path).await?;
+        let written = file
+            .metadata()
+            .await
+            .map_err(|e| io_error("inspect", &path, &e))?
+            .len();
+        Ok(Self::with_output(Output::File(RollingFile {
+            path,
+            max_bytes: max_bytes.max(1),
+            keep,
+            file,
+            written,
+        })))
+    }
+
+    fn with_output(output: Output) -> Self {
+        Self {
+            output: Mutex::new(output),
+            lines: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of lines written since startup.
+    pub fn lines(&self) -> u64 {
+        self.lines.load(Ordering::Relaxed)
+    }
+
+    /// Writes `value` as one JSON line.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Data` if `value` cannot be
+    /// serialized, or `SolanaIndexerError::Internal` if the line cannot
+    /// be written.
+    pub async fn write_line<S: Serialize + ?Sized>(&self, value: &S) -> Result<()> {
+        let mut line = serde_json::to_vec(value)
+            .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize line: {e}")))?;
+        line.push(b'\n');
+
+        let mut output = self.output.lock().await;
+        match &mut *output {
+            Output::Stdout(stdout) => {
+                stdout.write_all(&line).await.map_err(|e| {
+                    SolanaIndexerError::Internal(format!("Failed to write to stdout: {e}"))
+                })?;
+            }
+            Output::File(rolling) => {
+                if rolling.written > 0 && rolling.written + line.len() as u64 > rolling.max_bytes {
+                    rolling.roll().await?;
+                }
+                rolling
+                    .file
+                    .write_all(&line)
+                    .await
+                    .map_err(|e| io_error("write to", &rolling.path, &e))?;
+                rolling.written += line.len() as u64;
+            }
+        }
+        self.lines.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSink for JsonlWriter {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        self.write_line(record).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        match &mut *self.output.lock().await {
+            Output::Stdout(stdout) => stdout
+                .flush()
+                .await
+                .map_err(|e| SolanaIndexerError::Internal(format!("Failed to flush stdout: {e}"))),
+            Output::File(rolling) => rolling
+                .file
+                .flush()
+                .await
+                .map_err(|e| io_error("flush", &rolling.path, &e)),
+        }
+    }
+}
+
+/// Event handler writing every event of type `T`, with its transaction
+/// metadata, to a `JsonlWriter`.
+pub struct JsonlHandler<T> {
+    writer: Arc<JsonlWriter>,
+    _event: PhantomData<fn(T)>,
+}
+
+impl<T> JsonlHandler<T> {
+    /// Creates a handler writing to `writer`.
+    #[must_use]
+    pub fn new(writer: Arc<JsonlWriter>) -> Self {
+        Self {
+            writer,
+            _event: PhantomData,
+        }
+    }
+
+    /// Creates a handler printing to stdout.
+    #[must_use]
+    pub fn stdout() -> Self {
+        Self::new(Arc::new(JsonlWriter::stdout()))
+    }
+
+    /// Returns the writer lines go to.
+    pub fn writer(&self) -> &Arc<JsonlWriter> {
+        &self.writer
+    }
+}
+
+#[async_trait]
+impl<T> EventHandler<T> for JsonlHandler<T>
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    async fn handle(&self, event: T, context: &TxMetadata, _db: &PgPool) -> Result<()> {
+        self.writer
+            .write_line(&JsonlLine {
+                event: event_name::<T>(),
+                payload: &event,
+                metadata: context,
+            })
+            .await
+    }
+}
+
+/// Opens `path` for appending, creating it if needed.
+async fn open(path: &Path) -> Result<tokio::fs::File> {
+    tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| io_error("open", path, &e))
+}
+
+/// Returns the path of the `index`th older file of `path`.
+fn rolled_path(path: &Path, index: usize) -> PathBuf {
+    let mut rolled = path.as_os_str().to_owned();
+    rolled.push(format!(".{index}"));
+    PathBuf::from(rolled)
+}
+
+fn io_error(action: &str, path: &Path, error: &std::io::Error) -> SolanaIndexerError {
+    SolanaIndexerError::Internal(format!(
+        "Failed to {action} JSON lines file {}: {error}",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+
+    #[tokio::test]
+    async fn test_lines_roll_over() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("solstream-jsonl-{}", std::process::id()));
+        let path = dir.join("events.jsonl");
+        let writer = Arc::new(JsonlWriter::rolling_file(&path, 200, 1).await?);
+        let handler = JsonlHandler::<TransferEvent>::new(writer.clone());
+        // Never connects: the handler does not touch the pool
+        let pool = PgPool::connect_lazy("postgresql://localhost/unused")?;
+        let context = TxMetadata {
+            slot: 9,
+            block_time: None,
+            fee: 5000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        for amount in 1..=3 {
+            let event = TransferEvent {
+                from: "alice".to_string(),
+                to: "bob".to_string(),
+                amount,
+            };
+            handler.handle(event, &context, &pool).await?;
+        }
+        writer.flush().await?;
+        assert_eq!(writer.lines(), 3);
+
+        // Every line exceeds half the limit, so each one starts a new file
+        let current = tokio::fs::read_to_string(&path).await.unwrap();
+        let line: serde_json::Value = serde_json::from_str(current.trim_end()).unwrap();
+        assert_eq!(line["event"], "TransferEvent");
+        assert_eq!(line["payload"]["amount"], 3);
+        assert_eq!(line["metadata"]["slot"], 9);
+        let rolled = tokio::fs::read_to_string(rolled_path(&path, 1))
+            .await
+            .unwrap();
+        assert!(rolled.contains(r#""amount":2"#));
+        assert!(!rolled_path(&path, 2).exists());
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Output adapters that forward decoded events to external systems.
+//!
+//! An `EventSink` publishes `SinkRecord`s, the JSON form of a decoded event
+//! together with the transaction it came from. Every message-bus adapter
+//! implements the same trait, so the destination can change without touching
+//! handler code. `SinkHandler` turns any sink into an `EventHandler`, which
+//! lets it be registered with the indexer like a hand-written handler.
+//!
+//! Adapters are feature gated:
+//!
+//! - **`redis`**: `RedisStreamSink` appends records to a Redis Stream.
+//!
+//! `tail::TailSink` needs no feature: it streams records to `solstream tail`
+//! for live debugging. Neither does `webhook::WebhookSink`, which POSTs
+//! records to HTTP endpoints, signed with HMAC-SHA256, nor
+//! `jsonl::JsonlWriter`, which writes them to stdout or a rolling file.
+
+pub mod jsonl;
+#[cfg(feature = "redis")]
+pub mod redis;
+pub mod tail;
+pub mod webhook;
+
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::EventHandler;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A decoded event ready to be published.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SinkRecord {
+    /// Event type name, e.g. `TransferEvent`
+    pub event: String,
+    /// Signature of the transaction that emitted the event
+    pub signature: String,
+    /// Slot of the transaction
+    pub slot: u64,
+    /// Block time of the transaction, if known
+    pub block_time: Option<i64>,
+    /// The event serialized as JSON
+    pub payload: serde_json::Value,
+    /// Key that stays the same every time this event is delivered, for
+    /// consumers to drop repeats by; set on records relayed from the
+    /// transactional outbox
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedup_key: Option<String>,
+}
+
+impl SinkRecord {
+    /// Builds a record from a decoded event and its transaction context.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Data` if the event cannot be
+    /// serialized to JSON.
+    pub fn new<T: Serialize>(event: &T, context: &TxMetadata) -> Result<Self> {
+        let payload = serde_json::to_value(event)
+            .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize event: {e}")))?;
+
+        Ok(Self {
+            event: event_name::<T>().to_string(),
+            signature: context.signature.clone(),
+            slot: context.slot,
+            block_time: context.block_time,
+            payload,
+            dedup_key: None,
+        })
+    }
+}
+
+/// Destination for decoded events.
+///
+/// Implementations should be cheap to share; the indexer calls `publish`
+/// concurrently from its workers.
+#[async_trait]
+pub trait EventSink: Send + Sync + 'static {
+    /// Publishes one record.
+    async fn publish(&self, record: &SinkRecord) -> Result<()>;
+
+    /// Flushes any records the sink has buffered. The default does nothing.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: EventSink + ?Sized> EventSink for Arc<S> {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        (**self).publish(record).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        (**self).flush().await
+    }
+}
+
+/// Event handler that forwards every event of type `T` to a sink.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "redis")]
+/// # async fn example(indexer: &mut solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+/// use solana_indexer_sdk::sinks::{redis::RedisStreamSink, SinkHandler};
+/// use solana_indexer_sdk::TransferEvent;
+///
+/// let sink = RedisStreamSink::new("redis://127.0.0.1/", "transfers")
+///     .await?
+///     .with_maxlen(100_000);
+/// indexer.register_handler(SinkHandler::<TransferEvent, _>::new(sink))?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SinkHandler<T, S> {
+    sink: S,
+    _event: PhantomData<fn(T)>,
+}
+
+impl<T, S: EventSink> SinkHandler<T, S> {
+    /// Creates a handler publishing to `sink`.
+    #[must_use]
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            _event: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped sink.
+    pub fn sink(&self) -> &S {
+        &self.sink
+    }
+}
+
+#[async_trait]
+impl<T, S> EventHandler<T> for SinkHandler<T, S>
+where
+    T: Serialize + Send + Sync + 'static,
+    S: EventSink,
+{
+    async fn handle(&self, event: T, context: &TxMetadata, _db: &PgPool) -> Result<()> {
+        let record = SinkRecord::new(&event, context)?;
+        self.sink.publish(&record).await
+    }
+}
+
+/// Short type name of `T`, without module path or generics.
+fn event_name<T>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+    type_name
+        .split('<')
+        .next()
+        .and_then(|path| path.rsplit("::").next())
+        .unwrap_or(type_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemorySink {
+        records: Mutex<Vec<SinkRecord>>,
+    }
+
+    #[async_trait]
+    impl EventSink for MemorySink {
+        async fn publish(&self, record: &SinkRecord) -> Result<()> {
+            self.records.lock().await.push(record.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_handler_publishes_json_record() {
+        let handler = SinkHandler::<TransferEvent, _>::new(Arc::new(MemorySink::default()));
+        let context = TxMetadata {
+            slot: 42,
+            block_time: Some(1_700_000_000),
+            fee: 5000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+        let event = TransferEvent {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 7,
+        };
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+
+        handler.handle(event, &context, &pool).await.unwrap();
+
+        let records = handler.sink().records.lock().await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event, "TransferEvent");
+        assert_eq!(records[0].signature, "sig");
+        assert_eq!(records[0].slot, 42);
+        assert_eq!(records[0].payload["amount"], 7);
+    }
+}
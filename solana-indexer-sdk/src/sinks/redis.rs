@@ -0,0 +1,171 @@
+//! Redis Streams output adapter.
+//!
+//! `RedisStreamSink` appends each record to a stream with `XADD`, one entry
+//! per event. Web backends can then read the stream with `XREAD` or consumer
+//! groups without standing up a full message broker. A maximum length keeps
+//! the stream from growing without bound; by default it is trimmed
+//! approximately (`MAXLEN ~`), which Redis can do far more cheaply.
+
+use super::{EventSink, SinkRecord};
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+
+/// Publishes decoded events to a Redis Stream.
+///
+/// Entries carry the fields `event`, `signature`, `slot`, `block_time`
+/// (omitted when unknown), `payload` (the event as JSON) and `dedup_key`
+/// (only on records relayed from the outbox).
+#[derive(Clone)]
+pub struct RedisStreamSink {
+    connection: ConnectionManager,
+    stream: String,
+    maxlen: Option<usize>,
+    approximate: bool,
+}
+
+impl RedisStreamSink {
+    /// Connects to Redis and publishes to `stream`.
+    ///
+    /// The connection is re-established automatically if it drops.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Connection` if the URL is invalid or
+    /// Redis cannot be reached.
+    pub async fn new(url: &str, stream: impl Into<String>) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(connection_error)?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(connection_error)?;
+
+        Ok(Self {
+            connection,
+            stream: stream.into(),
+            maxlen: None,
+            approximate: true,
+        })
+    }
+
+    /// Trims the stream to about `maxlen` entries on every append.
+    #[must_use]
+    pub fn with_maxlen(mut self, maxlen: usize) -> Self {
+        self.maxlen = Some(maxlen);
+        self
+    }
+
+    /// Trims to exactly `maxlen` entries (`MAXLEN =`) instead of approximately.
+    #[must_use]
+    pub fn with_exact_trimming(mut self) -> Self {
+        self.approximate = false;
+        self
+    }
+
+    /// Returns the name of the stream events are appended to.
+    pub fn stream(&self) -> &str {
+        &self.stream
+    }
+
+    fn xadd(&self, record: &SinkRecord) -> redis::Cmd {
+        xadd_command(&self.stream, self.maxlen, self.approximate, record)
+    }
+}
+
+#[async_trait]
+impl EventSink for RedisStreamSink {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        let mut connection = self.connection.clone();
+        let _id: String = self
+            .xadd(record)
+            .query_async(&mut connection)
+            .await
+            .map_err(connection_error)?;
+        Ok(())
+    }
+}
+
+fn xadd_command(
+    stream: &str,
+    maxlen: Option<usize>,
+    approximate: bool,
+    record: &SinkRecord,
+) -> redis::Cmd {
+    let mut cmd = redis::cmd("XADD");
+    cmd.arg(stream);
+    if let Some(maxlen) = maxlen {
+        cmd.arg("MAXLEN")
+            .arg(if approximate { "~" } else { "=" })
+            .arg(maxlen);
+    }
+    cmd.arg("*")
+        .arg("event")
+        .arg(&record.event)
+        .arg("signature")
+        .arg(&record.signature)
+        .arg("slot")
+        .arg(record.slot);
+    if let Some(block_time) = record.block_time {
+        cmd.arg("block_time").arg(block_time);
+    }
+    cmd.arg("payload").arg(record.payload.to_string());
+    if let Some(key) = &record.dedup_key {
+        cmd.arg("dedup_key").arg(key);
+    }
+    cmd
+}
+
+fn connection_error(err: redis::RedisError) -> SolanaIndexerError {
+    SolanaIndexerError::Connection(format!("Redis error: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &redis::Cmd) -> Vec<String> {
+        cmd.args_iter()
+            .map(|arg| match arg {
+                redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                redis::Arg::Cursor => "<cursor>".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_xadd_command_fields_and_trimming() {
+        let record = SinkRecord {
+            event: "TransferEvent".to_string(),
+            signature: "sig".to_string(),
+            slot: 42,
+            block_time: None,
+            payload: serde_json::json!({ "amount": 7 }),
+            dedup_key: None,
+        };
+
+        assert_eq!(
+            args(&xadd_command("events", Some(1000), true, &record)),
+            [
+                "XADD",
+                "events",
+                "MAXLEN",
+                "~",
+                "1000",
+                "*",
+                "event",
+                "TransferEvent",
+                "signature",
+                "sig",
+                "slot",
+                "42",
+                "payload",
+                "{\"amount\":7}",
+            ]
+        );
+
+        let untrimmed = args(&xadd_command("events", None, true, &record));
+        assert_eq!(&untrimmed[..3], ["XADD", "events", "*"]);
+
+        let exact = args(&xadd_command("events", Some(10), false, &record));
+        assert_eq!(&exact[2..5], ["MAXLEN", "=", "10"]);
+    }
+}
@@ -0,0 +1,230 @@
+//! Live event feed for `solstream tail`.
+//!
+//! `TailSink` listens on a TCP address and streams every record it is given
+//! to each connected client, one JSON-encoded `SinkRecord` per line. Nothing
+//! is buffered for clients that are not connected, and records are dropped
+//! for clients that fall behind, so the feed never slows the indexer down.
+//!
+//! `solstream tail` connects to the feed and prints events as they are
+//! decoded, or `follow` can be used directly:
+//!
+//! ```text
+//! solstream tail --addr 127.0.0.1:9740 --event SystemTransferEvent --json
+//! ```
+
+use super::{EventSink, SinkRecord};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+/// Address `solstream tail` connects to by default.
+pub const DEFAULT_TAIL_ADDR: &str = "127.0.0.1:9740";
+
+/// Records held for each client before it is considered behind.
+const CLIENT_BUFFER: usize = 1024;
+
+/// Streams decoded events to `solstream tail` clients over TCP.
+///
+/// Register one `SinkHandler` per event type to watch, sharing the sink
+/// through an `Arc`. The listener stops when the last clone is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(indexer: &mut solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+/// use solana_indexer_sdk::sinks::{tail::{TailSink, DEFAULT_TAIL_ADDR}, SinkHandler};
+/// use solana_indexer_sdk::TransferEvent;
+/// use std::sync::Arc;
+///
+/// let tail = Arc::new(TailSink::bind(DEFAULT_TAIL_ADDR).await?);
+/// indexer.register_handler(SinkHandler::<TransferEvent, _>::new(tail.clone()))?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TailSink {
+    sender: broadcast::Sender<Arc<str>>,
+    local_addr: SocketAddr,
+    _listener: DropGuard,
+}
+
+impl TailSink {
+    /// Listens for clients on `addr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Connection` if the address cannot be
+    /// bound.
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let listener = TcpListener::bind(addr).await.map_err(connection_error)?;
+        let local_addr = listener.local_addr().map_err(connection_error)?;
+        let (sender, _) = broadcast::channel(CLIENT_BUFFER);
+        let token = CancellationToken::new();
+        tokio::spawn(accept(listener, sender.clone(), token.clone()));
+
+        Ok(Self {
+            sender,
+            local_addr,
+            _listener: token.drop_guard(),
+        })
+    }
+
+    /// Returns the address clients connect to.
+    #[must_use]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Returns the number of connected clients.
+    #[must_use]
+    pub fn clients(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[async_trait]
+impl EventSink for TailSink {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        if self.sender.receiver_count() == 0 {
+            return Ok(());
+        }
+        let line = serde_json::to_string(record)
+            .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize record: {e}")))?;
+        // Clients may disconnect in between; there is no one left to tell
+        let _ = self.sender.send(line.into());
+        Ok(())
+    }
+}
+
+/// Accepts clients until `token` is cancelled.
+async fn accept(
+    listener: TcpListener,
+    sender: broadcast::Sender<Arc<str>>,
+    token: CancellationToken,
+) {
+    loop {
+        let stream = tokio::select! {
+            () = token.cancelled() => break,
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    logging::log_error("Tail accept error", &e.to_string());
+                    continue;
+                }
+            },
+        };
+        let records = sender.subscribe();
+        tokio::spawn(serve(stream, records, token.clone()));
+    }
+}
+
+/// Writes `records` to one client until it disconnects.
+async fn serve(
+    mut stream: TcpStream,
+    mut records: broadcast::Receiver<Arc<str>>,
+    token: CancellationToken,
+) {
+    loop {
+        let line = tokio::select! {
+            () = token.cancelled() => break,
+            record = records.recv() => match record {
+                Ok(line) => line,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    logging::log(
+                        logging::LogLevel::Warning,
+                        &format!("Tail client fell behind; skipped {skipped} events"),
+                    );
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        };
+        if stream.write_all(line.as_bytes()).await.is_err()
+            || stream.write_all(b"\n").await.is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// Connects to the feed of a `TailSink` at `addr` and calls `on_record` for
+/// each record of the listed `events`, or of every event if `events` is
+/// empty, until the feed closes.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Connection` if the feed cannot be
+/// reached or the connection fails, and `SolanaIndexerError::Data` if
+/// a line is not a record.
+pub async fn follow(
+    addr: impl ToSocketAddrs,
+    events: &[String],
+    mut on_record: impl FnMut(SinkRecord),
+) -> Result<()> {
+    let stream = TcpStream::connect(addr).await.map_err(connection_error)?;
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(line) = lines.next_line().await.map_err(connection_error)? {
+        let record: SinkRecord = serde_json::from_str(&line)
+            .map_err(|e| SolanaIndexerError::Data(format!("Invalid tail record: {e}")))?;
+        if events.is_empty() || events.contains(&record.event) {
+            on_record(record);
+        }
+    }
+    Ok(())
+}
+
+fn connection_error(e: std::io::Error) -> SolanaIndexerError {
+    SolanaIndexerError::Connection(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record(event: &str, slot: u64) -> SinkRecord {
+        SinkRecord {
+            event: event.to_string(),
+            signature: format!("sig{slot}"),
+            slot,
+            block_time: None,
+            payload: serde_json::json!({ "amount": slot }),
+            dedup_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streams_records_to_clients() -> Result<()> {
+        let sink = TailSink::bind("127.0.0.1:0").await?;
+        // Nobody is listening yet
+        sink.publish(&record("TransferEvent", 1)).await?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let addr = sink.local_addr();
+        let client = tokio::spawn(async move {
+            follow(addr, &["TransferEvent".to_string()], |record| {
+                let _ = tx.send(record);
+            })
+            .await
+        });
+        while sink.clients() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        sink.publish(&record("DepositEvent", 2)).await?;
+        sink.publish(&record("TransferEvent", 3)).await?;
+        let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .map_err(|_| SolanaIndexerError::Internal("No record received".to_string()))?;
+        assert_eq!(received, Some(record("TransferEvent", 3)));
+
+        drop(sink);
+        assert!(client.await.is_ok());
+        Ok(())
+    }
+}
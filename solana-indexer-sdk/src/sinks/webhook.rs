@@ -0,0 +1,348 @@
+//! HTTP webhook output adapter.
+//!
+//! `WebhookSink` POSTs each record as JSON to one or more URLs, so events
+//! can be forwarded to an external service without writing HTTP code. With
+//! a secret, every delivery is signed the way `WebhookVerifier::hmac`
+//! expects: the hex HMAC-SHA256 of `"{timestamp}.{body}"` in the
+//! `x-webhook-signature` header and the Unix timestamp in
+//! `x-webhook-timestamp`. Records relayed from the transactional outbox
+//! carry their dedup key in the `Idempotency-Key` header.
+//!
+//! Deliveries that fail with a connection error, a timeout, `408`, `429` or
+//! a `5xx` response are retried per URL with backoff, so one slow receiver
+//! does not cause the others to receive the event twice. Any other response
+//! is a rejection and fails the event with `SolanaIndexerError::Data`,
+//! which handler retry policies do not retry. At most
+//! `with_max_concurrency` requests are in flight at once across all workers.
+//!
+//! ```no_run
+//! # fn example(indexer: &mut solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! use solana_indexer_sdk::sinks::webhook::{WebhookHandler, WebhookSink};
+//! use solana_indexer_sdk::{RetryPolicy, TransferEvent};
+//!
+//! let sink = WebhookSink::new(["https://example.com/hooks/transfers"])?
+//!     .with_secret("s3cret")
+//!     .with_max_concurrency(8);
+//! // The sink retries on its own
+//! indexer.register_handler_with_retry(
+//!     WebhookHandler::<TransferEvent>::new(sink),
+//!     RetryPolicy::never(),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{EventSink, SinkHandler, SinkRecord};
+use crate::streams::webhook::{DEFAULT_SIGNATURE_HEADER, DEFAULT_TIMESTAMP_HEADER};
+use crate::utils::backoff::{retry_with_backoff, BackoffConfig};
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::StatusCode;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Semaphore;
+
+/// How long a delivery may take before it is retried.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Header carrying a record's dedup key.
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Requests in flight at once by default.
+const DEFAULT_MAX_CONCURRENCY: usize = 16;
+
+/// Event handler POSTing every event of type `T` to a `WebhookSink`.
+pub type WebhookHandler<T> = SinkHandler<T, WebhookSink>;
+
+/// Publishes decoded events to HTTP endpoints.
+pub struct WebhookSink {
+    client: reqwest::Client,
+    urls: Vec<reqwest::Url>,
+    secret: Option<Vec<u8>>,
+    backoff: BackoffConfig,
+    permits: Arc<Semaphore>,
+    delivered: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl std::fmt::Debug for WebhookSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret
+        f.debug_struct("WebhookSink")
+            .field("urls", &self.urls)
+            .field("signed", &self.secret.is_some())
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebhookSink {
+    /// Creates a sink delivering every record to each of `urls`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if there are no URLs, one
+    /// is not a valid `http` or `https` URL, or the HTTP client cannot be
+    /// built.
+    pub fn new(urls: impl IntoIterator<Item = impl AsRef<str>>) -> Result<Self> {
+        let urls = urls
+            .into_iter()
+            .map(|url| {
+                let url = url.as_ref();
+                reqwest::Url::parse(url)
+                    .ok()
+                    .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+                    .ok_or_else(|| {
+                        SolanaIndexerError::Config(format!("Invalid webhook URL: {url}"))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if urls.is_empty() {
+            return Err(SolanaIndexerError::Config(
+                "A webhook sink needs at least one URL".to_string(),
+            ));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| SolanaIndexerError::Config(format!("Invalid HTTP client: {e}")))?;
+
+        Ok(Self {
+            client,
+            urls,
+            secret: None,
+            backoff: BackoffConfig::default(),
+            permits: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENCY)),
+            delivered: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        })
+    }
+
+    /// Signs every delivery with HMAC-SHA256 under `secret`.
+    #[must_use]
+    pub fn with_secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+
+    /// Sets the attempts and delays of retried deliveries (default: five
+    /// attempts from 100ms).
+    #[must_use]
+    pub fn with_backoff(mut self, backoff: BackoffConfig) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets how many requests may be in flight at once (default: 16).
+    #[must_use]
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(max_concurrency.max(1)));
+        self
+    }
+
+    /// Sends requests with `client`, e.g. one with proxy or TLS settings,
+    /// instead of a default client with a 10 second timeout.
+    #[must_use]
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Returns the URLs records are delivered to.
+    pub fn urls(&self) -> &[reqwest::Url] {
+        &self.urls
+    }
+
+    /// Returns the number of deliveries accepted since startup, one per URL.
+    pub fn delivered(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of deliveries rejected or given up on since
+    /// startup.
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// POSTs `body` to `url` once.
+    async fn post(&self, url: &reqwest::Url, body: &[u8], key: Option<&str>) -> Result<()> {
+        let _permit =
+            self.permits.acquire().await.map_err(|_| {
+                SolanaIndexerError::Internal("Webhook semaphore closed".to_string())
+            })?;
+
+        let mut request = self
+            .client
+            .post(url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec());
+        if let Some(key) = key {
+            request = request.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        if let Some(secret) = &self.secret {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            request = request
+                .header(DEFAULT_TIMESTAMP_HEADER, timestamp)
+                .header(DEFAULT_SIGNATURE_HEADER, sign(secret, timestamp, body)?);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| SolanaIndexerError::Connection(format!("Webhook {url}: {e}")))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        if status == StatusCode::REQUEST_TIMEOUT
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status.is_server_error()
+        {
+            // Classified by status code, 429 backing off harder
+            return Err(SolanaIndexerError::Connection(format!(
+                "Webhook {url} responded {}",
+                status.as_u16()
+            )));
+        }
+        Err(SolanaIndexerError::Data(format!(
+            "Webhook {url} rejected the event with {status}"
+        )))
+    }
+
+    /// Delivers `body` to `url`, retrying transient failures.
+    async fn deliver(&self, url: &reqwest::Url, body: &[u8], key: Option<&str>) -> Result<()> {
+        let result = retry_with_backoff(&self.backoff, &format!("Webhook {url}"), || {
+            self.post(url, body, key)
+        })
+        .await;
+        match &result {
+            Ok(()) => self.delivered.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.rejected.fetch_add(1, Ordering::Relaxed),
+        };
+        result
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        let body = serde_json::to_vec(record)
+            .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize record: {e}")))?;
+        let key = record.dedup_key.as_deref();
+        let results = futures_util::future::join_all(
+            self.urls.iter().map(|url| self.deliver(url, &body, key)),
+        )
+        .await;
+        results.into_iter().collect()
+    }
+}
+
+/// Returns the hex HMAC-SHA256 of `"{timestamp}.{body}"` under `secret`.
+fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| SolanaIndexerError::Config(e.to_string()))?;
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+    Ok(mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::webhook::WebhookVerifier;
+    use reqwest::header::HeaderMap;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Answers one request per connection with the next of `statuses`,
+    /// returning the headers and body of each request.
+    async fn serve(listener: TcpListener, statuses: Vec<u16>) -> Vec<(HeaderMap, Vec<u8>)> {
+        let mut requests = Vec::new();
+        for status in statuses {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut headers = HeaderMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(
+                        reqwest::header::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+                        value.trim().parse().unwrap(),
+                    );
+                }
+            }
+            let len: usize = headers["content-length"].to_str().unwrap().parse().unwrap();
+            let mut body = vec![0; len];
+            reader.read_exact(&mut body).await.unwrap();
+            let response =
+                format!("HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+            reader
+                .into_inner()
+                .write_all(response.as_bytes())
+                .await
+                .unwrap();
+            requests.push((headers, body));
+        }
+        requests
+    }
+
+    fn record() -> SinkRecord {
+        SinkRecord {
+            event: "TransferEvent".to_string(),
+            signature: "sig".to_string(),
+            slot: 7,
+            block_time: None,
+            payload: serde_json::json!({ "amount": 5 }),
+            dedup_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery_is_retried() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve(listener, vec![503, 200, 400]));
+        let sink = WebhookSink::new([url])?
+            .with_secret("s3cret")
+            .with_backoff(BackoffConfig {
+                base_delay_ms: 1,
+                jitter: 0.0,
+                ..BackoffConfig::default()
+            });
+
+        sink.publish(&record()).await?;
+        // Rejections are not retried
+        assert!(matches!(
+            sink.publish(&record()).await,
+            Err(SolanaIndexerError::Data(_))
+        ));
+        assert_eq!((sink.delivered(), sink.rejected()), (1, 1));
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 3);
+        for (headers, body) in &requests {
+            // One verifier per request: a retry may repeat the signature
+            WebhookVerifier::hmac("s3cret").verify(headers, body)?;
+            let sent: SinkRecord = serde_json::from_slice(body).unwrap();
+            assert_eq!(sent, record());
+        }
+        assert!(WebhookSink::new(Vec::<String>::new()).is_err());
+        assert!(WebhookSink::new(["ftp://example.com"]).is_err());
+        Ok(())
+    }
+}
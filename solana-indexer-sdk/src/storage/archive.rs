@@ -0,0 +1,156 @@
+//! Archive of raw fetched transactions.
+//!
+//! With `with_transaction_archive`, every transaction the indexer decodes is
+//! also kept in `_solana_indexer_sdk_transactions`, keyed by signature: the
+//! full `EncodedConfirmedTransactionWithStatusMeta` as gzip-compressed JSON.
+//! Decoders added later can then be run over the archive instead of fetching
+//! the same transactions from RPC again. `storage::cold` moves old rows out
+//! to object storage.
+
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use sqlx::PgPool;
+use std::io::{Read, Write};
+
+/// Name of the archive table.
+pub(crate) const ARCHIVE_TABLE: &str = "_solana_indexer_sdk_transactions";
+
+/// Outcome of `SolanaIndexer::replay`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// Transactions run through the decoders and handlers again
+    pub replayed: u64,
+    /// Transactions read from the archive
+    pub archived: u64,
+    /// Transactions fetched from RPC because they were not archived
+    pub fetched: u64,
+    /// Transactions whose replay failed; they are logged and skipped
+    pub failed: u64,
+}
+
+/// Raw transactions stored by signature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransactionArchive;
+
+impl TransactionArchive {
+    /// Stores `transaction` under `signature`. A signature already archived
+    /// is left as it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the transaction cannot be encoded or the database
+    /// write fails.
+    pub async fn store(
+        db: &PgPool,
+        signature: &str,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO {ARCHIVE_TABLE} (signature, slot, transaction)
+             VALUES ($1, $2, $3)
+             ON CONFLICT (signature) DO NOTHING"
+        ))
+        .bind(signature)
+        .bind(i64::try_from(transaction.slot).unwrap_or(i64::MAX))
+        .bind(compress(transaction)?)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads the archived transaction with `signature`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails or the stored data cannot
+    /// be decoded.
+    pub async fn load(
+        db: &PgPool,
+        signature: &str,
+    ) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>> {
+        let data: Option<Vec<u8>> = sqlx::query_scalar(&format!(
+            "SELECT transaction FROM {ARCHIVE_TABLE} WHERE signature = $1"
+        ))
+        .bind(signature)
+        .fetch_optional(db)
+        .await?;
+        data.as_deref().map(decompress).transpose()
+    }
+}
+
+/// Creates the archive table.
+#[async_trait]
+impl SchemaInitializer for TransactionArchive {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {ARCHIVE_TABLE} (
+                signature TEXT PRIMARY KEY,
+                slot BIGINT NOT NULL,
+                transaction BYTEA NOT NULL,
+                archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_archived_transactions_slot
+             ON {ARCHIVE_TABLE}(slot)"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Encodes a transaction as gzip-compressed JSON.
+fn compress(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Vec<u8>> {
+    let json =
+        serde_json::to_vec(transaction).map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&json)
+        .and_then(|()| encoder.finish())
+        .map_err(|e| SolanaIndexerError::Internal(format!("Failed to compress: {e}")))
+}
+
+/// Decodes a transaction written by `compress`.
+pub(crate) fn decompress(data: &[u8]) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    let mut json = Vec::new();
+    GzDecoder::new(data)
+        .read_to_end(&mut json)
+        .map_err(|e| SolanaIndexerError::Data(format!("Invalid archived transaction: {e}")))?;
+    serde_json::from_slice(&json)
+        .map_err(|e| SolanaIndexerError::Data(format!("Invalid archived transaction: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::TransactionEvent;
+    use crate::testing::synthetic::SyntheticSource;
+
+    #[test]
+    fn test_compression_roundtrip() -> Result<()> {
+        let TransactionEvent::FullTransaction { tx, .. } =
+            SyntheticSource::new(1).with_seed(7).next_transaction()
+        else {
+            panic!("synthetic source yields full transactions");
+        };
+        let transaction = tx.as_ref();
+        let data = compress(transaction)?;
+
+        let restored = decompress(&data)?;
+        assert_eq!(restored.slot, transaction.slot);
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(transaction).unwrap()
+        );
+        assert!(decompress(b"not gzip").is_err());
+        Ok(())
+    }
+}
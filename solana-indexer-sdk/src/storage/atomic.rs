@@ -0,0 +1,149 @@
+//! Atomic per-transaction handler writes.
+//!
+//! Handlers normally write straight to the pool, and the indexer marks the
+//! signature processed once they are done. A crash in between leaves handler
+//! rows for a signature that is indexed again on restart, and a failed mark
+//! after a partial write does the same. With `with_atomic_transactions`,
+//! writes made through `TxMetadata::execute` are held until every handler
+//! of the transaction has run, then committed in one database transaction
+//! together with the signature's processed (or tentative) marker: either
+//! all of them land or none do.
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{EventHandler, Result, TransferEvent, TxMetadata};
+//! # use async_trait::async_trait;
+//! # use sqlx::PgPool;
+//! struct TransferHandler;
+//!
+//! #[async_trait]
+//! impl EventHandler<TransferEvent> for TransferHandler {
+//!     async fn handle(&self, event: TransferEvent, context: &TxMetadata, db: &PgPool) -> Result<()> {
+//!         context
+//!             .execute(
+//!                 sqlx::query("INSERT INTO transfers (signature, amount) VALUES ($1, $2)")
+//!                     .bind(context.signature.clone())
+//!                     .bind(i64::try_from(event.amount).unwrap_or(i64::MAX)),
+//!                 db,
+//!             )
+//!             .await
+//!     }
+//! }
+//! ```
+//!
+//! Without the option, and when events are reprocessed from the spill or
+//! dead letter queue, `execute` runs the query right away. Writes a handler
+//! makes through `db` directly are never part of the transaction, and
+//! neither are the writes of an attempt that failed.
+
+use super::buffer::BufferedQuery;
+use crate::utils::error::Result;
+use sqlx::PgPool;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Handler writes of one transaction, given to handlers through
+/// `TxMetadata::writes`.
+///
+/// The default handle runs every write immediately, which is what handlers
+/// see when `with_atomic_transactions` is not set.
+#[derive(Clone, Default)]
+pub struct TxWrites {
+    held: Option<Arc<Mutex<Vec<BufferedQuery>>>>,
+}
+
+impl std::fmt::Debug for TxWrites {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxWrites")
+            .field("held", &self.held.as_ref().map(|_| self.len()))
+            .finish()
+    }
+}
+
+impl TxWrites {
+    /// Creates a handle that holds writes until the indexer commits them if
+    /// `atomic`, and runs them right away otherwise.
+    #[must_use]
+    pub(crate) fn new(atomic: bool) -> Self {
+        Self {
+            held: atomic.then(Arc::default),
+        }
+    }
+
+    /// Returns whether writes are held for an atomic commit.
+    #[must_use]
+    pub fn is_atomic(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// Returns the number of writes held.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.held.as_ref().map_or(0, |held| lock(held).len())
+    }
+
+    /// Returns whether no writes are held.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Holds `query` for the transaction's commit, or runs it on `db` right
+    /// away if writes are not atomic.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a query run right away
+    /// fails. Held queries fail at commit time instead.
+    pub async fn execute(&self, query: BufferedQuery, db: &PgPool) -> Result<()> {
+        match &self.held {
+            Some(held) => lock(held).push(query),
+            None => {
+                query.execute(db).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the writes held after the first `len`, those of a failed
+    /// handler attempt.
+    pub(crate) fn truncate(&self, len: usize) {
+        if let Some(held) = &self.held {
+            lock(held).truncate(len);
+        }
+    }
+
+    /// Removes and returns every write held.
+    pub(crate) fn take(&self) -> Vec<BufferedQuery> {
+        self.held
+            .as_ref()
+            .map(|held| std::mem::take(&mut *lock(held)))
+            .unwrap_or_default()
+    }
+}
+
+fn lock(held: &Mutex<Vec<BufferedQuery>>) -> MutexGuard<'_, Vec<BufferedQuery>> {
+    held.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_held_writes() {
+        // Never connects: held writes do not touch the pool
+        let db = PgPool::connect_lazy("postgresql://localhost/unused").unwrap();
+        let writes = TxWrites::new(true);
+        let shared = writes.clone();
+
+        writes.execute(sqlx::query("SELECT 1"), &db).await.unwrap();
+        let mark = writes.len();
+        shared.execute(sqlx::query("SELECT 2"), &db).await.unwrap();
+        assert_eq!(writes.len(), 2);
+
+        writes.truncate(mark);
+        assert_eq!(shared.len(), 1);
+        assert_eq!(writes.take().len(), 1);
+        assert!(writes.is_empty());
+        assert!(!TxWrites::default().is_atomic());
+    }
+}
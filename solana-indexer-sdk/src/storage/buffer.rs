@@ -0,0 +1,555 @@
+//! Batched writes in front of `Storage`.
+//!
+//! Committing every `mark_processed` on its own costs one round trip and one
+//! fsync per signature, which caps throughput at high TPS. `WriteBuffer`
+//! collects processed signatures, along with any inserts handlers queue
+//! through it, and writes them in a single transaction once the buffer holds
+//! `max_pending` writes or the oldest one has waited `max_delay_ms`.
+//!
+//! Cursors, the watermark and backfill progress are written through only
+//! after the buffer is flushed, so they never point past data that is still
+//! in memory. A crash loses at most the unflushed writes, and their
+//! signatures are indexed again on restart.
+
+use super::partitions::PartitionReport;
+use super::retention::{RetentionConfig, RetentionReport};
+use super::vacuum::{VacuumConfig, VacuumReport};
+use super::{ConfirmationStatus, ConfirmationSummary, DeadLetter, Storage, StorageBackend};
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use sqlx::postgres::{PgArguments, PgPool};
+use sqlx::Postgres;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// A handler insert deferred to the next flush.
+///
+/// Build it with `sqlx::query` and owned bind values.
+pub type BufferedQuery = sqlx::query::Query<'static, Postgres, PgArguments>;
+
+/// When a `WriteBuffer` commits its pending writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlushPolicy {
+    /// Flush once this many writes are pending (default: 500)
+    pub max_pending: usize,
+    /// Flush once the oldest pending write is this old, in milliseconds (default: 200)
+    pub max_delay_ms: u64,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        Self {
+            max_pending: 500,
+            max_delay_ms: 200,
+        }
+    }
+}
+
+impl FlushPolicy {
+    /// Returns the maximum age of a pending write as a `Duration`.
+    #[must_use]
+    pub fn max_delay(&self) -> Duration {
+        Duration::from_millis(self.max_delay_ms)
+    }
+}
+
+#[derive(Default)]
+struct Pending {
+    signatures: Vec<String>,
+    slots: Vec<i64>,
+    marked: HashSet<String>,
+    writes: Vec<BufferedQuery>,
+    oldest: Option<Instant>,
+}
+
+impl Pending {
+    fn len(&self) -> usize {
+        self.signatures.len() + self.writes.len()
+    }
+}
+
+/// Storage backend that batches writes to `Storage`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::{SolanaIndexer, SolanaIndexerConfig, Storage};
+/// # use solana_indexer_sdk::storage::buffer::{FlushPolicy, WriteBuffer};
+/// # use std::sync::Arc;
+/// # async fn example(config: SolanaIndexerConfig) -> Result<(), Box<dyn std::error::Error>> {
+/// let storage = Storage::new("postgresql://localhost/mydb").await?;
+/// let buffer = Arc::new(WriteBuffer::new(storage, FlushPolicy::default()));
+///
+/// let indexer = SolanaIndexer::new_with_storage(config, buffer.clone());
+/// buffer.clone().spawn(indexer.cancellation_token());
+///
+/// // Handlers holding the buffer can defer their inserts to the same transaction
+/// buffer
+///     .queue(sqlx::query("INSERT INTO transfers (signature) VALUES ($1)").bind("sig".to_string()))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WriteBuffer {
+    storage: Storage,
+    policy: FlushPolicy,
+    pending: Mutex<Pending>,
+    /// Signatures dropped by failed flushes, until a cache takes them
+    dropped: std::sync::Mutex<Vec<String>>,
+}
+
+impl WriteBuffer {
+    /// Creates a buffer in front of `storage`.
+    #[must_use]
+    pub fn new(storage: Storage, policy: FlushPolicy) -> Self {
+        Self {
+            storage,
+            policy,
+            pending: Mutex::new(Pending::default()),
+            dropped: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the flush policy.
+    #[must_use]
+    pub fn policy(&self) -> FlushPolicy {
+        self.policy
+    }
+
+    /// Returns the number of writes waiting for the next flush.
+    pub async fn pending_len(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+
+    /// Defers `query` to the next flush, where it commits together with the
+    /// processed signatures buffered so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if this write fills the
+    /// buffer and the resulting flush fails.
+    pub async fn queue(&self, query: BufferedQuery) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        pending.oldest.get_or_insert_with(Instant::now);
+        pending.writes.push(query);
+        self.flush_if_full(&mut pending).await
+    }
+
+    /// Spawns a task that flushes writes older than `max_delay_ms`, and
+    /// flushes once more when `token` is cancelled.
+    pub fn spawn(self: Arc<Self>, token: CancellationToken) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.policy.max_delay());
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => {
+                        if let Err(e) = self.flush().await {
+                            tracing::error!("Final write buffer flush failed: {e}");
+                        }
+                        break;
+                    }
+                    _ = interval.tick() => {
+                        if let Err(e) = self.flush_expired().await {
+                            tracing::error!("Write buffer flush failed: {e}");
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    async fn flush_expired(&self) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        match pending.oldest {
+            Some(oldest) if oldest.elapsed() >= self.policy.max_delay() => {
+                self.commit(&mut pending).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn flush_if_full(&self, pending: &mut Pending) -> Result<()> {
+        if pending.len() >= self.policy.max_pending.max(1) {
+            self.commit(pending).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes everything pending in one transaction.
+    ///
+    /// The buffer is emptied even if the transaction fails. Nothing from it
+    /// was committed, so the signatures read as unprocessed and are indexed
+    /// again; they are kept for `take_dropped_signatures` so a cache in
+    /// front forgets them too.
+    async fn commit(&self, pending: &mut Pending) -> Result<()> {
+        let Pending {
+            signatures,
+            slots,
+            writes,
+            ..
+        } = std::mem::take(pending);
+        if signatures.is_empty() && writes.is_empty() {
+            return Ok(());
+        }
+
+        let result = self.write(&signatures, &slots, writes).await;
+        if result.is_err() {
+            self.dropped
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .extend(signatures);
+        }
+        result
+    }
+
+    async fn write(
+        &self,
+        signatures: &[String],
+        slots: &[i64],
+        writes: Vec<BufferedQuery>,
+    ) -> Result<()> {
+        let mut tx = self.storage.pool().begin().await?;
+        for query in writes {
+            query.execute(&mut *tx).await?;
+        }
+        if !signatures.is_empty() {
+            sqlx::query(
+                "INSERT INTO _solana_indexer_sdk_processed (signature, slot)
+                 SELECT * FROM UNNEST($1::text[], $2::bigint[])
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(signatures)
+            .bind(slots)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for WriteBuffer {
+    async fn initialize(&self) -> Result<()> {
+        self.storage.initialize().await
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        if self.pending.lock().await.marked.contains(signature) {
+            return Ok(true);
+        }
+        self.storage.is_processed(signature).await
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        if !pending.marked.insert(signature.to_string()) {
+            return Ok(());
+        }
+        pending.oldest.get_or_insert_with(Instant::now);
+        pending.signatures.push(signature.to_string());
+        pending.slots.push(i64::try_from(slot).unwrap_or(i64::MAX));
+        self.flush_if_full(&mut pending).await
+    }
+
+    /// A batch is already one bulk write, so it goes straight to storage.
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.storage.mark_processed_batch(entries).await
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        self.flush().await?;
+        self.storage.get_last_processed_slot().await
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        self.flush().await?;
+        self.storage.get_last_processed_signature().await
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        Some(self.storage.pool())
+    }
+
+    /// Processed signatures and their writes join the pending writes, and
+    /// commit in the same flush.
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        if block_hash.is_some() {
+            return self
+                .storage
+                .commit_transaction(writes, signature, slot, block_hash)
+                .await;
+        }
+        let mut pending = self.pending.lock().await;
+        pending.oldest.get_or_insert_with(Instant::now);
+        pending.writes.extend(writes);
+        if pending.marked.insert(signature.to_string()) {
+            pending.signatures.push(signature.to_string());
+            pending.slots.push(i64::try_from(slot).unwrap_or(i64::MAX));
+        }
+        self.flush_if_full(&mut pending).await
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        self.storage
+            .mark_tentative(signature, slot, block_hash)
+            .await
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        self.storage.mark_finalized(slot, block_hash).await
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        self.storage.get_tentative_transactions(slot).await
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        // Buffered rows for the slot must be written before they can be removed
+        self.flush().await?;
+        self.storage.rollback_slot(slot).await
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        self.storage.get_block_hash(slot).await
+    }
+
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        self.storage
+            .cleanup_stale_tentative_transactions(slot_threshold)
+            .await
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        self.storage.get_tentative_slots_le(slot).await
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        self.flush().await?;
+        self.storage.save_backfill_progress(slot).await
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        self.storage.load_backfill_progress().await
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        self.flush().await?;
+        self.storage.mark_backfill_complete().await
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        self.storage.begin_backfill_range(range).await
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.flush().await?;
+        self.storage
+            .advance_backfill_range(range, slot, cursor, transactions)
+            .await
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.storage.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.flush().await?;
+        self.storage.save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.storage.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.flush().await?;
+        self.storage.save_snapshot_slot(program_id, slot).await
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.storage.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.flush().await?;
+        self.storage.advance_watermark(slot).await
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.storage.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.storage.record_commitment(signature, slot, level).await
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.storage.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.storage
+            .load_processed_signatures(slots, after, limit)
+            .await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.storage.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.storage.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.storage
+            .record_dead_letter(signature, slot, reason)
+            .await
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.storage.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.storage.record_usage(day, records).await
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.storage.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.storage.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        self.storage.prune(config, tip_slot).await
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.storage.vacuum(config).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        let mut pending = self.pending.lock().await;
+        self.commit(&mut pending).await
+    }
+
+    fn take_dropped_signatures(&self) -> Vec<String> {
+        std::mem::take(
+            &mut *self
+                .dropped
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_buffered_writes_commit_together() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            sqlx::query(
+                "CREATE TABLE IF NOT EXISTS write_buffer_test (signature TEXT PRIMARY KEY)",
+            )
+            .execute(storage.pool())
+            .await?;
+            sqlx::query("DELETE FROM write_buffer_test")
+                .execute(storage.pool())
+                .await?;
+            sqlx::query(
+                "DELETE FROM _solana_indexer_sdk_processed WHERE signature LIKE 'buffer_test_%'",
+            )
+            .execute(storage.pool())
+            .await?;
+
+            let buffer = WriteBuffer::new(
+                storage,
+                FlushPolicy {
+                    max_pending: 3,
+                    max_delay_ms: 60_000,
+                },
+            );
+
+            buffer.mark_processed("buffer_test_a", 1).await?;
+            buffer
+                .queue(
+                    sqlx::query("INSERT INTO write_buffer_test (signature) VALUES ($1)")
+                        .bind("buffer_test_a".to_string()),
+                )
+                .await?;
+            assert!(buffer.is_processed("buffer_test_a").await?);
+            assert!(!buffer.storage.is_processed("buffer_test_a").await?);
+            assert_eq!(buffer.pending_len().await, 2);
+
+            // The third write reaches max_pending and commits all three
+            buffer.mark_processed("buffer_test_b", 2).await?;
+            assert_eq!(buffer.pending_len().await, 0);
+            assert!(buffer.storage.is_processed("buffer_test_a").await?);
+            assert!(buffer.storage.is_processed("buffer_test_b").await?);
+            let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM write_buffer_test")
+                .fetch_one(buffer.require_pool()?)
+                .await?;
+            assert_eq!(rows, 1);
+
+            // Cursors are only written after the buffer is flushed
+            buffer.mark_processed("buffer_test_c", 3).await?;
+            buffer
+                .save_cursor("buffer_test_address", "buffer_test_c", 3)
+                .await?;
+            assert!(buffer.storage.is_processed("buffer_test_c").await?);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,596 @@
+//! In-memory cache of recently processed signatures.
+//!
+//! Every signature the indexer sees is checked with `is_processed` before it
+//! is fetched, and with RPC polling most of them were processed in an
+//! earlier, overlapping page. `ProcessedCache` sits in front of a storage
+//! backend and remembers the signatures written through it, so those checks
+//! are answered from memory. A signature missing from the cache is always
+//! looked up in the backend, so eviction only costs a query.
+//!
+//! Entries are dropped again when the backend forgets them: rolled-back
+//! slots, stale tentative transactions cleaned up, any retention run, and
+//! signatures a buffering backend dropped in a failed flush.
+
+use super::buffer::BufferedQuery;
+use super::partitions::PartitionReport;
+use super::retention::{RetentionConfig, RetentionReport};
+use super::vacuum::{VacuumConfig, VacuumReport};
+use super::{ConfirmationStatus, ConfirmationSummary, DeadLetter, StorageBackend};
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A cached signature.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    slot: u64,
+    /// Marked tentative and not finalized yet
+    tentative: bool,
+    /// Recency stamp; queue positions with an older stamp are stale
+    stamp: u64,
+}
+
+/// Bounded map of signatures, evicting the least recently used.
+#[derive(Debug, Default)]
+struct Recent {
+    capacity: usize,
+    entries: HashMap<String, Entry>,
+    /// Signatures in order of use, including stale positions
+    order: VecDeque<(String, u64)>,
+    next_stamp: u64,
+}
+
+impl Recent {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Self::default()
+        }
+    }
+
+    /// Returns `true` if `signature` is cached, marking it as recently used.
+    fn touch(&mut self, signature: &str) -> bool {
+        let stamp = self.next_stamp;
+        let Some(entry) = self.entries.get_mut(signature) else {
+            return false;
+        };
+        entry.stamp = stamp;
+        self.next_stamp += 1;
+        self.order.push_back((signature.to_string(), stamp));
+        self.evict();
+        true
+    }
+
+    fn insert(&mut self, signature: &str, slot: u64, tentative: bool) {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        self.entries.insert(
+            signature.to_string(),
+            Entry {
+                slot,
+                tentative,
+                stamp,
+            },
+        );
+        self.order.push_back((signature.to_string(), stamp));
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some((signature, stamp)) = self.order.pop_front() else {
+                break;
+            };
+            if self
+                .entries
+                .get(&signature)
+                .is_some_and(|e| e.stamp == stamp)
+            {
+                self.entries.remove(&signature);
+            }
+        }
+        // Repeated hits leave stale positions behind; drop them before the
+        // queue outgrows the map by too much
+        if self.order.len() > self.capacity.saturating_mul(2).max(64) {
+            let entries = &self.entries;
+            self.order.retain(|(signature, stamp)| {
+                entries.get(signature).is_some_and(|e| e.stamp == *stamp)
+            });
+        }
+    }
+
+    fn remove(&mut self, signature: &str) {
+        self.entries.remove(signature);
+    }
+
+    fn retain(&mut self, keep: impl Fn(&Entry) -> bool) {
+        self.entries.retain(|_, entry| keep(entry));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Storage backend wrapper answering `is_processed` from memory for
+/// signatures recently written through it.
+///
+/// Every other call goes straight to the wrapped backend.
+pub struct ProcessedCache {
+    inner: Arc<dyn StorageBackend>,
+    recent: Mutex<Recent>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ProcessedCache {
+    /// Wraps `inner`, remembering up to `capacity` signatures.
+    #[must_use]
+    pub fn new(inner: Arc<dyn StorageBackend>, capacity: usize) -> Self {
+        Self {
+            inner,
+            recent: Mutex::new(Recent::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the number of `is_processed` checks answered from memory.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of `is_processed` checks passed to the backend.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of cached signatures.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns `true` if no signature is cached.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Processed cache Stats: {} signatures | Hits: {} | Misses: {}",
+                self.len(),
+                self.hits(),
+                self.misses()
+            ),
+        );
+    }
+
+    /// Drops tentative entries more than `slot_threshold` slots behind the
+    /// last processed slot.
+    async fn forget_stale_tentative(&self, slot_threshold: u64) -> Result<()> {
+        let current_slot = self.inner.get_last_processed_slot().await?.unwrap_or(0);
+        if let Some(cutoff_slot) = current_slot.checked_sub(slot_threshold) {
+            self.lock()
+                .retain(|entry| !(entry.tentative && entry.slot < cutoff_slot));
+        }
+        Ok(())
+    }
+
+    /// Caches `signatures` before `write` runs, so a buffered flush that
+    /// later drops them always finds them here to forget. They are forgotten
+    /// right away if `write` fails.
+    async fn write_through(
+        &self,
+        signatures: impl Iterator<Item = (&str, u64)>,
+        tentative: bool,
+        write: impl std::future::Future<Output = Result<()>>,
+    ) -> Result<()> {
+        let signatures: Vec<_> = signatures.collect();
+        {
+            let mut recent = self.lock();
+            for (signature, slot) in &signatures {
+                recent.insert(signature, *slot, tentative);
+            }
+        }
+        let result = write.await;
+        if result.is_err() {
+            let mut recent = self.lock();
+            for (signature, _) in &signatures {
+                recent.remove(signature);
+            }
+        }
+        result
+    }
+
+    /// Forgets the signatures the backend dropped before storing them.
+    fn forget_dropped(&self) {
+        let dropped = self.inner.take_dropped_signatures();
+        if !dropped.is_empty() {
+            let mut recent = self.lock();
+            for signature in &dropped {
+                recent.remove(signature);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Recent> {
+        self.recent
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for ProcessedCache {
+    async fn initialize(&self) -> Result<()> {
+        self.inner.initialize().await
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        self.forget_dropped();
+        if self.lock().touch(signature) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(true);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.inner.is_processed(signature).await
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        self.write_through(
+            std::iter::once((signature, slot)),
+            false,
+            self.inner.mark_processed(signature, slot),
+        )
+        .await
+    }
+
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.write_through(
+            entries
+                .iter()
+                .map(|(signature, slot)| (signature.as_str(), *slot)),
+            false,
+            self.inner.mark_processed_batch(entries),
+        )
+        .await
+    }
+
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        self.write_through(
+            std::iter::once((signature, slot)),
+            block_hash.is_some(),
+            self.inner
+                .commit_transaction(writes, signature, slot, block_hash),
+        )
+        .await
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        self.inner.get_last_processed_slot().await
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        self.inner.get_last_processed_signature().await
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        self.inner.pool()
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        self.inner
+            .mark_tentative(signature, slot, block_hash)
+            .await?;
+        self.lock().insert(signature, slot, true);
+        Ok(())
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        self.inner.mark_finalized(slot, block_hash).await?;
+        for entry in self.lock().entries.values_mut() {
+            if entry.slot == slot {
+                entry.tentative = false;
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        self.inner.get_tentative_transactions(slot).await
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        // Forget the slot first, so a check racing the rollback misses the
+        // cache rather than reporting a rolled-back signature
+        self.lock().retain(|entry| entry.slot != slot);
+        self.inner.rollback_slot(slot).await
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        self.inner.get_block_hash(slot).await
+    }
+
+    /// `slot_threshold` is a distance behind the last processed slot, as in
+    /// the backends. The cutoff is worked out again after the backend ran,
+    /// since the last processed slot may have moved on in between.
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        self.forget_stale_tentative(slot_threshold).await?;
+        let deleted = self
+            .inner
+            .cleanup_stale_tentative_transactions(slot_threshold)
+            .await?;
+        if deleted > 0 {
+            self.forget_stale_tentative(slot_threshold).await?;
+        }
+        Ok(deleted)
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        self.inner.get_tentative_slots_le(slot).await
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        self.inner.save_backfill_progress(slot).await
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        self.inner.load_backfill_progress().await
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        self.inner.mark_backfill_complete().await
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        self.inner.begin_backfill_range(range).await
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.inner
+            .advance_backfill_range(range, slot, cursor, transactions)
+            .await
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.inner.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.inner.save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.inner.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.inner.save_snapshot_slot(program_id, slot).await
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.inner.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.inner.advance_watermark(slot).await
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.inner.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.inner.record_commitment(signature, slot, level).await
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner
+            .load_processed_signatures(slots, after, limit)
+            .await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.inner.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.inner.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.inner.record_dead_letter(signature, slot, reason).await
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.inner.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.inner.record_usage(day, records).await
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.inner.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.inner.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        let report = self.inner.prune(config, tip_slot).await;
+        self.lock().clear();
+        report
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.inner.vacuum(config).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_evicts_least_recently_used() {
+        let mut recent = Recent::new(2);
+        recent.insert("a", 1, false);
+        recent.insert("b", 2, false);
+        assert!(recent.touch("a"));
+        recent.insert("c", 3, false);
+
+        assert!(recent.touch("a"));
+        assert!(!recent.touch("b"));
+        assert!(recent.touch("c"));
+        assert_eq!(recent.entries.len(), 2);
+    }
+
+    #[test]
+    fn test_recent_queue_stays_bounded() {
+        let mut recent = Recent::new(4);
+        recent.insert("a", 1, false);
+        for _ in 0..1_000 {
+            assert!(recent.touch("a"));
+        }
+        assert!(recent.order.len() <= 128);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_cache_tracks_backend() -> Result<()> {
+        let inner = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?;
+        inner.initialize().await?;
+        let cache = ProcessedCache::new(Arc::new(inner), 100);
+
+        assert!(!cache.is_processed("sig_a").await?);
+        cache.mark_processed("sig_a", 10).await?;
+        cache.mark_tentative("sig_b", 12, "hash_12").await?;
+        assert!(cache.is_processed("sig_a").await?);
+        assert!(cache.is_processed("sig_b").await?);
+        assert_eq!((cache.hits(), cache.misses()), (2, 1));
+
+        // A rolled-back signature is looked up again and found missing
+        cache.rollback_slot(12).await?;
+        assert!(!cache.is_processed("sig_b").await?);
+        assert_eq!(cache.misses(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_flush_forgets_buffered_signatures() -> Result<()> {
+        use crate::storage::buffer::{FlushPolicy, WriteBuffer};
+        use crate::storage::Storage;
+
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            sqlx::query(
+                "DELETE FROM _solana_indexer_sdk_processed WHERE signature = 'cache_flush_test'",
+            )
+            .execute(storage.pool())
+            .await?;
+
+            let buffer = Arc::new(WriteBuffer::new(storage, FlushPolicy::default()));
+            let cache = ProcessedCache::new(buffer.clone(), 100);
+
+            cache.mark_processed("cache_flush_test", 1).await?;
+            assert!(cache.is_processed("cache_flush_test").await?);
+
+            // A bad handler insert fails the flush and takes the signature with it
+            buffer
+                .queue(sqlx::query(
+                    "INSERT INTO cache_flush_test_missing VALUES (1)",
+                ))
+                .await?;
+            assert!(cache.flush().await.is_err());
+            assert!(!cache.is_processed("cache_flush_test").await?);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_cleanup_uses_distance_behind_last_slot() -> Result<()> {
+        let inner = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?;
+        inner.initialize().await?;
+        let cache = ProcessedCache::new(Arc::new(inner), 100);
+
+        cache.mark_processed("sig_tip", 300_000_000).await?;
+        cache
+            .mark_tentative("sig_old", 299_999_000, "hash_old")
+            .await?;
+        cache
+            .mark_tentative("sig_new", 299_999_990, "hash_new")
+            .await?;
+
+        // Only the tentative signature more than 150 slots behind goes
+        assert_eq!(cache.cleanup_stale_tentative_transactions(150).await?, 1);
+        assert!(cache.is_processed("sig_new").await?);
+        assert!(cache.is_processed("sig_tip").await?);
+        assert_eq!(cache.misses(), 0);
+        assert!(!cache.is_processed("sig_old").await?);
+        assert_eq!(cache.misses(), 1);
+        Ok(())
+    }
+}
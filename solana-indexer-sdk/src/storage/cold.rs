@@ -0,0 +1,513 @@
+//! Export of old archived transactions to cold storage.
+//!
+//! The transaction archive kept with `with_transaction_archive` grows by a
+//! few kilobytes per transaction. `ColdArchive` moves rows archived more
+//! than `max_age_days` ago out of the database into segments in a
+//! `SegmentStore`, such as a directory on a mounted bucket, and deletes
+//! them from `_solana_indexer_sdk_transactions`. `SolanaIndexer::replay`
+//! reads exported transactions back from their segment, so the hot database
+//! stays small without giving up replays.
+//!
+//! Rows are partitioned by slot: each segment holds the transactions of
+//! `slots_per_segment` consecutive slots as gzip-compressed JSON lines,
+//! named after its range (`segments/000250000000-000250099999.jsonl.gz`).
+//! `manifest.json` lists every segment with its slot range, transaction
+//! count and size. A range exported again, because late rows aged out after
+//! it, is merged into the existing segment.
+//!
+//! A segment and the manifest are written before their rows are deleted, so
+//! an export interrupted halfway leaves rows in both places rather than in
+//! neither.
+
+use super::archive::{self, ARCHIVE_TABLE};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the manifest object.
+pub const MANIFEST_NAME: &str = "manifest.json";
+
+/// Pause between exports.
+const EXPORT_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Object storage for archive segments.
+#[async_trait]
+pub trait SegmentStore: Send + Sync + 'static {
+    /// Stores `data` under `name`, replacing any object with that name.
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()>;
+
+    /// Loads the object stored under `name`, if any.
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>>;
+}
+
+/// Segment store in a local directory, or a bucket mounted as one.
+#[derive(Debug, Clone)]
+pub struct DirectorySegmentStore {
+    root: PathBuf,
+}
+
+impl DirectorySegmentStore {
+    /// Creates a store writing below `root`, which is created on first use.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait]
+impl SegmentStore for DirectorySegmentStore {
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<()> {
+        let path = self.root.join(name);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error("create", parent, &e))?;
+        }
+        // Written aside and renamed, so readers never see half an object
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, data)
+            .await
+            .map_err(|e| io_error("write", &partial, &e))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| io_error("write", &path, &e))
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.root.join(name);
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(io_error("read", &path, &e)),
+        }
+    }
+}
+
+/// Cold archive settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColdArchiveConfig {
+    /// Days a transaction stays in the database after it was archived
+    pub max_age_days: u32,
+    /// Consecutive slots per segment
+    pub slots_per_segment: u64,
+}
+
+impl Default for ColdArchiveConfig {
+    /// 30 days, segments of 100,000 slots (about 11 hours).
+    fn default() -> Self {
+        Self {
+            max_age_days: 30,
+            slots_per_segment: 100_000,
+        }
+    }
+}
+
+/// A segment listed in the manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentInfo {
+    /// Object name in the segment store
+    pub name: String,
+    /// First slot of the segment's range
+    pub first_slot: u64,
+    /// Last slot of the segment's range
+    pub last_slot: u64,
+    /// Transactions in the segment
+    pub transactions: u64,
+    /// Compressed size, in bytes
+    pub bytes: u64,
+}
+
+/// Index of the exported segments, ordered by slot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentManifest {
+    /// Segments by first slot
+    pub segments: Vec<SegmentInfo>,
+}
+
+impl SegmentManifest {
+    /// Returns the segment covering `slot`, if one was exported.
+    #[must_use]
+    pub fn segment_for(&self, slot: u64) -> Option<&SegmentInfo> {
+        let index = self
+            .segments
+            .partition_point(|segment| segment.first_slot <= slot);
+        self.segments
+            .get(index.checked_sub(1)?)
+            .filter(|segment| slot <= segment.last_slot)
+    }
+
+    /// Adds `segment`, replacing the entry for the same range.
+    fn upsert(&mut self, segment: SegmentInfo) {
+        match self
+            .segments
+            .binary_search_by_key(&segment.first_slot, |s| s.first_slot)
+        {
+            Ok(index) => self.segments[index] = segment,
+            Err(index) => self.segments.insert(index, segment),
+        }
+    }
+}
+
+/// Outcome of one export.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColdArchiveReport {
+    /// Segments written or extended
+    pub segments: u64,
+    /// Transactions moved out of the database
+    pub transactions: u64,
+    /// Compressed bytes written
+    pub bytes: u64,
+}
+
+/// One line of a segment.
+#[derive(Serialize, Deserialize)]
+struct SegmentRow {
+    signature: String,
+    slot: u64,
+    transaction: EncodedConfirmedTransactionWithStatusMeta,
+}
+
+/// A transaction read back from a segment.
+type ArchivedTransaction = Arc<EncodedConfirmedTransactionWithStatusMeta>;
+
+/// Transactions of the most recently read segment, by signature.
+type CachedSegment = (String, Arc<HashMap<String, ArchivedTransaction>>);
+
+/// Moves old archived transactions into slot-partitioned segments and
+/// reads them back.
+pub struct ColdArchive {
+    store: Arc<dyn SegmentStore>,
+    config: ColdArchiveConfig,
+    exported: AtomicU64,
+    cache: Mutex<Option<CachedSegment>>,
+}
+
+impl std::fmt::Debug for ColdArchive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ColdArchive")
+            .field("config", &self.config)
+            .field("exported", &self.exported)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ColdArchive {
+    /// Creates an archive exporting to `store`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the age or segment size
+    /// is zero.
+    pub fn new(store: Arc<dyn SegmentStore>, config: ColdArchiveConfig) -> Result<Self> {
+        if config.max_age_days == 0 || config.slots_per_segment == 0 {
+            return Err(SolanaIndexerError::Config(
+                "Cold archive max_age_days and slots_per_segment must be greater than zero"
+                    .to_string(),
+            ));
+        }
+        Ok(Self {
+            store,
+            config,
+            exported: AtomicU64::new(0),
+            cache: Mutex::new(None),
+        })
+    }
+
+    /// Returns the number of transactions exported since startup.
+    #[must_use]
+    pub fn exported(&self) -> u64 {
+        self.exported.load(Ordering::Relaxed)
+    }
+
+    /// Returns the slot range of the segment holding `slot`.
+    fn range(&self, slot: u64) -> (u64, u64) {
+        let size = self.config.slots_per_segment;
+        let first = slot - slot % size;
+        (first, first.saturating_add(size - 1))
+    }
+
+    /// Reads the manifest, empty if nothing was exported yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store cannot be read or the manifest is
+    /// invalid.
+    pub async fn manifest(&self) -> Result<SegmentManifest> {
+        let Some(data) = self.store.get(MANIFEST_NAME).await? else {
+            return Ok(SegmentManifest::default());
+        };
+        serde_json::from_slice(&data)
+            .map_err(|e| SolanaIndexerError::Data(format!("Invalid segment manifest: {e}")))
+    }
+
+    /// Moves every transaction archived more than `max_age_days` ago out of
+    /// the database, one segment at a time, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive table or the store cannot be read or
+    /// written. Segments finished before it keep their rows exported.
+    pub async fn export(&self, db: &PgPool) -> Result<ColdArchiveReport> {
+        let mut report = ColdArchiveReport::default();
+        let mut manifest = self.manifest().await?;
+        let aged = format!(
+            "archived_at < NOW() - make_interval(days => {})",
+            self.config.max_age_days
+        );
+
+        loop {
+            let oldest: Option<i64> = sqlx::query_scalar(&format!(
+                "SELECT MIN(slot) FROM {ARCHIVE_TABLE} WHERE {aged}"
+            ))
+            .fetch_one(db)
+            .await?;
+            let Some(oldest) = oldest else {
+                return Ok(report);
+            };
+            let (first_slot, last_slot) = self.range(u64::try_from(oldest).unwrap_or(0));
+
+            let rows: Vec<(String, i64, Vec<u8>)> = sqlx::query_as(&format!(
+                "SELECT signature, slot, transaction FROM {ARCHIVE_TABLE}
+                 WHERE {aged} AND slot BETWEEN $1 AND $2"
+            ))
+            .bind(i64::try_from(first_slot).unwrap_or(i64::MAX))
+            .bind(i64::try_from(last_slot).unwrap_or(i64::MAX))
+            .fetch_all(db)
+            .await?;
+
+            let mut segment = BTreeMap::new();
+            if let Some(existing) = manifest.segment_for(first_slot) {
+                if let Some(data) = self.store.get(&existing.name).await? {
+                    for row in read_segment(&data)? {
+                        segment.insert((row.slot, row.signature.clone()), row);
+                    }
+                }
+            }
+            let signatures: Vec<String> = rows.iter().map(|(sig, _, _)| sig.clone()).collect();
+            for (signature, slot, data) in rows {
+                let row = SegmentRow {
+                    slot: u64::try_from(slot).unwrap_or(0),
+                    transaction: archive::decompress(&data)?,
+                    signature,
+                };
+                segment.insert((row.slot, row.signature.clone()), row);
+            }
+
+            let name = segment_name(first_slot, last_slot);
+            let data = write_segment(segment.values())?;
+            let bytes = data.len() as u64;
+            self.store.put(&name, data).await?;
+            manifest.upsert(SegmentInfo {
+                name,
+                first_slot,
+                last_slot,
+                transactions: segment.len() as u64,
+                bytes,
+            });
+            let manifest_json = serde_json::to_vec_pretty(&manifest)
+                .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+            self.store.put(MANIFEST_NAME, manifest_json).await?;
+            if let Ok(mut cache) = self.cache.lock() {
+                *cache = None;
+            }
+
+            sqlx::query(&format!(
+                "DELETE FROM {ARCHIVE_TABLE} WHERE signature = ANY($1)"
+            ))
+            .bind(&signatures)
+            .execute(db)
+            .await?;
+
+            let exported = signatures.len() as u64;
+            self.exported.fetch_add(exported, Ordering::Relaxed);
+            report.segments += 1;
+            report.transactions += exported;
+            report.bytes += bytes;
+        }
+    }
+
+    /// Loads the exported transaction with `signature` from the segment
+    /// covering `slot`, if any.
+    ///
+    /// The last segment read is kept in memory, so loading the transactions
+    /// of a slot range in order reads each segment once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest or segment cannot be read.
+    pub async fn load(&self, slot: u64, signature: &str) -> Result<Option<ArchivedTransaction>> {
+        let manifest = self.manifest().await?;
+        let Some(segment) = manifest.segment_for(slot) else {
+            return Ok(None);
+        };
+
+        let cached = self
+            .cache
+            .lock()
+            .ok()
+            .and_then(|cache| cache.clone())
+            .filter(|(name, _)| *name == segment.name);
+        let transactions = match cached {
+            Some((_, transactions)) => transactions,
+            None => {
+                let Some(data) = self.store.get(&segment.name).await? else {
+                    return Ok(None);
+                };
+                let transactions: Arc<HashMap<_, _>> = Arc::new(
+                    read_segment(&data)?
+                        .into_iter()
+                        .map(|row| (row.signature, Arc::new(row.transaction)))
+                        .collect(),
+                );
+                if let Ok(mut cache) = self.cache.lock() {
+                    *cache = Some((segment.name.clone(), transactions.clone()));
+                }
+                transactions
+            }
+        };
+        Ok(transactions.get(signature).cloned())
+    }
+}
+
+/// Returns the object name of the segment covering `first..=last`.
+fn segment_name(first: u64, last: u64) -> String {
+    format!("segments/{first:012}-{last:012}.jsonl.gz")
+}
+
+/// Encodes rows as gzip-compressed JSON lines.
+fn write_segment<'a>(rows: impl Iterator<Item = &'a SegmentRow>) -> Result<Vec<u8>> {
+    let compress_error =
+        |e: std::io::Error| SolanaIndexerError::Internal(format!("Failed to compress: {e}"));
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for row in rows {
+        serde_json::to_writer(&mut encoder, row)
+            .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+        encoder.write_all(b"\n").map_err(compress_error)?;
+    }
+    encoder.finish().map_err(compress_error)
+}
+
+/// Decodes a segment written by `write_segment`.
+fn read_segment(data: &[u8]) -> Result<Vec<SegmentRow>> {
+    BufReader::new(GzDecoder::new(data))
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
+        .map(|line| {
+            let line =
+                line.map_err(|e| SolanaIndexerError::Data(format!("Invalid segment: {e}")))?;
+            serde_json::from_str(&line)
+                .map_err(|e| SolanaIndexerError::Data(format!("Invalid segment row: {e}")))
+        })
+        .collect()
+}
+
+fn io_error(action: &str, path: &Path, error: &std::io::Error) -> SolanaIndexerError {
+    SolanaIndexerError::Internal(format!(
+        "Failed to {action} segment {}: {error}",
+        path.display()
+    ))
+}
+
+/// Exports aged transactions every hour until `cancellation_token` fires.
+pub fn spawn_cold_archive(
+    archive: Arc<ColdArchive>,
+    db: PgPool,
+    cancellation_token: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+        loop {
+            tokio::select! {
+                () = cancellation_token.cancelled() => break,
+                _ = interval.tick() => match archive.export(&db).await {
+                    Ok(report) if report.transactions > 0 => logging::log(
+                        logging::LogLevel::Info,
+                        &format!(
+                            "Exported {} archived transactions ({} bytes) to {} segments",
+                            report.transactions, report.bytes, report.segments
+                        ),
+                    ),
+                    Ok(_) => {}
+                    Err(e) => logging::log_error("Cold archive error", &e.to_string()),
+                },
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streams::TransactionEvent;
+    use crate::testing::synthetic::SyntheticSource;
+
+    fn segment(first_slot: u64, last_slot: u64) -> SegmentInfo {
+        SegmentInfo {
+            name: segment_name(first_slot, last_slot),
+            first_slot,
+            last_slot,
+            transactions: 1,
+            bytes: 1,
+        }
+    }
+
+    #[test]
+    fn test_manifest_lookup() {
+        let mut manifest = SegmentManifest::default();
+        manifest.upsert(segment(200, 299));
+        manifest.upsert(segment(0, 99));
+        manifest.upsert(segment(200, 299));
+
+        assert_eq!(manifest.segments.len(), 2);
+        assert_eq!(manifest.segment_for(0).unwrap().first_slot, 0);
+        assert_eq!(manifest.segment_for(250).unwrap().first_slot, 200);
+        assert!(manifest.segment_for(150).is_none());
+        assert!(manifest.segment_for(300).is_none());
+        assert_eq!(
+            manifest.segments[1].name,
+            "segments/000000000200-000000000299.jsonl.gz"
+        );
+    }
+
+    #[test]
+    fn test_segment_roundtrip() -> Result<()> {
+        let mut source = SyntheticSource::new(1).with_seed(3);
+        let rows: Vec<SegmentRow> = (0..3)
+            .map(|_| {
+                let TransactionEvent::FullTransaction {
+                    signature,
+                    slot,
+                    tx,
+                } = source.next_transaction()
+                else {
+                    panic!("synthetic source yields full transactions");
+                };
+                SegmentRow {
+                    signature: signature.to_string(),
+                    slot,
+                    transaction: Arc::try_unwrap(tx).expect("transaction is not shared"),
+                }
+            })
+            .collect();
+
+        let restored = read_segment(&write_segment(rows.iter())?)?;
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored[2].signature, rows[2].signature);
+        assert_eq!(restored[2].transaction.slot, rows[2].transaction.slot);
+        assert!(read_segment(b"not gzip").is_err());
+        Ok(())
+    }
+}
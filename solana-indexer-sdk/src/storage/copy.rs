@@ -0,0 +1,327 @@
+//! Bulk loading with `COPY ... FROM STDIN` in binary format.
+//!
+//! Inserting rows one statement at a time is dominated by round trips once a
+//! backfill reaches millions of rows. `COPY` streams a whole batch in one
+//! command instead. `BulkLoader` exposes this to handlers: build the rows
+//! with `CopyRows` and hand them to `copy` (plain append) or
+//! `copy_ignoring_conflicts` (stages through a temporary table so existing
+//! keys are skipped, like `ON CONFLICT DO NOTHING`).
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use sqlx::{PgConnection, PgPool};
+
+/// Header of the binary `COPY` format: signature, flags and extension length.
+const HEADER: &[u8] = b"PGCOPY\n\xff\r\n\0\0\0\0\0\0\0\0\0";
+
+/// Trailer of the binary `COPY` format: a field count of -1.
+const TRAILER: &[u8] = &(-1i16).to_be_bytes();
+
+/// A single column value in a `COPY` row.
+///
+/// Each variant is encoded in the binary representation of the matching
+/// `PostgreSQL` type, so the target column must have that type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyValue {
+    /// SQL `NULL`
+    Null,
+    /// `BOOLEAN`
+    Bool(bool),
+    /// `INTEGER`
+    Int4(i32),
+    /// `BIGINT`
+    Int8(i64),
+    /// `DOUBLE PRECISION`
+    Float8(f64),
+    /// `TEXT` or `VARCHAR`
+    Text(String),
+    /// `BYTEA`
+    Bytea(Vec<u8>),
+}
+
+impl CopyValue {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        let payload: &[u8] = match self {
+            Self::Null => {
+                buf.extend_from_slice(&(-1i32).to_be_bytes());
+                return;
+            }
+            Self::Bool(v) => &[u8::from(*v)],
+            Self::Int4(v) => &v.to_be_bytes(),
+            Self::Int8(v) => &v.to_be_bytes(),
+            Self::Float8(v) => &v.to_be_bytes(),
+            Self::Text(v) => v.as_bytes(),
+            Self::Bytea(v) => v,
+        };
+        let len = i32::try_from(payload.len()).unwrap_or(i32::MAX);
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(payload);
+    }
+}
+
+impl From<bool> for CopyValue {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i32> for CopyValue {
+    fn from(v: i32) -> Self {
+        Self::Int4(v)
+    }
+}
+
+impl From<i64> for CopyValue {
+    fn from(v: i64) -> Self {
+        Self::Int8(v)
+    }
+}
+
+/// Slots and amounts above `i64::MAX` are clamped, as elsewhere in storage.
+impl From<u64> for CopyValue {
+    fn from(v: u64) -> Self {
+        Self::Int8(i64::try_from(v).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<f64> for CopyValue {
+    fn from(v: f64) -> Self {
+        Self::Float8(v)
+    }
+}
+
+impl From<String> for CopyValue {
+    fn from(v: String) -> Self {
+        Self::Text(v)
+    }
+}
+
+impl From<&str> for CopyValue {
+    fn from(v: &str) -> Self {
+        Self::Text(v.to_string())
+    }
+}
+
+impl From<Vec<u8>> for CopyValue {
+    fn from(v: Vec<u8>) -> Self {
+        Self::Bytea(v)
+    }
+}
+
+impl<T: Into<CopyValue>> From<Option<T>> for CopyValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(Self::Null, Into::into)
+    }
+}
+
+/// Rows encoded for a binary `COPY`.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::storage::copy::{CopyRows, CopyValue};
+///
+/// let mut rows = CopyRows::new(2);
+/// rows.push(vec![CopyValue::from("sig1"), CopyValue::from(42u64)])?;
+/// assert_eq!(rows.len(), 1);
+/// # Ok::<(), solana_indexer_sdk::SolanaIndexerError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct CopyRows {
+    columns: usize,
+    rows: usize,
+    buf: Vec<u8>,
+}
+
+impl CopyRows {
+    /// Creates an empty batch of rows with `columns` values each.
+    #[must_use]
+    pub fn new(columns: usize) -> Self {
+        Self {
+            columns,
+            rows: 0,
+            buf: HEADER.to_vec(),
+        }
+    }
+
+    /// Appends a row.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Data` if the row does not have
+    /// exactly as many values as the batch has columns.
+    pub fn push(&mut self, row: Vec<CopyValue>) -> Result<()> {
+        if row.len() != self.columns {
+            return Err(SolanaIndexerError::Data(format!(
+                "COPY row has {} values, expected {}",
+                row.len(),
+                self.columns
+            )));
+        }
+        let columns = i16::try_from(self.columns).map_err(|_| {
+            SolanaIndexerError::Data(format!("COPY supports at most {} columns", i16::MAX))
+        })?;
+        self.buf.extend_from_slice(&columns.to_be_bytes());
+        for value in &row {
+            value.encode(&mut self.buf);
+        }
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Returns the number of rows in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns `true` if the batch has no rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        self.buf.extend_from_slice(TRAILER);
+        self.buf
+    }
+}
+
+/// Loads batches of rows into `PostgreSQL` tables with `COPY`.
+///
+/// Backfill handlers receive the database pool and can wrap it:
+///
+/// ```no_run
+/// # use solana_indexer_sdk::storage::copy::{BulkLoader, CopyRows};
+/// # async fn example(db: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> {
+/// let mut rows = CopyRows::new(3);
+/// rows.push(vec!["alice".into(), "bob".into(), 1_000u64.into()])?;
+/// BulkLoader::new(db.clone())
+///     .copy_ignoring_conflicts("transfers", &["from_wallet", "to_wallet", "amount"], rows)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BulkLoader {
+    pool: PgPool,
+}
+
+impl BulkLoader {
+    /// Creates a loader writing through `pool`.
+    #[must_use]
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Appends `rows` to `table`, returning the number of rows copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the copy fails, for
+    /// example because a row violates a unique constraint. Nothing is
+    /// written in that case.
+    pub async fn copy(&self, table: &str, columns: &[&str], rows: CopyRows) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let mut conn = self.pool.acquire().await?;
+        copy_in(&mut conn, table, columns, rows).await
+    }
+
+    /// Inserts `rows` into `table`, skipping rows that conflict with
+    /// existing ones. Returns the number of rows inserted.
+    ///
+    /// The rows are copied into a temporary table and moved over with
+    /// `INSERT ... SELECT ... ON CONFLICT DO NOTHING` in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the copy or the insert
+    /// fails. Nothing is written in that case.
+    pub async fn copy_ignoring_conflicts(
+        &self,
+        table: &str,
+        columns: &[&str],
+        rows: CopyRows,
+    ) -> Result<u64> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let mut tx = self.pool.begin().await?;
+        let inserted = copy_ignoring_conflicts(&mut tx, table, columns, rows).await?;
+        tx.commit().await?;
+        Ok(inserted)
+    }
+}
+
+/// Copies `rows` into `table` over `conn`.
+pub(crate) async fn copy_in(
+    conn: &mut PgConnection,
+    table: &str,
+    columns: &[&str],
+    rows: CopyRows,
+) -> Result<u64> {
+    let statement = format!(
+        "COPY {table} ({}) FROM STDIN WITH (FORMAT BINARY)",
+        columns.join(", ")
+    );
+    let mut copy = conn.copy_in_raw(&statement).await?;
+    if let Err(e) = copy.send(rows.finish()).await {
+        let _ = copy.abort(e.to_string()).await;
+        return Err(e.into());
+    }
+    Ok(copy.finish().await?)
+}
+
+/// Copies `rows` into `table` through a temporary staging table, skipping
+/// conflicting rows. Should run inside a transaction, so a failed insert
+/// leaves neither staged nor partial rows behind.
+pub(crate) async fn copy_ignoring_conflicts(
+    conn: &mut PgConnection,
+    table: &str,
+    columns: &[&str],
+    rows: CopyRows,
+) -> Result<u64> {
+    let staging = format!("_solana_indexer_sdk_copy_{}", table.replace('.', "_"));
+    let columns = columns.join(", ");
+
+    sqlx::query(&format!(
+        "CREATE TEMP TABLE {staging} (LIKE {table} INCLUDING DEFAULTS)"
+    ))
+    .execute(&mut *conn)
+    .await?;
+    copy_in(&mut *conn, &staging, &[&columns], rows).await?;
+    let inserted = sqlx::query(&format!(
+        "INSERT INTO {table} ({columns}) SELECT {columns} FROM {staging} ON CONFLICT DO NOTHING"
+    ))
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+    sqlx::query(&format!("DROP TABLE {staging}"))
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_encoding() -> Result<()> {
+        let mut rows = CopyRows::new(3);
+        rows.push(vec!["ab".into(), 7u64.into(), CopyValue::Null])?;
+        assert!(rows.push(vec![CopyValue::Null]).is_err());
+        assert_eq!(rows.len(), 1);
+
+        let mut expected = HEADER.to_vec();
+        expected.extend_from_slice(&3i16.to_be_bytes());
+        expected.extend_from_slice(&[0, 0, 0, 2, b'a', b'b']);
+        expected.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 0, 0, 0, 0, 7]);
+        expected.extend_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        expected.extend_from_slice(&[0xff, 0xff]);
+        assert_eq!(rows.finish(), expected);
+        Ok(())
+    }
+}
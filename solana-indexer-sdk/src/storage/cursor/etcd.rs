@@ -0,0 +1,143 @@
+//! etcd cursor store.
+//!
+//! `EtcdCursorStore` talks to etcd's v3 JSON gateway (`/v3/kv/put` and
+//! `/v3/kv/range`) over HTTP, so it needs no gRPC toolchain. Each cursor is
+//! stored at `<prefix><address>` as a small JSON document holding the
+//! signature and slot.
+
+use super::CursorStore;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// Key prefix used unless `with_prefix` sets another.
+const DEFAULT_PREFIX: &str = "/solana_indexer_sdk/cursors/";
+
+/// A cursor as stored in etcd.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredCursor {
+    signature: String,
+    slot: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RangeResponse {
+    #[serde(default)]
+    kvs: Vec<KeyValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeyValue {
+    #[serde(default)]
+    value: String,
+}
+
+/// Keeps polling cursors in etcd.
+#[derive(Debug, Clone)]
+pub struct EtcdCursorStore {
+    http: reqwest::Client,
+    endpoint: String,
+    prefix: String,
+}
+
+impl EtcdCursorStore {
+    /// Uses the etcd member at `endpoint`, e.g. `http://127.0.0.1:2379`.
+    #[must_use]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            prefix: DEFAULT_PREFIX.to_string(),
+        }
+    }
+
+    /// Prefixes keys with `prefix` instead of `/solana_indexer_sdk/cursors/`,
+    /// e.g. to keep the cursors of several indexers apart.
+    #[must_use]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, address: &str) -> String {
+        BASE64.encode(format!("{}{address}", self.prefix))
+    }
+
+    async fn post(&self, path: &str, body: serde_json::Value) -> Result<reqwest::Response> {
+        self.http
+            .post(format!("{}{path}", self.endpoint))
+            .json(&body)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| SolanaIndexerError::Connection(format!("etcd error: {e}")))
+    }
+}
+
+#[async_trait]
+impl CursorStore for EtcdCursorStore {
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        let value = serde_json::to_string(&StoredCursor {
+            signature: signature.to_string(),
+            slot,
+        })
+        .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+        self.post(
+            "/v3/kv/put",
+            serde_json::json!({ "key": self.key(address), "value": BASE64.encode(value) }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        let response: RangeResponse = self
+            .post(
+                "/v3/kv/range",
+                serde_json::json!({ "key": self.key(address) }),
+            )
+            .await?
+            .json()
+            .await
+            .map_err(|e| SolanaIndexerError::Connection(format!("etcd error: {e}")))?;
+        parse_range(response)
+    }
+}
+
+/// Extracts the cursor signature from a range response.
+fn parse_range(response: RangeResponse) -> Result<Option<String>> {
+    let Some(kv) = response.kvs.into_iter().next() else {
+        return Ok(None);
+    };
+    let value = BASE64
+        .decode(kv.value)
+        .map_err(|e| SolanaIndexerError::Data(format!("Invalid etcd cursor: {e}")))?;
+    let cursor: StoredCursor = serde_json::from_slice(&value)
+        .map_err(|e| SolanaIndexerError::Data(format!("Invalid etcd cursor: {e}")))?;
+    Ok(Some(cursor.signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() -> Result<()> {
+        let value = BASE64.encode(r#"{"signature":"sig","slot":42}"#);
+        let response: RangeResponse =
+            serde_json::from_value(serde_json::json!({ "kvs": [{ "value": value }] })).unwrap();
+        assert_eq!(parse_range(response)?.as_deref(), Some("sig"));
+
+        let empty: RangeResponse = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert_eq!(parse_range(empty)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_are_prefixed_and_encoded() {
+        let store = EtcdCursorStore::new("http://127.0.0.1:2379/").with_prefix("/idx/");
+        assert_eq!(store.endpoint, "http://127.0.0.1:2379");
+        assert_eq!(BASE64.decode(store.key("addr")).unwrap(), b"/idx/addr");
+    }
+}
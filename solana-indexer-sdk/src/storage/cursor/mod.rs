@@ -0,0 +1,352 @@
+//! Pluggable stores for per-address polling cursors.
+//!
+//! By default the newest signature polled for each address is kept in the
+//! `_solana_indexer_sdk_cursors` table, next to the indexed events. A
+//! `CursorStore` keeps them elsewhere instead, such as a key-value store
+//! shared by stateless replicas, while processed signatures and handler
+//! rows still go to the database. `CursorStorage` puts a store in front of
+//! any storage backend; `SolanaIndexer::set_cursor_store` installs it.
+//!
+//! Stores are available for:
+//!
+//! - **etcd**: `EtcdCursorStore`, over the v3 JSON gateway.
+//! - **Redis** (`redis` feature): `RedisCursorStore`, one hash per address.
+
+pub mod etcd;
+#[cfg(feature = "redis")]
+pub mod redis;
+
+use super::buffer::BufferedQuery;
+use super::partitions::PartitionReport;
+use super::retention::{RetentionConfig, RetentionReport};
+use super::vacuum::{VacuumConfig, VacuumReport};
+use super::{ConfirmationStatus, ConfirmationSummary, DeadLetter, StorageBackend};
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::Arc;
+
+/// Storage for the newest signature polled for each address.
+#[async_trait]
+pub trait CursorStore: Send + Sync + 'static {
+    /// Stores `signature`, seen at `slot`, as the cursor of `address`.
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()>;
+
+    /// Loads the cursor of `address`, if any.
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>>;
+}
+
+#[async_trait]
+impl<S: CursorStore + ?Sized> CursorStore for Arc<S> {
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        (**self).save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        (**self).load_cursor(address).await
+    }
+}
+
+/// Storage backend that keeps cursors in a `CursorStore` and everything
+/// else in the wrapped backend.
+pub struct CursorStorage {
+    inner: Arc<dyn StorageBackend>,
+    cursors: Arc<dyn CursorStore>,
+}
+
+impl CursorStorage {
+    /// Routes the cursors of `inner` to `cursors`.
+    #[must_use]
+    pub fn new(inner: Arc<dyn StorageBackend>, cursors: Arc<dyn CursorStore>) -> Self {
+        Self { inner, cursors }
+    }
+
+    /// Returns the store holding the cursors.
+    #[must_use]
+    pub fn cursors(&self) -> &Arc<dyn CursorStore> {
+        &self.cursors
+    }
+}
+
+#[async_trait]
+impl StorageBackend for CursorStorage {
+    async fn initialize(&self) -> Result<()> {
+        self.inner.initialize().await
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        self.inner.is_processed(signature).await
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        self.inner.mark_processed(signature, slot).await
+    }
+
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.inner.mark_processed_batch(entries).await
+    }
+
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        self.inner
+            .commit_transaction(writes, signature, slot, block_hash)
+            .await
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        self.inner.get_last_processed_slot().await
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        self.inner.get_last_processed_signature().await
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        self.inner.pool()
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        self.inner.mark_tentative(signature, slot, block_hash).await
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        self.inner.mark_finalized(slot, block_hash).await
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        self.inner.get_tentative_transactions(slot).await
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        self.inner.rollback_slot(slot).await
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        self.inner.get_block_hash(slot).await
+    }
+
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        self.inner
+            .cleanup_stale_tentative_transactions(slot_threshold)
+            .await
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        self.inner.get_tentative_slots_le(slot).await
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        self.inner.save_backfill_progress(slot).await
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        self.inner.load_backfill_progress().await
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        self.inner.mark_backfill_complete().await
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        self.inner.begin_backfill_range(range).await
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.inner
+            .advance_backfill_range(range, slot, cursor, transactions)
+            .await
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.inner.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.cursors.save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.cursors.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.inner.save_snapshot_slot(program_id, slot).await
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.inner.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.inner.advance_watermark(slot).await
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.inner.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.inner.record_commitment(signature, slot, level).await
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner
+            .load_processed_signatures(slots, after, limit)
+            .await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.inner.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.inner.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.inner.record_dead_letter(signature, slot, reason).await
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.inner.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.inner.record_usage(day, records).await
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.inner.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.inner.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        self.inner.prune(config, tip_slot).await
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.inner.vacuum(config).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    fn take_dropped_signatures(&self) -> Vec<String> {
+        self.inner.take_dropped_signatures()
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemoryCursors(Mutex<HashMap<String, String>>);
+
+    #[async_trait]
+    impl CursorStore for MemoryCursors {
+        async fn save_cursor(&self, address: &str, signature: &str, _slot: u64) -> Result<()> {
+            self.0
+                .lock()
+                .await
+                .insert(address.to_string(), signature.to_string());
+            Ok(())
+        }
+
+        async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+            Ok(self.0.lock().await.get(address).cloned())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cursors_bypass_inner_backend() -> Result<()> {
+        let inner: Arc<dyn StorageBackend> =
+            Arc::new(crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?);
+        inner.initialize().await?;
+        let cursors = Arc::new(MemoryCursors::default());
+        let storage = CursorStorage::new(inner.clone(), cursors.clone());
+
+        storage.save_cursor("address", "sig_a", 10).await?;
+        storage.mark_processed("sig_a", 10).await?;
+
+        assert_eq!(
+            storage.load_cursor("address").await?.as_deref(),
+            Some("sig_a")
+        );
+        assert_eq!(inner.load_cursor("address").await?, None);
+        assert!(inner.is_processed("sig_a").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backfill_resumes_through_wrapper() -> Result<()> {
+        let inner: Arc<dyn StorageBackend> =
+            Arc::new(crate::storage::sqlite::SqliteStorage::new("sqlite::memory:").await?);
+        inner.initialize().await?;
+        let range = BackfillRange::new(100, 199);
+        let storage = CursorStorage::new(inner.clone(), Arc::new(MemoryCursors::default()));
+
+        assert_eq!(storage.begin_backfill_range(range).await?, (100, None));
+        storage.advance_backfill_range(range, 100, None, 2).await?;
+        storage
+            .advance_backfill_range(range, 101, Some("sig_b"), 1)
+            .await?;
+
+        // A restarted indexer wraps the same backend again
+        let restarted = CursorStorage::new(inner, Arc::new(MemoryCursors::default()));
+        assert_eq!(
+            restarted.begin_backfill_range(range).await?,
+            (101, Some("sig_b".to_string()))
+        );
+        let ranges = restarted.load_backfill_ranges().await?;
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].transactions, 3);
+        Ok(())
+    }
+}
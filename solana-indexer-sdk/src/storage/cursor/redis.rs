@@ -0,0 +1,110 @@
+//! Redis cursor store.
+//!
+//! `RedisCursorStore` keeps each address's cursor in a hash at
+//! `<prefix><address>` with the fields `signature` and `slot`, so replicas
+//! sharing a Redis instance resume from the same place.
+
+use super::CursorStore;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use redis::aio::ConnectionManager;
+
+/// Key prefix used unless `with_prefix` sets another.
+const DEFAULT_PREFIX: &str = "solana_indexer_sdk:cursor:";
+
+/// Keeps polling cursors in Redis.
+#[derive(Clone)]
+pub struct RedisCursorStore {
+    connection: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisCursorStore {
+    /// Connects to Redis.
+    ///
+    /// The connection is re-established automatically if it drops.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Connection` if the URL is invalid or
+    /// Redis cannot be reached.
+    pub async fn new(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).map_err(connection_error)?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .map_err(connection_error)?;
+
+        Ok(Self {
+            connection,
+            prefix: DEFAULT_PREFIX.to_string(),
+        })
+    }
+
+    /// Prefixes keys with `prefix` instead of `solana_indexer_sdk:cursor:`,
+    /// e.g. to keep the cursors of several indexers apart.
+    #[must_use]
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, address: &str) -> String {
+        format!("{}{address}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl CursorStore for RedisCursorStore {
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        let mut connection = self.connection.clone();
+        hset_command(&self.key(address), signature, slot)
+            .query_async::<_, ()>(&mut connection)
+            .await
+            .map_err(connection_error)
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        let mut connection = self.connection.clone();
+        redis::cmd("HGET")
+            .arg(self.key(address))
+            .arg("signature")
+            .query_async(&mut connection)
+            .await
+            .map_err(connection_error)
+    }
+}
+
+fn hset_command(key: &str, signature: &str, slot: u64) -> redis::Cmd {
+    let mut cmd = redis::cmd("HSET");
+    cmd.arg(key)
+        .arg("signature")
+        .arg(signature)
+        .arg("slot")
+        .arg(slot);
+    cmd
+}
+
+fn connection_error(err: redis::RedisError) -> SolanaIndexerError {
+    SolanaIndexerError::Connection(format!("Redis error: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hset_command_fields() {
+        let args: Vec<String> = hset_command("cursor:addr", "sig", 42)
+            .args_iter()
+            .map(|arg| match arg {
+                redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                redis::Arg::Cursor => "<cursor>".to_string(),
+            })
+            .collect();
+
+        assert_eq!(
+            args,
+            ["HSET", "cursor:addr", "signature", "sig", "slot", "42"]
+        );
+    }
+}
@@ -0,0 +1,496 @@
+//! Dead letter queue for events whose handler keeps failing.
+//!
+//! A handler error that survives its retries normally fails the whole
+//! transaction, which the indexer then fetches and fails again. With
+//! `with_dead_letter_queue` the event is written to `_solana_indexer_sdk_dlq`
+//! instead, together with its transaction context and the error, and the
+//! rest of the transaction is processed as usual.
+//!
+//! The same table holds the whole transactions that guardrails set aside
+//! with `StorageBackend::record_dead_letter`. Those rows have no event, so
+//! `DeadLetterQueue` skips them; read them with
+//! `StorageBackend::load_dead_letters`.
+//!
+//! An entry records which of its event type's handlers failed, so an event
+//! with several handlers is dead-lettered once per failing handler and
+//! reprocessed with that handler only. Once the handler is fixed,
+//! `DeadLetterQueue::reprocess` runs an entry through the handler registry
+//! again and removes it on success:
+//!
+//! ```no_run
+//! # async fn example(indexer: &solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! if let Some(dlq) = indexer.dead_letter_queue() {
+//!     let db = indexer.storage().require_pool()?;
+//!     for entry in dlq.list(db, 100).await? {
+//!         println!("{} {}: {}", entry.id, entry.signature, entry.error);
+//!     }
+//!     let reprocessed = dlq.reprocess_all(indexer.handler_registry(), db).await?;
+//!     println!("{reprocessed} events reprocessed");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Each entry records whether its error is `SolanaIndexerError::is_retryable`.
+//! `DeadLetterQueue::reprocess_retryable` runs only those again, e.g. once
+//! an API the handler calls has recovered, leaving events that need a fix.
+//!
+//! Events the database could not be reached for are not dead-lettered,
+//! since the queue lives in the same database; see `with_spill_queue`.
+
+use super::spill::is_outage;
+use super::{discriminator_hex, parse_discriminator_hex};
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::{HandlerRegistry, SchemaInitializer};
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Name of the dead letter table.
+pub(crate) const DLQ_TABLE: &str = "_solana_indexer_sdk_dlq";
+
+/// Condition selecting event entries, as opposed to dead-lettered
+/// transactions.
+const EVENTS: &str = "discriminator IS NOT NULL";
+
+/// Columns of a `DlqRow`.
+const COLUMNS: &str = "id, signature, slot, discriminator, handler, payload, context, error,
+     retryable, attempts, EXTRACT(EPOCH FROM created_at)::BIGINT";
+
+/// Entries read per query by `reprocess_all`.
+const REPROCESS_BATCH: i64 = 100;
+
+/// An event waiting in the dead letter queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DlqEntry {
+    /// ID to reprocess or discard the entry by
+    pub id: i64,
+    /// Signature of the event's transaction
+    pub signature: String,
+    /// Slot of the event's transaction
+    pub slot: u64,
+    /// Event discriminator
+    pub discriminator: [u8; 8],
+    /// Index of the failed handler among the event type's handlers, or
+    /// `None` to reprocess the event with all of them
+    pub handler: Option<u32>,
+    /// Borsh-encoded event data
+    pub payload: Vec<u8>,
+    /// Context the handler was called with
+    pub context: TxMetadata,
+    /// Error of the last failed attempt
+    pub error: String,
+    /// Whether that error may go away on retry, as opposed to needing a
+    /// handler or data fix
+    pub retryable: bool,
+    /// Handler calls that failed, including reprocessing
+    pub attempts: u32,
+    /// When the event was first dead-lettered, in Unix seconds
+    pub created_at: i64,
+}
+
+type DlqRow = (
+    i64,
+    String,
+    i64,
+    String,
+    Option<i32>,
+    Vec<u8>,
+    serde_json::Value,
+    String,
+    bool,
+    i32,
+    i64,
+);
+
+impl TryFrom<DlqRow> for DlqEntry {
+    type Error = SolanaIndexerError;
+
+    fn try_from(row: DlqRow) -> Result<Self> {
+        let (
+            id,
+            signature,
+            slot,
+            discriminator,
+            handler,
+            payload,
+            context,
+            error,
+            retryable,
+            attempts,
+            created_at,
+        ) = row;
+        let discriminator = parse_discriminator_hex(&discriminator).ok_or_else(|| {
+            SolanaIndexerError::Data(format!(
+                "Invalid dead letter discriminator: {discriminator}"
+            ))
+        })?;
+        let context = serde_json::from_value(context)
+            .map_err(|e| SolanaIndexerError::Data(format!("Invalid dead letter context: {e}")))?;
+        Ok(Self {
+            id,
+            signature,
+            slot: u64::try_from(slot).unwrap_or(0),
+            discriminator,
+            handler: handler.and_then(|handler| u32::try_from(handler).ok()),
+            payload,
+            context,
+            error,
+            retryable,
+            attempts: u32::try_from(attempts).unwrap_or(0),
+            created_at,
+        })
+    }
+}
+
+/// Stores events whose handler failed after its retries, and runs them
+/// again on request.
+#[derive(Debug, Default)]
+pub struct DeadLetterQueue {
+    stored: AtomicU64,
+    reprocessed: AtomicU64,
+}
+
+impl DeadLetterQueue {
+    /// Creates a queue.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of events dead-lettered since startup.
+    #[must_use]
+    pub fn stored(&self) -> u64 {
+        self.stored.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of entries reprocessed successfully since startup.
+    #[must_use]
+    pub fn reprocessed(&self) -> u64 {
+        self.reprocessed.load(Ordering::Relaxed)
+    }
+
+    /// Writes one event that handler `handler` of its type failed.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn push(
+        &self,
+        discriminator: &[u8; 8],
+        handler: Option<usize>,
+        data: &[u8],
+        context: &TxMetadata,
+        error: &SolanaIndexerError,
+        attempts: u32,
+        db: &PgPool,
+    ) -> Result<()> {
+        let context_json = serde_json::to_value(context)
+            .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize context: {e}")))?;
+        sqlx::query(&format!(
+            "INSERT INTO {DLQ_TABLE}
+                (signature, slot, discriminator, handler, payload, context, error, retryable,
+                 attempts)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"
+        ))
+        .bind(&context.signature)
+        .bind(i64::try_from(context.slot).unwrap_or(i64::MAX))
+        .bind(discriminator_hex(Some(*discriminator)))
+        .bind(handler.map(|handler| i32::try_from(handler).unwrap_or(i32::MAX)))
+        .bind(data)
+        .bind(context_json)
+        .bind(error.to_string())
+        .bind(error.is_retryable())
+        .bind(i32::try_from(attempts).unwrap_or(i32::MAX))
+        .execute(db)
+        .await?;
+        self.stored.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns up to `limit` entries, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails, or
+    /// `SolanaIndexerError::Data` if an entry cannot be read back.
+    pub async fn list(&self, db: &PgPool, limit: i64) -> Result<Vec<DlqEntry>> {
+        let rows: Vec<DlqRow> = sqlx::query_as(&format!(
+            "SELECT {COLUMNS} FROM {DLQ_TABLE} WHERE {EVENTS} ORDER BY id LIMIT $1"
+        ))
+        .bind(limit)
+        .fetch_all(db)
+        .await?;
+        rows.into_iter().map(DlqEntry::try_from).collect()
+    }
+
+    /// Returns the entry with `id`, if it is still queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails, or
+    /// `SolanaIndexerError::Data` if the entry cannot be read back.
+    pub async fn get(&self, db: &PgPool, id: i64) -> Result<Option<DlqEntry>> {
+        let row: Option<DlqRow> = sqlx::query_as(&format!(
+            "SELECT {COLUMNS} FROM {DLQ_TABLE} WHERE id = $1 AND {EVENTS}"
+        ))
+        .bind(id)
+        .fetch_optional(db)
+        .await?;
+        row.map(DlqEntry::try_from).transpose()
+    }
+
+    /// Runs entry `id` through `registry` again, removing it on success.
+    /// On failure the entry is kept with the new error.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Data` if there is no entry `id`,
+    /// or the handler's error.
+    pub async fn reprocess(&self, registry: &HandlerRegistry, db: &PgPool, id: i64) -> Result<()> {
+        let entry = self
+            .get(db, id)
+            .await?
+            .ok_or_else(|| SolanaIndexerError::Data(format!("No dead letter with id {id}")))?;
+        self.reprocess_entry(registry, db, entry).await
+    }
+
+    /// Reprocesses every entry, oldest first, and returns how many
+    /// succeeded. Entries that fail again stay queued.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the queue cannot be
+    /// read or updated.
+    pub async fn reprocess_all(&self, registry: &HandlerRegistry, db: &PgPool) -> Result<u64> {
+        self.reprocess_where(registry, db, "TRUE").await
+    }
+
+    /// Reprocesses the entries whose last error was retryable, oldest
+    /// first, and returns how many succeeded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the queue cannot be
+    /// read or updated.
+    pub async fn reprocess_retryable(
+        &self,
+        registry: &HandlerRegistry,
+        db: &PgPool,
+    ) -> Result<u64> {
+        self.reprocess_where(registry, db, "retryable").await
+    }
+
+    /// Reprocesses the entries matching the SQL condition `filter`.
+    async fn reprocess_where(
+        &self,
+        registry: &HandlerRegistry,
+        db: &PgPool,
+        filter: &str,
+    ) -> Result<u64> {
+        let mut reprocessed = 0;
+        let mut after = 0;
+        loop {
+            let rows: Vec<DlqRow> = sqlx::query_as(&format!(
+                "SELECT {COLUMNS} FROM {DLQ_TABLE}
+                 WHERE id > $1 AND {EVENTS} AND {filter} ORDER BY id LIMIT $2"
+            ))
+            .bind(after)
+            .bind(REPROCESS_BATCH)
+            .fetch_all(db)
+            .await?;
+            let Some(last) = rows.last() else {
+                return Ok(reprocessed);
+            };
+            after = last.0;
+
+            for row in rows {
+                let entry = DlqEntry::try_from(row)?;
+                match self.reprocess_entry(registry, db, entry).await {
+                    Ok(()) => reprocessed += 1,
+                    Err(e) if is_outage(&e) => return Err(e),
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Removes entry `id` without reprocessing it, returning whether it
+    /// existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn discard(&self, db: &PgPool, id: i64) -> Result<bool> {
+        let deleted = sqlx::query(&format!(
+            "DELETE FROM {DLQ_TABLE} WHERE id = $1 AND {EVENTS}"
+        ))
+        .bind(id)
+        .execute(db)
+        .await?
+        .rows_affected();
+        Ok(deleted > 0)
+    }
+
+    async fn reprocess_entry(
+        &self,
+        registry: &HandlerRegistry,
+        db: &PgPool,
+        mut entry: DlqEntry,
+    ) -> Result<()> {
+        // Flags and jobs are not stored; the reprocessing indexer's apply
+        entry.context.flags = registry.feature_flags().clone();
+        entry.context.jobs = registry.deferred_jobs().clone();
+
+        let result = match entry.handler {
+            Some(handler) => {
+                registry
+                    .handle_at(
+                        &entry.discriminator,
+                        handler as usize,
+                        &entry.payload,
+                        &entry.context,
+                        db,
+                    )
+                    .await
+            }
+            None => {
+                registry
+                    .handle(&entry.discriminator, &entry.payload, &entry.context, db)
+                    .await
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.discard(db, entry.id).await?;
+                self.reprocessed.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                sqlx::query(&format!(
+                    "UPDATE {DLQ_TABLE} SET error = $2, retryable = $3, attempts = attempts + 1
+                     WHERE id = $1"
+                ))
+                .bind(entry.id)
+                .bind(e.to_string())
+                .bind(e.is_retryable())
+                .execute(db)
+                .await?;
+                Err(e)
+            }
+        }
+    }
+}
+
+/// Creates the dead letter table shared by `DeadLetterQueue` and
+/// `Storage::record_dead_letter`.
+pub(crate) async fn create_table(db: &PgPool) -> Result<()> {
+    sqlx::query(&format!(
+        "CREATE TABLE IF NOT EXISTS {DLQ_TABLE} (
+            id BIGSERIAL PRIMARY KEY,
+            signature TEXT NOT NULL,
+            slot BIGINT NOT NULL,
+            discriminator TEXT,
+            handler INTEGER,
+            payload BYTEA,
+            context JSONB,
+            error TEXT NOT NULL,
+            retryable BOOLEAN NOT NULL DEFAULT TRUE,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )"
+    ))
+    .execute(db)
+    .await?;
+    // Queues created before handlers were recorded
+    sqlx::query(&format!(
+        "ALTER TABLE {DLQ_TABLE} ADD COLUMN IF NOT EXISTS handler INTEGER"
+    ))
+    .execute(db)
+    .await?;
+    // Queues created before errors were classified
+    sqlx::query(&format!(
+        "ALTER TABLE {DLQ_TABLE} ADD COLUMN IF NOT EXISTS retryable BOOLEAN NOT NULL DEFAULT TRUE"
+    ))
+    .execute(db)
+    .await?;
+    // Queues created before whole transactions were dead-lettered
+    sqlx::query(&format!(
+        "ALTER TABLE {DLQ_TABLE}
+            ALTER COLUMN discriminator DROP NOT NULL,
+            ALTER COLUMN payload DROP NOT NULL,
+            ALTER COLUMN context DROP NOT NULL,
+            ALTER COLUMN attempts SET DEFAULT 0"
+    ))
+    .execute(db)
+    .await?;
+    // A transaction is dead-lettered at most once
+    sqlx::query(&format!(
+        "CREATE UNIQUE INDEX IF NOT EXISTS {DLQ_TABLE}_transaction_idx
+            ON {DLQ_TABLE} (signature) WHERE discriminator IS NULL"
+    ))
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Creates the dead letter table, for storage backends that do not.
+#[async_trait]
+impl SchemaInitializer for DeadLetterQueue {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        create_table(db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_from_row() {
+        let context = TxMetadata {
+            slot: 42,
+            block_time: Some(1_700_000_000),
+            fee: 5000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: Some(3),
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+        let row: DlqRow = (
+            7,
+            "sig".to_string(),
+            42,
+            discriminator_hex(Some([1, 2, 3, 4, 5, 6, 7, 8])),
+            Some(1),
+            vec![9, 9],
+            serde_json::to_value(&context).unwrap(),
+            "Database error: duplicate key".to_string(),
+            false,
+            3,
+            1_700_000_000,
+        );
+
+        let entry = DlqEntry::try_from(row.clone()).unwrap();
+        assert_eq!(entry.discriminator, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(entry.slot, 42);
+        assert_eq!(entry.handler, Some(1));
+        assert!(!entry.retryable);
+        assert_eq!(entry.attempts, 3);
+        assert_eq!(entry.context.index_in_block, Some(3));
+
+        let mut bad = row;
+        bad.3 = "zz".to_string();
+        assert!(DlqEntry::try_from(bad).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+//! Boolean feature flags stored in the database.
+//!
+//! With `with_feature_flags`, handlers read flags from
+//! `_solana_indexer_sdk_flags` through `TxMetadata::flags`, so a behavior such as
+//! enrichment or a write to a new table can be switched on or off while the
+//! indexer runs:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{EventHandler, Result, TxMetadata};
+//! # use async_trait::async_trait;
+//! # use sqlx::PgPool;
+//! # struct TransferEvent;
+//! # struct TransferHandler;
+//! #[async_trait]
+//! impl EventHandler<TransferEvent> for TransferHandler {
+//!     async fn handle(&self, _event: TransferEvent, context: &TxMetadata, _db: &PgPool) -> Result<()> {
+//!         if context.flags().is_enabled("transfers_v2").await {
+//!             // write to the new table
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//! ```
+//!
+//! Flags are toggled with plain SQL, or `FeatureFlags::set`:
+//!
+//! ```sql
+//! INSERT INTO _solana_indexer_sdk_flags (name, enabled) VALUES ('transfers_v2', TRUE)
+//! ON CONFLICT (name) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW();
+//! ```
+//!
+//! The whole table is cached and read again once the cache is older than the
+//! configured refresh interval, so a change takes effect within one interval
+//! and checking a flag costs no query in between.
+
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Name of the flags table.
+pub(crate) const FLAGS_TABLE: &str = "_solana_indexer_sdk_flags";
+
+/// Cached view of the flags table.
+///
+/// The default handle reads no table and reports every flag as disabled,
+/// which is what handlers see when `with_feature_flags` is not set.
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlags {
+    source: Option<Arc<FlagSource>>,
+}
+
+#[derive(Debug)]
+struct FlagSource {
+    db: PgPool,
+    refresh_interval: Duration,
+    cache: RwLock<FlagCache>,
+}
+
+#[derive(Debug, Default)]
+struct FlagCache {
+    flags: HashMap<String, bool>,
+    loaded_at: Option<Instant>,
+}
+
+impl FeatureFlags {
+    /// Reads flags from `db`, caching them for `refresh_interval`.
+    #[must_use]
+    pub fn new(db: PgPool, refresh_interval: Duration) -> Self {
+        Self {
+            source: Some(Arc::new(FlagSource {
+                db,
+                refresh_interval,
+                cache: RwLock::new(FlagCache::default()),
+            })),
+        }
+    }
+
+    /// Returns whether the flag `name` is enabled.
+    ///
+    /// Flags missing from the table are disabled. If the table cannot be
+    /// read, the error is logged and the last values read are used.
+    pub async fn is_enabled(&self, name: &str) -> bool {
+        let Some(source) = &self.source else {
+            return false;
+        };
+        if source.is_stale() {
+            if let Err(e) = self.refresh().await {
+                logging::log_error("Feature flag refresh error", &e.to_string());
+            }
+        }
+        source.cached(name).unwrap_or(false)
+    }
+
+    /// Reads the flags table again, whatever the age of the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the table cannot be
+    /// read. The cache is then left as it was.
+    pub async fn refresh(&self) -> Result<()> {
+        let Some(source) = &self.source else {
+            return Ok(());
+        };
+        let rows = sqlx::query_as::<_, (String, bool)>(&format!(
+            "SELECT name, enabled FROM {FLAGS_TABLE}"
+        ))
+        .fetch_all(&source.db)
+        .await;
+        // A failed read is retried after a full interval rather than on
+        // every check
+        if let Ok(mut cache) = source.cache.write() {
+            cache.loaded_at = Some(Instant::now());
+            if let Ok(rows) = &rows {
+                cache.flags = rows.iter().cloned().collect();
+            }
+        }
+        rows?;
+        Ok(())
+    }
+
+    /// Enables or disables the flag `name`, creating it if needed.
+    ///
+    /// Indexers see the change once their cache expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the write fails.
+    pub async fn set(db: &PgPool, name: &str, enabled: bool) -> Result<()> {
+        sqlx::query(&format!(
+            "INSERT INTO {FLAGS_TABLE} (name, enabled) VALUES ($1, $2)
+             ON CONFLICT (name) DO UPDATE
+             SET enabled = EXCLUDED.enabled, updated_at = NOW()"
+        ))
+        .bind(name)
+        .bind(enabled)
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+impl FlagSource {
+    fn is_stale(&self) -> bool {
+        self.cache
+            .read()
+            .map(|cache| {
+                cache
+                    .loaded_at
+                    .map_or(true, |loaded| loaded.elapsed() >= self.refresh_interval)
+            })
+            .unwrap_or(true)
+    }
+
+    fn cached(&self, name: &str) -> Option<bool> {
+        self.cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.flags.get(name).copied())
+    }
+}
+
+/// Creates the flags table.
+#[async_trait]
+impl SchemaInitializer for FeatureFlags {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {FLAGS_TABLE} (
+                name TEXT PRIMARY KEY,
+                enabled BOOLEAN NOT NULL DEFAULT FALSE,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_default_flags_are_disabled() {
+        let flags = FeatureFlags::default();
+        assert!(!flags.is_enabled("transfers_v2").await);
+        assert!(flags.refresh().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_cached_flags_are_served_until_stale() {
+        let db = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgresql://localhost/unused")
+            .unwrap();
+        let flags = FeatureFlags::new(db, Duration::from_secs(60));
+        let source = flags.source.clone().unwrap();
+        assert!(source.is_stale());
+
+        {
+            let mut cache = source.cache.write().unwrap();
+            cache.flags.insert("transfers_v2".to_string(), true);
+            cache.flags.insert("enrichment".to_string(), false);
+            cache.loaded_at = Some(Instant::now());
+        }
+        assert!(!source.is_stale());
+        assert!(flags.is_enabled("transfers_v2").await);
+        assert!(!flags.is_enabled("enrichment").await);
+        assert!(!flags.is_enabled("unknown").await);
+    }
+}
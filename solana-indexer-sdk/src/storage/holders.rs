@@ -0,0 +1,209 @@
+//! Balances of the holders of tracked mints.
+//!
+//! With `with_token_holders`, the indexer keeps one row per token account of
+//! each tracked mint in `_solana_indexer_sdk_token_holders`: its owner, its
+//! raw amount and the slot the amount was observed at. Rows are seeded on
+//! startup from `getProgramAccounts` and updated from the post-transaction
+//! token balances of every indexed transaction, so a holder list is one
+//! query away:
+//!
+//! ```sql
+//! SELECT owner, SUM(amount) AS balance
+//! FROM _solana_indexer_sdk_token_holders
+//! WHERE mint = 'EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v' AND amount > 0
+//! GROUP BY owner ORDER BY balance DESC;
+//! ```
+
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+
+/// Name of the holders table.
+pub(crate) const HOLDERS_TABLE: &str = "_solana_indexer_sdk_token_holders";
+
+/// Program IDs of SPL Token and Token-2022, whose accounts are listed.
+pub(crate) const TOKEN_PROGRAM_IDS: [&str; 2] = [
+    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+    "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+];
+
+/// Balance of one token account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenHolding {
+    /// Token account
+    pub token_account: String,
+    /// Mint of the token
+    pub mint: String,
+    /// Wallet owning the token account
+    pub owner: String,
+    /// Raw amount, in the mint's smallest unit
+    pub amount: u64,
+    /// Slot the amount was observed at
+    pub slot: u64,
+}
+
+/// Token account balances of tracked mints.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenHolders;
+
+impl TokenHolders {
+    /// Stores `holdings`. A row already observed at a later slot is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database write fails.
+    pub async fn upsert(db: &PgPool, holdings: &[TokenHolding]) -> Result<()> {
+        for holding in holdings {
+            sqlx::query(&format!(
+                "INSERT INTO {HOLDERS_TABLE} (token_account, mint, owner, amount, slot)
+                 VALUES ($1, $2, $3, $4::NUMERIC, $5)
+                 ON CONFLICT (token_account) DO UPDATE
+                 SET owner = EXCLUDED.owner, amount = EXCLUDED.amount,
+                     slot = EXCLUDED.slot, updated_at = NOW()
+                 WHERE {HOLDERS_TABLE}.slot <= EXCLUDED.slot"
+            ))
+            .bind(&holding.token_account)
+            .bind(&holding.mint)
+            .bind(&holding.owner)
+            .bind(holding.amount.to_string())
+            .bind(i64::try_from(holding.slot).unwrap_or(i64::MAX))
+            .execute(db)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Loads the token accounts of `mint` holding a non-zero amount,
+    /// largest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database read fails.
+    pub async fn load(db: &PgPool, mint: &str) -> Result<Vec<TokenHolding>> {
+        let rows: Vec<(String, String, String, String, i64)> = sqlx::query_as(&format!(
+            "SELECT token_account, mint, owner, amount::TEXT, slot
+             FROM {HOLDERS_TABLE}
+             WHERE mint = $1 AND amount > 0
+             ORDER BY amount DESC, token_account"
+        ))
+        .bind(mint)
+        .fetch_all(db)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(token_account, mint, owner, amount, slot)| TokenHolding {
+                token_account,
+                mint,
+                owner,
+                amount: amount.parse().unwrap_or_default(),
+                slot: u64::try_from(slot).unwrap_or_default(),
+            })
+            .collect())
+    }
+}
+
+/// Creates the holders table.
+#[async_trait]
+impl SchemaInitializer for TokenHolders {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {HOLDERS_TABLE} (
+                token_account TEXT PRIMARY KEY,
+                mint TEXT NOT NULL,
+                owner TEXT NOT NULL,
+                amount NUMERIC(20, 0) NOT NULL,
+                slot BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_token_holders_mint ON {HOLDERS_TABLE}(mint)"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+/// Returns the post-transaction balances of the token accounts of `mints`
+/// in a transaction with `account_keys`.
+pub(crate) fn holdings(
+    context: &TxMetadata,
+    account_keys: &[&str],
+    mints: &[Pubkey],
+) -> Vec<TokenHolding> {
+    context
+        .post_token_balances
+        .iter()
+        .filter(|balance| mints.iter().any(|mint| mint.to_string() == balance.mint))
+        .filter_map(|balance| {
+            Some(TokenHolding {
+                token_account: (*account_keys.get(usize::from(balance.account_index))?).to_string(),
+                mint: balance.mint.clone(),
+                owner: balance.owner.clone(),
+                amount: balance.amount.parse().ok()?,
+                slot: context.slot,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::metadata::TokenBalanceInfo;
+
+    #[test]
+    fn test_holdings_from_post_balances() {
+        let mint = Pubkey::new_unique();
+        let balance = |account_index: u8, mint: &Pubkey, amount: &str| TokenBalanceInfo {
+            account_index,
+            mint: mint.to_string(),
+            owner: "owner".to_string(),
+            amount: amount.to_string(),
+            decimals: 6,
+            program_id: None,
+        };
+        let context = TxMetadata {
+            slot: 42,
+            block_time: None,
+            fee: 5000,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![
+                balance(1, &mint, "1500"),
+                balance(2, &Pubkey::new_unique(), "7"),
+                balance(9, &mint, "3"),
+            ],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        let holdings = holdings(&context, &["payer", "alice_ata", "bob_ata"], &[mint]);
+        assert_eq!(
+            holdings,
+            [TokenHolding {
+                token_account: "alice_ata".to_string(),
+                mint: mint.to_string(),
+                owner: "owner".to_string(),
+                amount: 1500,
+                slot: 42,
+            }]
+        );
+    }
+}
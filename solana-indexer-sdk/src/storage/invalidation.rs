@@ -0,0 +1,346 @@
+//! Cache invalidation over `LISTEN`/`NOTIFY`.
+//!
+//! Handlers often keep in-process caches of slowly changing rows, such as
+//! token metadata or config accounts. When several indexer instances share a
+//! database, a row written by one instance leaves the others' caches stale.
+//! An `InvalidationTrigger` makes Postgres notify every change to a table on
+//! `solana_indexer_sdk_invalidate`, and an `InvalidationListener` turns those
+//! notifications into `Invalidation`s each instance applies to its caches:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{InvalidationListener, InvalidationTrigger, SolanaIndexer};
+//! # use std::collections::HashMap;
+//! # use std::sync::{Arc, RwLock};
+//! # async fn example(indexer: &mut SolanaIndexer, pool: &sqlx::PgPool) -> solana_indexer_sdk::Result<()> {
+//! indexer.register_schema_initializer(Box::new(InvalidationTrigger::new("token_metadata", "mint")));
+//!
+//! let cache: Arc<RwLock<HashMap<String, String>>> = Arc::default();
+//! let listener = InvalidationListener::connect(pool).await?;
+//! let entries = cache.clone();
+//! listener.watch("token_metadata", move |mint| {
+//!     let mut entries = entries.write().unwrap();
+//!     match mint {
+//!         Some(mint) => entries.remove(mint),
+//!         None => { entries.clear(); None }
+//!     };
+//! });
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Notifications sent while the listener is disconnected are lost, so a
+//! reconnect invalidates everything.
+
+use crate::storage::retention::is_identifier;
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+
+/// `NOTIFY` channel invalidations are published on.
+pub const INVALIDATION_CHANNEL: &str = "solana_indexer_sdk_invalidate";
+
+/// Trigger function shared by every watched table.
+const TRIGGER_FUNCTION: &str = "_solana_indexer_sdk_notify_invalidation";
+
+/// Invalidations buffered per subscriber before it is considered lagging.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// Notifies `solana_indexer_sdk_invalidate` on every insert, update and
+/// delete of a table.
+///
+/// Register it with `SolanaIndexer::register_schema_initializer`; the
+/// trigger is (re)created on startup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidationTrigger {
+    table: String,
+    key_column: String,
+}
+
+impl InvalidationTrigger {
+    /// Watches `table`, identifying changed rows by `key_column`.
+    #[must_use]
+    pub fn new(table: impl Into<String>, key_column: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            key_column: key_column.into(),
+        }
+    }
+
+    /// Name of the trigger, unique per table.
+    fn trigger_name(&self) -> String {
+        let table = self.table.rsplit('.').next().unwrap_or(&self.table);
+        format!("{table}_solana_indexer_sdk_invalidate")
+    }
+}
+
+/// Creates the trigger function and the table's trigger.
+#[async_trait]
+impl SchemaInitializer for InvalidationTrigger {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        for name in [&self.table, &self.key_column] {
+            if !is_identifier(name) {
+                return Err(SolanaIndexerError::Config(format!(
+                    "Invalid invalidation table or column name: {name:?}"
+                )));
+            }
+        }
+
+        // An update that changes the key invalidates the old key too
+        sqlx::query(&format!(
+            "CREATE OR REPLACE FUNCTION {TRIGGER_FUNCTION}() RETURNS trigger AS $$
+            DECLARE
+                new_key TEXT;
+                old_key TEXT;
+            BEGIN
+                IF TG_OP <> 'DELETE' THEN
+                    new_key := to_jsonb(NEW) ->> TG_ARGV[0];
+                    PERFORM pg_notify('{INVALIDATION_CHANNEL}',
+                        json_build_object('table', TG_TABLE_NAME, 'key', new_key)::text);
+                END IF;
+                IF TG_OP <> 'INSERT' THEN
+                    old_key := to_jsonb(OLD) ->> TG_ARGV[0];
+                    IF TG_OP = 'DELETE' OR old_key IS DISTINCT FROM new_key THEN
+                        PERFORM pg_notify('{INVALIDATION_CHANNEL}',
+                            json_build_object('table', TG_TABLE_NAME, 'key', old_key)::text);
+                    END IF;
+                END IF;
+                RETURN NULL;
+            END
+            $$ LANGUAGE plpgsql"
+        ))
+        .execute(db)
+        .await?;
+
+        let trigger = self.trigger_name();
+        sqlx::query(&format!(
+            "DROP TRIGGER IF EXISTS {trigger} ON {}",
+            self.table
+        ))
+        .execute(db)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE TRIGGER {trigger}
+             AFTER INSERT OR UPDATE OR DELETE ON {}
+             FOR EACH ROW EXECUTE FUNCTION {TRIGGER_FUNCTION}('{}')",
+            self.table, self.key_column
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+/// A change that makes cached entries stale.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Invalidation {
+    /// One row of `table` changed
+    Row {
+        /// Table the row belongs to, without its schema
+        table: String,
+        /// Value of the row's key column
+        key: String,
+    },
+    /// Any row of `table` may have changed
+    Table {
+        /// Table that changed, without its schema
+        table: String,
+    },
+    /// Notifications may have been missed; every cache is stale
+    All,
+}
+
+impl Invalidation {
+    /// Returns whether entries cached from `table` may be stale.
+    #[must_use]
+    pub fn affects(&self, table: &str) -> bool {
+        match self {
+            Self::Row { table: changed, .. } | Self::Table { table: changed } => changed == table,
+            Self::All => true,
+        }
+    }
+
+    /// Returns the key of the changed row, or `None` if every entry is
+    /// stale.
+    #[must_use]
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            Self::Row { key, .. } => Some(key),
+            Self::Table { .. } | Self::All => None,
+        }
+    }
+
+    fn from_payload(payload: &str) -> Option<Self> {
+        let Payload { table, key } = serde_json::from_str(payload).ok()?;
+        Some(match key {
+            Some(key) => Self::Row { table, key },
+            None => Self::Table { table },
+        })
+    }
+}
+
+/// Wire format of a notification.
+#[derive(Debug, Deserialize)]
+struct Payload {
+    table: String,
+    key: Option<String>,
+}
+
+/// Publishes an invalidation for `table`, or for one of its rows if `key` is
+/// given.
+///
+/// For caches of tables without an `InvalidationTrigger`. Sent inside a
+/// transaction, the notification is delivered once it commits.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Storage` if the notification fails.
+pub async fn notify_invalidation(db: &PgPool, table: &str, key: Option<&str>) -> Result<()> {
+    let payload = serde_json::json!({ "table": table, "key": key }).to_string();
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(INVALIDATION_CHANNEL)
+        .bind(payload)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Receives invalidations from every instance sharing the database.
+///
+/// The listener holds one connection outside the pool and stops when
+/// dropped.
+#[derive(Debug)]
+pub struct InvalidationListener {
+    sender: broadcast::Sender<Invalidation>,
+    cancel: CancellationToken,
+}
+
+impl InvalidationListener {
+    /// Starts listening on `solana_indexer_sdk_invalidate`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the listening
+    /// connection cannot be opened.
+    pub async fn connect(db: &PgPool) -> Result<Self> {
+        let mut listener = PgListener::connect_with(db).await?;
+        listener.listen(INVALIDATION_CHANNEL).await?;
+
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let cancel = CancellationToken::new();
+        let (task_sender, task_cancel) = (sender.clone(), cancel.clone());
+        tokio::spawn(async move {
+            loop {
+                let received = tokio::select! {
+                    () = task_cancel.cancelled() => break,
+                    received = listener.try_recv() => received,
+                };
+                match received {
+                    Ok(Some(notification)) => {
+                        if let Some(invalidation) =
+                            Invalidation::from_payload(notification.payload())
+                        {
+                            let _ = task_sender.send(invalidation);
+                        }
+                    }
+                    // The connection dropped and is reopened on the next
+                    // call; anything sent in between is lost
+                    Ok(None) => {
+                        let _ = task_sender.send(Invalidation::All);
+                    }
+                    Err(e) => {
+                        logging::log_error("Invalidation listener error", &e.to_string());
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { sender, cancel })
+    }
+
+    /// Returns a receiver of every invalidation from now on.
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<Invalidation> {
+        self.sender.subscribe()
+    }
+
+    /// Calls `invalidate` for every invalidation affecting `table`, with the
+    /// changed key, or `None` when the whole cache is stale.
+    ///
+    /// A callback that falls behind is also called with `None`, since
+    /// invalidations were dropped.
+    pub fn watch(&self, table: &str, invalidate: impl Fn(Option<&str>) + Send + 'static) {
+        let mut receiver = self.subscribe();
+        let table = table.rsplit('.').next().unwrap_or(table).to_string();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(invalidation) if invalidation.affects(&table) => {
+                        invalidate(invalidation.key());
+                    }
+                    Ok(_) => {}
+                    Err(broadcast::error::RecvError::Lagged(_)) => invalidate(None),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+}
+
+impl Drop for InvalidationListener {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_parsing() {
+        assert_eq!(
+            Invalidation::from_payload(r#"{"table":"token_metadata","key":"So111"}"#),
+            Some(Invalidation::Row {
+                table: "token_metadata".to_string(),
+                key: "So111".to_string(),
+            })
+        );
+        assert_eq!(
+            Invalidation::from_payload(r#"{"table":"token_metadata","key":null}"#),
+            Some(Invalidation::Table {
+                table: "token_metadata".to_string(),
+            })
+        );
+        assert_eq!(Invalidation::from_payload("42"), None);
+    }
+
+    #[test]
+    fn test_affects() {
+        let row = Invalidation::Row {
+            table: "pools".to_string(),
+            key: "abc".to_string(),
+        };
+        assert!(row.affects("pools"));
+        assert!(!row.affects("token_metadata"));
+        assert_eq!(row.key(), Some("abc"));
+        assert!(Invalidation::All.affects("pools"));
+        assert_eq!(Invalidation::All.key(), None);
+    }
+
+    #[test]
+    fn test_trigger_name_drops_schema() {
+        let trigger = InvalidationTrigger::new("app.pools", "pubkey");
+        assert_eq!(
+            trigger.trigger_name(),
+            "pools_solana_indexer_sdk_invalidate"
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Deferred jobs scheduled by handlers.
+//!
+//! With `with_deferred_jobs`, a handler can hand work to the indexer for
+//! later, such as re-checking a transaction once it is finalized or
+//! aggregating a pool once its activity has cooled down. The job is a
+//! serializable value, stored in `_solana_indexer_sdk_jobs` and delivered to the
+//! `JobHandler` registered for its type once its delay has passed:
+//!
+//! ```no_run
+//! # use solana_indexer_sdk::{DeferredJob, EventHandler, JobHandler, Result, SolanaIndexer, TransferEvent, TxMetadata};
+//! # use async_trait::async_trait;
+//! # use serde::{Deserialize, Serialize};
+//! # use sqlx::PgPool;
+//! # use std::time::Duration;
+//! #[derive(Serialize, Deserialize)]
+//! struct Recheck {
+//!     signature: String,
+//! }
+//!
+//! impl DeferredJob for Recheck {
+//!     fn kind() -> &'static str {
+//!         "recheck"
+//!     }
+//! }
+//!
+//! struct TransferHandler;
+//!
+//! #[async_trait]
+//! impl EventHandler<TransferEvent> for TransferHandler {
+//!     async fn handle(&self, _event: TransferEvent, context: &TxMetadata, _db: &PgPool) -> Result<()> {
+//!         let job = Recheck { signature: context.signature.clone() };
+//!         context.schedule_in(Duration::from_secs(30), &job).await?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! struct RecheckHandler;
+//!
+//! #[async_trait]
+//! impl JobHandler<Recheck> for RecheckHandler {
+//!     async fn run(&self, job: Recheck, _db: &PgPool) -> Result<()> {
+//!         println!("re-checking {}", job.signature);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn example(indexer: &mut SolanaIndexer) -> Result<()> {
+//! indexer.register_job_handler(RecheckHandler)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Jobs are delivered at least once: a job is leased while it runs, and
+//! one whose indexer stops before it completes is delivered again once the
+//! lease expires. A failing job is retried with a growing delay and kept
+//! in the table, marked failed, after its last attempt. Several indexers
+//! sharing the table each claim different jobs.
+
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the jobs table.
+pub(crate) const JOBS_TABLE: &str = "_solana_indexer_sdk_jobs";
+
+/// Jobs claimed per poll.
+const CLAIM_BATCH: i64 = 100;
+
+/// How long a claimed job is reserved for before it is delivered again.
+const LEASE: Duration = Duration::from_secs(300);
+
+/// Deliveries of a job before it is marked failed.
+const MAX_ATTEMPTS: i32 = 5;
+
+/// A unit of deferred work.
+pub trait DeferredJob: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Name the job is stored under and routed to its handler by.
+    fn kind() -> &'static str;
+}
+
+/// Runs deferred jobs of type `J`.
+#[async_trait]
+pub trait JobHandler<J: DeferredJob>: Send + Sync + 'static {
+    /// Runs one job. An error schedules another attempt.
+    async fn run(&self, job: J, db: &PgPool) -> Result<()>;
+}
+
+/// Type-erased job handler, running jobs from their stored JSON.
+#[async_trait]
+pub(crate) trait DynamicJobHandler: Send + Sync {
+    async fn run_dynamic(&self, payload: serde_json::Value, db: &PgPool) -> Result<()>;
+}
+
+/// Adapts a `JobHandler<J>` to `DynamicJobHandler`.
+pub(crate) struct TypedJobHandler<J, H> {
+    handler: H,
+    _job: PhantomData<fn(J)>,
+}
+
+impl<J, H> TypedJobHandler<J, H> {
+    pub(crate) fn new(handler: H) -> Self {
+        Self {
+            handler,
+            _job: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<J: DeferredJob, H: JobHandler<J>> DynamicJobHandler for TypedJobHandler<J, H> {
+    async fn run_dynamic(&self, payload: serde_json::Value, db: &PgPool) -> Result<()> {
+        let job = serde_json::from_value(payload)
+            .map_err(|e| SolanaIndexerError::Decode(format!("Invalid {} job: {e}", J::kind())))?;
+        self.handler.run(job, db).await
+    }
+}
+
+/// Handle to the jobs table, given to handlers through `TxMetadata::jobs`.
+///
+/// The default handle stores nothing and refuses every job, which is what
+/// handlers see when `with_deferred_jobs` is not set.
+#[derive(Debug, Clone, Default)]
+pub struct DeferredJobs {
+    db: Option<PgPool>,
+}
+
+impl DeferredJobs {
+    /// Stores jobs in `db`.
+    #[must_use]
+    pub fn new(db: PgPool) -> Self {
+        Self { db: Some(db) }
+    }
+
+    /// Returns whether jobs can be scheduled.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.db.is_some()
+    }
+
+    /// Schedules `job` to run once `delay` has passed, returning its ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if deferred jobs are not
+    /// enabled, `SolanaIndexerError::Data` if the job cannot be
+    /// serialized and `SolanaIndexerError::Storage` if it cannot be
+    /// stored.
+    pub async fn schedule_in<J: DeferredJob>(&self, delay: Duration, job: &J) -> Result<i64> {
+        let Some(db) = &self.db else {
+            return Err(SolanaIndexerError::Config(
+                "Deferred jobs are not enabled; see with_deferred_jobs".to_string(),
+            ));
+        };
+        let payload = serde_json::to_value(job).map_err(|e| {
+            SolanaIndexerError::Data(format!("Failed to serialize {} job: {e}", J::kind()))
+        })?;
+        let id = sqlx::query_scalar::<_, i64>(&format!(
+            "INSERT INTO {JOBS_TABLE} (kind, payload, run_at)
+             VALUES ($1, $2, NOW() + $3 * INTERVAL '1 millisecond')
+             RETURNING id"
+        ))
+        .bind(J::kind())
+        .bind(payload)
+        .bind(millis(delay))
+        .fetch_one(db)
+        .await?;
+        Ok(id)
+    }
+
+    /// Returns the number of jobs waiting to run, failed ones excluded.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the table cannot be
+    /// read; a disabled handle reports 0.
+    pub async fn pending(&self) -> Result<i64> {
+        let Some(db) = &self.db else {
+            return Ok(0);
+        };
+        let count = sqlx::query_scalar::<_, i64>(&format!(
+            "SELECT COUNT(*) FROM {JOBS_TABLE} WHERE failed_at IS NULL"
+        ))
+        .fetch_one(db)
+        .await?;
+        Ok(count)
+    }
+
+    /// Delivers due jobs to `handlers` every `poll_interval` until
+    /// `cancellation_token` fires.
+    pub(crate) fn spawn(
+        self,
+        handlers: HashMap<String, Arc<dyn DynamicJobHandler>>,
+        poll_interval: Duration,
+        cancellation_token: CancellationToken,
+    ) -> Option<tokio::task::JoinHandle<()>> {
+        let db = self.db?;
+        Some(tokio::spawn(async move {
+            while !cancellation_token.is_cancelled() {
+                match run_due(&db, &handlers).await {
+                    // A full batch means more jobs are probably due
+                    Ok(claimed) if claimed == CLAIM_BATCH as usize => continue,
+                    Ok(_) => {}
+                    Err(e) => logging::log_error("Deferred job poll error", &e.to_string()),
+                }
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    () = tokio::time::sleep(poll_interval) => {}
+                }
+            }
+        }))
+    }
+}
+
+/// Claims the jobs due now and runs them, returning how many were claimed.
+async fn run_due(
+    db: &PgPool,
+    handlers: &HashMap<String, Arc<dyn DynamicJobHandler>>,
+) -> Result<usize> {
+    let jobs = sqlx::query_as::<_, (i64, String, serde_json::Value, i32)>(&format!(
+        "UPDATE {JOBS_TABLE}
+         SET run_at = NOW() + $1 * INTERVAL '1 millisecond', attempts = attempts + 1
+         WHERE id IN (
+             SELECT id FROM {JOBS_TABLE}
+             WHERE run_at <= NOW() AND failed_at IS NULL
+             ORDER BY run_at
+             LIMIT $2
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, kind, payload, attempts"
+    ))
+    .bind(millis(LEASE))
+    .bind(CLAIM_BATCH)
+    .fetch_all(db)
+    .await?;
+
+    let claimed = jobs.len();
+    for (id, kind, payload, attempts) in jobs {
+        let outcome = match handlers.get(&kind) {
+            Some(handler) => handler.run_dynamic(payload, db).await,
+            None => Err(SolanaIndexerError::Config(format!(
+                "No job handler registered for '{kind}'"
+            ))),
+        };
+        match outcome {
+            Ok(()) => {
+                sqlx::query(&format!("DELETE FROM {JOBS_TABLE} WHERE id = $1"))
+                    .bind(id)
+                    .execute(db)
+                    .await?;
+            }
+            Err(e) => {
+                logging::log_error(
+                    "Deferred job failed",
+                    &format!("{kind} #{id}, attempt {attempts}: {e}"),
+                );
+                sqlx::query(&format!(
+                    "UPDATE {JOBS_TABLE}
+                     SET last_error = $2,
+                         run_at = NOW() + $3 * INTERVAL '1 millisecond',
+                         failed_at = CASE WHEN attempts >= $4 THEN NOW() END
+                     WHERE id = $1"
+                ))
+                .bind(id)
+                .bind(e.to_string())
+                .bind(millis(retry_delay(attempts)))
+                .bind(MAX_ATTEMPTS)
+                .execute(db)
+                .await?;
+            }
+        }
+    }
+    Ok(claimed)
+}
+
+/// Delay before the next attempt of a job that failed `attempts` times:
+/// 10 seconds, doubling up to an hour.
+fn retry_delay(attempts: i32) -> Duration {
+    let doublings = u32::try_from(attempts.saturating_sub(1))
+        .unwrap_or(0)
+        .min(9);
+    Duration::from_secs(10 * 2u64.pow(doublings)).min(Duration::from_secs(3600))
+}
+
+fn millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+/// Creates the jobs table.
+#[async_trait]
+impl SchemaInitializer for DeferredJobs {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {JOBS_TABLE} (
+                id BIGSERIAL PRIMARY KEY,
+                kind TEXT NOT NULL,
+                payload JSONB NOT NULL,
+                run_at TIMESTAMPTZ NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                last_error TEXT,
+                failed_at TIMESTAMPTZ,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS {JOBS_TABLE}_due_idx
+             ON {JOBS_TABLE} (run_at) WHERE failed_at IS NULL"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Recheck {
+        signature: String,
+    }
+
+    impl DeferredJob for Recheck {
+        fn kind() -> &'static str {
+            "recheck"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_jobs_are_refused() {
+        let jobs = DeferredJobs::default();
+        assert!(!jobs.is_enabled());
+        let job = Recheck {
+            signature: "sig".to_string(),
+        };
+        assert!(matches!(
+            jobs.schedule_in(Duration::from_secs(1), &job).await,
+            Err(SolanaIndexerError::Config(_))
+        ));
+        assert_eq!(jobs.pending().await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_an_hour() {
+        assert_eq!(retry_delay(1), Duration::from_secs(10));
+        assert_eq!(retry_delay(2), Duration::from_secs(20));
+        assert_eq!(retry_delay(4), Duration::from_secs(80));
+        assert_eq!(retry_delay(12), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,259 @@
+//! Throwaway server from the local `PostgreSQL` installation, for quick
+//! starts.
+//!
+//! With the `local-postgres` feature, a configuration built without
+//! `with_database` gets `LOCAL_DATABASE_URL`, and `SolanaIndexer::new`
+//! starts a private server for it: a fresh cluster in a temporary directory.
+//! The server is stopped and its directory removed when the indexer is
+//! dropped, so nothing survives the process.
+//!
+//! Nothing is bundled: the cluster is created with the `initdb` and `pg_ctl`
+//! of a `PostgreSQL` installation, found in `PG_BIN_DIR`, `pg_config
+//! --bindir` or `PATH`, and starting fails if they are missing.
+//! `PostgreSQL` refuses to run as root, so neither does this.
+//!
+//! The server does not listen on TCP. It only accepts connections on a Unix
+//! socket in a directory private to the current user, and requires the
+//! superuser's password, generated for each run.
+//!
+//! Meant for trying the SDK and examples out; use a real database for
+//! anything that should keep its data.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Database URL standing for a local server started by the indexer.
+pub const LOCAL_DATABASE_URL: &str = "local-postgres:";
+
+/// Environment variable naming the directory of the `PostgreSQL` binaries.
+const BIN_DIR_ENV: &str = "PG_BIN_DIR";
+
+/// Superuser of the cluster.
+const SUPERUSER: &str = "postgres";
+
+/// Port naming the server's socket file.
+const PORT: u16 = 5432;
+
+/// A private `PostgreSQL` server, stopped when dropped.
+#[derive(Debug)]
+pub struct LocalPostgres {
+    bin_dir: PathBuf,
+    root: PathBuf,
+    password: String,
+}
+
+impl LocalPostgres {
+    /// Creates a cluster in a new temporary directory and starts it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Connection` if `initdb` and `pg_ctl`
+    /// cannot be found, or the cluster fails to initialize or start.
+    pub async fn start() -> Result<Self> {
+        let bin_dir = find_bin_dir(std::env::var_os(BIN_DIR_ENV).map(PathBuf::from)).await?;
+        let password = random_hex();
+        let root = std::env::temp_dir().join(format!(
+            "solana-indexer-pg-{}-{}",
+            std::process::id(),
+            &random_hex()[..8]
+        ));
+        create_private_dir(&root)?;
+        let server = Self {
+            bin_dir,
+            root,
+            password,
+        };
+        create_private_dir(&server.socket_dir())?;
+
+        let password_file = server.root.join("password");
+        std::fs::write(&password_file, &server.password).map_err(|e| {
+            SolanaIndexerError::Connection(format!(
+                "Failed to write local PostgreSQL password file: {e}"
+            ))
+        })?;
+        let data_dir = server.data_dir().to_string_lossy().into_owned();
+        let initialized = server
+            .run(
+                "initdb",
+                &[
+                    "-D",
+                    &data_dir,
+                    "-U",
+                    SUPERUSER,
+                    "-A",
+                    "scram-sha-256",
+                    "--pwfile",
+                    &password_file.to_string_lossy(),
+                    "-E",
+                    "UTF8",
+                    "--no-sync",
+                ],
+            )
+            .await;
+        let _ = std::fs::remove_file(&password_file);
+        initialized?;
+
+        let options = format!(
+            "-p {PORT} -c listen_addresses='' -c unix_socket_directories='{}' -c fsync=off",
+            server.socket_dir().display()
+        );
+        let log = server.root.join("postgres.log");
+        server
+            .run(
+                "pg_ctl",
+                &[
+                    "start",
+                    "-w",
+                    "-D",
+                    &data_dir,
+                    "-l",
+                    &log.to_string_lossy(),
+                    "-o",
+                    &options,
+                ],
+            )
+            .await?;
+
+        logging::log(
+            logging::LogLevel::Info,
+            &format!("Started local PostgreSQL in {}", server.root.display()),
+        );
+        Ok(server)
+    }
+
+    /// Returns the URL to connect to the server with.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!(
+            "postgresql://{SUPERUSER}:{}@localhost:{PORT}/postgres?host={}",
+            self.password,
+            self.socket_dir().display()
+        )
+    }
+
+    /// Returns the directory holding the server's Unix socket.
+    #[must_use]
+    pub fn socket_dir(&self) -> PathBuf {
+        self.root.join("socket")
+    }
+
+    /// Returns the directory holding the cluster.
+    #[must_use]
+    pub fn data_dir(&self) -> PathBuf {
+        self.root.join("data")
+    }
+
+    /// Runs `program` to completion, failing with its output if it fails.
+    async fn run(&self, program: &str, args: &[&str]) -> Result<()> {
+        let output = Command::new(self.bin_dir.join(program))
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| {
+                SolanaIndexerError::Connection(format!(
+                    "Failed to run {program} for local PostgreSQL: {e}"
+                ))
+            })?;
+        if output.status.success() {
+            return Ok(());
+        }
+        Err(SolanaIndexerError::Connection(format!(
+            "{program} failed for local PostgreSQL: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+impl Drop for LocalPostgres {
+    fn drop(&mut self) {
+        let _ = std::process::Command::new(self.bin_dir.join("pg_ctl"))
+            .args(["stop", "-m", "immediate", "-D"])
+            .arg(self.data_dir())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Finds the directory holding both `initdb` and `pg_ctl`: `explicit` if
+/// given, otherwise `pg_config --bindir` or the first match on `PATH`.
+async fn find_bin_dir(explicit: Option<PathBuf>) -> Result<PathBuf> {
+    let has_binaries = |dir: &Path| dir.join("initdb").is_file() && dir.join("pg_ctl").is_file();
+
+    if let Some(dir) = explicit {
+        return if has_binaries(&dir) {
+            Ok(dir)
+        } else {
+            Err(SolanaIndexerError::Connection(format!(
+                "Local PostgreSQL needs initdb and pg_ctl, which are not in {BIN_DIR_ENV} ({})",
+                dir.display()
+            )))
+        };
+    }
+
+    if let Ok(output) = Command::new("pg_config").arg("--bindir").output().await {
+        let dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+        if output.status.success() && has_binaries(&dir) {
+            return Ok(dir);
+        }
+    }
+    std::env::var_os("PATH")
+        .and_then(|path| std::env::split_paths(&path).find(|dir| has_binaries(dir)))
+        .ok_or_else(|| {
+            SolanaIndexerError::Connection(format!(
+                "Local PostgreSQL needs initdb and pg_ctl, which were not found; install \
+                 PostgreSQL, set {BIN_DIR_ENV} to their directory, or configure a database URL"
+            ))
+        })
+}
+
+/// Creates `dir` readable only by the current user.
+fn create_private_dir(dir: &Path) -> Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir).map_err(|e| {
+        SolanaIndexerError::Connection(format!(
+            "Failed to create {} for local PostgreSQL: {e}",
+            dir.display()
+        ))
+    })
+}
+
+/// Returns 128 random bits as hex, from the OS-seeded hasher keys.
+fn random_hex() -> String {
+    let mut hex = String::new();
+    for _ in 0..2 {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(0);
+        hex.push_str(&format!("{:016x}", hasher.finish()));
+    }
+    hex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_missing_binaries_are_reported() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("solana-indexer-no-pg-{}", random_hex()));
+        create_private_dir(&dir)?;
+
+        let result = find_bin_dir(Some(dir.clone())).await;
+        let _ = std::fs::remove_dir_all(&dir);
+        let Err(error) = result else {
+            panic!("an empty directory has no PostgreSQL binaries");
+        };
+        assert!(error.to_string().contains("initdb and pg_ctl"));
+        assert!(error.to_string().contains(BIN_DIR_ENV));
+        Ok(())
+    }
+}
@@ -2,13 +2,57 @@
 //!
 //! This module provides database interaction utilities, connection pool management,
 //! and idempotency tracking to ensure reliable transaction processing.
-
-use crate::utils::error::Result;
+//!
+//! Backfill progress is kept in two tables. `_solana_indexer_sdk_backfill_progress`
+//! holds a single row: the highest slot backfilled and whether the backfill
+//! is complete (`save_backfill_progress`, `mark_backfill_complete`). It
+//! decides where the next backfill starts and feeds the backfill trigger.
+//! `_solana_indexer_sdk_backfill_ranges` holds one row per backfill range, with
+//! the signature cursor inside its current slot, its transaction count and
+//! timings (`begin_backfill_range`, `advance_backfill_range`). It lets an
+//! interrupted range resume mid-slot and backs `SolanaIndexer::backfill_status`.
+
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::core::execution::watermark::WATERMARK_CHANNEL;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::{Result, SolanaIndexerError};
+use buffer::BufferedQuery;
+use partitions::{PartitionConfig, PartitionReport};
+use retention::{RetentionConfig, RetentionReport};
 use sqlx::postgres::{PgPool, PgPoolOptions};
 use std::time::Duration;
+use vacuum::{VacuumConfig, VacuumReport};
 
 use async_trait::async_trait;
 
+pub mod archive;
+pub mod atomic;
+pub mod buffer;
+pub mod cache;
+pub mod cold;
+pub mod copy;
+pub mod cursor;
+pub mod dlq;
+pub mod flags;
+pub mod holders;
+pub mod invalidation;
+pub mod jobs;
+#[cfg(feature = "local-postgres")]
+pub mod local_postgres;
+pub mod outbox;
+pub mod partitions;
+pub mod replica;
+pub mod retention;
+pub mod spill;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod state;
+pub mod stats;
+pub mod unhandled;
+pub mod vacuum;
+
 /// Abstract interface for storage operations.
 #[async_trait]
 pub trait StorageBackend: Send + Sync {
@@ -17,7 +61,27 @@ pub trait StorageBackend: Send + Sync {
     async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()>;
     async fn get_last_processed_slot(&self) -> Result<Option<u64>>;
     async fn get_last_processed_signature(&self) -> Result<Option<String>>;
-    fn pool(&self) -> &PgPool;
+
+    /// Returns the `PostgreSQL` pool handed to handlers and schema
+    /// initializers, or `None` if the backend has none (SQLite storage
+    /// without a handler database).
+    fn pool(&self) -> Option<&PgPool>;
+
+    /// Returns the `PostgreSQL` pool handed to handlers and schema
+    /// initializers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the backend has none.
+    fn require_pool(&self) -> Result<&PgPool> {
+        self.pool().ok_or_else(|| {
+            SolanaIndexerError::Config(
+                "Storage has no PostgreSQL database for handlers; \
+                 set one with with_handler_database"
+                    .to_string(),
+            )
+        })
+    }
 
     // New methods for reorg handling and backfill
     async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()>;
@@ -32,6 +96,402 @@ pub trait StorageBackend: Send + Sync {
     async fn save_backfill_progress(&self, slot: u64) -> Result<()>;
     async fn load_backfill_progress(&self) -> Result<Option<u64>>;
     async fn mark_backfill_complete(&self) -> Result<()>;
+
+    // Per-range backfill progress
+
+    /// Records that a run over `range` started and returns the slot to
+    /// resume from, with the last signature handled in it if the previous
+    /// run stopped partway through the slot. A range backfilled before
+    /// starts over from its first slot.
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        Ok((range.start_slot, None))
+    }
+
+    /// Records that `range` is backfilled through `slot`, with
+    /// `transactions` more transactions processed. With a `cursor`, `slot`
+    /// is only handled through that signature.
+    async fn advance_backfill_range(
+        &self,
+        _range: BackfillRange,
+        _slot: u64,
+        _cursor: Option<&str>,
+        _transactions: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the recorded progress of every backfill range.
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        Ok(Vec::new())
+    }
+
+    // Per-address polling cursors
+
+    /// Stores the newest signature polled for `address`.
+    async fn save_cursor(&self, _address: &str, _signature: &str, _slot: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Loads the newest signature polled for `address`, if any.
+    async fn load_cursor(&self, _address: &str) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    // Program-account snapshots
+
+    /// Records that the accounts of `program_id` were bootstrapped from a
+    /// snapshot taken at `slot`.
+    async fn save_snapshot_slot(&self, _program_id: &str, _slot: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the slot of the last completed snapshot of `program_id`, if any.
+    async fn load_snapshot_slot(&self, _program_id: &str) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    // Processed-slot watermark for consistent reads
+
+    /// Raises the processed-slot watermark to `slot`.
+    ///
+    /// Called after a batch commits. Every transaction at or below the
+    /// watermark has been fully handled, so readers that bound their queries
+    /// by it never observe a half-processed batch. The watermark never moves
+    /// backwards.
+    async fn advance_watermark(&self, _slot: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the processed-slot watermark, or `None` if no batch has committed yet.
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        Ok(None)
+    }
+
+    // Signature confirmation tracking
+
+    /// Records that `signature` reached `level`.
+    ///
+    /// Reaching a level implies the lower ones. The first time each level is
+    /// recorded is kept; later calls never overwrite it.
+    async fn record_commitment(
+        &self,
+        _signature: &str,
+        _slot: u64,
+        _level: CommitmentLevel,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns up to `limit` tracked signatures that are not finalized yet,
+    /// ordered by slot and signature, starting after `after` (a previous
+    /// slot and signature) when given.
+    async fn load_unfinalized_signatures(
+        &self,
+        _after: Option<(u64, &str)>,
+        _limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns up to `limit` processed signatures whose slot lies within
+    /// `slots` (inclusive), ordered by slot and signature, starting after
+    /// `after` when given.
+    async fn load_processed_signatures(
+        &self,
+        _slots: (u64, u64),
+        _after: Option<(u64, &str)>,
+        _limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        Ok(Vec::new())
+    }
+
+    /// Returns the commitment progression of `signature`, if it is tracked.
+    async fn load_confirmation_status(
+        &self,
+        _signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        Ok(None)
+    }
+
+    /// Summarizes the commitment progression of every tracked signature.
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        Ok(ConfirmationSummary::default())
+    }
+
+    // Dead-lettered transactions
+
+    /// Records that `signature` was set aside without being handled, and why.
+    ///
+    /// Recording the same signature again replaces its reason.
+    async fn record_dead_letter(&self, _signature: &str, _slot: u64, _reason: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns up to `limit` dead-lettered transactions, oldest first.
+    ///
+    /// Events dead-lettered by a failing handler share the table but are
+    /// read through `DeadLetterQueue`.
+    async fn load_dead_letters(&self, _limit: usize) -> Result<Vec<DeadLetter>> {
+        Ok(Vec::new())
+    }
+
+    // Per-program usage
+
+    /// Adds `records` to the usage counted for `day` (UTC, `YYYY-MM-DD`).
+    async fn record_usage(&self, _day: &str, _records: &[UsageRecord]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the usage counted for `day`, ordered by program.
+    async fn load_usage_report(&self, _day: &str) -> Result<Vec<UsageRecord>> {
+        Ok(Vec::new())
+    }
+
+    // Bulk loading
+    /// Marks many signatures processed at once, as `(signature, slot)`
+    /// pairs. The default marks them one by one; `PostgreSQL` storage loads
+    /// them with a single `COPY`.
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        for (signature, slot) in entries {
+            self.mark_processed(signature, *slot).await?;
+        }
+        Ok(())
+    }
+
+    // Atomic handler writes
+    /// Runs `writes`, then marks `signature` processed, or tentative in
+    /// `block_hash` when given. `PostgreSQL` storage does all of it in one
+    /// transaction; the default runs them one after another.
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        let pool = self.require_pool()?;
+        for query in writes {
+            query.execute(pool).await?;
+        }
+        match block_hash {
+            Some(block_hash) => self.mark_tentative(signature, slot, block_hash).await,
+            None => self.mark_processed(signature, slot).await,
+        }
+    }
+
+    // Table partitioning
+    /// Creates upcoming partitions of the processed-signatures table and
+    /// detaches expired ones. The default does nothing.
+    async fn maintain_partitions(&self, _tip_slot: Option<u64>) -> Result<PartitionReport> {
+        Ok(PartitionReport::default())
+    }
+
+    // Retention
+
+    /// Prunes rows older than `config.max_age`, measuring slot ages from
+    /// `tip_slot` or, when it is `None`, the last processed slot. The
+    /// default prunes nothing.
+    async fn prune(
+        &self,
+        _config: &RetentionConfig,
+        _tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        Ok(RetentionReport::default())
+    }
+
+    /// Runs `VACUUM` or `ANALYZE` on the SDK tables and `config`'s handler
+    /// tables, then `config`'s statements. The default does nothing.
+    async fn vacuum(&self, _config: &VacuumConfig) -> Result<VacuumReport> {
+        Ok(VacuumReport::default())
+    }
+
+    // Buffered writes
+
+    /// Commits any writes the backend has buffered. Unbuffered backends
+    /// write through and have nothing to do.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns the processed signatures a failed flush dropped since the
+    /// last call, so a cache in front of the backend can forget them.
+    /// Unbuffered backends never drop a signature and return none.
+    fn take_dropped_signatures(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Resolves the slot a read should be served at.
+    ///
+    /// Requests for a slot above the watermark (or no slot at all) are clamped
+    /// to the watermark. Returns `None` if nothing is readable yet.
+    async fn read_slot(&self, requested: Option<u64>) -> Result<Option<u64>> {
+        let watermark = self.get_watermark().await?;
+        Ok(match (requested, watermark) {
+            (Some(requested), Some(watermark)) => Some(requested.min(watermark)),
+            (None, watermark) => watermark,
+            (Some(_), None) => None,
+        })
+    }
+}
+
+/// Commitment progression of one tracked signature.
+///
+/// Timestamps are Unix seconds; `None` means the level has not been reached
+/// (or was reached before tracking started for a level skipped over).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfirmationStatus {
+    /// Slot the transaction landed in
+    pub slot: u64,
+    /// When the signature was first seen at processed commitment or higher
+    pub processed_at: Option<i64>,
+    /// When the signature was first seen at confirmed commitment or higher
+    pub confirmed_at: Option<i64>,
+    /// When the signature was first seen finalized
+    pub finalized_at: Option<i64>,
+}
+
+impl ConfirmationStatus {
+    /// Returns the highest commitment level reached.
+    #[must_use]
+    pub fn level(&self) -> CommitmentLevel {
+        if self.finalized_at.is_some() {
+            CommitmentLevel::Finalized
+        } else if self.confirmed_at.is_some() {
+            CommitmentLevel::Confirmed
+        } else {
+            CommitmentLevel::Processed
+        }
+    }
+}
+
+/// Counts of tracked signatures by commitment level, for data-quality reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ConfirmationSummary {
+    /// Signatures tracked
+    pub tracked: u64,
+    /// Signatures that reached confirmed commitment (including finalized ones)
+    pub confirmed: u64,
+    /// Signatures that reached finalized commitment
+    pub finalized: u64,
+    /// Average seconds from first sighting to finalization, over finalized signatures
+    pub avg_seconds_to_finalized: Option<f64>,
+}
+
+impl ConfirmationSummary {
+    /// Signatures not finalized yet.
+    #[must_use]
+    pub fn unfinalized(&self) -> u64 {
+        self.tracked.saturating_sub(self.finalized)
+    }
+}
+
+/// A transaction set aside without being handled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadLetter {
+    /// Transaction signature
+    pub signature: String,
+    /// Slot of the transaction
+    pub slot: u64,
+    /// Why the transaction was set aside
+    pub reason: String,
+    /// When it was recorded, in Unix seconds
+    pub recorded_at: i64,
+}
+
+/// Row of `_solana_indexer_sdk_backfill_ranges`, timestamps in Unix
+/// milliseconds.
+pub(crate) type BackfillRangeRow = (i64, i64, i64, Option<String>, i64, i64, i64, i64, i64);
+
+/// Builds a `RangeProgress` from a stored row.
+pub(crate) fn range_progress(row: BackfillRangeRow) -> RangeProgress {
+    let unsigned = |value: i64| u64::try_from(value).unwrap_or(0);
+    RangeProgress {
+        range: BackfillRange::new(unsigned(row.0), unsigned(row.1)),
+        next_slot: unsigned(row.2),
+        cursor: row.3,
+        transactions: unsigned(row.4),
+        run_start_slot: unsigned(row.5),
+        run_start_transactions: unsigned(row.6),
+        run_started_at: row.7,
+        updated_at: row.8,
+    }
+}
+
+/// Hex form of a usage discriminator in SQL; empty for RPC-call rows.
+pub(crate) fn discriminator_hex(discriminator: Option<[u8; 8]>) -> String {
+    discriminator
+        .map(|bytes| bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+        .unwrap_or_default()
+}
+
+/// Checks that `schema` is a plain identifier (lowercase letters, digits and
+/// underscores) that can be interpolated into SQL.
+pub(crate) fn validate_schema_name(schema: &str) -> Result<()> {
+    if schema.is_empty()
+        || !schema
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(SolanaIndexerError::Config(format!(
+            "Invalid schema name '{schema}'"
+        )));
+    }
+    Ok(())
+}
+
+/// Parses a discriminator written by `discriminator_hex`.
+pub(crate) fn parse_discriminator_hex(hex: &str) -> Option<[u8; 8]> {
+    if hex.len() != 16 {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Rank of a commitment level in SQL: 0 processed, 1 confirmed, 2 finalized.
+fn commitment_rank(level: CommitmentLevel) -> i32 {
+    match level {
+        CommitmentLevel::Processed => 0,
+        CommitmentLevel::Confirmed => 1,
+        CommitmentLevel::Finalized => 2,
+    }
+}
+
+/// Computes the watermark a batch of transactions allows.
+///
+/// Each outcome is the transaction's slot and whether it was handled
+/// successfully. If every transaction succeeded the watermark is the highest
+/// slot in the batch; otherwise it stops just below the lowest failed slot.
+/// Returns `None` for an empty batch or when the lowest slot failed.
+///
+/// The polling loop keeps cursors below failed signatures (see
+/// `settled_cursor`), so a failed signature is in every following batch, and
+/// holds the watermark below its slot, until it is processed.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::storage::batch_watermark;
+///
+/// assert_eq!(batch_watermark(&[(10, true), (12, true)]), Some(12));
+/// assert_eq!(batch_watermark(&[(10, true), (12, false), (14, true)]), Some(11));
+/// assert_eq!(batch_watermark(&[]), None);
+/// ```
+#[must_use]
+pub fn batch_watermark(outcomes: &[(u64, bool)]) -> Option<u64> {
+    let lowest_failure = outcomes
+        .iter()
+        .filter(|(_, ok)| !ok)
+        .map(|(slot, _)| *slot)
+        .min();
+
+    match lowest_failure {
+        Some(slot) => slot.checked_sub(1),
+        None => outcomes.iter().map(|(slot, _)| *slot).max(),
+    }
 }
 
 /// Database storage manager for the indexer.
@@ -59,6 +519,8 @@ pub trait StorageBackend: Send + Sync {
 pub struct Storage {
     /// `PostgreSQL` connection pool
     pool: PgPool,
+    /// Partitioning of the processed-signatures table, if enabled
+    partitioning: Option<PartitionConfig>,
 }
 
 impl Storage {
@@ -70,7 +532,7 @@ impl Storage {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if connection fails.
+    /// Returns `SolanaIndexerError::Storage` if connection fails.
     ///
     /// # Example
     ///
@@ -89,7 +551,58 @@ impl Storage {
             .connect(database_url)
             .await?;
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            partitioning: None,
+        })
+    }
+
+    /// Creates a storage instance whose tables live in `schema`.
+    ///
+    /// The schema is created if it does not exist, and every pooled
+    /// connection uses it as its `search_path`, so unqualified table names in
+    /// handlers and schema initializers resolve to it as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `schema` is not a plain
+    /// identifier (lowercase letters, digits and underscores), or
+    /// `SolanaIndexerError::Storage` if connection fails.
+    pub async fn new_in_schema(database_url: &str, schema: &str) -> Result<Self> {
+        validate_schema_name(schema)?;
+
+        let search_path = format!("SET search_path TO {schema}");
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(3))
+            .after_connect(move |conn, _meta| {
+                let search_path = search_path.clone();
+                Box::pin(async move {
+                    sqlx::query(&search_path).execute(conn).await?;
+                    Ok(())
+                })
+            })
+            .connect(database_url)
+            .await?;
+
+        sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {schema}"))
+            .execute(&pool)
+            .await?;
+
+        Ok(Self {
+            pool,
+            partitioning: None,
+        })
+    }
+
+    /// Partitions the processed-signatures table under `config`.
+    ///
+    /// Takes effect when `initialize` creates the table; see
+    /// [`partitions`] for the maintenance that keeps partitions current.
+    #[must_use]
+    pub fn with_partitioning(mut self, config: PartitionConfig) -> Self {
+        self.partitioning = Some(config);
+        self
     }
 
     /// Returns a reference to the connection pool.
@@ -117,7 +630,7 @@ impl Storage {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if migration fails.
+    /// Returns `SolanaIndexerError::Storage` if migration fails.
     ///
     /// # Example
     ///
@@ -130,17 +643,21 @@ impl Storage {
     /// # }
     /// ```
     pub async fn initialize(&self) -> Result<()> {
-        sqlx::query(
-            r"
-            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_processed (
-                signature TEXT PRIMARY KEY,
-                slot BIGINT NOT NULL,
-                indexed_at TIMESTAMPTZ DEFAULT NOW()
+        if let Some(config) = self.partitioning {
+            self.create_partitioned_processed(config.scheme).await?;
+        } else {
+            sqlx::query(
+                r"
+                CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_processed (
+                    signature TEXT PRIMARY KEY,
+                    slot BIGINT NOT NULL,
+                    indexed_at TIMESTAMPTZ DEFAULT NOW()
+                )
+                ",
             )
-            ",
-        )
-        .execute(&self.pool)
-        .await?;
+            .execute(&self.pool)
+            .await?;
+        }
 
         // Create index for faster lookups
         sqlx::query(
@@ -203,6 +720,111 @@ impl Storage {
         .execute(&self.pool)
         .await?;
 
+        // Per-range backfill progress
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_backfill_ranges (
+                start_slot BIGINT NOT NULL,
+                end_slot BIGINT NOT NULL,
+                next_slot BIGINT NOT NULL,
+                cursor TEXT,
+                transactions BIGINT NOT NULL DEFAULT 0,
+                run_start_slot BIGINT NOT NULL,
+                run_start_transactions BIGINT NOT NULL DEFAULT 0,
+                run_started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (start_slot, end_slot)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Per-address polling cursors
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_cursors (
+                address TEXT PRIMARY KEY,
+                signature TEXT NOT NULL,
+                slot BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT NOW()
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Processed-slot watermark table
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_watermark (
+                id INTEGER PRIMARY KEY,
+                slot BIGINT NOT NULL,
+                updated_at TIMESTAMPTZ DEFAULT NOW()
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Program-account snapshots
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_snapshots (
+                program_id TEXT PRIMARY KEY,
+                slot BIGINT NOT NULL,
+                completed_at TIMESTAMPTZ DEFAULT NOW()
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Signature commitment progression
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_confirmations (
+                signature TEXT PRIMARY KEY,
+                slot BIGINT NOT NULL,
+                processed_at TIMESTAMPTZ,
+                confirmed_at TIMESTAMPTZ,
+                finalized_at TIMESTAMPTZ
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r"
+            CREATE INDEX IF NOT EXISTS idx_confirmations_unfinalized
+            ON _solana_indexer_sdk_confirmations(slot, signature)
+            WHERE finalized_at IS NULL
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Daily per-program usage
+        sqlx::query(
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_usage (
+                day TEXT NOT NULL,
+                program TEXT NOT NULL,
+                discriminator TEXT NOT NULL,
+                events BIGINT NOT NULL DEFAULT 0,
+                bytes BIGINT NOT NULL DEFAULT 0,
+                rpc_calls BIGINT NOT NULL DEFAULT 0,
+                PRIMARY KEY (day, program, discriminator)
+            )
+            ",
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Transactions set aside by guardrails, and failed handler events
+        dlq::create_table(&self.pool).await?;
+
         Ok(())
     }
 
@@ -214,7 +836,7 @@ impl Storage {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if query fails.
+    /// Returns `SolanaIndexerError::Storage` if query fails.
     ///
     /// # Returns
     ///
@@ -264,7 +886,7 @@ impl Storage {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if insert fails.
+    /// Returns `SolanaIndexerError::Storage` if insert fails.
     ///
     /// # Example
     ///
@@ -288,11 +910,38 @@ impl Storage {
         Ok(())
     }
 
+    /// Marks many signatures processed with one binary `COPY`.
+    ///
+    /// Signatures that are already marked are skipped. Backfills use this
+    /// once per block instead of inserting each signature on its own.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the copy fails, in
+    /// which case none of the signatures are marked.
+    pub async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut rows = copy::CopyRows::new(2);
+        for (signature, slot) in entries {
+            rows.push(vec![signature.as_str().into(), (*slot).into()])?;
+        }
+        copy::BulkLoader::new(self.pool.clone())
+            .copy_ignoring_conflicts(
+                "_solana_indexer_sdk_processed",
+                &["signature", "slot"],
+                rows,
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Gets the last processed slot number.
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if query fails.
+    /// Returns `SolanaIndexerError::Storage` if query fails.
     ///
     /// # Returns
     ///
@@ -325,7 +974,7 @@ impl Storage {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DatabaseError` if query fails.
+    /// Returns `SolanaIndexerError::Storage` if query fails.
     ///
     /// # Returns
     ///
@@ -358,6 +1007,50 @@ impl Storage {
         self.pool.close().await;
     }
 
+    /// Runs `writes` and marks `signature` processed, or tentative in
+    /// `block_hash` when given, in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a write or the mark
+    /// fails, in which case nothing is committed.
+    pub async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        let slot = i64::try_from(slot).unwrap_or(i64::MAX);
+        let mut tx = self.pool.begin().await?;
+        for query in writes {
+            query.execute(&mut *tx).await?;
+        }
+        match block_hash {
+            Some(block_hash) => {
+                sqlx::query(
+                    "INSERT INTO _solana_indexer_sdk_tentative (signature, slot, block_hash) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+                )
+                .bind(signature)
+                .bind(slot)
+                .bind(block_hash)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    "INSERT INTO _solana_indexer_sdk_processed (signature, slot) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                )
+                .bind(signature)
+                .bind(slot)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
         sqlx::query(
             "INSERT INTO _solana_indexer_sdk_tentative (signature, slot, block_hash) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
@@ -387,7 +1080,7 @@ impl Storage {
             SELECT signature, slot, indexed_at 
             FROM _solana_indexer_sdk_tentative 
             WHERE slot = $1
-            ON CONFLICT (signature) DO NOTHING
+            ON CONFLICT DO NOTHING
             ",
         )
         .bind(i64::try_from(slot).unwrap_or(i64::MAX))
@@ -525,22 +1218,552 @@ impl Storage {
         .await?;
         Ok(())
     }
-}
-
-#[async_trait]
-impl StorageBackend for Storage {
-    async fn initialize(&self) -> Result<()> {
-        self.initialize().await
-    }
 
-    async fn is_processed(&self, signature: &str) -> Result<bool> {
-        self.is_processed(signature).await
-    }
+    /// Records that a run over `range` started and returns the slot to
+    /// resume from, with the last signature handled in it if the previous
+    /// run stopped partway through the slot. A range backfilled before
+    /// starts over from its first slot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn begin_backfill_range(
+        &self,
+        range: BackfillRange,
+    ) -> Result<(u64, Option<String>)> {
+        let (next_slot, cursor) = sqlx::query_as::<_, (i64, Option<String>)>(
+            r"
+            INSERT INTO _solana_indexer_sdk_backfill_ranges AS p
+                (start_slot, end_slot, next_slot, run_start_slot)
+            VALUES ($1, $2, $1, $1)
+            ON CONFLICT (start_slot, end_slot) DO UPDATE SET
+                next_slot = CASE WHEN p.next_slot > p.end_slot THEN p.start_slot ELSE p.next_slot END,
+                cursor = CASE WHEN p.next_slot > p.end_slot THEN NULL ELSE p.cursor END,
+                transactions = CASE WHEN p.next_slot > p.end_slot THEN 0 ELSE p.transactions END,
+                run_start_slot = CASE WHEN p.next_slot > p.end_slot THEN p.start_slot ELSE p.next_slot END,
+                run_start_transactions = CASE WHEN p.next_slot > p.end_slot THEN 0 ELSE p.transactions END,
+                run_started_at = NOW(),
+                updated_at = NOW()
+            RETURNING next_slot, cursor
+            ",
+        )
+        .bind(i64::try_from(range.start_slot).unwrap_or(i64::MAX))
+        .bind(i64::try_from(range.end_slot).unwrap_or(i64::MAX))
+        .fetch_one(&self.pool)
+        .await?;
+        Ok((u64::try_from(next_slot).unwrap_or(range.start_slot), cursor))
+    }
+
+    /// Records that `range` is backfilled through `slot`, with
+    /// `transactions` more transactions processed. With a `cursor`, `slot`
+    /// is only handled through that signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the update fails.
+    pub async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        // A slot handled partway through stays the next one to backfill
+        let next_slot = if cursor.is_some() {
+            slot
+        } else {
+            slot.saturating_add(1)
+        };
+        sqlx::query(
+            r"
+            UPDATE _solana_indexer_sdk_backfill_ranges
+            SET next_slot = GREATEST(next_slot, $3),
+                cursor = $4,
+                transactions = transactions + $5,
+                updated_at = NOW()
+            WHERE start_slot = $1 AND end_slot = $2
+            ",
+        )
+        .bind(i64::try_from(range.start_slot).unwrap_or(i64::MAX))
+        .bind(i64::try_from(range.end_slot).unwrap_or(i64::MAX))
+        .bind(i64::try_from(next_slot).unwrap_or(i64::MAX))
+        .bind(cursor)
+        .bind(i64::try_from(transactions).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads the recorded progress of every backfill range.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        let rows = sqlx::query_as::<_, BackfillRangeRow>(
+            r"
+            SELECT start_slot, end_slot, next_slot, cursor, transactions,
+                   run_start_slot, run_start_transactions,
+                   (EXTRACT(EPOCH FROM run_started_at) * 1000)::BIGINT,
+                   (EXTRACT(EPOCH FROM updated_at) * 1000)::BIGINT
+            FROM _solana_indexer_sdk_backfill_ranges
+            ORDER BY start_slot, end_slot
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(range_progress).collect())
+    }
+
+    /// Stores the newest signature polled for `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_cursors (address, signature, slot, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (address) DO UPDATE
+            SET signature = EXCLUDED.signature, slot = EXCLUDED.slot, updated_at = NOW()
+            ",
+        )
+        .bind(address)
+        .bind(signature)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads the newest signature polled for `address`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        let signature = sqlx::query_scalar::<_, String>(
+            "SELECT signature FROM _solana_indexer_sdk_cursors WHERE address = $1",
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(signature)
+    }
+
+    /// Records the slot of a completed program-account snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_snapshots (program_id, slot, completed_at)
+            VALUES ($1, $2, NOW())
+            ON CONFLICT (program_id) DO UPDATE
+            SET slot = EXCLUDED.slot, completed_at = NOW()
+            ",
+        )
+        .bind(program_id)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads the slot of the last completed snapshot of `program_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>(
+            "SELECT slot FROM _solana_indexer_sdk_snapshots WHERE program_id = $1",
+        )
+        .bind(program_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(slot.map(|s| s.try_into().unwrap_or(0)))
+    }
+
+    /// Raises the processed-slot watermark to `slot`, never lowering it.
+    ///
+    /// When the watermark moves, the new slot is sent as a notification on
+    /// the `solana_indexer_sdk_watermark` channel, delivered to listeners
+    /// once the update commits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            WITH advanced AS (
+                INSERT INTO _solana_indexer_sdk_watermark (id, slot, updated_at)
+                VALUES (1, $1, NOW())
+                ON CONFLICT (id) DO UPDATE
+                SET slot = EXCLUDED.slot, updated_at = NOW()
+                WHERE _solana_indexer_sdk_watermark.slot < EXCLUDED.slot
+                RETURNING slot
+            )
+            SELECT pg_notify($2, slot::text) FROM advanced
+            ",
+        )
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .bind(WATERMARK_CHANNEL)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the processed-slot watermark.
+    ///
+    /// Queries that filter on `slot <= watermark` see only fully processed
+    /// batches.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use solana_indexer_sdk::Storage;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let storage = Storage::new("postgresql://localhost/mydb").await?;
+    /// if let Some(watermark) = storage.get_watermark().await? {
+    ///     let rows: Vec<String> = sqlx::query_scalar(
+    ///         "SELECT signature FROM _solana_indexer_sdk_processed WHERE slot <= $1",
+    ///     )
+    ///     .bind(watermark as i64)
+    ///     .fetch_all(storage.pool())
+    ///     .await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_watermark(&self) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>(
+            "SELECT slot FROM _solana_indexer_sdk_watermark WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(slot.map(|s| s.try_into().unwrap_or(0)))
+    }
+
+    /// Records that `signature` reached `level`, keeping the first time each
+    /// level was seen.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_confirmations
+                (signature, slot, processed_at, confirmed_at, finalized_at)
+            VALUES (
+                $1, $2, NOW(),
+                CASE WHEN $3 >= 1 THEN NOW() END,
+                CASE WHEN $3 >= 2 THEN NOW() END
+            )
+            ON CONFLICT (signature) DO UPDATE SET
+                slot = EXCLUDED.slot,
+                confirmed_at = COALESCE(_solana_indexer_sdk_confirmations.confirmed_at, EXCLUDED.confirmed_at),
+                finalized_at = COALESCE(_solana_indexer_sdk_confirmations.finalized_at, EXCLUDED.finalized_at)
+            ",
+        )
+        .bind(signature)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .bind(commitment_rank(level))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads up to `limit` processed signatures with a slot within `slots`,
+    /// ordered by slot and signature, starting after `after`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        let (after_slot, after_signature) = after.unwrap_or((slots.0, ""));
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            r"
+            SELECT slot, signature FROM _solana_indexer_sdk_processed
+            WHERE slot <= $1 AND (slot, signature) > ($2, $3)
+            ORDER BY slot, signature
+            LIMIT $4
+            ",
+        )
+        .bind(i64::try_from(slots.1).unwrap_or(i64::MAX))
+        .bind(i64::try_from(after_slot).unwrap_or(i64::MAX))
+        .bind(after_signature)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(slot, signature)| (slot.try_into().unwrap_or(0), signature))
+            .collect())
+    }
+
+    /// Loads up to `limit` tracked signatures that are not finalized yet,
+    /// ordered by slot and signature, starting after `after`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        let (after_slot, after_signature) = after.unwrap_or((0, ""));
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            r"
+            SELECT slot, signature FROM _solana_indexer_sdk_confirmations
+            WHERE finalized_at IS NULL AND (slot, signature) > ($1, $2)
+            ORDER BY slot, signature
+            LIMIT $3
+            ",
+        )
+        .bind(i64::try_from(after_slot).unwrap_or(i64::MAX))
+        .bind(after_signature)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(slot, signature)| (slot.try_into().unwrap_or(0), signature))
+            .collect())
+    }
+
+    /// Loads the commitment progression of `signature`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        let row = sqlx::query_as::<_, (i64, Option<i64>, Option<i64>, Option<i64>)>(
+            r"
+            SELECT slot,
+                   EXTRACT(EPOCH FROM processed_at)::BIGINT,
+                   EXTRACT(EPOCH FROM confirmed_at)::BIGINT,
+                   EXTRACT(EPOCH FROM finalized_at)::BIGINT
+            FROM _solana_indexer_sdk_confirmations WHERE signature = $1
+            ",
+        )
+        .bind(signature)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(
+            |(slot, processed_at, confirmed_at, finalized_at)| ConfirmationStatus {
+                slot: slot.try_into().unwrap_or(0),
+                processed_at,
+                confirmed_at,
+                finalized_at,
+            },
+        ))
+    }
+
+    /// Summarizes the commitment progression of every tracked signature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        let (tracked, confirmed, finalized, avg_seconds_to_finalized) =
+            sqlx::query_as::<_, (i64, i64, i64, Option<f64>)>(
+                r"
+                SELECT COUNT(*),
+                       COUNT(confirmed_at),
+                       COUNT(finalized_at),
+                       AVG(EXTRACT(EPOCH FROM finalized_at - processed_at))::FLOAT8
+                FROM _solana_indexer_sdk_confirmations
+                ",
+            )
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(ConfirmationSummary {
+            tracked: tracked.try_into().unwrap_or(0),
+            confirmed: confirmed.try_into().unwrap_or(0),
+            finalized: finalized.try_into().unwrap_or(0),
+            avg_seconds_to_finalized,
+        })
+    }
+
+    /// Records that `signature` was set aside without being handled.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the upsert fails.
+    pub async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_dlq (signature, slot, error, retryable)
+            VALUES ($1, $2, $3, FALSE)
+            ON CONFLICT (signature) WHERE discriminator IS NULL DO UPDATE SET
+                slot = EXCLUDED.slot,
+                error = EXCLUDED.error,
+                created_at = NOW()
+            ",
+        )
+        .bind(signature)
+        .bind(i64::try_from(slot).unwrap_or(i64::MAX))
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Loads up to `limit` dead-lettered transactions, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        let rows = sqlx::query_as::<_, (String, i64, String, i64)>(
+            r"
+            SELECT signature, slot, error, EXTRACT(EPOCH FROM created_at)::BIGINT
+            FROM _solana_indexer_sdk_dlq
+            WHERE discriminator IS NULL
+            ORDER BY created_at, id
+            LIMIT $1
+            ",
+        )
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(signature, slot, reason, recorded_at)| DeadLetter {
+                signature,
+                slot: slot.try_into().unwrap_or(0),
+                reason,
+                recorded_at,
+            })
+            .collect())
+    }
+
+    /// Adds `records` to the usage counted for `day`, in one transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a write fails.
+    pub async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(
+                r"
+                INSERT INTO _solana_indexer_sdk_usage
+                    (day, program, discriminator, events, bytes, rpc_calls)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (day, program, discriminator) DO UPDATE SET
+                    events = _solana_indexer_sdk_usage.events + EXCLUDED.events,
+                    bytes = _solana_indexer_sdk_usage.bytes + EXCLUDED.bytes,
+                    rpc_calls = _solana_indexer_sdk_usage.rpc_calls + EXCLUDED.rpc_calls
+                ",
+            )
+            .bind(day)
+            .bind(&record.program)
+            .bind(discriminator_hex(record.discriminator))
+            .bind(i64::try_from(record.events).unwrap_or(i64::MAX))
+            .bind(i64::try_from(record.bytes).unwrap_or(i64::MAX))
+            .bind(i64::try_from(record.rpc_calls).unwrap_or(i64::MAX))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Loads the usage counted for `day`, ordered by program.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the query fails.
+    pub async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        let rows = sqlx::query_as::<_, (String, String, i64, i64, i64)>(
+            r"
+            SELECT program, discriminator, events, bytes, rpc_calls
+            FROM _solana_indexer_sdk_usage
+            WHERE day = $1
+            ORDER BY program, discriminator
+            ",
+        )
+        .bind(day)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(usage_record).collect())
+    }
+}
+
+/// Builds a `UsageRecord` from a usage table row.
+pub(crate) fn usage_record(
+    (program, discriminator, events, bytes, rpc_calls): (String, String, i64, i64, i64),
+) -> UsageRecord {
+    UsageRecord {
+        program,
+        discriminator: parse_discriminator_hex(&discriminator),
+        events: events.try_into().unwrap_or(0),
+        bytes: bytes.try_into().unwrap_or(0),
+        rpc_calls: rpc_calls.try_into().unwrap_or(0),
+    }
+}
+
+#[async_trait]
+impl StorageBackend for Storage {
+    async fn initialize(&self) -> Result<()> {
+        self.initialize().await
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        self.is_processed(signature).await
+    }
 
     async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
         self.mark_processed(signature, slot).await
     }
 
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.mark_processed_batch(entries).await
+    }
+
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        self.commit_transaction(writes, signature, slot, block_hash)
+            .await
+    }
+
     async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
         self.get_last_processed_slot().await
     }
@@ -549,8 +1772,8 @@ impl StorageBackend for Storage {
         self.get_last_processed_signature().await
     }
 
-    fn pool(&self) -> &PgPool {
-        &self.pool
+    fn pool(&self) -> Option<&PgPool> {
+        Some(&self.pool)
     }
 
     async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
@@ -593,6 +1816,123 @@ impl StorageBackend for Storage {
     async fn mark_backfill_complete(&self) -> Result<()> {
         self.mark_backfill_complete().await
     }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        self.begin_backfill_range(range).await
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.advance_backfill_range(range, slot, cursor, transactions)
+            .await
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.save_snapshot_slot(program_id, slot).await
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.advance_watermark(slot).await
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.record_commitment(signature, slot, level).await
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.load_processed_signatures(slots, after, limit).await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.record_dead_letter(signature, slot, reason).await
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.record_usage(day, records).await
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        self.prune(config, tip_slot).await
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.vacuum(config).await
+    }
+
+    /// Writes go straight to the database; there is nothing to flush.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -606,6 +1946,140 @@ mod tests {
         // Success if we get here
     }
 
+    #[test]
+    fn test_discriminator_hex_roundtrip() {
+        let discriminator = [0, 1, 0xab, 0xff, 7, 8, 9, 10];
+        let hex = discriminator_hex(Some(discriminator));
+        assert_eq!(hex, "0001abff0708090a");
+        assert_eq!(parse_discriminator_hex(&hex), Some(discriminator));
+        assert_eq!(discriminator_hex(None), "");
+        assert_eq!(parse_discriminator_hex(""), None);
+    }
+
+    #[test]
+    fn test_batch_watermark() {
+        assert_eq!(batch_watermark(&[]), None);
+        assert_eq!(batch_watermark(&[(7, true), (5, true), (9, true)]), Some(9));
+        assert_eq!(
+            batch_watermark(&[(7, true), (5, false), (9, true)]),
+            Some(4)
+        );
+        assert_eq!(batch_watermark(&[(0, false)]), None);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_roundtrip() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+
+            let address = "cursor_test_address";
+            sqlx::query("DELETE FROM _solana_indexer_sdk_cursors WHERE address = $1")
+                .bind(address)
+                .execute(&storage.pool)
+                .await?;
+
+            assert_eq!(storage.load_cursor(address).await?, None);
+            storage.save_cursor(address, "sig_a", 10).await?;
+            storage.save_cursor(address, "sig_b", 12).await?;
+            assert_eq!(
+                storage.load_cursor(address).await?,
+                Some("sig_b".to_string())
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_slot_roundtrip() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+
+            let program_id = "snapshot_test_program";
+            sqlx::query("DELETE FROM _solana_indexer_sdk_snapshots WHERE program_id = $1")
+                .bind(program_id)
+                .execute(&storage.pool)
+                .await?;
+
+            assert_eq!(storage.load_snapshot_slot(program_id).await?, None);
+            storage.save_snapshot_slot(program_id, 100).await?;
+            storage.save_snapshot_slot(program_id, 250).await?;
+            assert_eq!(storage.load_snapshot_slot(program_id).await?, Some(250));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watermark_is_monotonic() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            sqlx::query("DELETE FROM _solana_indexer_sdk_watermark")
+                .execute(&storage.pool)
+                .await?;
+
+            assert_eq!(storage.get_watermark().await?, None);
+            storage.advance_watermark(100).await?;
+            storage.advance_watermark(50).await?;
+            assert_eq!(storage.get_watermark().await?, Some(100));
+            assert_eq!(
+                StorageBackend::read_slot(&storage, Some(80)).await?,
+                Some(80)
+            );
+            assert_eq!(
+                StorageBackend::read_slot(&storage, Some(120)).await?,
+                Some(100)
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_share_queue_table() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            sqlx::query("DELETE FROM _solana_indexer_sdk_dlq WHERE signature LIKE 'dlq_test_%'")
+                .execute(&storage.pool)
+                .await?;
+
+            storage
+                .record_dead_letter("dlq_test_a", 10, "too many")
+                .await?;
+            storage
+                .record_dead_letter("dlq_test_a", 10, "replaced")
+                .await?;
+            storage
+                .record_dead_letter("dlq_test_b", 11, "too many")
+                .await?;
+
+            let letters: Vec<_> = storage
+                .load_dead_letters(1_000)
+                .await?
+                .into_iter()
+                .filter(|l| l.signature.starts_with("dlq_test_"))
+                .collect();
+            assert_eq!(letters.len(), 2);
+            assert_eq!(letters[0].reason, "replaced");
+
+            // Whole transactions have no event to reprocess
+            let entries = dlq::DeadLetterQueue::new()
+                .list(&storage.pool, 1_000)
+                .await?;
+            assert!(!entries.iter().any(|e| e.signature.starts_with("dlq_test_")));
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     // #[ignore = "Requires database connection"] // Requires database connection
     async fn test_storage_initialize() {
@@ -618,6 +2092,38 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_confirmation_progression() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+
+            let signature = "confirmation_test_signature";
+            sqlx::query("DELETE FROM _solana_indexer_sdk_confirmations WHERE signature = $1")
+                .bind(signature)
+                .execute(&storage.pool)
+                .await?;
+
+            storage
+                .record_commitment(signature, 42, CommitmentLevel::Confirmed)
+                .await?;
+            let pending = storage
+                .load_unfinalized_signatures(Some((41, "")), 10)
+                .await?;
+            assert!(pending.contains(&(42, signature.to_string())));
+
+            storage
+                .record_commitment(signature, 42, CommitmentLevel::Finalized)
+                .await?;
+            let status = storage.load_confirmation_status(signature).await?;
+            assert_eq!(status.map(|s| s.level()), Some(CommitmentLevel::Finalized));
+            assert!(storage.confirmation_summary().await?.finalized >= 1);
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_idempotency_tracking() -> Result<()> {
         let db_url = std::env::var("DATABASE_URL")
@@ -653,6 +2159,43 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_mark_processed_batch_skips_existing() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            sqlx::query(
+                "DELETE FROM _solana_indexer_sdk_processed WHERE signature LIKE 'copy_sig_%'",
+            )
+            .execute(&storage.pool)
+            .await?;
+
+            storage.mark_processed("copy_sig_1", 10).await?;
+            let entries: Vec<(String, u64)> =
+                (1..=3).map(|i| (format!("copy_sig_{i}"), 10 + i)).collect();
+            storage.mark_processed_batch(&entries).await?;
+            // A second batch in the same session stages through a fresh table
+            storage.mark_processed_batch(&entries[2..]).await?;
+
+            let rows: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT signature, slot FROM _solana_indexer_sdk_processed WHERE signature LIKE 'copy_sig_%' ORDER BY signature",
+            )
+            .fetch_all(&storage.pool)
+            .await?;
+            assert_eq!(
+                rows,
+                vec![
+                    ("copy_sig_1".to_string(), 10),
+                    ("copy_sig_2".to_string(), 12),
+                    ("copy_sig_3".to_string(), 13),
+                ]
+            );
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cleanup_stale_transactions() -> Result<()> {
         let db_url = std::env::var("DATABASE_URL")
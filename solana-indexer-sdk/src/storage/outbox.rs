@@ -0,0 +1,341 @@
+//! Transactional outbox for exactly-once delivery to event sinks.
+//!
+//! A handler that writes rows and also publishes to a sink directly can
+//! leave the two out of step: a crash after the publish indexes the
+//! transaction again and publishes twice, and a failed publish after the
+//! rows were written loses the message. With `with_outbox`, handlers write
+//! records to `_solana_indexer_sdk_outbox` instead, through
+//! `TxMetadata::execute`, so with `with_atomic_transactions` they commit in
+//! the same database transaction as the handler rows and the processed
+//! marker. A relay task then publishes the stored records, oldest first, to
+//! the sinks added with `SolanaIndexer::add_outbox_sink` and deletes them
+//! once every sink has accepted them:
+//!
+//! ```no_run
+//! # async fn example(indexer: &mut solana_indexer_sdk::SolanaIndexer) -> solana_indexer_sdk::Result<()> {
+//! use solana_indexer_sdk::sinks::webhook::WebhookSink;
+//! use solana_indexer_sdk::{OutboxHandler, TransferEvent};
+//!
+//! indexer.register_handler(OutboxHandler::<TransferEvent>::new())?;
+//! indexer.add_outbox_sink(WebhookSink::new(["https://example.com/hooks/transfers"])?)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A record whose publish fails stays in the table and is published again
+//! on the next poll, to every sink, so delivery is at least once. Until a
+//! sink is added, records stay in the table unpublished. Each
+//! record carries a `dedup_key` built from its signature, event type and
+//! payload that stays the same across redeliveries and reindexing:
+//! consumers that drop keys they have already seen receive every event
+//! exactly once. Records with the same key are stored once.
+
+use crate::sinks::{EventSink, SinkRecord};
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::{EventHandler, SchemaInitializer};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use async_trait::async_trait;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the outbox table.
+pub(crate) const OUTBOX_TABLE: &str = "_solana_indexer_sdk_outbox";
+
+/// Stores one record, unless its key is already stored. A literal, since
+/// held writes outlive the call that builds them.
+const INSERT_RECORD: &str = "INSERT INTO _solana_indexer_sdk_outbox (dedup_key, record)
+     VALUES ($1, $2)
+     ON CONFLICT (dedup_key) DO NOTHING";
+
+/// Records claimed per poll.
+const CLAIM_BATCH: i64 = 100;
+
+/// How long claimed records are reserved for another relay sharing the
+/// table to skip them.
+const LEASE: Duration = Duration::from_secs(60);
+
+/// Returns the dedup key of `record`: its signature, event type and the
+/// leading hex digits of the SHA-256 of its payload.
+///
+/// Two events of the same type with identical payloads in one transaction
+/// share a key; give them distinct keys with `enqueue_with_key`.
+#[must_use]
+pub fn dedup_key(record: &SinkRecord) -> String {
+    let digest = Sha256::digest(record.payload.to_string().as_bytes());
+    let hash: String = digest[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    format!("{}:{}:{hash}", record.signature, record.event)
+}
+
+/// Writes `record` to the outbox as part of `context`'s handler writes,
+/// under its `dedup_key`.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Storage` if the write runs right away and
+/// fails.
+pub async fn enqueue(context: &TxMetadata, record: &SinkRecord, db: &PgPool) -> Result<()> {
+    enqueue_with_key(context, record, dedup_key(record), db).await
+}
+
+/// Writes `record` to the outbox as part of `context`'s handler writes,
+/// under `key` instead of its `dedup_key`. A record already stored under
+/// `key` is kept.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Storage` if the write runs right away and
+/// fails.
+pub async fn enqueue_with_key(
+    context: &TxMetadata,
+    record: &SinkRecord,
+    key: String,
+    db: &PgPool,
+) -> Result<()> {
+    let mut record = record.clone();
+    record.dedup_key = Some(key.clone());
+    let json = serde_json::to_value(&record)
+        .map_err(|e| SolanaIndexerError::Data(format!("Failed to serialize outbox record: {e}")))?;
+    context
+        .execute(sqlx::query(INSERT_RECORD).bind(key).bind(json), db)
+        .await
+}
+
+/// Event handler writing every event of type `T` to the outbox.
+pub struct OutboxHandler<T> {
+    _event: PhantomData<fn(T)>,
+}
+
+impl<T> Default for OutboxHandler<T> {
+    fn default() -> Self {
+        Self {
+            _event: PhantomData,
+        }
+    }
+}
+
+impl<T> OutboxHandler<T> {
+    /// Creates the handler.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl<T> EventHandler<T> for OutboxHandler<T>
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    async fn handle(&self, event: T, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        let record = SinkRecord::new(&event, context)?;
+        enqueue(context, &record, db).await
+    }
+}
+
+/// Publishes outbox records to event sinks.
+#[derive(Clone)]
+pub struct OutboxRelay {
+    db: PgPool,
+    sinks: Vec<Arc<dyn EventSink>>,
+    published: Arc<AtomicU64>,
+}
+
+impl std::fmt::Debug for OutboxRelay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OutboxRelay")
+            .field("sinks", &self.sinks.len())
+            .field("published", &self.published())
+            .finish_non_exhaustive()
+    }
+}
+
+impl OutboxRelay {
+    /// Creates a relay reading the outbox in `db`, with no sinks yet.
+    #[must_use]
+    pub fn new(db: PgPool) -> Self {
+        Self {
+            db,
+            sinks: Vec::new(),
+            published: Arc::default(),
+        }
+    }
+
+    /// Publishes every record to `sink` as well.
+    pub fn add_sink(&mut self, sink: impl EventSink) {
+        self.sinks.push(Arc::new(sink));
+    }
+
+    /// Returns the number of records published to every sink since startup.
+    #[must_use]
+    pub fn published(&self) -> u64 {
+        self.published.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of records waiting to be published.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the table cannot be read.
+    pub async fn pending(&self) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM {OUTBOX_TABLE}"))
+            .fetch_one(&self.db)
+            .await?;
+        Ok(count)
+    }
+
+    /// Publishes the oldest waiting records and returns how many were
+    /// published.
+    ///
+    /// Records are published in the order they were written. The first
+    /// one a sink fails stops the batch: it and the records after it are
+    /// released for the next call, keeping the order. Without sinks nothing
+    /// is claimed, so records are kept for a relay that has some.
+    ///
+    /// # Errors
+    ///
+    /// Returns the sink's error, or `SolanaIndexerError::Storage` if the
+    /// table cannot be read or updated.
+    pub async fn relay(&self) -> Result<usize> {
+        if self.sinks.is_empty() {
+            return Ok(0);
+        }
+        let mut records = sqlx::query_as::<_, (i64, serde_json::Value)>(&format!(
+            "UPDATE {OUTBOX_TABLE}
+             SET claimed_until = NOW() + $1 * INTERVAL '1 millisecond', attempts = attempts + 1
+             WHERE id IN (
+                 SELECT id FROM {OUTBOX_TABLE}
+                 WHERE claimed_until IS NULL OR claimed_until <= NOW()
+                 ORDER BY id
+                 LIMIT $2
+                 FOR UPDATE SKIP LOCKED
+             )
+             RETURNING id, record"
+        ))
+        .bind(i64::try_from(LEASE.as_millis()).unwrap_or(i64::MAX))
+        .bind(CLAIM_BATCH)
+        .fetch_all(&self.db)
+        .await?;
+
+        records.sort_unstable_by_key(|(id, _)| *id);
+        let mut published = 0;
+        for (position, (id, record)) in records.iter().enumerate() {
+            if let Err(e) = self.publish(record).await {
+                let released: Vec<i64> = records[position..].iter().map(|(id, _)| *id).collect();
+                sqlx::query(&format!(
+                    "UPDATE {OUTBOX_TABLE}
+                     SET claimed_until = NULL,
+                         last_error = CASE WHEN id = $2 THEN $3 ELSE last_error END
+                     WHERE id = ANY($1)"
+                ))
+                .bind(released)
+                .bind(id)
+                .bind(e.to_string())
+                .execute(&self.db)
+                .await?;
+                return Err(e);
+            }
+            sqlx::query(&format!("DELETE FROM {OUTBOX_TABLE} WHERE id = $1"))
+                .bind(id)
+                .execute(&self.db)
+                .await?;
+            self.published.fetch_add(1, Ordering::Relaxed);
+            published += 1;
+        }
+        Ok(published)
+    }
+
+    /// Publishes one stored record to every sink.
+    async fn publish(&self, record: &serde_json::Value) -> Result<()> {
+        let record: SinkRecord = serde_json::from_value(record.clone())
+            .map_err(|e| SolanaIndexerError::Decode(format!("Invalid outbox record: {e}")))?;
+        for sink in &self.sinks {
+            sink.publish(&record).await?;
+        }
+        Ok(())
+    }
+
+    /// Relays records every `poll_interval` until `cancellation_token`
+    /// fires.
+    pub(crate) fn spawn(
+        self,
+        poll_interval: Duration,
+        cancellation_token: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        if self.sinks.is_empty() {
+            logging::log(
+                logging::LogLevel::Warning,
+                "Outbox enabled without sinks; records are kept until one is added with add_outbox_sink",
+            );
+        }
+        tokio::spawn(async move {
+            while !cancellation_token.is_cancelled() {
+                match self.relay().await {
+                    // A full batch means more records are probably waiting
+                    Ok(published) if published == CLAIM_BATCH as usize => continue,
+                    Ok(_) => {}
+                    Err(e) => logging::log_error("Outbox relay error", &e.to_string()),
+                }
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    () = tokio::time::sleep(poll_interval) => {}
+                }
+            }
+        })
+    }
+}
+
+/// Creates the outbox table.
+#[async_trait]
+impl SchemaInitializer for OutboxRelay {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {OUTBOX_TABLE} (
+                id BIGSERIAL PRIMARY KEY,
+                dedup_key TEXT NOT NULL UNIQUE,
+                record JSONB NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                claimed_until TIMESTAMPTZ,
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(amount: u64) -> SinkRecord {
+        SinkRecord {
+            event: "TransferEvent".to_string(),
+            signature: "sig".to_string(),
+            slot: 7,
+            block_time: None,
+            payload: serde_json::json!({ "amount": amount }),
+            dedup_key: None,
+        }
+    }
+
+    #[test]
+    fn test_dedup_key_follows_payload() {
+        let key = dedup_key(&record(5));
+        assert!(key.starts_with("sig:TransferEvent:"));
+        assert_eq!(key.len(), "sig:TransferEvent:".len() + 16);
+        assert_eq!(key, dedup_key(&record(5)));
+        assert_ne!(key, dedup_key(&record(6)));
+    }
+}
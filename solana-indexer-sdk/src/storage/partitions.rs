@@ -0,0 +1,379 @@
+//! Declarative partitioning of the processed-signatures table.
+//!
+//! Long-running indexers accumulate hundreds of millions of rows in
+//! `_solana_indexer_sdk_processed`. With partitioning enabled, a new table is
+//! created as `PARTITION BY RANGE` on the slot or on the month it was
+//! indexed, plus a default partition for rows outside any range. A
+//! maintenance task then keeps partitions ready ahead of the chain and
+//! detaches the oldest ones beyond the retention count, so operators can
+//! archive or drop them without touching live data.
+//!
+//! An existing unpartitioned table is left as it is; converting it has to be
+//! done by hand. Partitioning applies to `PostgreSQL` storage only.
+
+use super::{Storage, StorageBackend};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the partitioned table.
+const PARENT: &str = "_solana_indexer_sdk_processed";
+
+/// Name of the partition catching rows outside every range.
+const DEFAULT_PARTITION: &str = "_solana_indexer_sdk_processed_default";
+
+/// Pause between maintenance runs.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// How the processed-signatures table is split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PartitionScheme {
+    /// One partition per fixed range of slots
+    SlotRange {
+        /// Slots covered by each partition (roughly 432,000 per epoch)
+        slots_per_partition: u64,
+    },
+    /// One partition per calendar month (UTC) of `indexed_at`
+    Monthly,
+}
+
+/// Partitioning settings for the processed-signatures table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartitionConfig {
+    /// How rows are split into partitions
+    pub scheme: PartitionScheme,
+    /// Number of most recent partitions kept attached (default: 0, which
+    /// never detaches)
+    pub retain: usize,
+}
+
+impl PartitionConfig {
+    /// Validates the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if a slot range is empty.
+    pub fn validate(&self) -> Result<()> {
+        if let PartitionScheme::SlotRange {
+            slots_per_partition: 0,
+        } = self.scheme
+        {
+            return Err(SolanaIndexerError::Config(
+                "Partition slots_per_partition must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Partitions created and detached by one maintenance run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionReport {
+    /// Partitions created
+    pub created: Vec<String>,
+    /// Partitions detached from the table
+    pub detached: Vec<String>,
+}
+
+/// A partition and its bounds, as SQL literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Partition {
+    name: String,
+    from: String,
+    to: String,
+    /// Condition matching the partition's rows
+    range: String,
+}
+
+impl PartitionScheme {
+    fn column(self) -> &'static str {
+        match self {
+            Self::SlotRange { .. } => "slot",
+            Self::Monthly => "indexed_at",
+        }
+    }
+
+    /// Returns the partition holding `slot` or `now`, and the one after it.
+    fn upcoming(self, slot: u64, now: DateTime<Utc>) -> [Partition; 2] {
+        match self {
+            Self::SlotRange {
+                slots_per_partition,
+            } => {
+                let start = slot - slot % slots_per_partition;
+                [
+                    slot_partition(start, slots_per_partition),
+                    slot_partition(
+                        start.saturating_add(slots_per_partition),
+                        slots_per_partition,
+                    ),
+                ]
+            }
+            Self::Monthly => {
+                let (year, month) = (now.year(), now.month());
+                let (next_year, next_month) = next_month(year, month);
+                [
+                    month_partition(year, month),
+                    month_partition(next_year, next_month),
+                ]
+            }
+        }
+    }
+}
+
+fn slot_partition(start: u64, len: u64) -> Partition {
+    let end = start.saturating_add(len);
+    Partition {
+        // Zero-padded so partition names sort by slot
+        name: format!("{PARENT}_s{start:020}"),
+        from: start.to_string(),
+        to: end.to_string(),
+        range: format!("slot >= {start} AND slot < {end}"),
+    }
+}
+
+fn next_month(year: i32, month: u32) -> (i32, u32) {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+fn month_partition(year: i32, month: u32) -> Partition {
+    let (next_year, next_month) = next_month(year, month);
+    let bound = |year: i32, month: u32| {
+        Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+            .single()
+            .map(|t| format!("'{}'", t.format("%Y-%m-%d %H:%M:%S+00")))
+            .unwrap_or_default()
+    };
+    let (from, to) = (bound(year, month), bound(next_year, next_month));
+    Partition {
+        name: format!("{PARENT}_m{year:04}_{month:02}"),
+        range: format!("indexed_at >= {from} AND indexed_at < {to}"),
+        from,
+        to,
+    }
+}
+
+impl Storage {
+    /// Creates the processed-signatures table partitioned under `scheme`,
+    /// with its default partition, if the table does not exist yet.
+    pub(crate) async fn create_partitioned_processed(&self, scheme: PartitionScheme) -> Result<()> {
+        let column = scheme.column();
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {PARENT} (
+                signature TEXT NOT NULL,
+                slot BIGINT NOT NULL,
+                indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (signature, {column})
+            ) PARTITION BY RANGE ({column})"
+        ))
+        .execute(&self.pool)
+        .await?;
+
+        if self.processed_is_partitioned().await? {
+            sqlx::query(&format!(
+                "CREATE TABLE IF NOT EXISTS {DEFAULT_PARTITION} PARTITION OF {PARENT} DEFAULT"
+            ))
+            .execute(&self.pool)
+            .await?;
+        } else {
+            logging::log(
+                logging::LogLevel::Warning,
+                &format!("{PARENT} already exists without partitions; partitioning is skipped"),
+            );
+        }
+        Ok(())
+    }
+
+    async fn processed_is_partitioned(&self) -> Result<bool> {
+        let partitioned: Option<bool> =
+            sqlx::query_scalar("SELECT relkind = 'p' FROM pg_class WHERE oid = to_regclass($1)")
+                .bind(PARENT)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(partitioned.unwrap_or(false))
+    }
+
+    /// Creates the partitions for the current range (holding `tip_slot`, or
+    /// the last processed slot) and the next one, then detaches the oldest
+    /// partitions beyond the retention count.
+    ///
+    /// Rows that already landed in the default partition for a new range are
+    /// moved into it. Does nothing unless partitioning is configured and the
+    /// table is partitioned.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a catalog query or a
+    /// detach fails. Failing to create a single partition is logged and
+    /// retried on the next run.
+    pub async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        let mut report = PartitionReport::default();
+        let Some(config) = self.partitioning else {
+            return Ok(report);
+        };
+        if !self.processed_is_partitioned().await? {
+            return Ok(report);
+        }
+
+        let slot = match tip_slot {
+            Some(slot) => Some(slot),
+            None => self.get_last_processed_slot().await?,
+        };
+        let upcoming = match (config.scheme, slot) {
+            (PartitionScheme::SlotRange { .. }, None) => Vec::new(),
+            (scheme, slot) => scheme.upcoming(slot.unwrap_or(0), Utc::now()).to_vec(),
+        };
+        for partition in upcoming {
+            match self.create_partition(&partition).await {
+                Ok(true) => report.created.push(partition.name),
+                Ok(false) => {}
+                Err(e) => logging::log_error(
+                    &format!("Failed to create partition {}", partition.name),
+                    &e.to_string(),
+                ),
+            }
+        }
+
+        if config.retain > 0 {
+            let mut attached: Vec<String> = sqlx::query_scalar(
+                "SELECT c.relname::text FROM pg_inherits i
+                 JOIN pg_class c ON c.oid = i.inhrelid
+                 WHERE i.inhparent = to_regclass($1)",
+            )
+            .bind(PARENT)
+            .fetch_all(&self.pool)
+            .await?;
+            attached.retain(|name| name != DEFAULT_PARTITION);
+            attached.sort();
+
+            let excess = attached.len().saturating_sub(config.retain);
+            for name in attached.into_iter().take(excess) {
+                sqlx::query(&format!("ALTER TABLE {PARENT} DETACH PARTITION {name}"))
+                    .execute(&self.pool)
+                    .await?;
+                report.detached.push(name);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Creates and attaches `partition`, moving over matching rows from the
+    /// default partition. Returns `false` if it already exists.
+    async fn create_partition(&self, partition: &Partition) -> Result<bool> {
+        let exists: Option<String> = sqlx::query_scalar("SELECT to_regclass($1)::text")
+            .bind(&partition.name)
+            .fetch_one(&self.pool)
+            .await?;
+        if exists.is_some() {
+            return Ok(false);
+        }
+
+        let Partition {
+            name,
+            from,
+            to,
+            range,
+        } = partition;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(&format!(
+            "CREATE TABLE {name} (LIKE {PARENT} INCLUDING DEFAULTS)"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "WITH moved AS (DELETE FROM {DEFAULT_PARTITION} WHERE {range} RETURNING *)
+             INSERT INTO {name} SELECT * FROM moved"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(&format!(
+            "ALTER TABLE {PARENT} ATTACH PARTITION {name} FOR VALUES FROM ({from}) TO ({to})"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+/// Runs partition maintenance now and then every hour until
+/// `cancellation_token` fires. `tip_slot` supplies the current chain slot,
+/// if it can be fetched.
+pub fn spawn_maintenance<F, Fut>(
+    storage: Arc<dyn StorageBackend>,
+    tip_slot: F,
+    cancellation_token: CancellationToken,
+) -> tokio::task::JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Option<u64>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+        loop {
+            tokio::select! {
+                () = cancellation_token.cancelled() => break,
+                _ = interval.tick() => {
+                    match storage.maintain_partitions(tip_slot().await).await {
+                        Ok(report) => {
+                            for name in &report.created {
+                                logging::log(logging::LogLevel::Info, &format!("Created partition {name}"));
+                            }
+                            for name in &report.detached {
+                                logging::log(logging::LogLevel::Info, &format!("Detached partition {name}"));
+                            }
+                        }
+                        Err(e) => logging::log_error("Partition maintenance error", &e.to_string()),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upcoming_partitions() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 15, 8, 0, 0).unwrap();
+
+        let [current, next] = PartitionScheme::SlotRange {
+            slots_per_partition: 1000,
+        }
+        .upcoming(12_345, now);
+        assert_eq!(current.name, format!("{PARENT}_s00000000000000012000"));
+        assert_eq!(
+            (current.from.as_str(), current.to.as_str()),
+            ("12000", "13000")
+        );
+        assert_eq!((next.from.as_str(), next.to.as_str()), ("13000", "14000"));
+        assert!(current.name < next.name);
+
+        let [current, next] = PartitionScheme::Monthly.upcoming(0, now);
+        assert_eq!(current.name, format!("{PARENT}_m2024_12"));
+        assert_eq!(current.from, "'2024-12-01 00:00:00+00'");
+        assert_eq!(current.to, "'2025-01-01 00:00:00+00'");
+        assert_eq!(next.name, format!("{PARENT}_m2025_01"));
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_slot_range() {
+        let config = PartitionConfig {
+            scheme: PartitionScheme::SlotRange {
+                slots_per_partition: 0,
+            },
+            retain: 0,
+        };
+        assert!(config.validate().is_err());
+    }
+}
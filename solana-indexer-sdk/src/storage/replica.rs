@@ -0,0 +1,524 @@
+//! Asynchronous replication of the SDK's own writes to a standby database.
+//!
+//! With `with_replica_database`, every write the indexer makes to its
+//! internal tables (processed signatures, tentative and finalized slots,
+//! polling cursors, backfill progress, the watermark, confirmation and
+//! dead-letter records, usage) is also applied to a secondary database,
+//! typically in another region. A standby indexer pointed at that database
+//! then resumes from nearly where the primary stopped instead of
+//! re-indexing from scratch.
+//!
+//! Writes reach the primary first and are mirrored in the background, in
+//! order, so the primary's latency is unaffected by the replica. A write
+//! the replica keeps rejecting is retried a few times and then dropped, as
+//! are writes arriving while the queue is full; the counts are kept in
+//! `ReplicaMetrics`. A dropped processed marker only makes the standby
+//! process that transaction again.
+//!
+//! Handler tables are not replicated; use database-level replication for
+//! them.
+
+use super::buffer::BufferedQuery;
+use super::partitions::PartitionReport;
+use super::retention::{RetentionConfig, RetentionReport};
+use super::vacuum::{VacuumConfig, VacuumReport};
+use super::{ConfirmationStatus, ConfirmationSummary, DeadLetter, StorageBackend};
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Writes waiting for the replica before new ones are dropped.
+const QUEUE_CAPACITY: usize = 10_000;
+
+/// Attempts at applying one write to the replica.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry, doubled for each further one.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// A write to mirror, owning its arguments.
+#[derive(Debug, Clone)]
+enum ReplicaWrite {
+    Processed(String, u64),
+    ProcessedBatch(Vec<(String, u64)>),
+    Tentative(String, u64, String),
+    Finalized(u64, String),
+    Rollback(u64),
+    CleanupTentative(u64),
+    BackfillProgress(u64),
+    BackfillComplete,
+    BackfillRangeBegin(BackfillRange),
+    BackfillRangeAdvance(BackfillRange, u64, Option<String>, u64),
+    Cursor(String, String, u64),
+    SnapshotSlot(String, u64),
+    Watermark(u64),
+    Commitment(String, u64, CommitmentLevel),
+    DeadLetter(String, u64, String),
+    Usage(String, Vec<UsageRecord>),
+}
+
+impl ReplicaWrite {
+    async fn apply(&self, replica: &dyn StorageBackend) -> Result<()> {
+        match self {
+            Self::Processed(signature, slot) => replica.mark_processed(signature, *slot).await,
+            Self::ProcessedBatch(entries) => replica.mark_processed_batch(entries).await,
+            Self::Tentative(signature, slot, block_hash) => {
+                replica.mark_tentative(signature, *slot, block_hash).await
+            }
+            Self::Finalized(slot, block_hash) => replica.mark_finalized(*slot, block_hash).await,
+            Self::Rollback(slot) => replica.rollback_slot(*slot).await,
+            Self::CleanupTentative(threshold) => replica
+                .cleanup_stale_tentative_transactions(*threshold)
+                .await
+                .map(|_| ()),
+            Self::BackfillProgress(slot) => replica.save_backfill_progress(*slot).await,
+            Self::BackfillComplete => replica.mark_backfill_complete().await,
+            Self::BackfillRangeBegin(range) => {
+                replica.begin_backfill_range(*range).await.map(|_| ())
+            }
+            Self::BackfillRangeAdvance(range, slot, cursor, transactions) => {
+                replica
+                    .advance_backfill_range(*range, *slot, cursor.as_deref(), *transactions)
+                    .await
+            }
+            Self::Cursor(address, signature, slot) => {
+                replica.save_cursor(address, signature, *slot).await
+            }
+            Self::SnapshotSlot(program_id, slot) => {
+                replica.save_snapshot_slot(program_id, *slot).await
+            }
+            Self::Watermark(slot) => replica.advance_watermark(*slot).await,
+            Self::Commitment(signature, slot, level) => {
+                replica.record_commitment(signature, *slot, *level).await
+            }
+            Self::DeadLetter(signature, slot, reason) => {
+                replica.record_dead_letter(signature, *slot, reason).await
+            }
+            Self::Usage(day, records) => replica.record_usage(day, records).await,
+        }
+    }
+}
+
+/// Counts of the writes mirrored to the replica.
+#[derive(Debug, Default)]
+pub struct ReplicaMetrics {
+    /// Writes applied to the replica
+    pub mirrored: AtomicU64,
+    /// Writes given up on after every attempt failed
+    pub failed: AtomicU64,
+    /// Writes dropped because the queue was full
+    pub dropped: AtomicU64,
+}
+
+/// Storage backend wrapper mirroring SDK writes to a second backend.
+///
+/// Reads and every other call go to the primary only.
+pub struct ReplicatedStorage {
+    primary: Arc<dyn StorageBackend>,
+    replica: Arc<dyn StorageBackend>,
+    queue: mpsc::Sender<ReplicaWrite>,
+    metrics: Arc<ReplicaMetrics>,
+}
+
+impl ReplicatedStorage {
+    /// Mirrors the writes made to `primary` to `replica`.
+    ///
+    /// Must be called within a Tokio runtime: the writes are applied by a
+    /// task that runs until the wrapper is dropped.
+    #[must_use]
+    pub fn new(primary: Arc<dyn StorageBackend>, replica: Arc<dyn StorageBackend>) -> Self {
+        let (queue, mut writes) = mpsc::channel::<ReplicaWrite>(QUEUE_CAPACITY);
+        let metrics = Arc::new(ReplicaMetrics::default());
+        let (target, counts) = (replica.clone(), metrics.clone());
+        tokio::spawn(async move {
+            while let Some(write) = writes.recv().await {
+                let mut delay = RETRY_DELAY;
+                for attempt in 1..=MAX_ATTEMPTS {
+                    match write.apply(target.as_ref()).await {
+                        Ok(()) => {
+                            counts.mirrored.fetch_add(1, Ordering::Relaxed);
+                            break;
+                        }
+                        Err(e) if attempt == MAX_ATTEMPTS => {
+                            counts.failed.fetch_add(1, Ordering::Relaxed);
+                            logging::log_error("Replica write failed", &format!("{write:?}: {e}"));
+                        }
+                        Err(_) => {
+                            tokio::time::sleep(delay).await;
+                            delay *= 2;
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            primary,
+            replica,
+            queue,
+            metrics,
+        }
+    }
+
+    /// Returns the replica backend.
+    #[must_use]
+    pub fn replica(&self) -> &Arc<dyn StorageBackend> {
+        &self.replica
+    }
+
+    /// Returns the counts of mirrored, failed and dropped writes.
+    #[must_use]
+    pub fn metrics(&self) -> &ReplicaMetrics {
+        &self.metrics
+    }
+
+    /// Returns the number of writes waiting for the replica.
+    #[must_use]
+    pub fn lag(&self) -> usize {
+        QUEUE_CAPACITY - self.queue.capacity()
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Replica Stats: Mirrored: {} | Pending: {} | Failed: {} | Dropped: {}",
+                self.metrics.mirrored.load(Ordering::Relaxed),
+                self.lag(),
+                self.metrics.failed.load(Ordering::Relaxed),
+                self.metrics.dropped.load(Ordering::Relaxed)
+            ),
+        );
+    }
+
+    /// Queues `write` for the replica once the primary accepted it.
+    fn mirror(&self, write: ReplicaWrite) {
+        if self.queue.try_send(write).is_err() {
+            self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for ReplicatedStorage {
+    /// Initializes the primary, then the replica. A replica that cannot be
+    /// initialized is logged rather than failing startup, since its writes
+    /// are retried anyway.
+    async fn initialize(&self) -> Result<()> {
+        self.primary.initialize().await?;
+        if let Err(e) = self.replica.initialize().await {
+            logging::log_error("Replica initialization failed", &e.to_string());
+        }
+        Ok(())
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        self.primary.is_processed(signature).await
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        self.primary.mark_processed(signature, slot).await?;
+        self.mirror(ReplicaWrite::Processed(signature.to_string(), slot));
+        Ok(())
+    }
+
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.primary.mark_processed_batch(entries).await?;
+        self.mirror(ReplicaWrite::ProcessedBatch(entries.to_vec()));
+        Ok(())
+    }
+
+    // Handler tables are not replicated, so only the mark is mirrored
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        self.primary
+            .commit_transaction(writes, signature, slot, block_hash)
+            .await?;
+        self.mirror(match block_hash {
+            Some(block_hash) => {
+                ReplicaWrite::Tentative(signature.to_string(), slot, block_hash.to_string())
+            }
+            None => ReplicaWrite::Processed(signature.to_string(), slot),
+        });
+        Ok(())
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        self.primary.get_last_processed_slot().await
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        self.primary.get_last_processed_signature().await
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        self.primary.pool()
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        self.primary
+            .mark_tentative(signature, slot, block_hash)
+            .await?;
+        self.mirror(ReplicaWrite::Tentative(
+            signature.to_string(),
+            slot,
+            block_hash.to_string(),
+        ));
+        Ok(())
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        self.primary.mark_finalized(slot, block_hash).await?;
+        self.mirror(ReplicaWrite::Finalized(slot, block_hash.to_string()));
+        Ok(())
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        self.primary.get_tentative_transactions(slot).await
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        self.primary.rollback_slot(slot).await?;
+        self.mirror(ReplicaWrite::Rollback(slot));
+        Ok(())
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        self.primary.get_block_hash(slot).await
+    }
+
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        let removed = self
+            .primary
+            .cleanup_stale_tentative_transactions(slot_threshold)
+            .await?;
+        self.mirror(ReplicaWrite::CleanupTentative(slot_threshold));
+        Ok(removed)
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        self.primary.get_tentative_slots_le(slot).await
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        self.primary.save_backfill_progress(slot).await?;
+        self.mirror(ReplicaWrite::BackfillProgress(slot));
+        Ok(())
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        self.primary.load_backfill_progress().await
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        self.primary.mark_backfill_complete().await?;
+        self.mirror(ReplicaWrite::BackfillComplete);
+        Ok(())
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        let resume = self.primary.begin_backfill_range(range).await?;
+        self.mirror(ReplicaWrite::BackfillRangeBegin(range));
+        Ok(resume)
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.primary
+            .advance_backfill_range(range, slot, cursor, transactions)
+            .await?;
+        self.mirror(ReplicaWrite::BackfillRangeAdvance(
+            range,
+            slot,
+            cursor.map(str::to_string),
+            transactions,
+        ));
+        Ok(())
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.primary.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.primary.save_cursor(address, signature, slot).await?;
+        self.mirror(ReplicaWrite::Cursor(
+            address.to_string(),
+            signature.to_string(),
+            slot,
+        ));
+        Ok(())
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.primary.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.primary.save_snapshot_slot(program_id, slot).await?;
+        self.mirror(ReplicaWrite::SnapshotSlot(program_id.to_string(), slot));
+        Ok(())
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.primary.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.primary.advance_watermark(slot).await?;
+        self.mirror(ReplicaWrite::Watermark(slot));
+        Ok(())
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.primary.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.primary
+            .record_commitment(signature, slot, level)
+            .await?;
+        self.mirror(ReplicaWrite::Commitment(signature.to_string(), slot, level));
+        Ok(())
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.primary.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.primary
+            .load_processed_signatures(slots, after, limit)
+            .await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.primary.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.primary.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.primary
+            .record_dead_letter(signature, slot, reason)
+            .await?;
+        self.mirror(ReplicaWrite::DeadLetter(
+            signature.to_string(),
+            slot,
+            reason.to_string(),
+        ));
+        Ok(())
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.primary.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.primary.record_usage(day, records).await?;
+        self.mirror(ReplicaWrite::Usage(day.to_string(), records.to_vec()));
+        Ok(())
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.primary.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.primary.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        self.primary.prune(config, tip_slot).await
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.primary.vacuum(config).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.primary.flush().await
+    }
+
+    fn take_dropped_signatures(&self) -> Vec<String> {
+        self.primary.take_dropped_signatures()
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use crate::storage::sqlite::SqliteStorage;
+
+    async fn backend() -> Result<Arc<dyn StorageBackend>> {
+        let storage = SqliteStorage::new("sqlite::memory:").await?;
+        storage.initialize().await?;
+        Ok(Arc::new(storage))
+    }
+
+    #[tokio::test]
+    async fn test_writes_reach_the_replica() -> Result<()> {
+        let (primary, replica) = (backend().await?, backend().await?);
+        let storage = ReplicatedStorage::new(primary.clone(), replica.clone());
+
+        storage.mark_processed("sig_a", 10).await?;
+        storage.save_cursor("program", "sig_a", 10).await?;
+        assert!(primary.is_processed("sig_a").await?);
+
+        for _ in 0..100 {
+            if storage.metrics().mirrored.load(Ordering::Relaxed) == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(replica.is_processed("sig_a").await?);
+        assert_eq!(
+            replica.load_cursor("program").await?,
+            Some("sig_a".to_string())
+        );
+        assert_eq!(storage.lag(), 0);
+        Ok(())
+    }
+}
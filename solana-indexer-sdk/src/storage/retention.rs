@@ -0,0 +1,367 @@
+//! Retention and pruning of old rows.
+//!
+//! Without retention, `_solana_indexer_sdk_processed` and the handler tables
+//! grow for as long as the indexer runs. A retention policy removes rows
+//! older than a number of days (by `indexed_at`) or slots (behind the chain
+//! tip), either deleting them or moving them into a `<table>_archive` table
+//! next to the original. A background task applies the policy every hour
+//! and counts the rows and bytes it reclaimed in `RetentionMetrics`.
+//!
+//! Handler tables are only pruned when listed in `handler_tables`, with the
+//! column holding their slot or timestamp.
+
+use super::{Storage, StorageBackend};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the processed-signatures table.
+pub(crate) const PROCESSED_TABLE: &str = "_solana_indexer_sdk_processed";
+
+/// Pause between retention runs.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// How old a row has to be before it is pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetentionAge {
+    /// Rows indexed more than this many days ago
+    Days(u32),
+    /// Rows more than this many slots behind the chain tip
+    Slots(u64),
+}
+
+/// What happens to pruned rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RetentionAction {
+    /// Delete them
+    #[default]
+    Delete,
+    /// Move them into `<table>_archive`, created on first use
+    Archive,
+}
+
+/// A handler table covered by the retention policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionTable {
+    /// Table name, optionally schema-qualified
+    pub table: String,
+    /// Column compared against the cutoff: a slot for `RetentionAge::Slots`,
+    /// a timestamp for `RetentionAge::Days`
+    pub column: String,
+}
+
+/// Retention settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Age beyond which rows are pruned
+    pub max_age: RetentionAge,
+    /// Whether pruned rows are deleted or archived
+    pub action: RetentionAction,
+    /// Handler tables pruned alongside the processed-signatures table
+    pub handler_tables: Vec<RetentionTable>,
+}
+
+impl RetentionConfig {
+    /// Validates the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the age is zero or a
+    /// table or column name is not a plain SQL identifier.
+    pub fn validate(&self) -> Result<()> {
+        if matches!(self.max_age, RetentionAge::Days(0) | RetentionAge::Slots(0)) {
+            return Err(SolanaIndexerError::Config(
+                "Retention max_age must be greater than zero".to_string(),
+            ));
+        }
+        for target in &self.handler_tables {
+            for name in [&target.table, &target.column] {
+                if !is_identifier(name) {
+                    return Err(SolanaIndexerError::Config(format!(
+                        "Invalid retention table or column name: {name:?}"
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every `(table, column)` pair to prune, starting with the
+    /// processed-signatures table.
+    pub(crate) fn targets(&self) -> Vec<(&str, &str)> {
+        let processed_column = match self.max_age {
+            RetentionAge::Days(_) => "indexed_at",
+            RetentionAge::Slots(_) => "slot",
+        };
+        std::iter::once((PROCESSED_TABLE, processed_column))
+            .chain(
+                self.handler_tables
+                    .iter()
+                    .map(|t| (t.table.as_str(), t.column.as_str())),
+            )
+            .collect()
+    }
+
+    /// Returns the slot below which rows are pruned, if pruning by slot.
+    /// `None` means nothing is old enough yet.
+    pub(crate) fn slot_cutoff(&self, tip_slot: u64) -> Option<u64> {
+        match self.max_age {
+            RetentionAge::Slots(slots) => tip_slot.checked_sub(slots),
+            RetentionAge::Days(_) => None,
+        }
+    }
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+/// Rows pruned from one table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrunedTable {
+    /// The table pruned
+    pub table: String,
+    /// Rows deleted or archived
+    pub rows: u64,
+    /// Space reclaimed, in bytes
+    pub bytes: u64,
+}
+
+/// Outcome of one retention run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Tables that had rows pruned
+    pub tables: Vec<PrunedTable>,
+}
+
+impl RetentionReport {
+    /// Returns the rows pruned across all tables.
+    #[must_use]
+    pub fn rows(&self) -> u64 {
+        self.tables.iter().map(|t| t.rows).sum()
+    }
+
+    /// Returns the bytes reclaimed across all tables.
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.tables.iter().map(|t| t.bytes).sum()
+    }
+}
+
+/// Running totals of the retention task.
+#[derive(Debug, Default)]
+pub struct RetentionMetrics {
+    /// Completed retention runs
+    pub runs: AtomicU64,
+    /// Rows deleted or archived
+    pub rows_pruned: AtomicU64,
+    /// Space reclaimed, in bytes
+    pub bytes_reclaimed: AtomicU64,
+}
+
+impl RetentionMetrics {
+    /// Creates zeroed metrics.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a run's outcome to the totals.
+    pub fn record(&self, report: &RetentionReport) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.rows_pruned.fetch_add(report.rows(), Ordering::Relaxed);
+        self.bytes_reclaimed
+            .fetch_add(report.bytes(), Ordering::Relaxed);
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Retention Stats: Runs: {} | Rows pruned: {} | Bytes reclaimed: {}",
+                self.runs.load(Ordering::Relaxed),
+                self.rows_pruned.load(Ordering::Relaxed),
+                self.bytes_reclaimed.load(Ordering::Relaxed)
+            ),
+        );
+    }
+}
+
+impl Storage {
+    /// Prunes rows older than `config.max_age` from the processed-signatures
+    /// table and the configured handler tables, each in its own transaction.
+    ///
+    /// Slot ages are measured from `tip_slot`, or from the last processed
+    /// slot when it is `None`. The bytes reported are the on-disk size of
+    /// the pruned rows; the space becomes reusable once `VACUUM` has run.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a table cannot be
+    /// pruned. Tables pruned before it keep their changes.
+    pub async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        let cutoff = match config.max_age {
+            RetentionAge::Days(days) => format!("NOW() - make_interval(days => {days})"),
+            RetentionAge::Slots(_) => {
+                let tip = match tip_slot {
+                    Some(slot) => Some(slot),
+                    None => self.get_last_processed_slot().await?,
+                };
+                let Some(cutoff) = tip.and_then(|tip| config.slot_cutoff(tip)) else {
+                    return Ok(report);
+                };
+                cutoff.to_string()
+            }
+        };
+
+        for (table, column) in config.targets() {
+            let condition = format!("{column} < {cutoff}");
+            let mut tx = self.pool.begin().await?;
+            let pruned = match config.action {
+                RetentionAction::Delete => format!(
+                    "WITH gone AS (DELETE FROM {table} WHERE {condition} RETURNING *)
+                     SELECT COUNT(*), COALESCE(SUM(pg_column_size(gone.*)), 0)::BIGINT FROM gone"
+                ),
+                RetentionAction::Archive => {
+                    sqlx::query(&format!(
+                        "CREATE TABLE IF NOT EXISTS {table}_archive (LIKE {table} INCLUDING DEFAULTS)"
+                    ))
+                    .execute(&mut *tx)
+                    .await?;
+                    format!(
+                        "WITH gone AS (DELETE FROM {table} WHERE {condition} RETURNING *),
+                              archived AS (INSERT INTO {table}_archive SELECT * FROM gone)
+                         SELECT COUNT(*), COALESCE(SUM(pg_column_size(gone.*)), 0)::BIGINT FROM gone"
+                    )
+                }
+            };
+            let (rows, bytes) = sqlx::query_as::<_, (i64, i64)>(&pruned)
+                .fetch_one(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            if rows > 0 {
+                report.tables.push(PrunedTable {
+                    table: table.to_string(),
+                    rows: u64::try_from(rows).unwrap_or(0),
+                    bytes: u64::try_from(bytes).unwrap_or(0),
+                });
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Applies `config` every hour until `cancellation_token` fires.
+///
+/// `tip_slot` is asked for the chain tip on every run; when it returns
+/// `None`, slot-based pruning measures from the last processed slot.
+pub fn spawn_retention<F, Fut>(
+    storage: Arc<dyn StorageBackend>,
+    config: RetentionConfig,
+    metrics: Arc<RetentionMetrics>,
+    tip_slot: F,
+    cancellation_token: CancellationToken,
+) -> tokio::task::JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Option<u64>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            tokio::select! {
+                () = cancellation_token.cancelled() => break,
+                _ = interval.tick() => {
+                    match storage.prune(&config, tip_slot().await).await {
+                        Ok(report) => {
+                            for table in &report.tables {
+                                logging::log(
+                                    logging::LogLevel::Info,
+                                    &format!("Pruned {} rows ({} bytes) from {}", table.rows, table.bytes, table.table),
+                                );
+                            }
+                            metrics.record(&report);
+                        }
+                        Err(e) => logging::log_error("Retention error", &e.to_string()),
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_age: RetentionAge) -> RetentionConfig {
+        RetentionConfig {
+            max_age,
+            action: RetentionAction::Delete,
+            handler_tables: vec![RetentionTable {
+                table: "public.transfers".to_string(),
+                column: "slot".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(config(RetentionAge::Slots(100)).validate().is_ok());
+        assert!(config(RetentionAge::Days(0)).validate().is_err());
+
+        let mut bad = config(RetentionAge::Days(30));
+        bad.handler_tables[0].column = "slot; DROP TABLE x".to_string();
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_targets_and_cutoff() {
+        let by_slot = config(RetentionAge::Slots(100));
+        assert_eq!(
+            by_slot.targets(),
+            vec![(PROCESSED_TABLE, "slot"), ("public.transfers", "slot")]
+        );
+        assert_eq!(by_slot.slot_cutoff(150), Some(50));
+        assert_eq!(by_slot.slot_cutoff(50), None);
+
+        let by_day = config(RetentionAge::Days(7));
+        assert_eq!(by_day.targets()[0], (PROCESSED_TABLE, "indexed_at"));
+        assert_eq!(by_day.slot_cutoff(150), None);
+    }
+
+    #[test]
+    fn test_metrics_accumulate() {
+        let metrics = RetentionMetrics::new();
+        metrics.record(&RetentionReport {
+            tables: vec![
+                PrunedTable {
+                    table: "a".to_string(),
+                    rows: 3,
+                    bytes: 120,
+                },
+                PrunedTable {
+                    table: "b".to_string(),
+                    rows: 1,
+                    bytes: 40,
+                },
+            ],
+        });
+        assert_eq!(metrics.runs.load(Ordering::Relaxed), 1);
+        assert_eq!(metrics.rows_pruned.load(Ordering::Relaxed), 4);
+        assert_eq!(metrics.bytes_reclaimed.load(Ordering::Relaxed), 160);
+    }
+}
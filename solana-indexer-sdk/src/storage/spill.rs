@@ -0,0 +1,643 @@
+//! Write-ahead spill queue for database outages.
+//!
+//! When a handler keeps failing because the database is unreachable, the
+//! transaction's remaining events are appended to a local JSON-lines file
+//! instead of failing the transaction. A background task replays the file
+//! through the handler registry once the database answers again, and only
+//! then marks the transactions processed, so a short maintenance window
+//! delays events rather than dropping or re-fetching them.
+//!
+//! Replay takes the file over by renaming it to `<path>.draining`, so new
+//! transactions keep spilling to a fresh file meanwhile. If the database
+//! fails again mid-replay, the unreplayed tail is written back and picked up
+//! first on the next attempt, keeping transactions in their original order.
+//! A transaction failing for any other reason would never drain, so it is
+//! moved to `<path>.rejected` with its unhandled events and replay moves on.
+
+use super::{discriminator_hex, parse_discriminator_hex, StorageBackend};
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::HandlerRegistry;
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+/// Pause between attempts to drain the queue.
+const DRAIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Returns `true` if `error` means the database could not be reached, as
+/// opposed to rejecting the query.
+pub(crate) fn is_outage(error: &SolanaIndexerError) -> bool {
+    let SolanaIndexerError::Storage(error) = error.root() else {
+        return false;
+    };
+    match error {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        // Connection exceptions (08) and operator intervention (57P),
+        // e.g. a server shutting down or still starting up
+        sqlx::Error::Database(e) => e
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code.starts_with("57P")),
+        _ => false,
+    }
+}
+
+/// One decoded event waiting to be handled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SpilledEvent {
+    /// Hex-encoded event discriminator
+    discriminator: String,
+    /// Base64-encoded event data
+    data: String,
+}
+
+/// A transaction whose events were set aside during an outage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SpilledTransaction {
+    context: TxMetadata,
+    /// Block the transaction is tentative in; `None` marks it processed
+    /// outright, as for log notifications handled without a fetch
+    block_hash: Option<String>,
+    is_finalized: bool,
+    events: Vec<SpilledEvent>,
+}
+
+impl SpilledTransaction {
+    pub(crate) fn new(
+        context: TxMetadata,
+        block_hash: Option<String>,
+        is_finalized: bool,
+        events: impl IntoIterator<Item = ([u8; 8], Vec<u8>)>,
+    ) -> Self {
+        Self {
+            context,
+            block_hash,
+            is_finalized,
+            events: events
+                .into_iter()
+                .map(|(discriminator, data)| SpilledEvent {
+                    discriminator: discriminator_hex(Some(discriminator)),
+                    data: BASE64.encode(data),
+                })
+                .collect(),
+        }
+    }
+
+    /// Handles the events in order, dropping each one once it is handled.
+    async fn replay(&mut self, registry: &HandlerRegistry, db: &sqlx::PgPool) -> Result<()> {
+        // Flags are not spilled; the replaying indexer's apply
+        self.context.flags = registry.feature_flags().clone();
+        self.context.jobs = registry.deferred_jobs().clone();
+        while let Some(event) = self.events.first() {
+            let discriminator = parse_discriminator_hex(&event.discriminator).ok_or_else(|| {
+                SolanaIndexerError::Data(format!(
+                    "Invalid spilled discriminator: {}",
+                    event.discriminator
+                ))
+            })?;
+            let data = BASE64.decode(&event.data).map_err(|e| {
+                SolanaIndexerError::Data(format!("Invalid spilled event data: {e}"))
+            })?;
+            registry
+                .handle(&discriminator, &data, &self.context, db)
+                .await?;
+            self.events.remove(0);
+        }
+        Ok(())
+    }
+
+    /// Records the transaction the way the indexer would have after
+    /// handling it.
+    async fn mark(&self, storage: &dyn StorageBackend) -> Result<()> {
+        let (signature, slot) = (&self.context.signature, self.context.slot);
+        match &self.block_hash {
+            Some(block_hash) if self.is_finalized => {
+                storage.mark_finalized(slot, block_hash).await?;
+                storage.mark_processed(signature, slot).await
+            }
+            Some(block_hash) => storage.mark_tentative(signature, slot, block_hash).await,
+            None => storage.mark_processed(signature, slot).await,
+        }
+    }
+}
+
+/// Append-only file of transactions waiting for the database to come back.
+///
+/// Set up with `with_spill_queue`; the indexer spills to it and drains it
+/// on its own.
+pub struct SpillQueue {
+    path: PathBuf,
+    /// Serializes access to the files
+    file: tokio::sync::Mutex<()>,
+    /// Signatures currently in the queue
+    pending: std::sync::Mutex<HashSet<String>>,
+    spilled: AtomicU64,
+    drained: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl SpillQueue {
+    /// Creates a queue stored at `path`. Entries left there by an earlier
+    /// run are picked up by `recover`.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            file: tokio::sync::Mutex::new(()),
+            pending: std::sync::Mutex::new(HashSet::new()),
+            spilled: AtomicU64::new(0),
+            drained: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the path of the queue file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of transactions waiting in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock_pending().len()
+    }
+
+    /// Returns `true` if no transaction is waiting.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if `signature` is waiting in the queue.
+    #[must_use]
+    pub fn contains(&self, signature: &str) -> bool {
+        self.lock_pending().contains(signature)
+    }
+
+    /// Returns the number of transactions spilled since startup.
+    #[must_use]
+    pub fn spilled(&self) -> u64 {
+        self.spilled.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of transactions drained since startup.
+    #[must_use]
+    pub fn drained(&self) -> u64 {
+        self.drained.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of transactions moved to the rejected file since
+    /// startup.
+    #[must_use]
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Spill queue Stats: {} pending | Spilled: {} | Drained: {} | Rejected: {}",
+                self.len(),
+                self.spilled(),
+                self.drained(),
+                self.rejected()
+            ),
+        );
+    }
+
+    /// Loads the signatures of entries left by an earlier run, so they are
+    /// not indexed a second time before the queue drains.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if a queue file cannot be
+    /// read.
+    pub async fn recover(&self) -> Result<usize> {
+        let _file = self.file.lock().await;
+        let mut entries = read_entries(&self.draining_path()).await?;
+        entries.extend(read_entries(&self.path).await?);
+        let mut pending = self.lock_pending();
+        pending.extend(entries.into_iter().map(|entry| entry.context.signature));
+        Ok(pending.len())
+    }
+
+    /// Appends `transaction` to the queue and syncs it to disk.
+    pub(crate) async fn push(&self, transaction: &SpilledTransaction) -> Result<()> {
+        let _file = self.file.lock().await;
+        append_entry(&self.path, transaction).await?;
+        self.lock_pending()
+            .insert(transaction.context.signature.clone());
+        self.spilled.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Replays queued transactions through `registry` and marks them in
+    /// `storage`, oldest first. Returns the number drained.
+    ///
+    /// A transaction the database rejects, rather than failing to reach, is
+    /// moved to the rejected file and replay continues with the next one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first outage or file error met; the transactions not
+    /// drained yet stay queued for the next attempt.
+    pub async fn drain(
+        &self,
+        registry: &HandlerRegistry,
+        storage: &dyn StorageBackend,
+    ) -> Result<usize> {
+        let draining = self.draining_path();
+        {
+            let _file = self.file.lock().await;
+            if !exists(&draining).await? {
+                if !exists(&self.path).await? {
+                    return Ok(0);
+                }
+                tokio::fs::rename(&self.path, &draining)
+                    .await
+                    .map_err(|e| io_error("rotate", &self.path, &e))?;
+            }
+        }
+
+        let mut entries = read_entries(&draining).await?;
+        let mut drained = 0;
+        while let Some(entry) = entries.first_mut() {
+            let signature = entry.context.signature.clone();
+            let result = async {
+                if !storage.is_processed(&signature).await? {
+                    entry.replay(registry, storage.require_pool()?).await?;
+                    entry.mark(storage).await?;
+                }
+                Ok(())
+            }
+            .await;
+            match result {
+                Ok(()) => {
+                    self.drained.fetch_add(1, Ordering::Relaxed);
+                    drained += 1;
+                }
+                Err(e) if is_outage(&e) => {
+                    write_entries(&draining, &entries).await?;
+                    return Err(e);
+                }
+                Err(e) => {
+                    logging::log_error(
+                        &format!("Rejecting spilled transaction {signature}"),
+                        &e.to_string(),
+                    );
+                    if let Err(e) = append_entry(&self.rejected_path(), &entries[0]).await {
+                        write_entries(&draining, &entries).await?;
+                        return Err(e);
+                    }
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            entries.remove(0);
+            self.lock_pending().remove(&signature);
+        }
+
+        tokio::fs::remove_file(&draining)
+            .await
+            .map_err(|e| io_error("remove", &draining, &e))?;
+        Ok(drained)
+    }
+
+    /// Spawns a task that drains the queue every few seconds until `token`
+    /// is cancelled.
+    pub fn spawn(
+        self: Arc<Self>,
+        registry: Arc<HandlerRegistry>,
+        storage: Arc<dyn StorageBackend>,
+        token: CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(DRAIN_INTERVAL);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => break,
+                    _ = interval.tick() => match self.drain(&registry, storage.as_ref()).await {
+                        Ok(0) => {}
+                        Ok(count) => logging::log(
+                            logging::LogLevel::Info,
+                            &format!("Drained {count} spilled transactions"),
+                        ),
+                        Err(e) if is_outage(&e) => {
+                            tracing::debug!("Database still unavailable, spill queue kept: {e}");
+                        }
+                        Err(e) => logging::log_error("Spill queue drain error", &e.to_string()),
+                    },
+                }
+            }
+        })
+    }
+
+    fn draining_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".draining");
+        path.into()
+    }
+
+    /// Returns the path of the file holding transactions that failed to
+    /// replay for a reason other than an outage.
+    #[must_use]
+    pub fn rejected_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".rejected");
+        path.into()
+    }
+
+    fn lock_pending(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn io_error(action: &str, path: &Path, error: &std::io::Error) -> SolanaIndexerError {
+    SolanaIndexerError::Internal(format!(
+        "Failed to {action} spill queue {}: {error}",
+        path.display()
+    ))
+}
+
+async fn exists(path: &Path) -> Result<bool> {
+    tokio::fs::try_exists(path)
+        .await
+        .map_err(|e| io_error("stat", path, &e))
+}
+
+/// Reads the entries of a queue file, empty if it does not exist. A line
+/// that does not parse, such as one torn by a crash mid-write, is skipped.
+async fn read_entries(path: &Path) -> Result<Vec<SpilledTransaction>> {
+    let contents = match tokio::fs::read_to_string(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error("read", path, &e)),
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                logging::log_error("Skipping unreadable spill entry", &e.to_string());
+                None
+            }
+        })
+        .collect())
+}
+
+/// Appends `entry` to a queue file and syncs it to disk.
+async fn append_entry(path: &Path, entry: &SpilledTransaction) -> Result<()> {
+    let mut line =
+        serde_json::to_string(entry).map_err(|e| SolanaIndexerError::Internal(e.to_string()))?;
+    line.push('\n');
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| io_error("open", path, &e))?;
+    file.write_all(line.as_bytes())
+        .await
+        .map_err(|e| io_error("write", path, &e))?;
+    file.sync_data()
+        .await
+        .map_err(|e| io_error("sync", path, &e))
+}
+
+/// Replaces the contents of a queue file with `entries`.
+async fn write_entries(path: &Path, entries: &[SpilledTransaction]) -> Result<()> {
+    let mut contents = String::new();
+    for entry in entries {
+        contents.push_str(
+            &serde_json::to_string(entry)
+                .map_err(|e| SolanaIndexerError::Internal(e.to_string()))?,
+        );
+        contents.push('\n');
+    }
+    let mut temp = path.to_path_buf().into_os_string();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    tokio::fs::write(&temp, contents)
+        .await
+        .map_err(|e| io_error("write", &temp, &e))?;
+    tokio::fs::rename(&temp, path)
+        .await
+        .map_err(|e| io_error("replace", path, &e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "sqlite")]
+    use {
+        crate::types::traits::DynamicEventHandler, async_trait::async_trait, sqlx::PgPool,
+        std::sync::atomic::AtomicBool,
+    };
+
+    fn context(signature: &str, slot: u64) -> TxMetadata {
+        TxMetadata {
+            slot,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: signature.to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        }
+    }
+
+    fn queue_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "solstream-spill-{}.jsonl",
+            solana_sdk::signature::Signature::new_unique()
+        ))
+    }
+
+    /// Counts handled events, failing like an unreachable database while
+    /// `down` is set.
+    #[cfg(feature = "sqlite")]
+    struct Flaky {
+        down: Arc<AtomicBool>,
+        handled: Arc<AtomicU64>,
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[async_trait]
+    impl DynamicEventHandler for Flaky {
+        async fn handle_dynamic(
+            &self,
+            _: &[u8; 8],
+            _: &[u8],
+            _: &TxMetadata,
+            _: &PgPool,
+        ) -> Result<()> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(sqlx::Error::PoolTimedOut.into());
+            }
+            self.handled.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn handle_rollback_dynamic(&self, _: &TxMetadata, _: &PgPool) -> Result<()> {
+            Ok(())
+        }
+
+        async fn initialize_schema(&self, _: &PgPool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_is_outage() {
+        assert!(is_outage(&sqlx::Error::PoolTimedOut.into()));
+        assert!(!is_outage(&sqlx::Error::RowNotFound.into()));
+        assert!(!is_outage(&SolanaIndexerError::rpc("down")));
+    }
+
+    #[tokio::test]
+    async fn test_recover_loads_earlier_entries() -> Result<()> {
+        let path = queue_path();
+        let queue = SpillQueue::new(&path);
+        let event = ([1u8; 8], vec![1, 2, 3]);
+        queue
+            .push(&SpilledTransaction::new(
+                context("sig_a", 5),
+                Some("hash".to_string()),
+                true,
+                [event],
+            ))
+            .await?;
+        assert!(queue.contains("sig_a"));
+
+        let reopened = SpillQueue::new(&path);
+        assert_eq!(reopened.recover().await?, 1);
+        assert!(reopened.contains("sig_a"));
+        tokio::fs::remove_file(&path).await.unwrap();
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_drain_resumes_after_outage() -> Result<()> {
+        // Handlers never touch the pool, so it is never connected
+        let handler_pool = PgPool::connect_lazy("postgresql://localhost/db")?;
+        let storage = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:")
+            .await?
+            .with_handler_pool(handler_pool);
+        storage.initialize().await?;
+        let down = Arc::new(AtomicBool::new(true));
+        let handled = Arc::new(AtomicU64::new(0));
+        let mut registry = HandlerRegistry::new();
+        registry.register(
+            [1u8; 8],
+            Box::new(Flaky {
+                down: down.clone(),
+                handled: handled.clone(),
+            }),
+        )?;
+
+        let queue = SpillQueue::new(queue_path());
+        for (signature, slot) in [("sig_a", 5), ("sig_b", 6)] {
+            let events = [([1u8; 8], vec![0]), ([1u8; 8], vec![1])];
+            queue
+                .push(&SpilledTransaction::new(
+                    context(signature, slot),
+                    Some(format!("hash_{slot}")),
+                    true,
+                    events,
+                ))
+                .await?;
+        }
+
+        let result = queue.drain(&registry, &storage).await;
+        assert!(matches!(&result, Err(e) if is_outage(e)));
+        assert_eq!(queue.len(), 2);
+
+        down.store(false, Ordering::SeqCst);
+        assert_eq!(queue.drain(&registry, &storage).await?, 2);
+        assert_eq!(handled.load(Ordering::SeqCst), 4);
+        assert!(queue.is_empty());
+        assert!(storage.is_processed("sig_a").await?);
+        assert!(storage.is_processed("sig_b").await?);
+        assert_eq!(queue.drain(&registry, &storage).await?, 0);
+        Ok(())
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_drain_rejects_bad_entry_and_continues() -> Result<()> {
+        let handler_pool = PgPool::connect_lazy("postgresql://localhost/db")?;
+        let storage = crate::storage::sqlite::SqliteStorage::new("sqlite::memory:")
+            .await?
+            .with_handler_pool(handler_pool);
+        storage.initialize().await?;
+        let handled = Arc::new(AtomicU64::new(0));
+        let mut registry = HandlerRegistry::new();
+        registry.register(
+            [1u8; 8],
+            Box::new(Flaky {
+                down: Arc::new(AtomicBool::new(false)),
+                handled: handled.clone(),
+            }),
+        )?;
+
+        let queue = SpillQueue::new(queue_path());
+        let mut bad = SpilledTransaction::new(context("sig_bad", 5), None, false, []);
+        bad.events.push(SpilledEvent {
+            discriminator: "not hex".to_string(),
+            data: String::new(),
+        });
+        queue.push(&bad).await?;
+        queue
+            .push(&SpilledTransaction::new(
+                context("sig_good", 6),
+                None,
+                false,
+                [([1u8; 8], vec![0])],
+            ))
+            .await?;
+
+        // The bad head entry is set aside instead of blocking the queue
+        assert_eq!(queue.drain(&registry, &storage).await?, 1);
+        assert_eq!(queue.rejected(), 1);
+        assert_eq!(handled.load(Ordering::SeqCst), 1);
+        assert!(queue.is_empty());
+        assert!(!storage.is_processed("sig_bad").await?);
+        assert!(storage.is_processed("sig_good").await?);
+
+        let rejected = read_entries(&queue.rejected_path()).await?;
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].context.signature, "sig_bad");
+        tokio::fs::remove_file(queue.rejected_path()).await.unwrap();
+        Ok(())
+    }
+}
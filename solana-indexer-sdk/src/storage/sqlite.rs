@@ -0,0 +1,1182 @@
+//! SQLite storage backend.
+//!
+//! `SqliteStorage` keeps the SDK's own bookkeeping (processed signatures,
+//! tentative transactions, finalized blocks, backfill progress, cursors,
+//! snapshots, the watermark, signature confirmations, dead letters and daily
+//! usage) in a SQLite database, so small deployments and tests can run
+//! without a `PostgreSQL` server.
+//!
+//! Handlers and schema initializers receive a `PgPool`, and SQLite storage
+//! has none of its own: handlers write to the `PostgreSQL` database set with
+//! `SolanaIndexerConfigBuilder::with_handler_database` (or
+//! [`SqliteStorage::with_handler_pool`]). That pool connects lazily, so
+//! handlers that keep their output in memory or forward it elsewhere run
+//! without a server behind it. Without a handler database, dispatching to a
+//! handler, initializing a schema, registering account state tables or
+//! scheduling tasks fails with `SolanaIndexerError::Config`.
+//!
+//! The SDK features that keep their data in `PostgreSQL` (transaction
+//! archive, dead-letter queue, token holders, feature flags, deferred jobs,
+//! outbox, atomic transactions, replication, vacuum and the like) are
+//! rejected by `SolanaIndexerConfigBuilder::build` for `sqlite:` URLs, and
+//! the matching `StorageBackend` methods return `SolanaIndexerError::Config`.
+
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::storage::buffer::BufferedQuery;
+use crate::storage::partitions::PartitionReport;
+use crate::storage::retention::{
+    PrunedTable, RetentionAction, RetentionAge, RetentionConfig, RetentionReport,
+};
+use crate::storage::vacuum::{VacuumConfig, VacuumReport};
+use crate::storage::{
+    commitment_rank, discriminator_hex, range_progress, usage_record, BackfillRangeRow,
+    ConfirmationStatus, ConfirmationSummary, DeadLetter, StorageBackend,
+};
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use sqlx::postgres::PgPool;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// SQLite-backed implementation of [`StorageBackend`].
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_sdk::storage::sqlite::SqliteStorage;
+/// use solana_indexer_sdk::StorageBackend;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let storage = SqliteStorage::new("sqlite://indexer.db").await?;
+/// storage.initialize().await?;
+/// storage.mark_processed("5j7s6NiJS3...", 12345).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SqliteStorage {
+    /// SQLite connection pool for SDK tables
+    pool: SqlitePool,
+    /// Pool handed to handlers and schema initializers, if any
+    handler_pool: Option<PgPool>,
+}
+
+impl SqliteStorage {
+    /// Opens (or creates) the SQLite database at `database_url`.
+    ///
+    /// Accepts `sqlite://path/to/file.db` and `sqlite::memory:`. In-memory
+    /// databases are shared by the pool's connections and dropped once the
+    /// last one closes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if the URL is invalid or
+    /// the database cannot be opened.
+    pub async fn new(database_url: &str) -> Result<Self> {
+        let options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(Duration::from_secs(3));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(5)
+            .acquire_timeout(Duration::from_secs(3))
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?;
+
+        Ok(Self {
+            pool,
+            handler_pool: None,
+        })
+    }
+
+    /// Hands `pool` to handlers and schema initializers.
+    #[must_use]
+    pub fn with_handler_pool(mut self, pool: PgPool) -> Self {
+        self.handler_pool = Some(pool);
+        self
+    }
+
+    /// Returns a reference to the SQLite connection pool.
+    #[must_use]
+    pub fn sqlite_pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// Closes the SQLite pool and the handler pool, if any.
+    pub async fn close(self) {
+        self.pool.close().await;
+        if let Some(pool) = self.handler_pool {
+            pool.close().await;
+        }
+    }
+}
+
+fn to_i64(slot: u64) -> i64 {
+    i64::try_from(slot).unwrap_or(i64::MAX)
+}
+
+fn to_u64(slot: i64) -> u64 {
+    slot.try_into().unwrap_or(0)
+}
+
+#[async_trait]
+impl StorageBackend for SqliteStorage {
+    async fn initialize(&self) -> Result<()> {
+        let statements = [
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_processed (
+                signature TEXT PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                indexed_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            "CREATE INDEX IF NOT EXISTS idx_processed_slot ON _solana_indexer_sdk_processed(slot)",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_finalized_blocks (
+                slot INTEGER PRIMARY KEY,
+                block_hash TEXT NOT NULL,
+                finalized_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_tentative (
+                signature TEXT PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                block_hash TEXT NOT NULL,
+                indexed_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            "CREATE INDEX IF NOT EXISTS idx_tentative_slot ON _solana_indexer_sdk_tentative(slot)",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_backfill_progress (
+                id INTEGER PRIMARY KEY,
+                last_slot INTEGER NOT NULL,
+                is_complete BOOLEAN DEFAULT FALSE,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_backfill_ranges (
+                start_slot INTEGER NOT NULL,
+                end_slot INTEGER NOT NULL,
+                next_slot INTEGER NOT NULL,
+                cursor TEXT,
+                transactions INTEGER NOT NULL DEFAULT 0,
+                run_start_slot INTEGER NOT NULL,
+                run_start_transactions INTEGER NOT NULL DEFAULT 0,
+                run_started_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (start_slot, end_slot)
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_cursors (
+                address TEXT PRIMARY KEY,
+                signature TEXT NOT NULL,
+                slot INTEGER NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_watermark (
+                id INTEGER PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_snapshots (
+                program_id TEXT PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                completed_at TEXT DEFAULT CURRENT_TIMESTAMP
+            )
+            ",
+            // Timestamps in Unix seconds
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_confirmations (
+                signature TEXT PRIMARY KEY,
+                slot INTEGER NOT NULL,
+                processed_at INTEGER,
+                confirmed_at INTEGER,
+                finalized_at INTEGER
+            )
+            ",
+            r"
+            CREATE INDEX IF NOT EXISTS idx_confirmations_unfinalized
+            ON _solana_indexer_sdk_confirmations(slot, signature)
+            WHERE finalized_at IS NULL
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_usage (
+                day TEXT NOT NULL,
+                program TEXT NOT NULL,
+                discriminator TEXT NOT NULL,
+                events INTEGER NOT NULL DEFAULT 0,
+                bytes INTEGER NOT NULL DEFAULT 0,
+                rpc_calls INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (day, program, discriminator)
+            )
+            ",
+            r"
+            CREATE TABLE IF NOT EXISTS _solana_indexer_sdk_dlq (
+                id INTEGER PRIMARY KEY,
+                signature TEXT NOT NULL UNIQUE,
+                slot INTEGER NOT NULL,
+                error TEXT NOT NULL,
+                created_at INTEGER NOT NULL DEFAULT (unixepoch())
+            )
+            ",
+        ];
+        for statement in statements {
+            sqlx::query(statement).execute(&self.pool).await?;
+        }
+        Ok(())
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        let processed = sqlx::query_scalar::<_, bool>(
+            r"
+            SELECT EXISTS(SELECT 1 FROM _solana_indexer_sdk_processed WHERE signature = ?1)
+                OR EXISTS(SELECT 1 FROM _solana_indexer_sdk_tentative WHERE signature = ?1)
+            ",
+        )
+        .bind(signature)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(processed)
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO _solana_indexer_sdk_processed (signature, slot) VALUES (?, ?)",
+        )
+        .bind(signature)
+        .bind(to_i64(slot))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT MAX(slot) FROM _solana_indexer_sdk_processed",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(slot.map(to_u64))
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        let signature = sqlx::query_scalar::<_, String>(
+            "SELECT signature FROM _solana_indexer_sdk_processed ORDER BY slot DESC, indexed_at DESC LIMIT 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(signature)
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        self.handler_pool.as_ref()
+    }
+
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for (signature, slot) in entries {
+            sqlx::query(
+                "INSERT OR IGNORE INTO _solana_indexer_sdk_processed (signature, slot) VALUES (?, ?)",
+            )
+            .bind(signature)
+            .bind(to_i64(*slot))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Handler writes live in `PostgreSQL` and cannot commit with SQLite
+    /// bookkeeping, so only a transaction without writes is accepted.
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        if !writes.is_empty() {
+            return Err(unsupported("atomic transactions"));
+        }
+        match block_hash {
+            Some(block_hash) => self.mark_tentative(signature, slot, block_hash).await,
+            None => self.mark_processed(signature, slot).await,
+        }
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        sqlx::query(
+            "INSERT OR IGNORE INTO _solana_indexer_sdk_tentative (signature, slot, block_hash) VALUES (?, ?, ?)",
+        )
+        .bind(signature)
+        .bind(to_i64(slot))
+        .bind(block_hash)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        let slot = to_i64(slot);
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO _solana_indexer_sdk_finalized_blocks (slot, block_hash) VALUES (?, ?)",
+        )
+        .bind(slot)
+        .bind(block_hash)
+        .execute(&mut *tx)
+        .await?;
+
+        // Move tentative transactions for this slot to the processed table
+        sqlx::query(
+            r"
+            INSERT OR IGNORE INTO _solana_indexer_sdk_processed (signature, slot, indexed_at)
+            SELECT signature, slot, indexed_at FROM _solana_indexer_sdk_tentative WHERE slot = ?
+            ",
+        )
+        .bind(slot)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM _solana_indexer_sdk_tentative WHERE slot = ?")
+            .bind(slot)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        let signatures = sqlx::query_scalar::<_, String>(
+            "SELECT signature FROM _solana_indexer_sdk_tentative WHERE slot = ?",
+        )
+        .bind(to_i64(slot))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(signatures)
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        let slot = to_i64(slot);
+        let mut tx = self.pool.begin().await?;
+        for table in [
+            "_solana_indexer_sdk_tentative",
+            "_solana_indexer_sdk_processed",
+            "_solana_indexer_sdk_finalized_blocks",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE slot = ?"))
+                .bind(slot)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        let hash = sqlx::query_scalar::<_, String>(
+            r"
+            SELECT block_hash FROM _solana_indexer_sdk_finalized_blocks WHERE slot = ?1
+            UNION ALL
+            SELECT block_hash FROM (
+                SELECT block_hash FROM _solana_indexer_sdk_tentative WHERE slot = ?1 LIMIT 1
+            )
+            LIMIT 1
+            ",
+        )
+        .bind(to_i64(slot))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(hash)
+    }
+
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        let current_slot = self.get_last_processed_slot().await?.unwrap_or(0);
+        let Some(cutoff_slot) = current_slot.checked_sub(slot_threshold) else {
+            return Ok(0);
+        };
+
+        let result = sqlx::query("DELETE FROM _solana_indexer_sdk_tentative WHERE slot < ?")
+            .bind(to_i64(cutoff_slot))
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        let slots = sqlx::query_scalar::<_, i64>(
+            "SELECT DISTINCT slot FROM _solana_indexer_sdk_tentative WHERE slot <= ? ORDER BY slot ASC",
+        )
+        .bind(to_i64(slot))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(slots.into_iter().map(to_u64).collect())
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_backfill_progress (id, last_slot, updated_at)
+            VALUES (1, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (id) DO UPDATE
+            SET last_slot = excluded.last_slot, updated_at = CURRENT_TIMESTAMP
+            ",
+        )
+        .bind(to_i64(slot))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>(
+            "SELECT last_slot FROM _solana_indexer_sdk_backfill_progress WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(slot.map(to_u64))
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        sqlx::query(
+            "UPDATE _solana_indexer_sdk_backfill_progress SET is_complete = TRUE, updated_at = CURRENT_TIMESTAMP WHERE id = 1",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        let (next_slot, cursor) = sqlx::query_as::<_, (i64, Option<String>)>(
+            r"
+            INSERT INTO _solana_indexer_sdk_backfill_ranges
+                (start_slot, end_slot, next_slot, run_start_slot, run_started_at, updated_at)
+            VALUES (?1, ?2, ?1, ?1, ?3, ?3)
+            ON CONFLICT (start_slot, end_slot) DO UPDATE SET
+                next_slot = CASE WHEN next_slot > end_slot THEN start_slot ELSE next_slot END,
+                cursor = CASE WHEN next_slot > end_slot THEN NULL ELSE cursor END,
+                transactions = CASE WHEN next_slot > end_slot THEN 0 ELSE transactions END,
+                run_start_slot = CASE WHEN next_slot > end_slot THEN start_slot ELSE next_slot END,
+                run_start_transactions = CASE WHEN next_slot > end_slot THEN 0 ELSE transactions END,
+                run_started_at = excluded.run_started_at,
+                updated_at = excluded.updated_at
+            RETURNING next_slot, cursor
+            ",
+        )
+        .bind(to_i64(range.start_slot))
+        .bind(to_i64(range.end_slot))
+        .bind(chrono::Utc::now().timestamp_millis())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok((to_u64(next_slot), cursor))
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        let next_slot = if cursor.is_some() {
+            slot
+        } else {
+            slot.saturating_add(1)
+        };
+        sqlx::query(
+            r"
+            UPDATE _solana_indexer_sdk_backfill_ranges
+            SET next_slot = MAX(next_slot, ?3),
+                cursor = ?4,
+                transactions = transactions + ?5,
+                updated_at = ?6
+            WHERE start_slot = ?1 AND end_slot = ?2
+            ",
+        )
+        .bind(to_i64(range.start_slot))
+        .bind(to_i64(range.end_slot))
+        .bind(to_i64(next_slot))
+        .bind(cursor)
+        .bind(to_i64(transactions))
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        let rows = sqlx::query_as::<_, BackfillRangeRow>(
+            r"
+            SELECT start_slot, end_slot, next_slot, cursor, transactions,
+                   run_start_slot, run_start_transactions, run_started_at, updated_at
+            FROM _solana_indexer_sdk_backfill_ranges
+            ORDER BY start_slot, end_slot
+            ",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(range_progress).collect())
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_cursors (address, signature, slot, updated_at)
+            VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (address) DO UPDATE
+            SET signature = excluded.signature, slot = excluded.slot, updated_at = CURRENT_TIMESTAMP
+            ",
+        )
+        .bind(address)
+        .bind(signature)
+        .bind(to_i64(slot))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        let signature = sqlx::query_scalar::<_, String>(
+            "SELECT signature FROM _solana_indexer_sdk_cursors WHERE address = ?",
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(signature)
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_snapshots (program_id, slot, completed_at)
+            VALUES (?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (program_id) DO UPDATE
+            SET slot = excluded.slot, completed_at = CURRENT_TIMESTAMP
+            ",
+        )
+        .bind(program_id)
+        .bind(to_i64(slot))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>(
+            "SELECT slot FROM _solana_indexer_sdk_snapshots WHERE program_id = ?",
+        )
+        .bind(program_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(slot.map(to_u64))
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_watermark (id, slot, updated_at)
+            VALUES (1, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT (id) DO UPDATE
+            SET slot = MAX(_solana_indexer_sdk_watermark.slot, excluded.slot),
+                updated_at = CURRENT_TIMESTAMP
+            ",
+        )
+        .bind(to_i64(slot))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        let slot = sqlx::query_scalar::<_, i64>(
+            "SELECT slot FROM _solana_indexer_sdk_watermark WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(slot.map(to_u64))
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_confirmations
+                (signature, slot, processed_at, confirmed_at, finalized_at)
+            VALUES (
+                ?1, ?2, unixepoch(),
+                CASE WHEN ?3 >= 1 THEN unixepoch() END,
+                CASE WHEN ?3 >= 2 THEN unixepoch() END
+            )
+            ON CONFLICT (signature) DO UPDATE SET
+                slot = excluded.slot,
+                confirmed_at = COALESCE(confirmed_at, excluded.confirmed_at),
+                finalized_at = COALESCE(finalized_at, excluded.finalized_at)
+            ",
+        )
+        .bind(signature)
+        .bind(to_i64(slot))
+        .bind(commitment_rank(level))
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        let (after_slot, after_signature) = after.unwrap_or((0, ""));
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            r"
+            SELECT slot, signature FROM _solana_indexer_sdk_confirmations
+            WHERE finalized_at IS NULL AND (slot, signature) > (?, ?)
+            ORDER BY slot, signature
+            LIMIT ?
+            ",
+        )
+        .bind(to_i64(after_slot))
+        .bind(after_signature)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(slot, signature)| (to_u64(slot), signature))
+            .collect())
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        let (after_slot, after_signature) = after.unwrap_or((slots.0, ""));
+        let rows = sqlx::query_as::<_, (i64, String)>(
+            r"
+            SELECT slot, signature FROM _solana_indexer_sdk_processed
+            WHERE slot <= ? AND (slot, signature) > (?, ?)
+            ORDER BY slot, signature
+            LIMIT ?
+            ",
+        )
+        .bind(to_i64(slots.1))
+        .bind(to_i64(after_slot))
+        .bind(after_signature)
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(slot, signature)| (to_u64(slot), signature))
+            .collect())
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        let row = sqlx::query_as::<_, (i64, Option<i64>, Option<i64>, Option<i64>)>(
+            r"
+            SELECT slot, processed_at, confirmed_at, finalized_at
+            FROM _solana_indexer_sdk_confirmations WHERE signature = ?
+            ",
+        )
+        .bind(signature)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.map(
+            |(slot, processed_at, confirmed_at, finalized_at)| ConfirmationStatus {
+                slot: to_u64(slot),
+                processed_at,
+                confirmed_at,
+                finalized_at,
+            },
+        ))
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        let (tracked, confirmed, finalized, avg_seconds_to_finalized) =
+            sqlx::query_as::<_, (i64, i64, i64, Option<f64>)>(
+                r"
+                SELECT COUNT(*),
+                       COUNT(confirmed_at),
+                       COUNT(finalized_at),
+                       AVG(CAST(finalized_at - processed_at AS REAL))
+                FROM _solana_indexer_sdk_confirmations
+                ",
+            )
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(ConfirmationSummary {
+            tracked: to_u64(tracked),
+            confirmed: to_u64(confirmed),
+            finalized: to_u64(finalized),
+            avg_seconds_to_finalized,
+        })
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        sqlx::query(
+            r"
+            INSERT INTO _solana_indexer_sdk_dlq (signature, slot, error)
+            VALUES (?, ?, ?)
+            ON CONFLICT (signature) DO UPDATE SET
+                slot = excluded.slot,
+                error = excluded.error,
+                created_at = unixepoch()
+            ",
+        )
+        .bind(signature)
+        .bind(to_i64(slot))
+        .bind(reason)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        let rows = sqlx::query_as::<_, (String, i64, String, i64)>(
+            r"
+            SELECT signature, slot, error, created_at
+            FROM _solana_indexer_sdk_dlq
+            ORDER BY created_at, id
+            LIMIT ?
+            ",
+        )
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(signature, slot, reason, recorded_at)| DeadLetter {
+                signature,
+                slot: to_u64(slot),
+                reason,
+                recorded_at,
+            })
+            .collect())
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        for record in records {
+            sqlx::query(
+                r"
+                INSERT INTO _solana_indexer_sdk_usage
+                    (day, program, discriminator, events, bytes, rpc_calls)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT (day, program, discriminator) DO UPDATE SET
+                    events = events + excluded.events,
+                    bytes = bytes + excluded.bytes,
+                    rpc_calls = rpc_calls + excluded.rpc_calls
+                ",
+            )
+            .bind(day)
+            .bind(&record.program)
+            .bind(discriminator_hex(record.discriminator))
+            .bind(to_i64(record.events))
+            .bind(to_i64(record.bytes))
+            .bind(to_i64(record.rpc_calls))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        let rows = sqlx::query_as::<_, (String, String, i64, i64, i64)>(
+            r"
+            SELECT program, discriminator, events, bytes, rpc_calls
+            FROM _solana_indexer_sdk_usage
+            WHERE day = ?
+            ORDER BY program, discriminator
+            ",
+        )
+        .bind(day)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(usage_record).collect())
+    }
+
+    /// Bytes reclaimed are the pages the pruned rows left free in the
+    /// database file, so pruning a handful of rows may reclaim nothing.
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        let mut report = RetentionReport::default();
+        let cutoff = match config.max_age {
+            RetentionAge::Days(days) => format!("datetime('now', '-{days} days')"),
+            RetentionAge::Slots(_) => {
+                let tip = match tip_slot {
+                    Some(slot) => Some(slot),
+                    None => self.get_last_processed_slot().await?,
+                };
+                let Some(cutoff) = tip.and_then(|tip| config.slot_cutoff(tip)) else {
+                    return Ok(report);
+                };
+                cutoff.to_string()
+            }
+        };
+        let page_size = sqlx::query_scalar::<_, i64>("PRAGMA page_size")
+            .fetch_one(&self.pool)
+            .await?;
+
+        for (table, column) in config.targets() {
+            let condition = format!("{column} < {cutoff}");
+            let free_before = sqlx::query_scalar::<_, i64>("PRAGMA freelist_count")
+                .fetch_one(&self.pool)
+                .await?;
+            let mut tx = self.pool.begin().await?;
+            if config.action == RetentionAction::Archive {
+                sqlx::query(&format!(
+                    "CREATE TABLE IF NOT EXISTS {table}_archive AS SELECT * FROM {table} WHERE 0"
+                ))
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(&format!(
+                    "INSERT INTO {table}_archive SELECT * FROM {table} WHERE {condition}"
+                ))
+                .execute(&mut *tx)
+                .await?;
+            }
+            let rows = sqlx::query(&format!("DELETE FROM {table} WHERE {condition}"))
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            tx.commit().await?;
+            let free_after = sqlx::query_scalar::<_, i64>("PRAGMA freelist_count")
+                .fetch_one(&self.pool)
+                .await?;
+            if rows > 0 {
+                report.tables.push(PrunedTable {
+                    table: table.to_string(),
+                    rows,
+                    bytes: u64::try_from((free_after - free_before) * page_size).unwrap_or(0),
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    async fn maintain_partitions(&self, _tip_slot: Option<u64>) -> Result<PartitionReport> {
+        Err(unsupported("partitioning"))
+    }
+
+    async fn vacuum(&self, _config: &VacuumConfig) -> Result<VacuumReport> {
+        Err(unsupported("vacuum"))
+    }
+
+    /// Writes go straight to the database; there is nothing to flush.
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Error for a `PostgreSQL`-only feature used with SQLite storage.
+fn unsupported(feature: &str) -> SolanaIndexerError {
+    SolanaIndexerError::Config(format!("SQLite storage does not support {feature}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn storage() -> Result<SqliteStorage> {
+        let storage = SqliteStorage::new("sqlite::memory:").await?;
+        storage.initialize().await?;
+        Ok(storage)
+    }
+
+    #[tokio::test]
+    async fn test_postgres_only_operations_are_rejected() -> Result<()> {
+        let storage = storage().await?;
+        assert!(storage.pool().is_none());
+        assert!(matches!(
+            storage.require_pool(),
+            Err(SolanaIndexerError::Config(_))
+        ));
+        assert!(matches!(
+            storage.vacuum(&VacuumConfig::default()).await,
+            Err(SolanaIndexerError::Config(_))
+        ));
+        assert!(matches!(
+            storage.maintain_partitions(None).await,
+            Err(SolanaIndexerError::Config(_))
+        ));
+
+        // Without handler writes, committing just marks the signature
+        storage
+            .commit_transaction(Vec::new(), "sig_a", 10, None)
+            .await?;
+        assert!(storage.is_processed("sig_a").await?);
+
+        let handler_pool = PgPool::connect_lazy("postgresql://localhost/db")?;
+        let storage = storage.with_handler_pool(handler_pool);
+        assert!(storage.pool().is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mark_processed_batch() -> Result<()> {
+        let storage = storage().await?;
+        let entries = [("sig_a".to_string(), 10), ("sig_b".to_string(), 12)];
+        storage.mark_processed_batch(&entries).await?;
+        storage.mark_processed_batch(&entries).await?;
+        assert!(storage.is_processed("sig_a").await?);
+        assert_eq!(storage.get_last_processed_slot().await?, Some(12));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_processed_and_tentative_lifecycle() -> Result<()> {
+        let storage = storage().await?;
+
+        assert_eq!(storage.get_last_processed_slot().await?, None);
+        storage.mark_processed("sig_a", 10).await?;
+        storage.mark_processed("sig_a", 10).await?;
+        storage.mark_tentative("sig_b", 12, "hash_12").await?;
+
+        assert!(storage.is_processed("sig_a").await?);
+        assert!(storage.is_processed("sig_b").await?);
+        assert!(!storage.is_processed("sig_c").await?);
+        assert_eq!(storage.get_tentative_slots_le(20).await?, vec![12]);
+        assert_eq!(
+            storage.get_block_hash(12).await?,
+            Some("hash_12".to_string())
+        );
+
+        storage.mark_finalized(12, "hash_12").await?;
+        assert!(storage.get_tentative_transactions(12).await?.is_empty());
+        assert_eq!(storage.get_last_processed_slot().await?, Some(12));
+        assert_eq!(
+            storage.get_last_processed_signature().await?,
+            Some("sig_b".to_string())
+        );
+
+        storage.rollback_slot(12).await?;
+        assert!(!storage.is_processed("sig_b").await?);
+        assert_eq!(storage.get_block_hash(12).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_progress_cursor_and_watermark() -> Result<()> {
+        let storage = storage().await?;
+
+        assert_eq!(storage.load_backfill_progress().await?, None);
+        storage.save_backfill_progress(100).await?;
+        storage.save_backfill_progress(150).await?;
+        storage.mark_backfill_complete().await?;
+        assert_eq!(storage.load_backfill_progress().await?, Some(150));
+
+        let range = BackfillRange::new(100, 102);
+        assert_eq!(storage.begin_backfill_range(range).await?, (100, None));
+        storage.advance_backfill_range(range, 100, None, 3).await?;
+        // A restart mid-slot resumes after the last checkpointed signature
+        storage
+            .advance_backfill_range(range, 101, Some("sig_x"), 1)
+            .await?;
+        assert_eq!(
+            storage.begin_backfill_range(range).await?,
+            (101, Some("sig_x".to_string()))
+        );
+        let ranges = storage.load_backfill_ranges().await?;
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0].next_slot, 101);
+        assert_eq!(ranges[0].run_start_slot, 101);
+        assert_eq!(ranges[0].run_start_transactions, 4);
+        storage.advance_backfill_range(range, 102, None, 1).await?;
+        let done = storage.load_backfill_ranges().await?.remove(0);
+        assert!(done.is_complete());
+        assert_eq!((done.cursor, done.transactions), (None, 5));
+        // Running a backfilled range again starts it over
+        assert_eq!(storage.begin_backfill_range(range).await?, (100, None));
+        let rerun = storage.load_backfill_ranges().await?.remove(0);
+        assert_eq!((rerun.next_slot, rerun.transactions), (100, 0));
+
+        assert_eq!(storage.load_cursor("addr").await?, None);
+        storage.save_cursor("addr", "sig_a", 10).await?;
+        storage.save_cursor("addr", "sig_b", 12).await?;
+        assert_eq!(
+            storage.load_cursor("addr").await?,
+            Some("sig_b".to_string())
+        );
+
+        storage.save_snapshot_slot("program", 250).await?;
+        assert_eq!(storage.load_snapshot_slot("program").await?, Some(250));
+
+        storage.advance_watermark(100).await?;
+        storage.advance_watermark(50).await?;
+        assert_eq!(storage.get_watermark().await?, Some(100));
+        assert_eq!(storage.read_slot(Some(120)).await?, Some(100));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_progression() -> Result<()> {
+        let storage = storage().await?;
+
+        storage
+            .record_commitment("sig_a", 10, CommitmentLevel::Processed)
+            .await?;
+        storage
+            .record_commitment("sig_b", 10, CommitmentLevel::Confirmed)
+            .await?;
+        storage
+            .record_commitment("sig_c", 11, CommitmentLevel::Confirmed)
+            .await?;
+
+        let pending = storage.load_unfinalized_signatures(None, 2).await?;
+        assert_eq!(pending, vec![(10, "sig_a".into()), (10, "sig_b".into())]);
+        let rest = storage
+            .load_unfinalized_signatures(Some((10, "sig_b")), 2)
+            .await?;
+        assert_eq!(rest, vec![(11, "sig_c".into())]);
+
+        let first = storage.load_confirmation_status("sig_a").await?;
+        assert_eq!(first.map(|s| s.level()), Some(CommitmentLevel::Processed));
+        storage
+            .record_commitment("sig_a", 10, CommitmentLevel::Finalized)
+            .await?;
+        storage
+            .record_commitment("sig_a", 10, CommitmentLevel::Confirmed)
+            .await?;
+        let status = storage
+            .load_confirmation_status("sig_a")
+            .await?
+            .expect("tracked");
+        assert_eq!(status.level(), CommitmentLevel::Finalized);
+        assert_eq!(status.processed_at, first.and_then(|s| s.processed_at));
+        assert!(status.confirmed_at.is_some());
+
+        let summary = storage.confirmation_summary().await?;
+        assert_eq!(
+            (summary.tracked, summary.confirmed, summary.finalized),
+            (3, 3, 1)
+        );
+        assert_eq!(summary.unfinalized(), 2);
+        assert!(summary.avg_seconds_to_finalized.is_some());
+        assert_eq!(storage.load_confirmation_status("sig_x").await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters() -> Result<()> {
+        let storage = storage().await?;
+
+        storage.record_dead_letter("sig_a", 10, "too many").await?;
+        storage.record_dead_letter("sig_b", 11, "too many").await?;
+        storage.record_dead_letter("sig_a", 10, "replaced").await?;
+
+        let letters = storage.load_dead_letters(10).await?;
+        assert_eq!(letters.len(), 2);
+        let sig_a = letters
+            .iter()
+            .find(|l| l.signature == "sig_a")
+            .expect("recorded");
+        assert_eq!((sig_a.slot, sig_a.reason.as_str()), (10, "replaced"));
+        assert_eq!(storage.load_dead_letters(1).await?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_usage_accumulates_per_day() -> Result<()> {
+        let storage = storage().await?;
+        let events = UsageRecord {
+            program: "ProgramA".into(),
+            discriminator: Some([1; 8]),
+            events: 2,
+            bytes: 100,
+            rpc_calls: 0,
+        };
+        let calls = UsageRecord {
+            program: "ProgramA".into(),
+            discriminator: None,
+            rpc_calls: 3,
+            ..UsageRecord::default()
+        };
+
+        storage
+            .record_usage("2024-01-01", &[events.clone(), calls.clone()])
+            .await?;
+        storage
+            .record_usage("2024-01-01", std::slice::from_ref(&events))
+            .await?;
+        storage
+            .record_usage("2024-01-02", std::slice::from_ref(&calls))
+            .await?;
+
+        let report = storage.load_usage_report("2024-01-01").await?;
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0], calls);
+        assert_eq!((report[1].events, report[1].bytes), (4, 200));
+        assert_eq!(report[1].discriminator, Some([1; 8]));
+        assert_eq!(storage.load_usage_report("2024-01-03").await?, vec![]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prune_by_slot_archives_rows() -> Result<()> {
+        let storage = storage().await?;
+        sqlx::query("CREATE TABLE transfers (signature TEXT, slot INTEGER)")
+            .execute(&storage.pool)
+            .await?;
+        for (signature, slot) in [("old", 10), ("new", 190)] {
+            storage.mark_processed(signature, slot).await?;
+            sqlx::query("INSERT INTO transfers VALUES (?, ?)")
+                .bind(signature)
+                .bind(to_i64(slot))
+                .execute(&storage.pool)
+                .await?;
+        }
+
+        let config = RetentionConfig {
+            max_age: RetentionAge::Slots(100),
+            action: RetentionAction::Archive,
+            handler_tables: vec![crate::storage::retention::RetentionTable {
+                table: "transfers".to_string(),
+                column: "slot".to_string(),
+            }],
+        };
+        let report = storage.prune(&config, Some(200)).await?;
+        assert_eq!(report.rows(), 2);
+        assert!(!storage.is_processed("old").await?);
+        assert!(storage.is_processed("new").await?);
+
+        let archived: Vec<String> = sqlx::query_scalar("SELECT signature FROM transfers_archive")
+            .fetch_all(&storage.pool)
+            .await?;
+        assert_eq!(archived, vec!["old".to_string()]);
+
+        // Nothing is old enough the second time around
+        assert_eq!(
+            storage.prune(&config, Some(200)).await?,
+            RetentionReport::default()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+//! Materialized latest-state tables for decoded accounts.
+//!
+//! Registering an account decoder with `SolanaIndexer::register_account_state`
+//! keeps a table with one row per account: its pubkey, the decoded state as
+//! `JSONB`, and the slot and signature of the transaction it was read after.
+//! A newer slot replaces the row; an older one is ignored, so out-of-order
+//! backfill pages never overwrite fresher state. With `with_history`, every
+//! decoded state is also appended to `<table>_history`.
+//!
+//! ```sql
+//! SELECT state->>'authority' FROM pools WHERE pubkey = $1;
+//! ```
+
+use crate::types::metadata::TxMetadata;
+use crate::types::schema::{EventSchema, TableSchema};
+use crate::types::traits::{
+    AccountDecoder, DynamicAccountDecoder, DynamicEventHandler, SchemaInitializer,
+};
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use solana_sdk::pubkey::Pubkey;
+use sqlx::PgPool;
+use std::marker::PhantomData;
+
+/// A table holding the latest decoded state of each account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountStateTable {
+    table: String,
+    history: bool,
+}
+
+impl AccountStateTable {
+    /// Keeps the latest state in `table`, created on startup.
+    #[must_use]
+    pub fn new(table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            history: false,
+        }
+    }
+
+    /// Also appends every decoded state to `<table>_history`.
+    #[must_use]
+    pub fn with_history(mut self) -> Self {
+        self.history = true;
+        self
+    }
+
+    /// Returns the name of the latest-state table.
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Returns the name of the history table, if history is kept.
+    #[must_use]
+    pub fn history_table(&self) -> Option<String> {
+        self.history.then(|| format!("{}_history", self.table))
+    }
+
+    /// Discriminator the decoded states are dispatched under, unique to the
+    /// table so several state tables can coexist.
+    #[must_use]
+    pub(crate) fn discriminator(&self) -> [u8; 8] {
+        let hash = Sha256::digest(format!("account_state:{}", self.table).as_bytes());
+        let mut discriminator = [0u8; 8];
+        discriminator.copy_from_slice(&hash[..8]);
+        discriminator
+    }
+
+    /// Checks that the table name can be interpolated into SQL.
+    pub(crate) fn validate(&self) -> Result<()> {
+        let valid = !self.table.is_empty()
+            && self
+                .table
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if valid {
+            Ok(())
+        } else {
+            Err(SolanaIndexerError::Config(format!(
+                "Invalid account state table name '{}'",
+                self.table
+            )))
+        }
+    }
+}
+
+/// Creates the latest-state table, and the history table when one is kept.
+#[async_trait]
+impl SchemaInitializer for AccountStateTable {
+    async fn initialize(&self, pool: &PgPool) -> Result<()> {
+        let table = self.table();
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                pubkey TEXT PRIMARY KEY,
+                slot BIGINT NOT NULL,
+                signature TEXT NOT NULL,
+                state JSONB NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(pool)
+        .await?;
+        if let Some(history) = self.history_table() {
+            sqlx::query(&format!(
+                "CREATE TABLE IF NOT EXISTS {history} (
+                    pubkey TEXT NOT NULL,
+                    slot BIGINT NOT NULL,
+                    signature TEXT NOT NULL,
+                    state JSONB NOT NULL,
+                    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                    PRIMARY KEY (pubkey, slot, signature)
+                )"
+            ))
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+/// A decoded account on its way from the decoder to the table.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+struct StateRow {
+    pubkey: String,
+    state: String,
+}
+
+/// Decodes accounts with `D` and emits them as `StateRow`s.
+pub(crate) struct StateDecoder<D, T> {
+    decoder: D,
+    discriminator: [u8; 8],
+    _state: PhantomData<fn() -> T>,
+}
+
+impl<D, T> StateDecoder<D, T> {
+    pub(crate) fn new(decoder: D, table: &AccountStateTable) -> Self {
+        Self {
+            decoder,
+            discriminator: table.discriminator(),
+            _state: PhantomData,
+        }
+    }
+}
+
+impl<D, T> DynamicAccountDecoder for StateDecoder<D, T>
+where
+    D: AccountDecoder<T>,
+    T: Serialize,
+{
+    fn decode_account_dynamic(
+        &self,
+        pubkey: &Pubkey,
+        account: &solana_sdk::account::Account,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        let state = self.decoder.decode(pubkey, account)?;
+        let row = StateRow {
+            pubkey: pubkey.to_string(),
+            state: serde_json::to_string(&state).ok()?,
+        };
+        Some((self.discriminator, borsh::to_vec(&row).ok()?))
+    }
+
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        Some(self.discriminator)
+    }
+}
+
+/// Writes `StateRow`s into an `AccountStateTable`.
+pub(crate) struct StateHandler {
+    table: AccountStateTable,
+}
+
+impl StateHandler {
+    pub(crate) fn new(table: AccountStateTable) -> Self {
+        Self { table }
+    }
+}
+
+#[async_trait]
+impl DynamicEventHandler for StateHandler {
+    async fn handle_dynamic(
+        &self,
+        _discriminator: &[u8; 8],
+        data: &[u8],
+        context: &TxMetadata,
+        db: &PgPool,
+    ) -> Result<()> {
+        let row = StateRow::try_from_slice(data).map_err(|e| {
+            SolanaIndexerError::Decode(format!("Failed to deserialize account state: {e}"))
+        })?;
+        let table = self.table.table();
+        let slot = i64::try_from(context.slot).unwrap_or(i64::MAX);
+
+        let mut tx = db.begin().await?;
+        sqlx::query(&format!(
+            "INSERT INTO {table} (pubkey, slot, signature, state, updated_at)
+             VALUES ($1, $2, $3, $4::jsonb, NOW())
+             ON CONFLICT (pubkey) DO UPDATE
+             SET slot = EXCLUDED.slot, signature = EXCLUDED.signature,
+                 state = EXCLUDED.state, updated_at = NOW()
+             WHERE {table}.slot <= EXCLUDED.slot"
+        ))
+        .bind(&row.pubkey)
+        .bind(slot)
+        .bind(&context.signature)
+        .bind(&row.state)
+        .execute(&mut *tx)
+        .await?;
+        if let Some(history) = self.table.history_table() {
+            sqlx::query(&format!(
+                "INSERT INTO {history} (pubkey, slot, signature, state)
+                 VALUES ($1, $2, $3, $4::jsonb)
+                 ON CONFLICT DO NOTHING"
+            ))
+            .bind(&row.pubkey)
+            .bind(slot)
+            .bind(&context.signature)
+            .bind(&row.state)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drops the states written by the rolled-back transaction. With
+    /// history, each affected account falls back to its latest remaining
+    /// state.
+    async fn handle_rollback_dynamic(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
+        let table = self.table.table();
+        let mut tx = db.begin().await?;
+        let pubkeys: Vec<String> = sqlx::query_scalar(&format!(
+            "DELETE FROM {table} WHERE signature = $1 RETURNING pubkey"
+        ))
+        .bind(&context.signature)
+        .fetch_all(&mut *tx)
+        .await?;
+        if let Some(history) = self.table.history_table() {
+            sqlx::query(&format!("DELETE FROM {history} WHERE signature = $1"))
+                .bind(&context.signature)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(&format!(
+                "INSERT INTO {table} (pubkey, slot, signature, state, updated_at)
+                 SELECT DISTINCT ON (pubkey) pubkey, slot, signature, state, NOW()
+                 FROM {history} WHERE pubkey = ANY($1)
+                 ORDER BY pubkey, slot DESC"
+            ))
+            .bind(&pubkeys)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
+        self.table.initialize(pool).await
+    }
+
+    fn event_schema(&self) -> Option<EventSchema> {
+        let columns = |table: TableSchema| {
+            table
+                .with_column("pubkey", "TEXT", "Account address")
+                .with_column("slot", "BIGINT", "Slot the state was read at")
+                .with_column("signature", "TEXT", "Transaction that wrote the account")
+                .with_column("state", "JSONB", "Decoded account state")
+        };
+        let mut tables = vec![columns(
+            TableSchema::new(self.table.table())
+                .with_description("Latest decoded state of each account"),
+        )];
+        if let Some(history) = self.table.history_table() {
+            tables.push(columns(
+                TableSchema::new(history).with_description("Every decoded state of each account"),
+            ));
+        }
+        Some(EventSchema {
+            event: format!("AccountState({})", self.table.table()),
+            discriminator: self.table.discriminator(),
+            tables,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::Account;
+
+    #[derive(Serialize)]
+    struct Pool {
+        liquidity: u64,
+    }
+
+    struct PoolDecoder;
+
+    impl AccountDecoder<Pool> for PoolDecoder {
+        fn decode(&self, _: &Pubkey, account: &Account) -> Option<Pool> {
+            Some(Pool {
+                liquidity: account.lamports,
+            })
+        }
+    }
+
+    #[test]
+    fn test_state_decoder_emits_json_row() {
+        let table = AccountStateTable::new("pools");
+        let decoder = StateDecoder::new(PoolDecoder, &table);
+        let pubkey = Pubkey::new_unique();
+        let account = Account {
+            lamports: 42,
+            ..Account::default()
+        };
+
+        let (discriminator, data) = decoder.decode_account_dynamic(&pubkey, &account).unwrap();
+
+        assert_eq!(discriminator, table.discriminator());
+        assert_ne!(
+            discriminator,
+            AccountStateTable::new("vaults").discriminator()
+        );
+        let row = StateRow::try_from_slice(&data).unwrap();
+        assert_eq!(row.pubkey, pubkey.to_string());
+        assert_eq!(row.state, r#"{"liquidity":42}"#);
+    }
+
+    #[test]
+    fn test_table_names() {
+        let table = AccountStateTable::new("pools").with_history();
+        assert_eq!(table.history_table().as_deref(), Some("pools_history"));
+        assert!(table.validate().is_ok());
+        assert!(AccountStateTable::new("pools; DROP").validate().is_err());
+    }
+}
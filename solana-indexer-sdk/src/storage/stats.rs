@@ -0,0 +1,260 @@
+//! Periodically refreshed row counts of the handler tables.
+//!
+//! Dashboards often show how many rows each indexed table holds and how far
+//! it has caught up. Running `COUNT(*)` for every dashboard refresh scans
+//! the production tables over and over; with table stats enabled the indexer
+//! counts them once per interval instead, and `TableStats` serves the latest
+//! counts from memory.
+//!
+//! The tables are those the handlers declare with
+//! `EventHandler::output_schema`. A table with a `slot` column also gets the
+//! highest slot it holds. All tables are counted in a single read-only
+//! `REPEATABLE READ` transaction, so the counts of one refresh come from the
+//! same snapshot and agree with each other.
+
+use super::retention::is_identifier;
+use crate::types::schema::DataDictionary;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Name of the column holding the slot of each row.
+const SLOT_COLUMN: &str = "slot";
+
+/// Counts of one table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableCount {
+    /// The table counted
+    pub table: String,
+    /// Rows in the table
+    pub rows: u64,
+    /// Highest value of the table's `slot` column, if it has one and rows
+    pub max_slot: Option<u64>,
+}
+
+/// Counts of every table, taken from one database snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStatsSnapshot {
+    /// Counts per table, sorted by table name
+    pub tables: Vec<TableCount>,
+    /// When the counts were taken
+    pub taken_at: DateTime<Utc>,
+}
+
+impl TableStatsSnapshot {
+    /// Returns the counts of `table`.
+    #[must_use]
+    pub fn table(&self, table: &str) -> Option<&TableCount> {
+        self.tables.iter().find(|count| count.table == table)
+    }
+}
+
+/// Latest counts of the handler tables, refreshed by the indexer.
+///
+/// Obtained with `SolanaIndexer::table_stats`; empty until the first
+/// refresh.
+#[derive(Debug, Default)]
+pub struct TableStats {
+    latest: RwLock<Option<Arc<TableStatsSnapshot>>>,
+}
+
+impl TableStats {
+    /// Creates empty stats.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the latest snapshot, if a refresh has completed.
+    #[must_use]
+    pub fn latest(&self) -> Option<Arc<TableStatsSnapshot>> {
+        self.latest.read().ok()?.clone()
+    }
+
+    /// Returns the latest counts of `table`.
+    #[must_use]
+    pub fn table(&self, table: &str) -> Option<TableCount> {
+        self.latest()?.table(table).cloned()
+    }
+
+    /// Replaces the latest snapshot.
+    pub fn record(&self, snapshot: TableStatsSnapshot) {
+        if let Ok(mut latest) = self.latest.write() {
+            *latest = Some(Arc::new(snapshot));
+        }
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        let Some(snapshot) = self.latest() else {
+            return;
+        };
+        for count in &snapshot.tables {
+            let max_slot = count
+                .max_slot
+                .map_or_else(|| "-".to_string(), |slot| slot.to_string());
+            logging::log(
+                logging::LogLevel::Info,
+                &format!(
+                    "Table Stats: {} | Rows: {} | Max slot: {} | As of: {}",
+                    count.table,
+                    count.rows,
+                    max_slot,
+                    snapshot.taken_at.to_rfc3339()
+                ),
+            );
+        }
+    }
+}
+
+/// Returns the tables declared in `dictionary`, mapped to whether they have
+/// a `slot` column. Names that are not plain SQL identifiers are skipped.
+pub(crate) fn counted_tables(dictionary: &DataDictionary) -> BTreeMap<String, bool> {
+    let mut tables = BTreeMap::new();
+    for table in dictionary.events.iter().flat_map(|event| &event.tables) {
+        if !is_identifier(&table.name) {
+            continue;
+        }
+        let has_slot = table.columns.iter().any(|c| c.name == SLOT_COLUMN);
+        *tables.entry(table.name.clone()).or_insert(false) |= has_slot;
+    }
+    tables
+}
+
+/// Returns the query counting `table`.
+fn count_query(table: &str, has_slot: bool) -> String {
+    if has_slot {
+        format!("SELECT COUNT(*), MAX({SLOT_COLUMN})::BIGINT FROM {table}")
+    } else {
+        format!("SELECT COUNT(*), NULL::BIGINT FROM {table}")
+    }
+}
+
+/// Counts `tables` in one snapshot. Tables that do not exist are skipped.
+pub(crate) async fn collect(
+    pool: &PgPool,
+    tables: &BTreeMap<String, bool>,
+) -> Result<TableStatsSnapshot> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+    let taken_at = Utc::now();
+
+    let mut counts = Vec::with_capacity(tables.len());
+    for (table, has_slot) in tables {
+        let exists = sqlx::query_scalar::<_, bool>("SELECT to_regclass($1) IS NOT NULL")
+            .bind(table)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !exists {
+            continue;
+        }
+        let (rows, max_slot) =
+            sqlx::query_as::<_, (i64, Option<i64>)>(&count_query(table, *has_slot))
+                .fetch_one(&mut *tx)
+                .await?;
+        counts.push(TableCount {
+            table: table.clone(),
+            rows: u64::try_from(rows).unwrap_or(0),
+            max_slot: max_slot.and_then(|slot| u64::try_from(slot).ok()),
+        });
+    }
+    tx.commit().await?;
+
+    Ok(TableStatsSnapshot {
+        tables: counts,
+        taken_at,
+    })
+}
+
+/// Refreshes `stats` every `interval` until `cancellation_token` fires.
+pub(crate) fn spawn_table_stats(
+    pool: PgPool,
+    tables: BTreeMap<String, bool>,
+    interval: Duration,
+    stats: Arc<TableStats>,
+    cancellation_token: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while !cancellation_token.is_cancelled() {
+            match collect(&pool, &tables).await {
+                Ok(snapshot) => stats.record(snapshot),
+                Err(e) => logging::log_error("Table stats error", &e.to_string()),
+            }
+            tokio::select! {
+                () = cancellation_token.cancelled() => break,
+                () = tokio::time::sleep(interval) => {}
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::schema::{EventSchema, TableSchema};
+
+    #[test]
+    fn test_counted_tables() {
+        let event = |name: &str, tables: Vec<TableSchema>| EventSchema {
+            event: name.to_string(),
+            discriminator: [0; 8],
+            tables,
+        };
+        let dictionary = DataDictionary {
+            events: vec![
+                event(
+                    "TransferEvent",
+                    vec![
+                        TableSchema::new("transfers").with_column("slot", "BIGINT", "Slot"),
+                        TableSchema::new("balances"),
+                    ],
+                ),
+                event(
+                    "DepositEvent",
+                    vec![
+                        TableSchema::new("balances"),
+                        TableSchema::new("bad name; DROP TABLE x"),
+                    ],
+                ),
+            ],
+        };
+
+        let tables = counted_tables(&dictionary);
+        assert_eq!(
+            tables.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("balances".to_string(), false),
+                ("transfers".to_string(), true)
+            ]
+        );
+        assert_eq!(
+            count_query("transfers", true),
+            "SELECT COUNT(*), MAX(slot)::BIGINT FROM transfers"
+        );
+    }
+
+    #[test]
+    fn test_latest_snapshot() {
+        let stats = TableStats::new();
+        assert!(stats.latest().is_none());
+
+        stats.record(TableStatsSnapshot {
+            tables: vec![TableCount {
+                table: "transfers".to_string(),
+                rows: 42,
+                max_slot: Some(1_000),
+            }],
+            taken_at: Utc::now(),
+        });
+        assert_eq!(stats.table("transfers").map(|t| t.rows), Some(42));
+        assert!(stats.table("balances").is_none());
+    }
+}
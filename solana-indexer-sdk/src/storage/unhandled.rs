@@ -0,0 +1,177 @@
+//! Fallback storage for decoded events without a handler.
+//!
+//! A decoder can be registered before the handler for its events is
+//! written. Without a fallback those events fail their transaction; with
+//! `with_unhandled_event_storage` they are written to
+//! `_solana_indexer_sdk_unhandled_events` instead, one row per event, and
+//! the transaction is processed as usual.
+//!
+//! Borsh data does not describe itself, so each row keeps the raw event
+//! bytes. Event types made known with `UnhandledEvents::describe` are also
+//! decoded into a `JSONB` payload and named, so the rows can be queried
+//! directly and replayed once their handler exists.
+
+use super::discriminator_hex;
+use crate::types::events::EventDiscriminator;
+use crate::types::metadata::TxMetadata;
+use crate::types::traits::SchemaInitializer;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use borsh::BorshDeserialize;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+/// Name of the fallback table.
+pub(crate) const UNHANDLED_TABLE: &str = "_solana_indexer_sdk_unhandled_events";
+
+/// Decodes the raw bytes of one event type into JSON.
+type Render = fn(&[u8]) -> Option<serde_json::Value>;
+
+fn render<E: BorshDeserialize + Serialize>(data: &[u8]) -> Option<serde_json::Value> {
+    let event = E::try_from_slice(data).ok()?;
+    serde_json::to_value(event).ok()
+}
+
+/// Writes decoded events that have no handler to a generic table.
+#[derive(Default)]
+pub struct UnhandledEvents {
+    /// Name and JSON rendering of each described event type
+    types: RwLock<HashMap<[u8; 8], (String, Render)>>,
+    stored: AtomicU64,
+}
+
+impl UnhandledEvents {
+    /// Creates a fallback with no described event types.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores unhandled `E` events with their fields as JSON and their type
+    /// name, rather than only their raw bytes.
+    pub fn describe<E>(&self)
+    where
+        E: EventDiscriminator + BorshDeserialize + Serialize + 'static,
+    {
+        let name = std::any::type_name::<E>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        self.types
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(E::discriminator(), (name, render::<E>));
+    }
+
+    /// Returns the number of events stored since startup.
+    #[must_use]
+    pub fn stored(&self) -> u64 {
+        self.stored.load(Ordering::Relaxed)
+    }
+
+    /// Writes one unhandled event.
+    pub(crate) async fn store(
+        &self,
+        discriminator: &[u8; 8],
+        data: &[u8],
+        context: &TxMetadata,
+        db: &PgPool,
+    ) -> Result<()> {
+        let (event, payload) = self.describe_event(discriminator, data);
+        sqlx::query(&format!(
+            "INSERT INTO {UNHANDLED_TABLE}
+                (signature, slot, discriminator, event, payload, data)
+             VALUES ($1, $2, $3, $4, $5, $6)"
+        ))
+        .bind(&context.signature)
+        .bind(i64::try_from(context.slot).unwrap_or(i64::MAX))
+        .bind(discriminator_hex(Some(*discriminator)))
+        .bind(event)
+        .bind(payload)
+        .bind(data)
+        .execute(db)
+        .await?;
+        self.stored.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Returns the type name and JSON form of an event, when its type was
+    /// described and the data decodes.
+    fn describe_event(
+        &self,
+        discriminator: &[u8; 8],
+        data: &[u8],
+    ) -> (Option<String>, Option<serde_json::Value>) {
+        let types = self
+            .types
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match types.get(discriminator) {
+            Some((name, render)) => (Some(name.clone()), render(data)),
+            None => (None, None),
+        }
+    }
+}
+
+/// Creates the fallback table.
+#[async_trait]
+impl SchemaInitializer for UnhandledEvents {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {UNHANDLED_TABLE} (
+                id BIGSERIAL PRIMARY KEY,
+                signature TEXT NOT NULL,
+                slot BIGINT NOT NULL,
+                discriminator TEXT NOT NULL,
+                event TEXT,
+                payload JSONB,
+                data BYTEA NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )"
+        ))
+        .execute(db)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_unhandled_events_discriminator
+             ON {UNHANDLED_TABLE}(discriminator, slot)"
+        ))
+        .execute(db)
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::events::TransferEvent;
+
+    #[test]
+    fn test_described_events_render_as_json() {
+        let unhandled = UnhandledEvents::new();
+        let event = TransferEvent {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            amount: 7,
+        };
+        let data = borsh::to_vec(&event).unwrap();
+        let discriminator = TransferEvent::discriminator();
+
+        assert_eq!(
+            unhandled.describe_event(&discriminator, &data),
+            (None, None)
+        );
+
+        unhandled.describe::<TransferEvent>();
+        let (name, payload) = unhandled.describe_event(&discriminator, &data);
+        assert_eq!(name.as_deref(), Some("TransferEvent"));
+        assert_eq!(payload.unwrap()["amount"], 7);
+
+        let (_, payload) = unhandled.describe_event(&discriminator, &[1, 2]);
+        assert!(payload.is_none());
+    }
+}
@@ -0,0 +1,361 @@
+//! Scheduled `VACUUM` and `ANALYZE` of the SDK tables.
+//!
+//! Tables such as `_solana_indexer_sdk_processed` take a steady stream of
+//! inserts, updates and, under a retention policy, deletes. Autovacuum tends
+//! to fall behind on them, leaving dead rows that bloat the table and stale
+//! planner statistics that slow down lookups. A vacuum policy runs
+//! `VACUUM (ANALYZE)`, or only `ANALYZE`, on every `_solana_indexer_sdk_*`
+//! table and the listed handler tables at cron times of your choosing, such
+//! as a nightly low-traffic window, followed by any maintenance statements
+//! of your own.
+//!
+//! Each run measures the live rows, dead rows and on-disk size of the tables
+//! before cleaning them, so `VacuumMetrics` shows how much bloat builds up
+//! between runs.
+
+use super::retention::is_identifier;
+use super::{Storage, StorageBackend};
+use crate::core::execution::schedule::CronSchedule;
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::logging;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+/// Prefix of every table the SDK creates.
+const SDK_TABLE_PATTERN: &str = "\\_solana\\_indexer\\_sdk\\_%";
+
+/// How thoroughly tables are cleaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum VacuumMode {
+    /// `VACUUM (ANALYZE)`: reclaim dead rows and refresh statistics
+    #[default]
+    VacuumAnalyze,
+    /// `ANALYZE`: only refresh statistics
+    Analyze,
+}
+
+/// Vacuum settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VacuumConfig {
+    /// Cron expression, in UTC, of the runs (default: `0 3 * * *`)
+    pub schedule: String,
+    /// What is run on each table (default: `VacuumMode::VacuumAnalyze`)
+    pub mode: VacuumMode,
+    /// Handler tables cleaned alongside the SDK tables, optionally
+    /// schema-qualified
+    pub handler_tables: Vec<String>,
+    /// SQL statements run after the tables are cleaned, in order
+    pub statements: Vec<String>,
+}
+
+impl Default for VacuumConfig {
+    fn default() -> Self {
+        Self {
+            schedule: "0 3 * * *".to_string(),
+            mode: VacuumMode::default(),
+            handler_tables: Vec::new(),
+            statements: Vec::new(),
+        }
+    }
+}
+
+impl VacuumConfig {
+    /// Validates the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if the schedule does not
+    /// parse or a table name is not a plain SQL identifier.
+    pub fn validate(&self) -> Result<()> {
+        self.cron()?;
+        for table in &self.handler_tables {
+            if !is_identifier(table) {
+                return Err(SolanaIndexerError::Config(format!(
+                    "Invalid vacuum table name: {table:?}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the schedule.
+    pub(crate) fn cron(&self) -> Result<CronSchedule> {
+        self.schedule.parse()
+    }
+
+    /// Returns the statement cleaning `table`.
+    pub(crate) fn command(&self, table: &str) -> String {
+        match self.mode {
+            VacuumMode::VacuumAnalyze => format!("VACUUM (ANALYZE) {table}"),
+            VacuumMode::Analyze => format!("ANALYZE {table}"),
+        }
+    }
+}
+
+/// Size and bloat of one table, measured before it was cleaned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableBloat {
+    /// The table cleaned
+    pub table: String,
+    /// Estimated live rows
+    pub live_rows: u64,
+    /// Estimated dead rows awaiting `VACUUM`
+    pub dead_rows: u64,
+    /// Size on disk, including indexes and TOAST, in bytes
+    pub bytes: u64,
+}
+
+impl TableBloat {
+    /// Returns the share of the table's rows that are dead, from 0 to 1.
+    #[must_use]
+    pub fn dead_ratio(&self) -> f64 {
+        let total = self.live_rows + self.dead_rows;
+        if total == 0 {
+            0.0
+        } else {
+            self.dead_rows as f64 / total as f64
+        }
+    }
+}
+
+/// Outcome of one vacuum run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VacuumReport {
+    /// Tables cleaned
+    pub tables: Vec<TableBloat>,
+    /// Maintenance statements run
+    pub statements: u64,
+}
+
+impl VacuumReport {
+    /// Returns the live rows across all tables.
+    #[must_use]
+    pub fn live_rows(&self) -> u64 {
+        self.tables.iter().map(|t| t.live_rows).sum()
+    }
+
+    /// Returns the dead rows across all tables.
+    #[must_use]
+    pub fn dead_rows(&self) -> u64 {
+        self.tables.iter().map(|t| t.dead_rows).sum()
+    }
+
+    /// Returns the size of all tables, in bytes.
+    #[must_use]
+    pub fn bytes(&self) -> u64 {
+        self.tables.iter().map(|t| t.bytes).sum()
+    }
+}
+
+/// Totals of the vacuum task, and the bloat found by its last run.
+#[derive(Debug, Default)]
+pub struct VacuumMetrics {
+    /// Completed vacuum runs
+    pub runs: AtomicU64,
+    /// Dead rows found across all runs
+    pub dead_rows_cleaned: AtomicU64,
+    /// Live rows in the tables at the last run
+    pub live_rows: AtomicU64,
+    /// Dead rows in the tables at the last run
+    pub dead_rows: AtomicU64,
+    /// Size of the tables at the last run, in bytes
+    pub bytes: AtomicU64,
+}
+
+impl VacuumMetrics {
+    /// Creates zeroed metrics.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a run's outcome to the totals.
+    pub fn record(&self, report: &VacuumReport) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.dead_rows_cleaned
+            .fetch_add(report.dead_rows(), Ordering::Relaxed);
+        self.live_rows.store(report.live_rows(), Ordering::Relaxed);
+        self.dead_rows.store(report.dead_rows(), Ordering::Relaxed);
+        self.bytes.store(report.bytes(), Ordering::Relaxed);
+    }
+
+    /// Reports current metrics to logs.
+    pub fn report(&self) {
+        let live = self.live_rows.load(Ordering::Relaxed);
+        let dead = self.dead_rows.load(Ordering::Relaxed);
+        let bloat = if live + dead == 0 {
+            0.0
+        } else {
+            dead as f64 * 100.0 / (live + dead) as f64
+        };
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Vacuum Stats: Runs: {} | Dead rows cleaned: {} | Last run: {} live, {} dead ({:.1}% bloat), {} bytes",
+                self.runs.load(Ordering::Relaxed),
+                self.dead_rows_cleaned.load(Ordering::Relaxed),
+                live,
+                dead,
+                bloat,
+                self.bytes.load(Ordering::Relaxed)
+            ),
+        );
+    }
+}
+
+impl Storage {
+    /// Cleans the SDK tables of the current schema and the configured
+    /// handler tables, then runs the configured statements.
+    ///
+    /// `VACUUM` cannot run inside a transaction, so each table is cleaned
+    /// with its own statement. Handler tables that do not exist are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a table cannot be
+    /// cleaned or a statement fails. Work done before it is kept.
+    pub async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        let mut tables: Vec<String> = sqlx::query_scalar(
+            "SELECT relname::TEXT FROM pg_stat_user_tables
+             WHERE schemaname = current_schema() AND relname LIKE $1
+             ORDER BY relname",
+        )
+        .bind(SDK_TABLE_PATTERN)
+        .fetch_all(&self.pool)
+        .await?;
+        tables.extend(config.handler_tables.iter().cloned());
+
+        let mut report = VacuumReport::default();
+        for table in tables {
+            let stats = sqlx::query_as::<_, (i64, i64, i64)>(
+                "SELECT n_live_tup, n_dead_tup, pg_total_relation_size(relid)
+                 FROM pg_stat_user_tables WHERE relid = to_regclass($1)",
+            )
+            .bind(&table)
+            .fetch_optional(&self.pool)
+            .await?;
+            let Some((live_rows, dead_rows, bytes)) = stats else {
+                continue;
+            };
+
+            sqlx::query(&config.command(&table))
+                .execute(&self.pool)
+                .await?;
+            report.tables.push(TableBloat {
+                table,
+                live_rows: u64::try_from(live_rows).unwrap_or(0),
+                dead_rows: u64::try_from(dead_rows).unwrap_or(0),
+                bytes: u64::try_from(bytes).unwrap_or(0),
+            });
+        }
+
+        for statement in &config.statements {
+            sqlx::query(statement).execute(&self.pool).await?;
+            report.statements += 1;
+        }
+        Ok(report)
+    }
+}
+
+/// Applies `config` at every scheduled time until `cancellation_token`
+/// fires.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Config` if the schedule does not parse.
+pub fn spawn_vacuum(
+    storage: Arc<dyn StorageBackend>,
+    config: VacuumConfig,
+    metrics: Arc<VacuumMetrics>,
+    cancellation_token: CancellationToken,
+) -> Result<tokio::task::JoinHandle<()>> {
+    let schedule = config.cron()?;
+    Ok(tokio::spawn(async move {
+        while let Some(next) = schedule.next_after(Utc::now()) {
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                () = cancellation_token.cancelled() => break,
+                () = tokio::time::sleep(wait) => {}
+            }
+
+            match storage.vacuum(&config).await {
+                Ok(report) => {
+                    for table in &report.tables {
+                        logging::log(
+                            logging::LogLevel::Info,
+                            &format!(
+                                "Vacuumed {}: {} live rows, {} dead rows ({:.1}% bloat), {} bytes",
+                                table.table,
+                                table.live_rows,
+                                table.dead_rows,
+                                table.dead_ratio() * 100.0,
+                                table.bytes
+                            ),
+                        );
+                    }
+                    metrics.record(&report);
+                }
+                Err(e) => logging::log_error("Vacuum error", &e.to_string()),
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut config = VacuumConfig {
+            handler_tables: vec!["public.transfers".to_string()],
+            ..VacuumConfig::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.schedule = "every night".to_string();
+        assert!(config.validate().is_err());
+
+        config.schedule = "@daily".to_string();
+        config
+            .handler_tables
+            .push("transfers; DROP TABLE x".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_command() {
+        let mut config = VacuumConfig::default();
+        assert_eq!(config.command("transfers"), "VACUUM (ANALYZE) transfers");
+        config.mode = VacuumMode::Analyze;
+        assert_eq!(config.command("transfers"), "ANALYZE transfers");
+    }
+
+    #[test]
+    fn test_metrics_keep_last_run() {
+        let metrics = VacuumMetrics::new();
+        let table = |dead_rows| TableBloat {
+            table: "a".to_string(),
+            live_rows: 300,
+            dead_rows,
+            bytes: 8192,
+        };
+        assert!((table(100).dead_ratio() - 0.25).abs() < f64::EPSILON);
+
+        metrics.record(&VacuumReport {
+            tables: vec![table(100), table(20)],
+            statements: 0,
+        });
+        metrics.record(&VacuumReport {
+            tables: vec![table(10)],
+            statements: 1,
+        });
+        assert_eq!(metrics.runs.load(Ordering::Relaxed), 2);
+        assert_eq!(metrics.dead_rows_cleaned.load(Ordering::Relaxed), 130);
+        assert_eq!(metrics.dead_rows.load(Ordering::Relaxed), 10);
+        assert_eq!(metrics.live_rows.load(Ordering::Relaxed), 300);
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::config::SolanaIndexerConfig;
 use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::network::{connect_websocket, NetworkConfig};
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
@@ -16,7 +17,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time::sleep;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+use tokio_tungstenite::tungstenite::protocol::Message;
 
 use super::TransactionSource;
 
@@ -30,22 +31,31 @@ impl HeliusSource {
     pub async fn new(config: SolanaIndexerConfig) -> Result<Self> {
         let ws_url = config
             .helius_ws_url()
-            .ok_or_else(|| {
-                SolanaIndexerError::ConfigError("Helius WS URL not configured".to_string())
-            })?
+            .ok_or_else(|| SolanaIndexerError::Config("Helius WS URL not configured".to_string()))?
             .to_string();
 
         if !matches!(config.source, crate::config::SourceConfig::Helius { .. }) {
-            return Err(SolanaIndexerError::ConfigError(
+            return Err(SolanaIndexerError::Config(
                 "Not a Helius config".to_string(),
             ));
         }
 
-        let program_ids = config.program_ids.iter().map(|p| p.to_string()).collect();
+        let program_ids = config
+            .watched_addresses()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
         let (sender, receiver) = mpsc::channel(1000); // Buffer size
 
         // Spawn background task to handle WS connection
-        tokio::spawn(Self::run_stream(ws_url, program_ids, sender));
+        tokio::spawn(Self::run_stream(
+            ws_url,
+            program_ids,
+            config.skip_failed_transactions,
+            config.skip_vote_transactions,
+            config.network.clone(),
+            sender,
+        ));
 
         Ok(Self { receiver })
     }
@@ -53,12 +63,15 @@ impl HeliusSource {
     async fn run_stream(
         ws_url: String,
         program_ids: Vec<String>,
+        skip_failed: bool,
+        skip_vote: bool,
+        network: NetworkConfig,
         sender: mpsc::Sender<crate::streams::TransactionEvent>,
     ) {
         loop {
             tracing::info!("Connecting to Helius WS: {}", ws_url);
-            match connect_async(&ws_url).await {
-                Ok((ws_stream, _)) => {
+            match connect_websocket(&ws_url, &network).await {
+                Ok(ws_stream) => {
                     tracing::info!("Connected to Helius WS");
                     let (mut write, mut read) = ws_stream.split();
 
@@ -70,7 +83,9 @@ impl HeliusSource {
                         "method": "transactionSubscribe",
                         "params": [
                             {
-                                "accountInclude": program_ids
+                                "accountInclude": program_ids,
+                                "failed": !skip_failed,
+                                "vote": !skip_vote
                             },
                             {
                                 "commitment": "confirmed",
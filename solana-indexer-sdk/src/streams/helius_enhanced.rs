@@ -0,0 +1,482 @@
+//! Helius Enhanced Transactions API client.
+//!
+//! Helius parses transactions of common protocols (NFT marketplaces, DEX
+//! aggregators, token transfers) server-side. `HeliusEnhancedClient` fetches
+//! those parsed transactions and `EnhancedTransaction::parsed_events` maps them
+//! into `ParsedEvent`s carrying Borsh-encoded SDK event types:
+//!
+//! - `HeliusNativeTransfer` - one per SOL transfer
+//! - `HeliusTokenTransfer` - one per SPL token transfer
+//! - `HeliusNftSale` - NFT sales reported by Helius
+//! - `HeliusSwap` - swaps reported by Helius
+//!
+//! When enabled with `with_helius_enhanced_transactions`, the indexer routes
+//! these events straight to handlers registered for the types above, so no
+//! decoder has to be written for the covered protocols.
+
+use crate::types::events::{calculate_discriminator, EventDiscriminator, EventType, ParsedEvent};
+use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::network::HttpTransport;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::signature::Signature;
+use tokio::sync::OnceCell;
+
+/// Maximum number of signatures per request accepted by the API.
+const MAX_SIGNATURES_PER_REQUEST: usize = 100;
+
+/// Client for the Helius `/v0/transactions` endpoint.
+#[derive(Debug, Clone)]
+pub struct HeliusEnhancedClient {
+    transport: HttpTransport,
+    /// Built from `transport` on the first request.
+    http: OnceCell<reqwest::Client>,
+    /// Endpoint URL including the `api-key` query parameter.
+    url: String,
+}
+
+impl HeliusEnhancedClient {
+    /// Creates a client for `url` (e.g. `https://api.helius.xyz/v0/transactions?api-key=...`).
+    #[must_use]
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            transport: HttpTransport::default(),
+            http: OnceCell::new(),
+            url: url.into(),
+        }
+    }
+
+    /// Sends requests through `transport` (proxy and extra TLS roots).
+    #[must_use]
+    pub fn with_transport(mut self, transport: HttpTransport) -> Self {
+        self.transport = transport;
+        self.http = OnceCell::new();
+        self
+    }
+
+    /// Fetches the enhanced representation of `signatures`.
+    ///
+    /// Requests are split into chunks of 100 signatures. Transactions Helius
+    /// cannot parse are omitted from the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Rpc` if a request fails or returns a
+    /// non-success status, or `SolanaIndexerError::Decode` if the
+    /// response is not valid.
+    pub async fn fetch_transactions(
+        &self,
+        signatures: &[Signature],
+    ) -> Result<Vec<EnhancedTransaction>> {
+        let http = self
+            .http
+            .get_or_try_init(|| async { self.transport.http_client() })
+            .await?;
+
+        let mut transactions = Vec::with_capacity(signatures.len());
+        for chunk in signatures.chunks(MAX_SIGNATURES_PER_REQUEST) {
+            let body = json!({
+                "transactions": chunk.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            });
+            let response = http
+                .post(&self.url)
+                .json(&body)
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| {
+                    SolanaIndexerError::rpc(format!("Helius enhanced request failed: {e}"))
+                })?;
+            let mut page: Vec<EnhancedTransaction> = response.json().await.map_err(|e| {
+                SolanaIndexerError::Decode(format!("Invalid Helius enhanced response: {e}"))
+            })?;
+            transactions.append(&mut page);
+        }
+        Ok(transactions)
+    }
+}
+
+/// A transaction as returned by the Helius Enhanced Transactions API.
+///
+/// Only the fields the SDK maps into events are modeled.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnhancedTransaction {
+    pub signature: String,
+    pub slot: u64,
+    /// Helius transaction type (e.g. `NFT_SALE`, `SWAP`, `TRANSFER`)
+    #[serde(rename = "type", default)]
+    pub transaction_type: String,
+    /// Protocol that produced the transaction (e.g. `MAGIC_EDEN`, `JUPITER`)
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub fee_payer: String,
+    #[serde(default)]
+    pub native_transfers: Vec<NativeTransfer>,
+    #[serde(default)]
+    pub token_transfers: Vec<TokenTransfer>,
+    #[serde(default)]
+    pub events: EnhancedEvents,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NativeTransfer {
+    #[serde(default)]
+    pub from_user_account: String,
+    #[serde(default)]
+    pub to_user_account: String,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenTransfer {
+    #[serde(default)]
+    pub from_user_account: String,
+    #[serde(default)]
+    pub to_user_account: String,
+    pub mint: String,
+    /// UI amount (already divided by the mint decimals)
+    pub token_amount: f64,
+}
+
+/// Protocol events Helius extracted from the transaction.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EnhancedEvents {
+    pub nft: Option<NftEvent>,
+    pub swap: Option<SwapEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NftEvent {
+    #[serde(rename = "type", default)]
+    pub event_type: String,
+    #[serde(default)]
+    pub source: String,
+    /// Price in lamports
+    #[serde(default)]
+    pub amount: u64,
+    #[serde(default)]
+    pub buyer: String,
+    #[serde(default)]
+    pub seller: String,
+    #[serde(default)]
+    pub nfts: Vec<NftToken>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NftToken {
+    pub mint: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapEvent {
+    pub native_input: Option<NativeAmount>,
+    pub native_output: Option<NativeAmount>,
+    #[serde(default)]
+    pub token_inputs: Vec<SwapTokenAmount>,
+    #[serde(default)]
+    pub token_outputs: Vec<SwapTokenAmount>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NativeAmount {
+    pub account: String,
+    /// Lamports, encoded as a string by the API
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapTokenAmount {
+    pub mint: String,
+    pub raw_token_amount: RawTokenAmount,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTokenAmount {
+    pub token_amount: String,
+    pub decimals: u8,
+}
+
+/// SOL transfer reported by Helius.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct HeliusNativeTransfer {
+    pub from: String,
+    pub to: String,
+    pub lamports: u64,
+}
+
+/// SPL token transfer reported by Helius.
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct HeliusTokenTransfer {
+    pub from: String,
+    pub to: String,
+    pub mint: String,
+    /// UI amount (already divided by the mint decimals)
+    pub amount: f64,
+}
+
+/// NFT sale reported by Helius.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct HeliusNftSale {
+    /// Marketplace (e.g. `MAGIC_EDEN`)
+    pub marketplace: String,
+    pub buyer: String,
+    pub seller: String,
+    /// Price in lamports
+    pub lamports: u64,
+    pub mints: Vec<String>,
+}
+
+/// One side of a swap.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct HeliusSwapAmount {
+    /// Token mint, or the native mint for SOL
+    pub mint: String,
+    /// Raw amount in base units
+    pub amount: String,
+    pub decimals: u8,
+}
+
+/// Swap reported by Helius.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct HeliusSwap {
+    /// Protocol that executed the swap (e.g. `JUPITER`)
+    pub protocol: String,
+    /// Wallet that initiated the swap
+    pub user: String,
+    pub inputs: Vec<HeliusSwapAmount>,
+    pub outputs: Vec<HeliusSwapAmount>,
+}
+
+macro_rules! helius_discriminator {
+    ($($ty:ident),*) => {
+        $(impl EventDiscriminator for $ty {
+            fn discriminator() -> [u8; 8] {
+                calculate_discriminator(stringify!($ty))
+            }
+        })*
+    };
+}
+
+helius_discriminator!(
+    HeliusNativeTransfer,
+    HeliusTokenTransfer,
+    HeliusNftSale,
+    HeliusSwap
+);
+
+const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
+
+impl EnhancedTransaction {
+    /// Maps the Helius-parsed events of this transaction into `ParsedEvent`s.
+    ///
+    /// Each event is a `ProgramData` event whose data is the base64-encoded
+    /// discriminator followed by the Borsh-encoded event, the same layout as
+    /// an Anchor `Program data:` log, so `decode_event` can split it for the
+    /// handler registry.
+    #[must_use]
+    pub fn parsed_events(&self) -> Vec<ParsedEvent> {
+        let mut events = Vec::new();
+
+        for transfer in &self.native_transfers {
+            events.push(encode(&HeliusNativeTransfer {
+                from: transfer.from_user_account.clone(),
+                to: transfer.to_user_account.clone(),
+                lamports: transfer.amount,
+            }));
+        }
+
+        for transfer in &self.token_transfers {
+            events.push(encode(&HeliusTokenTransfer {
+                from: transfer.from_user_account.clone(),
+                to: transfer.to_user_account.clone(),
+                mint: transfer.mint.clone(),
+                amount: transfer.token_amount,
+            }));
+        }
+
+        if let Some(nft) = self
+            .events
+            .nft
+            .as_ref()
+            .filter(|nft| nft.event_type == "NFT_SALE")
+        {
+            events.push(encode(&HeliusNftSale {
+                marketplace: nft.source.clone(),
+                buyer: nft.buyer.clone(),
+                seller: nft.seller.clone(),
+                lamports: nft.amount,
+                mints: nft.nfts.iter().map(|token| token.mint.clone()).collect(),
+            }));
+        }
+
+        if let Some(swap) = &self.events.swap {
+            events.push(encode(&HeliusSwap {
+                protocol: self.source.clone(),
+                user: self.fee_payer.clone(),
+                inputs: swap_legs(swap.native_input.as_ref(), &swap.token_inputs),
+                outputs: swap_legs(swap.native_output.as_ref(), &swap.token_outputs),
+            }));
+        }
+
+        events
+    }
+}
+
+/// Flattens the native and token amounts of one side of a swap.
+fn swap_legs(native: Option<&NativeAmount>, tokens: &[SwapTokenAmount]) -> Vec<HeliusSwapAmount> {
+    native
+        .map(|native| HeliusSwapAmount {
+            mint: NATIVE_MINT.to_string(),
+            amount: native.amount.clone(),
+            decimals: 9,
+        })
+        .into_iter()
+        .chain(tokens.iter().map(|token| HeliusSwapAmount {
+            mint: token.mint.clone(),
+            amount: token.raw_token_amount.token_amount.clone(),
+            decimals: token.raw_token_amount.decimals,
+        }))
+        .collect()
+}
+
+fn encode<T: EventDiscriminator + BorshSerialize>(event: &T) -> ParsedEvent {
+    let mut data = T::discriminator().to_vec();
+    // Serializing into a Vec cannot fail.
+    let _ = event.serialize(&mut data);
+    ParsedEvent {
+        event_type: EventType::ProgramData,
+        program_id: None,
+        data: Some(BASE64.encode(data)),
+    }
+}
+
+/// Splits a `ParsedEvent` produced by `EnhancedTransaction::parsed_events`
+/// into its discriminator and Borsh payload.
+#[must_use]
+pub fn decode_event(event: &ParsedEvent) -> Option<([u8; 8], Vec<u8>)> {
+    let bytes = BASE64.decode(event.data.as_deref()?).ok()?;
+    if bytes.len() < 8 {
+        return None;
+    }
+    let (discriminator, payload) = bytes.split_at(8);
+    Some((discriminator.try_into().ok()?, payload.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const NFT_SALE: &str = r#"
+    {
+        "signature": "5h6x",
+        "slot": 171942732,
+        "type": "NFT_SALE",
+        "source": "MAGIC_EDEN",
+        "feePayer": "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX",
+        "nativeTransfers": [
+            {"fromUserAccount": "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX", "toUserAccount": "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", "amount": 72936000000}
+        ],
+        "tokenTransfers": [
+            {"fromUserAccount": "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K", "toUserAccount": "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX", "mint": "FdsNQE5EeCe57tbEYCRV1JwW5dzNCof7MUTaGWhmzYqu", "tokenAmount": 1, "tokenStandard": "NonFungible"}
+        ],
+        "events": {
+            "nft": {
+                "type": "NFT_SALE",
+                "source": "MAGIC_EDEN",
+                "amount": 72936000000,
+                "buyer": "CKs1E69a2e9TmH4mKKLrXFF8kD3ZnwKjoEuXa6sz9WqX",
+                "seller": "M2mx93ekt1fmXSVkTrUL9xVFHkmME8HTUi5Cyc5aF7K",
+                "nfts": [{"mint": "FdsNQE5EeCe57tbEYCRV1JwW5dzNCof7MUTaGWhmzYqu", "tokenStandard": "NonFungible"}]
+            }
+        }
+    }
+    "#;
+
+    #[test]
+    fn test_nft_sale_maps_to_events() {
+        let tx: EnhancedTransaction = serde_json::from_str(NFT_SALE).unwrap();
+        let decoded: Vec<_> = tx.parsed_events().iter().filter_map(decode_event).collect();
+
+        let discriminators: Vec<_> = decoded.iter().map(|(d, _)| *d).collect();
+        assert_eq!(
+            discriminators,
+            vec![
+                HeliusNativeTransfer::discriminator(),
+                HeliusTokenTransfer::discriminator(),
+                HeliusNftSale::discriminator(),
+            ]
+        );
+
+        let sale = HeliusNftSale::try_from_slice(&decoded[2].1).unwrap();
+        assert_eq!(sale.marketplace, "MAGIC_EDEN");
+        assert_eq!(sale.lamports, 72_936_000_000);
+        assert_eq!(
+            sale.mints,
+            vec!["FdsNQE5EeCe57tbEYCRV1JwW5dzNCof7MUTaGWhmzYqu"]
+        );
+    }
+
+    #[test]
+    fn test_swap_maps_native_and_token_legs() {
+        let tx: EnhancedTransaction = serde_json::from_str(
+            r#"{
+                "signature": "5h6x",
+                "slot": 1,
+                "type": "SWAP",
+                "source": "JUPITER",
+                "feePayer": "user",
+                "events": {
+                    "swap": {
+                        "nativeInput": {"account": "user", "amount": "1000000000"},
+                        "tokenOutputs": [
+                            {"mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "rawTokenAmount": {"tokenAmount": "150000000", "decimals": 6}}
+                        ]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (discriminator, payload) = decode_event(&tx.parsed_events()[0]).unwrap();
+        assert_eq!(discriminator, HeliusSwap::discriminator());
+
+        let swap = HeliusSwap::try_from_slice(&payload).unwrap();
+        assert_eq!(swap.protocol, "JUPITER");
+        assert_eq!(swap.inputs[0].mint, NATIVE_MINT);
+        assert_eq!(swap.outputs[0].amount, "150000000");
+        assert_eq!(swap.outputs[0].decimals, 6);
+    }
+
+    #[tokio::test]
+    async fn test_client_fetches_transactions() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(query_param("api-key", "key"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(format!("[{NFT_SALE}]")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client =
+            HeliusEnhancedClient::new(format!("{}/v0/transactions?api-key=key", server.uri()));
+        let transactions = client
+            .fetch_transactions(&[Signature::new_unique()])
+            .await
+            .unwrap();
+
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].transaction_type, "NFT_SALE");
+    }
+}
@@ -6,8 +6,9 @@
 use super::{TransactionEvent, TransactionSource};
 use crate::utils::error::{Result, SolanaIndexerError};
 use crate::utils::logging;
+use crate::utils::network::{HttpTransport, NetworkConfig};
 use async_trait::async_trait;
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -23,12 +24,34 @@ pub struct HybridSource {
 impl HybridSource {
     /// Creates a new `HybridSource` instance.
     pub fn new(
+        ws_url: impl Into<String>,
+        rpc_url: impl Into<String>,
+        program_ids: Vec<Pubkey>,
+        poll_interval_secs: u64,
+        reconnect_delay_secs: u64,
+        gap_threshold_slots: u64,
+    ) -> Self {
+        Self::new_with_network(
+            ws_url,
+            rpc_url,
+            program_ids,
+            poll_interval_secs,
+            reconnect_delay_secs,
+            gap_threshold_slots,
+            NetworkConfig::default(),
+        )
+    }
+
+    /// Creates a new `HybridSource` whose WebSocket and RPC connections use
+    /// the proxy and TLS roots in `network`.
+    pub fn new_with_network(
         ws_url: impl Into<String>,
         rpc_url: impl Into<String>,
         program_ids: Vec<Pubkey>,
         poll_interval_secs: u64,
         reconnect_delay_secs: u64,
         _gap_threshold_slots: u64,
+        network: NetworkConfig,
     ) -> Self {
         let ws_url = ws_url.into();
         let rpc_url = rpc_url.into();
@@ -42,12 +65,14 @@ impl HybridSource {
         let metrics_ws = metrics.clone();
         let ws_url_clone = ws_url.clone();
         let program_ids_ws = program_ids.clone();
+        let network_ws = network.clone();
         tokio::spawn(async move {
             let mut ws_source = super::websocket::WebSocketSource::new(
                 ws_url_clone,
                 program_ids_ws,
                 reconnect_delay_secs,
-            );
+            )
+            .with_network(network_ws);
 
             loop {
                 match ws_source.next_batch().await {
@@ -76,7 +101,15 @@ impl HybridSource {
         let tx_rpc = tx.clone();
         let program_ids_rpc = program_ids;
         tokio::spawn(async move {
-            let rpc_client = RpcClient::new(rpc_url);
+            let rpc_client = match HttpTransport::new(network)
+                .nonblocking_rpc_client(rpc_url, CommitmentConfig::default())
+            {
+                Ok(rpc_client) => rpc_client,
+                Err(e) => {
+                    let _ = tx_rpc.send(Err(e)).await;
+                    return;
+                }
+            };
             let mut interval = interval(Duration::from_secs(poll_interval_secs));
 
             // We start polling from "now" roughly.
@@ -177,7 +210,7 @@ impl TransactionSource for HybridSource {
     async fn next_batch(&mut self) -> Result<Vec<TransactionEvent>> {
         let mut receiver = self.receiver.lock().await;
         receiver.recv().await.ok_or_else(|| {
-            SolanaIndexerError::InternalError("Hybrid source channel closed".to_string())
+            SolanaIndexerError::Internal("Hybrid source channel closed".to_string())
         })?
     }
 
@@ -29,6 +29,13 @@ use yellowstone_grpc_proto::tonic::Request;
 
 use super::TransactionSource;
 
+/// Server-side transaction filters applied to the gRPC subscription.
+#[derive(Debug, Clone, Copy)]
+struct TransactionFilters {
+    skip_failed: bool,
+    skip_vote: bool,
+}
+
 /// Laserstream source for acquiring transaction data via gRPC.
 pub struct LaserstreamSource {
     receiver: mpsc::Receiver<crate::streams::TransactionEvent>,
@@ -44,13 +51,17 @@ impl LaserstreamSource {
                 reconnect_delay_secs,
             } => (grpc_url.clone(), x_token.clone(), *reconnect_delay_secs),
             _ => {
-                return Err(SolanaIndexerError::ConfigError(
+                return Err(SolanaIndexerError::Config(
                     "Not a Laserstream config".to_string(),
                 ));
             }
         };
 
-        let program_ids: Vec<String> = config.program_ids.iter().map(|p| p.to_string()).collect();
+        let program_ids: Vec<String> = config
+            .watched_addresses()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
         let (sender, receiver) = mpsc::channel(1000); // Buffer size
 
         // Spawn background task to handle gRPC connection
@@ -59,6 +70,10 @@ impl LaserstreamSource {
             x_token,
             reconnect_delay,
             program_ids,
+            TransactionFilters {
+                skip_failed: config.skip_failed_transactions,
+                skip_vote: config.skip_vote_transactions,
+            },
             sender,
         ));
 
@@ -70,6 +85,7 @@ impl LaserstreamSource {
         x_token: Option<String>,
         reconnect_delay: u64,
         program_ids: Vec<String>,
+        filters: TransactionFilters,
         sender: mpsc::Sender<crate::streams::TransactionEvent>,
     ) {
         loop {
@@ -78,7 +94,7 @@ impl LaserstreamSource {
                 &format!("Connecting to Laserstream gRPC: {grpc_url}"),
             );
 
-            match Self::connect_and_subscribe(&grpc_url, &x_token, &program_ids).await {
+            match Self::connect_and_subscribe(&grpc_url, &x_token, &program_ids, filters).await {
                 Ok(mut stream) => {
                     crate::utils::logging::log(
                         crate::utils::logging::LogLevel::Success,
@@ -125,18 +141,22 @@ impl LaserstreamSource {
         grpc_url: &str,
         x_token: &Option<String>,
         program_ids: &[String],
+        filters: TransactionFilters,
     ) -> Result<yellowstone_grpc_proto::tonic::Streaming<SubscribeUpdate>> {
         // Create endpoint with TLS config if URL uses https/tls
         let endpoint = Endpoint::from_shared(grpc_url.to_string())
-            .map_err(|e| SolanaIndexerError::ConfigError(format!("Invalid URL: {e}")))?
+            .map_err(|e| SolanaIndexerError::Config(format!("Invalid URL: {e}")))?
             .tls_config(ClientTlsConfig::new())
-            .map_err(|e| SolanaIndexerError::ConfigError(format!("TLS error: {e}")))?;
+            .map_err(|e| SolanaIndexerError::Config(format!("TLS error: {e}")))?;
 
-        let channel = endpoint.connect().await.map_err(|e| {
-            SolanaIndexerError::ConnectionError(format!("gRPC connect failed: {e}"))
-        })?;
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| SolanaIndexerError::Connection(format!("gRPC connect failed: {e}")))?;
 
         let token = x_token.clone();
+        // tonic's interceptor signature fixes the error type to `Status`.
+        #[allow(clippy::result_large_err)]
         let mut client = GeyserClient::with_interceptor(channel, move |mut req: Request<()>| {
             if let Some(ref t) = token {
                 req.metadata_mut().insert(
@@ -152,8 +172,9 @@ impl LaserstreamSource {
         transactions.insert(
             "tx_sub".to_string(),
             SubscribeRequestFilterTransactions {
-                vote: Some(false),
-                failed: Some(false),
+                // `Some(false)` excludes the category, `None` streams everything
+                vote: filters.skip_vote.then_some(false),
+                failed: filters.skip_failed.then_some(false),
                 signature: None,
                 account_include: program_ids.to_vec(),
                 account_exclude: vec![],
@@ -177,7 +198,7 @@ impl LaserstreamSource {
         let stream = client
             .subscribe(futures_util::stream::once(async move { request }))
             .await
-            .map_err(|e| SolanaIndexerError::ConnectionError(format!("Subscribe failed: {e}")))?
+            .map_err(|e| SolanaIndexerError::Connection(format!("Subscribe failed: {e}")))?
             .into_inner();
 
         Ok(stream)
@@ -213,7 +234,7 @@ impl LaserstreamSource {
 
         // Extract top-level signature
         let signature = Signature::try_from(tx_info.signature.as_slice())
-            .map_err(|e| SolanaIndexerError::DataError(format!("Invalid signature bytes: {e}")))?;
+            .map_err(|e| SolanaIndexerError::Data(format!("Invalid signature bytes: {e}")))?;
 
         let Some(tx) = tx_info.transaction else {
             return Ok(());
@@ -431,7 +452,7 @@ impl LaserstreamSource {
         };
 
         if sender.send(event).await.is_err() {
-            return Err(SolanaIndexerError::InternalError(
+            return Err(SolanaIndexerError::Internal(
                 "Laserstream receiver dropped".to_string(),
             ));
         }
@@ -632,6 +653,6 @@ mod tests {
         };
 
         let result = LaserstreamSource::process_update(update, &sender).await;
-        assert!(matches!(result, Err(SolanaIndexerError::DataError(_))));
+        assert!(matches!(result, Err(SolanaIndexerError::Data(_))));
     }
 }
@@ -7,7 +7,10 @@
 
 use crate::utils::error::Result;
 use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedTransaction, UiMessage};
+use std::str::FromStr;
 
 /// Represents a transaction event from a source.
 #[derive(Debug, Clone)]
@@ -20,6 +23,8 @@ pub enum TransactionEvent {
         logs: Vec<String>,
         err: Option<serde_json::Value>,
         slot: u64,
+        /// The program whose subscription produced this event, if known
+        program_id: Option<Pubkey>,
     },
     /// A full transaction with metadata (e.g., from Helius transactionSubscribe)
     FullTransaction {
@@ -47,6 +52,51 @@ impl TransactionEvent {
             TransactionEvent::FullTransaction { slot, .. } => *slot,
         }
     }
+
+    /// Returns the program whose subscription produced this event, if known.
+    ///
+    /// Only sources that multiplex several program subscriptions tag events.
+    pub fn program_id(&self) -> Option<Pubkey> {
+        match self {
+            TransactionEvent::LogEvent { program_id, .. } => *program_id,
+            _ => None,
+        }
+    }
+
+    /// Returns the fee payer of a full transaction. Other events do not
+    /// carry account keys.
+    pub fn fee_payer(&self) -> Option<Pubkey> {
+        let TransactionEvent::FullTransaction { tx, .. } = self else {
+            return None;
+        };
+        match &tx.transaction.transaction {
+            EncodedTransaction::Json(ui_tx) => {
+                let key = match &ui_tx.message {
+                    UiMessage::Parsed(msg) => msg.account_keys.first().map(|k| k.pubkey.as_str()),
+                    UiMessage::Raw(msg) => msg.account_keys.first().map(String::as_str),
+                }?;
+                Pubkey::from_str(key).ok()
+            }
+            encoded => encoded
+                .decode()
+                .and_then(|tx| tx.message.static_account_keys().first().copied()),
+        }
+    }
+
+    /// Returns `true` if the source reported the transaction as failed.
+    ///
+    /// Plain signatures carry no status, so this is always `false` for them.
+    pub fn is_failed(&self) -> bool {
+        match self {
+            TransactionEvent::Signature { .. } => false,
+            TransactionEvent::LogEvent { err, .. } => err.as_ref().is_some_and(|e| !e.is_null()),
+            TransactionEvent::FullTransaction { tx, .. } => tx
+                .transaction
+                .meta
+                .as_ref()
+                .is_some_and(|meta| meta.err.is_some()),
+        }
+    }
 }
 
 /// Unified interface for transaction sources (Polling or WebSocket)
@@ -72,10 +122,14 @@ pub trait TransactionSource: Send + Sync {
 
 #[cfg(feature = "helius")]
 pub mod helius;
+#[cfg(feature = "helius")]
+pub mod helius_enhanced;
 #[cfg(feature = "websockets")]
 pub mod hybrid;
 #[cfg(feature = "laserstream")]
 pub mod laserstream;
 pub mod poller;
+pub mod slots;
+pub mod webhook;
 #[cfg(feature = "websockets")]
 pub mod websocket;
@@ -6,8 +6,10 @@
 use crate::config::SolanaIndexerConfig;
 use crate::core::decoding::Decoder;
 use crate::core::execution::fetcher::Fetcher;
+use crate::utils::backoff::poll_jitter;
 use crate::utils::error::{Result, SolanaIndexerError};
-use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use crate::utils::network::HttpTransport;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
 use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::signature::Signature;
 use std::time::Duration;
@@ -41,6 +43,9 @@ pub struct Poller {
 
     /// Last processed signature (for pagination)
     last_signature: Option<Signature>,
+
+    /// Proxy and TLS settings used to build RPC clients
+    transport: HttpTransport,
 }
 
 impl Poller {
@@ -71,9 +76,11 @@ impl Poller {
             crate::config::StartStrategy::Signature(sig) => Some(sig),
             _ => None, // Will be initialized by the indexer
         };
+        let transport = HttpTransport::new(config.network.clone());
         Self {
             config,
             last_signature,
+            transport,
         }
     }
 
@@ -84,21 +91,22 @@ impl Poller {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::RpcError` if the RPC request fails.
+    /// Returns `SolanaIndexerError::Rpc` if the RPC request fails.
     ///
     /// # Returns
     ///
     /// A vector of new transaction signatures to process.
     pub async fn fetch_new_signatures(&mut self) -> Result<Vec<Signature>> {
         // Capture values needed for the blocking task
-        let program_ids = self.config.program_ids.clone();
+        let program_ids = self.config.watched_addresses();
         let batch_size = self.config.batch_size;
         let last_sig = self.last_signature;
         let rpc_url = self.config.rpc_url().to_string();
+        let transport = self.transport.clone();
 
         let signatures = tokio::task::spawn_blocking(move || {
             // Create RPC client in the blocking task
-            let rpc_client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+            let rpc_client = transport.rpc_client(rpc_url, CommitmentConfig::confirmed())?;
             let mut all_sigs: Vec<
                 solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature,
             > = Vec::new();
@@ -114,14 +122,14 @@ impl Poller {
                 let sigs = rpc_client
                     .get_signatures_for_address_with_config(&program_id, config)
                     .map_err(|e| {
-                        SolanaIndexerError::RpcError(format!("Failed to fetch signatures: {e}"))
+                        SolanaIndexerError::rpc(format!("Failed to fetch signatures: {e}"))
                     })?;
                 all_sigs.extend(sigs);
             }
             Ok::<_, SolanaIndexerError>(all_sigs)
         })
         .await
-        .map_err(|e| SolanaIndexerError::InternalError(format!("Task join error: {e}")))??;
+        .map_err(|e| SolanaIndexerError::Internal(format!("Task join error: {e}")))??;
 
         // Extract signatures and update last_signature for pagination
         let sigs: Vec<Signature> = signatures
@@ -168,14 +176,16 @@ impl Poller {
         let mut interval = time::interval(poll_interval);
 
         // Initialize fetcher and decoder
-        let fetcher = Fetcher::new(self.config.rpc_url(), self.config.commitment_level.into());
+        let fetcher = Fetcher::new(self.config.rpc_url(), self.config.commitment_level.into())
+            .with_backoff(self.config.backoff)
+            .with_transport(self.transport.clone());
         let decoder = Decoder::new();
 
         tracing::info!("Starting poller with RPC: {}", self.config.rpc_url());
         tracing::info!(
-            "Monitoring programs: {}",
+            "Monitoring addresses: {}",
             self.config
-                .program_ids
+                .watched_addresses()
                 .iter()
                 .map(|p| p.to_string())
                 .collect::<Vec<String>>()
@@ -184,6 +194,7 @@ impl Poller {
 
         loop {
             interval.tick().await;
+            time::sleep(poll_jitter(self.config.poll_jitter_ms)).await;
 
             match self.fetch_new_signatures().await {
                 Ok(signatures) => {
@@ -210,8 +221,12 @@ impl Poller {
                     }
                 }
                 Err(e) => {
-                    if let SolanaIndexerError::RpcError(ref msg) = e {
-                        tracing::error!("RPC failure in poller (Exiting): {msg}");
+                    if let SolanaIndexerError::Rpc {
+                        ref message,
+                        retryable: false,
+                    } = e
+                    {
+                        tracing::error!("RPC failure in poller (Exiting): {message}");
                         return Err(e);
                     }
                     tracing::error!("Error fetching signatures: {e}");
@@ -288,8 +303,10 @@ mod tests {
         let config = SolanaIndexerConfig {
             database_url: "postgresql://localhost/db".to_string(),
             program_ids: vec![solana_sdk::pubkey::Pubkey::default()],
+            tracked_addresses: vec![],
             accounts_to_decode: vec![],
             poll_interval_secs: 5,
+            poll_jitter_ms: 0,
             batch_size: 100,
             source: crate::config::SourceConfig::Rpc {
                 rpc_url: "http://127.0.0.1:8899".to_string(),
@@ -299,10 +316,45 @@ mod tests {
             indexing_mode: crate::config::IndexingMode::inputs(),
             start_strategy: crate::config::StartStrategy::Latest,
             backfill: Default::default(),
+            snapshot: Default::default(),
             registry: Default::default(),
             stale_tentative_threshold: 1000,
             worker_threads: 10,
             commitment_level: crate::config::CommitmentLevel::Confirmed,
+            skip_failed_transactions: false,
+            skip_vote_transactions: false,
+            backoff: Default::default(),
+            addresses_per_poll: 0,
+            confirmation_tracking: false,
+            write_buffer: None,
+            usage_tracking: false,
+            failover: Default::default(),
+            slot_gap_alarm: None,
+            partitioning: None,
+            missing_meta: Default::default(),
+            retention: None,
+            vacuum: None,
+            replica_database_url: None,
+            handler_database_url: None,
+            processed_cache: 0,
+            block_index: false,
+            spill_queue: None,
+            db_schema: None,
+            unhandled_events: false,
+            dead_letter_queue: false,
+            atomic_transactions: false,
+            feature_flags_refresh_ms: None,
+            deferred_jobs_poll_ms: None,
+            outbox_poll_ms: None,
+            idl_refresh_secs: 300,
+            table_stats_refresh_secs: None,
+            archive_transactions: false,
+            duplicate_policy: Default::default(),
+            inner_instructions: false,
+            companion_programs: Default::default(),
+            token_holder_mints: Vec::new(),
+            network: Default::default(),
+            slot_tracking: false,
         };
 
         let poller = Poller::new(config);
@@ -0,0 +1,354 @@
+//! Chain-tip tracking via slot subscriptions.
+//!
+//! The backfill manager needs the latest finalized slot on every tick, and
+//! asking the RPC node with `getSlot` each time adds a round trip per tick.
+//! `SlotTracker` instead follows the chain over one WebSocket connection:
+//!
+//! - `slotSubscribe` reports every processed slot and the node's root, which
+//!   is used as the finalized slot.
+//! - `slotsUpdatesSubscribe` adds optimistic confirmations. It is an unstable
+//!   method that some providers disable; without it the confirmed slot
+//!   advances only with the root.
+//!
+//! The tracker implements `FinalizedBlockTracker`, falling back to `getSlot`
+//! whenever the subscription has gone quiet, so it can replace
+//! `DefaultFinalizedBlockTracker` without becoming a single point of failure.
+
+use crate::core::execution::fetcher::Fetcher;
+use crate::storage::StorageBackend;
+use crate::types::backfill_traits::FinalizedBlockTracker;
+use crate::utils::error::Result;
+use crate::utils::logging;
+use async_trait::async_trait;
+#[cfg(feature = "websockets")]
+use serde::Deserialize;
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Slots older than this are not trusted; `getSlot` is used instead.
+const STALE_AFTER: Duration = Duration::from_secs(30);
+
+/// Latest processed, confirmed and finalized slots, shared between clones.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::SolanaIndexer;
+/// # fn example(indexer: &SolanaIndexer) {
+/// let tip = indexer.slot_tracker();
+/// if let (Some(processed), Some(finalized)) = (tip.processed(), tip.finalized()) {
+///     println!("{} slots not yet finalized", processed - finalized);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SlotTracker {
+    inner: Arc<SlotState>,
+}
+
+#[derive(Debug, Default)]
+struct SlotState {
+    processed: AtomicU64,
+    confirmed: AtomicU64,
+    finalized: AtomicU64,
+    updated_at: Mutex<Option<Instant>>,
+}
+
+impl SlotTracker {
+    /// Creates a tracker that has not seen any slot yet.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the latest processed slot, if known.
+    #[must_use]
+    pub fn processed(&self) -> Option<u64> {
+        load(&self.inner.processed)
+    }
+
+    /// Returns the latest confirmed slot, if known.
+    #[must_use]
+    pub fn confirmed(&self) -> Option<u64> {
+        load(&self.inner.confirmed)
+    }
+
+    /// Returns the latest finalized slot, if known.
+    #[must_use]
+    pub fn finalized(&self) -> Option<u64> {
+        load(&self.inner.finalized)
+    }
+
+    /// Returns how long ago the tracker last received an update.
+    #[must_use]
+    pub fn age(&self) -> Option<Duration> {
+        self.inner
+            .updated_at
+            .lock()
+            .ok()
+            .and_then(|updated_at| updated_at.map(|at| at.elapsed()))
+    }
+
+    /// Records a slot reached at `commitment`.
+    ///
+    /// Slots only move forward, and a slot reached at a higher commitment also
+    /// counts for the lower ones.
+    pub fn record(&self, commitment: CommitmentLevel, slot: u64) {
+        self.raise(commitment, slot);
+        if let Ok(mut updated_at) = self.inner.updated_at.lock() {
+            *updated_at = Some(Instant::now());
+        }
+    }
+
+    /// Logs the current chain tip.
+    pub fn report(&self) {
+        let show = |slot: Option<u64>| slot.map_or_else(|| "?".to_string(), |s| s.to_string());
+        logging::log(
+            logging::LogLevel::Info,
+            &format!(
+                "Chain tip: processed {} | confirmed {} | finalized {}",
+                show(self.processed()),
+                show(self.confirmed()),
+                show(self.finalized())
+            ),
+        );
+    }
+
+    fn raise(&self, commitment: CommitmentLevel, slot: u64) {
+        let state = &self.inner;
+        let commitment = CommitmentConfig { commitment };
+        if commitment.is_finalized() {
+            state.finalized.fetch_max(slot, Ordering::Relaxed);
+        }
+        if commitment.is_at_least_confirmed() {
+            state.confirmed.fetch_max(slot, Ordering::Relaxed);
+        }
+        state.processed.fetch_max(slot, Ordering::Relaxed);
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.age().is_some_and(|age| age < STALE_AFTER)
+    }
+
+    /// Applies one subscription message, returning `true` if it was a slot update.
+    #[cfg(feature = "websockets")]
+    fn apply(&self, text: &str) -> bool {
+        let Ok(notification) = serde_json::from_str::<SlotMessage>(text) else {
+            return false;
+        };
+        match notification {
+            SlotMessage::Slot { params } => {
+                self.record(CommitmentLevel::Processed, params.result.slot);
+                self.record(CommitmentLevel::Finalized, params.result.root);
+            }
+            SlotMessage::SlotsUpdates { params } => {
+                let commitment = match params.result.kind.as_str() {
+                    "optimisticConfirmation" => CommitmentLevel::Confirmed,
+                    "root" => CommitmentLevel::Finalized,
+                    "frozen" => CommitmentLevel::Processed,
+                    _ => return false,
+                };
+                self.record(commitment, params.result.slot);
+            }
+        }
+        true
+    }
+}
+
+fn load(slot: &AtomicU64) -> Option<u64> {
+    Some(slot.load(Ordering::Relaxed)).filter(|&slot| slot > 0)
+}
+
+#[cfg(feature = "websockets")]
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method")]
+enum SlotMessage {
+    #[serde(rename = "slotNotification")]
+    Slot { params: Params<SlotInfo> },
+    #[serde(rename = "slotsUpdatesNotification")]
+    SlotsUpdates { params: Params<SlotUpdate> },
+}
+
+#[cfg(feature = "websockets")]
+#[derive(Debug, Deserialize)]
+struct Params<T> {
+    result: T,
+}
+
+#[cfg(feature = "websockets")]
+#[derive(Debug, Deserialize)]
+struct SlotInfo {
+    slot: u64,
+    root: u64,
+}
+
+#[cfg(feature = "websockets")]
+#[derive(Debug, Deserialize)]
+struct SlotUpdate {
+    slot: u64,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[cfg(feature = "websockets")]
+impl SlotTracker {
+    /// Follows the chain tip of `ws_url` until `cancellation_token` fires,
+    /// reconnecting after `reconnect_delay_secs` when the connection drops.
+    pub fn spawn(
+        &self,
+        ws_url: String,
+        network: crate::utils::network::NetworkConfig,
+        reconnect_delay_secs: u64,
+        cancellation_token: tokio_util::sync::CancellationToken,
+    ) -> tokio::task::JoinHandle<()> {
+        let tracker = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    result = tracker.follow(&ws_url, &network) => {
+                        if let Err(e) = result {
+                            logging::log_error("Slot subscription error", &e.to_string());
+                        }
+                    }
+                }
+                tokio::select! {
+                    () = cancellation_token.cancelled() => break,
+                    () = tokio::time::sleep(Duration::from_secs(reconnect_delay_secs)) => {}
+                }
+            }
+        })
+    }
+
+    /// Subscribes to slot updates and applies them until the connection closes.
+    async fn follow(
+        &self,
+        ws_url: &str,
+        network: &crate::utils::network::NetworkConfig,
+    ) -> Result<()> {
+        use crate::utils::error::SolanaIndexerError;
+        use futures_util::{SinkExt, StreamExt};
+        use tokio_tungstenite::tungstenite::Message;
+
+        let ws_stream = crate::utils::network::connect_websocket(ws_url, network).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        for (id, method) in [(1, "slotSubscribe"), (2, "slotsUpdatesSubscribe")] {
+            let request = serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": method });
+            write
+                .send(Message::Text(request.to_string()))
+                .await
+                .map_err(|e| SolanaIndexerError::rpc(format!("Failed to send {method}: {e}")))?;
+        }
+        logging::log(
+            logging::LogLevel::Success,
+            &format!("Tracking chain tip via {ws_url}"),
+        );
+
+        while let Some(message) = read.next().await {
+            let text = match message {
+                Ok(Message::Text(text)) => text,
+                Ok(Message::Close(_)) => break,
+                Ok(_) => continue,
+                Err(e) => {
+                    return Err(SolanaIndexerError::rpc(format!(
+                        "Slot subscription closed: {e}"
+                    )))
+                }
+            };
+            if self.apply(&text) {
+                continue;
+            }
+            // Only `slotsUpdatesSubscribe` is optional; its rejection is expected.
+            if let Ok(response) = serde_json::from_str::<serde_json::Value>(&text) {
+                if let Some(error) = response.get("error") {
+                    if response["id"] == 1 {
+                        return Err(SolanaIndexerError::rpc(format!(
+                            "slotSubscribe rejected: {error}"
+                        )));
+                    }
+                    logging::log(
+                        logging::LogLevel::Warning,
+                        "slotsUpdatesSubscribe unavailable, confirmed slot follows the root",
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl FinalizedBlockTracker for SlotTracker {
+    async fn is_finalized(&self, slot: u64, fetcher: &Fetcher) -> Result<bool> {
+        Ok(slot <= self.get_latest_finalized_slot(fetcher).await?)
+    }
+
+    async fn get_latest_finalized_slot(&self, fetcher: &Fetcher) -> Result<u64> {
+        if let Some(slot) = self.finalized().filter(|_| self.is_fresh()) {
+            return Ok(slot);
+        }
+        // Does not refresh `age`: an RPC answer says nothing about the subscription
+        let slot = fetcher.get_latest_finalized_slot().await?;
+        self.raise(CommitmentLevel::Finalized, slot);
+        Ok(slot)
+    }
+
+    async fn mark_finalized(
+        &self,
+        slot: u64,
+        block_hash: &str,
+        storage: &dyn StorageBackend,
+    ) -> Result<()> {
+        storage.mark_finalized(slot, block_hash).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_is_monotonic_across_commitments() {
+        let tracker = SlotTracker::new();
+        assert_eq!(tracker.processed(), None);
+        assert!(!tracker.is_fresh());
+
+        tracker.record(CommitmentLevel::Processed, 120);
+        tracker.record(CommitmentLevel::Confirmed, 110);
+        tracker.record(CommitmentLevel::Finalized, 90);
+        tracker.record(CommitmentLevel::Processed, 100);
+
+        assert_eq!(tracker.processed(), Some(120));
+        assert_eq!(tracker.confirmed(), Some(110));
+        assert_eq!(tracker.finalized(), Some(90));
+        assert!(tracker.is_fresh());
+
+        // A finalized slot is also confirmed
+        tracker.clone().record(CommitmentLevel::Finalized, 115);
+        assert_eq!(tracker.confirmed(), Some(115));
+    }
+
+    #[cfg(feature = "websockets")]
+    #[test]
+    fn test_apply_subscription_messages() {
+        let tracker = SlotTracker::new();
+
+        assert!(tracker.apply(
+            r#"{"jsonrpc":"2.0","method":"slotNotification","params":{"result":{"parent":199,"root":168,"slot":200},"subscription":0}}"#
+        ));
+        assert!(tracker.apply(
+            r#"{"jsonrpc":"2.0","method":"slotsUpdatesNotification","params":{"result":{"slot":198,"timestamp":1,"type":"optimisticConfirmation"},"subscription":1}}"#
+        ));
+        assert!(!tracker.apply(
+            r#"{"jsonrpc":"2.0","method":"slotsUpdatesNotification","params":{"result":{"slot":205,"timestamp":1,"type":"firstShredReceived"},"subscription":1}}"#
+        ));
+        assert!(!tracker.apply(r#"{"jsonrpc":"2.0","result":0,"id":1}"#));
+
+        assert_eq!(tracker.processed(), Some(200));
+        assert_eq!(tracker.confirmed(), Some(198));
+        assert_eq!(tracker.finalized(), Some(168));
+    }
+}
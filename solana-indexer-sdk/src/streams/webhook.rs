@@ -0,0 +1,347 @@
+//! Authentication of webhook deliveries.
+//!
+//! Providers such as Helius push transactions to an HTTP endpoint instead of
+//! being polled. Anyone who finds that endpoint can post to it, so every
+//! delivery must be checked before its payload enters the pipeline.
+//! `WebhookVerifier` checks the request headers and raw body of a delivery:
+//!
+//! - **Helius auth header**: Helius sends the `authHeader` configured on the
+//!   webhook verbatim in the `Authorization` header.
+//! - **HMAC-SHA256**: the sender signs `"{timestamp}.{body}"` with a shared
+//!   secret and sends the hex digest and the Unix timestamp in two headers.
+//!
+//! Replayed deliveries are rejected too. Signed deliveries must carry a
+//! timestamp within the tolerance window, and a nonce (the HMAC signature,
+//! or a configured nonce header) is accepted only once within that window.
+//! Secrets and signatures are compared in constant time.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use hmac::{Hmac, Mac};
+use reqwest::header::HeaderMap;
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default header carrying the hex HMAC-SHA256 signature.
+pub const DEFAULT_SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+/// Default header carrying the Unix timestamp the delivery was signed at.
+pub const DEFAULT_TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+
+/// Largest number of nonces remembered for replay detection.
+const MAX_NONCES: usize = 100_000;
+
+/// How a webhook sender proves who it is.
+#[derive(Clone)]
+pub enum WebhookAuth {
+    /// The `Authorization` header must equal this value (Helius `authHeader`).
+    HeliusAuthHeader(String),
+    /// The body must be signed with HMAC-SHA256 under this secret.
+    Hmac {
+        /// Shared secret
+        secret: Vec<u8>,
+        /// Header carrying the hex signature
+        signature_header: String,
+        /// Header carrying the Unix timestamp
+        timestamp_header: String,
+    },
+}
+
+impl std::fmt::Debug for WebhookAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print secrets
+        match self {
+            Self::HeliusAuthHeader(_) => f.write_str("HeliusAuthHeader(..)"),
+            Self::Hmac {
+                signature_header,
+                timestamp_header,
+                ..
+            } => f
+                .debug_struct("Hmac")
+                .field("signature_header", signature_header)
+                .field("timestamp_header", timestamp_header)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// Verifies webhook deliveries and rejects spoofed or replayed ones.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::streams::webhook::WebhookVerifier;
+/// use reqwest::header::HeaderMap;
+///
+/// let verifier = WebhookVerifier::helius("Bearer s3cret");
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("authorization", "Bearer s3cret".parse().unwrap());
+/// assert!(verifier.verify(&headers, br#"[{"signature":"..."}]"#).is_ok());
+///
+/// headers.insert("authorization", "Bearer guess".parse().unwrap());
+/// assert!(verifier.verify(&headers, b"[]").is_err());
+/// ```
+#[derive(Debug)]
+pub struct WebhookVerifier {
+    auth: WebhookAuth,
+    /// Largest accepted distance between a signed timestamp and now
+    tolerance: Duration,
+    /// Header carrying a unique delivery id, if the sender sends one
+    nonce_header: Option<String>,
+    nonces: Mutex<NonceCache>,
+}
+
+impl WebhookVerifier {
+    /// Creates a verifier for `auth` with a five-minute replay window.
+    #[must_use]
+    pub fn new(auth: WebhookAuth) -> Self {
+        Self {
+            auth,
+            tolerance: Duration::from_secs(300),
+            nonce_header: None,
+            nonces: Mutex::new(NonceCache::default()),
+        }
+    }
+
+    /// Creates a verifier for Helius webhooks configured with `auth_header`.
+    #[must_use]
+    pub fn helius(auth_header: impl Into<String>) -> Self {
+        Self::new(WebhookAuth::HeliusAuthHeader(auth_header.into()))
+    }
+
+    /// Creates a verifier for HMAC-SHA256 signed webhooks, reading the
+    /// signature and timestamp from the default headers.
+    #[must_use]
+    pub fn hmac(secret: impl Into<Vec<u8>>) -> Self {
+        Self::new(WebhookAuth::Hmac {
+            secret: secret.into(),
+            signature_header: DEFAULT_SIGNATURE_HEADER.to_string(),
+            timestamp_header: DEFAULT_TIMESTAMP_HEADER.to_string(),
+        })
+    }
+
+    /// Sets how far a signed timestamp may be from now, and how long nonces
+    /// are remembered (default: 5 minutes).
+    #[must_use]
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Rejects deliveries whose `header` value was already seen within the
+    /// tolerance window. Deliveries without the header are rejected.
+    #[must_use]
+    pub fn with_nonce_header(mut self, header: impl Into<String>) -> Self {
+        self.nonce_header = Some(header.into());
+        self
+    }
+
+    /// Checks one delivery's headers and raw body.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Authentication` if a required header
+    /// is missing, the credentials or signature do not match, the timestamp
+    /// is outside the tolerance window, or the nonce was already used.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> Result<()> {
+        self.verify_at(headers, body, unix_now())
+    }
+
+    fn verify_at(&self, headers: &HeaderMap, body: &[u8], now: u64) -> Result<()> {
+        let signed_nonce = match &self.auth {
+            WebhookAuth::HeliusAuthHeader(expected) => {
+                let provided = header(headers, "authorization")?;
+                if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+                    return Err(rejected("authorization header does not match"));
+                }
+                None
+            }
+            WebhookAuth::Hmac {
+                secret,
+                signature_header,
+                timestamp_header,
+            } => {
+                let timestamp = header(headers, timestamp_header)?;
+                let signature = header(headers, signature_header)?;
+                let signed_at: u64 = timestamp
+                    .trim()
+                    .parse()
+                    .map_err(|_| rejected("timestamp is not a Unix time"))?;
+                if signed_at.abs_diff(now) > self.tolerance.as_secs() {
+                    return Err(rejected("timestamp is outside the tolerance window"));
+                }
+
+                let digest = signature.trim();
+                let digest = digest.strip_prefix("sha256=").unwrap_or(digest);
+                let digest = decode_hex(digest).ok_or_else(|| rejected("signature is not hex"))?;
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .map_err(|e| SolanaIndexerError::Config(e.to_string()))?;
+                mac.update(timestamp.as_bytes());
+                mac.update(b".");
+                mac.update(body);
+                mac.verify_slice(&digest)
+                    .map_err(|_| rejected("signature does not match"))?;
+                Some(signature.to_string())
+            }
+        };
+
+        let nonce = match &self.nonce_header {
+            Some(name) => Some(header(headers, name)?.to_string()),
+            None => signed_nonce,
+        };
+        if let Some(nonce) = nonce {
+            let mut nonces = self
+                .nonces
+                .lock()
+                .map_err(|_| SolanaIndexerError::Internal("Nonce cache poisoned".into()))?;
+            if !nonces.insert(nonce, now, self.tolerance.as_secs()) {
+                return Err(rejected("delivery was already received"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Nonces seen within the replay window, oldest first.
+#[derive(Debug, Default)]
+struct NonceCache {
+    seen: HashMap<String, u64>,
+    order: VecDeque<(u64, String)>,
+}
+
+impl NonceCache {
+    /// Returns `true` if `nonce` was not seen within `window` seconds of `now`.
+    fn insert(&mut self, nonce: String, now: u64, window: u64) -> bool {
+        while let Some((seen_at, _)) = self.order.front() {
+            if now.saturating_sub(*seen_at) <= window && self.order.len() < MAX_NONCES {
+                break;
+            }
+            if let Some((_, expired)) = self.order.pop_front() {
+                self.seen.remove(&expired);
+            }
+        }
+        if self.seen.contains_key(&nonce) {
+            return false;
+        }
+        self.seen.insert(nonce.clone(), now);
+        self.order.push_back((now, nonce));
+        true
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Result<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| rejected(&format!("missing {name} header")))
+}
+
+fn rejected(reason: &str) -> SolanaIndexerError {
+    SolanaIndexerError::Authentication(format!("Webhook delivery rejected: {reason}"))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(body);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn signed_headers(signature: &str, timestamp: u64) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(DEFAULT_SIGNATURE_HEADER, signature.parse().unwrap());
+        headers.insert(DEFAULT_TIMESTAMP_HEADER, timestamp.into());
+        headers
+    }
+
+    #[test]
+    fn test_hmac_rejects_spoofed_and_stale_deliveries() {
+        let verifier = WebhookVerifier::hmac("secret");
+        let now = 1_700_000_000;
+        let body = br#"[{"signature":"abc"}]"#;
+        let signature = sign(b"secret", now, body);
+
+        assert!(verifier
+            .verify_at(&signed_headers(&signature, now), body, now + 10)
+            .is_ok());
+
+        // Tampered body, wrong secret, stale timestamp, missing headers
+        let spoofed = [
+            verifier.verify_at(&signed_headers(&signature, now), b"[]", now),
+            verifier.verify_at(&signed_headers(&sign(b"guess", now, body), now), body, now),
+            verifier.verify_at(
+                &signed_headers(&sign(b"secret", now - 600, body), now - 600),
+                body,
+                now,
+            ),
+            verifier.verify_at(&HeaderMap::new(), body, now),
+        ];
+        for result in spoofed {
+            assert!(matches!(result, Err(SolanaIndexerError::Authentication(_))));
+        }
+    }
+
+    #[test]
+    fn test_replayed_delivery_rejected() {
+        let verifier = WebhookVerifier::hmac("secret").with_tolerance(Duration::from_secs(60));
+        let now = 1_700_000_000;
+        let body = b"[]";
+        let headers = signed_headers(&format!("sha256={}", sign(b"secret", now, body)), now);
+
+        assert!(verifier.verify_at(&headers, body, now).is_ok());
+        assert!(verifier.verify_at(&headers, body, now + 5).is_err());
+    }
+
+    #[test]
+    fn test_helius_auth_header_with_nonce() {
+        let verifier = WebhookVerifier::helius("Bearer s3cret").with_nonce_header("x-delivery-id");
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer s3cret".parse().unwrap());
+        assert!(verifier.verify_at(&headers, b"[]", 100).is_err());
+
+        headers.insert("x-delivery-id", "delivery-1".parse().unwrap());
+        assert!(verifier.verify_at(&headers, b"[]", 100).is_ok());
+        assert!(verifier.verify_at(&headers, b"[]", 101).is_err());
+        // Forgotten once the window has passed
+        assert!(verifier.verify_at(&headers, b"[]", 500).is_ok());
+    }
+
+    #[test]
+    fn test_nonce_cache_expires_entries() {
+        let mut cache = NonceCache::default();
+        assert!(cache.insert("a".into(), 10, 5));
+        assert!(!cache.insert("a".into(), 14, 5));
+        assert!(cache.insert("a".into(), 20, 5));
+        assert_eq!(cache.order.len(), 1);
+    }
+}
@@ -1,24 +1,37 @@
 //! WebSocket-based transaction source for real-time indexing
 //!
 //! This module provides a WebSocket client that subscribes to Solana program
-//! notifications and yields transaction signatures in real-time.
+//! notifications and yields transaction signatures in real-time. Several
+//! programs share one connection: each gets its own `logsSubscribe`
+//! subscription and events are tagged with the program that produced them.
+//!
+//! Subscriptions are managed independently. A subscription the node rejects,
+//! or never confirms, is retried on its own while the others keep streaming;
+//! only a dropped connection resubscribes everything.
 
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
 use serde_json::json;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
-use tokio::time::{sleep, Duration};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
 
 use super::TransactionSource;
 use crate::utils::error::{Result, SolanaIndexerError};
+use crate::utils::network::{connect_websocket, NetworkConfig};
+
+/// Number of recent signatures remembered to drop duplicate notifications
+/// for transactions that mention several subscribed programs.
+const RECENT_SIGNATURES: usize = 4096;
 
 /// WebSocket-based input source for acquiring transaction signatures.
 ///
 /// The `WebSocketSource` subscribes to a Solana RPC WebSocket endpoint for
-/// real-time notifications of new transactions related to a specific program ID.
+/// real-time notifications of new transactions related to a set of program IDs.
+/// All subscriptions are multiplexed over a single connection.
 /// This is ideal for production environments requiring low latency and high throughput.
 ///
 /// # Example
@@ -43,10 +56,12 @@ use crate::utils::error::{Result, SolanaIndexerError};
 pub struct WebSocketSource {
     /// WebSocket URL (ws:// or wss://)
     ws_url: String,
-    /// Program ID to subscribe to
+    /// Program IDs to subscribe to
     program_ids: Vec<Pubkey>,
     /// Reconnection delay in seconds
     reconnect_delay_secs: u64,
+    /// Proxy and TLS settings for the connection
+    network: NetworkConfig,
     /// Internal state
     state: WebSocketState,
 }
@@ -55,8 +70,6 @@ pub struct WebSocketSource {
 enum WebSocketState {
     Disconnected,
     Connected {
-        #[allow(dead_code)] // Kept for future unsubscribe functionality
-        subscription_id: u64,
         receiver: tokio::sync::mpsc::UnboundedReceiver<crate::streams::TransactionEvent>,
     },
 }
@@ -70,6 +83,7 @@ struct LogsNotification {
 #[derive(Debug, Deserialize)]
 struct LogsNotificationParams {
     pub(super) result: LogsNotificationResult,
+    pub(super) subscription: u64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -93,7 +107,242 @@ struct LogsNotificationValue {
 /// Subscription response from Solana
 #[derive(Debug, Deserialize)]
 struct SubscriptionResponse {
-    result: u64,
+    id: u64,
+    #[serde(default)]
+    result: Option<u64>,
+    #[serde(default)]
+    error: Option<serde_json::Value>,
+}
+
+impl LogsNotification {
+    /// Converts the notification into an event tagged with the program whose
+    /// subscription produced it.
+    fn into_event(
+        self,
+        subscriptions: &HashMap<u64, Pubkey>,
+    ) -> Option<crate::streams::TransactionEvent> {
+        let params = self.params;
+        let signature = Signature::from_str(&params.result.value.signature).ok()?;
+        Some(crate::streams::TransactionEvent::LogEvent {
+            signature,
+            logs: params.result.value.logs,
+            err: params.result.value.err,
+            slot: params.result.context.slot,
+            program_id: subscriptions.get(&params.subscription).copied(),
+        })
+    }
+}
+
+/// Bounded set of recently seen signatures.
+///
+/// A transaction that mentions several subscribed programs is delivered once
+/// per subscription; only the first delivery is forwarded.
+#[derive(Default)]
+struct RecentSignatures {
+    seen: HashSet<Signature>,
+    order: VecDeque<Signature>,
+}
+
+impl RecentSignatures {
+    /// Returns `true` the first time `signature` is seen.
+    fn insert(&mut self, signature: Signature) -> bool {
+        if !self.seen.insert(signature) {
+            return false;
+        }
+        self.order.push_back(signature);
+        if self.order.len() > RECENT_SIGNATURES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Subscription bookkeeping for one connection.
+///
+/// Tracks which program every request id and subscription id belongs to, so
+/// notifications can be routed and each subscription retried on its own.
+struct SubscriptionRouter {
+    /// Confirmed subscriptions by server-assigned subscription id
+    active: HashMap<u64, Pubkey>,
+    /// Unanswered subscribe requests by request id, with the time they were sent
+    pending: HashMap<u64, (Pubkey, Instant)>,
+    /// Timed-out requests sent again under a new id, which the node may
+    /// still answer
+    superseded: HashMap<u64, Pubkey>,
+    /// Subscriptions opened for a program that already had one, to be closed
+    extra: Vec<u64>,
+    /// Programs waiting to be (re)subscribed, with the time they are due
+    queued: Vec<(Pubkey, Instant)>,
+    /// Next request id
+    next_id: u64,
+    /// How long a request may go unanswered before it is sent again
+    response_timeout: Duration,
+    /// Delay before retrying a rejected subscription
+    retry_delay: Duration,
+}
+
+impl SubscriptionRouter {
+    fn new(program_ids: &[Pubkey], response_timeout: Duration, retry_delay: Duration) -> Self {
+        let now = Instant::now();
+        Self {
+            active: HashMap::new(),
+            pending: HashMap::new(),
+            superseded: HashMap::new(),
+            extra: Vec::new(),
+            queued: program_ids
+                .iter()
+                .map(|&program_id| (program_id, now))
+                .collect(),
+            next_id: 1,
+            response_timeout,
+            retry_delay,
+        }
+    }
+
+    /// Returns the subscribe requests due at `now` as `(request id, program)`.
+    ///
+    /// Requests left unanswered past the response timeout are sent again
+    /// under a new id. The old id stays known, so a late answer to it still
+    /// opens the subscription, or is closed as an extra.
+    fn due(&mut self, now: Instant) -> Vec<(u64, Pubkey)> {
+        let timeout = self.response_timeout;
+        let expired: Vec<u64> = self
+            .pending
+            .iter()
+            .filter(|(_, (_, sent_at))| now.duration_since(*sent_at) >= timeout)
+            .map(|(&id, _)| id)
+            .collect();
+        let mut due = Vec::new();
+        for id in expired {
+            if let Some((program_id, _)) = self.pending.remove(&id) {
+                self.superseded.insert(id, program_id);
+                due.push(program_id);
+            }
+        }
+        self.queued.retain(|&(program_id, at)| {
+            let ready = at <= now;
+            if ready {
+                due.push(program_id);
+            }
+            !ready
+        });
+
+        due.into_iter()
+            .map(|program_id| {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.pending.insert(id, (program_id, now));
+                (id, program_id)
+            })
+            .collect()
+    }
+
+    /// Records the answer to a subscribe request.
+    ///
+    /// Returns the program and the outcome: the subscription id, or the
+    /// node's error, in which case the program is queued for a retry.
+    /// Returns `None` for answers that change nothing, and for
+    /// subscriptions the program already has, which are left for
+    /// `take_extras`.
+    fn confirm(
+        &mut self,
+        response: SubscriptionResponse,
+        now: Instant,
+    ) -> Option<(Pubkey, std::result::Result<u64, serde_json::Value>)> {
+        let (program_id, current) = match self.pending.remove(&response.id) {
+            Some((program_id, _)) => (program_id, true),
+            None => (self.superseded.remove(&response.id)?, false),
+        };
+        match response.result {
+            Some(subscription_id) => self
+                .open(program_id, subscription_id)
+                .then_some((program_id, Ok(subscription_id))),
+            // A superseded request failing leaves its replacement to answer
+            None if !current => None,
+            None => {
+                self.queued.push((program_id, now + self.retry_delay));
+                Some((program_id, Err(response.error.unwrap_or_default())))
+            }
+        }
+    }
+
+    /// Routes `subscription_id` to `program_id`, unless the program already
+    /// has a subscription, in which case it is set aside as an extra.
+    /// Returns `true` if the subscription was opened.
+    fn open(&mut self, program_id: Pubkey, subscription_id: u64) -> bool {
+        if self.active.values().any(|&active| active == program_id) {
+            self.extra.push(subscription_id);
+            return false;
+        }
+        self.active.insert(subscription_id, program_id);
+
+        // Any other request for the program can only open an extra now
+        let others: Vec<u64> = self
+            .pending
+            .iter()
+            .filter(|(_, &(pending, _))| pending == program_id)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in others {
+            self.pending.remove(&id);
+            self.superseded.insert(id, program_id);
+        }
+        self.queued.retain(|&(queued, _)| queued != program_id);
+        true
+    }
+
+    /// Returns the unsubscribe requests for extra subscriptions as
+    /// `(request id, subscription id)`.
+    fn take_extras(&mut self) -> Vec<(u64, u64)> {
+        std::mem::take(&mut self.extra)
+            .into_iter()
+            .map(|subscription_id| {
+                let id = self.next_id;
+                self.next_id += 1;
+                (id, subscription_id)
+            })
+            .collect()
+    }
+
+    /// Returns when `due` next has work, if ever.
+    fn next_deadline(&self) -> Option<Instant> {
+        let retries = self.queued.iter().map(|&(_, at)| at);
+        let timeouts = self
+            .pending
+            .values()
+            .map(|&(_, sent_at)| sent_at + self.response_timeout);
+        retries.chain(timeouts).min()
+    }
+}
+
+/// Builds the `logsUnsubscribe` request closing `subscription_id`.
+fn unsubscribe_request(id: u64, subscription_id: u64) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "logsUnsubscribe",
+        "params": [subscription_id]
+    })
+    .to_string()
+}
+
+/// Builds the `logsSubscribe` request for `program_id`.
+///
+/// `logsSubscribe` accepts a single address in `mentions`, so every program
+/// gets its own subscription.
+fn subscribe_request(id: u64, program_id: &Pubkey) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "logsSubscribe",
+        "params": [
+            { "mentions": [program_id.to_string()] },
+            { "commitment": "confirmed" }
+        ]
+    })
+    .to_string()
 }
 
 impl WebSocketSource {
@@ -102,7 +351,7 @@ impl WebSocketSource {
     /// # Arguments
     ///
     /// * `ws_url` - The WebSocket URL (e.g., `<ws://127.0.0.1:8900>`)
-    /// * `program_id` - The program ID to monitor for notifications
+    /// * `program_ids` - The program IDs to monitor; all share one connection
     /// * `reconnect_delay_secs` - Seconds to wait before reconnecting after a disconnect
     ///
     /// # Example
@@ -125,10 +374,18 @@ impl WebSocketSource {
             ws_url: ws_url.into(),
             program_ids,
             reconnect_delay_secs,
+            network: NetworkConfig::default(),
             state: WebSocketState::Disconnected,
         }
     }
 
+    /// Connects through the proxy and TLS roots in `network`.
+    #[must_use]
+    pub fn with_network(mut self, network: NetworkConfig) -> Self {
+        self.network = network;
+        self
+    }
+
     /// Connects to WebSocket and subscribes to program notifications
     async fn connect(&mut self) -> Result<()> {
         use crate::utils::logging;
@@ -139,77 +396,84 @@ impl WebSocketSource {
         );
 
         // Connect to WebSocket
-        let (ws_stream, _) = connect_async(&self.ws_url).await.map_err(|e| {
-            SolanaIndexerError::RpcError(format!("WebSocket connection failed: {e}"))
-        })?;
-
+        let ws_stream = connect_websocket(&self.ws_url, &self.network).await?;
         let (mut write, mut read) = ws_stream.split();
 
-        // Subscribe to transaction logs mentioning this program
-        // This captures all transactions that involve the program
-        let subscribe_request = json!({
-            "jsonrpc": "2.0",
-            "id": 1,
-            "method": "logsSubscribe",
-            "params": [
-                {
-                    "mentions": self.program_ids.iter().map(|p| p.to_string()).collect::<Vec<String>>()
-                },
-                {
-                    "commitment": "confirmed"
-                }
-            ]
-        });
-
-        write
-            .send(Message::Text(subscribe_request.to_string()))
-            .await
-            .map_err(|e| {
-                SolanaIndexerError::RpcError(format!("Failed to send subscription: {e}"))
-            })?;
-
-        // Wait for subscription confirmation
-        let subscription_id = loop {
-            #[allow(clippy::collapsible_if)]
-            if let Some(Ok(Message::Text(text))) = read.next().await {
-                if let Ok(response) = serde_json::from_str::<SubscriptionResponse>(&text) {
-                    break response.result;
-                }
-            }
-        };
-
-        logging::log(
-            logging::LogLevel::Success,
-            &format!("WebSocket subscribed (ID: {subscription_id})"),
+        let mut router = SubscriptionRouter::new(
+            &self.program_ids,
+            self.network.timeouts.ws_connect(),
+            Duration::from_secs(self.reconnect_delay_secs),
         );
 
         // Create channel for events
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
 
-        // Spawn background task to handle incoming messages
+        // Spawn background task that owns the connection: it sends subscribe
+        // requests as they fall due and routes responses and notifications.
+        // Dropping `tx` when the connection ends signals a reconnect.
         tokio::spawn(async move {
-            while let Some(Ok(Message::Text(text))) = read.next().await {
-                #[allow(clippy::collapsible_if)]
-                if let Ok(notification) = serde_json::from_str::<LogsNotification>(&text) {
-                    if let Ok(sig) =
-                        Signature::from_str(&notification.params.result.value.signature)
+            let mut recent = RecentSignatures::default();
+            loop {
+                for (id, program_id) in router.due(Instant::now()) {
+                    if let Err(e) = write
+                        .send(Message::Text(subscribe_request(id, &program_id)))
+                        .await
                     {
-                        let event = crate::streams::TransactionEvent::LogEvent {
-                            signature: sig,
-                            logs: notification.params.result.value.logs,
-                            err: notification.params.result.value.err,
-                            slot: notification.params.result.context.slot,
-                        };
-                        let _ = tx.send(event);
+                        logging::log_error("Failed to send subscription", &e.to_string());
+                        return;
+                    }
+                }
+
+                let wake = router
+                    .next_deadline()
+                    .unwrap_or_else(|| Instant::now() + Duration::from_secs(3600));
+                let text = tokio::select! {
+                    message = read.next() => match message {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                        Some(Ok(_)) => continue,
+                    },
+                    () = sleep_until(wake) => continue,
+                };
+
+                if let Ok(response) = serde_json::from_str::<SubscriptionResponse>(&text) {
+                    match router.confirm(response, Instant::now()) {
+                        Some((program_id, Ok(subscription_id))) => logging::log(
+                            logging::LogLevel::Success,
+                            &format!(
+                                "WebSocket subscribed to {program_id} (ID: {subscription_id})"
+                            ),
+                        ),
+                        Some((program_id, Err(error))) => logging::log(
+                            logging::LogLevel::Warning,
+                            &format!("Subscription for {program_id} rejected, retrying: {error}"),
+                        ),
+                        None => {}
+                    }
+                    for (id, subscription_id) in router.take_extras() {
+                        if let Err(e) = write
+                            .send(Message::Text(unsubscribe_request(id, subscription_id)))
+                            .await
+                        {
+                            logging::log_error("Failed to send unsubscribe", &e.to_string());
+                            return;
+                        }
+                    }
+                    continue;
+                }
+
+                if let Some(event) = serde_json::from_str::<LogsNotification>(&text)
+                    .ok()
+                    .and_then(|notification| notification.into_event(&router.active))
+                {
+                    if recent.insert(event.signature()) && tx.send(event).is_err() {
+                        return;
                     }
                 }
             }
         });
 
-        self.state = WebSocketState::Connected {
-            subscription_id,
-            receiver: rx,
-        };
+        self.state = WebSocketState::Connected { receiver: rx };
 
         Ok(())
     }
@@ -263,7 +527,7 @@ impl TransactionSource for WebSocketSource {
 
                 Ok(events)
             }
-            WebSocketState::Disconnected => Err(SolanaIndexerError::InternalError(
+            WebSocketState::Disconnected => Err(SolanaIndexerError::Internal(
                 "WebSocket not connected".to_string(),
             )),
         }
@@ -327,4 +591,136 @@ mod tests {
         assert_eq!(notification.params.result.value.logs.len(), 2);
         Ok(())
     }
+
+    #[test]
+    fn test_notification_tagged_with_program() -> std::result::Result<(), Box<dyn std::error::Error>>
+    {
+        let program_id = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+        let json_data = json!({
+            "jsonrpc": "2.0",
+            "method": "logsNotification",
+            "params": {
+                "result": {
+                    "context": { "slot": 42 },
+                    "value": { "signature": signature.to_string(), "err": null, "logs": [] }
+                },
+                "subscription": 7
+            }
+        });
+
+        let notification: LogsNotification = serde_json::from_value(json_data)?;
+        let subscriptions = HashMap::from([(7, program_id)]);
+        let event = notification
+            .into_event(&subscriptions)
+            .ok_or("notification should convert")?;
+
+        assert_eq!(event.signature(), signature);
+        assert_eq!(event.slot(), 42);
+        assert_eq!(event.program_id(), Some(program_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_subscription_response_deserialization(
+    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let ok: SubscriptionResponse =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","result":23784,"id":2}"#)?;
+        assert_eq!((ok.id, ok.result), (2, Some(23784)));
+
+        let rejected: SubscriptionResponse = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Invalid params"},"id":1}"#,
+        )?;
+        assert_eq!(rejected.result, None);
+        assert!(rejected.error.is_some());
+
+        // Notifications carry no request id and must not parse as a response.
+        assert!(serde_json::from_str::<SubscriptionResponse>(
+            r#"{"jsonrpc":"2.0","method":"logsNotification","params":{}}"#
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_router_retries_subscriptions_independently() {
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let timeout = Duration::from_secs(10);
+        let retry_delay = Duration::from_secs(5);
+        let mut router = SubscriptionRouter::new(&[first, second], timeout, retry_delay);
+        let start = Instant::now();
+
+        let requests = router.due(start);
+        assert_eq!(requests, vec![(1, first), (2, second)]);
+        assert!(router.due(start).is_empty());
+
+        let response = |id, result, error| SubscriptionResponse { id, result, error };
+        let rejected = router.confirm(response(1, None, Some(json!("busy"))), start);
+        assert!(matches!(rejected, Some((program_id, Err(_))) if program_id == first));
+        let confirmed = router.confirm(response(2, Some(77), None), start);
+        assert!(matches!(confirmed, Some((program_id, Ok(77))) if program_id == second));
+        assert_eq!(router.active.get(&77), Some(&second));
+
+        // Only the rejected subscription is retried, after the delay
+        assert_eq!(router.next_deadline(), Some(start + retry_delay));
+        assert!(router.due(start + Duration::from_secs(1)).is_empty());
+        assert_eq!(router.due(start + retry_delay), vec![(3, first)]);
+
+        // An unanswered request is resent under a new id; whichever answer
+        // comes first opens the subscription and the other is closed
+        let later = start + retry_delay + timeout;
+        assert_eq!(router.due(later), vec![(4, first)]);
+        assert!(router.confirm(response(4, Some(81), None), later).is_some());
+        assert_eq!(router.next_deadline(), None);
+        assert!(router.take_extras().is_empty());
+    }
+
+    #[test]
+    fn test_router_closes_late_confirmations() {
+        let program = Pubkey::new_unique();
+        let timeout = Duration::from_secs(10);
+        let mut router = SubscriptionRouter::new(&[program], timeout, Duration::from_secs(5));
+        let start = Instant::now();
+        let response = |id, result| SubscriptionResponse {
+            id,
+            result,
+            error: None,
+        };
+
+        assert_eq!(router.due(start), vec![(1, program)]);
+        assert_eq!(router.due(start + timeout), vec![(2, program)]);
+
+        // The resent request is answered first; the late original is extra
+        assert!(router.confirm(response(2, Some(20)), start).is_some());
+        assert!(router.confirm(response(1, Some(10)), start).is_none());
+        assert_eq!(router.take_extras(), vec![(3, 10)]);
+        assert_eq!(router.active.len(), 1);
+
+        // The late original is answered first; the resend turns into the extra
+        let mut router = SubscriptionRouter::new(&[program], timeout, Duration::from_secs(5));
+        let start = Instant::now();
+        assert_eq!(router.due(start), vec![(1, program)]);
+        assert_eq!(router.due(start + timeout), vec![(2, program)]);
+        assert!(router.confirm(response(1, Some(10)), start).is_some());
+        assert_eq!(router.active.get(&10), Some(&program));
+        assert_eq!(router.next_deadline(), None);
+        assert!(router.confirm(response(2, Some(20)), start).is_none());
+        assert_eq!(router.take_extras(), vec![(3, 20)]);
+        assert!(router.take_extras().is_empty());
+    }
+
+    #[test]
+    fn test_recent_signatures_dedup() {
+        let mut recent = RecentSignatures::default();
+        let signature = Signature::new_unique();
+
+        assert!(recent.insert(signature));
+        assert!(!recent.insert(signature));
+
+        for _ in 0..RECENT_SIGNATURES {
+            recent.insert(Signature::new_unique());
+        }
+        // Evicted once the window is full.
+        assert!(recent.insert(signature));
+    }
 }
@@ -0,0 +1,835 @@
+//! Fault injection for resilience tests.
+//!
+//! The wrappers in this module sit between the indexer and a real source or
+//! storage backend and inject faults at configurable rates:
+//!
+//! - `ChaosSource` drops a fraction of the events a `TransactionSource`
+//!   delivers (simulating lost WebSocket notifications) and delays every
+//!   batch. It only applies to custom sources run with `start_with_source`.
+//! - `ChaosProxy` sits in front of an RPC or WebSocket endpoint and delays
+//!   responses and drops connections, so the built-in polling, WebSocket
+//!   and fetcher paths see real network faults.
+//! - `ChaosStorage` fails a fraction of the writes to a `StorageBackend`
+//!   with `SolanaIndexerError::Storage`.
+//!
+//! Faults are drawn from a seeded generator, so a failing run can be replayed
+//! with the same seed. Each wrapper counts the faults it injected so tests
+//! can assert that recovery actually happened.
+//!
+//! `ChaosStorage` only sees writes made through the `StorageBackend` trait
+//! (processed markers, cursors, watermarks, backfill progress, snapshot
+//! slots) and the maintenance runs (partitions, retention, vacuum). Handler
+//! writes through `pool()` are passed through untouched; a failed
+//! `mark_processed` after a successful handler is exactly the crash window
+//! idempotent handlers have to survive.
+
+use crate::config::CommitmentLevel;
+use crate::core::backfill::status::RangeProgress;
+use crate::core::execution::usage::UsageRecord;
+use crate::storage::buffer::BufferedQuery;
+use crate::storage::partitions::PartitionReport;
+use crate::storage::retention::{RetentionConfig, RetentionReport};
+use crate::storage::vacuum::{VacuumConfig, VacuumReport};
+use crate::storage::{ConfirmationStatus, ConfirmationSummary, DeadLetter, StorageBackend};
+use crate::streams::{TransactionEvent, TransactionSource};
+use crate::testing::rng::XorShift;
+use crate::types::backfill_traits::BackfillRange;
+use crate::utils::error::{Result, SolanaIndexerError};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+/// `TransactionSource` wrapper that drops and delays events.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_sdk::testing::chaos::ChaosSource;
+/// use solana_indexer_sdk::testing::synthetic::SyntheticSource;
+/// use std::time::Duration;
+///
+/// // Lose 5% of events and add 200ms of latency to every batch.
+/// let source = ChaosSource::new(SyntheticSource::new(500))
+///     .with_drop_rate(0.05)
+///     .with_delay(Duration::from_millis(200));
+/// ```
+#[derive(Debug)]
+pub struct ChaosSource<S> {
+    inner: S,
+    /// Fraction of events dropped, `0.0..=1.0`.
+    drop_rate: f64,
+    /// Latency added before every batch is returned.
+    delay: Duration,
+    rng: XorShift,
+    dropped: u64,
+}
+
+impl<S: TransactionSource> ChaosSource<S> {
+    /// Wraps `inner` without injecting any faults.
+    #[must_use]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            drop_rate: 0.0,
+            delay: Duration::ZERO,
+            rng: XorShift::new(0xc4a05),
+            dropped: 0,
+        }
+    }
+
+    /// Drops each event with probability `rate` (`0.0..=1.0`).
+    #[must_use]
+    pub fn with_drop_rate(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+
+    /// Delays every batch by `delay`.
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Reseeds the fault generator.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = XorShift::new(seed);
+        self
+    }
+
+    /// Returns the number of events dropped so far.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+}
+
+#[async_trait]
+impl<S: TransactionSource> TransactionSource for ChaosSource<S> {
+    async fn next_batch(&mut self) -> Result<Vec<TransactionEvent>> {
+        let mut events = self.inner.next_batch().await?;
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+
+        let before = events.len();
+        events.retain(|_| !self.rng.chance(self.drop_rate));
+        self.dropped += (before - events.len()) as u64;
+        Ok(events)
+    }
+
+    fn source_name(&self) -> &str {
+        self.inner.source_name()
+    }
+}
+
+/// Local TCP proxy that delays responses from, and drops connections to, an
+/// RPC or WebSocket endpoint.
+///
+/// Unlike `ChaosSource`, the proxy sits below the indexer's own network
+/// code, so the built-in RPC polling, WebSocket and fetcher paths all see the
+/// faults. Point `with_rpc` at `ChaosProxy::url` and `with_ws` at
+/// `ChaosProxy::ws_url` of a proxy started in front of each endpoint. The
+/// upstream must be a plain `http://` or `ws://` endpoint (a local validator
+/// or mock server); TLS would fail on the proxy's host name.
+///
+/// Faults can be changed while the indexer runs:
+///
+/// - `set_delay` holds every chunk coming back from the upstream for a while,
+///   simulating a slow RPC node.
+/// - `set_drop_rate` closes a fraction of new connections before anything is
+///   forwarded.
+/// - `disconnect_all` closes every open connection, which is how a dropped
+///   WebSocket looks to the indexer.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_sdk::testing::chaos::ChaosProxy;
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let rpc = ChaosProxy::start("http://127.0.0.1:8899").await?;
+/// let ws = ChaosProxy::start("ws://127.0.0.1:8900").await?;
+/// rpc.set_delay(Duration::from_millis(500));
+/// // Run the indexer with `.with_rpc(rpc.url())` and `.with_ws(ws.ws_url(), ..)`,
+/// // then cut its subscriptions:
+/// ws.disconnect_all();
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChaosProxy {
+    address: SocketAddr,
+    faults: Arc<ProxyFaults>,
+    shutdown: CancellationToken,
+}
+
+/// Faults shared between a `ChaosProxy` and its connections.
+struct ProxyFaults {
+    /// Latency added to every chunk read from the upstream, in microseconds.
+    delay_micros: AtomicU64,
+    /// Fraction of new connections dropped, as `f64` bits.
+    drop_rate_bits: AtomicU64,
+    rng: Mutex<XorShift>,
+    /// Cancelled by `disconnect_all`, then replaced for later connections.
+    generation: Mutex<CancellationToken>,
+    dropped: AtomicU64,
+}
+
+impl ProxyFaults {
+    fn delay(&self) -> Duration {
+        Duration::from_micros(self.delay_micros.load(Ordering::Relaxed))
+    }
+
+    fn drop_next(&self) -> bool {
+        let rate = f64::from_bits(self.drop_rate_bits.load(Ordering::Relaxed));
+        self.rng
+            .lock()
+            .map(|mut rng| rng.chance(rate))
+            .unwrap_or(false)
+    }
+
+    fn generation(&self) -> CancellationToken {
+        self.generation
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl ChaosProxy {
+    /// Starts a proxy on a free local port forwarding to `upstream`, an
+    /// `http://` or `ws://` URL, without injecting any faults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if `upstream` has no host, or
+    /// `SolanaIndexerError::Internal` if no local port can be bound.
+    pub async fn start(upstream: &str) -> Result<Self> {
+        let url = reqwest::Url::parse(upstream)
+            .map_err(|e| SolanaIndexerError::Config(format!("Invalid upstream URL: {e}")))?;
+        let host = url
+            .host_str()
+            .ok_or_else(|| SolanaIndexerError::Config(format!("No host in {upstream}")))?;
+        let upstream = format!("{host}:{}", url.port_or_known_default().unwrap_or(80));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.map_err(|e| {
+            SolanaIndexerError::Internal(format!("Failed to start chaos proxy: {e}"))
+        })?;
+        let address = listener.local_addr().map_err(|e| {
+            SolanaIndexerError::Internal(format!("Failed to start chaos proxy: {e}"))
+        })?;
+
+        let faults = Arc::new(ProxyFaults {
+            delay_micros: AtomicU64::new(0),
+            drop_rate_bits: AtomicU64::new(0.0f64.to_bits()),
+            rng: Mutex::new(XorShift::new(0xc4a05)),
+            generation: Mutex::new(CancellationToken::new()),
+            dropped: AtomicU64::new(0),
+        });
+        let shutdown = CancellationToken::new();
+
+        let (accepting, stopped) = (faults.clone(), shutdown.clone());
+        tokio::spawn(async move {
+            loop {
+                let client = tokio::select! {
+                    () = stopped.cancelled() => break,
+                    accepted = listener.accept() => match accepted {
+                        Ok((client, _)) => client,
+                        Err(e) => {
+                            tracing::warn!("Chaos proxy stopped accepting: {e}");
+                            break;
+                        }
+                    },
+                };
+                if accepting.drop_next() {
+                    accepting.dropped.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                let cancelled = accepting.generation().child_token();
+                tokio::spawn(relay(
+                    client,
+                    upstream.clone(),
+                    accepting.clone(),
+                    cancelled,
+                    stopped.clone(),
+                ));
+            }
+        });
+
+        Ok(Self {
+            address,
+            faults,
+            shutdown,
+        })
+    }
+
+    /// Returns the URL to use as the RPC endpoint.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Returns the URL to use as the WebSocket endpoint.
+    #[must_use]
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.address)
+    }
+
+    /// Delays every chunk of every upstream response by `delay`.
+    pub fn set_delay(&self, delay: Duration) {
+        let micros = u64::try_from(delay.as_micros()).unwrap_or(u64::MAX);
+        self.faults.delay_micros.store(micros, Ordering::Relaxed);
+    }
+
+    /// Closes each new connection with probability `rate` (`0.0..=1.0`).
+    pub fn set_drop_rate(&self, rate: f64) {
+        self.faults
+            .drop_rate_bits
+            .store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Reseeds the fault generator.
+    pub fn set_seed(&self, seed: u64) {
+        if let Ok(mut rng) = self.faults.rng.lock() {
+            *rng = XorShift::new(seed);
+        }
+    }
+
+    /// Closes every open connection. Connections made afterwards are
+    /// forwarded as usual.
+    pub fn disconnect_all(&self) {
+        let mut generation = self
+            .faults
+            .generation
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        generation.cancel();
+        *generation = CancellationToken::new();
+    }
+
+    /// Returns the number of connections dropped so far, by the drop rate
+    /// or `disconnect_all`.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.faults.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for ChaosProxy {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Copies bytes both ways between `client` and `upstream` until either side
+/// closes or the connection is cut.
+async fn relay(
+    mut client: TcpStream,
+    upstream: String,
+    faults: Arc<ProxyFaults>,
+    cancelled: CancellationToken,
+    stopped: CancellationToken,
+) {
+    let Ok(mut server) = TcpStream::connect(&upstream).await else {
+        return;
+    };
+    let (mut client_read, mut client_write) = client.split();
+    let (mut server_read, mut server_write) = server.split();
+
+    let requests = tokio::io::copy(&mut client_read, &mut server_write);
+    let responses = async {
+        let mut buffer = vec![0u8; 16 * 1024];
+        loop {
+            let read = server_read.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok::<_, std::io::Error>(());
+            }
+            let delay = faults.delay();
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+            client_write.write_all(&buffer[..read]).await?;
+        }
+    };
+
+    tokio::select! {
+        () = cancelled.cancelled() => {
+            faults.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        () = stopped.cancelled() => {}
+        _ = requests => {}
+        _ = responses => {}
+    }
+}
+
+/// `StorageBackend` wrapper that fails a fraction of writes.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_sdk::storage::Storage;
+/// use solana_indexer_sdk::testing::chaos::ChaosStorage;
+/// use solana_indexer_sdk::{SolanaIndexer, SolanaIndexerConfigBuilder};
+/// use std::sync::Arc;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = SolanaIndexerConfigBuilder::new()
+///     .with_rpc("http://127.0.0.1:8899")
+///     .with_database("postgresql://localhost/chaos")
+///     .program_id("11111111111111111111111111111111")
+///     .build()?;
+/// let storage = Storage::new("postgresql://localhost/chaos").await?;
+///
+/// // Fail one in ten writes.
+/// let storage = Arc::new(ChaosStorage::new(storage).with_write_failure_rate(0.1));
+/// let indexer = SolanaIndexer::new_with_storage(config, storage);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ChaosStorage<S> {
+    inner: S,
+    /// Fraction of writes that fail, `0.0..=1.0`.
+    write_failure_rate: f64,
+    rng: Mutex<XorShift>,
+    failed_writes: AtomicU64,
+}
+
+impl<S: StorageBackend> ChaosStorage<S> {
+    /// Wraps `inner` without injecting any faults.
+    #[must_use]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            write_failure_rate: 0.0,
+            rng: Mutex::new(XorShift::new(0xc4a05)),
+            failed_writes: AtomicU64::new(0),
+        }
+    }
+
+    /// Fails each write with probability `rate` (`0.0..=1.0`).
+    #[must_use]
+    pub fn with_write_failure_rate(mut self, rate: f64) -> Self {
+        self.write_failure_rate = rate;
+        self
+    }
+
+    /// Reseeds the fault generator.
+    #[must_use]
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(XorShift::new(seed)),
+            ..self
+        }
+    }
+
+    /// Returns the number of writes failed so far.
+    #[must_use]
+    pub fn failed_writes(&self) -> u64 {
+        self.failed_writes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the wrapped backend.
+    #[must_use]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Decides whether the next write fails.
+    fn inject(&self, operation: &str) -> Result<()> {
+        let fail = self
+            .rng
+            .lock()
+            .map(|mut rng| rng.chance(self.write_failure_rate))
+            .unwrap_or(false);
+        if fail {
+            self.failed_writes.fetch_add(1, Ordering::Relaxed);
+            return Err(SolanaIndexerError::Storage(sqlx::Error::Protocol(format!(
+                "chaos: injected failure in {operation}"
+            ))));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<S: StorageBackend> StorageBackend for ChaosStorage<S> {
+    async fn initialize(&self) -> Result<()> {
+        self.inner.initialize().await
+    }
+
+    async fn is_processed(&self, signature: &str) -> Result<bool> {
+        self.inner.is_processed(signature).await
+    }
+
+    async fn mark_processed(&self, signature: &str, slot: u64) -> Result<()> {
+        self.inject("mark_processed")?;
+        self.inner.mark_processed(signature, slot).await
+    }
+
+    async fn mark_processed_batch(&self, entries: &[(String, u64)]) -> Result<()> {
+        self.inject("mark_processed_batch")?;
+        self.inner.mark_processed_batch(entries).await
+    }
+
+    async fn commit_transaction(
+        &self,
+        writes: Vec<BufferedQuery>,
+        signature: &str,
+        slot: u64,
+        block_hash: Option<&str>,
+    ) -> Result<()> {
+        self.inject("commit_transaction")?;
+        self.inner
+            .commit_transaction(writes, signature, slot, block_hash)
+            .await
+    }
+
+    async fn get_last_processed_slot(&self) -> Result<Option<u64>> {
+        self.inner.get_last_processed_slot().await
+    }
+
+    async fn get_last_processed_signature(&self) -> Result<Option<String>> {
+        self.inner.get_last_processed_signature().await
+    }
+
+    fn pool(&self) -> Option<&PgPool> {
+        self.inner.pool()
+    }
+
+    async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
+        self.inject("mark_tentative")?;
+        self.inner.mark_tentative(signature, slot, block_hash).await
+    }
+
+    async fn mark_finalized(&self, slot: u64, block_hash: &str) -> Result<()> {
+        self.inject("mark_finalized")?;
+        self.inner.mark_finalized(slot, block_hash).await
+    }
+
+    async fn get_tentative_transactions(&self, slot: u64) -> Result<Vec<String>> {
+        self.inner.get_tentative_transactions(slot).await
+    }
+
+    async fn rollback_slot(&self, slot: u64) -> Result<()> {
+        self.inject("rollback_slot")?;
+        self.inner.rollback_slot(slot).await
+    }
+
+    async fn get_block_hash(&self, slot: u64) -> Result<Option<String>> {
+        self.inner.get_block_hash(slot).await
+    }
+
+    async fn cleanup_stale_tentative_transactions(&self, slot_threshold: u64) -> Result<u64> {
+        self.inject("cleanup_stale_tentative_transactions")?;
+        self.inner
+            .cleanup_stale_tentative_transactions(slot_threshold)
+            .await
+    }
+
+    async fn get_tentative_slots_le(&self, slot: u64) -> Result<Vec<u64>> {
+        self.inner.get_tentative_slots_le(slot).await
+    }
+
+    async fn save_backfill_progress(&self, slot: u64) -> Result<()> {
+        self.inject("save_backfill_progress")?;
+        self.inner.save_backfill_progress(slot).await
+    }
+
+    async fn load_backfill_progress(&self) -> Result<Option<u64>> {
+        self.inner.load_backfill_progress().await
+    }
+
+    async fn mark_backfill_complete(&self) -> Result<()> {
+        self.inject("mark_backfill_complete")?;
+        self.inner.mark_backfill_complete().await
+    }
+
+    async fn begin_backfill_range(&self, range: BackfillRange) -> Result<(u64, Option<String>)> {
+        self.inject("begin_backfill_range")?;
+        self.inner.begin_backfill_range(range).await
+    }
+
+    async fn advance_backfill_range(
+        &self,
+        range: BackfillRange,
+        slot: u64,
+        cursor: Option<&str>,
+        transactions: u64,
+    ) -> Result<()> {
+        self.inject("advance_backfill_range")?;
+        self.inner
+            .advance_backfill_range(range, slot, cursor, transactions)
+            .await
+    }
+
+    async fn load_backfill_ranges(&self) -> Result<Vec<RangeProgress>> {
+        self.inner.load_backfill_ranges().await
+    }
+
+    async fn save_cursor(&self, address: &str, signature: &str, slot: u64) -> Result<()> {
+        self.inject("save_cursor")?;
+        self.inner.save_cursor(address, signature, slot).await
+    }
+
+    async fn load_cursor(&self, address: &str) -> Result<Option<String>> {
+        self.inner.load_cursor(address).await
+    }
+
+    async fn save_snapshot_slot(&self, program_id: &str, slot: u64) -> Result<()> {
+        self.inject("save_snapshot_slot")?;
+        self.inner.save_snapshot_slot(program_id, slot).await
+    }
+
+    async fn load_snapshot_slot(&self, program_id: &str) -> Result<Option<u64>> {
+        self.inner.load_snapshot_slot(program_id).await
+    }
+
+    async fn advance_watermark(&self, slot: u64) -> Result<()> {
+        self.inject("advance_watermark")?;
+        self.inner.advance_watermark(slot).await
+    }
+
+    async fn get_watermark(&self) -> Result<Option<u64>> {
+        self.inner.get_watermark().await
+    }
+
+    async fn record_commitment(
+        &self,
+        signature: &str,
+        slot: u64,
+        level: CommitmentLevel,
+    ) -> Result<()> {
+        self.inject("record_commitment")?;
+        self.inner.record_commitment(signature, slot, level).await
+    }
+
+    async fn load_unfinalized_signatures(
+        &self,
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner.load_unfinalized_signatures(after, limit).await
+    }
+
+    async fn load_processed_signatures(
+        &self,
+        slots: (u64, u64),
+        after: Option<(u64, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(u64, String)>> {
+        self.inner
+            .load_processed_signatures(slots, after, limit)
+            .await
+    }
+
+    async fn load_confirmation_status(
+        &self,
+        signature: &str,
+    ) -> Result<Option<ConfirmationStatus>> {
+        self.inner.load_confirmation_status(signature).await
+    }
+
+    async fn confirmation_summary(&self) -> Result<ConfirmationSummary> {
+        self.inner.confirmation_summary().await
+    }
+
+    async fn record_dead_letter(&self, signature: &str, slot: u64, reason: &str) -> Result<()> {
+        self.inject("record_dead_letter")?;
+        self.inner.record_dead_letter(signature, slot, reason).await
+    }
+
+    async fn load_dead_letters(&self, limit: usize) -> Result<Vec<DeadLetter>> {
+        self.inner.load_dead_letters(limit).await
+    }
+
+    async fn record_usage(&self, day: &str, records: &[UsageRecord]) -> Result<()> {
+        self.inject("record_usage")?;
+        self.inner.record_usage(day, records).await
+    }
+
+    async fn load_usage_report(&self, day: &str) -> Result<Vec<UsageRecord>> {
+        self.inner.load_usage_report(day).await
+    }
+
+    async fn maintain_partitions(&self, tip_slot: Option<u64>) -> Result<PartitionReport> {
+        self.inject("maintain_partitions")?;
+        self.inner.maintain_partitions(tip_slot).await
+    }
+
+    async fn prune(
+        &self,
+        config: &RetentionConfig,
+        tip_slot: Option<u64>,
+    ) -> Result<RetentionReport> {
+        self.inject("prune")?;
+        self.inner.prune(config, tip_slot).await
+    }
+
+    async fn vacuum(&self, config: &VacuumConfig) -> Result<VacuumReport> {
+        self.inject("vacuum")?;
+        self.inner.vacuum(config).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inject("flush")?;
+        self.inner.flush().await
+    }
+
+    fn take_dropped_signatures(&self) -> Vec<String> {
+        self.inner.take_dropped_signatures()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::Storage;
+    use crate::testing::synthetic::SyntheticSource;
+
+    #[tokio::test]
+    async fn test_source_drops_events() -> Result<()> {
+        let inner = SyntheticSource::new(10_000)
+            .with_tick(Duration::from_millis(50))
+            .with_limit(1_000);
+        let mut source = ChaosSource::new(inner).with_drop_rate(0.3);
+
+        let mut delivered = 0;
+        while source.inner.produced() < 1_000 {
+            delivered += source.next_batch().await?.len() as u64;
+        }
+
+        assert_eq!(delivered + source.dropped(), 1_000);
+        // 30% of 1,000 with generous slack for the generator
+        assert!((200..400).contains(&source.dropped()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_delays_and_drops_rpc_calls() -> Result<()> {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .mount(&server)
+            .await;
+        let proxy = ChaosProxy::start(&server.uri()).await?;
+        let call = || async {
+            reqwest::Client::new()
+                .post(proxy.url())
+                .body("{}")
+                .send()
+                .await?
+                .text()
+                .await
+        };
+
+        proxy.set_delay(Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        assert_eq!(call().await.ok().as_deref(), Some("ok"));
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        proxy.set_drop_rate(1.0);
+        assert!(call().await.is_err());
+        assert_eq!(proxy.dropped(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_proxy_disconnects_open_connections() -> Result<()> {
+        let upstream = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_url = format!("ws://{}", upstream.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = upstream.accept().await {
+                tokio::spawn(async move {
+                    let (mut read, mut write) = stream.split();
+                    let _ = tokio::io::copy(&mut read, &mut write).await;
+                });
+            }
+        });
+        let proxy = ChaosProxy::start(&upstream_url).await?;
+        let address = proxy.ws_url().trim_start_matches("ws://").to_string();
+
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        stream.write_all(b"ping").await.unwrap();
+        let mut echoed = [0u8; 4];
+        stream.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"ping");
+
+        proxy.disconnect_all();
+        let mut rest = Vec::new();
+        let closed = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut rest))
+            .await
+            .expect("connection was not closed");
+        assert!(matches!(closed, Ok(0) | Err(_)));
+        assert_eq!(proxy.dropped(), 1);
+
+        // New connections go through again
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+        stream.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"pong");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_fails_writes() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            let storage = ChaosStorage::new(storage).with_write_failure_rate(1.0);
+            storage.initialize().await?;
+
+            assert!(matches!(
+                storage.mark_processed("chaos_test_sig", 1).await,
+                Err(SolanaIndexerError::Storage(_))
+            ));
+            assert!(matches!(
+                storage
+                    .commit_transaction(Vec::new(), "chaos_test_sig", 1, None)
+                    .await,
+                Err(SolanaIndexerError::Storage(_))
+            ));
+            assert!(!storage.is_processed("chaos_test_sig").await?);
+            assert_eq!(storage.failed_writes(), 2);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_forwards_vacuum() -> Result<()> {
+        let db_url = std::env::var("DATABASE_URL")
+            .unwrap_or_else(|_| "postgresql://localhost/test".to_string());
+
+        if let Ok(storage) = Storage::new(&db_url).await {
+            storage.initialize().await?;
+            let config = VacuumConfig {
+                mode: crate::storage::vacuum::VacuumMode::Analyze,
+                ..VacuumConfig::default()
+            };
+
+            let storage = ChaosStorage::new(storage);
+            let report = storage.vacuum(&config).await?;
+            assert!(!report.tables.is_empty());
+
+            let storage = storage.with_write_failure_rate(1.0);
+            assert!(matches!(
+                storage.vacuum(&config).await,
+                Err(SolanaIndexerError::Storage(_))
+            ));
+            assert_eq!(storage.failed_writes(), 1);
+        }
+        Ok(())
+    }
+}
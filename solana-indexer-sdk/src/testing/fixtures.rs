@@ -0,0 +1,242 @@
+//! Transaction fixtures captured from a live RPC endpoint.
+//!
+//! `capture_fixtures` pulls the most recent transactions of a program and
+//! writes one JSON file per transaction into a directory. Files are named
+//! `<slot>-<signature>.json` with the slot zero-padded, so a directory
+//! listing is in slot order, and
+//! `load_fixtures` reads them back as the same
+//! `EncodedConfirmedTransactionWithStatusMeta` values the indexer fetches,
+//! ready to be fed to decoders and handlers in golden tests.
+//!
+//! Fixtures are sanitized before being written: validator rewards are
+//! stripped (they depend on the block, not the program under test) and keys
+//! are sorted so re-capturing the same transaction produces an identical file.
+
+use crate::core::execution::fetcher::Fetcher;
+use crate::utils::error::{Result, SolanaIndexerError};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::path::{Path, PathBuf};
+
+/// Fetches the `count` most recent transactions of `program_id` and writes
+/// them as sanitized JSON fixtures into `dir`.
+///
+/// Transactions that can no longer be fetched are skipped with a warning.
+///
+/// # Arguments
+///
+/// * `fetcher` - Fetcher connected to the RPC endpoint to capture from
+/// * `program_id` - Program (or any address) whose transactions to capture
+/// * `count` - Number of transactions to capture
+/// * `dir` - Output directory, created if missing
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Rpc` if the signature lookup fails, or
+/// `SolanaIndexerError::Internal` if a fixture cannot be written.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::Fetcher;
+/// # use solana_indexer_sdk::testing::fixtures::capture_fixtures;
+/// # use solana_sdk::commitment_config::CommitmentConfig;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let fetcher = Fetcher::new("https://api.mainnet-beta.solana.com", CommitmentConfig::confirmed());
+/// let program = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".parse()?;
+/// let written = capture_fixtures(&fetcher, &program, 50, "tests/fixtures/spl_token").await?;
+/// println!("Captured {} fixtures", written.len());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn capture_fixtures(
+    fetcher: &Fetcher,
+    program_id: &Pubkey,
+    count: usize,
+    dir: impl AsRef<Path>,
+) -> Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir).map_err(|e| {
+        SolanaIndexerError::Internal(format!(
+            "Failed to create fixture directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+
+    let signatures = fetcher.fetch_signatures(program_id, None, count).await?;
+
+    let mut written = Vec::with_capacity(signatures.len());
+    for signature in signatures {
+        match fetcher.fetch_transaction(&signature).await {
+            Ok(transaction) => written.push(write_fixture(dir, &signature, &transaction)?),
+            Err(e) => tracing::warn!("Skipping fixture {signature}: {e}"),
+        }
+    }
+
+    Ok(written)
+}
+
+/// Converts a transaction into its sanitized fixture representation.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Internal` if the transaction cannot be
+/// serialized.
+pub fn sanitize(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Result<Value> {
+    let mut value = serde_json::to_value(transaction).map_err(|e| {
+        SolanaIndexerError::Internal(format!("Failed to serialize transaction: {e}"))
+    })?;
+    if let Some(meta) = value.get_mut("meta").and_then(Value::as_object_mut) {
+        meta.remove("rewards");
+    }
+    Ok(value)
+}
+
+/// Writes `transaction` as a sanitized fixture into `dir`.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Internal` if the file cannot be written.
+pub fn write_fixture(
+    dir: impl AsRef<Path>,
+    signature: &Signature,
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<PathBuf> {
+    let path = dir
+        .as_ref()
+        .join(format!("{:020}-{signature}.json", transaction.slot));
+    let json = serde_json::to_string_pretty(&sanitize(transaction)?)
+        .map_err(|e| SolanaIndexerError::Internal(format!("Failed to serialize fixture: {e}")))?;
+
+    std::fs::write(&path, json + "\n").map_err(|e| {
+        SolanaIndexerError::Internal(format!("Failed to write fixture {}: {e}", path.display()))
+    })?;
+    Ok(path)
+}
+
+/// Loads a single fixture file.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Internal` if the file cannot be read, or
+/// `SolanaIndexerError::Decode` if it is not a valid transaction.
+pub fn load_fixture(path: impl AsRef<Path>) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        SolanaIndexerError::Internal(format!("Failed to read fixture {}: {e}", path.display()))
+    })?;
+    serde_json::from_str(&contents)
+        .map_err(|e| SolanaIndexerError::Decode(format!("Invalid fixture {}: {e}", path.display())))
+}
+
+/// Loads every `.json` fixture in `dir`, ordered by file name (slot order for
+/// captured fixtures).
+///
+/// # Errors
+///
+/// Returns an error if the directory or any fixture cannot be read.
+pub fn load_fixtures(
+    dir: impl AsRef<Path>,
+) -> Result<Vec<EncodedConfirmedTransactionWithStatusMeta>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        SolanaIndexerError::Internal(format!(
+            "Failed to read fixture directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths.iter().map(load_fixture).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::option_serializer::OptionSerializer;
+    use solana_transaction_status::{
+        EncodedTransaction, EncodedTransactionWithStatusMeta, Reward, UiMessage, UiRawMessage,
+        UiTransaction, UiTransactionStatusMeta,
+    };
+
+    fn transaction(slot: u64, signature: &Signature) -> EncodedConfirmedTransactionWithStatusMeta {
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot,
+            block_time: Some(1_700_000_000),
+            transaction: EncodedTransactionWithStatusMeta {
+                version: None,
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: vec![signature.to_string()],
+                    message: UiMessage::Raw(UiRawMessage {
+                        header: solana_sdk::message::MessageHeader::default(),
+                        account_keys: vec![Pubkey::new_unique().to_string()],
+                        recent_blockhash: String::new(),
+                        instructions: vec![],
+                        address_table_lookups: None,
+                    }),
+                }),
+                meta: Some(UiTransactionStatusMeta {
+                    err: None,
+                    status: Ok(()),
+                    fee: 5000,
+                    pre_balances: vec![10],
+                    post_balances: vec![5],
+                    inner_instructions: OptionSerializer::None,
+                    log_messages: OptionSerializer::Some(vec!["Program log: hi".into()]),
+                    pre_token_balances: OptionSerializer::None,
+                    post_token_balances: OptionSerializer::None,
+                    rewards: OptionSerializer::Some(vec![Reward {
+                        pubkey: Pubkey::new_unique().to_string(),
+                        lamports: 1,
+                        post_balance: 1,
+                        reward_type: None,
+                        commission: None,
+                    }]),
+                    loaded_addresses: OptionSerializer::Skip,
+                    return_data: OptionSerializer::Skip,
+                    compute_units_consumed: OptionSerializer::Skip,
+                }),
+            },
+        }
+    }
+
+    #[test]
+    fn test_sanitize_strips_rewards() -> Result<()> {
+        let value = sanitize(&transaction(1, &Signature::new_unique()))?;
+
+        assert!(value["meta"].get("rewards").is_none());
+        assert_eq!(value["meta"]["fee"], 5000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixture_roundtrip_in_slot_order() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("solstream-fixtures-{}", Signature::new_unique()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let (late, early) = (Signature::new_unique(), Signature::new_unique());
+        write_fixture(&dir, &late, &transaction(1000, &late))?;
+        let path = write_fixture(&dir, &early, &transaction(200, &early))?;
+        assert!(path.ends_with(format!("{:020}-{early}.json", 200)));
+
+        let loaded = load_fixtures(&dir)?;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            loaded.iter().map(|tx| tx.slot).collect::<Vec<_>>(),
+            vec![200, 1000]
+        );
+        let meta = loaded[0].transaction.meta.as_ref().unwrap();
+        assert_eq!(meta.fee, 5000);
+        assert!(!matches!(meta.rewards, OptionSerializer::Some(_)));
+        Ok(())
+    }
+}
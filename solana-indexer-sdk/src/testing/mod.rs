@@ -0,0 +1,21 @@
+//! Utilities for testing indexers without a live cluster.
+//!
+//! - `chaos` wraps sources and storage backends and proxies RPC and
+//!   WebSocket endpoints to drop events and connections, delay responses and
+//!   fail writes, for validating resilience guarantees.
+//! - `fixtures` captures real transactions from RPC into JSON files and loads
+//!   them back, so decoders and handlers can be exercised against recorded
+//!   mainnet traffic in golden tests.
+//! - `recorder` proxies JSON-RPC to a live endpoint and records the traffic
+//!   as `WireMock` stubs, so integration-test mocks come from a real run.
+//! - `replay` reports the table diffs of `SolanaIndexer::verify_idempotence`,
+//!   which replays archived transactions twice to prove handlers idempotent.
+//! - `synthetic` generates parsed transactions at a target rate for load
+//!   testing handlers and database sizing.
+
+pub mod chaos;
+pub mod fixtures;
+pub mod recorder;
+pub mod replay;
+mod rng;
+pub mod synthetic;
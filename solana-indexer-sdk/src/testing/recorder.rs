@@ -0,0 +1,363 @@
+//! Recording proxy for generating RPC mocks from a live endpoint.
+//!
+//! `RecordingProxy` listens on a local port and forwards every JSON-RPC
+//! request to a real endpoint, keeping each request with the response it
+//! got. Point an indexer (or any RPC client) at `RecordingProxy::url`, run it
+//! once against the cluster, then write the exchanges out with
+//! `RecordingProxy::write_stubs`.
+//!
+//! Stubs use the `WireMock` JSON mapping format, one file per call:
+//!
+//! ```json
+//! {
+//!   "request": {
+//!     "method": "POST",
+//!     "bodyPatterns": [{ "equalToJson": { "method": "getSlot", "params": [] },
+//!                        "ignoreExtraElements": true }]
+//!   },
+//!   "response": { "status": 200, "jsonBody": { "jsonrpc": "2.0", "result": 42, "id": 1 } }
+//! }
+//! ```
+//!
+//! The request is matched on its method and params only, since clients
+//! number requests differently from run to run. A standalone `WireMock`
+//! server can load the directory as is; Rust tests read it back with
+//! `load_stubs` and mount each call on a `wiremock::MockServer`:
+//!
+//! ```ignore
+//! for call in load_stubs("tests/stubs/jupiter")? {
+//!     Mock::given(method("POST"))
+//!         .and(body_partial_json(call.matcher()))
+//!         .respond_with(ResponseTemplate::new(call.status).set_body_json(&call.response))
+//!         .mount(&server)
+//!         .await;
+//! }
+//! ```
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+/// One JSON-RPC request and the response the upstream endpoint gave it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    /// Request body as sent by the client
+    pub request: Value,
+    /// Response body returned by the upstream endpoint
+    pub response: Value,
+    /// HTTP status of the upstream response
+    pub status: u16,
+}
+
+impl RecordedCall {
+    /// Returns the JSON-RPC method called, if the request is a single call.
+    #[must_use]
+    pub fn method(&self) -> Option<&str> {
+        self.request.get("method").and_then(Value::as_str)
+    }
+
+    /// Returns the part of the request a stub matches on: the body without
+    /// its `jsonrpc` and `id` members.
+    #[must_use]
+    pub fn matcher(&self) -> Value {
+        let mut matcher = self.request.clone();
+        if let Some(call) = matcher.as_object_mut() {
+            call.remove("jsonrpc");
+            call.remove("id");
+        }
+        matcher
+    }
+
+    /// Renders the call as a `WireMock` stub mapping.
+    #[must_use]
+    pub fn to_stub(&self) -> Value {
+        json!({
+            "request": {
+                "method": "POST",
+                "bodyPatterns": [{
+                    "equalToJson": self.matcher(),
+                    "ignoreExtraElements": true,
+                }],
+            },
+            "response": {
+                "status": self.status,
+                "jsonBody": self.response,
+                "headers": { "Content-Type": "application/json" },
+            },
+        })
+    }
+
+    /// Reads a call back from a stub written by `to_stub`.
+    fn from_stub(stub: &Value) -> Option<Self> {
+        Some(Self {
+            request: stub.pointer("/request/bodyPatterns/0/equalToJson")?.clone(),
+            response: stub.pointer("/response/jsonBody")?.clone(),
+            status: stub
+                .pointer("/response/status")
+                .and_then(Value::as_u64)
+                .and_then(|status| u16::try_from(status).ok())
+                .unwrap_or(200),
+        })
+    }
+}
+
+/// Local HTTP proxy recording the JSON-RPC traffic it forwards.
+///
+/// The proxy stops when dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// # use solana_indexer_sdk::testing::recorder::RecordingProxy;
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let proxy = RecordingProxy::start("https://api.mainnet-beta.solana.com").await?;
+/// // Run the indexer with `.with_rpc(proxy.url())` ...
+/// let written = proxy.write_stubs("tests/stubs/mainnet")?;
+/// println!("Recorded {} calls", written.len());
+/// # Ok(())
+/// # }
+/// ```
+pub struct RecordingProxy {
+    address: SocketAddr,
+    calls: Arc<Mutex<Vec<RecordedCall>>>,
+    shutdown: CancellationToken,
+}
+
+impl RecordingProxy {
+    /// Starts a proxy on a free local port forwarding to `upstream`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if no local port can be
+    /// bound.
+    pub async fn start(upstream: &str) -> Result<Self> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| {
+                listener.set_nonblocking(true)?;
+                Ok(listener)
+            })
+            .map_err(|e| proxy_error(&e))?;
+        let address = listener.local_addr().map_err(|e| proxy_error(&e))?;
+
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let shutdown = CancellationToken::new();
+        let client = reqwest::Client::new();
+        let upstream = upstream.to_string();
+
+        let recorded = calls.clone();
+        let service = make_service_fn(move |_| {
+            let (client, upstream, recorded) = (client.clone(), upstream.clone(), recorded.clone());
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    forward(client.clone(), upstream.clone(), recorded.clone(), request)
+                }))
+            }
+        });
+        let server = Server::from_tcp(listener)
+            .map_err(|e| proxy_error(&e))?
+            .serve(service)
+            .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+        tokio::spawn(async move {
+            if let Err(e) = server.await {
+                tracing::warn!("Recording proxy stopped: {e}");
+            }
+        });
+
+        Ok(Self {
+            address,
+            calls,
+            shutdown,
+        })
+    }
+
+    /// Returns the URL to use as the RPC endpoint.
+    #[must_use]
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Returns the calls recorded so far, in the order they completed.
+    #[must_use]
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.calls
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Writes every recorded call as a stub into `dir`, named
+    /// `<sequence>-<method>.json` so a listing is in call order.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if a file cannot be
+    /// written.
+    pub fn write_stubs(&self, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir).map_err(|e| {
+            SolanaIndexerError::Internal(format!(
+                "Failed to create stub directory {}: {e}",
+                dir.display()
+            ))
+        })?;
+
+        let calls = self.calls();
+        let mut written = Vec::with_capacity(calls.len());
+        for (sequence, call) in calls.iter().enumerate() {
+            let path = dir.join(format!(
+                "{sequence:05}-{}.json",
+                call.method().unwrap_or("batch")
+            ));
+            let json = serde_json::to_string_pretty(&call.to_stub()).map_err(|e| {
+                SolanaIndexerError::Internal(format!("Failed to serialize stub: {e}"))
+            })?;
+            std::fs::write(&path, json + "\n").map_err(|e| {
+                SolanaIndexerError::Internal(format!(
+                    "Failed to write stub {}: {e}",
+                    path.display()
+                ))
+            })?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+impl Drop for RecordingProxy {
+    fn drop(&mut self) {
+        self.shutdown.cancel();
+    }
+}
+
+/// Loads the stubs in `dir`, in file name order.
+///
+/// # Errors
+///
+/// Returns `SolanaIndexerError::Internal` if the directory or a file
+/// cannot be read, or `SolanaIndexerError::Decode` if a file is not a
+/// stub mapping.
+pub fn load_stubs(dir: impl AsRef<Path>) -> Result<Vec<RecordedCall>> {
+    let dir = dir.as_ref();
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        SolanaIndexerError::Internal(format!(
+            "Failed to read stub directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let contents = std::fs::read_to_string(path).map_err(|e| {
+                SolanaIndexerError::Internal(format!("Failed to read stub {}: {e}", path.display()))
+            })?;
+            serde_json::from_str(&contents)
+                .ok()
+                .as_ref()
+                .and_then(RecordedCall::from_stub)
+                .ok_or_else(|| {
+                    SolanaIndexerError::Decode(format!("Invalid stub {}", path.display()))
+                })
+        })
+        .collect()
+}
+
+/// Forwards one request upstream and records the exchange when both bodies
+/// are JSON. Upstream failures are answered with `502 Bad Gateway` and not
+/// recorded.
+async fn forward(
+    client: reqwest::Client,
+    upstream: String,
+    recorded: Arc<Mutex<Vec<RecordedCall>>>,
+    request: Request<Body>,
+) -> std::result::Result<Response<Body>, Infallible> {
+    let body = match hyper::body::to_bytes(request.into_body()).await {
+        Ok(body) => body,
+        Err(e) => return Ok(bad_gateway(&e.to_string())),
+    };
+    let upstream_response = match client
+        .post(&upstream)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_vec())
+        .send()
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => return Ok(bad_gateway(&e.to_string())),
+    };
+    let status = upstream_response.status().as_u16();
+    let response = match upstream_response.bytes().await {
+        Ok(response) => response,
+        Err(e) => return Ok(bad_gateway(&e.to_string())),
+    };
+
+    if let (Ok(request), Ok(response)) = (
+        serde_json::from_slice(&body),
+        serde_json::from_slice(&response),
+    ) {
+        recorded
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(RecordedCall {
+                request,
+                response,
+                status,
+            });
+    }
+
+    Ok(Response::builder()
+        .status(status)
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(response))
+        .unwrap_or_else(|_| bad_gateway("invalid upstream status")))
+}
+
+fn bad_gateway(reason: &str) -> Response<Body> {
+    let mut response = Response::new(Body::from(reason.to_string()));
+    *response.status_mut() = StatusCode::BAD_GATEWAY;
+    response
+}
+
+fn proxy_error(e: &dyn std::fmt::Display) -> SolanaIndexerError {
+    SolanaIndexerError::Internal(format!("Failed to start recording proxy: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stub_roundtrip() {
+        let call = RecordedCall {
+            request: json!({ "jsonrpc": "2.0", "id": 7, "method": "getSlot", "params": [] }),
+            response: json!({ "jsonrpc": "2.0", "id": 7, "result": 42 }),
+            status: 200,
+        };
+        assert_eq!(call.method(), Some("getSlot"));
+
+        let stub = call.to_stub();
+        assert_eq!(
+            stub["request"]["bodyPatterns"][0]["equalToJson"],
+            json!({ "method": "getSlot", "params": [] })
+        );
+
+        let restored = RecordedCall::from_stub(&stub).unwrap();
+        assert_eq!(restored.matcher(), call.matcher());
+        assert_eq!(restored.response, call.response);
+        assert_eq!(restored.status, 200);
+        assert!(RecordedCall::from_stub(&json!({})).is_none());
+    }
+}
@@ -0,0 +1,192 @@
+//! Deterministic replay verification.
+//!
+//! At-least-once delivery means a handler may see the same transaction more
+//! than once (after a crash between the handler and `mark_processed`, on
+//! reconnect overlap, or during gap repair). `SolanaIndexer::verify_idempotence`
+//! proves handlers tolerate that before it happens in production: it replays
+//! a set of archived transactions twice into a scratch Postgres schema,
+//! snapshots every table the handlers wrote after each pass, and reports the
+//! rows that differ.
+//!
+//! Archived transactions typically come from `testing::fixtures::load_fixtures`.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Rows of one table that changed between the two replay passes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDiff {
+    /// Table name within the scratch schema
+    pub table: String,
+    /// Rows present after the first pass but not after the second, as JSON
+    pub removed: Vec<String>,
+    /// Rows present after the second pass but not after the first, as JSON
+    pub added: Vec<String>,
+}
+
+/// Outcome of a replay verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Number of transactions replayed per pass
+    pub transactions: usize,
+    /// Number of handler tables compared
+    pub tables: usize,
+    /// Tables whose contents differ between the passes
+    pub diffs: Vec<TableDiff>,
+}
+
+impl ReplayReport {
+    /// Returns `true` if the second pass left every table unchanged.
+    #[must_use]
+    pub fn is_idempotent(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+/// Table contents keyed by table name, each a sorted list of JSON rows.
+pub(crate) type Snapshot = BTreeMap<String, Vec<String>>;
+
+/// Returns the signature of an archived transaction.
+pub(crate) fn transaction_signature(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<Signature> {
+    let signature = match &transaction.transaction.transaction {
+        EncodedTransaction::Json(ui_tx) => ui_tx
+            .signatures
+            .first()
+            .and_then(|s| Signature::from_str(s).ok()),
+        encoded => encoded
+            .decode()
+            .and_then(|tx| tx.signatures.first().copied()),
+    };
+    signature.ok_or_else(|| {
+        SolanaIndexerError::Decode(format!(
+            "Transaction at slot {} has no signature",
+            transaction.slot
+        ))
+    })
+}
+
+/// Reads every handler table in `schema`, skipping the SDK's own tables.
+pub(crate) async fn snapshot(pool: &PgPool, schema: &str) -> Result<Snapshot> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        r"
+        SELECT table_name::TEXT FROM information_schema.tables
+        WHERE table_schema = $1
+          AND table_type = 'BASE TABLE'
+          AND table_name NOT LIKE '\_solana\_indexer\_sdk\_%'
+        ORDER BY table_name
+        ",
+    )
+    .bind(schema)
+    .fetch_all(pool)
+    .await?;
+
+    let mut snapshot = Snapshot::new();
+    for table in tables {
+        let mut rows: Vec<String> = sqlx::query_scalar(&format!(
+            r#"SELECT row_to_json(t)::TEXT FROM "{schema}"."{table}" t"#
+        ))
+        .fetch_all(pool)
+        .await?;
+        rows.sort();
+        snapshot.insert(table, rows);
+    }
+    Ok(snapshot)
+}
+
+/// Compares two snapshots table by table.
+///
+/// Rows are compared as multisets, so duplicated inserts show up as added rows.
+pub(crate) fn diff(first: &Snapshot, second: &Snapshot) -> Vec<TableDiff> {
+    let empty = Vec::new();
+    let mut tables: Vec<&String> = first.keys().chain(second.keys()).collect();
+    tables.sort();
+    tables.dedup();
+
+    tables
+        .into_iter()
+        .filter_map(|table| {
+            let before = first.get(table).unwrap_or(&empty);
+            let after = second.get(table).unwrap_or(&empty);
+            let removed = multiset_difference(before, after);
+            let added = multiset_difference(after, before);
+            (!removed.is_empty() || !added.is_empty()).then(|| TableDiff {
+                table: table.clone(),
+                removed,
+                added,
+            })
+        })
+        .collect()
+}
+
+/// Returns the rows of sorted `left` that are not matched by a row in sorted `right`.
+fn multiset_difference(left: &[String], right: &[String]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut j = 0;
+    for row in left {
+        while j < right.len() && right[j] < *row {
+            j += 1;
+        }
+        if j < right.len() && right[j] == *row {
+            j += 1;
+        } else {
+            out.push(row.clone());
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(tables: &[(&str, &[&str])]) -> Snapshot {
+        tables
+            .iter()
+            .map(|(table, rows)| {
+                let mut rows: Vec<String> = rows.iter().map(ToString::to_string).collect();
+                rows.sort();
+                (table.to_string(), rows)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_snapshots_have_no_diff() {
+        let first = snapshot(&[("transfers", &[r#"{"id":1}"#, r#"{"id":2}"#])]);
+        assert!(diff(&first, &first.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reports_duplicates_and_changes() {
+        let first = snapshot(&[
+            ("transfers", &[r#"{"id":1}"#]),
+            ("totals", &[r#"{"total":10}"#]),
+        ]);
+        let second = snapshot(&[
+            ("transfers", &[r#"{"id":1}"#, r#"{"id":1}"#]),
+            ("totals", &[r#"{"total":20}"#]),
+        ]);
+
+        assert_eq!(
+            diff(&first, &second),
+            vec![
+                TableDiff {
+                    table: "totals".to_string(),
+                    removed: vec![r#"{"total":10}"#.to_string()],
+                    added: vec![r#"{"total":20}"#.to_string()],
+                },
+                TableDiff {
+                    table: "transfers".to_string(),
+                    removed: vec![],
+                    added: vec![r#"{"id":1}"#.to_string()],
+                },
+            ]
+        );
+    }
+}
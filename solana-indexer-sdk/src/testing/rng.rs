@@ -0,0 +1,28 @@
+//! Small deterministic random number generator shared by the testing utilities.
+
+/// xorshift64* generator: fast, seedable, and good enough for test data.
+#[derive(Debug, Clone)]
+pub(crate) struct XorShift {
+    state: u64,
+}
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // xorshift must not start from zero
+        Self { state: seed | 1 }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns `true` with probability `rate` (clamped to `0.0..=1.0`).
+    pub(crate) fn chance(&mut self, rate: f64) -> bool {
+        // 53 random bits give a uniform float in [0, 1)
+        let sample = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        sample < rate.clamp(0.0, 1.0)
+    }
+}
@@ -0,0 +1,552 @@
+//! Synthetic transaction source for load testing.
+//!
+//! `SyntheticSource` implements `TransactionSource` and emits fully parsed
+//! transactions at a target rate, so handlers and database sizing can be
+//! load-tested before pointing the indexer at mainnet. Three kinds of
+//! transactions are generated, mixed according to `SyntheticMix`:
+//!
+//! - System Program transfers (parsed `transfer` instructions with balances)
+//! - SPL Token transfers (parsed `transfer` instructions with token balances)
+//! - Anchor events (a partially decoded instruction plus a `Program data:`
+//!   log carrying a Borsh-encoded `TransferEvent`)
+//!
+//! Generation is deterministic for a given seed, so runs can be compared.
+
+use crate::streams::{TransactionEvent, TransactionSource};
+use crate::testing::rng::XorShift;
+use crate::types::events::TransferEvent;
+use crate::utils::error::Result;
+use async_trait::async_trait;
+use base64::Engine as _;
+use borsh::BorshSerialize;
+use serde_json::json;
+use solana_account_decoder::parse_token::UiTokenAmount;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::parse_accounts::ParsedAccount;
+use solana_transaction_status::parse_instruction::ParsedInstruction;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    EncodedTransactionWithStatusMeta, UiInstruction, UiMessage, UiParsedInstruction,
+    UiParsedMessage, UiPartiallyDecodedInstruction, UiTransaction, UiTransactionStatusMeta,
+    UiTransactionTokenBalance,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SYSTEM_PROGRAM: &str = "11111111111111111111111111111111";
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+const FEE: u64 = 5000;
+/// Average slot time on mainnet.
+const SLOT_DURATION_MS: u64 = 400;
+
+/// Relative weights of the generated transaction kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntheticMix {
+    /// Weight of System Program transfers (default: 50)
+    pub system_transfers: u32,
+    /// Weight of SPL Token transfers (default: 40)
+    pub spl_transfers: u32,
+    /// Weight of Anchor event transactions (default: 10)
+    pub anchor_events: u32,
+}
+
+impl Default for SyntheticMix {
+    fn default() -> Self {
+        Self {
+            system_transfers: 50,
+            spl_transfers: 40,
+            anchor_events: 10,
+        }
+    }
+}
+
+/// Transaction source that generates parsed transactions at a target rate.
+///
+/// # Example
+///
+/// ```no_run
+/// use solana_indexer_sdk::testing::synthetic::{SyntheticMix, SyntheticSource};
+/// use solana_indexer_sdk::{SolanaIndexer, SolanaIndexerConfigBuilder};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = SolanaIndexerConfigBuilder::new()
+///     .with_rpc("http://127.0.0.1:8899")
+///     .with_database("postgresql://localhost/loadtest")
+///     .program_id("11111111111111111111111111111111")
+///     .build()?;
+/// let indexer = SolanaIndexer::new(config).await?;
+///
+/// // 2,000 transactions per second, at most one million in total.
+/// let source = SyntheticSource::new(2_000)
+///     .with_mix(SyntheticMix { system_transfers: 0, spl_transfers: 80, anchor_events: 20 })
+///     .with_limit(1_000_000);
+/// indexer.start_with_source(source).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SyntheticSource {
+    /// Target transactions per second.
+    rate_per_sec: u64,
+    /// How often a batch is emitted.
+    tick: Duration,
+    mix: SyntheticMix,
+    /// Program that emits the Anchor events.
+    anchor_program: Pubkey,
+    /// Mint used for SPL transfers.
+    mint: Pubkey,
+    /// Wallet pool that senders and receivers are drawn from.
+    wallets: Vec<Pubkey>,
+    /// Stop after this many transactions (`None` = unbounded).
+    limit: Option<u64>,
+    start_slot: u64,
+    slot: u64,
+    produced: u64,
+    started: Option<Instant>,
+    rng: XorShift,
+}
+
+impl SyntheticSource {
+    /// Creates a source emitting `rate_per_sec` transactions per second.
+    #[must_use]
+    pub fn new(rate_per_sec: u64) -> Self {
+        let mut source = Self {
+            rate_per_sec,
+            tick: Duration::from_millis(100),
+            mix: SyntheticMix::default(),
+            anchor_program: Pubkey::default(),
+            mint: Pubkey::default(),
+            wallets: Vec::new(),
+            limit: None,
+            start_slot: 250_000_000,
+            slot: 250_000_000,
+            produced: 0,
+            started: None,
+            rng: XorShift::new(0),
+        };
+        source = source.with_seed(0x5eed).with_wallets(1_000);
+        source.anchor_program = source.random_pubkey();
+        source.mint = source.random_pubkey();
+        source
+    }
+
+    /// Sets the relative weights of the generated transaction kinds.
+    #[must_use]
+    pub fn with_mix(mut self, mix: SyntheticMix) -> Self {
+        self.mix = mix;
+        self
+    }
+
+    /// Sets the program that emits the generated Anchor events.
+    #[must_use]
+    pub fn with_anchor_program(mut self, program_id: Pubkey) -> Self {
+        self.anchor_program = program_id;
+        self
+    }
+
+    /// Sets the size of the wallet pool (default: 1,000).
+    ///
+    /// Smaller pools produce more rows per wallet, larger pools more distinct keys.
+    #[must_use]
+    pub fn with_wallets(mut self, count: usize) -> Self {
+        self.wallets = (0..count.max(2)).map(|_| self.random_pubkey()).collect();
+        self
+    }
+
+    /// Stops emitting transactions after `count` have been produced.
+    #[must_use]
+    pub fn with_limit(mut self, count: u64) -> Self {
+        self.limit = Some(count);
+        self
+    }
+
+    /// Sets how often a batch is emitted (default: 100ms).
+    #[must_use]
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Reseeds the generator. Sources with the same seed and settings
+    /// generate the same transactions.
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = XorShift::new(seed);
+        self
+    }
+
+    /// Returns the number of transactions produced so far.
+    #[must_use]
+    pub fn produced(&self) -> u64 {
+        self.produced
+    }
+
+    /// Returns `true` once the configured limit has been reached.
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.limit.is_some_and(|limit| self.produced >= limit)
+    }
+
+    /// Generates the next transaction, ignoring the rate limit.
+    pub fn next_transaction(&mut self) -> TransactionEvent {
+        let signature = self.random_signature();
+        let total = self.mix.system_transfers + self.mix.spl_transfers + self.mix.anchor_events;
+        let pick = if total == 0 {
+            0
+        } else {
+            (self.next_u64() % u64::from(total)) as u32
+        };
+
+        let tx = if pick < self.mix.system_transfers || total == 0 {
+            self.system_transfer(&signature)
+        } else if pick < self.mix.system_transfers + self.mix.spl_transfers {
+            self.spl_transfer(&signature)
+        } else {
+            self.anchor_event(&signature)
+        };
+
+        self.produced += 1;
+        TransactionEvent::FullTransaction {
+            signature,
+            slot: self.slot,
+            tx: Arc::new(tx),
+        }
+    }
+
+    fn system_transfer(
+        &mut self,
+        signature: &Signature,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let (from, to) = self.wallet_pair();
+        let lamports = 1_000 + self.next_u64() % 10_000_000_000;
+        let from_balance = lamports + FEE + self.next_u64() % 100_000_000_000;
+        let to_balance = self.next_u64() % 100_000_000_000;
+
+        let instruction = parsed_instruction(
+            "system",
+            SYSTEM_PROGRAM,
+            json!({
+                "type": "transfer",
+                "info": {
+                    "source": from.to_string(),
+                    "destination": to.to_string(),
+                    "lamports": lamports,
+                }
+            }),
+        );
+
+        self.transaction(
+            signature,
+            vec![
+                account(&from.to_string(), true, true),
+                account(&to.to_string(), true, false),
+                account(SYSTEM_PROGRAM, false, false),
+            ],
+            vec![instruction],
+            vec![from_balance, to_balance, 1],
+            vec![from_balance - lamports - FEE, to_balance + lamports, 1],
+            (vec![], vec![]),
+            vec![
+                format!("Program {SYSTEM_PROGRAM} invoke [1]"),
+                format!("Program {SYSTEM_PROGRAM} success"),
+            ],
+        )
+    }
+
+    fn spl_transfer(&mut self, signature: &Signature) -> EncodedConfirmedTransactionWithStatusMeta {
+        let (owner, receiver) = self.wallet_pair();
+        let source = Pubkey::find_program_address(&[owner.as_ref()], &self.mint).0;
+        let destination = Pubkey::find_program_address(&[receiver.as_ref()], &self.mint).0;
+        let amount = 1 + self.next_u64() % 1_000_000_000;
+        let source_balance = amount + self.next_u64() % 1_000_000_000_000;
+        let destination_balance = self.next_u64() % 1_000_000_000_000;
+
+        let instruction = parsed_instruction(
+            "spl-token",
+            TOKEN_PROGRAM,
+            json!({
+                "type": "transfer",
+                "info": {
+                    "source": source.to_string(),
+                    "destination": destination.to_string(),
+                    "authority": owner.to_string(),
+                    "amount": amount.to_string(),
+                }
+            }),
+        );
+
+        let mint = self.mint.to_string();
+        let token_balance = |index: u8, owner: &Pubkey, amount: u64| UiTransactionTokenBalance {
+            account_index: index,
+            mint: mint.clone(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: Some(amount as f64 / 1e6),
+                decimals: 6,
+                amount: amount.to_string(),
+                ui_amount_string: (amount as f64 / 1e6).to_string(),
+            },
+            owner: OptionSerializer::Some(owner.to_string()),
+            program_id: OptionSerializer::Some(TOKEN_PROGRAM.to_string()),
+        };
+        let pre_token = vec![
+            token_balance(1, &owner, source_balance),
+            token_balance(2, &receiver, destination_balance),
+        ];
+        let post_token = vec![
+            token_balance(1, &owner, source_balance - amount),
+            token_balance(2, &receiver, destination_balance + amount),
+        ];
+
+        let lamports = 1_000_000 + self.next_u64() % 10_000_000_000;
+        self.transaction(
+            signature,
+            vec![
+                account(&owner.to_string(), true, true),
+                account(&source.to_string(), true, false),
+                account(&destination.to_string(), true, false),
+                account(TOKEN_PROGRAM, false, false),
+            ],
+            vec![instruction],
+            vec![lamports, 2_039_280, 2_039_280, 1],
+            vec![lamports - FEE, 2_039_280, 2_039_280, 1],
+            (pre_token, post_token),
+            vec![
+                format!("Program {TOKEN_PROGRAM} invoke [1]"),
+                "Program log: Instruction: Transfer".to_string(),
+                format!("Program {TOKEN_PROGRAM} consumed 4645 of 200000 compute units"),
+                format!("Program {TOKEN_PROGRAM} success"),
+            ],
+        )
+    }
+
+    fn anchor_event(&mut self, signature: &Signature) -> EncodedConfirmedTransactionWithStatusMeta {
+        let (from, to) = self.wallet_pair();
+        let event = TransferEvent {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: 1 + self.next_u64() % 1_000_000_000,
+        };
+        let mut data = TransferEvent::discriminator().to_vec();
+        // Serializing into a Vec cannot fail.
+        let _ = event.serialize(&mut data);
+
+        let program = self.anchor_program.to_string();
+        let instruction = UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: program.clone(),
+                accounts: vec![from.to_string(), to.to_string()],
+                data: bs58::encode(TransferEvent::discriminator()).into_string(),
+                stack_height: None,
+            },
+        ));
+
+        let lamports = 1_000_000 + self.next_u64() % 10_000_000_000;
+        self.transaction(
+            signature,
+            vec![
+                account(&from.to_string(), true, true),
+                account(&to.to_string(), true, false),
+                account(&program, false, false),
+            ],
+            vec![instruction],
+            vec![lamports, 0, 1],
+            vec![lamports - FEE, 0, 1],
+            (vec![], vec![]),
+            vec![
+                format!("Program {program} invoke [1]"),
+                "Program log: Instruction: Transfer".to_string(),
+                format!(
+                    "Program data: {}",
+                    base64::engine::general_purpose::STANDARD.encode(&data)
+                ),
+                format!("Program {program} consumed 12034 of 200000 compute units"),
+                format!("Program {program} success"),
+            ],
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn transaction(
+        &mut self,
+        signature: &Signature,
+        account_keys: Vec<ParsedAccount>,
+        instructions: Vec<UiInstruction>,
+        pre_balances: Vec<u64>,
+        post_balances: Vec<u64>,
+        token_balances: (
+            Vec<UiTransactionTokenBalance>,
+            Vec<UiTransactionTokenBalance>,
+        ),
+        log_messages: Vec<String>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        let recent_blockhash = bs58::encode(self.random_bytes::<32>()).into_string();
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: self.slot,
+            block_time: Some(1_700_000_000 + (self.slot * SLOT_DURATION_MS / 1000) as i64),
+            transaction: EncodedTransactionWithStatusMeta {
+                version: None,
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: vec![signature.to_string()],
+                    message: UiMessage::Parsed(UiParsedMessage {
+                        account_keys,
+                        recent_blockhash,
+                        instructions,
+                        address_table_lookups: None,
+                    }),
+                }),
+                meta: Some(UiTransactionStatusMeta {
+                    err: None,
+                    status: Ok(()),
+                    fee: FEE,
+                    pre_balances,
+                    post_balances,
+                    inner_instructions: OptionSerializer::Some(vec![]),
+                    log_messages: OptionSerializer::Some(log_messages),
+                    pre_token_balances: OptionSerializer::Some(token_balances.0),
+                    post_token_balances: OptionSerializer::Some(token_balances.1),
+                    rewards: OptionSerializer::None,
+                    loaded_addresses: OptionSerializer::Skip,
+                    return_data: OptionSerializer::Skip,
+                    compute_units_consumed: OptionSerializer::Skip,
+                }),
+            },
+        }
+    }
+
+    fn wallet_pair(&mut self) -> (Pubkey, Pubkey) {
+        let len = self.wallets.len() as u64;
+        let from = (self.next_u64() % len) as usize;
+        let offset = 1 + (self.next_u64() % (len - 1)) as usize;
+        (
+            self.wallets[from],
+            self.wallets[(from + offset) % self.wallets.len()],
+        )
+    }
+
+    fn random_pubkey(&mut self) -> Pubkey {
+        Pubkey::new_from_array(self.random_bytes())
+    }
+
+    fn random_signature(&mut self) -> Signature {
+        Signature::from(self.random_bytes::<64>())
+    }
+
+    fn random_bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for chunk in bytes.chunks_mut(8) {
+            let value = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&value[..chunk.len()]);
+        }
+        bytes
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+}
+
+fn account(pubkey: &str, writable: bool, signer: bool) -> ParsedAccount {
+    ParsedAccount {
+        pubkey: pubkey.to_string(),
+        writable,
+        signer,
+        source: None,
+    }
+}
+
+fn parsed_instruction(program: &str, program_id: &str, parsed: serde_json::Value) -> UiInstruction {
+    UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+        program: program.to_string(),
+        program_id: program_id.to_string(),
+        parsed,
+        stack_height: None,
+    }))
+}
+
+#[async_trait]
+impl TransactionSource for SyntheticSource {
+    async fn next_batch(&mut self) -> Result<Vec<TransactionEvent>> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        tokio::time::sleep(self.tick).await;
+
+        // Emit however many transactions the target rate owes by now, so
+        // slow consumers are caught up instead of drifting below the rate.
+        let elapsed_ms = u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+        let mut due = self
+            .rate_per_sec
+            .saturating_mul(elapsed_ms)
+            .checked_div(1000)
+            .unwrap_or(0)
+            .saturating_sub(self.produced);
+        if let Some(limit) = self.limit {
+            due = due.min(limit.saturating_sub(self.produced));
+        }
+
+        self.slot = self.start_slot + elapsed_ms / SLOT_DURATION_MS;
+        Ok((0..due).map(|_| self.next_transaction()).collect())
+    }
+
+    fn source_name(&self) -> &str {
+        "Synthetic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::decoding::Decoder;
+    use crate::types::events::EventType;
+
+    fn transactions(source: &mut SyntheticSource, count: usize) -> Vec<TransactionEvent> {
+        (0..count).map(|_| source.next_transaction()).collect()
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let a = transactions(&mut SyntheticSource::new(10).with_seed(7), 20);
+        let b = transactions(&mut SyntheticSource::new(10).with_seed(7), 20);
+
+        let signatures = |events: &[TransactionEvent]| -> Vec<Signature> {
+            events.iter().map(TransactionEvent::signature).collect()
+        };
+        assert_eq!(signatures(&a), signatures(&b));
+    }
+
+    #[test]
+    fn test_anchor_events_decode() -> Result<()> {
+        let mut source = SyntheticSource::new(10).with_mix(SyntheticMix {
+            system_transfers: 0,
+            spl_transfers: 0,
+            anchor_events: 1,
+        });
+
+        let TransactionEvent::FullTransaction { tx, .. } = source.next_transaction() else {
+            panic!("synthetic source emits full transactions");
+        };
+        let decoded = Decoder::new().decode_transaction(&tx)?;
+
+        assert!(decoded
+            .events
+            .iter()
+            .any(|event| event.event_type == EventType::ProgramData && event.data.is_some()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rate_and_limit() -> Result<()> {
+        let mut source = SyntheticSource::new(1_000)
+            .with_tick(Duration::from_millis(20))
+            .with_limit(30);
+
+        let mut total = 0;
+        for _ in 0..5 {
+            total += source.next_batch().await?.len();
+        }
+
+        assert_eq!(total, 30);
+        assert!(source.is_exhausted());
+        Ok(())
+    }
+}
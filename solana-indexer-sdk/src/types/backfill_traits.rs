@@ -1,6 +1,8 @@
 use crate::config::RegistryConfig;
 use crate::core::execution::fetcher::Fetcher;
 use crate::core::registry::metrics::RegistryMetrics;
+use crate::storage::flags::FeatureFlags;
+use crate::storage::jobs::DeferredJobs;
 use crate::storage::StorageBackend;
 use crate::types::events::EventDiscriminator;
 use crate::types::metadata::TxMetadata;
@@ -102,7 +104,7 @@ pub trait BackfillProgress: Send + Sync {
 }
 
 /// Represents a range of slots to backfill.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BackfillRange {
     pub start_slot: u64,
     pub end_slot: u64,
@@ -140,6 +142,10 @@ impl BackfillRange {
 pub struct BackfillContext {
     /// Latest finalized slot from the chain.
     pub latest_finalized_slot: u64,
+    /// Latest confirmed slot, when slot tracking is enabled.
+    pub latest_confirmed_slot: Option<u64>,
+    /// Latest processed slot, when slot tracking is enabled.
+    pub latest_processed_slot: Option<u64>,
     /// Last slot that was successfully backfilled (if any).
     pub last_backfilled_slot: Option<u64>,
     /// Maximum depth to backfill (slots behind latest finalized).
@@ -348,17 +354,14 @@ where
     ) -> Result<()> {
         // Verify discriminator matches
         if *discriminator != T::discriminator() {
-            return Err(SolanaIndexerError::DecodingError(
+            return Err(SolanaIndexerError::Decode(
                 "Discriminator mismatch".to_string(),
             ));
         }
 
         // Deserialize event
         let event = T::try_from_slice(data).map_err(|e| {
-            SolanaIndexerError::DecodingError(format!(
-                "Failed to deserialize backfill event: {}",
-                e
-            ))
+            SolanaIndexerError::Decode(format!("Failed to deserialize backfill event: {}", e))
         })?;
 
         // Delegate to typed handler
@@ -386,6 +389,8 @@ pub struct BackfillHandlerRegistry {
     /// Map of discriminators to handlers
     handlers: HashMap<[u8; 8], Box<dyn DynamicBackfillHandler>>,
     metrics: RegistryMetrics,
+    flags: FeatureFlags,
+    jobs: DeferredJobs,
 }
 
 impl BackfillHandlerRegistry {
@@ -395,6 +400,8 @@ impl BackfillHandlerRegistry {
         Self {
             handlers: HashMap::new(),
             metrics: RegistryMetrics::new("BackfillHandler", 0),
+            flags: FeatureFlags::default(),
+            jobs: DeferredJobs::default(),
         }
     }
 
@@ -403,9 +410,39 @@ impl BackfillHandlerRegistry {
         Self {
             handlers: HashMap::new(),
             metrics: RegistryMetrics::new("BackfillHandler", config.max_handlers),
+            flags: FeatureFlags::default(),
+            jobs: DeferredJobs::default(),
         }
     }
 
+    /// Exposes `flags` to backfill handlers through `TxMetadata::flags`.
+    #[must_use]
+    pub fn with_feature_flags(mut self, flags: FeatureFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Returns the feature flags set with `with_feature_flags`, or flags
+    /// that are all disabled.
+    #[must_use]
+    pub fn feature_flags(&self) -> &FeatureFlags {
+        &self.flags
+    }
+
+    /// Lets backfill handlers schedule jobs through `TxMetadata::jobs`.
+    #[must_use]
+    pub fn with_deferred_jobs(mut self, jobs: DeferredJobs) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Returns the jobs handle set with `with_deferred_jobs`, or one that
+    /// refuses every job.
+    #[must_use]
+    pub fn deferred_jobs(&self) -> &DeferredJobs {
+        &self.jobs
+    }
+
     /// Registers a backfill handler for a specific event discriminator.
     ///
     /// # Arguments
@@ -440,7 +477,7 @@ impl BackfillHandlerRegistry {
     /// * `db` - Database connection pool
     ///
     /// # Errors
-    /// Returns `SolanaIndexerError::DecodingError` if no handler is registered
+    /// Returns `SolanaIndexerError::Decode` if no handler is registered
     /// for the discriminator, or propagates handler errors.
     pub async fn handle_backfill(
         &self,
@@ -451,7 +488,7 @@ impl BackfillHandlerRegistry {
     ) -> Result<()> {
         self.metrics.inc_calls();
         let handler = self.handlers.get(discriminator).ok_or_else(|| {
-            SolanaIndexerError::DecodingError(format!(
+            SolanaIndexerError::Decode(format!(
                 "No backfill handler registered for discriminator: {discriminator:?}"
             ))
         })?;
@@ -4,6 +4,8 @@
 //! IDL-based type generation. In a full implementation, these types would
 //! be automatically generated from Solana program IDLs during compilation.
 
+use crate::types::schema::{FieldLayout, TableSchema};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -35,7 +37,8 @@ pub enum EventType {
 ///
 /// The discriminator is the first 8 bytes of the SHA256 hash of the
 /// event name prefixed with "event:". This matches Anchor's event
-/// discriminator calculation.
+/// discriminator calculation; see `DiscriminatorStrategy` for other
+/// schemes.
 ///
 /// # Arguments
 ///
@@ -203,6 +206,60 @@ impl WithdrawEvent {
 pub trait EventDiscriminator {
     /// Returns the 8-byte discriminator for this event type.
     fn discriminator() -> [u8; 8];
+
+    /// Describes the fields of the type's Borsh encoding, in order, so they
+    /// can be checked against an IDL. Implemented by `#[derive(IndexerEvent)]`
+    /// and IDL-generated types; the default describes none.
+    fn borsh_layout() -> Vec<FieldLayout> {
+        Vec::new()
+    }
+}
+
+/// An event type that decodes itself from discriminator-prefixed Borsh
+/// data and can describe the table it is stored in.
+///
+/// Usually implemented with `#[derive(IndexerEvent)]` (`derive` feature),
+/// which also implements `EventDiscriminator`:
+///
+/// ```ignore
+/// use borsh::{BorshDeserialize, BorshSerialize};
+/// use solana_indexer_sdk::IndexerEvent;
+///
+/// #[derive(BorshSerialize, BorshDeserialize, IndexerEvent)]
+/// #[indexer(table = "swaps")]
+/// pub struct SwapEvent {
+///     /// Pool the swap went through
+///     pub pool: Pubkey,
+///     /// Input amount
+///     pub amount_in: u64,
+/// }
+/// ```
+pub trait IndexerEvent: EventDiscriminator + BorshDeserialize {
+    /// Name the discriminator is computed from, normally the type name.
+    fn event_name() -> &'static str;
+
+    /// Describes a table with one column per field, if the event declares
+    /// one.
+    fn table_schema() -> Option<TableSchema> {
+        None
+    }
+
+    /// Decodes an event from its discriminator followed by its Borsh
+    /// encoding, as Anchor emits them. Returns `None` if the discriminator
+    /// does not match or the data does not decode.
+    fn decode_event(data: &[u8]) -> Option<Self> {
+        let data = data.strip_prefix(&Self::discriminator()[..])?;
+        Self::try_from_slice(data).ok()
+    }
+
+    /// Decodes an event from a `Program data:` log line.
+    fn decode_log(event: &ParsedEvent) -> Option<Self> {
+        if event.event_type != EventType::ProgramData {
+            return None;
+        }
+        let data = BASE64.decode(event.data.as_deref()?).ok()?;
+        Self::decode_event(&data)
+    }
 }
 
 impl EventDiscriminator for TransferEvent {
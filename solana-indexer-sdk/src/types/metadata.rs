@@ -1,5 +1,25 @@
+use crate::storage::atomic::TxWrites;
+use crate::storage::buffer::BufferedQuery;
+use crate::storage::flags::FeatureFlags;
+use crate::storage::jobs::{DeferredJob, DeferredJobs};
+use crate::utils::error::Result;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiCompiledInstruction,
+    UiInnerInstructions, UiInstruction, UiLoadedAddresses, UiMessage, UiParsedInstruction,
+    UiTransactionReturnData, UiTransactionTokenBalance,
+};
+use sqlx::PgPool;
+use std::time::Duration;
+
 /// Rich transaction context passed to EventHandlers.
-#[derive(Debug, Clone)]
+///
+/// Outside the SDK, build one with `TxMetadata::new` and set the fields
+/// needed, e.g. to test a handler. The runtime handles (`flags`, `jobs` and
+/// `writes`) are set by the indexer and only reachable through accessors.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TxMetadata {
     /// The slot number where the transaction was confirmed.
     pub slot: u64,
@@ -17,10 +37,487 @@ pub struct TxMetadata {
     pub post_token_balances: Vec<TokenBalanceInfo>,
     /// The transaction signature.
     pub signature: String,
+    /// The on-chain error if the transaction failed, `None` on success.
+    pub err: Option<String>,
+    /// `true` if the RPC response had no `meta`, in which case the fee,
+    /// balances and error above are empty rather than observed.
+    pub meta_missing: bool,
+    /// Position of the transaction within its block, for ordering
+    /// transactions of the same slot. `None` when the block was not looked
+    /// up; see `with_block_index`.
+    pub index_in_block: Option<u32>,
+    /// Text of the transaction's Memo program instructions, joined with
+    /// `"; "` when there are several. `None` when it carries no memo.
+    pub memo: Option<String>,
+    /// Data the transaction returned with `set_return_data`, `None` when it
+    /// returned nothing or `meta` is missing.
+    pub return_data: Option<ReturnData>,
+    /// Compute unit limit requested with a ComputeBudget
+    /// `SetComputeUnitLimit` instruction, `None` when the default applies.
+    pub compute_unit_limit: Option<u32>,
+    /// Compute unit price, in micro-lamports, set with a ComputeBudget
+    /// `SetComputeUnitPrice` instruction. `None` when no priority fee is paid.
+    pub compute_unit_price: Option<u64>,
+    /// Feature flags, see `flags`.
+    #[serde(skip)]
+    pub(crate) flags: FeatureFlags,
+    /// Deferred jobs, see `schedule_in`.
+    #[serde(skip)]
+    pub(crate) jobs: DeferredJobs,
+    /// Handler writes of this transaction, see `execute`.
+    #[serde(skip)]
+    pub(crate) writes: TxWrites,
+}
+
+impl TxMetadata {
+    /// Creates the context of transaction `signature` at `slot`, with every
+    /// other field empty, no feature flag enabled and deferred jobs refused.
+    #[must_use]
+    pub fn new(signature: impl Into<String>, slot: u64) -> Self {
+        Self {
+            signature: signature.into(),
+            slot,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the feature flags read from `_solana_indexer_sdk_flags`. Every
+    /// flag is disabled unless `with_feature_flags` is set.
+    #[must_use]
+    pub fn flags(&self) -> &FeatureFlags {
+        &self.flags
+    }
+
+    /// Returns the deferred jobs queue, see `schedule_in`. Jobs are refused
+    /// unless `with_deferred_jobs` is set.
+    #[must_use]
+    pub fn jobs(&self) -> &DeferredJobs {
+        &self.jobs
+    }
+
+    /// Returns the handler writes of this transaction, see `execute`. They
+    /// are held for one atomic commit if `with_atomic_transactions` is set.
+    #[must_use]
+    pub fn writes(&self) -> &TxWrites {
+        &self.writes
+    }
+
+    /// Builds the context of a fetched transaction. A missing `meta` leaves
+    /// the fee and balances empty and sets `meta_missing`.
+    pub(crate) fn from_transaction(
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        signature: String,
+        err: Option<String>,
+    ) -> Self {
+        let mut context = Self {
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature,
+            err,
+            meta_missing: true,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: FeatureFlags::default(),
+            jobs: DeferredJobs::default(),
+            writes: TxWrites::default(),
+        };
+        if let Some(meta) = &transaction.transaction.meta {
+            let token_balances =
+                |balances: &solana_transaction_status::option_serializer::OptionSerializer<
+                    Vec<UiTransactionTokenBalance>,
+                >| {
+                    let balances: Option<Vec<UiTransactionTokenBalance>> = balances.clone().into();
+                    balances
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(TokenBalanceInfo::from)
+                        .collect()
+                };
+            context.fee = meta.fee;
+            context.pre_balances = meta.pre_balances.clone();
+            context.post_balances = meta.post_balances.clone();
+            context.pre_token_balances = token_balances(&meta.pre_token_balances);
+            context.post_token_balances = token_balances(&meta.post_token_balances);
+            context.return_data =
+                Option::<&UiTransactionReturnData>::from(meta.return_data.as_ref())
+                    .and_then(ReturnData::decode);
+            context.meta_missing = false;
+        }
+        context.memo = memo(transaction);
+        (context.compute_unit_limit, context.compute_unit_price) = compute_budget(transaction);
+        context
+    }
+
+    /// Schedules `job` to run once `delay` has passed, returning its ID.
+    /// See `DeferredJobs::schedule_in`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Config` if deferred jobs are not
+    /// enabled, or the error of storing the job.
+    pub async fn schedule_in<J: DeferredJob>(&self, delay: Duration, job: &J) -> Result<i64> {
+        self.jobs.schedule_in(delay, job).await
+    }
+
+    /// Runs `query` as part of this transaction's handler writes. See
+    /// `TxWrites::execute`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Storage` if a query run right away
+    /// fails.
+    pub async fn execute(&self, query: BufferedQuery, db: &PgPool) -> Result<()> {
+        self.writes.execute(query, db).await
+    }
+
+    /// Returns the priority fee in lamports, the compute unit price times
+    /// the requested limit, rounded up. `None` unless both were set by
+    /// ComputeBudget instructions.
+    #[must_use]
+    pub fn priority_fee(&self) -> Option<u64> {
+        let micro_lamports =
+            u128::from(self.compute_unit_price?) * u128::from(self.compute_unit_limit?);
+        u64::try_from(micro_lamports.div_ceil(1_000_000)).ok()
+    }
+}
+
+/// Data returned by a program with `set_return_data`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReturnData {
+    /// The program that set the data.
+    pub program_id: Pubkey,
+    /// The returned bytes.
+    pub data: Vec<u8>,
+}
+
+impl ReturnData {
+    /// Decodes the base64 return data reported by the RPC node.
+    fn decode(return_data: &UiTransactionReturnData) -> Option<Self> {
+        Some(Self {
+            program_id: return_data.program_id.parse().ok()?,
+            data: BASE64.decode(&return_data.data.0).ok()?,
+        })
+    }
+}
+
+/// Program ID of the ComputeBudget program.
+const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+/// Reads the compute unit limit and price set by the ComputeBudget
+/// instructions of a transaction. Only top-level instructions take effect.
+fn compute_budget(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> (Option<u32>, Option<u64>) {
+    let EncodedTransaction::Json(ui_tx) = &transaction.transaction.transaction else {
+        return (None, None);
+    };
+    let instructions: Vec<Vec<u8>> = match &ui_tx.message {
+        UiMessage::Parsed(msg) => msg
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial))
+                    if partial.program_id == COMPUTE_BUDGET_PROGRAM_ID =>
+                {
+                    bs58::decode(&partial.data).into_vec().ok()
+                }
+                _ => None,
+            })
+            .collect(),
+        UiMessage::Raw(msg) => msg
+            .instructions
+            .iter()
+            .filter(|instruction| {
+                msg.account_keys
+                    .get(usize::from(instruction.program_id_index))
+                    .is_some_and(|key| key == COMPUTE_BUDGET_PROGRAM_ID)
+            })
+            .filter_map(|instruction| bs58::decode(&instruction.data).into_vec().ok())
+            .collect(),
+    };
+
+    let (mut limit, mut price) = (None, None);
+    for data in instructions {
+        // Borsh enum: a one-byte variant tag, then the little-endian value
+        match data.split_first() {
+            Some((2, value)) => limit = value.try_into().ok().map(u32::from_le_bytes),
+            Some((3, value)) => price = value.try_into().ok().map(u64::from_le_bytes),
+            _ => {}
+        }
+    }
+    (limit, price)
+}
+
+/// Program IDs of the Memo program, v1 and v2.
+const MEMO_PROGRAM_IDS: [&str; 2] = [
+    "Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo",
+    "MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr",
+];
+
+/// Collects the memos of a transaction, from its top-level and inner
+/// instructions, in execution order.
+fn memo(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Option<String> {
+    let EncodedTransaction::Json(ui_tx) = &transaction.transaction.transaction else {
+        return None;
+    };
+    let (account_keys, top_level): (Vec<&str>, Vec<Option<String>>) = match &ui_tx.message {
+        UiMessage::Parsed(msg) => {
+            let keys: Vec<&str> = msg.account_keys.iter().map(|k| k.pubkey.as_str()).collect();
+            let memos = msg
+                .instructions
+                .iter()
+                .map(|instruction| memo_text(instruction, &keys))
+                .collect();
+            (keys, memos)
+        }
+        UiMessage::Raw(msg) => {
+            let keys: Vec<&str> = msg.account_keys.iter().map(String::as_str).collect();
+            let memos = msg
+                .instructions
+                .iter()
+                .map(|instruction| compiled_memo_text(instruction, &keys))
+                .collect();
+            (keys, memos)
+        }
+    };
+    let inner = transaction.transaction.meta.as_ref().and_then(|meta| {
+        Option::<&Vec<UiInnerInstructions>>::from(meta.inner_instructions.as_ref())
+    });
+
+    let mut memos = Vec::new();
+    for (index, memo) in top_level.into_iter().enumerate() {
+        memos.extend(memo);
+        for inner in inner
+            .into_iter()
+            .flatten()
+            .filter(|inner| usize::from(inner.index) == index)
+        {
+            memos.extend(
+                inner
+                    .instructions
+                    .iter()
+                    .filter_map(|instruction| memo_text(instruction, &account_keys)),
+            );
+        }
+    }
+    (!memos.is_empty()).then(|| memos.join("; "))
+}
+
+/// Returns the text of a Memo program instruction.
+fn memo_text(instruction: &UiInstruction, account_keys: &[&str]) -> Option<String> {
+    let (program_id, data) = match instruction {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+            // The node parses memos into their text
+            return MEMO_PROGRAM_IDS
+                .contains(&parsed.program_id.as_str())
+                .then(|| parsed.parsed.as_str().map(ToString::to_string))
+                .flatten();
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => {
+            (partial.program_id.as_str(), &partial.data)
+        }
+        UiInstruction::Compiled(compiled) => return compiled_memo_text(compiled, account_keys),
+    };
+    raw_memo_text(program_id, data)
+}
+
+fn compiled_memo_text(
+    instruction: &UiCompiledInstruction,
+    account_keys: &[&str],
+) -> Option<String> {
+    let program_id = account_keys.get(usize::from(instruction.program_id_index))?;
+    raw_memo_text(program_id, &instruction.data)
+}
+
+/// Decodes the base58 data of an instruction to `program_id` as memo text.
+fn raw_memo_text(program_id: &str, data: &str) -> Option<String> {
+    if !MEMO_PROGRAM_IDS.contains(&program_id) {
+        return None;
+    }
+    let bytes = bs58::decode(data).into_vec().ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// An account of a transaction's message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionAccount {
+    /// Address of the account.
+    pub pubkey: Pubkey,
+    /// Whether the transaction is signed by the account.
+    pub signer: bool,
+    /// Whether the transaction may write to the account.
+    pub writable: bool,
+}
+
+impl TransactionAccount {
+    /// Returns the accounts of a fetched transaction in message order: the
+    /// static keys, then the writable and readonly addresses loaded from
+    /// lookup tables. Compiled instructions index into this list.
+    #[must_use]
+    pub fn from_transaction(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Vec<Self> {
+        let EncodedTransaction::Json(ui_tx) = &transaction.transaction.transaction else {
+            return Vec::new();
+        };
+        let account = |pubkey: &str, signer: bool, writable: bool| {
+            Some(Self {
+                pubkey: pubkey.parse().ok()?,
+                signer,
+                writable,
+            })
+        };
+        match &ui_tx.message {
+            // Parsed messages already list loaded addresses with their flags
+            UiMessage::Parsed(msg) => msg
+                .account_keys
+                .iter()
+                .filter_map(|key| account(&key.pubkey, key.signer, key.writable))
+                .collect(),
+            UiMessage::Raw(msg) => {
+                let header = &msg.header;
+                let signers = usize::from(header.num_required_signatures);
+                let writable_signers =
+                    signers.saturating_sub(usize::from(header.num_readonly_signed_accounts));
+                let writable_unsigned = msg
+                    .account_keys
+                    .len()
+                    .saturating_sub(usize::from(header.num_readonly_unsigned_accounts));
+                let mut accounts: Vec<Self> = msg
+                    .account_keys
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, key)| {
+                        let writable = if index < signers {
+                            index < writable_signers
+                        } else {
+                            index < writable_unsigned
+                        };
+                        account(key, index < signers, writable)
+                    })
+                    .collect();
+                let loaded = transaction.transaction.meta.as_ref().and_then(|meta| {
+                    Option::<&UiLoadedAddresses>::from(meta.loaded_addresses.as_ref())
+                });
+                if let Some(loaded) = loaded {
+                    accounts.extend(
+                        loaded
+                            .writable
+                            .iter()
+                            .filter_map(|key| account(key, false, true)),
+                    );
+                    accounts.extend(
+                        loaded
+                            .readonly
+                            .iter()
+                            .filter_map(|key| account(key, false, false)),
+                    );
+                }
+                accounts
+            }
+        }
+    }
+}
+
+/// Where an instruction sits within its transaction, passed to
+/// `InstructionDecoder::decode_in_context`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct InstructionContext {
+    /// Position of the instruction among the message's instructions, or for
+    /// an inner instruction, among those recorded under its parent.
+    pub index: usize,
+    /// Index of the top-level instruction an inner instruction was invoked
+    /// under; `None` for top-level instructions.
+    pub parent_index: Option<usize>,
+    /// Invocation depth: 1 for top-level instructions, 2 and up for CPIs.
+    /// `None` for inner instructions when the RPC node did not report it.
+    pub stack_height: Option<u32>,
+    /// Program the instruction calls; `None` if it could not be resolved.
+    pub program_id: Option<Pubkey>,
+    /// Accounts of the transaction, which the `accounts` and
+    /// `program_id_index` of compiled instructions index into. Empty when
+    /// the transaction's message was not available.
+    pub account_keys: Vec<TransactionAccount>,
+}
+
+impl InstructionContext {
+    /// Returns `true` for instructions invoked through a CPI.
+    #[must_use]
+    pub fn is_inner(&self) -> bool {
+        self.parent_index.is_some()
+    }
+
+    /// Returns the transaction account at `index`.
+    #[must_use]
+    pub fn account(&self, index: u8) -> Option<&TransactionAccount> {
+        self.account_keys.get(usize::from(index))
+    }
+
+    /// Returns the program `instruction` calls, resolving the program index
+    /// of compiled instructions.
+    #[must_use]
+    pub fn program_id_of(&self, instruction: &UiInstruction) -> Option<Pubkey> {
+        match instruction {
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+                parsed.program_id.parse().ok()
+            }
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => {
+                partial.program_id.parse().ok()
+            }
+            UiInstruction::Compiled(compiled) => self
+                .account(compiled.program_id_index)
+                .map(|account| account.pubkey),
+        }
+    }
+
+    /// Returns the accounts `instruction` is passed, in order, with their
+    /// signer and writable flags.
+    ///
+    /// Indices of compiled instructions are resolved against
+    /// `account_keys`. Returns `None` for instructions the RPC node parsed,
+    /// which carry no account list, and if an account cannot be resolved.
+    #[must_use]
+    pub fn instruction_accounts(
+        &self,
+        instruction: &UiInstruction,
+    ) -> Option<Vec<TransactionAccount>> {
+        match instruction {
+            UiInstruction::Compiled(compiled) => compiled
+                .accounts
+                .iter()
+                .map(|&index| self.account(index).copied())
+                .collect(),
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => partial
+                .accounts
+                .iter()
+                .map(|key| {
+                    let pubkey: Pubkey = key.parse().ok()?;
+                    Some(
+                        self.account_keys
+                            .iter()
+                            .find(|account| account.pubkey == pubkey)
+                            .copied()
+                            .unwrap_or(TransactionAccount {
+                                pubkey,
+                                signer: false,
+                                writable: false,
+                            }),
+                    )
+                })
+                .collect(),
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => None,
+        }
+    }
 }
 
 /// Information about a token balance change.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenBalanceInfo {
     /// Index of the account in the transaction's account list.
     pub account_index: u8,
@@ -41,3 +538,195 @@ pub struct TokenBalanceInfo {
     /// The programming ID (optional in some contexts but usually Token Program)
     pub program_id: Option<String>,
 }
+
+impl From<UiTransactionTokenBalance> for TokenBalanceInfo {
+    fn from(balance: UiTransactionTokenBalance) -> Self {
+        Self {
+            account_index: balance.account_index,
+            mint: balance.mint,
+            owner: Into::<Option<String>>::into(balance.owner).unwrap_or_default(),
+            amount: balance.ui_token_amount.amount,
+            decimals: balance.ui_token_amount.decimals,
+            program_id: balance.program_id.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::{
+        EncodedTransactionWithStatusMeta, UiRawMessage, UiTransaction,
+    };
+
+    fn raw_transaction(
+        account_keys: Vec<String>,
+        instructions: Vec<UiCompiledInstruction>,
+    ) -> EncodedConfirmedTransactionWithStatusMeta {
+        EncodedConfirmedTransactionWithStatusMeta {
+            slot: 7,
+            block_time: Some(1_700_000_000),
+            transaction: EncodedTransactionWithStatusMeta {
+                version: None,
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: vec!["sig".to_string()],
+                    message: UiMessage::Raw(UiRawMessage {
+                        header: solana_sdk::message::MessageHeader::default(),
+                        account_keys,
+                        recent_blockhash: String::new(),
+                        instructions,
+                        address_table_lookups: None,
+                    }),
+                }),
+                meta: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_new_leaves_runtime_handles_unset() {
+        let context = TxMetadata::new("sig", 9);
+        assert_eq!(context.signature, "sig");
+        assert_eq!(context.slot, 9);
+        assert!(!context.meta_missing);
+        assert!(!context.writes().is_atomic());
+    }
+
+    #[test]
+    fn test_from_transaction_without_meta() {
+        let transaction = raw_transaction(vec![], vec![]);
+
+        let context = TxMetadata::from_transaction(&transaction, "sig".to_string(), None);
+        assert!(context.meta_missing);
+        assert_eq!(context.slot, 7);
+        assert_eq!(context.fee, 0);
+        assert!(context.pre_balances.is_empty());
+        assert_eq!(context.memo, None);
+        assert_eq!(context.return_data, None);
+    }
+
+    #[test]
+    fn test_compute_budget_is_decoded() {
+        let budget = |data: Vec<u8>| UiCompiledInstruction {
+            program_id_index: 0,
+            accounts: vec![],
+            data: bs58::encode(data).into_string(),
+            stack_height: None,
+        };
+        let transaction = raw_transaction(
+            vec![COMPUTE_BUDGET_PROGRAM_ID.to_string()],
+            vec![
+                budget([&[2u8][..], &300_000u32.to_le_bytes()].concat()),
+                budget([&[3u8][..], &25_000u64.to_le_bytes()].concat()),
+                budget(vec![1, 0, 0, 4, 0]),
+            ],
+        );
+
+        let context = TxMetadata::from_transaction(&transaction, "sig".to_string(), None);
+        assert_eq!(context.compute_unit_limit, Some(300_000));
+        assert_eq!(context.compute_unit_price, Some(25_000));
+        assert_eq!(context.priority_fee(), Some(7_500));
+
+        let context =
+            TxMetadata::from_transaction(&raw_transaction(vec![], vec![]), "sig".into(), None);
+        assert_eq!(context.priority_fee(), None);
+    }
+
+    #[test]
+    fn test_return_data_is_decoded() {
+        let program = Pubkey::new_unique();
+        let decoded = ReturnData::decode(&UiTransactionReturnData {
+            program_id: program.to_string(),
+            data: (
+                BASE64.encode([1, 2, 3]),
+                solana_transaction_status::UiReturnDataEncoding::Base64,
+            ),
+        });
+        assert_eq!(
+            decoded,
+            Some(ReturnData {
+                program_id: program,
+                data: vec![1, 2, 3],
+            })
+        );
+    }
+
+    #[test]
+    fn test_memos_are_extracted() {
+        let call = |program_id_index, data: &str| UiCompiledInstruction {
+            program_id_index,
+            accounts: vec![],
+            data: bs58::encode(data).into_string(),
+            stack_height: None,
+        };
+        let transaction = raw_transaction(
+            vec![
+                MEMO_PROGRAM_IDS[1].to_string(),
+                "11111111111111111111111111111111".to_string(),
+                MEMO_PROGRAM_IDS[0].to_string(),
+            ],
+            vec![
+                call(0, "deposit-4821"),
+                call(1, "not a memo"),
+                call(2, "legacy"),
+            ],
+        );
+
+        let context = TxMetadata::from_transaction(&transaction, "sig".to_string(), None);
+        assert_eq!(context.memo.as_deref(), Some("deposit-4821; legacy"));
+    }
+
+    #[test]
+    fn test_compiled_accounts_are_resolved() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut transaction =
+            raw_transaction(keys.iter().map(ToString::to_string).collect(), vec![]);
+        if let EncodedTransaction::Json(ui_tx) = &mut transaction.transaction.transaction {
+            if let UiMessage::Raw(msg) = &mut ui_tx.message {
+                // Fee payer, a readonly signer, a writable account, the program
+                msg.header = solana_sdk::message::MessageHeader {
+                    num_required_signatures: 2,
+                    num_readonly_signed_accounts: 1,
+                    num_readonly_unsigned_accounts: 1,
+                };
+            }
+        }
+
+        let context = InstructionContext {
+            account_keys: TransactionAccount::from_transaction(&transaction),
+            ..Default::default()
+        };
+        let flags: Vec<(bool, bool)> = context
+            .account_keys
+            .iter()
+            .map(|account| (account.signer, account.writable))
+            .collect();
+        assert_eq!(
+            flags,
+            [(true, true), (true, false), (false, true), (false, false)]
+        );
+
+        let swap = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![2, 1],
+            data: String::new(),
+            stack_height: None,
+        });
+        assert_eq!(context.program_id_of(&swap), Some(keys[3]));
+        let accounts: Vec<Pubkey> = context
+            .instruction_accounts(&swap)
+            .unwrap()
+            .iter()
+            .map(|account| account.pubkey)
+            .collect();
+        assert_eq!(accounts, [keys[2], keys[1]]);
+
+        let out_of_range = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: 3,
+            accounts: vec![2, 9],
+            data: String::new(),
+            stack_height: None,
+        });
+        assert_eq!(context.instruction_accounts(&out_of_range), None);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod backfill_traits;
 pub mod events;
 pub mod metadata;
+pub mod schema;
 pub mod traits;
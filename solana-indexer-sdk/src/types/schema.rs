@@ -0,0 +1,332 @@
+//! Output schema descriptions for event handlers.
+//!
+//! Handlers describe the tables and columns they write by overriding
+//! `EventHandler::output_schema`. The indexer collects these descriptions into
+//! a `DataDictionary`, which renders to Markdown or HTML so downstream
+//! analysts know what each table contains without reading handler code.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// A column written by an event handler.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSchema {
+    /// Column name.
+    pub name: String,
+    /// SQL type of the column (e.g. `BIGINT`, `TEXT`).
+    pub sql_type: String,
+    /// Human-readable description of the column.
+    pub description: String,
+}
+
+/// A table written by an event handler.
+///
+/// # Example
+///
+/// ```
+/// use solana_indexer_sdk::TableSchema;
+///
+/// let table = TableSchema::new("transfers")
+///     .with_description("One row per system transfer")
+///     .with_column("signature", "TEXT", "Transaction signature")
+///     .with_column("amount", "BIGINT", "Lamports transferred");
+///
+/// assert_eq!(table.columns.len(), 2);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableSchema {
+    /// Table name.
+    pub name: String,
+    /// Human-readable description of the table.
+    pub description: String,
+    /// Columns in declaration order.
+    pub columns: Vec<ColumnSchema>,
+}
+
+impl TableSchema {
+    /// Creates a table description with no columns.
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// Sets the table description.
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Appends a column.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Column name
+    /// * `sql_type` - SQL type of the column
+    /// * `description` - Human-readable description
+    #[must_use]
+    pub fn with_column(
+        mut self,
+        name: impl Into<String>,
+        sql_type: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        self.columns.push(ColumnSchema {
+            name: name.into(),
+            sql_type: sql_type.into(),
+            description: description.into(),
+        });
+        self
+    }
+
+    /// Renders a `CREATE TABLE IF NOT EXISTS` statement with the table's
+    /// columns, all nullable and in declaration order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use solana_indexer_sdk::TableSchema;
+    ///
+    /// let table = TableSchema::new("transfers").with_column("amount", "BIGINT", "");
+    /// assert_eq!(
+    ///     table.create_table_sql(),
+    ///     "CREATE TABLE IF NOT EXISTS transfers (amount BIGINT)"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn create_table_sql(&self) -> String {
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| format!("{} {}", column.name, column.sql_type))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("CREATE TABLE IF NOT EXISTS {} ({columns})", self.name)
+    }
+}
+
+/// An event type together with the tables its handler writes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSchema {
+    /// Name of the event type (e.g. `TransferEvent`).
+    pub event: String,
+    /// The 8-byte event discriminator.
+    pub discriminator: [u8; 8],
+    /// Tables written by the handler for this event.
+    pub tables: Vec<TableSchema>,
+}
+
+/// A field of an event's Borsh encoding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldLayout {
+    /// Field name.
+    pub name: String,
+    /// Rust type of the field as written (e.g. `Option<u64>`).
+    pub ty: String,
+}
+
+impl FieldLayout {
+    /// Describes a field.
+    #[must_use]
+    pub fn new(name: impl Into<String>, ty: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ty: ty.into(),
+        }
+    }
+}
+
+/// The Borsh encoding of a handled event type: its discriminator followed
+/// by its fields in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLayout {
+    /// Name of the event type (e.g. `TransferEvent`).
+    pub event: String,
+    /// The 8-byte event discriminator.
+    pub discriminator: [u8; 8],
+    /// Fields in encoding order; empty if the type does not describe them.
+    pub fields: Vec<FieldLayout>,
+}
+
+/// Data dictionary of every registered event and the tables it populates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataDictionary {
+    /// Events sorted by name.
+    pub events: Vec<EventSchema>,
+}
+
+impl DataDictionary {
+    /// Renders the dictionary as Markdown.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Data Dictionary\n");
+
+        for event in &self.events {
+            let _ = write!(
+                out,
+                "\n## {}\n\nDiscriminator: `{}`\n",
+                event.event,
+                hex(&event.discriminator)
+            );
+
+            if event.tables.is_empty() {
+                out.push_str("\n_No tables declared._\n");
+            }
+
+            for table in &event.tables {
+                let _ = writeln!(out, "\n### `{}`\n", table.name);
+                if !table.description.is_empty() {
+                    let _ = writeln!(out, "{}\n", table.description);
+                }
+                out.push_str("| Column | Type | Description |\n");
+                out.push_str("|--------|------|-------------|\n");
+                for column in &table.columns {
+                    let _ = writeln!(
+                        out,
+                        "| `{}` | `{}` | {} |",
+                        column.name,
+                        column.sql_type,
+                        column.description.replace('|', "\\|")
+                    );
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Renders the dictionary as a standalone HTML page.
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Data Dictionary</title>\n</head>\n<body>\n<h1>Data Dictionary</h1>\n",
+        );
+
+        for event in &self.events {
+            let _ = write!(
+                out,
+                "<h2>{}</h2>\n<p>Discriminator: <code>{}</code></p>\n",
+                escape_html(&event.event),
+                hex(&event.discriminator)
+            );
+
+            if event.tables.is_empty() {
+                out.push_str("<p><em>No tables declared.</em></p>\n");
+            }
+
+            for table in &event.tables {
+                let _ = writeln!(out, "<h3><code>{}</code></h3>", escape_html(&table.name));
+                if !table.description.is_empty() {
+                    let _ = writeln!(out, "<p>{}</p>", escape_html(&table.description));
+                }
+                out.push_str(
+                    "<table>\n<tr><th>Column</th><th>Type</th><th>Description</th></tr>\n",
+                );
+                for column in &table.columns {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>",
+                        escape_html(&column.name),
+                        escape_html(&column.sql_type),
+                        escape_html(&column.description)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Writes the dictionary to `path`.
+    ///
+    /// Files ending in `.html` or `.htm` are rendered as HTML, anything else
+    /// as Markdown.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Internal` if the file cannot be written.
+    pub fn write_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let is_html = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+        let contents = if is_html {
+            self.to_html()
+        } else {
+            self.to_markdown()
+        };
+
+        std::fs::write(path, contents).map_err(|e| {
+            SolanaIndexerError::Internal(format!(
+                "Failed to write data dictionary to {}: {e}",
+                path.display()
+            ))
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dictionary() -> DataDictionary {
+        DataDictionary {
+            events: vec![
+                EventSchema {
+                    event: "TransferEvent".to_string(),
+                    discriminator: [0xab; 8],
+                    tables: vec![TableSchema::new("transfers")
+                        .with_description("One row per transfer")
+                        .with_column("amount", "BIGINT", "Lamports <moved> | sent")],
+                },
+                EventSchema {
+                    event: "DepositEvent".to_string(),
+                    discriminator: [1; 8],
+                    tables: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let markdown = dictionary().to_markdown();
+
+        assert!(markdown.starts_with("# Data Dictionary\n"));
+        assert!(markdown.contains("## TransferEvent"));
+        assert!(markdown.contains("Discriminator: `abababababababab`"));
+        assert!(markdown.contains("### `transfers`"));
+        assert!(markdown.contains("| `amount` | `BIGINT` | Lamports <moved> \\| sent |"));
+        assert!(markdown.contains("_No tables declared._"));
+    }
+
+    #[test]
+    fn test_to_html_escapes() {
+        let html = dictionary().to_html();
+
+        assert!(html.contains("<h2>TransferEvent</h2>"));
+        assert!(html.contains("Lamports &lt;moved&gt; | sent"));
+        assert!(html.ends_with("</html>\n"));
+    }
+}
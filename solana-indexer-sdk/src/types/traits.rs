@@ -4,15 +4,25 @@
 //! the `EventHandler` trait. Developers implement this trait to define custom
 //! business logic for processing decoded events and transactions.
 
+use crate::core::execution::usage::UsageTracker;
 use crate::core::registry::metrics::RegistryMetrics;
+use crate::core::registry::retry::RetryPolicy;
+use crate::core::registry::toggles::HandlerToggles;
+use crate::storage::dlq::DeadLetterQueue;
+use crate::storage::flags::FeatureFlags;
+use crate::storage::jobs::DeferredJobs;
+use crate::storage::spill::SpillQueue;
+use crate::storage::unhandled::UnhandledEvents;
 use crate::types::events::{EventDiscriminator, ParsedEvent};
-use crate::types::metadata::TxMetadata;
+use crate::types::metadata::{InstructionContext, TxMetadata};
+use crate::types::schema::{DataDictionary, EventLayout, EventSchema, TableSchema};
 use crate::utils::error::{Result, SolanaIndexerError};
 use async_trait::async_trait;
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_sdk::pubkey::Pubkey;
 use solana_transaction_status::UiInstruction;
 use sqlx::PgPool;
+use std::sync::{Arc, RwLock};
 
 /// Generic instruction decoder trait for custom parsing logic.
 ///
@@ -52,6 +62,45 @@ pub trait InstructionDecoder<T>: Send + Sync {
     /// * `Some(T)` - Successfully decoded event
     /// * `None` - Instruction doesn't match or failed to decode
     fn decode(&self, instruction: &UiInstruction) -> Option<T>;
+
+    /// Decodes an instruction knowing where it sits in its transaction.
+    ///
+    /// Called for both top-level and inner instructions; the default ignores
+    /// `context` and calls `decode`. Override it to tell CPIs apart from
+    /// direct calls, or to resolve the account indices of
+    /// `UiInstruction::Compiled` through `context.instruction_accounts`.
+    fn decode_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<T> {
+        let _ = context;
+        self.decode(instruction)
+    }
+
+    /// Decodes an instruction into any number of events.
+    ///
+    /// The default returns the event of `decode_in_context`, if any.
+    /// Override it when one instruction stands for several events, such as
+    /// one swap per hop of a routed trade; every event is dispatched to its
+    /// handlers on its own, in the order returned. An empty `Vec` means the
+    /// instruction did not match.
+    fn decode_many(&self, instruction: &UiInstruction, context: &InstructionContext) -> Vec<T> {
+        self.decode_in_context(instruction, context)
+            .into_iter()
+            .collect()
+    }
+
+    /// Leading bytes of the instruction data this decoder handles, such as
+    /// Anchor's 8-byte `sha256("global:<name>")` sighash or a native
+    /// program's tag byte.
+    ///
+    /// Only used to tell decode misses (see `RegistryConfig`) apart from
+    /// other instructions of the program; the default declares none, so no
+    /// misses are recorded for the decoder.
+    fn instruction_prefixes(&self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
 }
 
 /// Type-erased instruction decoder for internal SDK use.
@@ -62,6 +111,37 @@ pub trait InstructionDecoder<T>: Send + Sync {
 pub trait DynamicInstructionDecoder: Send + Sync {
     /// Decodes an instruction into discriminator + raw event data.
     fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)>;
+
+    /// Decodes an instruction together with its position in the transaction.
+    fn decode_dynamic_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        let _ = context;
+        self.decode_dynamic(instruction)
+    }
+
+    /// Decodes an instruction into every event it produces.
+    fn decode_dynamic_many(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_dynamic_in_context(instruction, context)
+            .into_iter()
+            .collect()
+    }
+
+    /// Discriminator of the events this decoder produces, if known.
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        None
+    }
+
+    /// Leading bytes of the instruction data this decoder handles, if known.
+    fn instruction_prefixes(&self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
 }
 
 /// Automatic conversion from typed decoder to dynamic decoder.
@@ -75,6 +155,35 @@ where
         let data = borsh::to_vec(&event).ok()?;
         Some((discriminator, data))
     }
+
+    fn decode_dynamic_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        let event = self.decode_in_context(instruction, context)?;
+        let data = borsh::to_vec(&event).ok()?;
+        Some((T::discriminator(), data))
+    }
+
+    fn decode_dynamic_many(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_many(instruction, context)
+            .iter()
+            .filter_map(|event| Some((T::discriminator(), borsh::to_vec(event).ok()?)))
+            .collect()
+    }
+
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        Some(T::discriminator())
+    }
+
+    fn instruction_prefixes(&self) -> Vec<Vec<u8>> {
+        (**self).instruction_prefixes()
+    }
 }
 
 /// Generic log decoder trait for custom parsing logic.
@@ -98,6 +207,11 @@ pub trait LogDecoder<T>: Send + Sync {
 pub trait DynamicLogDecoder: Send + Sync {
     /// Decodes a log event into discriminator + raw event data.
     fn decode_log_dynamic(&self, event: &ParsedEvent) -> Option<([u8; 8], Vec<u8>)>;
+
+    /// Discriminator of the events this decoder produces, if known.
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        None
+    }
 }
 
 impl<T> DynamicLogDecoder for Box<dyn LogDecoder<T>>
@@ -110,6 +224,10 @@ where
         let data = borsh::to_vec(&event).ok()?;
         Some((discriminator, data))
     }
+
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        Some(T::discriminator())
+    }
 }
 
 /// Generic account decoder trait for custom parsing logic.
@@ -138,6 +256,23 @@ pub trait DynamicAccountDecoder: Send + Sync {
         pubkey: &Pubkey,
         account: &solana_sdk::account::Account,
     ) -> Option<([u8; 8], Vec<u8>)>;
+
+    /// Decodes an account into any number of structures. The default
+    /// returns the result of `decode_account_dynamic`.
+    fn decode_account_dynamic_many(
+        &self,
+        pubkey: &Pubkey,
+        account: &solana_sdk::account::Account,
+    ) -> Vec<([u8; 8], Vec<u8>)> {
+        self.decode_account_dynamic(pubkey, account)
+            .into_iter()
+            .collect()
+    }
+
+    /// Discriminator of the structures this decoder produces, if known.
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        None
+    }
 }
 
 impl<T> DynamicAccountDecoder for Box<dyn AccountDecoder<T>>
@@ -154,6 +289,10 @@ where
         let data = borsh::to_vec(&event).ok()?;
         Some((discriminator, data))
     }
+
+    fn discriminator(&self) -> Option<[u8; 8]> {
+        Some(T::discriminator())
+    }
 }
 
 /// Trait for initializing custom database schemas.
@@ -170,6 +309,13 @@ pub trait SchemaInitializer: Send + Sync {
     async fn initialize(&self, db: &PgPool) -> Result<()>;
 }
 
+#[async_trait]
+impl<S: SchemaInitializer + ?Sized> SchemaInitializer for Arc<S> {
+    async fn initialize(&self, db: &PgPool) -> Result<()> {
+        (**self).initialize(db).await
+    }
+}
+
 /// Event handler trait for processing decoded events.
 ///
 /// The `EventHandler` trait is the primary extension point for `SolanaIndexer`,
@@ -288,6 +434,37 @@ pub trait EventHandler<T>: Send + Sync + 'static {
         let _ = pool; // Default implementation does nothing
         Ok(())
     }
+
+    /// Describes the tables and columns this handler writes.
+    ///
+    /// Used to build the data dictionary returned by
+    /// `SolanaIndexer::data_dictionary`. The default declares no tables.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use solana_indexer_sdk::{EventHandler, TableSchema, TxMetadata};
+    /// # use async_trait::async_trait;
+    /// # use sqlx::PgPool;
+    /// # pub struct MyEvent;
+    /// pub struct MyHandler;
+    ///
+    /// #[async_trait]
+    /// impl EventHandler<MyEvent> for MyHandler {
+    ///     async fn handle(&self, _: MyEvent, _: &TxMetadata, _: &PgPool) -> solana_indexer_sdk::Result<()> {
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn output_schema(&self) -> Vec<TableSchema> {
+    ///         vec![TableSchema::new("my_events")
+    ///             .with_column("signature", "TEXT", "Transaction signature")
+    ///             .with_column("amount", "BIGINT", "Amount in lamports")]
+    ///     }
+    /// }
+    /// ```
+    fn output_schema(&self) -> Vec<TableSchema> {
+        Vec::new()
+    }
 }
 
 /// Type-erased event handler for dynamic dispatch.
@@ -311,6 +488,16 @@ pub trait DynamicEventHandler: Send + Sync {
 
     /// Initializes schema for the dynamic handler.
     async fn initialize_schema(&self, pool: &PgPool) -> Result<()>;
+
+    /// Describes the handled event and the tables the handler writes.
+    fn event_schema(&self) -> Option<EventSchema> {
+        None
+    }
+
+    /// Describes the Borsh encoding of the handled event.
+    fn event_layout(&self) -> Option<EventLayout> {
+        None
+    }
 }
 
 /// Automatic conversion from typed handler to dynamic handler.
@@ -328,14 +515,14 @@ where
     ) -> Result<()> {
         // Verify discriminator matches
         if *discriminator != T::discriminator() {
-            return Err(SolanaIndexerError::DecodingError(
+            return Err(SolanaIndexerError::Decode(
                 "Discriminator mismatch".to_string(),
             ));
         }
 
         // Deserialize event
         let event = T::try_from_slice(data).map_err(|e| {
-            SolanaIndexerError::DecodingError(format!("Failed to deserialize event: {}", e))
+            SolanaIndexerError::Decode(format!("Failed to deserialize event: {}", e))
         })?;
 
         // Delegate to typed handler
@@ -349,14 +536,56 @@ where
     async fn initialize_schema(&self, pool: &PgPool) -> Result<()> {
         (**self).initialize_schema(pool).await
     }
+
+    fn event_schema(&self) -> Option<EventSchema> {
+        Some(EventSchema {
+            event: short_type_name::<T>().to_string(),
+            discriminator: T::discriminator(),
+            tables: (**self).output_schema(),
+        })
+    }
+
+    fn event_layout(&self) -> Option<EventLayout> {
+        Some(EventLayout {
+            event: short_type_name::<T>().to_string(),
+            discriminator: T::discriminator(),
+            fields: T::borsh_layout(),
+        })
+    }
+}
+
+/// Short type name of `T`, without module path or generics.
+fn short_type_name<T>() -> &'static str {
+    let type_name = std::any::type_name::<T>();
+    type_name
+        .split('<')
+        .next()
+        .and_then(|path| path.rsplit("::").next())
+        .unwrap_or(type_name)
+}
+
+/// A handler of one event type, with the retry policy it was registered
+/// with, if any.
+#[derive(Clone)]
+pub(crate) struct RegisteredHandler {
+    pub(crate) handler: Arc<dyn DynamicEventHandler>,
+    pub(crate) retry: Option<RetryPolicy>,
 }
 
+/// Event handlers by discriminator, in registration order.
+pub(crate) type EventHandlers = std::collections::HashMap<[u8; 8], Vec<RegisteredHandler>>;
+
 /// Handler registry for managing multiple event handlers.
 ///
 /// The `HandlerRegistry` stores and manages event handlers, allowing the
-/// indexer to dispatch events to the appropriate handler based on their
+/// indexer to dispatch events to the appropriate handlers based on their
 /// discriminator.
 ///
+/// An event type can have several handlers, e.g. one storing the event and
+/// one pushing it to a webhook. Each handler of an event is called with
+/// its own retries, and one that keeps failing is dead-lettered on its own
+/// without keeping the others from running.
+///
 /// # Example
 ///
 /// ```
@@ -366,9 +595,19 @@ where
 /// // Register handlers here
 /// ```
 pub struct HandlerRegistry {
-    /// Map of discriminators to handlers
-    handlers: std::collections::HashMap<[u8; 8], Box<dyn DynamicEventHandler>>,
-    metrics: RegistryMetrics,
+    /// Map of discriminators to handlers, shared with `RegistryHandle`s
+    handlers: Arc<RwLock<EventHandlers>>,
+    metrics: Arc<RegistryMetrics>,
+    toggles: HandlerToggles,
+    /// Retry policies set for every handler of an event type
+    retry_policies: std::collections::HashMap<[u8; 8], RetryPolicy>,
+    default_retry_policy: RetryPolicy,
+    usage: Option<Arc<UsageTracker>>,
+    spill: Option<Arc<SpillQueue>>,
+    dead_letters: Option<Arc<DeadLetterQueue>>,
+    unhandled: Option<Arc<UnhandledEvents>>,
+    flags: FeatureFlags,
+    jobs: DeferredJobs,
 }
 
 impl HandlerRegistry {
@@ -384,20 +623,111 @@ impl HandlerRegistry {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            handlers: std::collections::HashMap::new(),
-            metrics: RegistryMetrics::new("EventHandler", 0),
+            handlers: Arc::default(),
+            metrics: Arc::new(RegistryMetrics::new("EventHandler", 0)),
+            toggles: HandlerToggles::new(),
+            retry_policies: std::collections::HashMap::new(),
+            default_retry_policy: RetryPolicy::default(),
+            usage: None,
+            spill: None,
+            dead_letters: None,
+            unhandled: None,
+            flags: FeatureFlags::default(),
+            jobs: DeferredJobs::default(),
         }
     }
 
     /// Creates a new handler registry with a specific capacity limit.
     pub fn new_bounded(config: &crate::config::RegistryConfig) -> Self {
         Self {
-            handlers: std::collections::HashMap::new(),
-            metrics: RegistryMetrics::new("EventHandler", config.max_handlers),
+            handlers: Arc::default(),
+            metrics: Arc::new(RegistryMetrics::new("EventHandler", config.max_handlers)),
+            toggles: HandlerToggles::new(),
+            retry_policies: std::collections::HashMap::new(),
+            default_retry_policy: RetryPolicy::default(),
+            usage: None,
+            spill: None,
+            dead_letters: None,
+            unhandled: None,
+            flags: FeatureFlags::default(),
+            jobs: DeferredJobs::default(),
         }
     }
 
-    /// Registers a handler for a specific event discriminator.
+    /// Counts every successfully handled event in `tracker`.
+    #[must_use]
+    pub fn with_usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.usage = Some(tracker);
+        self
+    }
+
+    /// Spills events to `queue` instead of failing their transaction when
+    /// the database is unreachable.
+    #[must_use]
+    pub fn with_spill_queue(mut self, queue: Arc<SpillQueue>) -> Self {
+        self.spill = Some(queue);
+        self
+    }
+
+    /// Dead-letters events whose handler fails after its retries to `queue`
+    /// instead of failing their transaction.
+    #[must_use]
+    pub fn with_dead_letter_queue(mut self, queue: Arc<DeadLetterQueue>) -> Self {
+        self.dead_letters = Some(queue);
+        self
+    }
+
+    /// Stores events without a registered handler in `unhandled` instead of
+    /// failing them.
+    #[must_use]
+    pub fn with_unhandled_events(mut self, unhandled: Arc<UnhandledEvents>) -> Self {
+        self.unhandled = Some(unhandled);
+        self
+    }
+
+    /// Exposes `flags` to handlers through `TxMetadata::flags`.
+    #[must_use]
+    pub fn with_feature_flags(mut self, flags: FeatureFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Returns the spill queue set with `with_spill_queue`, if any.
+    #[must_use]
+    pub fn spill_queue(&self) -> Option<&Arc<SpillQueue>> {
+        self.spill.as_ref()
+    }
+
+    /// Returns the dead letter queue set with `with_dead_letter_queue`, if
+    /// any.
+    #[must_use]
+    pub fn dead_letter_queue(&self) -> Option<&Arc<DeadLetterQueue>> {
+        self.dead_letters.as_ref()
+    }
+
+    /// Returns the feature flags set with `with_feature_flags`, or flags
+    /// that are all disabled.
+    #[must_use]
+    pub fn feature_flags(&self) -> &FeatureFlags {
+        &self.flags
+    }
+
+    /// Lets handlers schedule jobs through `TxMetadata::jobs`.
+    #[must_use]
+    pub fn with_deferred_jobs(mut self, jobs: DeferredJobs) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Returns the jobs handle set with `with_deferred_jobs`, or one that
+    /// refuses every job.
+    #[must_use]
+    pub fn deferred_jobs(&self) -> &DeferredJobs {
+        &self.jobs
+    }
+
+    /// Registers a handler for a specific event discriminator, after any
+    /// handlers already registered for it.
     ///
     /// # Arguments
     ///
@@ -417,27 +747,157 @@ impl HandlerRegistry {
         discriminator: [u8; 8],
         handler: Box<dyn DynamicEventHandler>,
     ) -> Result<()> {
-        if !self.handlers.contains_key(&discriminator) && self.metrics.is_full() {
+        Self::insert(
+            &self.handlers,
+            &self.metrics,
+            discriminator,
+            Arc::from(handler),
+            None,
+        )
+    }
+
+    /// Registers a handler for `discriminator` that is retried as `policy`
+    /// says, whatever the policy of the event type's other handlers.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::RegistryCapacityExceeded` if the
+    /// registry is full.
+    pub fn register_with_retry(
+        &mut self,
+        discriminator: [u8; 8],
+        handler: Box<dyn DynamicEventHandler>,
+        policy: RetryPolicy,
+    ) -> Result<()> {
+        Self::insert(
+            &self.handlers,
+            &self.metrics,
+            discriminator,
+            Arc::from(handler),
+            Some(policy),
+        )
+    }
+
+    /// Adds `handler` to the handlers of `discriminator`.
+    pub(crate) fn insert(
+        handlers: &RwLock<EventHandlers>,
+        metrics: &RegistryMetrics,
+        discriminator: [u8; 8],
+        handler: Arc<dyn DynamicEventHandler>,
+        retry: Option<RetryPolicy>,
+    ) -> Result<()> {
+        let mut handlers = handlers.write().map_err(|_| {
+            SolanaIndexerError::Internal("HandlerRegistry lock poisoned".to_string())
+        })?;
+        if metrics.is_full() {
             return Err(SolanaIndexerError::RegistryCapacityExceeded(format!(
                 "EventHandler registry full (limit: {})",
-                self.metrics.capacity_limit
+                metrics.capacity_limit
             )));
         }
 
-        self.handlers.insert(discriminator, handler);
-        self.metrics.inc_registered();
+        handlers
+            .entry(discriminator)
+            .or_default()
+            .push(RegisteredHandler { handler, retry });
+        metrics.inc_registered();
         Ok(())
     }
 
+    /// Returns the handlers, metrics and usage tracker shared with
+    /// `RegistryHandle`s.
+    pub(crate) fn shared(
+        &self,
+    ) -> (
+        Arc<RwLock<EventHandlers>>,
+        Arc<RegistryMetrics>,
+        Option<Arc<UsageTracker>>,
+    ) {
+        (
+            self.handlers.clone(),
+            self.metrics.clone(),
+            self.usage.clone(),
+        )
+    }
+
+    /// Retries the handlers of `discriminator` as `policy` says, instead of
+    /// the default policy. Handlers registered with a policy of their own
+    /// keep it.
+    pub fn set_retry_policy(&mut self, discriminator: [u8; 8], policy: RetryPolicy) {
+        self.retry_policies.insert(discriminator, policy);
+    }
+
+    /// Returns the retry policy set for the handlers of `discriminator`.
+    #[must_use]
+    pub fn retry_policy(&self, discriminator: &[u8; 8]) -> &RetryPolicy {
+        self.retry_policies
+            .get(discriminator)
+            .unwrap_or(&self.default_retry_policy)
+    }
+
+    /// Returns the retry policy of handler `index` of `discriminator`: the
+    /// one it was registered with, or the event type's.
+    #[must_use]
+    pub fn handler_retry_policy(&self, discriminator: &[u8; 8], index: usize) -> RetryPolicy {
+        self.get(discriminator)
+            .into_iter()
+            .nth(index)
+            .and_then(|registered| registered.retry)
+            .unwrap_or_else(|| self.retry_policy(discriminator).clone())
+    }
+
+    /// Returns the handlers registered for `discriminator`.
+    fn get(&self, discriminator: &[u8; 8]) -> Vec<RegisteredHandler> {
+        self.handlers
+            .read()
+            .ok()
+            .and_then(|handlers| handlers.get(discriminator).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Returns every registered handler.
+    fn all(&self) -> Vec<Arc<dyn DynamicEventHandler>> {
+        self.handlers
+            .read()
+            .map(|handlers| {
+                handlers
+                    .values()
+                    .flatten()
+                    .map(|registered| registered.handler.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if a handler is registered for `discriminator`.
+    #[must_use]
+    pub fn contains(&self, discriminator: &[u8; 8]) -> bool {
+        self.handler_count(discriminator) > 0
+    }
+
+    /// Returns the number of handlers registered for `discriminator`.
+    #[must_use]
+    pub fn handler_count(&self, discriminator: &[u8; 8]) -> usize {
+        self.handlers
+            .read()
+            .ok()
+            .and_then(|handlers| handlers.get(discriminator).map(Vec::len))
+            .unwrap_or(0)
+    }
+
     /// Triggers rollback on all registered handlers.
     pub async fn handle_rollback(&self, context: &TxMetadata, db: &PgPool) -> Result<()> {
-        for handler in self.handlers.values() {
+        for handler in self.all() {
             handler.handle_rollback_dynamic(context, db).await?;
         }
         Ok(())
     }
 
-    /// Handles an event by dispatching to the appropriate handler.
+    /// Handles an event by dispatching it to every handler of its
+    /// discriminator.
+    ///
+    /// Every handler is called even if an earlier one fails; the first
+    /// error is returned once they have all run.
     ///
     /// # Arguments
     ///
@@ -448,7 +908,7 @@ impl HandlerRegistry {
     ///
     /// # Errors
     ///
-    /// Returns `SolanaIndexerError::DecodingError` if no handler is registered
+    /// Returns `SolanaIndexerError::Decode` if no handler is registered
     /// for the discriminator, or propagates handler errors.
     ///
     /// # Example
@@ -472,24 +932,102 @@ impl HandlerRegistry {
         event_data: &[u8],
         context: &TxMetadata,
         db: &PgPool,
+    ) -> Result<()> {
+        let mut first_error = None;
+        for index in 0..self.handler_count(discriminator).max(1) {
+            if let Err(e) = self
+                .handle_at(discriminator, index, event_data, context, db)
+                .await
+            {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Handles an event with handler `index` of its discriminator only, in
+    /// registration order. The indexer calls each handler this way so it
+    /// can retry and dead-letter them independently.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolanaIndexerError::Decode` if the discriminator has
+    /// no handler `index`, or propagates the handler's error.
+    pub async fn handle_at(
+        &self,
+        discriminator: &[u8; 8],
+        index: usize,
+        event_data: &[u8],
+        context: &TxMetadata,
+        db: &PgPool,
     ) -> Result<()> {
         self.metrics.inc_calls();
-        let handler = self.handlers.get(discriminator).ok_or_else(|| {
-            SolanaIndexerError::DecodingError(format!(
+        let handlers = self.get(discriminator);
+        let Some(registered) = handlers.get(index) else {
+            if !handlers.is_empty() {
+                return Err(SolanaIndexerError::Decode(format!(
+                    "No handler {index} registered for discriminator: {discriminator:?}"
+                )));
+            }
+            if let Some(unhandled) = &self.unhandled {
+                return unhandled
+                    .store(discriminator, event_data, context, db)
+                    .await;
+            }
+            return Err(SolanaIndexerError::Decode(format!(
                 "No handler registered for discriminator: {discriminator:?}"
-            ))
-        })?;
+            )));
+        };
+
+        if !self.toggles.is_enabled(discriminator) {
+            tracing::debug!(
+                "Skipping disabled handler for discriminator {discriminator:?} ({})",
+                context.signature
+            );
+            return Ok(());
+        }
 
-        let result = handler
+        let result = registered
+            .handler
             .handle_dynamic(discriminator, event_data, context, db)
             .await;
         if result.is_ok() {
             self.metrics.inc_hits();
+            if let Some(usage) = &self.usage {
+                usage.record_event(*discriminator, event_data.len());
+            }
         }
         result
     }
 
-    /// Returns the number of registered handlers.
+    /// Builds a data dictionary of every registered event and the tables its
+    /// handler declares via `EventHandler::output_schema`.
+    #[must_use]
+    pub fn data_dictionary(&self) -> DataDictionary {
+        let mut events: Vec<EventSchema> = self
+            .all()
+            .iter()
+            .filter_map(|handler| handler.event_schema())
+            .collect();
+        events.sort_by(|a, b| a.event.cmp(&b.event));
+        DataDictionary { events }
+    }
+
+    /// Returns the Borsh encoding of every handled event type, sorted by
+    /// event name.
+    #[must_use]
+    pub fn event_layouts(&self) -> Vec<EventLayout> {
+        let mut layouts: Vec<EventLayout> = self
+            .all()
+            .iter()
+            .filter_map(|handler| handler.event_layout())
+            .collect();
+        layouts.sort_by(|a, b| a.event.cmp(&b.event));
+        layouts
+    }
+
+    /// Returns the number of registered handlers, counting every handler
+    /// of an event type.
     ///
     /// # Example
     ///
@@ -501,7 +1039,9 @@ impl HandlerRegistry {
     /// ```
     #[must_use]
     pub fn len(&self) -> usize {
-        self.handlers.len()
+        self.handlers
+            .read()
+            .map_or(0, |handlers| handlers.values().map(Vec::len).sum())
     }
 
     /// Returns true if no handlers are registered.
@@ -516,13 +1056,22 @@ impl HandlerRegistry {
     /// ```
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.handlers.is_empty()
+        self.len() == 0
     }
 
     /// Returns the metrics for this registry.
     pub fn metrics(&self) -> &RegistryMetrics {
         &self.metrics
     }
+
+    /// Returns a handle for enabling or disabling handlers at runtime.
+    ///
+    /// Disabled handlers stay registered; their events are acknowledged
+    /// without being dispatched until they are enabled again.
+    #[must_use]
+    pub fn toggles(&self) -> HandlerToggles {
+        self.toggles.clone()
+    }
 }
 
 impl Default for HandlerRegistry {
@@ -585,7 +1134,7 @@ mod tests {
             _db: &PgPool,
         ) -> Result<()> {
             if *discriminator != self.discriminator {
-                return Err(SolanaIndexerError::DecodingError(
+                return Err(SolanaIndexerError::Decode(
                     "Discriminator mismatch".to_string(),
                 ));
             }
@@ -618,6 +1167,49 @@ mod tests {
         Ok(())
     }
 
+    struct TransferTableHandler;
+
+    #[async_trait]
+    impl EventHandler<crate::types::events::TransferEvent> for TransferTableHandler {
+        async fn handle(
+            &self,
+            _event: crate::types::events::TransferEvent,
+            _context: &TxMetadata,
+            _db: &PgPool,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn output_schema(&self) -> Vec<TableSchema> {
+            vec![TableSchema::new("transfers").with_column("amount", "BIGINT", "Amount")]
+        }
+    }
+
+    #[test]
+    fn test_handler_registry_data_dictionary() -> Result<()> {
+        use crate::types::events::TransferEvent;
+
+        let mut registry = HandlerRegistry::new();
+        let typed: Box<dyn EventHandler<TransferEvent>> = Box::new(TransferTableHandler);
+        registry.register(TransferEvent::discriminator(), Box::new(typed))?;
+        registry.register(
+            [9; 8],
+            Box::new(MockDynamicHandler {
+                discriminator: [9; 8],
+            }),
+        )?;
+
+        let dictionary = registry.data_dictionary();
+        assert_eq!(dictionary.events.len(), 1);
+        assert_eq!(dictionary.events[0].event, "TransferEvent");
+        assert_eq!(
+            dictionary.events[0].discriminator,
+            TransferEvent::discriminator()
+        );
+        assert_eq!(dictionary.events[0].tables[0].name, "transfers");
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_handler_registry_handle_not_found() {
         let registry = HandlerRegistry::new();
@@ -639,6 +1231,16 @@ mod tests {
                 pre_token_balances: vec![],
                 post_token_balances: vec![],
                 signature: "sig".to_string(),
+                err: None,
+                meta_missing: false,
+                index_in_block: None,
+                memo: None,
+                return_data: None,
+                compute_unit_limit: None,
+                compute_unit_price: None,
+                flags: Default::default(),
+                jobs: Default::default(),
+                writes: Default::default(),
             };
             let result = registry
                 .handle(&discriminator, b"data", &context, &db)
@@ -646,4 +1248,151 @@ mod tests {
             assert!(result.is_err());
         }
     }
+
+    struct CountingHandler {
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl DynamicEventHandler for CountingHandler {
+        async fn handle_dynamic(
+            &self,
+            _discriminator: &[u8; 8],
+            _data: &[u8],
+            _context: &TxMetadata,
+            _db: &PgPool,
+        ) -> Result<()> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn handle_rollback_dynamic(&self, _context: &TxMetadata, _db: &PgPool) -> Result<()> {
+            Ok(())
+        }
+
+        async fn initialize_schema(&self, _pool: &PgPool) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handler_registry_toggles() -> Result<()> {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let discriminator = [7u8; 8];
+        let mut registry = HandlerRegistry::new();
+        registry.register(
+            discriminator,
+            Box::new(CountingHandler {
+                calls: calls.clone(),
+            }),
+        )?;
+
+        let db =
+            sqlx::postgres::PgPoolOptions::new().connect_lazy("postgresql://localhost/test")?;
+        let context = TxMetadata {
+            slot: 0,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        let toggles = registry.toggles();
+        toggles.disable(discriminator);
+        registry.handle(&discriminator, b"", &context, &db).await?;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+        toggles.enable(discriminator);
+        registry.handle(&discriminator, b"", &context, &db).await?;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_handler_registry_fan_out() -> Result<()> {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let discriminator = [3u8; 8];
+        let mut registry = HandlerRegistry::new();
+        // Fails every event, since it expects another discriminator
+        registry.register_with_retry(
+            discriminator,
+            Box::new(MockDynamicHandler {
+                discriminator: [4; 8],
+            }),
+            RetryPolicy::never(),
+        )?;
+        registry.register(
+            discriminator,
+            Box::new(CountingHandler {
+                calls: calls.clone(),
+            }),
+        )?;
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.handler_count(&discriminator), 2);
+        assert_eq!(
+            registry
+                .handler_retry_policy(&discriminator, 0)
+                .max_attempts(),
+            1
+        );
+        assert_eq!(
+            registry
+                .handler_retry_policy(&discriminator, 1)
+                .max_attempts(),
+            3
+        );
+
+        let db =
+            sqlx::postgres::PgPoolOptions::new().connect_lazy("postgresql://localhost/test")?;
+        let context = TxMetadata {
+            slot: 0,
+            block_time: None,
+            fee: 0,
+            pre_balances: vec![],
+            post_balances: vec![],
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+            signature: "sig".to_string(),
+            err: None,
+            meta_missing: false,
+            index_in_block: None,
+            memo: None,
+            return_data: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+            flags: Default::default(),
+            jobs: Default::default(),
+            writes: Default::default(),
+        };
+
+        // The failing handler does not keep the other from running
+        assert!(registry
+            .handle(&discriminator, b"", &context, &db)
+            .await
+            .is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        registry
+            .handle_at(&discriminator, 1, b"", &context, &db)
+            .await?;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert!(registry
+            .handle_at(&discriminator, 2, b"", &context, &db)
+            .await
+            .is_err());
+        Ok(())
+    }
 }
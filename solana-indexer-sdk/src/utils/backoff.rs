@@ -0,0 +1,312 @@
+//! Shared retry policy for RPC calls.
+//!
+//! This module provides `BackoffConfig`, an exponential backoff policy with
+//! jitter, and `retry_with_backoff`, which classifies each failure before
+//! deciding whether (and how long) to wait. Rate-limited responses back off
+//! harder than transient ones, and fatal errors are returned immediately.
+
+use crate::utils::error::{Result, SolanaIndexerError};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::Duration;
+
+/// Exponential backoff policy with jitter.
+///
+/// The delay before retry `n` (1-based) is `base_delay_ms * 2^(n-1)`, capped at
+/// `max_delay_ms`, then reduced by a random fraction of up to `jitter` so that
+/// concurrent workers do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BackoffConfig {
+    /// Delay before the first retry, in milliseconds (default: 100)
+    pub base_delay_ms: u64,
+    /// Upper bound for a single delay, in milliseconds (default: 10_000)
+    pub max_delay_ms: u64,
+    /// Total number of attempts, including the first one (default: 5)
+    pub max_attempts: u32,
+    /// Fraction of each delay that is randomized, from 0.0 to 1.0 (default: 0.5)
+    pub jitter: f64,
+    /// Extra multiplier applied to delays after a rate-limit response (default: 4)
+    pub rate_limit_multiplier: u64,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: 100,
+            max_delay_ms: 10_000,
+            max_attempts: 5,
+            jitter: 0.5,
+            rate_limit_multiplier: 4,
+        }
+    }
+}
+
+/// How an RPC failure should be treated by the retry loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The endpoint asked us to slow down (HTTP 429 or equivalent).
+    RateLimited,
+    /// A failure that is likely to succeed on retry (timeouts, resets, 5xx).
+    Transient,
+    /// A failure that will not change on retry (invalid params, skipped slot).
+    Fatal,
+}
+
+impl ErrorClass {
+    /// Classifies an error: errors that are not
+    /// `SolanaIndexerError::is_retryable` are fatal, and retryable ones are
+    /// rate limited if their message says so.
+    #[must_use]
+    pub fn of(error: &SolanaIndexerError) -> Self {
+        if !error.is_retryable() {
+            return Self::Fatal;
+        }
+        match Self::from_message(&error.to_string()) {
+            Self::RateLimited => Self::RateLimited,
+            _ => Self::Transient,
+        }
+    }
+
+    /// Classifies a raw RPC error message.
+    #[must_use]
+    pub fn from_message(message: &str) -> Self {
+        let message = message.to_ascii_lowercase();
+        if message.contains("429")
+            || message.contains("too many requests")
+            || message.contains("rate limit")
+        {
+            return Self::RateLimited;
+        }
+
+        // JSON-RPC codes that will not resolve by waiting:
+        // -32602 invalid params, -32007 slot skipped, -32009 slot missing from
+        // long-term storage, -32010 key excluded from secondary indexes.
+        const FATAL_MARKERS: [&str; 7] = [
+            "-32602",
+            "-32007",
+            "-32009",
+            "-32010",
+            "invalid param",
+            "was skipped",
+            "accountnotfound",
+        ];
+        if FATAL_MARKERS.iter().any(|marker| message.contains(marker)) {
+            return Self::Fatal;
+        }
+
+        Self::Transient
+    }
+}
+
+impl BackoffConfig {
+    /// Returns the delay before the next attempt, or `None` if the operation
+    /// should not be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - The attempt that just failed (1-based)
+    /// * `class` - Classification of the failure
+    #[must_use]
+    pub fn delay(&self, attempt: u32, class: ErrorClass) -> Option<Duration> {
+        if class == ErrorClass::Fatal || attempt >= self.max_attempts {
+            return None;
+        }
+
+        let exponent = attempt.saturating_sub(1).min(32);
+        let mut delay_ms = self.base_delay_ms.saturating_mul(1u64 << exponent);
+        if class == ErrorClass::RateLimited {
+            delay_ms = delay_ms.saturating_mul(self.rate_limit_multiplier.max(1));
+        }
+        let capped = delay_ms.min(self.max_delay_ms) as f64;
+
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let jittered = capped * (1.0 - jitter * random_unit());
+        Some(Duration::from_millis(jittered as u64))
+    }
+}
+
+/// Runs `operation` until it succeeds, the error is fatal, or the attempt
+/// budget of `policy` is exhausted.
+///
+/// # Arguments
+///
+/// * `policy` - The backoff policy to apply
+/// * `context` - Human-readable description used in retry logs
+/// * `operation` - Closure producing the future to (re)try
+///
+/// # Errors
+///
+/// Returns the last error produced by `operation`.
+pub async fn retry_with_backoff<T, F, Fut>(
+    policy: &BackoffConfig,
+    context: &str,
+    mut operation: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                let class = ErrorClass::of(&e);
+                let Some(delay) = policy.delay(attempt, class) else {
+                    return Err(e);
+                };
+                tracing::warn!(
+                    "{context} failed ({class:?}, attempt {attempt}/{}): {e}. Retrying in {delay:?}...",
+                    policy.max_attempts
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Returns a random delay between zero and `max_ms` milliseconds.
+///
+/// Periodic loops sleep for this long before each tick so that instances
+/// started together drift apart instead of hitting the provider in lockstep.
+#[must_use]
+pub fn poll_jitter(max_ms: u64) -> Duration {
+    if max_ms == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis((max_ms as f64 * random_unit()) as u64)
+}
+
+/// Returns a pseudo-random number in `[0, 1)`.
+///
+/// Jitter does not need cryptographic quality, so the randomly keyed std
+/// hasher is enough and avoids pulling in an RNG dependency.
+fn random_unit() -> f64 {
+    use std::hash::{BuildHasher, Hasher};
+
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_jitter_bounds() {
+        assert_eq!(poll_jitter(0), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(poll_jitter(250) < Duration::from_millis(250));
+        }
+    }
+
+    #[test]
+    fn test_classify_messages() {
+        assert_eq!(
+            ErrorClass::from_message("HTTP status client error (429 Too Many Requests)"),
+            ErrorClass::RateLimited
+        );
+        assert_eq!(
+            ErrorClass::from_message("RPC response error -32007: Slot 12 was skipped"),
+            ErrorClass::Fatal
+        );
+        assert_eq!(
+            ErrorClass::from_message("error sending request: connection reset"),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            ErrorClass::of(&SolanaIndexerError::Decode("bad data".into())),
+            ErrorClass::Fatal
+        );
+    }
+
+    #[test]
+    fn test_delay_growth_and_cap() {
+        let policy = BackoffConfig {
+            base_delay_ms: 100,
+            max_delay_ms: 500,
+            max_attempts: 10,
+            jitter: 0.0,
+            rate_limit_multiplier: 4,
+        };
+
+        assert_eq!(
+            policy.delay(1, ErrorClass::Transient),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            policy.delay(2, ErrorClass::Transient),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(
+            policy.delay(5, ErrorClass::Transient),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            policy.delay(1, ErrorClass::RateLimited),
+            Some(Duration::from_millis(400))
+        );
+        assert_eq!(policy.delay(1, ErrorClass::Fatal), None);
+        assert_eq!(policy.delay(10, ErrorClass::Transient), None);
+    }
+
+    #[test]
+    fn test_delay_jitter_bounds() {
+        let policy = BackoffConfig {
+            jitter: 0.5,
+            ..BackoffConfig::default()
+        };
+        for _ in 0..100 {
+            let delay = policy.delay(1, ErrorClass::Transient).unwrap();
+            assert!(delay >= Duration::from_millis(50));
+            assert!(delay <= Duration::from_millis(100));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_on_fatal() {
+        let policy = BackoffConfig {
+            base_delay_ms: 1,
+            ..BackoffConfig::default()
+        };
+        let mut calls = 0;
+        let result: Result<()> = retry_with_backoff(&policy, "test", || {
+            calls += 1;
+            async { Err(SolanaIndexerError::rpc("-32602 invalid params")) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let policy = BackoffConfig {
+            base_delay_ms: 1,
+            ..BackoffConfig::default()
+        };
+        let mut calls = 0;
+        let result = retry_with_backoff(&policy, "test", || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt < 3 {
+                    Err(SolanaIndexerError::rpc("timed out"))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.ok(), Some(3));
+    }
+}
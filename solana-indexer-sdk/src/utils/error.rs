@@ -3,6 +3,7 @@
 //! This module defines a comprehensive error enumeration using `thiserror`
 //! to provide clear, actionable error reporting throughout the SDK.
 
+use crate::utils::backoff::ErrorClass;
 use thiserror::Error;
 
 /// Custom error type for `SolanaIndexer` operations.
@@ -17,35 +18,57 @@ pub enum SolanaIndexerError {
     /// This variant automatically wraps `sqlx::Error` using the `#[from]` attribute,
     /// allowing seamless error propagation with the `?` operator.
     #[error("Database error: {0}")]
-    DatabaseError(#[from] sqlx::Error),
+    Storage(#[from] sqlx::Error),
 
     /// Errors during transaction data or event decoding.
     ///
     /// This includes failures in parsing IDL-based structures, deserializing
     /// instruction data, or interpreting event logs.
     #[error("Decoding error: {0}")]
-    DecodingError(String),
+    Decode(String),
 
     /// Errors interacting with the Solana RPC.
     ///
     /// This covers network failures, timeout errors, or unexpected responses
-    /// from the Solana RPC endpoint.
-    #[error("RPC error: {0}")]
-    RpcError(String),
+    /// from the Solana RPC endpoint. `retryable` is `false` for responses
+    /// that will not change on retry, such as invalid params or a skipped
+    /// slot; `SolanaIndexerError::rpc` sets it from the message.
+    #[error("RPC error: {message}")]
+    Rpc {
+        /// Description of the failure
+        message: String,
+        /// Whether the same request may succeed later
+        retryable: bool,
+    },
+
+    /// A handler failed for an event after its retries.
+    ///
+    /// Wraps the handler's last error, which decides whether the event is
+    /// worth handling again.
+    #[error("Handler {index} for discriminator {discriminator:?} failed: {source}")]
+    Handler {
+        /// Discriminator of the event
+        discriminator: [u8; 8],
+        /// Index of the handler among the event type's handlers
+        index: usize,
+        /// The handler's error
+        #[source]
+        source: Box<SolanaIndexerError>,
+    },
 
     /// Errors related to configuration.
     ///
     /// This includes missing environment variables, invalid configuration values,
     /// or failures in parsing configuration data.
     #[error("Configuration error: {0}")]
-    ConfigError(String),
+    Config(String),
 
     /// Errors from environment variable operations.
     ///
     /// Automatically wraps `std::env::VarError` for convenient error propagation
     /// when reading environment variables.
     #[error("Environment variable error: {0}")]
-    EnvVarError(#[from] std::env::VarError),
+    EnvVar(#[from] std::env::VarError),
 
     /// Errors during Solana public key parsing.
     ///
@@ -53,16 +76,12 @@ pub enum SolanaIndexerError {
     #[error("Invalid public key: {0}")]
     InvalidPublicKey(#[from] solana_sdk::pubkey::ParsePubkeyError),
 
-    /// Errors from the Solana RPC client.
-    #[error("RPC client error: {0}")]
-    RpcClientError(Box<solana_client::client_error::ClientError>),
-
     /// Generic errors for operations that don't fit other categories.
     ///
     /// This provides a catch-all for unexpected errors while still maintaining
     /// error context.
     #[error("Internal error: {0}")]
-    InternalError(String),
+    Internal(String),
 
     /// Error when a registry exceeds its configured capacity.
     #[error("Registry capacity exceeded: {0}")]
@@ -70,11 +89,15 @@ pub enum SolanaIndexerError {
 
     /// Connection error (e.g. gRPC or WebSocket failure)
     #[error("Connection error: {0}")]
-    ConnectionError(String),
+    Connection(String),
 
     /// Invalid data error
     #[error("Data error: {0}")]
-    DataError(String),
+    Data(String),
+
+    /// A request failed authentication (e.g. a spoofed or replayed webhook delivery)
+    #[error("Authentication error: {0}")]
+    Authentication(String),
 }
 
 /// Type alias for Results using `SolanaIndexerError`.
@@ -83,8 +106,98 @@ pub enum SolanaIndexerError {
 /// `Result<T, SolanaIndexerError>`.
 pub type Result<T> = std::result::Result<T, SolanaIndexerError>;
 
+impl SolanaIndexerError {
+    /// Creates an RPC error, classifying it as retryable unless its message
+    /// names a failure that will not change on retry.
+    pub fn rpc(message: impl Into<String>) -> Self {
+        let message = message.into();
+        let retryable = ErrorClass::from_message(&message) != ErrorClass::Fatal;
+        Self::Rpc { message, retryable }
+    }
+
+    /// Returns `true` if the operation that failed may succeed when tried
+    /// again: transient RPC and connection failures, and database errors
+    /// caused by an unreachable, overloaded or contended server.
+    ///
+    /// Decoding, configuration, data and authentication errors, and
+    /// rejected queries such as constraint violations, fail the same way
+    /// every time.
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Rpc { retryable, .. } => *retryable,
+            Self::Storage(error) => is_retryable_database_error(error),
+            Self::Handler { source, .. } => source.is_retryable(),
+            Self::Connection(_) => true,
+            Self::Internal(message) => ErrorClass::from_message(message) != ErrorClass::Fatal,
+            Self::Decode(_)
+            | Self::Config(_)
+            | Self::EnvVar(_)
+            | Self::InvalidPublicKey(_)
+            | Self::RegistryCapacityExceeded(_)
+            | Self::Data(_)
+            | Self::Authentication(_) => false,
+        }
+    }
+
+    /// Returns the error a handler failed with, looking through any
+    /// `SolanaIndexerError::Handler` wrapping it.
+    #[must_use]
+    pub fn root(&self) -> &Self {
+        match self {
+            Self::Handler { source, .. } => source.root(),
+            _ => self,
+        }
+    }
+}
+
+/// Returns `true` for database errors that may go away on retry.
+fn is_retryable_database_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        // Connection exceptions (08), transaction rollbacks such as
+        // serialization failures and deadlocks (40), insufficient
+        // resources (53) and operator intervention (57P)
+        sqlx::Error::Database(e) => e.code().is_some_and(|code| {
+            ["08", "40", "53", "57P"]
+                .iter()
+                .any(|class| code.starts_with(class))
+        }),
+        _ => false,
+    }
+}
+
 impl From<solana_client::client_error::ClientError> for SolanaIndexerError {
     fn from(err: solana_client::client_error::ClientError) -> Self {
-        SolanaIndexerError::RpcClientError(Box::new(err))
+        Self::rpc(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_classification() {
+        assert!(SolanaIndexerError::rpc("error sending request: connection reset").is_retryable());
+        assert!(
+            !SolanaIndexerError::rpc("RPC response error -32602: invalid param").is_retryable()
+        );
+        assert!(SolanaIndexerError::Storage(sqlx::Error::PoolTimedOut).is_retryable());
+        assert!(!SolanaIndexerError::Storage(sqlx::Error::RowNotFound).is_retryable());
+        assert!(!SolanaIndexerError::Decode("truncated".to_string()).is_retryable());
+
+        let handler = SolanaIndexerError::Handler {
+            discriminator: [1; 8],
+            index: 0,
+            source: Box::new(SolanaIndexerError::Connection("reset".to_string())),
+        };
+        assert!(handler.is_retryable());
+        assert!(matches!(handler.root(), SolanaIndexerError::Connection(_)));
     }
 }
@@ -32,9 +32,12 @@ use std::collections::HashMap;
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Idl {
-    /// IDL version
+    /// IDL version (empty for Anchor 0.30+ IDLs, which keep it under
+    /// `metadata`)
+    #[serde(default)]
     pub version: String,
-    /// Program name
+    /// Program name (empty for Anchor 0.30+ IDLs)
+    #[serde(default)]
     pub name: String,
     /// Program instructions
     #[serde(default)]
@@ -45,6 +48,22 @@ pub struct Idl {
     /// Program events
     #[serde(default)]
     pub events: Vec<IdlEvent>,
+    /// Custom types, which also hold the event fields of Anchor 0.30+ IDLs
+    #[serde(default, deserialize_with = "lenient_types")]
+    pub types: Vec<IdlAccount>,
+}
+
+/// Deserializes the custom types of an IDL, skipping definitions
+/// `IdlAccount` cannot represent, such as tuple structs.
+fn lenient_types<'de, D>(deserializer: D) -> Result<Vec<IdlAccount>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(|value| serde_json::from_value(value).ok())
+        .collect())
 }
 
 /// Represents an instruction in the IDL.
@@ -65,8 +84,9 @@ pub struct IdlInstruction {
 pub struct IdlAccount {
     /// Account name
     pub name: String,
-    /// Account type definition
-    #[serde(rename = "type")]
+    /// Account type definition (empty for Anchor 0.30+ IDLs, which define
+    /// it under `types`)
+    #[serde(rename = "type", default)]
     pub ty: IdlTypeDefinition,
 }
 
@@ -75,7 +95,8 @@ pub struct IdlAccount {
 pub struct IdlEvent {
     /// Event name
     pub name: String,
-    /// Event fields
+    /// Event fields (empty for Anchor 0.30+ IDLs)
+    #[serde(default)]
     pub fields: Vec<IdlField>,
 }
 
@@ -95,15 +116,15 @@ pub struct IdlAccountItem {
     /// Account name
     pub name: String,
     /// Whether the account is mutable
-    #[serde(rename = "isMut")]
+    #[serde(rename = "isMut", alias = "writable", default)]
     pub is_mut: bool,
     /// Whether the account is a signer
-    #[serde(rename = "isSigner")]
+    #[serde(rename = "isSigner", alias = "signer", default)]
     pub is_signer: bool,
 }
 
 /// Represents a type definition for accounts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IdlTypeDefinition {
     /// Type kind
     pub kind: String,
@@ -212,6 +233,39 @@ impl Idl {
     pub fn instruction_names(&self) -> Vec<String> {
         self.instructions.iter().map(|i| i.name.clone()).collect()
     }
+
+    /// Gets the fields of the event named `name`, from the event itself or,
+    /// in Anchor 0.30+ IDLs, from the type of the same name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use solana_indexer_sdk::Idl;
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let idl = Idl::parse(r#"{
+    ///     "events": [{"name": "TransferEvent", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}],
+    ///     "types": [{
+    ///         "name": "TransferEvent",
+    ///         "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}
+    ///     }]
+    /// }"#)?;
+    /// assert_eq!(idl.event_fields("TransferEvent").map(<[_]>::len), Some(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn event_fields(&self, name: &str) -> Option<&[IdlField]> {
+        let event = self.events.iter().find(|e| e.name == name)?;
+        if !event.fields.is_empty() {
+            return Some(&event.fields);
+        }
+        Some(
+            self.types
+                .iter()
+                .find(|ty| ty.name == name)
+                .map_or(&event.fields[..], |ty| &ty.ty.fields),
+        )
+    }
 }
 
 /// Generates Rust type name from IDL type.
@@ -243,7 +297,7 @@ pub fn idl_type_to_rust(idl_type: &str) -> String {
         }
         "bool" => "bool".to_string(),
         "string" => "String".to_string(),
-        "publicKey" => "Pubkey".to_string(),
+        "publicKey" | "pubkey" => "Pubkey".to_string(),
         "bytes" => "Vec<u8>".to_string(),
         _ => format!("/* Unknown type: {idl_type} */"),
     }
@@ -1,4 +1,6 @@
+pub mod backoff;
 pub mod error;
 pub mod logging;
 pub mod macros;
+pub mod network;
 pub mod rpc;
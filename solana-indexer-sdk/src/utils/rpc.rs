@@ -70,7 +70,7 @@ impl RpcProvider for DefaultRpcProvider {
             .client
             .get_signatures_for_address_with_config(address, config)
             .await
-            .map_err(|e| crate::utils::error::SolanaIndexerError::RpcClientError(Box::new(e)))?)
+            .map_err(crate::utils::error::SolanaIndexerError::from)?)
     }
 
     async fn get_transaction(
@@ -89,7 +89,7 @@ impl RpcProvider for DefaultRpcProvider {
                 },
             )
             .await
-            .map_err(|e| crate::utils::error::SolanaIndexerError::RpcClientError(Box::new(e)))?)
+            .map_err(crate::utils::error::SolanaIndexerError::from)?)
     }
 
     async fn get_multiple_accounts(
@@ -101,7 +101,7 @@ impl RpcProvider for DefaultRpcProvider {
             .client
             .get_multiple_accounts_with_commitment(pubkeys, commitment.unwrap_or_default())
             .await
-            .map_err(|e| crate::utils::error::SolanaIndexerError::RpcClientError(Box::new(e)))?
+            .map_err(crate::utils::error::SolanaIndexerError::from)?
             .value)
     }
 
@@ -123,6 +123,6 @@ impl RpcProvider for DefaultRpcProvider {
                 },
             )
             .await
-            .map_err(|e| crate::utils::error::SolanaIndexerError::RpcClientError(Box::new(e)))?)
+            .map_err(crate::utils::error::SolanaIndexerError::from)?)
     }
 }
@@ -0,0 +1,107 @@
+#![cfg(feature = "derive")]
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_indexer_sdk::{
+    calculate_discriminator, EventDiscriminator, EventType, IndexerEvent, ParsedEvent,
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize, IndexerEvent)]
+#[indexer(table = "swaps")]
+struct SwapEvent {
+    /// Pool the swap went through
+    pool: Pubkey,
+    /// Input amount
+    amount_in: u64,
+    min_out: Option<u128>,
+    #[indexer(sql_type = "NUMERIC")]
+    fee: u32,
+    route: Vec<u8>,
+    #[indexer(skip)]
+    _reserved: [u8; 4],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, IndexerEvent)]
+#[indexer(name = "Deposit", discriminator = "name")]
+struct DepositEvent {
+    amount: u64,
+}
+
+#[test]
+fn test_derived_discriminators() {
+    assert_eq!(
+        SwapEvent::discriminator(),
+        calculate_discriminator("SwapEvent")
+    );
+    assert_eq!(SwapEvent::event_name(), "SwapEvent");
+
+    let hash = <sha2::Sha256 as sha2::Digest>::digest(b"Deposit");
+    assert_eq!(DepositEvent::discriminator()[..], hash[..8]);
+    assert_eq!(DepositEvent::event_name(), "Deposit");
+}
+
+#[test]
+fn test_derived_borsh_layout() {
+    let layout: Vec<_> = SwapEvent::borsh_layout()
+        .into_iter()
+        .map(|field| (field.name, field.ty.replace(' ', "")))
+        .collect();
+    let expected = [
+        ("pool", "Pubkey"),
+        ("amount_in", "u64"),
+        ("min_out", "Option<u128>"),
+        ("fee", "u32"),
+        ("route", "Vec<u8>"),
+        ("_reserved", "[u8;4]"),
+    ];
+    assert_eq!(
+        layout,
+        expected.map(|(name, ty)| (name.to_string(), ty.to_string()))
+    );
+}
+
+#[test]
+fn test_derived_table_schema() {
+    let table = SwapEvent::table_schema().unwrap();
+    assert_eq!(table.name, "swaps");
+    let columns: Vec<_> = table
+        .columns
+        .iter()
+        .map(|column| (column.name.as_str(), column.sql_type.as_str()))
+        .collect();
+    assert_eq!(
+        columns,
+        [
+            ("pool", "TEXT"),
+            ("amount_in", "BIGINT"),
+            ("min_out", "NUMERIC(39, 0)"),
+            ("fee", "NUMERIC"),
+            ("route", "BYTEA"),
+        ]
+    );
+    assert_eq!(table.columns[0].description, "Pool the swap went through");
+    assert!(DepositEvent::table_schema().is_none());
+}
+
+#[test]
+fn test_decode_program_data() {
+    let event = SwapEvent {
+        pool: Pubkey::new_unique(),
+        amount_in: 10,
+        min_out: Some(9),
+        fee: 1,
+        route: vec![1, 2],
+        _reserved: [0; 4],
+    };
+    let mut data = SwapEvent::discriminator().to_vec();
+    data.extend(borsh::to_vec(&event).unwrap());
+
+    let log = ParsedEvent {
+        event_type: EventType::ProgramData,
+        program_id: None,
+        data: Some(BASE64.encode(&data)),
+    };
+    assert_eq!(SwapEvent::decode_log(&log), Some(event));
+    assert!(DepositEvent::decode_event(&data).is_none());
+}
@@ -191,3 +191,72 @@ async fn test_fetch_transactions_batch() {
     assert!(results[0].is_ok());
     assert!(results[1].is_ok()); // Both succeed because mock returns valid JSON for both requests
 }
+
+#[tokio::test]
+async fn test_get_token_accounts_by_mint() {
+    use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+    use solana_sdk::pubkey::Pubkey;
+
+    let mock_server = MockServer::start().await;
+    let fetcher = Fetcher::new(
+        mock_server.uri(),
+        solana_sdk::commitment_config::CommitmentConfig::confirmed(),
+    );
+    let (mint, account, owner) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+
+    Mock::given(method("POST"))
+        .and(body_string_contains("getVersion"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "result": { "solana-core": "1.16.7", "feature-set": 0 },
+            "id": 1
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(body_string_contains("getSlot"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "result": 500,
+            "id": 1
+        })))
+        .mount(&mock_server)
+        .await;
+
+    // The owner and amount slice of the token account
+    let mut data = owner.to_bytes().to_vec();
+    data.extend(1_500u64.to_le_bytes());
+    Mock::given(method("POST"))
+        .and(body_string_contains("getProgramAccounts"))
+        .and(body_string_contains(mint.to_string()))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "result": [{
+                "pubkey": account.to_string(),
+                "account": {
+                    "data": [BASE64.encode(&data), "base64"],
+                    "executable": false,
+                    "lamports": 2_039_280,
+                    "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "rentEpoch": 0,
+                    "space": 165
+                }
+            }],
+            "id": 1
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let token_program = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+    let (slot, accounts) = fetcher
+        .get_token_accounts_by_mint(&token_program, &mint)
+        .await
+        .unwrap();
+    assert_eq!(slot, 500);
+    assert_eq!(accounts, [(account, owner, 1_500)]);
+}
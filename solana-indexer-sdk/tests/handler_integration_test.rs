@@ -192,7 +192,6 @@ async fn test_handler_integration_with_database(
 
     // Register decoder for System Program, which will automatically enable `indexing_mode.inputs`
     indexer.register_decoder("system", TestTransferDecoder)?;
-    indexer.register_decoder("11111111111111111111111111111111", TestTransferDecoder)?;
 
     let handler: Box<dyn EventHandler<TransferEvent>> = Box::new(TestTransferHandler);
     indexer
@@ -67,8 +67,8 @@ impl StorageBackend for MockStorage {
         Ok(sigs.last().cloned())
     }
 
-    fn pool(&self) -> &PgPool {
-        &self.pool
+    fn pool(&self) -> Option<&PgPool> {
+        Some(&self.pool)
     }
 
     async fn mark_tentative(&self, signature: &str, slot: u64, block_hash: &str) -> Result<()> {
@@ -420,6 +420,7 @@ async fn test_indexer_backfill() {
     let backfill_config = BackfillConfig {
         enabled: true,
         start_slot: Some(100),
+        start_time: None,
         end_slot: Some(100),
         batch_size: 1,
         concurrency: 1,
@@ -428,6 +429,7 @@ async fn test_indexer_backfill() {
         poll_interval_secs: 1,
         max_depth: None,
         desired_lag_slots: None,
+        bandwidth: Default::default(),
     };
 
     let config = SolanaIndexerConfigBuilder::new()
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use solana_indexer_sdk::utils::error::{Result, SolanaIndexerError};
+use solana_indexer_sdk::{EventSink, OutboxRelay, SchemaInitializer, SinkRecord};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Sink recording the slots it accepted, failing slot 2 while `fail` is set
+#[derive(Default)]
+struct FlakySink {
+    fail: AtomicBool,
+    slots: Mutex<Vec<u64>>,
+}
+
+#[async_trait]
+impl EventSink for FlakySink {
+    async fn publish(&self, record: &SinkRecord) -> Result<()> {
+        if record.slot == 2 && self.fail.load(Ordering::Relaxed) {
+            return Err(SolanaIndexerError::Connection(
+                "sink unavailable".to_string(),
+            ));
+        }
+        self.slots.lock().unwrap().push(record.slot);
+        Ok(())
+    }
+}
+
+async fn insert_record(db: &PgPool, slot: u64) -> Result<()> {
+    let record = SinkRecord {
+        event: "TransferEvent".to_string(),
+        signature: format!("sig{slot}"),
+        slot,
+        block_time: None,
+        payload: serde_json::json!({ "amount": slot }),
+        dedup_key: None,
+    };
+    sqlx::query("INSERT INTO _solana_indexer_sdk_outbox (dedup_key, record) VALUES ($1, $2)")
+        .bind(format!("sig{slot}"))
+        .bind(serde_json::to_value(&record).unwrap())
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_relay_releases_failed_records_in_order() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let database_url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("DATABASE_URL not set, skipping integration test");
+            return Ok(());
+        }
+    };
+    let db = PgPool::connect(&database_url).await?;
+    let relay = OutboxRelay::new(db.clone());
+    relay.initialize(&db).await?;
+    sqlx::query("TRUNCATE _solana_indexer_sdk_outbox")
+        .execute(&db)
+        .await?;
+    for slot in 1..=3 {
+        insert_record(&db, slot).await?;
+    }
+
+    // Without sinks records are kept, not dropped
+    assert_eq!(relay.relay().await?, 0);
+    assert_eq!(relay.pending().await?, 3);
+
+    let sink = Arc::new(FlakySink::default());
+    sink.fail.store(true, Ordering::Relaxed);
+    let mut relay = relay;
+    relay.add_sink(sink.clone());
+
+    // The failed record and the one after it are released for the next pass
+    assert!(relay.relay().await.is_err());
+    assert_eq!(relay.pending().await?, 2);
+    let (claimed, last_error) = sqlx::query_as::<_, (i64, Option<String>)>(
+        "SELECT COUNT(claimed_until), MAX(last_error) FROM _solana_indexer_sdk_outbox",
+    )
+    .fetch_one(&db)
+    .await?;
+    assert_eq!(claimed, 0);
+    assert!(last_error.is_some_and(|error| error.contains("sink unavailable")));
+
+    sink.fail.store(false, Ordering::Relaxed);
+    assert_eq!(relay.relay().await?, 2);
+    assert_eq!(relay.pending().await?, 0);
+    assert_eq!(*sink.slots.lock().unwrap(), vec![1, 2, 3]);
+    Ok(())
+}
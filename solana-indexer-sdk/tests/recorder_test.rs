@@ -0,0 +1,55 @@
+use serde_json::json;
+use solana_indexer_sdk::testing::recorder::{load_stubs, RecordingProxy};
+use wiremock::matchers::{body_partial_json, body_string_contains, method};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn rpc(url: &str, body: serde_json::Value) -> serde_json::Value {
+    reqwest::Client::new()
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_recorded_stubs_replay_on_mock_server() {
+    let upstream = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("getSlot"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "result": 4242,
+            "id": 1
+        })))
+        .mount(&upstream)
+        .await;
+
+    let proxy = RecordingProxy::start(&upstream.uri()).await.unwrap();
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "getSlot", "params": [] });
+    let response = rpc(&proxy.url(), request).await;
+    assert_eq!(response["result"], 4242);
+    assert_eq!(proxy.calls().len(), 1);
+
+    let dir = std::env::temp_dir().join(format!("recorder-test-{}", std::process::id()));
+    let written = proxy.write_stubs(&dir).unwrap();
+    assert_eq!(written.len(), 1);
+    assert!(written[0].ends_with("00000-getSlot.json"));
+
+    // Stubs match on method and params, whatever id the client sends
+    let replay = MockServer::start().await;
+    for call in load_stubs(&dir).unwrap() {
+        Mock::given(method("POST"))
+            .and(body_partial_json(call.matcher()))
+            .respond_with(ResponseTemplate::new(call.status).set_body_json(&call.response))
+            .mount(&replay)
+            .await;
+    }
+    let request = json!({ "jsonrpc": "2.0", "id": 9, "method": "getSlot", "params": [] });
+    assert_eq!(rpc(&replay.uri(), request).await["result"], 4242);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -1,9 +1,18 @@
+use borsh::BorshSerialize;
 use serde_json::json;
-use solana_indexer_sdk::{DecoderRegistry, DynamicInstructionDecoder};
+use solana_indexer_sdk::{
+    ChainControl, DecoderRegistry, DynamicInstructionDecoder, EventDiscriminator,
+    InstructionContext, InstructionDecoder, ProgramScope, TransactionAccount,
+};
+use solana_sdk::pubkey::Pubkey;
 use solana_transaction_status::{
-    parse_instruction::ParsedInstruction, UiInstruction, UiParsedInstruction,
+    parse_instruction::ParsedInstruction, UiCompiledInstruction, UiInnerInstructions,
+    UiInstruction, UiParsedInstruction, UiPartiallyDecodedInstruction,
 };
 
+const AMM: &str = "AMM1111111111111111111111111111111111111111";
+const ROUTER: &str = "Router1111111111111111111111111111111111111";
+
 struct MockDecoder {
     should_succeed: bool,
 }
@@ -35,7 +44,7 @@ fn test_register_and_decode() {
     // Mock an instruction that matches the registered program
     let instruction = UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
         program: registry_key,
-        program_id: "Program1111...".to_string(),
+        program_id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
         parsed: json!({}),
         stack_height: None,
     }));
@@ -52,7 +61,7 @@ fn test_decode_no_matching_decoder() {
     let mut registry = DecoderRegistry::new();
     registry
         .register(
-            "other-program".to_string(),
+            Pubkey::new_unique().to_string(),
             Box::new(MockDecoder {
                 should_succeed: true,
             }),
@@ -61,7 +70,7 @@ fn test_decode_no_matching_decoder() {
 
     let instruction = UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
         program: "spl-token".to_string(),
-        program_id: "Program1111...".to_string(),
+        program_id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
         parsed: json!({}),
         stack_height: None,
     }));
@@ -84,7 +93,7 @@ fn test_decode_decoder_returns_none() {
 
     let instruction = UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
         program: "spl-token".to_string(),
-        program_id: "Program1111...".to_string(),
+        program_id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
         parsed: json!({}),
         stack_height: None,
     }));
@@ -92,3 +101,339 @@ fn test_decode_decoder_returns_none() {
     let events = registry.decode_transaction(&[instruction]);
     assert!(events.is_empty());
 }
+
+#[test]
+fn test_decode_instructions_of_one_program() {
+    let mut registry = DecoderRegistry::new();
+    for key in ["spl-token", "system"] {
+        registry
+            .register(
+                key.to_string(),
+                Box::new(MockDecoder {
+                    should_succeed: true,
+                }),
+            )
+            .unwrap();
+    }
+    let instruction = |program: &str, program_id: &str| {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: program.to_string(),
+            program_id: program_id.to_string(),
+            parsed: json!({}),
+            stack_height: None,
+        }))
+    };
+    let instructions = [
+        instruction("spl-token", "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"),
+        instruction("system", "11111111111111111111111111111111"),
+    ];
+
+    let system = "11111111111111111111111111111111";
+    let all = ProgramScope::all();
+    assert_eq!(registry.decode_instructions(&instructions, &all).len(), 2);
+    let only = ProgramScope::only(system);
+    assert_eq!(registry.decode_instructions(&instructions, &only).len(), 1);
+    let excluded = ProgramScope::all().exclude(system);
+    assert_eq!(
+        registry.decode_instructions(&instructions, &excluded).len(),
+        1
+    );
+}
+
+#[test]
+fn test_program_names_and_ids_reach_the_same_decoders() {
+    let token = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    let token_2022 = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+    let parsed = |program_id: &str| {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: "spl-token".to_string(),
+            program_id: program_id.to_string(),
+            parsed: json!({}),
+            stack_height: None,
+        }))
+    };
+    let raw = |program_id: &str| {
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: program_id.to_string(),
+                accounts: vec![],
+                data: String::new(),
+                stack_height: None,
+            },
+        ))
+    };
+    let decoder = || {
+        Box::new(MockDecoder {
+            should_succeed: true,
+        })
+    };
+
+    // A name covers the raw instructions of every program it stands for
+    let mut by_name = DecoderRegistry::new();
+    by_name
+        .register("spl-token".to_string(), decoder())
+        .unwrap();
+    for instruction in [
+        parsed(token),
+        raw(token),
+        parsed(token_2022),
+        raw(token_2022),
+    ] {
+        assert_eq!(by_name.decode_transaction(&[instruction]).len(), 1);
+    }
+
+    // An ID covers the instructions the RPC node parsed
+    let mut by_id = DecoderRegistry::new();
+    by_id.register(token.to_string(), decoder()).unwrap();
+    assert_eq!(by_id.decode_transaction(&[parsed(token)]).len(), 1);
+    assert!(by_id.decode_transaction(&[parsed(token_2022)]).is_empty());
+
+    assert!(by_id.register("raydium".to_string(), decoder()).is_err());
+}
+
+/// Encodes where each instruction was found as its event data.
+struct PositionDecoder;
+
+impl DynamicInstructionDecoder for PositionDecoder {
+    fn decode_dynamic(&self, _instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        None
+    }
+
+    fn decode_dynamic_in_context(
+        &self,
+        _instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        let parent = context.parent_index.map_or(u8::MAX, |index| index as u8);
+        let height = context.stack_height.unwrap_or_default() as u8;
+        Some(([0; 8], vec![context.index as u8, parent, height]))
+    }
+}
+
+#[test]
+fn test_decode_inner_instructions_in_execution_order() {
+    let mut registry = DecoderRegistry::new();
+    registry
+        .register("spl-token".to_string(), Box::new(PositionDecoder))
+        .unwrap();
+    let transfer = |stack_height| {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: "spl-token".to_string(),
+            program_id: "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA".to_string(),
+            parsed: json!({}),
+            stack_height,
+        }))
+    };
+    let swap = UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+        program: "jupiter".to_string(),
+        program_id: "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4".to_string(),
+        parsed: json!({}),
+        stack_height: None,
+    }));
+    let inner = [UiInnerInstructions {
+        index: 0,
+        instructions: vec![transfer(Some(2)), transfer(None)],
+    }];
+
+    let events = registry.decode_with_inner(&[swap, transfer(None)], &inner, &ProgramScope::all());
+    let positions: Vec<Vec<u8>> = events.into_iter().map(|(_, data)| data).collect();
+    assert_eq!(
+        positions,
+        [vec![0, 0, 2], vec![1, 0, 0], vec![1, u8::MAX, 1]]
+    );
+
+    // Without inner instructions only the top-level transfer is decoded
+    assert_eq!(registry.decode_transaction(&[transfer(None)]).len(), 1);
+}
+
+/// Encodes the signer among the instruction's accounts as its event data.
+struct SignerDecoder;
+
+impl DynamicInstructionDecoder for SignerDecoder {
+    fn decode_dynamic(&self, _instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        None
+    }
+
+    fn decode_dynamic_in_context(
+        &self,
+        instruction: &UiInstruction,
+        context: &InstructionContext,
+    ) -> Option<([u8; 8], Vec<u8>)> {
+        let signer = context
+            .instruction_accounts(instruction)?
+            .into_iter()
+            .find(|account| account.signer)?;
+        Some(([0; 8], signer.pubkey.to_bytes().to_vec()))
+    }
+}
+
+#[test]
+fn test_decode_compiled_instructions_with_accounts() {
+    let program = Pubkey::new_unique();
+    let (user, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut registry = DecoderRegistry::new();
+    registry
+        .register(program.to_string(), Box::new(SignerDecoder))
+        .unwrap();
+    let account = |pubkey, signer| TransactionAccount {
+        pubkey,
+        signer,
+        writable: true,
+    };
+    let accounts = vec![
+        account(user, true),
+        account(pool, false),
+        account(program, false),
+    ];
+    let swap = UiInstruction::Compiled(UiCompiledInstruction {
+        program_id_index: 2,
+        accounts: vec![1, 0],
+        data: String::new(),
+        stack_height: None,
+    });
+
+    let events = registry.decode_with_accounts(
+        std::slice::from_ref(&swap),
+        &[],
+        accounts,
+        &ProgramScope::only(program.to_string()),
+    );
+    assert_eq!(events, [([0; 8], user.to_bytes().to_vec())]);
+
+    // Without the transaction's accounts the program cannot be resolved
+    assert!(registry.decode_transaction(&[swap]).is_empty());
+}
+
+/// Accepts instructions whose parsed type starts with `prefix`, tagging the
+/// event with `tag`.
+struct PrefixDecoder {
+    prefix: &'static str,
+    tag: u8,
+}
+
+impl DynamicInstructionDecoder for PrefixDecoder {
+    fn decode_dynamic(&self, instruction: &UiInstruction) -> Option<([u8; 8], Vec<u8>)> {
+        let UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) = instruction else {
+            return None;
+        };
+        let kind = parsed.parsed["type"].as_str()?;
+        kind.starts_with(self.prefix)
+            .then(|| ([self.tag; 8], vec![self.tag]))
+    }
+}
+
+#[test]
+fn test_decoder_chain_priority_and_control() {
+    let call = |kind: &str| {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: AMM.to_string(),
+            program_id: AMM.to_string(),
+            parsed: json!({ "type": kind }),
+            stack_height: None,
+        }))
+    };
+    let tags = |registry: &DecoderRegistry, kind: &str| -> Vec<u8> {
+        registry
+            .decode_transaction(&[call(kind)])
+            .into_iter()
+            .map(|(_, data)| data[0])
+            .collect()
+    };
+    let decoder = |prefix, tag| Box::new(PrefixDecoder { prefix, tag });
+
+    let mut registry = DecoderRegistry::new();
+    // Registered first, but the catch-all runs last
+    registry
+        .register_with_priority(AMM.to_string(), decoder("", 0), -10, ChainControl::Stop)
+        .unwrap();
+    registry
+        .register_with_priority(AMM.to_string(), decoder("swap", 1), 10, ChainControl::Stop)
+        .unwrap();
+    registry
+        .register_with_priority(AMM.to_string(), decoder("", 2), 20, ChainControl::Continue)
+        .unwrap();
+
+    assert_eq!(tags(&registry, "swap"), [2, 1]);
+    assert_eq!(tags(&registry, "deposit"), [2, 0]);
+}
+
+#[derive(BorshSerialize)]
+struct SwapHop {
+    pool: String,
+}
+
+impl EventDiscriminator for SwapHop {
+    fn discriminator() -> [u8; 8] {
+        *b"swap_hop"
+    }
+}
+
+struct RouteDecoder;
+
+impl InstructionDecoder<SwapHop> for RouteDecoder {
+    fn decode(&self, _instruction: &UiInstruction) -> Option<SwapHop> {
+        None
+    }
+
+    fn decode_many(
+        &self,
+        instruction: &UiInstruction,
+        _context: &InstructionContext,
+    ) -> Vec<SwapHop> {
+        let UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) = instruction else {
+            return Vec::new();
+        };
+        parsed.parsed["hops"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|hop| hop.as_str())
+            .map(|pool| SwapHop {
+                pool: pool.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_decode_many_events_per_instruction() {
+    let route = |hops: serde_json::Value| {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(ParsedInstruction {
+            program: "router".to_string(),
+            program_id: ROUTER.to_string(),
+            parsed: json!({ "hops": hops }),
+            stack_height: None,
+        }))
+    };
+    let mut registry = DecoderRegistry::new();
+    let decoder: Box<dyn InstructionDecoder<SwapHop>> = Box::new(RouteDecoder);
+    registry
+        .register(ROUTER.to_string(), Box::new(decoder))
+        .unwrap();
+    registry
+        .register_with_priority(
+            ROUTER.to_string(),
+            Box::new(MockDecoder {
+                should_succeed: true,
+            }),
+            -1,
+            ChainControl::Stop,
+        )
+        .unwrap();
+
+    // Each hop is its own event, and the route stops the chain
+    let events = registry.decode_transaction(&[route(json!(["sol_usdc", "usdc_bonk"]))]);
+    assert_eq!(events.len(), 2);
+    assert!(events
+        .iter()
+        .all(|(discriminator, _)| discriminator == b"swap_hop"));
+    assert_eq!(events[0].1, borsh::to_vec(&"sol_usdc".to_string()).unwrap());
+    assert_eq!(
+        events[1].1,
+        borsh::to_vec(&"usdc_bonk".to_string()).unwrap()
+    );
+
+    // No hops is no match, so the next decoder runs
+    let events = registry.decode_transaction(&[route(json!([]))]);
+    assert_eq!(events, [([1, 2, 3, 4, 5, 6, 7, 8], vec![10, 20, 30])]);
+}
@@ -0,0 +1,119 @@
+//! RPC calls through a SOCKS5 proxy.
+//!
+//! Kept in its own test binary: it counts the listening sockets of the whole
+//! process, which tests running in parallel would disturb.
+
+use solana_indexer_sdk::utils::network::{HttpTransport, NetworkConfig};
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashSet;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Inodes of the TCP sockets this process is listening on.
+fn listening_sockets() -> HashSet<String> {
+    let owned: HashSet<String> = std::fs::read_dir("/proc/self/fd")
+        .unwrap()
+        .filter_map(|entry| std::fs::read_link(entry.ok()?.path()).ok())
+        .filter_map(|target| {
+            let target = target.to_string_lossy().into_owned();
+            Some(
+                target
+                    .strip_prefix("socket:[")?
+                    .strip_suffix(']')?
+                    .to_string(),
+            )
+        })
+        .collect();
+
+    ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .filter_map(|table| std::fs::read_to_string(table).ok())
+        .flat_map(|table| {
+            table
+                .lines()
+                .skip(1)
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    // State 0A is LISTEN
+                    (fields.get(3) == Some(&"0A")).then(|| fields.get(9).map(|s| s.to_string()))?
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|inode| owned.contains(inode))
+        .collect()
+}
+
+/// Plays a SOCKS5 proxy requiring `user`/`pass` and the RPC node behind it,
+/// answering one JSON-RPC request. Returns the requested target.
+async fn serve_through_socks5(listener: TcpListener) -> Vec<u8> {
+    let (mut stream, _) = listener.accept().await.unwrap();
+
+    let mut greeting = [0u8; 4];
+    stream.read_exact(&mut greeting).await.unwrap();
+    assert_eq!(greeting, [0x05, 0x02, 0x00, 0x02]);
+    stream.write_all(&[0x05, 0x02]).await.unwrap();
+
+    let mut auth = [0u8; 11];
+    stream.read_exact(&mut auth).await.unwrap();
+    assert_eq!(&auth, b"\x01\x04user\x04pass");
+    stream.write_all(&[0x01, 0x00]).await.unwrap();
+
+    let mut request = [0u8; 5];
+    stream.read_exact(&mut request).await.unwrap();
+    assert_eq!(&request[..4], &[0x05, 0x01, 0x00, 0x03]);
+    let mut target = vec![0u8; usize::from(request[4]) + 2];
+    stream.read_exact(&mut target).await.unwrap();
+    stream
+        .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+        .await
+        .unwrap();
+
+    answer_rpc(&mut stream).await;
+    target
+}
+
+async fn answer_rpc(stream: &mut TcpStream) {
+    let mut head = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut chunk).await.unwrap();
+        assert!(read > 0, "connection closed mid-request");
+        head.extend_from_slice(&chunk[..read]);
+    }
+    let body = br#"{"jsonrpc":"2.0","result":{"solana-core":"1.18.26","feature-set":0},"id":1}"#;
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await.unwrap();
+    stream.write_all(body).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_socks5_rpc_leaves_no_listener_open() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let proxy_url = format!("socks5://user:pass@{}", listener.local_addr().unwrap());
+    let before = listening_sockets();
+    assert!(!before.is_empty(), "the mock proxy should be listed");
+
+    let server = tokio::spawn(serve_through_socks5(listener));
+    let transport = HttpTransport::new(NetworkConfig {
+        proxy_url: Some(proxy_url),
+        ..NetworkConfig::default()
+    });
+    let version = tokio::task::spawn_blocking(move || {
+        transport
+            .rpc_client("http://rpc.example:8899", CommitmentConfig::confirmed())
+            .unwrap()
+            .get_version()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(version.solana_core, "1.18.26");
+    assert_eq!(server.await.unwrap(), b"rpc.example\x22\xc3");
+    // The mock proxy has closed its listener; nothing may have taken its place
+    let after = listening_sockets();
+    assert!(after.is_subset(&before), "new listeners: {after:?}");
+}
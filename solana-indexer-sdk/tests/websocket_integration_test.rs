@@ -1,6 +1,13 @@
+use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
-use solana_indexer_sdk::{SolanaIndexer, SolanaIndexerConfigBuilder, Storage};
+use solana_indexer_sdk::types::events::{EventType, ParsedEvent};
+use solana_indexer_sdk::utils::backoff::BackoffConfig;
+use solana_indexer_sdk::{
+    EventHandler, LogDecoder, Result, RetryPolicy, SolanaIndexer, SolanaIndexerConfigBuilder,
+    SolanaIndexerError, Storage, TransferEvent, TxMetadata,
+};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tokio_tungstenite::accept_async;
@@ -202,3 +209,156 @@ async fn test_indexer_websocket_integration() {
 
     server_handle.abort();
 }
+
+/// Log decoder turning every `Program data:` log into a transfer
+struct AnyDataDecoder;
+
+impl LogDecoder<TransferEvent> for AnyDataDecoder {
+    fn decode(&self, event: &ParsedEvent) -> Option<TransferEvent> {
+        (event.event_type == EventType::ProgramData).then(|| TransferEvent {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            amount: 1,
+        })
+    }
+}
+
+/// Handler that always fails, counting its calls
+struct FailingHandler(Arc<AtomicU32>);
+
+#[async_trait]
+impl EventHandler<TransferEvent> for FailingHandler {
+    async fn handle(&self, _: TransferEvent, _: &TxMetadata, _: &sqlx::PgPool) -> Result<()> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Err(SolanaIndexerError::rpc("webhook unavailable"))
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_logs_failing_handler_is_retried_and_dead_lettered() {
+    dotenvy::dotenv().ok();
+
+    let database_url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("DATABASE_URL not set, skipping integration test");
+            return;
+        }
+    };
+
+    let mock_rpc = MockServer::start().await;
+    setup_rpc_mocks(&mock_rpc).await;
+
+    let test_signature =
+        "5j7s6NiJS3JAkvgkoc18WVAsiSaci2pxB2A6ueCJP4tprA2TFg9wSyTLeYouxPBJEMzJinENTkpA52YStRW5Dia9";
+    let program = "11111111111111111111111111111111";
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let ws_url = format!("ws://{}", listener.local_addr().unwrap());
+    let server_handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws_stream = accept_async(stream).await.unwrap();
+        if let Some(Ok(Message::Text(_))) = ws_stream.next().await {
+            for message in [
+                json!({ "jsonrpc": "2.0", "result": 12345, "id": 1 }),
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "logsNotification",
+                    "params": {
+                        "result": {
+                            "context": { "slot": 123457 },
+                            "value": {
+                                "signature": test_signature,
+                                "err": null,
+                                "logs": [
+                                    format!("Program {program} invoke [1]"),
+                                    "Program data: AQIDBA==".to_string(),
+                                    format!("Program {program} success"),
+                                ]
+                            }
+                        },
+                        "subscription": 12345
+                    }
+                }),
+            ] {
+                ws_stream
+                    .send(Message::Text(message.to_string()))
+                    .await
+                    .unwrap();
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        }
+    });
+
+    let storage = Arc::new(
+        Storage::new(&database_url)
+            .await
+            .expect("Failed to connect to database"),
+    );
+    storage
+        .initialize()
+        .await
+        .expect("Failed to initialize storage");
+    let _ = sqlx::query("DELETE FROM _solana_indexer_sdk_processed WHERE signature = $1")
+        .bind(test_signature)
+        .execute(storage.pool())
+        .await;
+    let _ = sqlx::query("DELETE FROM _solana_indexer_sdk_dlq WHERE signature = $1")
+        .bind(test_signature)
+        .execute(storage.pool())
+        .await;
+
+    let config = SolanaIndexerConfigBuilder::new()
+        .with_ws(ws_url, mock_rpc.uri())
+        .with_database(&database_url)
+        .program_id(program)
+        .with_dead_letter_queue(true)
+        .build()
+        .expect("Failed to build config");
+
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut indexer = SolanaIndexer::new_with_storage(config, storage.clone());
+    indexer
+        .register_log_decoder(program, AnyDataDecoder)
+        .unwrap();
+    indexer
+        .register_handler_with_retry(
+            FailingHandler(calls.clone()),
+            RetryPolicy::new(2).with_backoff(BackoffConfig {
+                base_delay_ms: 1,
+                ..BackoffConfig::default()
+            }),
+        )
+        .unwrap();
+    let dlq = indexer.dead_letter_queue().unwrap();
+    let token = indexer.cancellation_token();
+    let indexer_handle = tokio::spawn(async move { indexer.start().await });
+
+    let mut processed = false;
+    for _ in 0..10 {
+        if storage.is_processed(test_signature).await.unwrap() {
+            processed = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+
+    token.cancel();
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), indexer_handle).await;
+    server_handle.abort();
+
+    // The handler's own policy applies, the event is dead-lettered and the
+    // stream keeps running instead of stopping on the error
+    assert!(processed, "Notification should be marked processed");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    let entries = dlq.list(storage.pool(), 100).await.unwrap();
+    assert!(entries
+        .iter()
+        .any(|entry| entry.signature == test_signature && entry.error.contains("webhook")));
+    assert!(matches!(result, Ok(Ok(Ok(())))));
+
+    let _ = sqlx::query("DELETE FROM _solana_indexer_sdk_dlq WHERE signature = $1")
+        .bind(test_signature)
+        .execute(storage.pool())
+        .await;
+}